@@ -1,5 +1,8 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
-use crate::{lexer::IdProvider, typesystem::DataTypeInformation};
+use crate::{
+    diagnostics::WarningLevel, lexer::IdProvider, typesystem::DataTypeInformation, WarningCode,
+};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
 use std::{
     fmt::{Debug, Display, Formatter, Result},
@@ -11,13 +14,13 @@ mod pre_processor;
 
 pub type AstId = usize;
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct GenericBinding {
     pub name: String,
     pub nature: TypeNature,
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize, Deserialize)]
 pub struct Pou {
     pub name: String,
     pub variable_blocks: Vec<VariableBlock>,
@@ -27,9 +30,46 @@ pub struct Pou {
     pub poly_mode: Option<PolymorphismMode>,
     pub generics: Vec<GenericBinding>,
     pub linkage: LinkageType,
+    /// the interfaces this pou declares to implement (`IMPLEMENTS a, b`)
+    pub interfaces: Vec<String>,
+    /// the class or function block this pou extends (`EXTENDS a`)
+    pub super_class: Option<String>,
+    /// the attributes declared by the pragmas preceding the pou
+    pub attributes: Vec<Attribute>,
+    /// the comments preceding the pou
+    pub comments: Vec<Comment>,
+}
+
+/// an attribute declared by a pragma and attached to the following declaration
+/// (e.g. `{attribute 'pack' := '1'}` or `{pack}`)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Attribute {
+    pub name: String,
+    pub value: Option<String>,
+    pub location: SourceRange,
 }
 
-#[derive(Debug, PartialEq)]
+/// a block (`(* *)`, `/* */`) or line (`//`) comment, it is attached to the declaration it
+/// precedes or describes, or to the compilation unit if it belongs to no declaration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    /// the text of the comment including its delimiters
+    pub text: String,
+    pub location: SourceRange,
+}
+
+/// a `{warning <level> <code>}` pragma, it sets the level of the warning with the given code
+/// from its position to the next pragma for the same code (`{warning restore <code>}` restores
+/// the level configured on the command line)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WarningPragma {
+    pub code: WarningCode,
+    /// the level of the warning, `None` if the pragma restores the configured level
+    pub level: Option<WarningLevel>,
+    pub location: SourceRange,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub enum PolymorphismMode {
     None,
     Abstract,
@@ -54,7 +94,7 @@ pub enum DirectAccessType {
     Template,
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum TypeNature {
     Any,
     Derived,
@@ -146,7 +186,7 @@ impl TypeNature {
 impl DirectAccessType {
     /// Returns true if the current index is in the range for the given type
     pub fn is_in_range(&self, index: u64, data_type: &DataTypeInformation) -> bool {
-        (self.get_bit_width() * index) < data_type.get_size() as u64
+        self.get_bit_width().saturating_mul(index) < data_type.get_size() as u64
     }
 
     /// Returns the range from 0 for the given data type
@@ -181,13 +221,27 @@ impl Debug for Pou {
         if !self.generics.is_empty() {
             str.field("generics", &self.generics);
         }
+        if !self.interfaces.is_empty() {
+            str.field("interfaces", &self.interfaces);
+        }
+        if let Some(super_class) = &self.super_class {
+            str.field("super_class", super_class);
+        }
+        if !self.attributes.is_empty() {
+            str.field("attributes", &self.attributes);
+        }
+        if !self.comments.is_empty() {
+            str.field("comments", &self.comments);
+        }
         str.finish()
     }
 }
 
 impl Pou {
     pub fn get_return_name(&self) -> &str {
-        Pou::calc_return_name(&self.name)
+        let name = Pou::calc_return_name(&self.name);
+        //the getter of a property returns the property's value
+        name.strip_prefix(PROPERTY_GETTER_PREFIX).unwrap_or(name)
     }
 
     pub fn calc_return_name(pou_name: &str) -> &str {
@@ -195,7 +249,7 @@ impl Pou {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Implementation {
     pub name: String,
     pub type_name: String,
@@ -205,15 +259,20 @@ pub struct Implementation {
     pub location: SourceRange,
     pub overriding: bool,
     pub access: Option<AccessModifier>,
+    /// the comments of the implementation's body
+    pub comments: Vec<Comment>,
 }
 
-#[derive(Debug, Copy, PartialEq, Clone)]
+#[derive(Debug, Copy, PartialEq, Clone, Serialize, Deserialize)]
 pub enum LinkageType {
     Internal,
     External,
+    /// an external function implemented in a foreign language (e.g. C), it is called with its
+    /// parameters instead of an instance of its parameter-struct
+    Foreign,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
 pub enum AccessModifier {
     Private,
     Public,
@@ -221,13 +280,26 @@ pub enum AccessModifier {
     Internal,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+/// the name of the reference to the current instance inside a method or function block
+pub const THIS: &str = "THIS";
+
+/// the name of the pointer to the base instance inside a method or function block of a derived type
+pub const SUPER: &str = "SUPER";
+
+/// the prefix of the method reading a property (e.g. `__get_Level` for the property `Level`)
+pub const PROPERTY_GETTER_PREFIX: &str = "__get_";
+
+/// the prefix of the method writing a property (e.g. `__set_Level` for the property `Level`)
+pub const PROPERTY_SETTER_PREFIX: &str = "__set_";
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub enum PouType {
     Program,
     Function,
     FunctionBlock,
     Action,
     Class,
+    Interface,
     Method { owner_class: String },
 }
 
@@ -242,12 +314,28 @@ impl PouType {
     }
 }
 
-#[derive(Debug, PartialEq, Default)]
+#[derive(Debug, PartialEq, Default, Serialize, Deserialize)]
 pub struct CompilationUnit {
     pub global_vars: Vec<VariableBlock>,
     pub units: Vec<Pou>,
     pub implementations: Vec<Implementation>,
     pub types: Vec<UserTypeDeclaration>,
+    /// the namespaces declared in this unit (e.g. `Lib` and `Lib.Sub`)
+    pub namespaces: Vec<String>,
+    /// the namespaces to search for unqualified names used by a declaration, keyed by the
+    /// lowercase qualified name of the declaration (the namespaces enclosing the declaration
+    /// from the innermost outwards, followed by the namespaces it is `USING`)
+    pub lookup_scopes: IndexMap<String, Vec<String>>,
+    /// the configurations declared in this unit
+    pub configurations: Vec<Configuration>,
+    /// the name of the file this unit was parsed from
+    pub file_name: String,
+    /// the line breaks of the source this unit was parsed from
+    pub new_lines: NewLines,
+    /// the comments that do not belong to a declaration
+    pub comments: Vec<Comment>,
+    /// the warning pragmas of the unit in the order of their position
+    pub warning_pragmas: Vec<WarningPragma>,
 }
 
 impl CompilationUnit {
@@ -262,10 +350,71 @@ impl CompilationUnit {
         self.units.extend(other.units);
         self.implementations.extend(other.implementations);
         self.types.extend(other.types);
+        self.namespaces.extend(other.namespaces);
+        self.lookup_scopes.extend(other.lookup_scopes);
+        self.configurations.extend(other.configurations);
+        self.comments.extend(other.comments);
+        self.warning_pragmas.extend(other.warning_pragmas);
     }
 }
 
-#[derive(Debug, Copy, PartialEq, Clone)]
+/// a `CONFIGURATION` declaring the resources of a plc
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Configuration {
+    pub name: String,
+    pub resources: Vec<Resource>,
+    /// the addresses assigned to instance variables in `VAR_CONFIG` blocks
+    pub config_variables: Vec<ConfigVariable>,
+    pub location: SourceRange,
+}
+
+/// an entry of a `VAR_CONFIG` block assigning an address to an instance variable
+/// (e.g. `prg.inst.input AT %IX0.0 : BOOL;`)
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConfigVariable {
+    /// the path to the instance variable (e.g. `prg.inst.input`)
+    pub reference: String,
+    pub data_type: String,
+    /// the `HardwareAccess` assigned to the variable
+    pub address: AstStatement,
+    pub location: SourceRange,
+}
+
+/// a `RESOURCE` of a configuration declaring the tasks and the program instances that run on it
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Resource {
+    pub name: String,
+    /// the processing unit the resource runs `ON` (e.g. `PLC` of `RESOURCE cpu ON PLC`)
+    pub processing_unit: Option<String>,
+    pub tasks: Vec<Task>,
+    pub programs: Vec<ProgramConfiguration>,
+    pub location: SourceRange,
+}
+
+/// a `TASK` scheduling the program instances associated with it
+/// (e.g. `TASK fast(INTERVAL := T#10ms, PRIORITY := 1);`)
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct Task {
+    pub name: String,
+    /// the cycle time of a periodic task
+    pub interval: Option<AstStatement>,
+    /// the variable triggering an event task on its rising edge
+    pub single: Option<AstStatement>,
+    pub priority: Option<AstStatement>,
+    pub location: SourceRange,
+}
+
+/// a program instance of a resource (e.g. `PROGRAM fast_main WITH fast : main;`)
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProgramConfiguration {
+    pub name: String,
+    /// the task scheduling the instance
+    pub task: Option<String>,
+    pub program: String,
+    pub location: SourceRange,
+}
+
+#[derive(Debug, Copy, PartialEq, Clone, Serialize, Deserialize)]
 pub enum VariableBlockType {
     Local,
     Temp,
@@ -273,13 +422,15 @@ pub enum VariableBlockType {
     Output,
     Global,
     InOut,
+    External,
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize, Deserialize)]
 pub struct VariableBlock {
     pub access: AccessModifier,
     pub constant: bool,
     pub retain: bool,
+    pub persistent: bool,
     pub variables: Vec<Variable>,
     pub variable_block_type: VariableBlockType,
     pub linkage: LinkageType,
@@ -295,13 +446,18 @@ impl Debug for VariableBlock {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct Variable {
     pub name: String,
     pub data_type: DataTypeDeclaration,
     pub initializer: Option<AstStatement>,
     pub address: Option<AstStatement>,
     pub location: SourceRange,
+    /// the attributes declared by the pragmas preceding the variable or its block
+    pub attributes: Vec<Attribute>,
+    /// the comments preceding the variable or its block and the comment following the variable
+    /// on the same line
+    pub comments: Vec<Comment>,
 }
 
 impl Debug for Variable {
@@ -315,6 +471,12 @@ impl Debug for Variable {
         if self.address.is_some() {
             var.field("address", &self.address);
         }
+        if !self.attributes.is_empty() {
+            var.field("attributes", &self.attributes);
+        }
+        if !self.comments.is_empty() {
+            var.field("comments", &self.comments);
+        }
         var.finish()
     }
 }
@@ -332,18 +494,29 @@ impl Variable {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SourceRange {
     range: core::ops::Range<usize>,
+    /// the id the file containing the range was registered with at the diagnostician, or None
+    /// if the file is not known. The ids are only valid for one compilation, so they are not
+    /// serialized (e.g. into the unit cache or a library). A deserialized range is in the file
+    /// of the cached unit that is loaded, see `cache::get_loaded_file`
+    #[serde(skip_serializing, default = "crate::cache::get_loaded_file")]
+    file: Option<usize>,
 }
 
 impl SourceRange {
     pub fn new(range: core::ops::Range<usize>) -> SourceRange {
-        SourceRange { range }
+        SourceRange { range, file: None }
+    }
+
+    /// creates a range in the file with the given id
+    pub fn in_file(range: core::ops::Range<usize>, file: Option<usize>) -> SourceRange {
+        SourceRange { range, file }
     }
 
     pub fn undefined() -> SourceRange {
-        SourceRange { range: 0..0 }
+        SourceRange::new(0..0)
     }
 
     pub fn get_start(&self) -> usize {
@@ -354,8 +527,28 @@ impl SourceRange {
         self.range.end
     }
 
+    /// returns the id of the file containing this range, see `Diagnostician::register_file`
+    pub fn get_file(&self) -> Option<usize> {
+        self.file
+    }
+
+    /// returns the range from the start of this range to the end of the given range
+    pub fn span(&self, end: &SourceRange) -> SourceRange {
+        SourceRange::in_file(self.get_start()..end.get_end(), self.file)
+    }
+
+    /// returns the line and column (both 1-based) this range starts at, given the line breaks
+    /// of its file
+    pub fn get_line_and_column(&self, new_lines: &NewLines) -> (usize, usize) {
+        let line = new_lines.get_line_nr(self.get_start());
+        (line, new_lines.get_column(line, self.get_start()))
+    }
+
     pub fn sub_range(&self, start: usize, len: usize) -> SourceRange {
-        SourceRange::new((self.get_start() + start)..(self.get_start() + len))
+        SourceRange::in_file(
+            (self.get_start() + start)..(self.get_start() + len),
+            self.file,
+        )
     }
 
     pub fn to_range(&self) -> Range<usize> {
@@ -363,13 +556,46 @@ impl SourceRange {
     }
 }
 
+/// the offsets of the lines of a source, used to translate a `SourceRange` into lines and columns
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NewLines {
+    line_starts: Vec<usize>,
+}
+
+impl NewLines {
+    pub fn new(source: &str) -> NewLines {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        NewLines { line_starts }
+    }
+
+    /// returns the 1-based line of the given offset
+    pub fn get_line_nr(&self, offset: usize) -> usize {
+        match self.line_starts.binary_search(&offset) {
+            Ok(line) => line + 1,
+            Err(line) => line.max(1),
+        }
+    }
+
+    /// returns the 1-based column of the given offset in the given line
+    pub fn get_column(&self, line: usize, offset: usize) -> usize {
+        let line_start = self
+            .line_starts
+            .get(line.saturating_sub(1))
+            .copied()
+            .unwrap_or_default();
+        offset.saturating_sub(line_start) + 1
+    }
+}
+
 impl From<std::ops::Range<usize>> for SourceRange {
     fn from(range: std::ops::Range<usize>) -> SourceRange {
         SourceRange::new(range)
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub enum DataTypeDeclaration {
     DataTypeReference {
         referenced_type: String,
@@ -417,31 +643,45 @@ impl DataTypeDeclaration {
     }
 }
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Serialize, Deserialize)]
 pub struct UserTypeDeclaration {
     pub data_type: DataType,
     pub initializer: Option<AstStatement>,
     pub location: SourceRange,
     /// stores the original scope for compiler-generated types
     pub scope: Option<String>,
+    /// the attributes declared by the pragmas preceding the type
+    pub attributes: Vec<Attribute>,
+    /// the comments preceding the type
+    pub comments: Vec<Comment>,
 }
 
 impl Debug for UserTypeDeclaration {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result {
-        f.debug_struct("UserTypeDeclaration")
-            .field("data_type", &self.data_type)
+        let mut str = f.debug_struct("UserTypeDeclaration");
+        str.field("data_type", &self.data_type)
             .field("initializer", &self.initializer)
-            .field("scope", &self.scope)
-            .finish()
+            .field("scope", &self.scope);
+        if !self.attributes.is_empty() {
+            str.field("attributes", &self.attributes);
+        }
+        if !self.comments.is_empty() {
+            str.field("comments", &self.comments);
+        }
+        str.finish()
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DataType {
     StructType {
         name: Option<String>, //maybe None for inline structs
         variables: Vec<Variable>,
     },
+    UnionType {
+        name: Option<String>, //maybe None for inline unions
+        variables: Vec<Variable>,
+    },
     EnumType {
         name: Option<String>, //maybe empty for inline enums
         numeric_type: String,
@@ -480,6 +720,7 @@ impl DataType {
     pub fn set_name(&mut self, new_name: String) {
         match self {
             DataType::StructType { name, .. }
+            | DataType::UnionType { name, .. }
             | DataType::EnumType { name, .. }
             | DataType::SubRangeType { name, .. }
             | DataType::ArrayType { name, .. }
@@ -493,6 +734,7 @@ impl DataType {
     pub fn get_name(&self) -> Option<&str> {
         match &self {
             DataType::StructType { name, .. }
+            | DataType::UnionType { name, .. }
             | DataType::EnumType { name, .. }
             | DataType::ArrayType { name, .. }
             | DataType::PointerType { name, .. }
@@ -537,7 +779,7 @@ fn replace_reference(
     Some(*old_data_type)
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct ConditionalBlock {
     pub condition: Box<AstStatement>,
     pub body: Vec<AstStatement>,
@@ -552,7 +794,7 @@ impl Debug for ConditionalBlock {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub enum AstStatement {
     EmptyStatement {
         location: SourceRange,
@@ -578,7 +820,7 @@ pub enum AstStatement {
         hour: u32,
         min: u32,
         sec: u32,
-        milli: u32,
+        nano: u32,
         location: SourceRange,
         id: AstId,
     },
@@ -586,7 +828,7 @@ pub enum AstStatement {
         hour: u32,
         min: u32,
         sec: u32,
-        milli: u32,
+        nano: u32,
         location: SourceRange,
         id: AstId,
     },
@@ -788,7 +1030,7 @@ impl Debug for AstStatement {
                 hour,
                 min,
                 sec,
-                milli,
+                nano,
                 ..
             } => f
                 .debug_struct("LiteralDateAndTime")
@@ -798,20 +1040,20 @@ impl Debug for AstStatement {
                 .field("hour", hour)
                 .field("min", min)
                 .field("sec", sec)
-                .field("milli", milli)
+                .field("nano", nano)
                 .finish(),
             AstStatement::LiteralTimeOfDay {
                 hour,
                 min,
                 sec,
-                milli,
+                nano,
                 ..
             } => f
                 .debug_struct("LiteralTimeOfDay")
                 .field("hour", hour)
                 .field("min", min)
                 .field("sec", sec)
-                .field("milli", milli)
+                .field("nano", nano)
                 .finish(),
             AstStatement::LiteralTime {
                 day,
@@ -1006,6 +1248,111 @@ impl Debug for AstStatement {
 }
 
 impl AstStatement {
+    /// returns the direct children of this statement
+    pub fn get_children(&self) -> Vec<&AstStatement> {
+        match self {
+            AstStatement::EmptyStatement { .. }
+            | AstStatement::LiteralInteger { .. }
+            | AstStatement::LiteralDate { .. }
+            | AstStatement::LiteralDateAndTime { .. }
+            | AstStatement::LiteralTimeOfDay { .. }
+            | AstStatement::LiteralTime { .. }
+            | AstStatement::LiteralReal { .. }
+            | AstStatement::LiteralBool { .. }
+            | AstStatement::LiteralString { .. }
+            | AstStatement::LiteralNull { .. }
+            | AstStatement::Reference { .. }
+            | AstStatement::ExitStatement { .. }
+            | AstStatement::ContinueStatement { .. }
+            | AstStatement::ReturnStatement { .. } => vec![],
+            AstStatement::LiteralArray { elements, .. } => {
+                elements.as_deref().into_iter().collect()
+            }
+            AstStatement::CastStatement { target: child, .. }
+            | AstStatement::MultipliedStatement { element: child, .. }
+            | AstStatement::PointerAccess {
+                reference: child, ..
+            }
+            | AstStatement::DirectAccess { index: child, .. }
+            | AstStatement::UnaryExpression { value: child, .. }
+            | AstStatement::CaseCondition {
+                condition: child, ..
+            } => vec![child.as_ref()],
+            AstStatement::QualifiedReference {
+                elements: children, ..
+            }
+            | AstStatement::HardwareAccess {
+                address: children, ..
+            }
+            | AstStatement::ExpressionList {
+                expressions: children,
+                ..
+            } => children.iter().collect(),
+            AstStatement::ArrayAccess {
+                reference: first,
+                access: second,
+                ..
+            }
+            | AstStatement::BinaryExpression {
+                left: first,
+                right: second,
+                ..
+            }
+            | AstStatement::RangeStatement {
+                start: first,
+                end: second,
+                ..
+            }
+            | AstStatement::Assignment {
+                left: first,
+                right: second,
+                ..
+            }
+            | AstStatement::OutputAssignment {
+                left: first,
+                right: second,
+                ..
+            } => vec![first.as_ref(), second.as_ref()],
+            AstStatement::CallStatement {
+                operator,
+                parameters,
+                ..
+            } => std::iter::once(operator.as_ref())
+                .chain((**parameters).as_ref())
+                .collect(),
+            AstStatement::IfStatement {
+                blocks, else_block, ..
+            } => get_block_children(blocks).chain(else_block).collect(),
+            AstStatement::CaseStatement {
+                selector,
+                case_blocks,
+                else_block,
+                ..
+            } => std::iter::once(selector.as_ref())
+                .chain(get_block_children(case_blocks))
+                .chain(else_block)
+                .collect(),
+            AstStatement::ForLoopStatement {
+                counter,
+                start,
+                end,
+                by_step,
+                body,
+                ..
+            } => vec![counter.as_ref(), start.as_ref(), end.as_ref()]
+                .into_iter()
+                .chain(by_step.as_deref())
+                .chain(body)
+                .collect(),
+            AstStatement::WhileLoopStatement {
+                condition, body, ..
+            }
+            | AstStatement::RepeatLoopStatement {
+                condition, body, ..
+            } => std::iter::once(condition.as_ref()).chain(body).collect(),
+        }
+    }
+
     ///Returns the statement in a singleton list, or the contained statements if the statement is already a list
     pub fn get_as_list(&self) -> Vec<&AstStatement> {
         if let AstStatement::ExpressionList { expressions, .. } = self {
@@ -1035,12 +1382,12 @@ impl AstStatement {
                 let last = elements
                     .last()
                     .map_or_else(SourceRange::undefined, |it| it.get_location());
-                SourceRange::new(first.get_start()..last.get_end())
+                first.span(&last)
             }
             AstStatement::BinaryExpression { left, right, .. } => {
                 let left_loc = left.get_location();
                 let right_loc = right.get_location();
-                SourceRange::new(left_loc.range.start..right_loc.range.end)
+                left_loc.span(&right_loc)
             }
             AstStatement::UnaryExpression { location, .. } => location.clone(),
             AstStatement::ExpressionList { expressions, .. } => {
@@ -1050,22 +1397,22 @@ impl AstStatement {
                 let last = expressions
                     .last()
                     .map_or_else(SourceRange::undefined, |it| it.get_location());
-                SourceRange::new(first.get_start()..last.get_end())
+                first.span(&last)
             }
             AstStatement::RangeStatement { start, end, .. } => {
                 let start_loc = start.get_location();
                 let end_loc = end.get_location();
-                SourceRange::new(start_loc.range.start..end_loc.range.end)
+                start_loc.span(&end_loc)
             }
             AstStatement::Assignment { left, right, .. } => {
                 let left_loc = left.get_location();
                 let right_loc = right.get_location();
-                SourceRange::new(left_loc.range.start..right_loc.range.end)
+                left_loc.span(&right_loc)
             }
             AstStatement::OutputAssignment { left, right, .. } => {
                 let left_loc = left.get_location();
                 let right_loc = right.get_location();
-                SourceRange::new(left_loc.range.start..right_loc.range.end)
+                left_loc.span(&right_loc)
             }
             AstStatement::CallStatement { location, .. } => location.clone(),
             AstStatement::IfStatement { location, .. } => location.clone(),
@@ -1078,7 +1425,7 @@ impl AstStatement {
             } => {
                 let reference_loc = reference.get_location();
                 let access_loc = access.get_location();
-                SourceRange::new(reference_loc.range.start..access_loc.range.end)
+                reference_loc.span(&access_loc)
             }
             AstStatement::PointerAccess { reference, .. } => reference.get_location(),
             AstStatement::DirectAccess { location, .. } => location.clone(),
@@ -1141,11 +1488,20 @@ impl AstStatement {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// returns the conditions and the bodies of the given blocks
+fn get_block_children(blocks: &[ConditionalBlock]) -> impl Iterator<Item = &AstStatement> {
+    blocks
+        .iter()
+        .flat_map(|it| std::iter::once(it.condition.as_ref()).chain(&it.body))
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum Operator {
     Plus,
     Minus,
     Multiplication,
+    /// `**`, raises the left operand to the power of the right operand
+    Exponentiation,
     Division,
     Equal,
     NotEqual,
@@ -1158,6 +1514,10 @@ pub enum Operator {
     And,
     Or,
     Xor,
+    /// `AND_THEN`, only evaluates the right operand if the left one is `TRUE`
+    AndThen,
+    /// `OR_ELSE`, only evaluates the right operand if the left one is `FALSE`
+    OrElse,
     Address,
 }
 
@@ -1167,9 +1527,12 @@ impl Display for Operator {
             Operator::Plus => "+",
             Operator::Minus => "-",
             Operator::Multiplication => "*",
+            Operator::Exponentiation => "**",
             Operator::Division => "/",
             Operator::Equal => "=",
             Operator::Modulo => "MOD",
+            Operator::AndThen => "AND_THEN",
+            Operator::OrElse => "OR_ELSE",
             _ => unimplemented!(),
         };
         f.write_str(symbol)
@@ -1232,6 +1595,45 @@ pub fn flatten_expression_list(condition: &AstStatement) -> Vec<&AstStatement> {
     }
 }
 
+/// returns the argument of a call with a single, implicitly passed argument
+/// (e.g. `x` for `f(x)`)
+pub fn get_single_argument(parameters: &Option<AstStatement>) -> Option<&AstStatement> {
+    match parameters.as_ref()? {
+        AstStatement::ExpressionList { .. }
+        | AstStatement::MultipliedStatement { .. }
+        | AstStatement::Assignment { .. }
+        | AstStatement::OutputAssignment { .. }
+        | AstStatement::EmptyStatement { .. } => None,
+        argument => Some(argument),
+    }
+}
+
+/// returns the arguments of a call if all of them are passed implicitly
+/// (e.g. `x`, `y` and `z` for `f(x, y, z)`)
+pub fn get_implicit_arguments(parameters: &Option<AstStatement>) -> Option<Vec<&AstStatement>> {
+    let arguments = flatten_expression_list(parameters.as_ref()?);
+    let is_implicit = |it: &&AstStatement| {
+        !matches!(
+            it,
+            AstStatement::Assignment { .. }
+                | AstStatement::OutputAssignment { .. }
+                | AstStatement::EmptyStatement { .. }
+        )
+    };
+    arguments.iter().all(is_implicit).then(|| arguments)
+}
+
+/// returns the arguments of a call with two implicitly passed arguments
+/// (e.g. `x` and `y` for `f(x, y)`)
+pub fn get_argument_pair(
+    parameters: &Option<AstStatement>,
+) -> Option<(&AstStatement, &AstStatement)> {
+    match get_implicit_arguments(parameters)?.as_slice() {
+        [first, second] => Some((*first, *second)),
+        _ => None,
+    }
+}
+
 /// helper function that creates a call-statement
 pub fn create_call_to(
     function_name: String,
@@ -1333,9 +1735,17 @@ impl Operator {
                 | Operator::Greater
                 | Operator::LessOrEqual
                 | Operator::GreaterOrEqual
+                | Operator::AndThen
+                | Operator::OrElse
         )
     }
 
+    /// returns true, if this operator only evaluates its right operand if the left one
+    /// does not decide the result (`AND_THEN`, `OR_ELSE`)
+    pub(crate) fn is_short_circuit_operator(&self) -> bool {
+        matches!(self, Operator::AndThen | Operator::OrElse)
+    }
+
     /// returns true, if this operator is a comparison operator
     /// (=, <>, >, <, >=, <=)
     pub(crate) fn is_comparison_operator(&self) -> bool {
@@ -48,6 +48,9 @@ pub fn pre_process(unit: &mut CompilationUnit, mut id_provider: IdProvider) {
             match &mut dt.data_type {
                 DataType::StructType {
                     name, variables, ..
+                }
+                | DataType::UnionType {
+                    name, variables, ..
                 } => {
                     let name: &str = name.as_ref().map(|it| it.as_str()).unwrap_or("undefined");
                     variables
@@ -86,6 +89,8 @@ pub fn pre_process(unit: &mut CompilationUnit, mut id_provider: IdProvider) {
                             initializer: None,
                             location,
                             scope,
+                            attributes: vec![],
+                            comments: vec![],
                         };
                         new_types.push(data_type);
                     }
@@ -199,9 +204,12 @@ fn preprocess_generic_structs(pou: &mut Pou) -> Vec<UserTypeDeclaration> {
             initializer: None,
             scope: Some(pou.name.clone()),
             location: pou.location.clone(),
+            attributes: vec![],
+            comments: vec![],
         };
         types.push(data_type);
-        generic_types.insert(binding.name.clone(), new_name);
+        //identifiers are case-insensitive, so the generic type may be referenced in any case
+        generic_types.insert(binding.name.to_lowercase(), new_name);
     }
     for var in pou
         .variable_blocks
@@ -238,6 +246,8 @@ fn preprocess_return_type(pou: &mut Pou, types: &mut Vec<UserTypeDeclaration>) {
                     initializer: None,
                     location,
                     scope,
+                    attributes: vec![],
+                    comments: vec![],
                 };
                 types.push(data_type);
             }
@@ -280,6 +290,8 @@ fn pre_process_variable_data_type(
             initializer: None,
             location,
             scope,
+            attributes: vec![],
+            comments: vec![],
         });
     }
     //make sure it gets generated
@@ -310,6 +322,8 @@ fn add_nested_datatypes(
             initializer: None,
             location: location.clone(),
             scope,
+            attributes: vec![],
+            comments: vec![],
         });
     }
 }
@@ -328,7 +342,7 @@ fn replace_generic_type_name(dt: &mut DataTypeDeclaration, generics: &HashMap<St
         DataTypeDeclaration::DataTypeReference {
             referenced_type, ..
         } => {
-            if let Some(type_name) = generics.get(referenced_type) {
+            if let Some(type_name) = generics.get(&referenced_type.to_lowercase()) {
                 *referenced_type = type_name.clone();
             }
         }
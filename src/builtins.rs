@@ -0,0 +1,319 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+//! Implementations of the standard function blocks (IEC 61131-3, 2.5.2.3).
+//!
+//! The standard function blocks are written in structured text and compiled alongside the
+//! user's sources whenever they are used without being declared by the user. A user-declared
+//! POU with the same name always takes precedence over the built-in implementation.
+use crate::{index::Index, SourceCode};
+
+/// R_TRIG sets Q for exactly one invocation after CLK changed from FALSE to TRUE
+const R_TRIG: &str = "
+FUNCTION_BLOCK R_TRIG
+VAR_INPUT
+    CLK : BOOL;
+END_VAR
+VAR_OUTPUT
+    Q : BOOL;
+END_VAR
+VAR
+    M : BOOL;
+END_VAR
+    Q := CLK AND NOT M;
+    M := CLK;
+END_FUNCTION_BLOCK
+";
+
+/// F_TRIG sets Q for exactly one invocation after CLK changed from TRUE to FALSE
+const F_TRIG: &str = "
+FUNCTION_BLOCK F_TRIG
+VAR_INPUT
+    CLK : BOOL;
+END_VAR
+VAR_OUTPUT
+    Q : BOOL;
+END_VAR
+VAR
+    M : BOOL;
+END_VAR
+    Q := NOT CLK AND M;
+    M := CLK;
+END_FUNCTION_BLOCK
+";
+
+/// the directory the sources of the standard function blocks are named after
+pub const BUILTINS_PATH: &str = "<builtin>";
+
+/// the monotonic time source the runtime has to supply, returning nanoseconds
+pub const TIME_SOURCE_FUNCTION: &str = "rusty_get_time_ns";
+
+const TIME_SOURCE: &str = "
+@EXTERNAL FUNCTION rusty_get_time_ns : TIME
+END_FUNCTION
+";
+
+/// TON sets Q once IN was TRUE for at least PT, ET is the time IN has been TRUE so far
+const TON: &str = "
+FUNCTION_BLOCK TON
+VAR_INPUT
+    IN : BOOL;
+    PT : TIME;
+END_VAR
+VAR_OUTPUT
+    Q : BOOL;
+    ET : TIME;
+END_VAR
+VAR
+    running : BOOL;
+    start : TIME;
+    current_time : TIME;
+END_VAR
+    IF IN THEN
+        current_time := rusty_get_time_ns();
+        IF NOT running THEN
+            running := TRUE;
+            start := current_time;
+        END_IF
+        ET := current_time - start;
+        IF ET >= PT THEN
+            ET := PT;
+        END_IF
+        Q := ET >= PT;
+    ELSE
+        running := FALSE;
+        Q := FALSE;
+        ET := T#0s;
+    END_IF
+END_FUNCTION_BLOCK
+";
+
+/// TOF keeps Q set for PT after IN changed to FALSE, ET is the time IN has been FALSE so far
+const TOF: &str = "
+FUNCTION_BLOCK TOF
+VAR_INPUT
+    IN : BOOL;
+    PT : TIME;
+END_VAR
+VAR_OUTPUT
+    Q : BOOL;
+    ET : TIME;
+END_VAR
+VAR
+    running : BOOL;
+    start : TIME;
+    current_time : TIME;
+END_VAR
+    IF IN THEN
+        running := FALSE;
+        Q := TRUE;
+        ET := T#0s;
+    ELSIF Q THEN
+        current_time := rusty_get_time_ns();
+        IF NOT running THEN
+            running := TRUE;
+            start := current_time;
+        END_IF
+        ET := current_time - start;
+        IF ET >= PT THEN
+            ET := PT;
+        END_IF
+        Q := ET < PT;
+    END_IF
+END_FUNCTION_BLOCK
+";
+
+/// TP sets Q for PT after a rising edge of IN, edges during a running pulse are ignored
+const TP: &str = "
+FUNCTION_BLOCK TP
+VAR_INPUT
+    IN : BOOL;
+    PT : TIME;
+END_VAR
+VAR_OUTPUT
+    Q : BOOL;
+    ET : TIME;
+END_VAR
+VAR
+    running : BOOL;
+    start : TIME;
+    current_time : TIME;
+    M : BOOL;
+END_VAR
+    current_time := rusty_get_time_ns();
+    IF IN AND NOT M AND NOT running THEN
+        running := TRUE;
+        start := current_time;
+    END_IF
+    IF running THEN
+        ET := current_time - start;
+        IF ET >= PT THEN
+            ET := PT;
+            running := FALSE;
+        END_IF
+    ELSIF NOT IN THEN
+        ET := T#0s;
+    END_IF
+    Q := running;
+    M := IN;
+END_FUNCTION_BLOCK
+";
+
+/// CTU counts the rising edges of CU up to the maximum of INT, R resets CV to 0
+const CTU: &str = "
+FUNCTION_BLOCK CTU
+VAR_INPUT
+    CU : BOOL;
+    R : BOOL;
+    PV : INT;
+END_VAR
+VAR_OUTPUT
+    Q : BOOL;
+    CV : INT;
+END_VAR
+VAR
+    CU_M : BOOL;
+END_VAR
+    IF R THEN
+        CV := 0;
+    ELSIF CU AND NOT CU_M AND CV < 32767 THEN
+        CV := CV + 1;
+    END_IF
+    Q := CV >= PV;
+    CU_M := CU;
+END_FUNCTION_BLOCK
+";
+
+/// CTD counts the rising edges of CD down to the minimum of INT, LD loads PV into CV
+const CTD: &str = "
+FUNCTION_BLOCK CTD
+VAR_INPUT
+    CD : BOOL;
+    LD : BOOL;
+    PV : INT;
+END_VAR
+VAR_OUTPUT
+    Q : BOOL;
+    CV : INT;
+END_VAR
+VAR
+    CD_M : BOOL;
+END_VAR
+    IF LD THEN
+        CV := PV;
+    ELSIF CD AND NOT CD_M AND CV > -32768 THEN
+        CV := CV - 1;
+    END_IF
+    Q := CV <= 0;
+    CD_M := CD;
+END_FUNCTION_BLOCK
+";
+
+/// CTUD counts the rising edges of CU up and those of CD down, simultaneous edges cancel out.
+/// R resets CV to 0 and takes precedence over LD loading PV into CV
+const CTUD: &str = "
+FUNCTION_BLOCK CTUD
+VAR_INPUT
+    CU : BOOL;
+    CD : BOOL;
+    R : BOOL;
+    LD : BOOL;
+    PV : INT;
+END_VAR
+VAR_OUTPUT
+    QU : BOOL;
+    QD : BOOL;
+    CV : INT;
+END_VAR
+VAR
+    CU_M : BOOL;
+    CD_M : BOOL;
+    count_up : BOOL;
+    count_down : BOOL;
+END_VAR
+    count_up := CU AND NOT CU_M;
+    count_down := CD AND NOT CD_M;
+    IF R THEN
+        CV := 0;
+    ELSIF LD THEN
+        CV := PV;
+    ELSIF count_up AND NOT count_down AND CV < 32767 THEN
+        CV := CV + 1;
+    ELSIF count_down AND NOT count_up AND CV > -32768 THEN
+        CV := CV - 1;
+    END_IF
+    QU := CV >= PV;
+    QD := CV <= 0;
+    CU_M := CU;
+    CD_M := CD;
+END_FUNCTION_BLOCK
+";
+
+/// SR is a set dominant latch, Q1 is set while S1 is TRUE even if R is TRUE as well
+const SR: &str = "
+FUNCTION_BLOCK SR
+VAR_INPUT
+    S1 : BOOL;
+    R : BOOL;
+END_VAR
+VAR_OUTPUT
+    Q1 : BOOL;
+END_VAR
+    Q1 := S1 OR (NOT R AND Q1);
+END_FUNCTION_BLOCK
+";
+
+/// RS is a reset dominant latch, Q1 is cleared while R1 is TRUE even if S is TRUE as well
+const RS: &str = "
+FUNCTION_BLOCK RS
+VAR_INPUT
+    S : BOOL;
+    R1 : BOOL;
+END_VAR
+VAR_OUTPUT
+    Q1 : BOOL;
+END_VAR
+    Q1 := NOT R1 AND (S OR Q1);
+END_FUNCTION_BLOCK
+";
+
+/// all standard function blocks with their structured text implementation
+const STANDARD_FUNCTION_BLOCKS: &[(&str, &str)] = &[
+    ("R_TRIG", R_TRIG),
+    ("F_TRIG", F_TRIG),
+    ("TON", TON),
+    ("TOF", TOF),
+    ("TP", TP),
+    ("CTU", CTU),
+    ("CTD", CTD),
+    ("CTUD", CTUD),
+    ("SR", SR),
+    ("RS", RS),
+];
+
+/// the standard function blocks that read the time from the `TIME_SOURCE_FUNCTION`
+const TIMER_FUNCTION_BLOCKS: &[&str] = &["TON", "TOF", "TP"];
+
+/// returns the sources of all standard function blocks that are referenced by a variable
+/// in the given index but are not declared by the user. If a timer is among them, the
+/// declaration of the `TIME_SOURCE_FUNCTION` is added unless the user declared it already.
+pub fn get_missing_function_blocks(index: &Index) -> Vec<SourceCode> {
+    let missing = STANDARD_FUNCTION_BLOCKS
+        .iter()
+        .filter(|(name, _)| !index.get_pou_types().contains_key(&name.to_lowercase()))
+        .filter(|(name, _)| index.is_type_referenced(name))
+        .collect::<Vec<_>>();
+
+    let needs_time_source = missing
+        .iter()
+        .any(|(name, _)| TIMER_FUNCTION_BLOCKS.contains(name))
+        && index.find_implementation(TIME_SOURCE_FUNCTION).is_none();
+
+    needs_time_source
+        .then(|| (TIME_SOURCE_FUNCTION, TIME_SOURCE))
+        .iter()
+        .chain(missing.into_iter())
+        .map(|(name, source)| SourceCode {
+            source: (*source).into(),
+            path: format!("{}/{}.st", BUILTINS_PATH, name.to_lowercase()),
+        })
+        .collect()
+}
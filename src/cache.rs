@@ -0,0 +1,369 @@
+// Copyright (c) 2022 Ghaith Hachem and Mathias Rieder
+//! Caches the parsed units of the source files on disk, so a re-compilation only lexes and
+//! parses the files that changed since the previous compilation.
+//!
+//! A unit is cached in a file named after the hash of its source, linkage, the defined names
+//! and the compiler's version. The entry stores them next to the unit and a unit is only loaded
+//! if they match, so two sources with the same hash are parsed again instead of loading the
+//! other's unit. Units with syntax errors are not cached, so their errors are reported again.
+//! The locations of a loaded unit are in the file the unit is loaded for.
+use std::{borrow::Cow, cell::Cell, fs, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ast::{AstStatement, CompilationUnit, DataType, DataTypeDeclaration, LinkageType, Variable},
+    diagnostics::Diagnostic,
+    lexer::IdProvider,
+    resolver::generics,
+};
+
+thread_local! {
+    /// the id of the file whose cached unit is deserialized on this thread
+    static LOADED_FILE: Cell<Option<usize>> = Cell::new(None);
+}
+
+/// returns the id of the file whose cached unit is deserialized on the current thread, every
+/// location of the unit is deserialized in this file. Returns None outside of `UnitCache::load`
+pub(crate) fn get_loaded_file() -> Option<usize> {
+    LOADED_FILE.with(Cell::get)
+}
+
+/// identifies the unit of a source, a cached unit is only loaded for the same key
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct CacheKey<'a> {
+    version: Cow<'a, str>,
+    linkage: LinkageType,
+    defines: Cow<'a, [String]>,
+    source: Cow<'a, str>,
+}
+
+impl<'a> CacheKey<'a> {
+    fn new(source: &'a str, linkage: LinkageType, defines: &'a [String]) -> Self {
+        CacheKey {
+            version: Cow::Borrowed(env!("CARGO_PKG_VERSION")),
+            linkage,
+            defines: Cow::Borrowed(defines),
+            source: Cow::Borrowed(source),
+        }
+    }
+
+    fn get_hash(&self) -> u64 {
+        get_stable_hash(&serde_json::to_vec(self).unwrap_or_default())
+    }
+}
+
+/// returns the 64-bit FNV-1a hash of the given bytes. Unlike the hashers of the standard
+/// library, it does not change between Rust releases, so the cache outlives a rebuild
+fn get_stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[derive(Serialize, Deserialize)]
+struct CacheEntry<'a, U> {
+    key: CacheKey<'a>,
+    unit: U,
+}
+
+pub struct UnitCache {
+    directory: PathBuf,
+}
+
+impl UnitCache {
+    /// creates a cache in the given directory, the directory is created if it does not exist
+    pub fn new(directory: &str) -> Result<UnitCache, Diagnostic> {
+        fs::create_dir_all(directory)
+            .map_err(|it| Diagnostic::io_write_error(directory, &it.to_string()))?;
+        Ok(UnitCache {
+            directory: directory.into(),
+        })
+    }
+
+    /// returns the cached unit of the given source, or None if the source was not cached yet.
+    /// The statements of the unit get new ids from the given id provider and its locations are
+    /// in the file with the given id
+    pub fn load(
+        &self,
+        source: &str,
+        file_id: usize,
+        linkage: LinkageType,
+        defines: &[String],
+        id_provider: &IdProvider,
+    ) -> Option<CompilationUnit> {
+        let key = CacheKey::new(source, linkage, defines);
+        let content = fs::read(self.get_path(&key)).ok()?;
+        //the file ids are not cached, the locations are deserialized in the given file
+        LOADED_FILE.with(|it| it.set(Some(file_id)));
+        let entry = serde_json::from_slice::<CacheEntry<CompilationUnit>>(&content);
+        LOADED_FILE.with(|it| it.set(None));
+        //an unreadable entry (e.g. of an older compiler) or the entry of another source with
+        //the same hash is parsed again
+        let CacheEntry {
+            key: cached_key,
+            mut unit,
+        } = entry.ok()?;
+        if cached_key != key {
+            return None;
+        }
+        IdRenewer {
+            id_provider: id_provider.clone(),
+        }
+        .visit_unit(&mut unit);
+        Some(unit)
+    }
+
+    /// caches the unit parsed from the given source. A failure to write the cache does not
+    /// fail the compilation, the source is parsed again by the next compilation
+    pub fn store(
+        &self,
+        source: &str,
+        linkage: LinkageType,
+        defines: &[String],
+        unit: &CompilationUnit,
+    ) {
+        let entry = CacheEntry {
+            key: CacheKey::new(source, linkage, defines),
+            unit,
+        };
+        if let Ok(content) = serde_json::to_vec(&entry) {
+            let _ = fs::write(self.get_path(&entry.key), content);
+        }
+    }
+
+    fn get_path(&self, key: &CacheKey) -> PathBuf {
+        self.directory.join(format!("{:016x}.json", key.get_hash()))
+    }
+}
+
+/// gives all statements of a cached unit new ids, so they do not collide with the ids of the
+/// other units of the compilation
+struct IdRenewer {
+    id_provider: IdProvider,
+}
+
+impl IdRenewer {
+    fn visit_unit(&self, unit: &mut CompilationUnit) {
+        for block in unit.global_vars.iter_mut() {
+            self.visit_variables(&mut block.variables);
+        }
+        for pou in unit.units.iter_mut() {
+            for block in pou.variable_blocks.iter_mut() {
+                self.visit_variables(&mut block.variables);
+            }
+            if let Some(return_type) = pou.return_type.as_mut() {
+                self.visit_data_type_declaration(return_type);
+            }
+        }
+        for implementation in unit.implementations.iter_mut() {
+            implementation
+                .statements
+                .iter_mut()
+                .for_each(|it| self.visit(it));
+        }
+        for user_type in unit.types.iter_mut() {
+            self.visit_data_type(&mut user_type.data_type);
+            if let Some(initializer) = user_type.initializer.as_mut() {
+                self.visit(initializer);
+            }
+        }
+        for configuration in unit.configurations.iter_mut() {
+            for config_variable in configuration.config_variables.iter_mut() {
+                self.visit(&mut config_variable.address);
+            }
+            for task in configuration
+                .resources
+                .iter_mut()
+                .flat_map(|it| it.tasks.iter_mut())
+            {
+                task.interval
+                    .iter_mut()
+                    .chain(task.single.iter_mut())
+                    .chain(task.priority.iter_mut())
+                    .for_each(|it| self.visit(it));
+            }
+        }
+    }
+
+    fn visit_variables(&self, variables: &mut [Variable]) {
+        for variable in variables.iter_mut() {
+            self.visit_data_type_declaration(&mut variable.data_type);
+            variable
+                .initializer
+                .iter_mut()
+                .chain(variable.address.iter_mut())
+                .for_each(|it| self.visit(it));
+        }
+    }
+
+    fn visit_data_type_declaration(&self, declaration: &mut DataTypeDeclaration) {
+        if let DataTypeDeclaration::DataTypeDefinition { data_type, .. } = declaration {
+            self.visit_data_type(data_type);
+        }
+    }
+
+    fn visit_data_type(&self, data_type: &mut DataType) {
+        match data_type {
+            DataType::StructType { variables, .. } | DataType::UnionType { variables, .. } => {
+                self.visit_variables(variables)
+            }
+            DataType::EnumType { elements, .. } => self.visit(elements),
+            DataType::SubRangeType { bounds, .. } | DataType::StringType { size: bounds, .. } => {
+                if let Some(bounds) = bounds.as_mut() {
+                    self.visit(bounds);
+                }
+            }
+            DataType::ArrayType {
+                bounds,
+                referenced_type,
+                ..
+            } => {
+                self.visit(bounds);
+                self.visit_data_type_declaration(referenced_type);
+            }
+            DataType::PointerType {
+                referenced_type, ..
+            }
+            | DataType::VarArgs {
+                referenced_type: Some(referenced_type),
+            } => self.visit_data_type_declaration(referenced_type),
+            DataType::VarArgs { .. } | DataType::GenericType { .. } => {}
+        }
+    }
+
+    fn visit(&self, statement: &mut AstStatement) {
+        generics::renew_ids(statement, self.id_provider.clone());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_stable_hash, CacheKey, UnitCache};
+    use crate::{
+        ast::{AstStatement, LinkageType},
+        lexer::{self, IdProvider},
+        parser,
+    };
+
+    const SOURCE: &str = "
+        PROGRAM prg
+        VAR x : INT := 1; END_VAR
+            x := x + 1;
+        END_PROGRAM
+        ";
+
+    fn create_cache(name: &str) -> UnitCache {
+        let directory = std::env::temp_dir().join(format!("{}_{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        UnitCache::new(directory.to_str().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn cached_units_are_loaded_with_new_ids() {
+        let cache = create_cache("cached_units_are_loaded");
+        let id_provider = IdProvider::default();
+        let (unit, _) = parser::parse(
+            lexer::lex_file(SOURCE, 0, id_provider.clone(), &[]),
+            LinkageType::Internal,
+        );
+        assert!(cache
+            .load(SOURCE, 0, LinkageType::Internal, &[], &id_provider)
+            .is_none());
+
+        cache.store(SOURCE, LinkageType::Internal, &[], &unit);
+        let cached = cache
+            .load(SOURCE, 0, LinkageType::Internal, &[], &id_provider)
+            .unwrap();
+
+        //the cached unit only differs by the ids of its statements
+        assert_eq!(format!("{:?}", cached), format!("{:?}", unit));
+        let get_id = |statements: &[AstStatement]| statements[0].get_id();
+        assert_ne!(
+            get_id(&cached.implementations[0].statements),
+            get_id(&unit.implementations[0].statements)
+        );
+    }
+
+    #[test]
+    fn cached_units_are_loaded_in_the_given_file() {
+        let cache = create_cache("cached_units_are_loaded_in_the_given_file");
+        let id_provider = IdProvider::default();
+        let (unit, _) = parser::parse(
+            lexer::lex_file(SOURCE, 0, id_provider.clone(), &[]),
+            LinkageType::Internal,
+        );
+        cache.store(SOURCE, LinkageType::Internal, &[], &unit);
+
+        //the same source is loaded for another file (e.g. the order of the files changed)
+        let cached = cache
+            .load(SOURCE, 3, LinkageType::Internal, &[], &id_provider)
+            .unwrap();
+        let pou = &cached.units[0];
+        assert_eq!(pou.location.get_file(), Some(3));
+        assert_eq!(
+            pou.variable_blocks[0].variables[0].location.get_file(),
+            Some(3)
+        );
+        let statement = &cached.implementations[0].statements[0];
+        assert_eq!(statement.get_location().get_file(), Some(3));
+        //the file is only assigned while the unit is loaded
+        assert_eq!(super::get_loaded_file(), None);
+    }
+
+    #[test]
+    fn units_are_cached_per_source_linkage_and_defines() {
+        let cache = create_cache("units_are_cached_per_source");
+        let id_provider = IdProvider::default();
+        let (unit, _) = parser::parse(
+            lexer::lex_with_ids(SOURCE, id_provider.clone()),
+            LinkageType::Internal,
+        );
+        cache.store(SOURCE, LinkageType::Internal, &[], &unit);
+
+        let changed_source = SOURCE.replace("x + 1", "x + 2");
+        assert!(cache
+            .load(&changed_source, 0, LinkageType::Internal, &[], &id_provider)
+            .is_none());
+        assert!(cache
+            .load(SOURCE, 0, LinkageType::External, &[], &id_provider)
+            .is_none());
+        assert!(cache
+            .load(
+                SOURCE,
+                0,
+                LinkageType::Internal,
+                &["PLC_A".to_string()],
+                &id_provider
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn the_cache_key_is_hashed_with_fnv_1a() {
+        assert_eq!(get_stable_hash(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(get_stable_hash(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(get_stable_hash(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn an_entry_of_another_source_with_the_same_hash_is_not_loaded() {
+        let cache = create_cache("an_entry_of_another_source");
+        let id_provider = IdProvider::default();
+        let (unit, _) = parser::parse(
+            lexer::lex_with_ids(SOURCE, id_provider.clone()),
+            LinkageType::Internal,
+        );
+        cache.store(SOURCE, LinkageType::Internal, &[], &unit);
+
+        //the entry of SOURCE is found under the hash of another source
+        let other_source = SOURCE.replace("x + 1", "x - 1");
+        let other_key = CacheKey::new(&other_source, LinkageType::Internal, &[]);
+        let key = CacheKey::new(SOURCE, LinkageType::Internal, &[]);
+        std::fs::rename(cache.get_path(&key), cache.get_path(&other_key)).unwrap();
+
+        assert!(cache
+            .load(&other_source, 0, LinkageType::Internal, &[], &id_provider)
+            .is_none());
+    }
+}
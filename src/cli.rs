@@ -1,9 +1,13 @@
 // Copyright (c) 2021 Ghaith Hachem and Mathias Rieder
-use clap::{ArgGroup, Parser};
+use clap::{AppSettings, ArgGroup, Args, Parser, Subcommand};
 use encoding_rs::Encoding;
 use std::{ffi::OsStr, path::Path};
 
-use crate::{ConfigFormat, FormatOption};
+use crate::{
+    diagnostics::WarningLevel, formatter::FormatOptions, AstFormat, ConfigFormat,
+    DivisionCheckMode, DocFormat, ErrorFormat, FormatOption, KeywordCase, LtoMode,
+    OptimizationLevel, OverflowMode, RangeCheckMode, SectionNames, WarningCode,
+};
 
 // => Set the default output format here:
 const DEFAULT_FORMAT: FormatOption = FormatOption::Static;
@@ -16,6 +20,7 @@ pub type ParameterError = clap::Error;
     group = ArgGroup::new("format"),
     about = "IEC61131-3 Structured Text compiler powered by Rust & LLVM ",
     version,
+    setting = AppSettings::SubcommandsNegateReqs,
 )]
 pub struct CompileParameters {
     #[clap(
@@ -64,6 +69,13 @@ pub struct CompileParameters {
     )]
     pub output_bit_code: bool,
 
+    #[clap(
+        long = "stlib",
+        group = "format",
+        help = "Emit a precompiled library (the index of the declarations and the object code) as output"
+    )]
+    pub output_library: bool,
+
     #[clap(short = 'c', help = "Do not link after compiling object code")]
     pub skip_linking: bool,
 
@@ -85,7 +97,7 @@ pub struct CompileParameters {
     #[clap(
         name = "input-files",
         help = "Read input from <input-files>, may be a glob expression like 'src/**/*' or a sequence of files",
-        required = true,
+        required_unless_present = "lsp",
         min_values = 1
     )]
     // having a vec allows bash to resolve *.st itself
@@ -102,17 +114,47 @@ pub struct CompileParameters {
     #[clap(name = "library", long, short = 'l', help = "Library name to link")]
     pub libraries: Vec<String>,
 
+    #[clap(
+        name = "define",
+        long,
+        short = 'D',
+        help = "Define a name for the conditional compilation directives (e.g. {IF defined(name)})",
+        parse(try_from_str = validate_define)
+    )]
+    pub defines: Vec<String>,
+
     #[clap(long, name = "sysroot", help = "Path to system root, used for linking")]
     pub sysroot: Option<String>,
 
+    #[clap(
+        long,
+        name = "data-layout",
+        help = "The LLVM data layout of the target, overrides the layout of the target-triple"
+    )]
+    pub data_layout: Option<String>,
+
     #[clap(
         name = "include",
         long,
         short = 'i',
-        help = "Include source files for external functions"
+        help = "Include files declaring the POUs, types and global variables of a library, their declarations are used by the compiled sources but no code is generated for them, may be a glob expression like 'lib/**/*.st'"
     )]
     pub includes: Vec<String>,
 
+    #[clap(
+        name = "lib",
+        long = "lib",
+        help = "Use a precompiled library (.stlib), its declarations are used by the compiled sources and its object code is linked"
+    )]
+    pub precompiled_libraries: Vec<String>,
+
+    #[clap(
+        long,
+        name = "cache-dir",
+        help = "Cache the parsed source files in the given directory, so a re-compilation only parses the changed files"
+    )]
+    pub cache_dir: Option<String>,
+
     #[clap(
         name = "hardware-conf",
         long,
@@ -122,6 +164,283 @@ pub struct CompileParameters {
     parse(try_from_str = validate_config)
     ) ]
     pub hardware_config: Option<String>,
+
+    #[clap(
+        name = "task-conf",
+        long,
+        help = "Generate a table of the configured tasks and their program instances to the given location. 
+    Format is detected by extenstion.
+    Supported formats : json, toml",
+    parse(try_from_str = validate_config)
+    ) ]
+    pub task_config: Option<String>,
+
+    #[clap(
+        name = "retain-section",
+        long,
+        help = "The section to place VAR_GLOBAL RETAIN variables in (defaults to '.retain')"
+    )]
+    pub retain_section: Option<String>,
+
+    #[clap(
+        name = "persistent-section",
+        long,
+        help = "The section to place VAR_GLOBAL PERSISTENT variables in (defaults to '.persistent')"
+    )]
+    pub persistent_section: Option<String>,
+
+    #[clap(
+        name = "range-checks",
+        long,
+        arg_enum,
+        help = "Check assignments to sub-range types that have no check function"
+    )]
+    pub range_checks: Option<RangeCheckMode>,
+
+    #[clap(
+        name = "division-checks",
+        long,
+        arg_enum,
+        help = "Check integer divisions (/ and MOD) by zero, fault calls rusty_division_fault(location) and results in zero if it returns"
+    )]
+    pub division_checks: Option<DivisionCheckMode>,
+
+    #[clap(
+        name = "bounds-checks",
+        long,
+        help = "Check every array index against the bounds of its dimension, an index out of bounds calls rusty_bounds_fault(index, lower, upper, location)"
+    )]
+    pub bounds_checks: bool,
+
+    #[clap(
+        name = "overflow",
+        long,
+        arg_enum,
+        default_value = "wrap",
+        help = "The behavior of integer arithmetic (+, - and *) whose result does not fit its type"
+    )]
+    pub overflow: OverflowMode,
+
+    #[clap(
+        name = "header-out",
+        long,
+        help = "Generate a C header declaring the compiled POUs and global variables to the given file"
+    )]
+    pub header_out: Option<String>,
+
+    #[clap(
+        name = "stack-report",
+        long,
+        help = "Write the stack used by every generated POU and its deepest chain of calls to the given file"
+    )]
+    pub stack_report: Option<String>,
+
+    #[clap(
+        name = "xref-out",
+        long,
+        help = "Write the locations every variable is read and written at and every POU is called at to the given file as JSON"
+    )]
+    pub xref_out: Option<String>,
+
+    #[clap(
+        long,
+        help = "Generate the code of every POU into its own section (.text.<pou>) and the instance data of programs into .data.<pou>"
+    )]
+    pub pou_sections: bool,
+
+    #[clap(
+        long,
+        help = "Generate position-independent code for objects emitted with --static or --relocatable (objects emitted with --pic always are)"
+    )]
+    pub position_independent: bool,
+
+    #[clap(
+        long,
+        help = "Write the signatures (CRC-32) of the generated POUs and the project to the given file and generate them into the object"
+    )]
+    pub signatures: Option<String>,
+
+    #[clap(
+        name = "print-ast",
+        long,
+        arg_enum,
+        help = "Print the parsed source files in the given format instead of compiling them"
+    )]
+    pub print_ast: Option<AstFormat>,
+
+    #[clap(
+        name = "print-index",
+        long,
+        help = "Print the compiled POUs, types and global variables as JSON"
+    )]
+    pub print_index: bool,
+
+    #[clap(
+        name = "doc-out",
+        long,
+        help = "Generate the API documentation of the source files from their doc comments into the given directory instead of compiling them"
+    )]
+    pub doc_out: Option<String>,
+
+    #[clap(
+        name = "doc-format",
+        long,
+        arg_enum,
+        default_value = "markdown",
+        help = "The format of the documentation generated by --doc-out"
+    )]
+    pub doc_format: DocFormat,
+
+    #[clap(
+        long,
+        help = "Run a language server for structured text files, communicating over std-in and std-out"
+    )]
+    pub lsp: bool,
+
+    #[clap(
+        long,
+        short = 'g',
+        help = "Generate debug information describing the POUs and variables of the source files"
+    )]
+    pub debug: bool,
+
+    #[clap(
+        short = 'O',
+        long = "optimization",
+        default_value = "0",
+        help = "The optimization level of the generated code: 0 (none), 1, 2 or 3 (aggressive)"
+    )]
+    pub optimization: OptimizationLevel,
+
+    #[clap(
+        name = "main",
+        long,
+        help = "Generate a main function calling the given PROGRAM (or configured program instance) cyclically"
+    )]
+    pub main_program: Option<String>,
+
+    #[clap(
+        long,
+        help = "Prefix the symbols of the compiled POUs, program instances and global variables, so several compiled libraries can be linked together",
+        parse(try_from_str = validate_symbol_prefix)
+    )]
+    pub symbol_prefix: Option<String>,
+
+    #[clap(
+        long,
+        arg_enum,
+        help = "Compile every source file into its own module and link them with link-time optimization"
+    )]
+    pub lto: Option<LtoMode>,
+
+    #[clap(
+        long,
+        arg_enum,
+        default_value = "rich",
+        help = "The format the diagnostics are reported in, json reports every diagnostic as a line of JSON"
+    )]
+    pub error_format: ErrorFormat,
+
+    #[clap(
+        long,
+        help = "Report initializers that do not provide a value for every element as errors instead of warnings"
+    )]
+    pub deny_partial_initializers: bool,
+
+    #[clap(long, help = "Report recursive calls as warnings instead of errors")]
+    pub warn_recursion: bool,
+
+    #[clap(
+        name = "allow",
+        long,
+        arg_enum,
+        help = "Do not report the warnings with the given code"
+    )]
+    pub allowed_warnings: Vec<WarningCode>,
+
+    #[clap(
+        name = "warn",
+        short = 'W',
+        long,
+        arg_enum,
+        help = "Report the warnings with the given code as warnings, also if --werror is given"
+    )]
+    pub warned_warnings: Vec<WarningCode>,
+
+    #[clap(
+        name = "error",
+        long,
+        arg_enum,
+        help = "Report the warnings with the given code as errors"
+    )]
+    pub error_warnings: Vec<WarningCode>,
+
+    #[clap(
+        long,
+        help = "Report all warnings as errors, except the warnings configured by --allow or --warn"
+    )]
+    pub werror: bool,
+
+    #[clap(subcommand)]
+    pub command: Option<SubCommand>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SubCommand {
+    #[clap(about = "Format the given structured text files in place")]
+    Fmt(FormatParameters),
+}
+
+#[derive(Args, Debug)]
+pub struct FormatParameters {
+    #[clap(
+        name = "input-files",
+        help = "The files to format, may be a glob expression like 'src/**/*' or a sequence of files",
+        required = true,
+        min_values = 1
+    )]
+    pub input: Vec<String>,
+
+    #[clap(
+        long,
+        default_value = "4",
+        help = "The number of spaces nested lines are indented with"
+    )]
+    pub indent: usize,
+
+    #[clap(long, help = "Indent nested lines with tabs instead of spaces")]
+    pub tabs: bool,
+
+    #[clap(
+        long,
+        arg_enum,
+        default_value = "upper",
+        help = "The case of the keywords"
+    )]
+    pub keyword_case: KeywordCase,
+
+    #[clap(long, default_value = "100", help = "The width lines are wrapped at")]
+    pub line_width: usize,
+
+    #[clap(
+        long,
+        help = "Report the files that are not formatted instead of formatting them"
+    )]
+    pub check: bool,
+}
+
+impl FormatParameters {
+    pub fn format_options(&self) -> FormatOptions {
+        FormatOptions {
+            indent: if self.tabs {
+                "\t".to_string()
+            } else {
+                " ".repeat(self.indent)
+            },
+            keyword_case: self.keyword_case,
+            line_width: self.line_width,
+        }
+    }
 }
 
 fn parse_encoding(encoding: &str) -> Result<&'static Encoding, String> {
@@ -139,6 +458,40 @@ fn validate_config(config_name: &str) -> Result<String, String> {
     }
 }
 
+/// the prefix becomes part of the symbols and of the identifiers in the generated header
+fn validate_symbol_prefix(prefix: &str) -> Result<String, String> {
+    if !prefix.is_empty()
+        && !prefix.starts_with(|it: char| it.is_ascii_digit())
+        && prefix
+            .chars()
+            .all(|it| it.is_ascii_alphanumeric() || it == '_')
+    {
+        Ok(prefix.to_string())
+    } else {
+        Err(format!(
+            "Invalid symbol prefix {}, expected letters, digits and underscores",
+            prefix
+        ))
+    }
+}
+
+/// defined names are tested like identifiers by the conditional compilation directives
+fn validate_define(name: &str) -> Result<String, String> {
+    if !name.is_empty()
+        && !name.starts_with(|it: char| it.is_ascii_digit())
+        && name
+            .chars()
+            .all(|it| it.is_ascii_alphanumeric() || it == '_')
+    {
+        Ok(name.to_string())
+    } else {
+        Err(format!(
+            "Invalid define {}, expected letters, digits and underscores",
+            name
+        ))
+    }
+}
+
 pub fn get_config_format(name: &str) -> Option<ConfigFormat> {
     let ext = name.split('.').last();
     match ext {
@@ -148,6 +501,18 @@ pub fn get_config_format(name: &str) -> Option<ConfigFormat> {
     }
 }
 
+/// returns the output format indicated by the extension of the given output file
+fn get_output_format(name: &str) -> Option<FormatOption> {
+    let ext = Path::new(name).extension().and_then(OsStr::to_str);
+    match ext {
+        Some("so") => Some(FormatOption::PIC),
+        Some("bc") => Some(FormatOption::Bitcode),
+        Some("ll") | Some("ir") => Some(FormatOption::IR),
+        Some("stlib") => Some(FormatOption::Library),
+        _ => None,
+    }
+}
+
 impl CompileParameters {
     pub fn parse(args: Vec<String>) -> Result<CompileParameters, ParameterError> {
         CompileParameters::try_parse_from(args)
@@ -167,16 +532,21 @@ impl CompileParameters {
             Some(FormatOption::Static)
         } else if self.output_reloc_code {
             Some(FormatOption::Relocatable)
+        } else if self.output_library {
+            Some(FormatOption::Library)
         } else {
             None
         }
     }
 
-    /// return the selected output format, or the default if none.
+    /// return the selected output format, the format indicated by the output's extension
+    /// (e.g. a shared library for `-o plc.so`) or the default if none.
     pub fn output_format_or_default(&self) -> FormatOption {
         // structop makes sure only one or zero format flags are
         // selected. So if none are selected, the default is chosen
-        self.output_format().unwrap_or(DEFAULT_FORMAT)
+        self.output_format()
+            .or_else(|| self.output.as_deref().and_then(get_output_format))
+            .unwrap_or(DEFAULT_FORMAT)
     }
 
     /// return the output filename with the correct ending
@@ -192,6 +562,7 @@ impl CompileParameters {
                 FormatOption::Static => "",
                 FormatOption::Shared | FormatOption::PIC => ".so",
                 FormatOption::IR => ".ir",
+                FormatOption::Library => ".stlib",
             };
 
             let output_name = self.input.first().map(String::as_str);
@@ -206,12 +577,45 @@ impl CompileParameters {
     pub fn config_format(&self) -> Option<ConfigFormat> {
         self.hardware_config.as_deref().and_then(get_config_format)
     }
+
+    pub fn task_config_format(&self) -> Option<ConfigFormat> {
+        self.task_config.as_deref().and_then(get_config_format)
+    }
+
+    /// return the sections for retained variables, falling back to the defaults
+    pub fn section_names(&self) -> SectionNames {
+        let defaults = SectionNames::default();
+        SectionNames {
+            retain: self.retain_section.clone().unwrap_or(defaults.retain),
+            persistent: self
+                .persistent_section
+                .clone()
+                .unwrap_or(defaults.persistent),
+        }
+    }
+
+    /// returns the levels configured for the warning codes, a code given to several options gets
+    /// the most severe level
+    pub fn warning_levels(&self) -> Vec<(WarningCode, WarningLevel)> {
+        let levels =
+            |codes: &[WarningCode], level| codes.iter().map(|it| (*it, level)).collect::<Vec<_>>();
+        [
+            levels(&self.allowed_warnings, WarningLevel::Allow),
+            levels(&self.warned_warnings, WarningLevel::Warn),
+            levels(&self.error_warnings, WarningLevel::Error),
+        ]
+        .concat()
+    }
 }
 
 #[cfg(test)]
 mod cli_tests {
-    use super::{CompileParameters, ParameterError};
-    use crate::{ConfigFormat, FormatOption};
+    use super::{CompileParameters, ParameterError, SubCommand};
+    use crate::{
+        diagnostics::WarningLevel, AstFormat, ConfigFormat, DivisionCheckMode, DocFormat,
+        ErrorFormat, FormatOption, KeywordCase, LtoMode, OptimizationLevel, OverflowMode,
+        RangeCheckMode, SectionNames, WarningCode,
+    };
     use clap::ErrorKind;
     use pretty_assertions::assert_eq;
 
@@ -329,6 +733,427 @@ mod cli_tests {
         assert_eq!(parameters.target, Some("x86_64-linux-gnu".to_string()));
     }
 
+    #[test]
+    fn test_section_names() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert_eq!(parameters.section_names(), SectionNames::default());
+
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "alpha.st",
+            "--retain-section",
+            ".noinit",
+            "--persistent-section",
+            ".nvram"
+        ))
+        .unwrap();
+        assert_eq!(
+            parameters.section_names(),
+            SectionNames {
+                retain: ".noinit".to_string(),
+                persistent: ".nvram".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_bounds_checks() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert!(!parameters.bounds_checks);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--bounds-checks")).unwrap();
+        assert!(parameters.bounds_checks);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert_eq!(parameters.overflow, OverflowMode::Wrap);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--overflow", "trap")).unwrap();
+        assert_eq!(parameters.overflow, OverflowMode::Trap);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--overflow", "saturate"))
+                .unwrap();
+        assert_eq!(parameters.overflow, OverflowMode::Saturate);
+
+        expect_argument_error(
+            vec_of_strings!("alpha.st", "--overflow", "zero"),
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn test_division_checks() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert_eq!(parameters.division_checks, None);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--division-checks", "trap"))
+                .unwrap();
+        assert_eq!(parameters.division_checks, Some(DivisionCheckMode::Trap));
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--division-checks", "zero"))
+                .unwrap();
+        assert_eq!(parameters.division_checks, Some(DivisionCheckMode::Zero));
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--division-checks", "fault"))
+                .unwrap();
+        assert_eq!(parameters.division_checks, Some(DivisionCheckMode::Fault));
+
+        expect_argument_error(
+            vec_of_strings!("alpha.st", "--division-checks", "saturate"),
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn test_range_checks() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert_eq!(parameters.range_checks, None);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--range-checks", "trap"))
+                .unwrap();
+        assert_eq!(parameters.range_checks, Some(RangeCheckMode::Trap));
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--range-checks", "saturate"))
+                .unwrap();
+        assert_eq!(parameters.range_checks, Some(RangeCheckMode::Saturate));
+
+        expect_argument_error(
+            vec_of_strings!("alpha.st", "--range-checks", "wrap"),
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn debug_option_set() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert!(!parameters.debug);
+
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st", "-g")).unwrap();
+        assert!(parameters.debug);
+
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st", "--debug")).unwrap();
+        assert!(parameters.debug);
+    }
+
+    #[test]
+    fn optimization_levels() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert_eq!(parameters.optimization, OptimizationLevel::None);
+
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st", "-O1")).unwrap();
+        assert_eq!(parameters.optimization, OptimizationLevel::Less);
+
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st", "-O2")).unwrap();
+        assert_eq!(parameters.optimization, OptimizationLevel::Default);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--optimization", "3")).unwrap();
+        assert_eq!(parameters.optimization, OptimizationLevel::Aggressive);
+
+        expect_argument_error(
+            vec_of_strings!("alpha.st", "-O4"),
+            ErrorKind::ValueValidation,
+        );
+    }
+
+    #[test]
+    fn output_format_is_detected_by_the_output_extension() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "-o", "plc.so")).unwrap();
+        assert_eq!(parameters.output_format_or_default(), FormatOption::PIC);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "-o", "plc.bc")).unwrap();
+        assert_eq!(parameters.output_format_or_default(), FormatOption::Bitcode);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "-o", "plc.ll")).unwrap();
+        assert_eq!(parameters.output_format_or_default(), FormatOption::IR);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "-o", "plc")).unwrap();
+        assert_eq!(parameters.output_format_or_default(), FormatOption::Static);
+
+        // an explicit format wins over the extension
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--shared", "-o", "plc.so"))
+                .unwrap();
+        assert_eq!(parameters.output_format_or_default(), FormatOption::Shared);
+    }
+
+    #[test]
+    fn main_program_option_set() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert_eq!(parameters.main_program, None);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--main", "PLC_PRG")).unwrap();
+        assert_eq!(parameters.main_program, Some("PLC_PRG".to_string()));
+    }
+
+    #[test]
+    fn defines_added() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st", "-D", "PLC_A", "-DDEBUG", "--define", "x2"
+        ))
+        .unwrap();
+        assert_eq!(parameters.defines, vec!["PLC_A", "DEBUG", "x2"]);
+
+        expect_argument_error(
+            vec_of_strings!("input.st", "-D", "2x"),
+            ErrorKind::ValueValidation,
+        );
+    }
+
+    #[test]
+    fn symbol_prefix_option_set() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert_eq!(parameters.symbol_prefix, None);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--symbol-prefix", "lib1_"))
+                .unwrap();
+        assert_eq!(parameters.symbol_prefix, Some("lib1_".to_string()));
+
+        expect_argument_error(
+            vec_of_strings!("alpha.st", "--symbol-prefix", "lib.1"),
+            ErrorKind::ValueValidation,
+        );
+    }
+
+    #[test]
+    fn data_layout_option_set() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert_eq!(parameters.data_layout, None);
+
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "alpha.st",
+            "--target",
+            "arm-linux-gnueabihf",
+            "--data-layout",
+            "e-m:e-p:32:32-i64:64-n32-S64"
+        ))
+        .unwrap();
+        assert_eq!(
+            parameters.data_layout,
+            Some("e-m:e-p:32:32-i64:64-n32-S64".to_string())
+        );
+    }
+
+    #[test]
+    fn lto_modes() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert_eq!(parameters.lto, None);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--lto=full")).unwrap();
+        assert_eq!(parameters.lto, Some(LtoMode::Full));
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--lto", "thin")).unwrap();
+        assert_eq!(parameters.lto, Some(LtoMode::Thin));
+
+        expect_argument_error(
+            vec_of_strings!("alpha.st", "--lto=partial"),
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn error_formats() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert_eq!(parameters.error_format, ErrorFormat::Rich);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--error-format=json")).unwrap();
+        assert_eq!(parameters.error_format, ErrorFormat::Json);
+
+        expect_argument_error(
+            vec_of_strings!("alpha.st", "--error-format=xml"),
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn print_ast_formats() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert_eq!(parameters.print_ast, None);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--print-ast=text")).unwrap();
+        assert_eq!(parameters.print_ast, Some(AstFormat::Text));
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--print-ast", "json")).unwrap();
+        assert_eq!(parameters.print_ast, Some(AstFormat::Json));
+
+        expect_argument_error(
+            vec_of_strings!("alpha.st", "--print-ast=xml"),
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn print_index_option() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert!(!parameters.print_index);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--print-index")).unwrap();
+        assert!(parameters.print_index);
+    }
+
+    #[test]
+    fn doc_out_options() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert_eq!(parameters.doc_out, None);
+        assert_eq!(parameters.doc_format, DocFormat::Markdown);
+
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "alpha.st",
+            "--doc-out",
+            "docs",
+            "--doc-format=html"
+        ))
+        .unwrap();
+        assert_eq!(parameters.doc_out.as_deref(), Some("docs"));
+        assert_eq!(parameters.doc_format, DocFormat::Html);
+
+        expect_argument_error(
+            vec_of_strings!("alpha.st", "--doc-format=pdf"),
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn lsp_option_does_not_require_input_files() {
+        let parameters = CompileParameters::parse(vec_of_strings!("--lsp")).unwrap();
+        assert!(parameters.lsp);
+        assert!(parameters.input.is_empty());
+
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert!(!parameters.lsp);
+    }
+
+    #[test]
+    fn fmt_subcommand() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert!(parameters.command.is_none());
+
+        let parameters = CompileParameters::parse(vec_of_strings!("fmt", "alpha.st")).unwrap();
+        let SubCommand::Fmt(format) = parameters.command.unwrap();
+        assert_eq!(format.input, vec!["alpha.st".to_string()]);
+        assert!(!format.check);
+        let options = format.format_options();
+        assert_eq!(options.indent, "    ");
+        assert_eq!(options.keyword_case, KeywordCase::Upper);
+        assert_eq!(options.line_width, 100);
+
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "fmt",
+            "alpha.st",
+            "--tabs",
+            "--keyword-case",
+            "lower",
+            "--line-width",
+            "80",
+            "--check"
+        ))
+        .unwrap();
+        let SubCommand::Fmt(format) = parameters.command.unwrap();
+        assert!(format.check);
+        let options = format.format_options();
+        assert_eq!(options.indent, "\t");
+        assert_eq!(options.keyword_case, KeywordCase::Lower);
+        assert_eq!(options.line_width, 80);
+
+        //the files to format are required
+        expect_argument_error(vec_of_strings!("fmt"), ErrorKind::MissingRequiredArgument);
+    }
+
+    #[test]
+    fn deny_partial_initializers_option() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert!(!parameters.deny_partial_initializers);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--deny-partial-initializers"))
+                .unwrap();
+        assert!(parameters.deny_partial_initializers);
+    }
+
+    #[test]
+    fn warn_recursion_option() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert!(!parameters.warn_recursion);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("alpha.st", "--warn-recursion")).unwrap();
+        assert!(parameters.warn_recursion);
+    }
+
+    #[test]
+    fn allow_option() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert!(parameters.allowed_warnings.is_empty());
+
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "alpha.st",
+            "--allow",
+            "var__unused_variable",
+            "--allow",
+            "statement__unreachable_code"
+        ))
+        .unwrap();
+        assert_eq!(
+            parameters.allowed_warnings,
+            vec![WarningCode::UnusedVariable, WarningCode::UnreachableCode]
+        );
+
+        //only warnings can be allowed
+        expect_argument_error(
+            vec_of_strings!("alpha.st", "--allow", "reference__unresolved"),
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn warning_level_options() {
+        let parameters = CompileParameters::parse(vec_of_strings!("alpha.st")).unwrap();
+        assert!(parameters.warning_levels().is_empty());
+        assert!(!parameters.werror);
+
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "alpha.st",
+            "--werror",
+            "-W",
+            "pou__unused_pou",
+            "--error",
+            "var__unused_variable",
+            "--allow",
+            "pou__unused_pou"
+        ))
+        .unwrap();
+        assert!(parameters.werror);
+        assert_eq!(
+            parameters.warning_levels(),
+            vec![
+                (WarningCode::UnusedPou, WarningLevel::Allow),
+                (WarningCode::UnusedPou, WarningLevel::Warn),
+                (WarningCode::UnusedVariable, WarningLevel::Error),
+            ]
+        );
+    }
+
     #[test]
     fn test_default_format() {
         let parameters = CompileParameters::parse(vec_of_strings!("alpha.st", "--ir")).unwrap();
@@ -510,6 +1335,46 @@ mod cli_tests {
         );
     }
 
+    #[test]
+    fn precompiled_libraries_added() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--lib",
+            "util.stlib",
+            "--lib",
+            "lib/motion.stlib",
+            "-l",
+            "c"
+        ))
+        .unwrap();
+        assert_eq!(
+            parameters.precompiled_libraries,
+            vec!["util.stlib", "lib/motion.stlib"]
+        );
+        assert_eq!(parameters.libraries, vec!["c"]);
+    }
+
+    #[test]
+    fn cache_dir_set() {
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st")).unwrap();
+        assert_eq!(parameters.cache_dir, None);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--cache-dir", ".rusty")).unwrap();
+        assert_eq!(parameters.cache_dir, Some(".rusty".to_string()));
+    }
+
+    #[test]
+    fn precompiled_libraries_are_emitted() {
+        let parameters = CompileParameters::parse(vec_of_strings!("util.st", "--stlib")).unwrap();
+        assert_eq!(parameters.output_format_or_default(), FormatOption::Library);
+        assert_eq!(parameters.output_name().unwrap(), "util.stlib");
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("util.st", "-o", "util.stlib")).unwrap();
+        assert_eq!(parameters.output_format_or_default(), FormatOption::Library);
+    }
+
     #[test]
     fn config_option_set() {
         let parameters =
@@ -534,4 +1399,73 @@ mod cli_tests {
             ErrorKind::ValueValidation,
         );
     }
+
+    #[test]
+    fn task_config_option_set() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("foo", "--task-conf=tasks.json")).unwrap();
+        assert_eq!(parameters.task_config, Some("tasks.json".to_string()));
+        assert_eq!(parameters.task_config_format().unwrap(), ConfigFormat::JSON);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("foo", "--task-conf=tasks.toml")).unwrap();
+        assert_eq!(parameters.task_config_format().unwrap(), ConfigFormat::TOML);
+
+        expect_argument_error(
+            vec_of_strings!("foo", "--task-conf=tasks.xml"),
+            ErrorKind::ValueValidation,
+        );
+    }
+
+    #[test]
+    fn stack_report_option_set() {
+        let parameters = CompileParameters::parse(vec_of_strings!("foo")).unwrap();
+        assert_eq!(parameters.stack_report, None);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("foo", "--stack-report", "stack.txt"))
+                .unwrap();
+        assert_eq!(parameters.stack_report, Some("stack.txt".to_string()));
+    }
+
+    #[test]
+    fn xref_out_option_set() {
+        let parameters = CompileParameters::parse(vec_of_strings!("foo")).unwrap();
+        assert_eq!(parameters.xref_out, None);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("foo", "--xref-out", "xref.json")).unwrap();
+        assert_eq!(parameters.xref_out, Some("xref.json".to_string()));
+    }
+
+    #[test]
+    fn signatures_option_set() {
+        let parameters = CompileParameters::parse(vec_of_strings!("foo")).unwrap();
+        assert_eq!(parameters.signatures, None);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("foo", "--signatures", "signatures.json"))
+                .unwrap();
+        assert_eq!(parameters.signatures, Some("signatures.json".to_string()));
+    }
+
+    #[test]
+    fn pou_sections_and_position_independent_options() {
+        let parameters = CompileParameters::parse(vec_of_strings!("foo")).unwrap();
+        assert!(!parameters.pou_sections);
+        assert!(!parameters.position_independent);
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "foo",
+            "--pou-sections",
+            "--position-independent"
+        ))
+        .unwrap();
+        assert!(parameters.pou_sections);
+        assert!(parameters.position_independent);
+    }
+
+    #[test]
+    fn header_option_set() {
+        let parameters = CompileParameters::parse(vec_of_strings!("foo")).unwrap();
+        assert_eq!(parameters.header_out, None);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("foo", "--header-out", "foo.h")).unwrap();
+        assert_eq!(parameters.header_out, Some("foo.h".to_string()));
+    }
 }
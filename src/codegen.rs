@@ -2,6 +2,7 @@
 
 /// module to generate llvm intermediate representation for a CompilationUnit
 use self::{
+    debug::DebugBuilder,
     generators::{
         data_type_generator,
         llvm::{GlobalValueExt, Llvm},
@@ -13,19 +14,61 @@ use self::{
 use crate::{
     diagnostics::Diagnostic,
     resolver::{AstAnnotations, StringLiterals},
+    signature::Signatures,
+    typesystem::{BOUNDS_FAULT_FN, DIVISION_FAULT_FN, RANGE_CHECK_TRAP_FN},
+    CodegenOptions, DivisionCheckMode, OptimizationLevel, OverflowMode, RangeCheckMode,
 };
 
 use super::ast::*;
 use super::index::*;
-use inkwell::module::Module;
-use inkwell::{context::Context, types::BasicType};
+use inkwell::memory_buffer::MemoryBuffer;
+use inkwell::module::{Linkage, Module};
+use inkwell::passes::{PassManager, PassManagerBuilder};
+use inkwell::targets::{TargetData, TargetTriple};
+use inkwell::{
+    context::Context,
+    support::LLVMString,
+    types::{BasicMetadataTypeEnum, BasicType},
+    values::{BasicValue, FunctionValue},
+    AddressSpace,
+};
+use std::collections::HashSet;
 
-mod generators;
+mod debug;
+pub(crate) mod generators;
 mod llvm_index;
 mod llvm_typesystem;
+pub(crate) mod stack_usage;
 #[cfg(test)]
 mod tests;
 
+/// the name of the constant holding the signature of the project, the signatures of the POUs are
+/// named like the POUs with this prefix (e.g. `__signature_foo`)
+pub(crate) const SIGNATURE_SYMBOL: &str = "__signature";
+
+/// the float functions called by the generated numeric functions and exponentiations with their
+/// number of parameters and their names for REAL and LREAL. LLVM intrinsics are used where LLVM
+/// offers one, the remaining functions are provided by the C math library (libm).
+const FLOAT_FUNCTIONS: &[(&str, usize, &str, &str)] = &[
+    ("EXPT", 2, "llvm.pow.f32", "llvm.pow.f64"),
+    ("ABS", 1, "llvm.fabs.f32", "llvm.fabs.f64"),
+    ("SQRT", 1, "llvm.sqrt.f32", "llvm.sqrt.f64"),
+    ("LN", 1, "llvm.log.f32", "llvm.log.f64"),
+    ("LOG", 1, "llvm.log10.f32", "llvm.log10.f64"),
+    ("EXP", 1, "llvm.exp.f32", "llvm.exp.f64"),
+    ("SIN", 1, "llvm.sin.f32", "llvm.sin.f64"),
+    ("COS", 1, "llvm.cos.f32", "llvm.cos.f64"),
+    ("TAN", 1, "tanf", "tan"),
+    ("ASIN", 1, "asinf", "asin"),
+    ("ACOS", 1, "acosf", "acos"),
+    ("ATAN", 1, "atanf", "atan"),
+    ("ATAN2", 2, "atan2f", "atan2"),
+];
+
+/// the sizes of the integers whose arithmetic calls the overflow intrinsics, the arithmetic of
+/// other integers wraps around
+const OVERFLOW_INT_SIZES: &[u32] = &[8, 16, 32, 64];
+
 /// the codegen struct carries all dependencies required to generate
 /// the IR code for a compilation unit
 pub struct CodeGen<'ink> {
@@ -33,13 +76,370 @@ pub struct CodeGen<'ink> {
     pub context: &'ink Context,
     /// the module represents a llvm compilation unit
     pub module: Module<'ink>,
+    /// the options controlling the generated code
+    options: CodegenOptions,
+    /// the builder of the debug information, if it is generated
+    debug: Option<DebugBuilder<'ink>>,
 }
 
 impl<'ink> CodeGen<'ink> {
     /// constructs a new code-generator that generates CompilationUnits into a module with the given module_name
     pub fn new(context: &'ink Context, module_name: &str) -> CodeGen<'ink> {
         let module = context.create_module(module_name);
-        CodeGen { context, module }
+        CodeGen {
+            context,
+            module,
+            options: CodegenOptions::default(),
+            debug: None,
+        }
+    }
+
+    /// overrides the default options controlling the generated code
+    pub fn with_options(mut self, options: CodegenOptions) -> CodeGen<'ink> {
+        if let Some(triple) = &options.triple {
+            self.module.set_triple(&TargetTriple::create(triple));
+        }
+        if let Some(data_layout) = &options.data_layout {
+            self.module
+                .set_data_layout(&TargetData::create(data_layout).get_data_layout());
+        }
+        self.debug = options
+            .debug
+            .then(|| DebugBuilder::new(self.context, &self.module));
+        self.options = options;
+        self
+    }
+
+    /// returns the optimization level the generated code is optimized with
+    pub fn get_optimization_level(&self) -> OptimizationLevel {
+        self.options.optimization
+    }
+
+    /// returns true if the objects are generated as position-independent code
+    pub fn is_position_independent(&self) -> bool {
+        self.options.position_independent
+    }
+
+    /// removes the declarations of the float functions and overflow intrinsics that are not called by any generated
+    /// code, this must be called after all units were generated
+    pub fn remove_unused_intrinsics(&self) {
+        let overflow_intrinsics = get_overflow_intrinsics(self.options.overflow);
+        let unused = FLOAT_FUNCTIONS
+            .iter()
+            .flat_map(|(_, _, real_name, lreal_name)| [*real_name, *lreal_name])
+            .chain(overflow_intrinsics.iter().map(|(name, _)| name.as_str()))
+            .filter_map(|name| self.module.get_function(name))
+            //only declarations are removed, an implemented POU with the same name is kept
+            .filter(|it| it.count_basic_blocks() == 0)
+            .filter(|it| {
+                it.as_global_value()
+                    .as_pointer_value()
+                    .get_first_use()
+                    .is_none()
+            })
+            .collect::<Vec<_>>();
+        for function in unused {
+            //the function has no uses, so no dangling references remain
+            unsafe { function.delete() };
+        }
+    }
+
+    /// returns the report of the stack used by the generated functions (see `stack_usage`), this
+    /// must be called after all units were generated
+    pub fn generate_stack_report(&self) -> String {
+        stack_usage::generate_stack_report(&stack_usage::collect_stack_usage(&self.module))
+    }
+
+    /// generates a constant holding the signature of every given POU (e.g. `__signature_foo`)
+    pub fn generate_pou_signatures(&self, signatures: &[(String, u32)]) {
+        let signature_type = self.context.i32_type();
+        for (pou, signature) in signatures {
+            let name = format!("{}_{}", SIGNATURE_SYMBOL, pou);
+            let global = self.module.add_global(signature_type, None, &name);
+            global.set_initializer(&signature_type.const_int(*signature as u64, false));
+            global.set_constant(true);
+        }
+    }
+
+    /// collects the signatures of the POUs generated into the module (see
+    /// `generate_pou_signatures`) and generates a constant holding the signature of the project
+    /// (`__signature`). This must be called after the units were linked and their symbols
+    /// prefixed
+    pub fn generate_project_signature(&self) -> Signatures {
+        let project_symbol =
+            get_symbol_name(self.options.symbol_prefix.as_deref(), SIGNATURE_SYMBOL);
+        let pou_prefix = format!("{}_", project_symbol);
+        let pous = self
+            .module
+            .get_globals()
+            .filter_map(|it| {
+                let name = it.get_name().to_string_lossy();
+                let pou = name.strip_prefix(&pou_prefix)?.to_string();
+                let signature = it
+                    .get_initializer()?
+                    .into_int_value()
+                    .get_zero_extended_constant()?;
+                Some((pou, signature as u32))
+            })
+            .collect();
+        let signatures = Signatures::new(pous);
+
+        let signature_type = self.context.i32_type();
+        let global = self
+            .module
+            .add_global(signature_type, None, &project_symbol);
+        global.set_initializer(&signature_type.const_int(signatures.project as u64, false));
+        global.set_constant(true);
+        signatures
+    }
+
+    /// runs the LLVM optimization passes of the configured optimization level over the module,
+    /// this must be called after all units were generated
+    pub fn optimize(&self) {
+        let level = self.options.optimization;
+        if level == OptimizationLevel::None {
+            return;
+        }
+        let pass_manager_builder = PassManagerBuilder::create();
+        pass_manager_builder.set_optimization_level(level.into());
+        //inline calls like clang does for -O2 and -O3
+        match level {
+            OptimizationLevel::Default => pass_manager_builder.set_inliner_with_threshold(225),
+            OptimizationLevel::Aggressive => pass_manager_builder.set_inliner_with_threshold(275),
+            _ => {}
+        }
+
+        let function_passes = PassManager::create(&self.module);
+        pass_manager_builder.populate_function_pass_manager(&function_passes);
+        function_passes.initialize();
+        for function in self.module.get_functions() {
+            function_passes.run_on(&function);
+        }
+        function_passes.finalize();
+
+        let module_passes = PassManager::create(());
+        pass_manager_builder.populate_module_pass_manager(&module_passes);
+        module_passes.run_on(&self.module);
+    }
+
+    /// runs the link-time optimization passes over the module linked from the modules of
+    /// several units
+    pub fn optimize_lto(&self) {
+        let pass_manager_builder = PassManagerBuilder::create();
+        pass_manager_builder.set_optimization_level(self.options.optimization.into());
+        let lto_passes = PassManager::create(());
+        //the POUs stay visible to the runtime calling them
+        pass_manager_builder.populate_lto_pass_manager(&lto_passes, false, true);
+        lto_passes.run_on(&self.module);
+    }
+
+    /// returns the module as bitcode, so a module generated with another context (e.g. on
+    /// another thread) can be linked into a module of this context (see `link_bitcode`)
+    pub fn to_bitcode(&self) -> Vec<u8> {
+        self.module.write_bitcode_to_memory().as_slice().to_vec()
+    }
+
+    /// links the module of another unit, given as bitcode (see `to_bitcode`), into this module
+    pub fn link_bitcode(&self, bitcode: &[u8]) -> Result<(), Diagnostic> {
+        let buffer = MemoryBuffer::create_from_memory_range_copy(bitcode, "unit");
+        let to_diagnostic = |err: LLVMString| {
+            Diagnostic::codegen_error(
+                &format!("Cannot link the modules of the units: {}", err),
+                SourceRange::undefined(),
+            )
+        };
+        let other =
+            Module::parse_bitcode_from_buffer(&buffer, self.context).map_err(to_diagnostic)?;
+        self.module.link_in_module(other).map_err(to_diagnostic)
+    }
+
+    /// makes the constants every unit's module defines for itself (string literals and the
+    /// initial values of POU members) private to the module, so the modules of several units
+    /// can be linked without duplicate symbols
+    pub fn make_unit_constants_private(&self, global_index: &Index) {
+        let member_initializers = global_index
+            .get_implementations()
+            .values()
+            .flat_map(|it| global_index.get_container_members(it.get_type_name()))
+            .map(|it| get_initializer_name(it.get_qualified_name()))
+            .filter_map(|it| self.module.get_global(&it));
+        let literals = self.module.get_globals().filter(|it| {
+            let name = it.get_name().to_string_lossy();
+            name.starts_with("utf08_literal_") || name.starts_with("utf16_literal_")
+        });
+        for global in member_initializers.chain(literals) {
+            if global.get_initializer().is_some() {
+                global.set_linkage(Linkage::Private);
+            }
+        }
+    }
+
+    /// prefixes the symbols defined by the module with the configured symbol prefix (see
+    /// `get_symbol_name`). Declarations of external POUs and variables, private symbols, the
+    /// symbols of hardware addresses and the generated `main` keep their names.
+    /// This must be called after all units were generated and linked
+    pub fn prefix_symbols(&self, global_index: &Index) {
+        let prefix = match &self.options.symbol_prefix {
+            Some(prefix) => prefix.as_str(),
+            None => return,
+        };
+        //the I/O symbols are provided by the runtime for all libraries
+        let hardware_symbols = global_index
+            .get_globals()
+            .values()
+            .filter_map(|it| it.get_hardware_binding())
+            .filter_map(|it| it.get_symbol_name(global_index.get_const_expressions()))
+            .collect::<HashSet<_>>();
+        let functions = self
+            .module
+            .get_functions()
+            .filter(|it| it.count_basic_blocks() > 0)
+            .filter(|it| self.options.main_program.is_none() || it.get_name().to_bytes() != b"main")
+            .map(|it| it.as_global_value());
+        let globals = self
+            .module
+            .get_globals()
+            .filter(|it| it.get_initializer().is_some())
+            .filter(|it| !hardware_symbols.contains(it.get_name().to_string_lossy().as_ref()));
+        for symbol in functions.chain(globals) {
+            //weak definitions (e.g. the default bounds fault handler) keep their name, so they
+            //are replaced by the definitions of other objects
+            if matches!(
+                symbol.get_linkage(),
+                Linkage::Private | Linkage::Internal | Linkage::WeakAny
+            ) {
+                continue;
+            }
+            let name = get_symbol_name(Some(prefix), &symbol.get_name().to_string_lossy());
+            symbol.as_pointer_value().set_name(&name);
+        }
+    }
+
+    /// places the code of every POU into its own section (`.text.<pou>`) and the instance of a
+    /// program and the initial values of a POU's members into `.data.<pou>` (`.rodata.<pou>` if
+    /// they are constant), so a runtime can replace single POUs. Symbols with a configured
+    /// section (e.g. retained variables) keep their section. This must be called before the
+    /// symbols are prefixed
+    pub fn assign_pou_sections(&self, global_index: &Index) {
+        if !self.options.pou_sections {
+            return;
+        }
+        let implementations = global_index
+            .get_implementations()
+            .values()
+            .filter(|it| it.get_linkage() == LinkageType::Internal);
+        for implementation in implementations {
+            let name = implementation.get_call_name();
+            let mut symbols = vec![];
+            if let Some(function) = self
+                .module
+                .get_function(name)
+                .filter(|it| it.count_basic_blocks() > 0)
+            {
+                symbols.push((".text", function.as_global_value()));
+            }
+            //actions share the instance and the initial values of their program
+            if name.eq_ignore_ascii_case(implementation.get_type_name()) {
+                let instance = global_index
+                    .find_global_variable(name)
+                    .filter(|_| {
+                        implementation.get_implementation_type() == &ImplementationType::Program
+                    })
+                    .and_then(|it| self.module.get_global(it.get_qualified_name()))
+                    .filter(|it| it.get_initializer().is_some());
+                symbols.extend(instance.map(|it| (".data", it)));
+                let initial_values = global_index
+                    .get_container_members(name)
+                    .into_iter()
+                    .map(|it| get_initializer_name(it.get_qualified_name()))
+                    .chain(std::iter::once(get_initializer_name(name)))
+                    .filter_map(|it| self.module.get_global(&it))
+                    .filter(|it| it.get_initializer().is_some());
+                symbols.extend(
+                    initial_values
+                        .map(|it| (if it.is_constant() { ".rodata" } else { ".data" }, it)),
+                );
+            }
+            for (kind, symbol) in symbols {
+                if symbol.get_section().is_none() {
+                    symbol.set_section(&format!("{}.{}", kind, name));
+                }
+            }
+        }
+    }
+
+    /// declares the overflow intrinsic with the given name for integers of the given size (see
+    /// `get_overflow_intrinsic_name`), the `with.overflow` intrinsics return the result and
+    /// whether it overflowed, the fixed-point multiplications take the scale of their operands as
+    /// third parameter
+    fn generate_overflow_intrinsic(&self, name: &str, size: u32) -> FunctionValue<'ink> {
+        let int_type = self.context.custom_width_int_type(size);
+        let parameters = [int_type.into(), int_type.into()];
+        let function_type = if name.contains(".with.overflow.") {
+            self.context
+                .struct_type(&[int_type.into(), self.context.bool_type().into()], false)
+                .fn_type(&parameters, false)
+        } else if name.contains(".fix.sat.") {
+            int_type.fn_type(
+                &[
+                    int_type.into(),
+                    int_type.into(),
+                    self.context.i32_type().into(),
+                ],
+                false,
+            )
+        } else {
+            int_type.fn_type(&parameters, false)
+        };
+        self.module.add_function(name, function_type, None)
+    }
+
+    /// makes the fault handler with the given name called by compiler-generated checks known to
+    /// the given index. A handler declared by the sources is called like a C function, so it must
+    /// be `{external}`. Without a declared handler, a weak default with the given parameters
+    /// aborts the program.
+    fn declare_fault_handler(
+        &self,
+        name: &str,
+        parameters: &[BasicMetadataTypeEnum<'ink>],
+        global_index: &Index,
+        index: &mut LlvmTypedIndex<'ink>,
+    ) -> Result<(), Diagnostic> {
+        match global_index.find_implementation(name) {
+            Some(implementation) if implementation.is_foreign() => Ok(()),
+            Some(_) => Err(Diagnostic::codegen_error(
+                &format!("The fault handler {} must be declared {{external}}", name),
+                SourceRange::undefined(),
+            )),
+            None => {
+                let handler = self.module.add_function(
+                    name,
+                    self.context.void_type().fn_type(parameters, false),
+                    Some(Linkage::WeakAny),
+                );
+                let trap = index
+                    .find_associated_implementation(RANGE_CHECK_TRAP_FN)
+                    .ok_or_else(|| {
+                        Diagnostic::codegen_error(
+                            "Cannot find the trap for the default fault handler",
+                            SourceRange::undefined(),
+                        )
+                    })?;
+                let builder = self.context.create_builder();
+                builder.position_at_end(self.context.append_basic_block(handler, "entry"));
+                builder.build_call(trap, &[], "");
+                builder.build_unreachable();
+                index.associate_implementation(name, handler)
+            }
+        }
+    }
+
+    /// resolves the generated debug information, this must be called after all units were
+    /// generated and before the module is emitted
+    pub fn finalize_debug_info(&self) {
+        if let Some(debug) = &self.debug {
+            debug.finalize();
+        }
     }
 
     pub fn generate_llvm_index(
@@ -51,8 +451,12 @@ impl<'ink> CodeGen<'ink> {
         let llvm = Llvm::new(self.context, self.context.create_builder());
         let mut index = LlvmTypedIndex::default();
         //Generate types index, and any global variables associated with them.
-        let llvm_type_index =
-            data_type_generator::generate_data_types(&llvm, global_index, annotations)?;
+        let llvm_type_index = data_type_generator::generate_data_types(
+            &llvm,
+            &self.module,
+            global_index,
+            annotations,
+        )?;
         index.merge(llvm_type_index);
 
         //Generate global variables
@@ -62,6 +466,7 @@ impl<'ink> CodeGen<'ink> {
             global_index,
             annotations,
             &index,
+            &self.options.sections,
         )?;
         index.merge(llvm_gv_index);
 
@@ -76,6 +481,71 @@ impl<'ink> CodeGen<'ink> {
         )?;
         let llvm = Llvm::new(self.context, self.context.create_builder());
         index.merge(llvm_impl_index);
+
+        //Generate the vtables of all polymorphic classes and implemented interfaces
+        let llvm_vtable_index =
+            pou_generator::generate_vtables(&self.module, llvm, global_index, annotations, &index)?;
+        let llvm = Llvm::new(self.context, self.context.create_builder());
+        index.merge(llvm_vtable_index);
+
+        //compiler-generated range, division, bounds and overflow checks (and the default fault
+        //handlers) abort the program using the trap intrinsic
+        if self.options.range_checks == Some(RangeCheckMode::Trap)
+            || matches!(
+                self.options.division_checks,
+                Some(DivisionCheckMode::Trap | DivisionCheckMode::Fault)
+            )
+            || self.options.bounds_checks
+            || self.options.overflow == OverflowMode::Trap
+        {
+            let trap = self.module.add_function(
+                RANGE_CHECK_TRAP_FN,
+                self.context.void_type().fn_type(&[], false),
+                None,
+            );
+            index.associate_implementation(RANGE_CHECK_TRAP_FN, trap)?;
+        }
+        //the bounds and division checks call the handlers declared by the sources, or a generated
+        //default
+        let i64_type = self.context.i64_type();
+        let location_type = self.context.i8_type().ptr_type(AddressSpace::Generic);
+        if self.options.bounds_checks {
+            let parameters = [
+                i64_type.into(),
+                i64_type.into(),
+                i64_type.into(),
+                location_type.into(),
+            ];
+            self.declare_fault_handler(BOUNDS_FAULT_FN, &parameters, global_index, &mut index)?;
+        }
+        if self.options.division_checks == Some(DivisionCheckMode::Fault) {
+            let parameters = [location_type.into()];
+            self.declare_fault_handler(DIVISION_FAULT_FN, &parameters, global_index, &mut index)?;
+        }
+        //integer arithmetic that traps or saturates is generated as calls to overflow intrinsics,
+        //declarations that remain unused are removed by `remove_unused_intrinsics`
+        for (name, size) in get_overflow_intrinsics(self.options.overflow) {
+            let function = self.generate_overflow_intrinsic(&name, size);
+            index.associate_implementation(&name, function)?;
+        }
+        //numeric functions and exponentiations of reals are generated as calls to float functions,
+        //declarations that remain unused are removed by `remove_unused_intrinsics`
+        for (_, parameters, real_name, lreal_name) in FLOAT_FUNCTIONS {
+            for (size, name) in [(32, real_name), (64, lreal_name)] {
+                //a POU with the same name takes precedence over the generated function
+                if index.find_associated_implementation(name).is_some() {
+                    continue;
+                }
+                let float_type = llvm_typesystem::get_llvm_float_type(self.context, size, name)?;
+                let parameter_types = vec![BasicMetadataTypeEnum::from(float_type); *parameters];
+                let function = self.module.add_function(
+                    name,
+                    float_type.fn_type(&parameter_types, false),
+                    None,
+                );
+                index.associate_implementation(name, function)?;
+            }
+        }
         let llvm_values_index = pou_generator::generate_global_constants_for_pou_members(
             &self.module,
             &llvm,
@@ -133,26 +603,205 @@ impl<'ink> CodeGen<'ink> {
         unit: &CompilationUnit,
         annotations: &AstAnnotations,
         global_index: &Index,
-        llvm_index: &LlvmTypedIndex,
+        llvm_index: &LlvmTypedIndex<'ink>,
     ) -> Result<String, Diagnostic> {
+        if let Some(debug) = &self.debug {
+            debug.set_unit(unit);
+            self.declare_global_variables(debug, unit, global_index, llvm_index);
+        }
+
         //generate all pous
         let llvm = Llvm::new(self.context, self.context.create_builder());
-        let pou_generator = PouGenerator::new(llvm, global_index, annotations, llvm_index);
+        let pou_generator = PouGenerator::new(
+            llvm,
+            global_index,
+            annotations,
+            llvm_index,
+            &self.options,
+            self.debug.as_ref(),
+        );
 
         //Generate the POU stubs in the first go to make sure they can be referenced.
         for implementation in &unit.implementations {
             //Don't generate external or generic functions
-            if implementation.linkage != LinkageType::External
+            if implementation.linkage == LinkageType::Internal
                 && !global_index
                     .get_type_information_or_void(&implementation.type_name)
                     .is_generic()
             {
-                pou_generator.generate_implementation(implementation)?;
+                pou_generator.generate_implementation(implementation, unit)?;
             }
         }
 
         Ok(self.module.print_to_string().to_string())
     }
+
+    /// generates a `main` function calling the configured PROGRAM (or program instance)
+    /// cyclically, if any
+    pub fn generate_main(
+        &self,
+        global_index: &Index,
+        llvm_index: &LlvmTypedIndex<'ink>,
+    ) -> Result<(), Diagnostic> {
+        let program = match &self.options.main_program {
+            Some(program) => program,
+            None => return Ok(()),
+        };
+        //a program's instance is a global variable named like the program
+        let instance = global_index.find_global_variable(program).filter(|it| {
+            global_index
+                .find_implementation(it.get_type_name())
+                .map(|it| it.get_implementation_type() == &ImplementationType::Program)
+                .unwrap_or(false)
+        });
+        let (instance, implementation) = instance
+            .and_then(|it| {
+                Some((
+                    llvm_index.find_global_value(program)?,
+                    llvm_index.find_associated_implementation(it.get_type_name())?,
+                ))
+            })
+            .ok_or_else(|| {
+                Diagnostic::param_error(&format!(
+                    "Cannot generate a main calling '{}', it is not a PROGRAM",
+                    program
+                ))
+            })?;
+
+        if self.module.get_function("main").is_some() {
+            return Err(Diagnostic::param_error(
+                "Cannot generate a main, a POU named 'main' already exists",
+            ));
+        }
+        let main =
+            self.module
+                .add_function("main", self.context.i32_type().fn_type(&[], false), None);
+        let builder = self.context.create_builder();
+        let entry = self.context.append_basic_block(main, "entry");
+        let cycle = self.context.append_basic_block(main, "cycle");
+        builder.position_at_end(entry);
+        builder.build_unconditional_branch(cycle);
+        builder.position_at_end(cycle);
+        builder.build_call(implementation, &[instance.as_pointer_value().into()], "");
+        builder.build_unconditional_branch(cycle);
+        Ok(())
+    }
+
+    /// describes the global variables and program instances declared in the given unit
+    fn declare_global_variables(
+        &self,
+        debug: &DebugBuilder<'ink>,
+        unit: &CompilationUnit,
+        global_index: &Index,
+        llvm_index: &LlvmTypedIndex<'ink>,
+    ) {
+        let globals = unit
+            .global_vars
+            .iter()
+            .flat_map(|it| it.variables.iter())
+            .map(|it| it.name.as_str());
+        let programs = unit
+            .units
+            .iter()
+            .filter(|it| it.pou_type == PouType::Program)
+            .map(|it| it.name.as_str());
+        let program_instances = unit
+            .configurations
+            .iter()
+            .flat_map(|it| it.resources.iter())
+            .flat_map(|it| it.programs.iter())
+            .map(|it| it.name.as_str());
+        for name in globals.chain(programs).chain(program_instances) {
+            if let (Some(variable), Some(global)) = (
+                global_index.find_global_variable(name),
+                llvm_index.find_global_value(name),
+            ) {
+                debug.declare_global_variable(variable, global, global_index, llvm_index);
+            }
+        }
+    }
+}
+
+/// returns the symbol of the POU, program instance or global variable with the given name that
+/// is defined by the compiled sources:
+/// - POUs are named like their qualified name (e.g. `Lib.foo`), methods and actions are
+///   qualified by their POU (e.g. `Lib.Shape.grow`)
+/// - program instances are named like the program with an `_instance` suffix (e.g. `main_instance`)
+/// - global variables are named like their qualified name (e.g. `Lib.counter`)
+/// - the initial values of function blocks and classes are named `__<pou>__init`, their vtables
+///   `__vtable_<class>`
+/// - the signatures of the POUs are named `__signature_<pou>`, the signature of the project
+///   `__signature` (see `--signatures`)
+///
+/// every symbol starts with the configured `--symbol-prefix` (e.g. `lib1_Lib.foo`)
+pub(crate) fn get_symbol_name(prefix: Option<&str>, name: &str) -> String {
+    format!("{}{}", prefix.unwrap_or_default(), name)
+}
+
+/// returns the name of the float function called by the given numeric function (or `EXPT`) for
+/// a float of the given size (e.g. `llvm.sqrt.f64` for `SQRT` of an `LREAL`)
+pub(crate) fn get_float_function_name(function_name: &str, size: u32) -> Option<&'static str> {
+    FLOAT_FUNCTIONS
+        .iter()
+        .find(|(name, ..)| name.eq_ignore_ascii_case(function_name))
+        .map(|(_, _, real_name, lreal_name)| if size > 32 { *lreal_name } else { *real_name })
+}
+
+/// returns the name of the LLVM intrinsic that adds, subtracts or multiplies (signed or unsigned)
+/// integers of the given size with the given overflow mode (e.g. `llvm.sadd.with.overflow.i32`
+/// for `Trap` or `llvm.usub.sat.i16` for `Saturate`), None for wrapping arithmetic, other
+/// operators or sizes without intrinsics
+pub(crate) fn get_overflow_intrinsic_name(
+    operator: &Operator,
+    signed: bool,
+    size: u32,
+    overflow: OverflowMode,
+) -> Option<String> {
+    let operation = match operator {
+        Operator::Plus => "add",
+        Operator::Minus => "sub",
+        Operator::Multiplication => "mul",
+        _ => return None,
+    };
+    if !OVERFLOW_INT_SIZES.contains(&size) {
+        return None;
+    }
+    let sign = if signed { "s" } else { "u" };
+    match overflow {
+        OverflowMode::Wrap => None,
+        OverflowMode::Trap => Some(format!(
+            "llvm.{}{}.with.overflow.i{}",
+            sign, operation, size
+        )),
+        //LLVM has no saturating multiplication, a fixed-point multiplication with scale 0 is one
+        OverflowMode::Saturate if operator == &Operator::Multiplication => {
+            Some(format!("llvm.{}mul.fix.sat.i{}", sign, size))
+        }
+        OverflowMode::Saturate => Some(format!("llvm.{}{}.sat.i{}", sign, operation, size)),
+    }
+}
+
+/// returns the names of all overflow intrinsics called by arithmetic with the given overflow mode
+/// with the size of their integers
+fn get_overflow_intrinsics(overflow: OverflowMode) -> Vec<(String, u32)> {
+    let operators = [Operator::Plus, Operator::Minus, Operator::Multiplication];
+    OVERFLOW_INT_SIZES
+        .iter()
+        .flat_map(|size| [true, false].map(|signed| (*size, signed)))
+        .flat_map(|(size, signed)| {
+            operators
+                .iter()
+                .filter_map(move |it| get_overflow_intrinsic_name(it, signed, size, overflow))
+                .map(move |name| (name, size))
+        })
+        .collect()
+}
+
+/// returns the layout of the target the given module is generated for, the layout of the host
+/// if the module has no data layout
+pub(crate) fn get_target_data(module: &Module) -> TargetData {
+    let data_layout = module.get_data_layout();
+    TargetData::create(data_layout.as_str().to_str().unwrap_or_default())
 }
 
 #[cfg(test)]
@@ -0,0 +1,446 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+
+//! Generates the DWARF debug information (`-g`) describing the generated POUs, their variables
+//! and the source locations of their statements, so a debugger can step through the ST source
+//! and inspect variables by their names.
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    path::Path,
+};
+
+use inkwell::{
+    builder::Builder,
+    context::Context,
+    debug_info::{
+        AsDIScope, DICompileUnit, DIFile, DIFlags, DIFlagsConstants, DIType, DWARFEmissionKind,
+        DWARFSourceLanguage, DebugInfoBuilder,
+    },
+    module::{FlagBehavior, Module},
+    targets::TargetData,
+    types::BasicTypeEnum,
+    values::{FunctionValue, GlobalValue, PointerValue},
+    AddressSpace,
+};
+
+use crate::{
+    ast::{CompilationUnit, Implementation, NewLines, SourceRange},
+    index::{Index, VariableIndexEntry},
+    typesystem::{DataTypeInformation, StringEncoding, StructSource},
+};
+
+use super::llvm_index::LlvmTypedIndex;
+
+/// the version of the debug information metadata generated by LLVM
+const DEBUG_INFO_VERSION: u64 = 3;
+
+// the DWARF encodings of basic types
+const DW_ATE_BOOLEAN: u32 = 0x02;
+const DW_ATE_FLOAT: u32 = 0x04;
+const DW_ATE_SIGNED: u32 = 0x05;
+const DW_ATE_UNSIGNED: u32 = 0x07;
+const DW_ATE_UNSIGNED_CHAR: u32 = 0x08;
+const DW_ATE_UTF: u32 = 0x10;
+
+pub struct DebugBuilder<'ink> {
+    context: &'ink Context,
+    debug_info: DebugInfoBuilder<'ink>,
+    compile_unit: DICompileUnit<'ink>,
+    /// the file of the unit currently generated and the line breaks of its source
+    file: RefCell<(DIFile<'ink>, NewLines)>,
+    /// the debug types created so far, by the lowercase name of their datatype
+    types: RefCell<HashMap<String, DIType<'ink>>>,
+    /// the datatypes whose debug type is currently created, used to break cyclic references
+    pending_types: RefCell<HashSet<String>>,
+    target_data: TargetData,
+}
+
+impl<'ink> DebugBuilder<'ink> {
+    pub fn new(context: &'ink Context, module: &Module<'ink>) -> DebugBuilder<'ink> {
+        module.add_basic_value_flag(
+            "Debug Info Version",
+            FlagBehavior::Warning,
+            context.i32_type().const_int(DEBUG_INFO_VERSION, false),
+        );
+        let module_name = module.get_name().to_str().unwrap_or_default();
+        let (debug_info, compile_unit) = module.create_debug_info_builder(
+            true,
+            DWARFSourceLanguage::C,
+            module_name,
+            "",
+            "RuSTy Structured text Compiler",
+            false,
+            "",
+            0,
+            "",
+            DWARFEmissionKind::Full,
+            0,
+            false,
+            false,
+            "",
+            "",
+        );
+        let file = compile_unit.get_file();
+        DebugBuilder {
+            context,
+            debug_info,
+            compile_unit,
+            file: RefCell::new((file, NewLines::default())),
+            types: RefCell::default(),
+            pending_types: RefCell::default(),
+            target_data: super::get_target_data(module),
+        }
+    }
+
+    /// sets the unit whose POUs and global variables are described next
+    pub fn set_unit(&self, unit: &CompilationUnit) {
+        let path = Path::new(&unit.file_name);
+        let file = self.debug_info.create_file(
+            path.file_name()
+                .and_then(|it| it.to_str())
+                .unwrap_or_default(),
+            path.parent().and_then(|it| it.to_str()).unwrap_or_default(),
+        );
+        self.file.replace((file, unit.new_lines.clone()));
+    }
+
+    /// resolves all debug information, this must be called before the module is emitted
+    pub fn finalize(&self) {
+        self.debug_info.finalize();
+    }
+
+    /// returns the current file with the line and column of the given location
+    fn get_position(&self, location: &SourceRange) -> (DIFile<'ink>, u32, u32) {
+        let (file, new_lines) = &*self.file.borrow();
+        let (line, column) = location.get_line_and_column(new_lines);
+        (*file, line as u32, column as u32)
+    }
+
+    /// describes the given function generated for the given implementation
+    pub fn create_function(&self, function: FunctionValue<'ink>, implementation: &Implementation) {
+        let (file, line, _) = self.get_position(&implementation.location);
+        let subroutine_type =
+            self.debug_info
+                .create_subroutine_type(file, None, &[], DIFlags::ZERO);
+        let subprogram = self.debug_info.create_function(
+            file.as_debug_info_scope(),
+            &implementation.name,
+            function.get_name().to_str().ok(),
+            file,
+            line,
+            subroutine_type,
+            false,
+            true,
+            line,
+            DIFlags::PUBLIC,
+            false,
+        );
+        function.set_subprogram(subprogram);
+    }
+
+    /// sets the location of the instructions generated next by the given builder
+    pub fn set_location(
+        &self,
+        builder: &Builder<'ink>,
+        function: FunctionValue<'ink>,
+        location: &SourceRange,
+    ) {
+        if let Some(subprogram) = function.get_subprogram() {
+            let (_, line, column) = self.get_position(location);
+            let location = self.debug_info.create_debug_location(
+                self.context,
+                line,
+                column,
+                subprogram.as_debug_info_scope(),
+                None,
+            );
+            builder.set_current_debug_location(self.context, location);
+        }
+    }
+
+    /// describes the given variable of the function stored at the given pointer
+    pub fn declare_variable(
+        &self,
+        builder: &Builder<'ink>,
+        function: FunctionValue<'ink>,
+        variable: &VariableIndexEntry,
+        storage: PointerValue<'ink>,
+        index: &Index,
+        llvm_index: &LlvmTypedIndex<'ink>,
+    ) {
+        let (subprogram, block) = match (function.get_subprogram(), builder.get_insert_block()) {
+            (Some(subprogram), Some(block)) => (subprogram, block),
+            _ => return,
+        };
+        if let Some(debug_type) = self.get_type(index, llvm_index, variable.get_type_name()) {
+            let (file, line, column) = self.get_position(&variable.source_location);
+            let scope = subprogram.as_debug_info_scope();
+            let variable = self.debug_info.create_auto_variable(
+                scope,
+                variable.get_name(),
+                file,
+                line,
+                debug_type,
+                true,
+                DIFlags::ZERO,
+                0,
+            );
+            let location =
+                self.debug_info
+                    .create_debug_location(self.context, line, column, scope, None);
+            self.debug_info
+                .insert_declare_at_end(storage, Some(variable), None, location, block);
+        }
+    }
+
+    /// describes the given global variable
+    pub fn declare_global_variable(
+        &self,
+        variable: &VariableIndexEntry,
+        global: GlobalValue<'ink>,
+        index: &Index,
+        llvm_index: &LlvmTypedIndex<'ink>,
+    ) {
+        if let Some(debug_type) = self.get_type(index, llvm_index, variable.get_type_name()) {
+            let (file, line, _) = self.get_position(&variable.source_location);
+            let expression = self.debug_info.create_global_variable_expression(
+                self.compile_unit.as_debug_info_scope(),
+                variable.get_qualified_name(),
+                global.get_name().to_str().unwrap_or_default(),
+                file,
+                line,
+                debug_type,
+                false,
+                None,
+                None,
+                0,
+            );
+            global.set_metadata(
+                expression.as_metadata_value(self.context),
+                self.context.get_kind_id("dbg"),
+            );
+        }
+    }
+
+    /// returns the debug type of the given datatype, `None` if it cannot be described
+    /// (e.g. generic types)
+    fn get_type(
+        &self,
+        index: &Index,
+        llvm_index: &LlvmTypedIndex<'ink>,
+        type_name: &str,
+    ) -> Option<DIType<'ink>> {
+        let key = type_name.to_lowercase();
+        if let Some(debug_type) = self.types.borrow().get(&key) {
+            return Some(*debug_type);
+        }
+        if !self.pending_types.borrow_mut().insert(key.clone()) {
+            return None;
+        }
+        let debug_type = self.create_type(index, llvm_index, type_name);
+        self.pending_types.borrow_mut().remove(&key);
+        if let Some(debug_type) = debug_type {
+            self.types.borrow_mut().insert(key, debug_type);
+        }
+        debug_type
+    }
+
+    fn create_type(
+        &self,
+        index: &Index,
+        llvm_index: &LlvmTypedIndex<'ink>,
+        type_name: &str,
+    ) -> Option<DIType<'ink>> {
+        let data_type = index.find_effective_type(type_name)?;
+        let name = data_type.get_name();
+        match data_type.get_type_information() {
+            DataTypeInformation::Integer {
+                signed,
+                size,
+                semantic_size,
+                ..
+            } => {
+                let encoding = if *semantic_size == Some(1) {
+                    DW_ATE_BOOLEAN
+                } else if *signed {
+                    DW_ATE_SIGNED
+                } else {
+                    DW_ATE_UNSIGNED
+                };
+                self.create_basic_type(name, *size as u64, encoding)
+            }
+            DataTypeInformation::Float { size, .. } => {
+                self.create_basic_type(name, *size as u64, DW_ATE_FLOAT)
+            }
+            DataTypeInformation::Enum {
+                referenced_type, ..
+            }
+            | DataTypeInformation::SubRange {
+                referenced_type, ..
+            }
+            | DataTypeInformation::Alias {
+                referenced_type, ..
+            } => self.get_type(index, llvm_index, referenced_type),
+            DataTypeInformation::String { size, encoding } => {
+                let (char_name, char_size, char_encoding) = match encoding {
+                    StringEncoding::Utf8 => ("CHAR", 8, DW_ATE_UNSIGNED_CHAR),
+                    StringEncoding::Utf16 => ("WCHAR", 16, DW_ATE_UTF),
+                };
+                let length = size.as_int_value(index).ok()?;
+                let char_type = self.create_basic_type(char_name, char_size, char_encoding)?;
+                Some(
+                    self.debug_info
+                        .create_array_type(
+                            char_type,
+                            length as u64 * char_size,
+                            char_size as u32,
+                            #[allow(clippy::single_range_in_vec_init)]
+                            &[0..length],
+                        )
+                        .as_type(),
+                )
+            }
+            DataTypeInformation::Array {
+                inner_type_name,
+                dimensions,
+                ..
+            } => {
+                let inner_type = self.get_type(index, llvm_index, inner_type_name)?;
+                let subscripts = dimensions
+                    .iter()
+                    .map(|it| it.get_range_inclusive(index).ok())
+                    .map(|it| it.map(|it| *it.start()..*it.end() + 1))
+                    .collect::<Option<Vec<_>>>()?;
+                let llvm_type = llvm_index.find_associated_type(name)?;
+                Some(
+                    self.debug_info
+                        .create_array_type(
+                            inner_type,
+                            self.target_data.get_bit_size(&llvm_type),
+                            self.get_alignment(&llvm_type),
+                            &subscripts,
+                        )
+                        .as_type(),
+                )
+            }
+            DataTypeInformation::Pointer {
+                inner_type_name, ..
+            } => {
+                //a pointer to a type that is currently described points to bytes instead
+                let pointee = self
+                    .get_type(index, llvm_index, inner_type_name)
+                    .or_else(|| self.create_basic_type("BYTE", 8, DW_ATE_UNSIGNED))?;
+                let llvm_type = llvm_index.find_associated_type(name)?;
+                Some(
+                    self.debug_info
+                        .create_pointer_type(
+                            name,
+                            pointee,
+                            self.target_data.get_bit_size(&llvm_type),
+                            self.get_alignment(&llvm_type),
+                            AddressSpace::Generic,
+                        )
+                        .as_type(),
+                )
+            }
+            DataTypeInformation::Struct { source, .. } => {
+                self.create_struct_type(index, llvm_index, name, source)
+            }
+            DataTypeInformation::Generic { .. } | DataTypeInformation::Void => None,
+        }
+    }
+
+    fn create_basic_type(&self, name: &str, size: u64, encoding: u32) -> Option<DIType<'ink>> {
+        self.debug_info
+            .create_basic_type(name, size, encoding, DIFlags::PUBLIC)
+            .ok()
+            .map(|it| it.as_type())
+    }
+
+    /// describes the struct of a STRUCT, UNION or POU with the members stored in it
+    fn create_struct_type(
+        &self,
+        index: &Index,
+        llvm_index: &LlvmTypedIndex<'ink>,
+        name: &str,
+        source: &StructSource,
+    ) -> Option<DIType<'ink>> {
+        let llvm_type = match source {
+            StructSource::Pou(..) => llvm_index.find_associated_pou_type(name),
+            _ => llvm_index.find_associated_type(name),
+        }?;
+        let (file, line, _) = self.get_position(&SourceRange::undefined());
+        let scope = self.compile_unit.as_debug_info_scope();
+        let members = index
+            .get_container_members(name)
+            .into_iter()
+            .filter(|it| !it.is_temp() && !it.is_return())
+            .enumerate()
+            .filter_map(|(position, member)| {
+                let member_type = self.get_type(index, llvm_index, member.get_type_name())?;
+                let member_llvm_type = llvm_index.find_associated_type(member.get_type_name())?;
+                //the members of a union share its storage
+                let offset = if *source == StructSource::Union {
+                    0
+                } else {
+                    self.target_data
+                        .offset_of_element(&llvm_type.into_struct_type(), position as u32)?
+                        * 8
+                };
+                let (_, member_line, _) = self.get_position(&member.source_location);
+                Some(
+                    self.debug_info
+                        .create_member_type(
+                            scope,
+                            member.get_name(),
+                            file,
+                            member_line,
+                            self.target_data.get_bit_size(&member_llvm_type),
+                            self.get_alignment(&member_llvm_type),
+                            offset,
+                            DIFlags::PUBLIC,
+                            member_type,
+                        )
+                        .as_type(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let size = self.target_data.get_bit_size(&llvm_type);
+        let alignment = self.get_alignment(&llvm_type);
+        let struct_type = if *source == StructSource::Union {
+            self.debug_info.create_union_type(
+                scope,
+                name,
+                file,
+                line,
+                size,
+                alignment,
+                DIFlags::PUBLIC,
+                &members,
+                0,
+                name,
+            )
+        } else {
+            self.debug_info.create_struct_type(
+                scope,
+                name,
+                file,
+                line,
+                size,
+                alignment,
+                DIFlags::PUBLIC,
+                None,
+                &members,
+                0,
+                None,
+                name,
+            )
+        };
+        Some(struct_type.as_type())
+    }
+
+    fn get_alignment(&self, llvm_type: &BasicTypeEnum<'ink>) -> u32 {
+        self.target_data.get_abi_alignment(llvm_type) * 8
+    }
+}
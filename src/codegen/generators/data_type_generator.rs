@@ -3,14 +3,15 @@ use std::convert::TryInto;
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 /// the data_type_generator generates user defined data-types
 /// - Structures
+/// - Unions
 /// - Enum types
 /// - SubRange types
 /// - Alias types
 /// - sized Strings
-use crate::ast::SourceRange;
+use crate::ast::{PouType, SourceRange};
 use crate::index::{Index, VariableIndexEntry, VariableType};
 use crate::resolver::AstAnnotations;
-use crate::typesystem::{Dimension, StringEncoding, StructSource};
+use crate::typesystem::{self, Dimension, StringEncoding, StructSource};
 use crate::Diagnostic;
 use crate::{ast::AstStatement, typesystem::DataTypeInformation};
 use crate::{
@@ -21,18 +22,24 @@ use crate::{
     typesystem::DataType,
 };
 use inkwell::{
+    module::{Linkage, Module},
+    targets::TargetData,
     types::{ArrayType, BasicType, BasicTypeEnum},
     values::{BasicValue, BasicValueEnum},
     AddressSpace,
 };
 
-use super::{expression_generator::ExpressionCodeGenerator, llvm::Llvm};
+use super::{
+    expression_generator::ExpressionCodeGenerator, llvm::Llvm, pou_generator::get_class_vtable_name,
+};
 
 pub struct DataTypeGenerator<'ink, 'b> {
     llvm: &'b Llvm<'ink>,
     index: &'b Index,
     annotations: &'b AstAnnotations,
     types_index: LlvmTypedIndex<'ink>,
+    /// the layout of the target the types are generated for
+    target_data: TargetData,
 }
 
 /// generates the llvm-type for the given data-type and registers it at the index
@@ -44,6 +51,7 @@ pub struct DataTypeGenerator<'ink, 'b> {
 /// - array type for sized Strings
 pub fn generate_data_types<'ink>(
     llvm: &Llvm<'ink>,
+    module: &Module<'ink>,
     index: &Index,
     annotations: &AstAnnotations,
 ) -> Result<LlvmTypedIndex<'ink>, Diagnostic> {
@@ -52,6 +60,7 @@ pub fn generate_data_types<'ink>(
         index,
         annotations,
         types_index: LlvmTypedIndex::default(),
+        target_data: crate::codegen::get_target_data(module),
     };
 
     let types = generator
@@ -104,8 +113,15 @@ pub fn generate_data_types<'ink>(
 
     // now since all types should be available in the llvm index, we can think about constructing and associating
     // initial values for the types
-    for (name, user_type) in &types {
+    for (_, user_type) in types.iter().chain(pou_types.iter()) {
         generator.expand_opaque_types(user_type)?;
+    }
+    // unions can only be laid out once the size of all of their members is known
+    generator.expand_union_types(&types)?;
+    // instances of polymorphic classes are initialized with the address of their vtable
+    generator.declare_vtables(module, &pou_types)?;
+
+    for (name, user_type) in &types {
         if let Some(init_value) = generator.generate_initial_value(user_type)? {
             generator
                 .types_index
@@ -113,7 +129,6 @@ pub fn generate_data_types<'ink>(
         }
     }
     for (name, user_type) in &pou_types {
-        generator.expand_opaque_types(user_type)?;
         if let Some(init_value) = generator.generate_initial_value(user_type)? {
             generator
                 .types_index
@@ -128,26 +143,159 @@ impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
     /// generates the members of an opaque struct and associates its initial values
     fn expand_opaque_types(&mut self, data_type: &DataType) -> Result<(), Diagnostic> {
         let information = data_type.get_type_information();
+        if information.is_union() {
+            //unions are expanded separately (see expand_union_types)
+            return Ok(());
+        }
         if let DataTypeInformation::Struct { source, .. } = information {
-            let members = self
+            //a struct containing itself cannot be given a body
+            if let Some(cycle) = self
+                .index
+                .find_cyclic_struct_definition(data_type.get_name())
+            {
+                return Err(Diagnostic::cyclic_type_definition(
+                    &cycle,
+                    SourceRange::undefined(),
+                ));
+            }
+            let mut members = self
                 .index
                 .get_container_members(data_type.get_name())
                 .into_iter()
                 .filter(|it| !it.is_temp() && !it.is_return())
                 .map(|m| self.types_index.get_associated_type(m.get_type_name()))
                 .collect::<Result<Vec<BasicTypeEnum>, Diagnostic>>()?;
+            // the topmost class of a hierarchy carries the vtable as an additional last member
+            if is_vtable_carrier(self.index, data_type) {
+                members.push(
+                    self.llvm
+                        .context
+                        .i8_type()
+                        .ptr_type(AddressSpace::Generic)
+                        .into(),
+                );
+            }
 
             let struct_type = match source {
                 StructSource::Pou(..) => self
                     .types_index
                     .get_associated_pou_type(data_type.get_name()),
-                StructSource::OriginalDeclaration => {
+                StructSource::OriginalDeclaration | StructSource::Union => {
                     self.types_index.get_associated_type(data_type.get_name())
                 }
             }
             .map(BasicTypeEnum::into_struct_type)?;
 
-            struct_type.set_body(members.as_slice(), false);
+            //a pack mode below the natural alignment removes the padding between the members
+            let packed = typesystem::is_packed_struct(data_type.get_name(), self.index);
+            struct_type.set_body(members.as_slice(), packed);
+        }
+        Ok(())
+    }
+
+    /// declares the vtables of all polymorphic classes and function blocks, their thunks
+    /// are generated once all implementations are known (see `generate_vtables`)
+    fn declare_vtables(
+        &mut self,
+        module: &Module<'ink>,
+        pou_types: &[(&str, &DataType)],
+    ) -> Result<(), Diagnostic> {
+        let byte_pointer = self.llvm.context.i8_type().ptr_type(AddressSpace::Generic);
+        for (name, _) in pou_types
+            .iter()
+            .filter(|(name, _)| self.index.is_polymorphic(name))
+        {
+            let methods = self.index.get_virtual_methods(name).len();
+            let vtable = module.add_global(
+                byte_pointer.array_type(methods as u32),
+                None,
+                &get_class_vtable_name(name),
+            );
+            vtable.set_constant(true);
+            vtable.set_linkage(Linkage::Private);
+            self.types_index
+                .associate_global(&get_class_vtable_name(name), vtable)?;
+        }
+        Ok(())
+    }
+
+    /// generates the bodies of all opaque union types.
+    /// A union is generated as a struct that starts with its member with the strictest alignment,
+    /// followed by enough padding-bytes to fit its largest member. Members are accessed by
+    /// casting the pointer to the union to a pointer of the member's type.
+    fn expand_union_types(&mut self, types: &[(&str, &DataType)]) -> Result<(), Diagnostic> {
+        let target_data = &self.target_data;
+        let mut pending = types
+            .iter()
+            .filter(|(_, it)| it.get_type_information().is_union())
+            .map(|(_, it)| *it)
+            .collect::<Vec<_>>();
+
+        while !pending.is_empty() {
+            let mut unresolved = vec![];
+            for data_type in pending.iter() {
+                let members = self
+                    .index
+                    .get_container_members(data_type.get_name())
+                    .into_iter()
+                    .map(|m| self.types_index.get_associated_type(m.get_type_name()))
+                    .collect::<Result<Vec<BasicTypeEnum>, Diagnostic>>()?;
+
+                //members of nested unions may not be laid out yet
+                if !members.iter().all(BasicTypeEnum::is_sized) {
+                    unresolved.push(*data_type);
+                    continue;
+                }
+
+                let size = members
+                    .iter()
+                    .map(|it| target_data.get_abi_size(it))
+                    .max()
+                    .unwrap_or_default();
+                //on equal alignment the first declared member is preferred
+                let mut body = members
+                    .iter()
+                    .rev()
+                    .max_by_key(|it| {
+                        (
+                            target_data.get_abi_alignment(*it),
+                            target_data.get_abi_size(*it),
+                        )
+                    })
+                    .map(|it| vec![*it])
+                    .unwrap_or_default();
+                let padding = size
+                    - body
+                        .iter()
+                        .map(|it| target_data.get_abi_size(it))
+                        .sum::<u64>();
+                if padding > 0 {
+                    body.push(
+                        self.llvm
+                            .context
+                            .i8_type()
+                            .array_type(padding as u32)
+                            .into(),
+                    );
+                }
+
+                self.types_index
+                    .get_associated_type(data_type.get_name())?
+                    .into_struct_type()
+                    .set_body(body.as_slice(), false);
+            }
+
+            if unresolved.len() == pending.len() {
+                //no progress, the remaining unions contain themselves
+                return Err(Diagnostic::codegen_error(
+                    &format!(
+                        "Cannot generate union type {} because its size cannot be determined",
+                        unresolved[0].get_name()
+                    ),
+                    SourceRange::undefined(),
+                ));
+            }
+            pending = unresolved;
         }
         Ok(())
     }
@@ -166,7 +314,7 @@ impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
                 StructSource::Pou(..) => self
                     .types_index
                     .get_associated_pou_type(data_type.get_name()),
-                StructSource::OriginalDeclaration => {
+                StructSource::OriginalDeclaration | StructSource::Union => {
                     self.types_index.get_associated_type(data_type.get_name())
                 }
             },
@@ -225,10 +373,21 @@ impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
             }
             | DataTypeInformation::Alias {
                 referenced_type, ..
-            } => self
-                .index
-                .get_effective_type(referenced_type)
-                .and_then(|data_type| self.create_type(name, data_type)),
+            } => {
+                //cyclic definitions cannot be resolved to a built-in type (reported by the validator)
+                if matches!(
+                    self.index.find_intrinsic_type(information),
+                    DataTypeInformation::SubRange { .. } | DataTypeInformation::Alias { .. }
+                ) {
+                    return Err(Diagnostic::unknown_type(
+                        referenced_type,
+                        SourceRange::undefined(),
+                    ));
+                }
+                self.index
+                    .get_effective_type(referenced_type)
+                    .and_then(|data_type| self.create_type(name, data_type))
+            }
             DataTypeInformation::Void => {
                 get_llvm_int_type(self.llvm.context, 32, "Void").map(Into::into)
             }
@@ -251,46 +410,18 @@ impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
     ) -> Result<Option<BasicValueEnum<'ink>>, Diagnostic> {
         let information = data_type.get_type_information();
         match information {
-            DataTypeInformation::Struct { source, .. } => {
-                let members = self.index.get_container_members(data_type.get_name());
-                let member_names_and_initializers = members
-                    .iter()
-                    .filter(|it| it.get_variable_type() != VariableType::Temp)
-                    .map(|it| {
-                        self.generate_initial_value_for_variable(it)
-                            .and_then(|v| match v {
-                                Some(v) => Ok((it.get_qualified_name(), v)),
-                                None => self
-                                    .types_index
-                                    .get_associated_type(it.get_type_name())
-                                    .map(get_default_for)
-                                    .map(|v| (it.get_qualified_name(), v)),
-                            })
-                    })
-                    .collect::<Result<Vec<(&str, BasicValueEnum)>, Diagnostic>>()?;
-
-                let mut member_values: Vec<BasicValueEnum> = Vec::new();
-                for (name, v) in &member_names_and_initializers {
-                    self.types_index.associate_initial_value(name, *v)?;
-                    member_values.push(*v);
-                }
-
-                let struct_type = match source {
-                    StructSource::Pou(..) => self
-                        .types_index
-                        .get_associated_pou_type(data_type.get_name()),
-                    StructSource::OriginalDeclaration => {
-                        self.types_index.get_associated_type(data_type.get_name())
-                    }
-                }?
-                .into_struct_type();
-
-                Ok(Some(
-                    struct_type
-                        .const_named_struct(&member_values)
-                        .as_basic_value_enum(),
-                ))
+            DataTypeInformation::Struct {
+                source: StructSource::Union,
+                ..
+            } => {
+                //the members of a union overlap, so it is initialized with zeros
+                Ok(Some(get_default_for(
+                    self.types_index.get_associated_type(data_type.get_name())?,
+                )))
             }
+            DataTypeInformation::Struct { source, .. } => Ok(Some(
+                self.generate_struct_initial_value(data_type, source, data_type.get_name())?,
+            )),
             DataTypeInformation::Array { .. } => self.generate_array_initializer(
                 data_type,
                 |stmt| matches!(stmt, AstStatement::LiteralArray { .. }),
@@ -312,6 +443,86 @@ impl<'ink, 'b> DataTypeGenerator<'ink, 'b> {
         }
     }
 
+    /// generates the initial value of the given struct. The instance of a polymorphic class
+    /// references the vtable of the given `vtable_owner` (the class of the whole instance)
+    fn generate_struct_initial_value(
+        &mut self,
+        data_type: &DataType,
+        source: &StructSource,
+        vtable_owner: &str,
+    ) -> Result<BasicValueEnum<'ink>, Diagnostic> {
+        let members = self.index.get_container_members(data_type.get_name());
+        let mut member_names_and_initializers = members
+            .iter()
+            .filter(|it| it.get_variable_type() != VariableType::Temp)
+            .map(|it| {
+                self.generate_initial_value_for_variable(it)
+                    .and_then(|v| match v {
+                        Some(v) => Ok((it.get_qualified_name(), v)),
+                        None => self
+                            .types_index
+                            .get_associated_type(it.get_type_name())
+                            .map(get_default_for)
+                            .map(|v| (it.get_qualified_name(), v)),
+                    })
+            })
+            .collect::<Result<Vec<(&str, BasicValueEnum)>, Diagnostic>>()?;
+
+        //the embedded instance of the super class references the vtable of the whole instance
+        if let Some((super_type, super_source)) = self
+            .index
+            .find_super_class(data_type.get_name())
+            .filter(|_| !self.index.has_cyclic_inheritance(data_type.get_name()))
+            .and_then(|it| self.index.find_effective_type(it))
+            .and_then(|it| match it.get_type_information() {
+                DataTypeInformation::Struct { source, .. } => Some((it, source)),
+                _ => None,
+            })
+        {
+            let base =
+                self.generate_struct_initial_value(super_type, super_source, vtable_owner)?;
+            if let Some((_, value)) = member_names_and_initializers.first_mut() {
+                *value = base;
+            }
+        }
+
+        let mut member_values: Vec<BasicValueEnum> = Vec::new();
+        for (name, v) in &member_names_and_initializers {
+            self.types_index.associate_initial_value(name, *v)?;
+            member_values.push(*v);
+        }
+        if is_vtable_carrier(self.index, data_type) {
+            let vtable = self
+                .types_index
+                .find_global_value(&get_class_vtable_name(vtable_owner))
+                .map(|it| {
+                    it.as_pointer_value()
+                        .const_cast(self.llvm.context.i8_type().ptr_type(AddressSpace::Generic))
+                })
+                .ok_or_else(|| {
+                    Diagnostic::codegen_error(
+                        &format!("Cannot find the vtable of {}", vtable_owner),
+                        SourceRange::undefined(),
+                    )
+                })?;
+            member_values.push(vtable.into());
+        }
+
+        let struct_type = match source {
+            StructSource::Pou(..) => self
+                .types_index
+                .get_associated_pou_type(data_type.get_name()),
+            StructSource::OriginalDeclaration | StructSource::Union => {
+                self.types_index.get_associated_type(data_type.get_name())
+            }
+        }?
+        .into_struct_type();
+
+        Ok(struct_type
+            .const_named_struct(&member_values)
+            .as_basic_value_enum())
+    }
+
     /// generates and returns an optional inital value at the given declared variable
     /// if no initial value is defined, it returns the initial value of the variable's
     /// datatype or Ok(None) if the type also has no declared default value
@@ -468,3 +679,16 @@ pub fn get_default_for(basic_type: BasicTypeEnum) -> BasicValueEnum {
         BasicTypeEnum::VectorType(t) => t.const_zero().into(),
     }
 }
+
+/// returns true if the given type is the topmost class or function block of a hierarchy.
+/// Its instances carry a pointer to the vtable of the instance's class as their last member
+pub fn is_vtable_carrier(index: &Index, data_type: &DataType) -> bool {
+    matches!(
+        data_type.get_type_information(),
+        DataTypeInformation::Struct {
+            source: StructSource::Pou(PouType::Class) | StructSource::Pou(PouType::FunctionBlock),
+            ..
+        }
+    ) && index.is_polymorphic(data_type.get_name())
+        && index.find_super_class(data_type.get_name()).is_none()
+}
@@ -1,24 +1,28 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 use crate::{
-    ast::{self, DirectAccessType, SourceRange},
-    codegen::llvm_typesystem,
+    ast::{self, CompilationUnit, DirectAccessType, PouType, SourceRange},
+    codegen::{self, llvm_typesystem},
     diagnostics::{Diagnostic, INTERNAL_LLVM_ERROR},
-    index::{ImplementationIndexEntry, ImplementationType, Index, VariableIndexEntry},
+    index::{ImplementationIndexEntry, ImplementationType, Index, VariableType},
     resolver::{AnnotationMap, AstAnnotations, StatementAnnotation},
     typesystem::{
-        is_same_type_class, Dimension, StringEncoding, DINT_TYPE, INT_SIZE, INT_TYPE, LINT_TYPE,
+        self, is_same_type_class, Dimension, StringEncoding, StructSource, BOUNDS_FAULT_FN,
+        DATE_AND_TIME_TYPE, DATE_TYPE, DINT_TYPE, DIVISION_FAULT_FN, EXPT_FUNCTION, INT_SIZE,
+        INT_TYPE, LINT_TYPE, RANGE_CHECK_TRAP_FN, TIME_OF_DAY_TYPE, ULINT_TYPE,
     },
+    DivisionCheckMode, OverflowMode,
 };
 use inkwell::{
     builder::Builder,
-    types::BasicTypeEnum,
+    types::{AnyType, AnyTypeEnum, BasicType, BasicTypeEnum},
     values::{
-        ArrayValue, BasicMetadataValueEnum, BasicValue, BasicValueEnum, FloatValue, IntValue,
-        PointerValue, StructValue, VectorValue,
+        AggregateValue, ArrayValue, BasicMetadataValueEnum, BasicValue, BasicValueEnum,
+        CallSiteValue, CallableValue, FloatValue, FunctionValue, IntValue, PointerValue,
+        StructValue, VectorValue,
     },
     AddressSpace, FloatPredicate, IntPredicate,
 };
-use std::collections::HashSet;
+use std::{collections::HashSet, convert::TryFrom};
 
 use crate::{
     ast::{flatten_expression_list, AstStatement, Operator},
@@ -29,10 +33,13 @@ use crate::{
     typesystem::{DataType, DataTypeInformation},
 };
 
-use super::{llvm::Llvm, statement_generator::FunctionContext};
+use super::{llvm::Llvm, pou_generator::get_vtable_name, statement_generator::FunctionContext};
 
 use chrono::{LocalResult, TimeZone, Utc};
 
+/// the number of nanoseconds in a day
+const NANOS_PER_DAY: u64 = 86_400_000_000_000;
+
 /// the generator for expressions
 pub struct ExpressionCodeGenerator<'a, 'b> {
     llvm: &'b Llvm<'a>,
@@ -40,7 +47,7 @@ pub struct ExpressionCodeGenerator<'a, 'b> {
     annotations: &'b AstAnnotations,
     llvm_index: &'b LlvmTypedIndex<'a>,
     /// the current function to create blocks in
-    function_context: Option<&'b FunctionContext<'a>>,
+    function_context: Option<&'b FunctionContext<'a, 'b>>,
 
     /// the string-prefix to use for temporary variables
     pub temp_variable_prefix: String,
@@ -72,7 +79,7 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
         index: &'b Index,
         annotations: &'b AstAnnotations,
         llvm_index: &'b LlvmTypedIndex<'a>,
-        function_context: &'b FunctionContext<'a>,
+        function_context: &'b FunctionContext<'a, 'b>,
     ) -> ExpressionCodeGenerator<'a, 'b> {
         ExpressionCodeGenerator {
             llvm,
@@ -115,7 +122,7 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
     fn get_function_context(
         &self,
         statement: &AstStatement,
-    ) -> Result<&'b FunctionContext<'a>, Diagnostic> {
+    ) -> Result<&'b FunctionContext<'a, 'b>, Diagnostic> {
         self.function_context
             .ok_or_else(|| Diagnostic::missing_function(statement.get_location()))
     }
@@ -158,6 +165,21 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
             }
         }
 
+        //reading a property calls its getter
+        if let Some(StatementAnnotation::Property { getter, .. }) = self.annotations.get(expression)
+        {
+            let getter = getter
+                .as_deref()
+                .and_then(|it| self.index.find_implementation(it))
+                .ok_or_else(|| {
+                    Diagnostic::codegen_error(
+                        "Cannot read a property without a GET accessor",
+                        expression.get_location(),
+                    )
+                })?;
+            return self.generate_call(expression, getter, &None);
+        }
+
         match expression {
             AstStatement::Reference { name, .. } => {
                 let load_name = format!(
@@ -208,17 +230,56 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
                 }
 
                 if ltype.is_int() && rtype.is_int() {
-                    Ok(self.create_llvm_int_binary_expression(
+                    let left_value = self.generate_expression(left)?;
+                    let right_value = self.generate_expression(right)?;
+                    if matches!(operator, Operator::Division | Operator::Modulo) {
+                        self.generate_int_division(
+                            operator,
+                            left_value.into_int_value(),
+                            right_value.into_int_value(),
+                            ltype.is_signed_int(),
+                            expression,
+                        )
+                    } else if operator == &Operator::Exponentiation {
+                        self.generate_int_power(
+                            left_value.into_int_value(),
+                            right_value.into_int_value(),
+                            ltype.is_signed_int(),
+                            expression,
+                        )
+                    } else if let Some(result) = self.generate_int_arithmetic_with_overflow(
                         operator,
-                        self.generate_expression(left)?,
-                        self.generate_expression(right)?,
-                    ))
+                        left_value.into_int_value(),
+                        right_value.into_int_value(),
+                        ltype.is_signed_int(),
+                        expression,
+                    )? {
+                        Ok(result)
+                    } else {
+                        Ok(self.create_llvm_int_binary_expression(
+                            operator,
+                            left_value,
+                            right_value,
+                        ))
+                    }
                 } else if ltype.is_float() && rtype.is_float() {
-                    Ok(self.create_llvm_float_binary_expression(
-                        operator,
-                        self.generate_expression(left)?,
-                        self.generate_expression(right)?,
-                    ))
+                    if operator == &Operator::Exponentiation {
+                        self.generate_float_function_call(
+                            EXPT_FUNCTION,
+                            ltype,
+                            &[
+                                self.generate_expression(left)?,
+                                self.generate_expression(right)?,
+                            ],
+                            expression,
+                        )
+                    } else {
+                        Ok(self.create_llvm_float_binary_expression(
+                            operator,
+                            self.generate_expression(left)?,
+                            self.generate_expression(right)?,
+                        ))
+                    }
                 } else if (ltype.is_pointer() && rtype.is_int())
                     || (ltype.is_int() && rtype.is_pointer())
                     || (ltype.is_pointer() && rtype.is_pointer())
@@ -234,7 +295,35 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
                 operator,
                 parameters,
                 ..
-            } => self.generate_call_statement(operator, parameters),
+            } => {
+                if let Some((name, selector, inputs)) =
+                    self.find_selection_call(operator, parameters)
+                {
+                    self.generate_selection(name, selector, &inputs, expression)
+                } else if let Some((name, value, amount)) =
+                    self.find_bit_shift_call(operator, parameters)
+                {
+                    self.generate_bit_shift(name, value, amount)
+                } else if let Some(argument) = self.find_sizeof_call(operator, parameters) {
+                    self.generate_sizeof(argument)
+                } else if let Some(argument) = self.find_address_call(operator, parameters) {
+                    //ADR(x) and REF(x) result in the address of x, just like &x
+                    self.generate_element_pointer(argument)
+                        .map(|result| result.as_basic_value_enum())
+                } else if let Some((base, exponent)) = self.find_exponent_call(operator, parameters)
+                {
+                    self.generate_exponentiation(base, exponent, expression)
+                } else if let Some((name, arguments)) = self.find_numeric_call(operator, parameters)
+                {
+                    self.generate_numeric_function(name, &arguments, expression)
+                } else if let Some((argument, source, target, truncate)) =
+                    self.find_conversion_call(operator, parameters)
+                {
+                    self.generate_conversion(argument, source, target, truncate)
+                } else {
+                    self.generate_call_statement(operator, parameters)
+                }
+            }
             AstStatement::UnaryExpression {
                 operator, value, ..
             } => self.generate_unary_expression(operator, value),
@@ -243,6 +332,576 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
         }
     }
 
+    /// returns the name, the selector and the inputs if the given call calls a selection function
+    /// that is generated by the compiler (e.g. `MAX(x, y)` or `SEL(g, x, y)`)
+    fn find_selection_call<'s>(
+        &self,
+        operator: &'s AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<(&'s str, Option<&'s AstStatement>, Vec<&'s AstStatement>)> {
+        match operator {
+            AstStatement::Reference { name, .. }
+                if typesystem::is_selection_function(name)
+                    && self.index.find_implementation(name).is_none() =>
+            {
+                let arguments = ast::get_implicit_arguments(parameters)?;
+                let (selector, inputs) = typesystem::split_selection_arguments(name, &arguments)?;
+                Some((name.as_str(), selector.copied(), inputs.to_vec()))
+            }
+            _ => None,
+        }
+    }
+
+    /// generates the selection of one of the given inputs without branching. The inputs are
+    /// annotated with the resulting type of the call, so they are already casted to it.
+    /// - `SEL(G, IN0, IN1)` selects `IN1` if `G` is `TRUE`, `IN0` otherwise
+    /// - `MAX(IN0, ...)` and `MIN(IN0, ...)` select the biggest or the smallest input
+    /// - `LIMIT(MN, IN, MX)` limits `IN` to the range `MN..MX`
+    /// - `MUX(K, IN0, ...)` selects the `K`th input, `IN0` if `K` is out of range
+    fn generate_selection(
+        &self,
+        function_name: &str,
+        selector: Option<&AstStatement>,
+        inputs: &[&AstStatement],
+        call: &AstStatement,
+    ) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let result_type = self
+            .index
+            .get_intrinsic_type_by_name(
+                self.annotations
+                    .get_type_or_void(call, self.index)
+                    .get_name(),
+            )
+            .get_type_information();
+        let inputs = inputs
+            .iter()
+            .map(|input| self.generate_expression(input))
+            .collect::<Result<Vec<_>, _>>()?;
+        let selector = selector
+            .map(|selector| self.generate_expression(selector))
+            .transpose()?;
+
+        let function_name = function_name.to_uppercase();
+        let result = match (function_name.as_str(), selector, inputs.as_slice()) {
+            ("SEL", Some(selector), [in0, in1]) => {
+                let selector = selector.into_int_value();
+                let zero = selector.get_type().const_zero();
+                let is_true = builder.build_int_compare(IntPredicate::NE, selector, zero, "");
+                builder.build_select(is_true, *in1, *in0, "sel")
+            }
+            ("MUX", Some(selector), [in0, others @ ..]) => {
+                let selector = selector.into_int_value();
+                others
+                    .iter()
+                    .enumerate()
+                    .fold(*in0, |selected, (position, input)| {
+                        let k = selector.get_type().const_int(position as u64 + 1, false);
+                        let is_selected =
+                            builder.build_int_compare(IntPredicate::EQ, selector, k, "");
+                        builder.build_select(is_selected, *input, selected, "mux")
+                    })
+            }
+            ("LIMIT", None, [min, value, max]) => {
+                let bigger = self.generate_max_or_min(*value, *min, result_type, true);
+                self.generate_max_or_min(bigger, *max, result_type, false)
+            }
+            ("MAX", None, [first, others @ ..]) | ("MIN", None, [first, others @ ..]) => {
+                let max = function_name == "MAX";
+                others.iter().fold(*first, |selected, input| {
+                    self.generate_max_or_min(selected, *input, result_type, max)
+                })
+            }
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    &format!("Invalid arguments for {}", function_name),
+                    call.get_location(),
+                ))
+            }
+        };
+        Ok(result)
+    }
+
+    /// selects the bigger (`max`) or the smaller of the two given values of the given type
+    fn generate_max_or_min(
+        &self,
+        left: BasicValueEnum<'a>,
+        right: BasicValueEnum<'a>,
+        data_type: &DataTypeInformation,
+        max: bool,
+    ) -> BasicValueEnum<'a> {
+        let builder = &self.llvm.builder;
+        let is_left = if data_type.is_float() {
+            let predicate = if max {
+                FloatPredicate::OGE
+            } else {
+                FloatPredicate::OLE
+            };
+            let (left, right) = (left.into_float_value(), right.into_float_value());
+            builder.build_float_compare(predicate, left, right, "")
+        } else {
+            let predicate = match (max, data_type.is_signed_int()) {
+                (true, true) => IntPredicate::SGE,
+                (true, false) => IntPredicate::UGE,
+                (false, true) => IntPredicate::SLE,
+                (false, false) => IntPredicate::ULE,
+            };
+            let (left, right) = (left.into_int_value(), right.into_int_value());
+            builder.build_int_compare(predicate, left, right, "")
+        };
+        builder.build_select(is_left, left, right, if max { "max" } else { "min" })
+    }
+
+    /// returns the name, the shifted value and the shift amount if the given call calls a
+    /// bit-shift function that is generated by the compiler (e.g. `SHL(x, 2)`)
+    fn find_bit_shift_call<'s>(
+        &self,
+        operator: &'s AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<(&'s str, &'s AstStatement, &'s AstStatement)> {
+        match operator {
+            AstStatement::Reference { name, .. }
+                if typesystem::is_bit_shift_function(name)
+                    && self.index.find_implementation(name).is_none() =>
+            {
+                let (value, amount) = ast::get_argument_pair(parameters)?;
+                Some((name.as_str(), value, amount))
+            }
+            _ => None,
+        }
+    }
+
+    /// returns the argument if the given call calls the `SIZEOF` function that is generated by
+    /// the compiler (e.g. `SIZEOF(x)` or `SIZEOF(INT)`)
+    fn find_sizeof_call<'s>(
+        &self,
+        operator: &'s AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<&'s AstStatement> {
+        match operator {
+            AstStatement::Reference { name, .. }
+                if name.eq_ignore_ascii_case(typesystem::SIZEOF_FUNCTION)
+                    && self.index.find_implementation(name).is_none() =>
+            {
+                ast::get_single_argument(parameters)
+            }
+            _ => None,
+        }
+    }
+
+    /// returns the argument if the given call calls one of the address functions that are
+    /// generated by the compiler (e.g. `ADR(x)` or `REF(x)`)
+    fn find_address_call<'s>(
+        &self,
+        operator: &'s AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<&'s AstStatement> {
+        match operator {
+            AstStatement::Reference { name, .. }
+                if typesystem::is_address_function(name)
+                    && self.index.find_implementation(name).is_none() =>
+            {
+                ast::get_single_argument(parameters)
+            }
+            _ => None,
+        }
+    }
+
+    /// returns the base and the exponent if the given call calls the `EXPT` function that is
+    /// generated by the compiler (e.g. `EXPT(x, 2)` or `EXPT(IN1 := x, IN2 := 2)`)
+    fn find_exponent_call<'s>(
+        &self,
+        operator: &'s AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<(&'s AstStatement, &'s AstStatement)> {
+        match operator {
+            AstStatement::Reference { name, .. }
+                if name.eq_ignore_ascii_case(EXPT_FUNCTION)
+                    && self.index.find_implementation(name).is_none() =>
+            {
+                typesystem::get_exponent_arguments(parameters)
+            }
+            _ => None,
+        }
+    }
+
+    /// generates a call to `EXPT` like the exponentiation `base ** exponent`. Both arguments are
+    /// annotated with the call's type, so they are already converted.
+    fn generate_exponentiation(
+        &self,
+        base: &AstStatement,
+        exponent: &AstStatement,
+        expression: &AstStatement,
+    ) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        let result_type = self
+            .index
+            .get_intrinsic_type_by_name(
+                self.annotations
+                    .get_type_or_void(expression, self.index)
+                    .get_name(),
+            )
+            .get_type_information();
+        let base = self.generate_expression(base)?;
+        let exponent = self.generate_expression(exponent)?;
+        if result_type.is_int() {
+            self.generate_int_power(
+                base.into_int_value(),
+                exponent.into_int_value(),
+                result_type.is_signed_int(),
+                expression,
+            )
+        } else {
+            self.generate_float_function_call(
+                EXPT_FUNCTION,
+                result_type,
+                &[base, exponent],
+                expression,
+            )
+        }
+    }
+
+    /// returns the name and the arguments if the given call calls a numeric function that is
+    /// generated by the compiler (e.g. `SQRT(x)` or `ATAN2(y, x)`)
+    fn find_numeric_call<'s>(
+        &self,
+        operator: &'s AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<(&'s str, Vec<&'s AstStatement>)> {
+        match operator {
+            AstStatement::Reference { name, .. }
+                if typesystem::is_numeric_function(name)
+                    && self.index.find_implementation(name).is_none() =>
+            {
+                typesystem::get_numeric_function_arguments(name, parameters)
+                    .map(|arguments| (name.as_str(), arguments))
+            }
+            _ => None,
+        }
+    }
+
+    /// generates the given numeric function. `ABS` of an integer negates negative values, all
+    /// other numeric functions call a float function of the call's real type. Integer arguments
+    /// are annotated with this real type, so they are already converted.
+    fn generate_numeric_function(
+        &self,
+        function_name: &str,
+        arguments: &[&AstStatement],
+        expression: &AstStatement,
+    ) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        let result_type = self
+            .index
+            .get_intrinsic_type_by_name(
+                self.annotations
+                    .get_type_or_void(expression, self.index)
+                    .get_name(),
+            )
+            .get_type_information();
+        let values = arguments
+            .iter()
+            .map(|it| self.generate_expression(it))
+            .collect::<Result<Vec<_>, _>>()?;
+        match values.as_slice() {
+            [value] if result_type.is_int() => {
+                let value = value.into_int_value();
+                if !result_type.is_signed_int() {
+                    return Ok(value.as_basic_value_enum());
+                }
+                let builder = &self.llvm.builder;
+                let zero = value.get_type().const_zero();
+                let is_negative = builder.build_int_compare(IntPredicate::SLT, value, zero, "");
+                let negated = builder.build_int_neg(value, "");
+                Ok(builder.build_select(is_negative, negated, value, "abs"))
+            }
+            _ => self.generate_float_function_call(function_name, result_type, &values, expression),
+        }
+    }
+
+    /// generates the number of bytes of the argument's type as laid out by the target's
+    /// data layout. The argument is not evaluated.
+    fn generate_sizeof(&self, argument: &AstStatement) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        let type_name = match self.annotations.get(argument) {
+            Some(StatementAnnotation::Type { type_name }) => type_name.as_str(),
+            _ => self
+                .annotations
+                .get_type_or_void(argument, self.index)
+                .get_name(),
+        };
+        let size = self
+            .llvm_index
+            .find_associated_type(type_name)
+            .or_else(|| self.llvm_index.find_associated_pou_type(type_name))
+            .and_then(|it| it.size_of())
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!("Cannot determine the size of '{}'", type_name),
+                    argument.get_location(),
+                )
+            })?;
+        let result_type = self
+            .llvm_index
+            .get_associated_type(ULINT_TYPE)?
+            .into_int_type();
+        Ok(size.const_cast(result_type, false).as_basic_value_enum())
+    }
+
+    /// generates the shift (`SHL`, `SHR`) or rotation (`ROL`, `ROR`) of the given value by the
+    /// given amount of bits. Shifts fill the vacated bits with zeros, so shifting by the
+    /// bit-width of the value or more results in zero. Rotations rotate by the amount modulo
+    /// the bit-width.
+    fn generate_bit_shift(
+        &self,
+        function_name: &str,
+        value: &AstStatement,
+        amount: &AstStatement,
+    ) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let value = self.generate_expression(value)?.into_int_value();
+        let amount = self.generate_expression(amount)?.into_int_value();
+        let value_type = value.get_type();
+        let bit_width = value_type.get_bit_width() as u64;
+        let width = amount.get_type().const_int(bit_width, false);
+
+        let function_name = function_name.to_uppercase();
+        let result = if function_name == "SHL" || function_name == "SHR" {
+            let exceeds_width = builder.build_int_compare(IntPredicate::UGE, amount, width, "");
+            let amount = builder.build_int_cast(amount, value_type, "");
+            let shifted = if function_name == "SHL" {
+                builder.build_left_shift(value, amount, "")
+            } else {
+                builder.build_right_shift(value, amount, false, "")
+            };
+            builder.build_select(exceeds_width, value_type.const_zero(), shifted, "shift")
+        } else {
+            let amount = builder.build_int_unsigned_rem(amount, width, "");
+            let amount = builder.build_int_cast(amount, value_type, "");
+            //the bits rotated out on the other side, masked so a rotation by 0 shifts by 0 as well
+            let remaining = builder.build_and(
+                builder.build_int_sub(value_type.const_int(bit_width, false), amount, ""),
+                value_type.const_int(bit_width - 1, false),
+                "",
+            );
+            let (left, right) = if function_name == "ROL" {
+                (amount, remaining)
+            } else {
+                (remaining, amount)
+            };
+            builder
+                .build_or(
+                    builder.build_left_shift(value, left, ""),
+                    builder.build_right_shift(value, right, false, ""),
+                    "rotate",
+                )
+                .as_basic_value_enum()
+        };
+        Ok(result)
+    }
+
+    /// returns the argument, the source- and the target-type and whether a real is truncated
+    /// if the given call calls a conversion function that is generated by the compiler
+    /// (e.g. `DT_TO_DATE(x)`)
+    fn find_conversion_call<'s>(
+        &self,
+        operator: &AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<(&'s AstStatement, &'b DataType, &'b DataType, bool)> {
+        match operator {
+            AstStatement::Reference { name, .. }
+                if self.index.find_implementation(name).is_none() =>
+            {
+                let argument = ast::get_single_argument(parameters)?;
+                typesystem::find_conversion(name, self.index).map(|(source, target)| {
+                    (
+                        argument,
+                        source,
+                        target,
+                        typesystem::is_truncating_conversion(name),
+                    )
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// generates the conversion of the given argument from the source- to the target-type.
+    /// Converting a `DATE_AND_TIME` to a `DATE` drops the time of the day, converting it to a
+    /// `TIME_OF_DAY` drops the date. Converting to a `BOOL` results in `TRUE` for every value
+    /// but zero, reals are rounded to the nearest integer (halfway cases away from zero) unless
+    /// `truncate` is set. All other conversions keep the value (nanoseconds for date and time
+    /// types) and only adapt its size. A character converts to a string holding only this
+    /// character, a string converts to its first character.
+    fn generate_conversion(
+        &self,
+        argument: &AstStatement,
+        source: &DataType,
+        target: &DataType,
+        truncate: bool,
+    ) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        //the argument is annotated with the source-type, so it is already casted to it
+        let value = self.generate_expression(argument)?;
+        let target_information = target.get_type_information();
+        if source.get_type_information().is_string() && target_information.is_character() {
+            return self.generate_first_character(value, argument);
+        }
+        if source.get_type_information().is_character() && target_information.is_string() {
+            let string_type = self
+                .llvm_index
+                .get_associated_type(target.get_name())?
+                .into_array_type();
+            return self
+                .llvm
+                .builder
+                .build_insert_value(string_type.const_zero(), value, 0, "")
+                .map(|it| it.into_array_value().as_basic_value_enum())
+                .ok_or_else(|| {
+                    Diagnostic::codegen_error(
+                        "Cannot convert the character to a string",
+                        argument.get_location(),
+                    )
+                });
+        }
+        if target_information.is_bool() && !source.get_type_information().is_bool() {
+            let builder = &self.llvm.builder;
+            let is_true = if value.is_float_value() {
+                let value = value.into_float_value();
+                let zero = value.get_type().const_zero();
+                builder.build_float_compare(FloatPredicate::UNE, value, zero, "")
+            } else {
+                let value = value.into_int_value();
+                let zero = value.get_type().const_zero();
+                builder.build_int_compare(IntPredicate::NE, value, zero, "")
+            };
+            return cast_if_needed(
+                self.llvm,
+                self.index,
+                self.llvm_index,
+                target,
+                is_true.as_basic_value_enum(),
+                target,
+                argument,
+            );
+        }
+        if value.is_float_value() && target_information.is_int() && !truncate {
+            return self.generate_rounded_conversion(value, source, target, argument);
+        }
+        let value = match (
+            typesystem::get_date_time_base_type(source.get_name()),
+            typesystem::get_date_time_base_type(target.get_name()),
+        ) {
+            (DATE_AND_TIME_TYPE, DATE_TYPE) => {
+                let date_and_time = value.into_int_value();
+                let time_of_day = self.generate_time_of_day(date_and_time);
+                self.llvm
+                    .builder
+                    .build_int_sub(date_and_time, time_of_day, "date")
+                    .as_basic_value_enum()
+            }
+            (DATE_AND_TIME_TYPE, TIME_OF_DAY_TYPE) => self
+                .generate_time_of_day(value.into_int_value())
+                .as_basic_value_enum(),
+            _ => value,
+        };
+        cast_if_needed(
+            self.llvm,
+            self.index,
+            self.llvm_index,
+            target,
+            value,
+            source,
+            argument,
+        )
+    }
+
+    /// returns the first character of the given string-value. The string may either be
+    /// a loaded array or a pointer to it (e.g. a string-literal's global constant)
+    fn generate_first_character(
+        &self,
+        string: BasicValueEnum<'a>,
+        argument: &AstStatement,
+    ) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        if string.is_pointer_value() {
+            let zero = self.llvm.context.i32_type().const_zero();
+            let first_character =
+                self.llvm
+                    .load_array_element(string.into_pointer_value(), &[zero, zero], "")?;
+            Ok(builder.build_load(first_character, ""))
+        } else {
+            builder
+                .build_extract_value(string.into_array_value(), 0, "")
+                .ok_or_else(|| {
+                    Diagnostic::codegen_error(
+                        "Cannot access the first character of the string",
+                        argument.get_location(),
+                    )
+                })
+        }
+    }
+
+    /// generates the conversion of the given real value to the integer target-type, rounding
+    /// it to the nearest integer. Halfway cases are rounded away from zero.
+    fn generate_rounded_conversion(
+        &self,
+        value: BasicValueEnum<'a>,
+        source: &DataType,
+        target: &DataType,
+        argument: &AstStatement,
+    ) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let truncated = cast_if_needed(
+            self.llvm,
+            self.index,
+            self.llvm_index,
+            target,
+            value,
+            source,
+            argument,
+        )?;
+        //the difference between the value and its truncated value is exact
+        let truncated_real = cast_if_needed(
+            self.llvm,
+            self.index,
+            self.llvm_index,
+            source,
+            truncated,
+            target,
+            argument,
+        )?;
+        let fraction = builder.build_float_sub(
+            value.into_float_value(),
+            truncated_real.into_float_value(),
+            "",
+        );
+        let half = fraction.get_type().const_float(0.5);
+        let minus_half = fraction.get_type().const_float(-0.5);
+        let round_up = builder.build_float_compare(FloatPredicate::OGE, fraction, half, "");
+        let round_down = builder.build_float_compare(FloatPredicate::OLE, fraction, minus_half, "");
+
+        let truncated = truncated.into_int_value();
+        let one = truncated.get_type().const_int(1, false);
+        let rounded_up = builder.build_int_add(truncated, one, "");
+        let rounded_down = builder.build_int_sub(truncated, one, "");
+        let rounded = builder.build_select(round_down, rounded_down, truncated, "");
+        Ok(builder.build_select(round_up, rounded_up, rounded.into_int_value(), "rounded"))
+    }
+
+    /// generates the nanoseconds that passed since midnight of the given date and time
+    fn generate_time_of_day(&self, date_and_time: IntValue<'a>) -> IntValue<'a> {
+        let builder = &self.llvm.builder;
+        let nanos_per_day = date_and_time.get_type().const_int(NANOS_PER_DAY, true);
+        let remainder = builder.build_int_signed_rem(date_and_time, nanos_per_day, "");
+        //dates before 1970 result in a negative remainder
+        let is_negative = builder.build_int_compare(
+            IntPredicate::SLT,
+            remainder,
+            date_and_time.get_type().const_zero(),
+            "",
+        );
+        let positive_remainder = builder.build_int_add(remainder, nanos_per_day, "");
+        builder
+            .build_select(is_negative, positive_remainder, remainder, "time_of_day")
+            .into_int_value()
+    }
+
     fn generate_directaccess(
         &self,
         elements: &[AstStatement],
@@ -427,7 +1086,6 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
             })
         }
 
-        let function_context = self.get_function_context(operator)?;
         //find call name
         let implementation = self
             .annotations
@@ -449,6 +1107,22 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
                 )
             })?;
 
+        self.generate_call(operator, implementation, parameters)
+    }
+
+    /// generates a call of the given implementation <operator>(<parameters>)
+    /// returns the call's result as a BasicValueEnum (may be a void-type for PROGRAMs)
+    ///
+    /// - `operator` - the expression that points to the callable instance, or to the instance of the method's class
+    /// - `implementation` - the implementation to call
+    /// - `parameters` - an optional StatementList of parameters
+    pub fn generate_call(
+        &self,
+        operator: &AstStatement,
+        implementation: &ImplementationIndexEntry,
+        parameters: &Option<AstStatement>,
+    ) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        let function_context = self.get_function_context(operator)?;
         let (class_ptr, call_ptr) = match implementation {
             ImplementationIndexEntry {
                 implementation_type: ImplementationType::Function,
@@ -462,7 +1136,12 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
                 implementation_type: ImplementationType::Method,
                 ..
             } => {
+                //an inherited method is called with the embedded instance of its class
                 let class_ptr = self.generate_element_pointer(operator)?;
+                let class_ptr = implementation
+                    .get_associated_class_name()
+                    .map(|class| self.cast_to_super_class(class_ptr, class))
+                    .unwrap_or(class_ptr);
                 let call_ptr = self
                     .allocate_function_struct_instance(implementation.get_call_name(), operator)?;
                 (Some(class_ptr), call_ptr)
@@ -501,6 +1180,16 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
             instance,
             parameters,
         )?;
+        let parameters_data = if index_entry.is_foreign() {
+            self.generate_foreign_call_arguments(
+                function_name,
+                instance,
+                parameters_data,
+                operator,
+            )?
+        } else {
+            parameters_data
+        };
 
         let function = self
             .llvm_index
@@ -516,24 +1205,169 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
             })?;
         //If the target is a function, declare the struct locally
         //Assign all parameters into the struct values
-        let call_result = builder
-            .build_call(function, &parameters_data, "call")
-            .try_as_basic_value();
+        let call_result = match class_struct {
+            //methods of polymorphic classes are called through the instance's vtable,
+            //unless the method of the super class is called explicitly using SUPER^
+            Some(class_struct)
+                if index_entry
+                    .get_associated_class_name()
+                    .map(|it| self.index.is_polymorphic(it))
+                    .unwrap_or(false)
+                    && !is_super_call(operator) =>
+            {
+                self.generate_virtual_call(operator, index_entry, function, class_struct, instance)?
+            }
+            _ => builder.build_call(function, &parameters_data, "call"),
+        }
+        .try_as_basic_value();
 
         //build output-parameters
         self.generate_output_function_parameters(function_name, instance, parameters)?;
 
-        // we return an uninitialized int pointer for void methods :-/
-        // dont deref it!!
-        let value = call_result.either(Ok, |_| {
-            get_llvm_int_type(self.llvm.context, INT_SIZE, INT_TYPE).map(|int| {
-                int.ptr_type(AddressSpace::Const)
-                    .const_null()
-                    .as_basic_value_enum()
-            })
+        // we return an uninitialized int pointer for void methods :-/
+        // dont deref it!!
+        let value = call_result.either(Ok, |_| {
+            get_llvm_int_type(self.llvm.context, INT_SIZE, INT_TYPE).map(|int| {
+                int.ptr_type(AddressSpace::Const)
+                    .const_null()
+                    .as_basic_value_enum()
+            })
+        })?;
+
+        Ok(value)
+    }
+
+    /// calls the given method of a polymorphic class through the vtable referenced by the given
+    /// instance. The vtable's slot expects the struct of the method's first declaration in the
+    /// class hierarchy, if the called method overrides it, the parameters are copied.
+    fn generate_virtual_call(
+        &self,
+        operator: &AstStatement,
+        method: &ImplementationIndexEntry,
+        function: FunctionValue<'a>,
+        instance: PointerValue<'a>,
+        parameter_struct: PointerValue<'a>,
+    ) -> Result<CallSiteValue<'a>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let byte_pointer = self.llvm.context.i8_type().ptr_type(AddressSpace::Generic);
+        let class_name = method
+            .get_associated_class_name()
+            .expect("method of a class");
+        let method_name = ast::Pou::calc_return_name(method.get_call_name());
+        let (slot, declaration) = self
+            .index
+            .get_virtual_methods(class_name)
+            .into_iter()
+            .enumerate()
+            .find(|(_, it)| {
+                ast::Pou::calc_return_name(it.get_call_name()).eq_ignore_ascii_case(method_name)
+            })
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!(
+                        "Cannot find {} in the vtable of {}",
+                        method_name, class_name
+                    ),
+                    operator.get_location(),
+                )
+            })?;
+        let declaration_name = declaration.get_call_name();
+
+        //the vtable follows the members of the topmost class of the hierarchy
+        let root = self
+            .index
+            .get_super_classes(class_name)
+            .last()
+            .copied()
+            .unwrap_or(class_name);
+        let root_instance = self.cast_to_super_class(instance, root);
+        let vtable_ptr = builder
+            .build_struct_gep(
+                root_instance,
+                get_vtable_location(self.index, root),
+                "vtable_ptr",
+            )
+            .expect(INTERNAL_LLVM_ERROR);
+        let vtable = builder
+            .build_load(vtable_ptr, "vtable")
+            .into_pointer_value();
+        //instances without an initial value (e.g. array elements) fall back to the declared class
+        let declared_vtable = self
+            .llvm_index
+            .find_global_value(&get_class_vtable_name(class_name))
+            .map(|it| it.as_pointer_value().const_cast(byte_pointer))
+            .unwrap_or_else(|| byte_pointer.const_null());
+        let vtable = builder
+            .build_select(
+                builder.build_is_null(vtable, ""),
+                declared_vtable,
+                vtable,
+                "",
+            )
+            .into_pointer_value();
+        let slots = builder
+            .build_bitcast(vtable, byte_pointer.ptr_type(AddressSpace::Generic), "")
+            .into_pointer_value();
+        let thunk_slot = unsafe {
+            builder.build_in_bounds_gep(
+                slots,
+                &[self.llvm.i32_type().const_int(slot as u64, false)],
+                "",
+            )
+        };
+
+        //the arguments are passed in the struct of the method's first declaration
+        let (declaration_struct, parameters) =
+            if declaration_name.eq_ignore_ascii_case(method.get_call_name()) {
+                (parameter_struct, vec![])
+            } else {
+                let declaration_struct =
+                    self.allocate_function_struct_instance(declaration_name, operator)?;
+                let parameters = get_parameter_pointers(
+                    builder,
+                    self.index,
+                    (method.get_call_name(), parameter_struct),
+                    (declaration_name, declaration_struct),
+                );
+                for (_, from, to) in &parameters {
+                    builder.build_store(*to, builder.build_load(*from, ""));
+                }
+                (declaration_struct, parameters)
+            };
+
+        let parameter_types = [byte_pointer.into(), declaration_struct.get_type().into()];
+        let thunk_type = match function.get_type().get_return_type() {
+            Some(return_type) => return_type.fn_type(&parameter_types, false),
+            None => self
+                .llvm
+                .context
+                .void_type()
+                .fn_type(&parameter_types, false),
+        };
+        let thunk = builder
+            .build_bitcast(
+                builder.build_load(thunk_slot, "thunk"),
+                thunk_type.ptr_type(AddressSpace::Generic),
+                "",
+            )
+            .into_pointer_value();
+        let thunk = CallableValue::try_from(thunk).map_err(|_| {
+            Diagnostic::codegen_error(
+                &format!("Cannot call {} through the vtable", method.get_call_name()),
+                operator.get_location(),
+            )
         })?;
+        let instance = builder.build_bitcast(instance, byte_pointer, "");
+        let result =
+            builder.build_call(thunk, &[instance.into(), declaration_struct.into()], "call");
 
-        Ok(value)
+        for (_, from, to) in parameters
+            .iter()
+            .filter(|(variable_type, ..)| *variable_type == VariableType::Output)
+        {
+            builder.build_store(*from, builder.build_load(*to, ""));
+        }
+        Ok(result)
     }
 
     /// generates a new instance of a function called `function_name` and returns a PointerValue to it
@@ -604,6 +1438,61 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
         Ok(result)
     }
 
+    /// replaces the parameter-struct of a call to a foreign function by the parameters assigned
+    /// to it, passed as expected by the foreign function (see `LinkageType::Foreign`).
+    /// The outputs are passed as pointers into the parameter-struct, so they can be assigned
+    /// after the call like the outputs of any other function.
+    ///
+    /// - `function_name` the name of the foreign function
+    /// - `parameter_struct` a pointer to a struct-instance that holds all function-parameters
+    /// - `arguments` the parameter-struct followed by the variadic arguments of the call
+    fn generate_foreign_call_arguments(
+        &self,
+        function_name: &str,
+        parameter_struct: PointerValue<'a>,
+        arguments: Vec<BasicMetadataValueEnum<'a>>,
+        operator: &AstStatement,
+    ) -> Result<Vec<BasicMetadataValueEnum<'a>>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let mut result = vec![];
+        for parameter in self
+            .index
+            .get_container_members(function_name)
+            .into_iter()
+            .filter(|it| it.is_parameter())
+        {
+            let pointer_to_param = builder
+                .build_struct_gep(
+                    parameter_struct,
+                    parameter.get_location_in_parent(),
+                    parameter.get_name(),
+                )
+                .map_err(|_| {
+                    Diagnostic::codegen_error(
+                        &format!("Cannot generate parameter {}", parameter.get_name()),
+                        operator.get_location(),
+                    )
+                })?;
+            let by_reference = parameter.get_variable_type() == VariableType::Output
+                || (parameter.get_variable_type() == VariableType::Input
+                    && (pointer_to_param
+                        .get_type()
+                        .get_element_type()
+                        .is_array_type()
+                        || pointer_to_param
+                            .get_type()
+                            .get_element_type()
+                            .is_struct_type()));
+            result.push(if by_reference {
+                pointer_to_param.into()
+            } else {
+                builder.build_load(pointer_to_param, "").into()
+            });
+        }
+        result.extend(arguments.into_iter().skip(1));
+        Ok(result)
+    }
+
     /// generates the output assignments of a function-call's parameters
     /// the call parameters are passed to the function using a struct-instance with all the parameters
     ///
@@ -700,7 +1589,10 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
                     .generate_element_pointer(expression)?
                     .as_basic_value_enum();
 
-                builder.build_store(pointer_to_param, generated_exp);
+                builder.build_store(
+                    pointer_to_param,
+                    self.cast_to_target_pointer(generated_exp, pointer_to_param),
+                );
             } else {
                 self.generate_store(parameter, expression, pointer_to_param)?;
             };
@@ -847,6 +1739,28 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
         result.and_then(|it| self.auto_deref_if_necessary(it, reference_statement))
     }
 
+    /// casts the given pointer to an instance to a pointer to the given class if it is one of
+    /// the instance's super classes. The instance of a super class is embedded at the
+    /// start of the derived instance.
+    fn cast_to_super_class(
+        &self,
+        instance: PointerValue<'a>,
+        class_name: &str,
+    ) -> PointerValue<'a> {
+        match self.llvm_index.find_associated_pou_type(class_name) {
+            Some(class_type)
+                if self.index.is_polymorphic(class_name)
+                    && instance.get_type().get_element_type() != class_type.as_any_type_enum() =>
+            {
+                self.llvm
+                    .builder
+                    .build_bitcast(instance, class_type.ptr_type(AddressSpace::Generic), "")
+                    .into_pointer_value()
+            }
+            _ => instance,
+        }
+    }
+
     /// geneartes a gep for the given reference with an optional qualifier
     ///
     /// - `qualifier` an optional qualifier for a reference (e.g. myStruct.x where myStruct is the qualifier for x)
@@ -869,16 +1783,44 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
                         return Ok(qualifier.to_owned());
                     }
                 }
+                //the accessors of a property are called with the property's instance
+                Some(StatementAnnotation::Property { .. }) => {
+                    return Ok(qualifier.to_owned());
+                }
                 Some(StatementAnnotation::Variable { qualified_name, .. }) => {
-                    let member_location = self
+                    let member = self
                         .index
                         .find_fully_qualified_variable(qualified_name)
-                        .map(VariableIndexEntry::get_location_in_parent)
                         .ok_or_else(|| {
                             Diagnostic::unresolved_reference(qualified_name, offset.clone())
                         })?;
+
+                    //all members of a union start at the union's address
+                    let is_union_member = qualified_name
+                        .rsplit_once('.')
+                        .and_then(|(container, _)| self.index.find_effective_type_info(container))
+                        .map(DataTypeInformation::is_union)
+                        .unwrap_or(false);
+                    if is_union_member {
+                        let member_type = self
+                            .llvm_index
+                            .get_associated_type(member.get_type_name())?;
+                        return Ok(self.llvm.get_member_pointer_from_union(
+                            *qualifier,
+                            member_type,
+                            name,
+                        ));
+                    }
+
+                    //an inherited member is accessed through the embedded instance of its class
+                    let qualifier = qualified_name
+                        .rsplit_once('.')
+                        .map(|(container, _)| self.cast_to_super_class(*qualifier, container))
+                        .unwrap_or(*qualifier);
+
+                    let member_location = member.get_location_in_parent();
                     let gep = self.llvm.get_member_pointer_from_struct(
-                        *qualifier,
+                        qualifier,
                         member_location,
                         name,
                         offset,
@@ -951,6 +1893,18 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
             .map_err(|it| Diagnostic::codegen_error(&it, access_expression.get_location()))?;
 
         let access_value = self.generate_expression(access_expression)?;
+        if let Some(unit) = self
+            .function_context
+            .filter(|it| it.bounds_checks)
+            .map(|it| it.unit)
+        {
+            self.generate_bounds_check(
+                dimension,
+                access_value.into_int_value(),
+                access_expression,
+                unit,
+            )?;
+        }
         //If start offset is not 0, adjust the current statement with an add operation
         let result = if start_offset != 0 {
             let access_int_value = access_value.into_int_value();
@@ -975,6 +1929,124 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
         )
     }
 
+    /// generates a check of the given index against the bounds of the given dimension. An index
+    /// out of bounds calls the `rusty_bounds_fault` handler with the index, the bounds and the
+    /// location of the access (`file:line:column`), the program is aborted if the handler returns
+    fn generate_bounds_check(
+        &self,
+        dimension: &Dimension,
+        index: IntValue<'a>,
+        access_expression: &AstStatement,
+        unit: &CompilationUnit,
+    ) -> Result<(), Diagnostic> {
+        let location = access_expression.get_location();
+        let range = dimension
+            .get_range(self.index)
+            .map_err(|it| Diagnostic::codegen_error(&it, location.clone()))?;
+        let function = self.get_function_context(access_expression)?.function;
+        let (handler, trap) = match (
+            self.llvm_index
+                .find_associated_implementation(BOUNDS_FAULT_FN),
+            self.llvm_index
+                .find_associated_implementation(RANGE_CHECK_TRAP_FN),
+        ) {
+            (Some(handler), Some(trap)) => (handler, trap),
+            _ => {
+                return Err(Diagnostic::codegen_error(
+                    "Cannot find the handler for bounds checks",
+                    location,
+                ))
+            }
+        };
+
+        //the index and the bounds are compared as LINT
+        let builder = &self.llvm.builder;
+        let i64_type = self.llvm.context.i64_type();
+        let index = if self
+            .get_type_hint_info_for(access_expression)?
+            .is_signed_int()
+        {
+            builder.build_int_s_extend_or_bit_cast(index, i64_type, "")
+        } else {
+            builder.build_int_z_extend_or_bit_cast(index, i64_type, "")
+        };
+        let lower = i64_type.const_int(range.start as u64, true);
+        let upper = i64_type.const_int(range.end as u64, true);
+        let is_below = builder.build_int_compare(IntPredicate::SLT, index, lower, "is_below");
+        let is_above = builder.build_int_compare(IntPredicate::SGT, index, upper, "is_above");
+        let out_of_bounds = self
+            .llvm
+            .context
+            .append_basic_block(function, "out_of_bounds");
+        let in_bounds = self.llvm.context.append_basic_block(function, "in_bounds");
+        let is_out_of_bounds = builder.build_or(is_below, is_above, "");
+        builder.build_conditional_branch(is_out_of_bounds, out_of_bounds, in_bounds);
+
+        builder.position_at_end(out_of_bounds);
+        self.generate_fault_handler_call(
+            handler,
+            &[
+                index.as_basic_value_enum(),
+                lower.as_basic_value_enum(),
+                upper.as_basic_value_enum(),
+            ],
+            access_expression,
+            unit,
+            "bounds_location",
+        );
+        builder.build_call(trap, &[], "");
+        builder.build_unreachable();
+
+        builder.position_at_end(in_bounds);
+        Ok(())
+    }
+
+    /// calls the given fault handler with the given arguments followed by the location of the
+    /// given expression as a C string (`file:line:column`). A handler declared by the sources
+    /// may declare other integer and pointer types, the arguments are converted to them.
+    fn generate_fault_handler_call(
+        &self,
+        handler: FunctionValue<'a>,
+        arguments: &[BasicValueEnum<'a>],
+        expression: &AstStatement,
+        unit: &CompilationUnit,
+        location_name: &str,
+    ) {
+        let builder = &self.llvm.builder;
+        let (line, column) = expression
+            .get_location()
+            .get_line_and_column(&unit.new_lines);
+        let location_text = builder
+            .build_global_string_ptr(
+                &format!("{}:{}:{}", unit.file_name, line, column),
+                location_name,
+            )
+            .as_pointer_value();
+        let arguments = arguments
+            .iter()
+            .copied()
+            .chain(std::iter::once(location_text.as_basic_value_enum()))
+            .zip(handler.get_type().get_param_types())
+            .map(
+                |(argument, parameter_type)| match (argument, parameter_type) {
+                    (BasicValueEnum::IntValue(value), BasicTypeEnum::IntType(int_type)) => builder
+                        .build_int_cast(value, int_type, "")
+                        .as_basic_value_enum()
+                        .into(),
+                    (
+                        BasicValueEnum::PointerValue(value),
+                        BasicTypeEnum::PointerType(pointer_type),
+                    ) => builder
+                        .build_pointer_cast(value, pointer_type, "")
+                        .as_basic_value_enum()
+                        .into(),
+                    (argument, _) => argument.into(),
+                },
+            )
+            .collect::<Vec<BasicMetadataValueEnum>>();
+        builder.build_call(handler, &arguments, "");
+    }
+
     /// generates a gep statement for a array-reference with an optional qualifier
     ///
     /// - `qualifier` an optional qualifier for a reference (e.g. myStruct.x[2] where myStruct is the qualifier for x)
@@ -1232,6 +2304,335 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
         }
     }
 
+    /// generates the integer division (`/`) or remainder (`MOD`) of the given values. Both
+    /// truncate towards zero, so the remainder has the sign of the dividend. Unsigned values
+    /// are divided as unsigned values. A division by zero aborts the program or results in
+    /// zero if division checks are generated.
+    fn generate_int_division(
+        &self,
+        operator: &Operator,
+        dividend: IntValue<'a>,
+        divisor: IntValue<'a>,
+        is_signed: bool,
+        expression: &AstStatement,
+    ) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let division_checks = self.function_context.and_then(|it| it.division_checks);
+        let result = match division_checks {
+            None => self.build_int_division(operator, dividend, divisor, is_signed),
+            Some(DivisionCheckMode::Trap) => {
+                let trap = self
+                    .llvm_index
+                    .find_associated_implementation(RANGE_CHECK_TRAP_FN)
+                    .ok_or_else(|| {
+                        Diagnostic::codegen_error(
+                            "Cannot find the trap for division checks",
+                            expression.get_location(),
+                        )
+                    })?;
+                let function = self.get_function_context(expression)?.function;
+                let is_zero = builder.build_int_compare(
+                    IntPredicate::EQ,
+                    divisor,
+                    divisor.get_type().const_zero(),
+                    "is_zero",
+                );
+                let division_by_zero = self
+                    .llvm
+                    .context
+                    .append_basic_block(function, "division_by_zero");
+                let division = self.llvm.context.append_basic_block(function, "division");
+                builder.build_conditional_branch(is_zero, division_by_zero, division);
+
+                builder.position_at_end(division_by_zero);
+                builder.build_call(trap, &[], "");
+                builder.build_unreachable();
+
+                builder.position_at_end(division);
+                self.build_int_division(operator, dividend, divisor, is_signed)
+            }
+            Some(mode @ (DivisionCheckMode::Zero | DivisionCheckMode::Fault)) => {
+                //divide by one instead of zero and replace the result with zero afterwards
+                let zero = divisor.get_type().const_zero();
+                let is_zero = builder.build_int_compare(IntPredicate::EQ, divisor, zero, "is_zero");
+                if mode == DivisionCheckMode::Fault {
+                    self.generate_division_fault(is_zero, expression)?;
+                }
+                let divisor = builder
+                    .build_select(is_zero, divisor.get_type().const_int(1, false), divisor, "")
+                    .into_int_value();
+                let result = self.build_int_division(operator, dividend, divisor, is_signed);
+                builder
+                    .build_select(is_zero, zero, result, "")
+                    .into_int_value()
+            }
+        };
+        Ok(result.as_basic_value_enum())
+    }
+
+    /// generates the addition, subtraction or multiplication of the given values as a call to the
+    /// overflow intrinsic of the function's overflow mode. An overflowing result aborts the
+    /// program (`Trap`) or is limited to the bounds of the values' type (`Saturate`). Returns
+    /// None if the arithmetic wraps around, it is generated as plain instructions then.
+    fn generate_int_arithmetic_with_overflow(
+        &self,
+        operator: &Operator,
+        left: IntValue<'a>,
+        right: IntValue<'a>,
+        is_signed: bool,
+        expression: &AstStatement,
+    ) -> Result<Option<BasicValueEnum<'a>>, Diagnostic> {
+        let overflow = self
+            .function_context
+            .map_or(OverflowMode::Wrap, |it| it.overflow);
+        let size = left.get_type().get_bit_width();
+        let intrinsic_name =
+            match codegen::get_overflow_intrinsic_name(operator, is_signed, size, overflow) {
+                Some(name) => name,
+                None => return Ok(None),
+            };
+        let intrinsic = self
+            .llvm_index
+            .find_associated_implementation(&intrinsic_name)
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!("Cannot find the overflow intrinsic {}", intrinsic_name),
+                    expression.get_location(),
+                )
+            })?;
+        let builder = &self.llvm.builder;
+        let mut arguments: Vec<BasicMetadataValueEnum> = vec![left.into(), right.into()];
+        if operator == &Operator::Multiplication && overflow == OverflowMode::Saturate {
+            //the operands of the fixed-point multiplication have no fractional bits
+            arguments.push(self.llvm.context.i32_type().const_zero().into());
+        }
+        let call_error = || {
+            Diagnostic::codegen_error(
+                &format!("Cannot generate a call to {}", intrinsic_name),
+                expression.get_location(),
+            )
+        };
+        let result = builder
+            .build_call(intrinsic, &arguments, "tmpVar")
+            .try_as_basic_value()
+            .left()
+            .ok_or_else(call_error)?;
+        if overflow != OverflowMode::Trap {
+            return Ok(Some(result));
+        }
+
+        //the result is returned with a flag telling whether it overflowed
+        let result = result.into_struct_value();
+        let value = builder
+            .build_extract_value(result, 0, "tmpVar")
+            .ok_or_else(call_error)?;
+        let is_overflow = builder
+            .build_extract_value(result, 1, "is_overflow")
+            .ok_or_else(call_error)?
+            .into_int_value();
+        let trap = self
+            .llvm_index
+            .find_associated_implementation(RANGE_CHECK_TRAP_FN)
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    "Cannot find the trap for overflow checks",
+                    expression.get_location(),
+                )
+            })?;
+        let function = self.get_function_context(expression)?.function;
+        let overflowed = self.llvm.context.append_basic_block(function, "overflow");
+        let no_overflow = self
+            .llvm
+            .context
+            .append_basic_block(function, "no_overflow");
+        builder.build_conditional_branch(is_overflow, overflowed, no_overflow);
+
+        builder.position_at_end(overflowed);
+        builder.build_call(trap, &[], "");
+        builder.build_unreachable();
+
+        builder.position_at_end(no_overflow);
+        Ok(Some(value))
+    }
+
+    /// calls the division fault handler if `is_zero` is true, the generated code continues in a
+    /// new block if the handler returns
+    fn generate_division_fault(
+        &self,
+        is_zero: IntValue<'a>,
+        expression: &AstStatement,
+    ) -> Result<(), Diagnostic> {
+        let function_context = self.get_function_context(expression)?;
+        let handler = self
+            .llvm_index
+            .find_associated_implementation(DIVISION_FAULT_FN)
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    "Cannot find the handler for division checks",
+                    expression.get_location(),
+                )
+            })?;
+        let builder = &self.llvm.builder;
+        let division_by_zero = self
+            .llvm
+            .context
+            .append_basic_block(function_context.function, "division_by_zero");
+        let division = self
+            .llvm
+            .context
+            .append_basic_block(function_context.function, "division");
+        builder.build_conditional_branch(is_zero, division_by_zero, division);
+
+        builder.position_at_end(division_by_zero);
+        self.generate_fault_handler_call(
+            handler,
+            &[],
+            expression,
+            function_context.unit,
+            "division_location",
+        );
+        builder.build_unconditional_branch(division);
+
+        builder.position_at_end(division);
+        Ok(())
+    }
+
+    /// builds the signed or unsigned division or remainder of the given values
+    fn build_int_division(
+        &self,
+        operator: &Operator,
+        dividend: IntValue<'a>,
+        divisor: IntValue<'a>,
+        is_signed: bool,
+    ) -> IntValue<'a> {
+        let builder = &self.llvm.builder;
+        match (operator, is_signed) {
+            (Operator::Modulo, true) => builder.build_int_signed_rem(dividend, divisor, "tmpVar"),
+            (Operator::Modulo, false) => {
+                builder.build_int_unsigned_rem(dividend, divisor, "tmpVar")
+            }
+            (_, true) => builder.build_int_signed_div(dividend, divisor, "tmpVar"),
+            (_, false) => builder.build_int_unsigned_div(dividend, divisor, "tmpVar"),
+        }
+    }
+
+    /// generates the exponentiation `base ** exponent` of integers by squaring the base in a loop,
+    /// the result wraps around on overflow. A negative exponent results in the truncated
+    /// reciprocal, which is only non-zero for the bases 1 and -1.
+    fn generate_int_power(
+        &self,
+        base: IntValue<'a>,
+        exponent: IntValue<'a>,
+        is_signed: bool,
+        expression: &AstStatement,
+    ) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let function = self.get_function_context(expression)?.function;
+        let int_type = base.get_type();
+        let zero = int_type.const_zero();
+        let one = int_type.const_int(1, false);
+        //the loop raises the base to the power of the exponent's absolute value
+        let (exponent, is_negative) = if is_signed {
+            let is_negative = builder.build_int_compare(IntPredicate::SLT, exponent, zero, "");
+            let negated = builder.build_int_neg(exponent, "");
+            let exponent = builder.build_select(is_negative, negated, exponent, "");
+            (exponent.into_int_value(), Some(is_negative))
+        } else {
+            (exponent, None)
+        };
+
+        let entry_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
+        let condition_block = self
+            .llvm
+            .context
+            .append_basic_block(function, "power_condition");
+        let body_block = self.llvm.context.append_basic_block(function, "power_body");
+        let continue_block = self
+            .llvm
+            .context
+            .append_basic_block(function, "power_continue");
+        builder.build_unconditional_branch(condition_block);
+
+        //multiply the result with the squared factor for every set bit of the exponent
+        builder.position_at_end(condition_block);
+        let result = builder.build_phi(int_type, "result");
+        let factor = builder.build_phi(int_type, "factor");
+        let remaining = builder.build_phi(int_type, "remaining");
+        let remaining_value = remaining.as_basic_value().into_int_value();
+        let is_done = builder.build_int_compare(IntPredicate::EQ, remaining_value, zero, "");
+        builder.build_conditional_branch(is_done, continue_block, body_block);
+
+        builder.position_at_end(body_block);
+        let result_value = result.as_basic_value().into_int_value();
+        let factor_value = factor.as_basic_value().into_int_value();
+        let is_odd = builder.build_int_truncate(remaining_value, self.llvm.context.bool_type(), "");
+        let multiplied = builder.build_int_mul(result_value, factor_value, "");
+        let next_result = builder
+            .build_select(is_odd, multiplied, result_value, "")
+            .into_int_value();
+        let next_factor = builder.build_int_mul(factor_value, factor_value, "");
+        let next_remaining = builder.build_right_shift(remaining_value, one, false, "");
+        builder.build_unconditional_branch(condition_block);
+
+        result.add_incoming(&[(&one, entry_block), (&next_result, body_block)]);
+        factor.add_incoming(&[(&base, entry_block), (&next_factor, body_block)]);
+        remaining.add_incoming(&[(&exponent, entry_block), (&next_remaining, body_block)]);
+
+        builder.position_at_end(continue_block);
+        let is_negative = match is_negative {
+            Some(is_negative) => is_negative,
+            None => return Ok(result_value.as_basic_value_enum()),
+        };
+        //1 ** -n is 1, (-1) ** -n is the same as (-1) ** n and any other base results in 0
+        let minus_one = int_type.const_all_ones();
+        let is_one = builder.build_int_compare(IntPredicate::EQ, base, one, "");
+        let is_minus_one = builder.build_int_compare(IntPredicate::EQ, base, minus_one, "");
+        let reciprocal = builder
+            .build_select(is_minus_one, result_value, zero, "")
+            .into_int_value();
+        let reciprocal = builder
+            .build_select(is_one, one, reciprocal, "")
+            .into_int_value();
+        Ok(builder.build_select(is_negative, reciprocal, result_value, "tmpVar"))
+    }
+
+    /// generates a call to the float function (an LLVM intrinsic or a libm function) that
+    /// implements the given numeric function (or `EXPT`) for the given float type
+    fn generate_float_function_call(
+        &self,
+        function_name: &str,
+        float_type: &DataTypeInformation,
+        arguments: &[BasicValueEnum<'a>],
+        expression: &AstStatement,
+    ) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        let intrinsic_name = codegen::get_float_function_name(function_name, float_type.get_size())
+            .unwrap_or(function_name);
+        let intrinsic = self
+            .llvm_index
+            .find_associated_implementation(intrinsic_name)
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!("Cannot find the float function {}", intrinsic_name),
+                    expression.get_location(),
+                )
+            })?;
+        let arguments = arguments
+            .iter()
+            .map(|it| BasicMetadataValueEnum::from(*it))
+            .collect::<Vec<_>>();
+        self.llvm
+            .builder
+            .build_call(intrinsic, &arguments, "tmpVar")
+            .try_as_basic_value()
+            .left()
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!("Cannot generate a call to {}", intrinsic_name),
+                    expression.get_location(),
+                )
+            })
+    }
+
     /// generates the result of an int/bool binary-expression (+, -, *, /, %, ==)
     ///
     /// - `operator` the binary operator
@@ -1456,22 +2857,22 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
                 hour,
                 min,
                 sec,
-                milli,
+                nano,
                 location,
                 ..
             } => self.create_const_int(
-                calculate_date_time(*year, *month, *day, *hour, *min, *sec, *milli)
+                calculate_date_time(*year, *month, *day, *hour, *min, *sec, *nano)
                     .map_err(|op| Diagnostic::codegen_error(op.as_str(), location.clone()))?,
             ),
             AstStatement::LiteralTimeOfDay {
                 hour,
                 min,
                 sec,
-                milli,
+                nano,
                 location,
                 ..
             } => self.create_const_int(
-                calculate_date_time(1970, 1, 1, *hour, *min, *sec, *milli)
+                calculate_date_time(1970, 1, 1, *hour, *min, *sec, *nano)
                     .map_err(|op| Diagnostic::codegen_error(op.as_str(), location.clone()))?,
             ),
             AstStatement::LiteralTime {
@@ -1626,11 +3027,21 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
         assignments: &AstStatement,
         declaration_location: &SourceRange,
     ) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        let type_info = self.get_type_hint_info_for(assignments)?;
+        if type_info.is_union() {
+            return Err(Diagnostic::codegen_error(
+                &format!(
+                    "Cannot initialize union {} with a literal, assign one of its members instead",
+                    type_info.get_name()
+                ),
+                assignments.get_location(),
+            ));
+        }
         if let DataTypeInformation::Struct {
             name: struct_name,
             member_names,
             ..
-        } = self.get_type_hint_info_for(assignments)?
+        } = type_info
         {
             let mut uninitialized_members: HashSet<&str> =
                 member_names.iter().map(|it| it.as_str()).collect();
@@ -1707,9 +3118,31 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
                 let ordered_values: Vec<BasicValueEnum<'a>> =
                     member_values.iter().map(|(_, v)| *v).collect();
 
-                return Ok(struct_type
-                    .const_named_struct(ordered_values.as_slice())
-                    .as_basic_value_enum());
+                if ordered_values
+                    .iter()
+                    .all(|it| it.as_instruction_value().is_none())
+                {
+                    return Ok(struct_type
+                        .const_named_struct(ordered_values.as_slice())
+                        .as_basic_value_enum());
+                }
+
+                //some members are runtime-values (e.g. variables), so the struct is built member by member
+                let struct_value = ordered_values.iter().enumerate().try_fold(
+                    struct_type.get_undef().as_aggregate_value_enum(),
+                    |struct_value, (index, value)| {
+                        self.llvm
+                            .builder
+                            .build_insert_value(struct_value, *value, index as u32, "")
+                            .ok_or_else(|| {
+                                Diagnostic::codegen_error(
+                                    &format!("Cannot generate member {} of {}", index, struct_name),
+                                    assignments.get_location(),
+                                )
+                            })
+                    },
+                )?;
+                return Ok(struct_value.into_struct_value().as_basic_value_enum());
             } else {
                 return Err(Diagnostic::codegen_error(
                     &format!(
@@ -1850,7 +3283,7 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
         right: &AstStatement,
     ) -> Result<BasicValueEnum<'a>, Diagnostic> {
         match operator {
-            Operator::And | Operator::Or => {
+            Operator::And | Operator::Or | Operator::AndThen | Operator::OrElse => {
                 self.generate_bool_short_circuit_expression(operator, left, right)
             }
             Operator::Equal => Ok(self
@@ -1902,14 +3335,14 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
                 .as_basic_value_enum()),
             _ => Err(Diagnostic::codegen_error(
                 format!("illegal boolean expresspion for operator {:}", operator).as_str(),
-                (left.get_location().get_start()..right.get_location().get_end()).into(),
+                left.get_location().span(&right.get_location()),
             )),
         }
     }
 
     /// generates a phi-expression (&& or || expression) with respect to short-circuit evaluation
     ///
-    /// - `operator` AND / OR
+    /// - `operator` AND / OR / AND_THEN / OR_ELSE
     /// - `left` the left side of the expression as an i1 value
     /// - `right` the right side of an expression as an i1 value
     pub fn generate_bool_short_circuit_expression(
@@ -1929,8 +3362,12 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
         //Compare left to 0
 
         match operator {
-            Operator::Or => builder.build_conditional_branch(lhs, continue_branch, right_branch),
-            Operator::And => builder.build_conditional_branch(lhs, right_branch, continue_branch),
+            Operator::Or | Operator::OrElse => {
+                builder.build_conditional_branch(lhs, continue_branch, right_branch)
+            }
+            Operator::And | Operator::AndThen => {
+                builder.build_conditional_branch(lhs, right_branch, continue_branch)
+            }
             _ => {
                 return Err(Diagnostic::codegen_error(
                     &format!("Cannot generate phi-expression for operator {:}", operator),
@@ -2078,6 +3515,49 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
             })
     }
 
+    /// stores the instance of the given `right_statement` together with the vtable of its type
+    /// for the interface `left_type` into the interface variable `left`
+    fn generate_interface_store(
+        &self,
+        left_type: &DataTypeInformation,
+        right_type: &DataTypeInformation,
+        right_statement: &AstStatement,
+        left: PointerValue,
+    ) -> Result<(), Diagnostic> {
+        let builder = &self.llvm.builder;
+        let byte_pointer = self.llvm.context.i8_type().ptr_type(AddressSpace::Generic);
+        let vtable = self
+            .llvm_index
+            .find_global_value(&get_vtable_name(right_type, left_type))
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    &format!(
+                        "Cannot find the vtable of {} for {}",
+                        right_type.get_name(),
+                        left_type.get_name()
+                    ),
+                    right_statement.get_location(),
+                )
+            })?;
+        let instance = self.generate_element_pointer(right_statement)?;
+
+        let instance_member = builder
+            .build_struct_gep(left, 0, typesystem::INTERFACE_INSTANCE_MEMBER)
+            .expect(INTERNAL_LLVM_ERROR);
+        builder.build_store(
+            instance_member,
+            builder.build_bitcast(instance, byte_pointer, ""),
+        );
+        let vtable_member = builder
+            .build_struct_gep(left, 1, typesystem::INTERFACE_VTABLE_MEMBER)
+            .expect(INTERNAL_LLVM_ERROR);
+        builder.build_store(
+            vtable_member,
+            builder.build_bitcast(vtable.as_pointer_value(), byte_pointer, ""),
+        );
+        Ok(())
+    }
+
     pub fn generate_store(
         &self,
         left_type: &DataTypeInformation,
@@ -2089,6 +3569,23 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
             .get_type_or_void(right_statement, self.index)
             .get_type_information();
 
+        //an instance assigned to an interface is stored with the vtable of its type
+        if let (
+            DataTypeInformation::Struct {
+                source: StructSource::Pou(PouType::Interface),
+                ..
+            },
+            DataTypeInformation::Struct {
+                source: StructSource::Pou(source_type),
+                ..
+            },
+        ) = (left_type, right_type)
+        {
+            if *source_type != PouType::Interface {
+                return self.generate_interface_store(left_type, right_type, right_statement, left);
+            }
+        }
+
         //Special string handling
         if left_type.is_string() && right_type.is_string()
         //string-literals are also generated as global constant variables so we can always assume that
@@ -2114,7 +3611,12 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
             };
             let target_size = self.get_string_size(left_type, right_statement.get_location())?; //we report error on parameter :-/
             let value_size = self.get_string_size(right_type, right_statement.get_location())?;
-            let size = std::cmp::min(target_size - 1, value_size) as i64;
+            //the sizes are given in characters, memcpy needs the number of bytes
+            let bytes_per_char = match left_type {
+                DataTypeInformation::String { encoding, .. } => encoding.get_bytes_per_char(),
+                _ => 1,
+            } as i64;
+            let size = std::cmp::min(target_size - 1, value_size) * bytes_per_char;
             let align_left = left_type.get_alignment();
             let align_right = right_type.get_alignment();
             self.llvm
@@ -2134,13 +3636,80 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
             //     self.llvm.i32_type().const_int(size + 1, false),
             //     "null_terminator",
             // ), self.get_null_terminator(left_type, left_statement.get_location()));
+        } else if matches!(
+            left_type,
+            DataTypeInformation::Array { .. } | DataTypeInformation::Struct { .. }
+        ) && self.is_addressable(right_statement)
+        {
+            self.generate_aggregate_copy(left, right_statement)?;
         } else {
             let expression = self.generate_expression(right_statement)?;
-            self.llvm.builder.build_store(left, expression);
+            self.llvm
+                .builder
+                .build_store(left, self.cast_to_target_pointer(expression, left));
         }
         Ok(())
     }
 
+    /// copies the array or struct referenced by `right_statement` to `left` using a memcpy of
+    /// the size of the target's type. Instances of a derived class start with their super class's
+    /// instance, so they can be copied to a variable of their super class the same way.
+    fn generate_aggregate_copy(
+        &self,
+        left: PointerValue<'a>,
+        right_statement: &AstStatement,
+    ) -> Result<(), Diagnostic> {
+        let right = self.generate_element_pointer(right_statement)?;
+        let size = BasicTypeEnum::try_from(left.get_type().get_element_type())
+            .ok()
+            .and_then(|it| it.size_of())
+            .ok_or_else(|| {
+                Diagnostic::codegen_error(
+                    "Cannot determine the size of the assigned value",
+                    right_statement.get_location(),
+                )
+            })?;
+        self.llvm
+            .builder
+            .build_memcpy(left, 1, right, 1, size)
+            .map_err(|err| Diagnostic::codegen_error(err, right_statement.get_location()))?;
+        Ok(())
+    }
+
+    /// returns true if the given statement references a variable (or an element of it) that
+    /// can be copied from its address instead of loading its value
+    fn is_addressable(&self, statement: &AstStatement) -> bool {
+        match statement {
+            AstStatement::QualifiedReference { elements, .. } => elements
+                .last()
+                .map_or(false, |last| self.is_addressable(last)),
+            AstStatement::Reference { .. } => matches!(
+                self.annotations.get(statement),
+                Some(StatementAnnotation::Variable { .. })
+            ),
+            AstStatement::ArrayAccess { .. } | AstStatement::PointerAccess { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// casts the given pointer to the pointer type stored in `target` if they differ
+    /// (e.g. a pointer to a derived instance stored in a pointer to its super class)
+    fn cast_to_target_pointer(
+        &self,
+        value: BasicValueEnum<'a>,
+        target: PointerValue<'a>,
+    ) -> BasicValueEnum<'a> {
+        match target.get_type().get_element_type() {
+            AnyTypeEnum::PointerType(pointer_type)
+                if value.is_pointer_value()
+                    && value.get_type() != pointer_type.as_basic_type_enum() =>
+            {
+                self.llvm.builder.build_bitcast(value, pointer_type, "")
+            }
+            _ => value,
+        }
+    }
+
     fn get_string_size(
         &self,
         datatype: &DataTypeInformation,
@@ -2159,14 +3728,20 @@ impl<'a, 'b> ExpressionCodeGenerator<'a, 'b> {
 }
 
 /// calculates the seconds in the given days, hours minutes and seconds
-fn calculate_dhm_time_seconds(day: f64, hour: f64, min: f64, sec: f64) -> f64 {
+pub(crate) fn calculate_dhm_time_seconds(day: f64, hour: f64, min: f64, sec: f64) -> f64 {
     let hours = day * 24_f64 + hour;
     let mins = hours * 60_f64 + min;
     mins * 60_f64 + sec
 }
 
 /// calculates the nanos in the given seconds, millis, micros and nano/**
-fn calculate_time_nano(negative: bool, sec: f64, milli: f64, micro: f64, nano: u32) -> i64 {
+pub(crate) fn calculate_time_nano(
+    negative: bool,
+    sec: f64,
+    milli: f64,
+    micro: f64,
+    nano: u32,
+) -> i64 {
     let millis = sec * 1000_f64 + milli;
     let micro = millis * 1000_f64 + micro;
     let nano = micro * 1000_f64 + nano as f64;
@@ -2180,8 +3755,8 @@ fn calculate_time_nano(negative: bool, sec: f64, milli: f64, micro: f64, nano: u
     }
 }
 
-/// calculates the milliseconds since 1970-01-01-00:00:00 for the given
-/// point in time
+/// calculates the nanoseconds since 1970-01-01-00:00:00 for the given
+/// point in time (the same resolution as `TIME`)
 fn calculate_date_time(
     year: i32,
     month: u32,
@@ -2189,17 +3764,17 @@ fn calculate_date_time(
     hour: u32,
     min: u32,
     sec: u32,
-    milli: u32,
+    nano: u32,
 ) -> Result<i64, String> {
     if let LocalResult::Single(date_time) = Utc
         .ymd_opt(year, month, day)
-        .and_hms_milli_opt(hour, min, sec, milli)
+        .and_hms_nano_opt(hour, min, sec, nano)
     {
-        return Ok(date_time.timestamp_millis());
+        return Ok(date_time.timestamp_nanos());
     }
     Err(format!(
-        "Invalid Date {}-{}-{}-{}:{}:{}.{}",
-        year, month, day, hour, min, sec, milli
+        "Invalid Date {}-{}-{}-{}:{}:{}.{:09}",
+        year, month, day, hour, min, sec, nano
     ))
 }
 
@@ -2216,3 +3791,13 @@ pub fn to_i1<'a>(value: IntValue<'a>, builder: &Builder<'a>) -> IntValue<'a> {
         value
     }
 }
+
+/// returns true if the given call operator explicitly calls a method of the super class (`SUPER^.m`)
+fn is_super_call(operator: &AstStatement) -> bool {
+    if let AstStatement::QualifiedReference { elements, .. } = operator {
+        if let Some(AstStatement::PointerAccess { reference, .. }) = elements.first() {
+            return matches!(reference.as_ref(), AstStatement::Reference { name, .. } if name.eq_ignore_ascii_case(ast::SUPER));
+        }
+    }
+    false
+}
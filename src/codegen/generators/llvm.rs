@@ -6,7 +6,7 @@ use inkwell::{
     builder::Builder,
     context::Context,
     module::{Linkage, Module},
-    types::{BasicTypeEnum, StringRadix},
+    types::{BasicType, BasicTypeEnum, StringRadix},
     values::{BasicValue, BasicValueEnum, GlobalValue, IntValue, PointerValue},
     AddressSpace,
 };
@@ -149,6 +149,24 @@ impl<'a> Llvm<'a> {
             })
     }
 
+    /// creates a pointervalue that points to a member of a union
+    ///
+    /// - `pointer_to_union_instance` a pointer to the union
+    /// - `member_type` the type of the member we want a pointer to
+    /// - `name` the name of the temporary variable
+    pub fn get_member_pointer_from_union(
+        &self,
+        pointer_to_union_instance: PointerValue<'a>,
+        member_type: BasicTypeEnum<'a>,
+        name: &str,
+    ) -> PointerValue<'a> {
+        self.builder.build_pointer_cast(
+            pointer_to_union_instance,
+            member_type.ptr_type(AddressSpace::Generic),
+            name,
+        )
+    }
+
     /// loads the value behind the given pointer
     ///
     /// - `lvalue` the pointer and it's datatype
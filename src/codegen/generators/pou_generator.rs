@@ -7,10 +7,11 @@ use super::{
 };
 use crate::{
     ast::Pou,
-    codegen::llvm_index::LlvmTypedIndex,
+    codegen::{debug::DebugBuilder, llvm_index::LlvmTypedIndex},
     diagnostics::{Diagnostic, INTERNAL_LLVM_ERROR},
     index::{self, ImplementationType},
     resolver::AstAnnotations,
+    CodegenOptions, RangeCheckMode,
 };
 
 /// The pou_generator contains functions to generate the code for POUs (PROGRAM, FUNCTION, FUNCTION_BLOCK)
@@ -21,22 +22,27 @@ use crate::{
 use crate::index::{ImplementationIndexEntry, VariableIndexEntry};
 
 use crate::{
-    ast::{Implementation, PouType, SourceRange},
-    index::Index,
+    ast::{CompilationUnit, Implementation, PouType, SourceRange, SUPER, THIS},
+    index::{Index, VariableType},
+    typesystem::{DataTypeInformation, INTERFACE_INSTANCE_MEMBER, INTERFACE_VTABLE_MEMBER},
 };
 use inkwell::types::{BasicType, StructType};
 use inkwell::{
-    module::Module,
+    builder::Builder,
+    module::{Linkage, Module},
     types::{BasicMetadataTypeEnum, BasicTypeEnum, FunctionType},
-    values::{BasicValueEnum, FunctionValue},
+    values::{BasicValue, BasicValueEnum, CallableValue, FunctionValue, PointerValue},
     AddressSpace,
 };
+use std::convert::TryFrom;
 
 pub struct PouGenerator<'ink, 'cg> {
     llvm: Llvm<'ink>,
     index: &'cg Index,
     annotations: &'cg AstAnnotations,
     llvm_index: &'cg LlvmTypedIndex<'ink>,
+    options: &'cg CodegenOptions,
+    debug: Option<&'cg DebugBuilder<'ink>>,
 }
 
 /// Creates opaque implementations for all callable items in the index
@@ -49,7 +55,8 @@ pub fn generate_implementation_stubs<'ink>(
     types_index: &LlvmTypedIndex<'ink>,
 ) -> Result<LlvmTypedIndex<'ink>, Diagnostic> {
     let mut llvm_index = LlvmTypedIndex::default();
-    let pou_generator = PouGenerator::new(llvm, index, annotations, types_index);
+    let options = CodegenOptions::default();
+    let pou_generator = PouGenerator::new(llvm, index, annotations, types_index, &options, None);
     for (name, implementation) in index.get_implementations() {
         let type_info = index.get_type_information_or_void(implementation.get_type_name());
         if !type_info.is_generic() {
@@ -127,21 +134,183 @@ pub fn generate_global_constants_for_pou_members<'ink>(
     Ok(local_llvm_index)
 }
 
+/// Generates a vtable for every interface implemented by a class or function block and
+/// defines the vtables of all polymorphic classes and function blocks.
+/// An interface's vtable holds a thunk for every method of the interface (in the order of their
+/// declaration), a class' vtable holds a thunk for every method that can be overridden
+/// (see `Index::get_virtual_methods`).
+/// A thunk forwards the call to the implementing method, copying the parameters between the
+/// declared method's struct and the (possibly bigger) struct of the implementing method.
+/// Returns a new LLVM index with the vtables to be merged with the parent codegen index.
+pub fn generate_vtables<'ink>(
+    module: &Module<'ink>,
+    llvm: Llvm<'ink>,
+    index: &Index,
+    annotations: &AstAnnotations,
+    llvm_index: &LlvmTypedIndex<'ink>,
+) -> Result<LlvmTypedIndex<'ink>, Diagnostic> {
+    let mut local_llvm_index = LlvmTypedIndex::default();
+    let options = CodegenOptions::default();
+    let pou_generator = PouGenerator::new(llvm, index, annotations, llvm_index, &options, None);
+    for class in index.get_pou_types().values() {
+        //the vtables of classes are declared with the data types
+        if let Some(vtable) = llvm_index.find_global_value(&get_class_vtable_name(class.get_name()))
+        {
+            let thunks = index
+                .get_virtual_methods(class.get_name())
+                .iter()
+                .map(|method| pou_generator.generate_thunk(module, class.get_name(), method))
+                .collect::<Result<Vec<_>, Diagnostic>>()?;
+            let byte_pointer = pou_generator
+                .llvm
+                .context
+                .i8_type()
+                .ptr_type(AddressSpace::Generic);
+            vtable.set_initializer(&byte_pointer.const_array(&thunks));
+        }
+
+        for interface in index.get_all_implemented_interfaces(class.get_name()) {
+            let interface_type = match index.find_effective_type(interface) {
+                Some(it) if index.is_interface(interface) => it,
+                _ => continue,
+            };
+            let thunks = index
+                .get_interface_methods(interface)
+                .iter()
+                .map(|method| pou_generator.generate_thunk(module, class.get_name(), method))
+                .collect::<Result<Vec<_>, Diagnostic>>()?;
+
+            let byte_pointer = pou_generator
+                .llvm
+                .context
+                .i8_type()
+                .ptr_type(AddressSpace::Generic);
+            let vtable_type = byte_pointer.array_type(thunks.len() as u32);
+            let vtable_value = byte_pointer.const_array(&thunks);
+            let vtable = pou_generator
+                .llvm
+                .create_global_variable(
+                    module,
+                    &format!(
+                        "__vtable_{}_{}",
+                        class.get_name(),
+                        interface_type.get_name()
+                    ),
+                    vtable_type.as_basic_type_enum(),
+                )
+                .make_constant()
+                .set_initial_value(Some(vtable_value.into()), vtable_type.into());
+            //every module generates its own vtables
+            vtable.set_linkage(Linkage::Private);
+            local_llvm_index.associate_global(
+                &get_vtable_name(
+                    class.get_type_information(),
+                    interface_type.get_type_information(),
+                ),
+                vtable,
+            )?;
+        }
+    }
+    Ok(local_llvm_index)
+}
+
+/// returns the name of the vtable of the given polymorphic class or function block
+pub fn get_class_vtable_name(class: &str) -> String {
+    format!("__vtable_{}", class)
+}
+
+/// returns the position of the vtable in the instances of the given topmost class of a hierarchy,
+/// the vtable follows the declared members
+pub fn get_vtable_location(index: &Index, class: &str) -> u32 {
+    index
+        .get_container_members(class)
+        .iter()
+        .filter(|it| !it.is_temp() && !it.is_return())
+        .count() as u32
+}
+
+/// returns the name the vtable of the given class for the given interface is associated with
+pub fn get_vtable_name(class: &DataTypeInformation, interface: &DataTypeInformation) -> String {
+    format!("__vtable_{}_{}", class.get_name(), interface.get_name())
+}
+
+/// returns the pointers to the parameters (inputs, outputs and inouts) of the struct `from`
+/// of the pou `from.0` and the parameters with the same name in the struct `to` of the pou `to.0`
+/// together with the parameter's variable type
+pub fn get_parameter_pointers<'ink>(
+    builder: &Builder<'ink>,
+    index: &Index,
+    from: (&str, PointerValue<'ink>),
+    to: (&str, PointerValue<'ink>),
+) -> Vec<(VariableType, PointerValue<'ink>, PointerValue<'ink>)> {
+    let ((from_pou, from_struct), (to_pou, to_struct)) = (from, to);
+    index
+        .get_container_members(from_pou)
+        .into_iter()
+        .filter(|it| {
+            matches!(
+                it.get_variable_type(),
+                VariableType::Input | VariableType::Output | VariableType::InOut
+            )
+        })
+        .filter_map(|it| {
+            let from = get_struct_member_index(index, from_pou, it.get_name())?;
+            let to = get_struct_member_index(index, to_pou, it.get_name())?;
+            let from = builder
+                .build_struct_gep(from_struct, from, it.get_name())
+                .expect(INTERNAL_LLVM_ERROR);
+            let to = builder
+                .build_struct_gep(to_struct, to, it.get_name())
+                .expect(INTERNAL_LLVM_ERROR);
+            Some((it.get_variable_type(), from, to))
+        })
+        .collect()
+}
+
+/// returns the position of the given member in the generated struct of its container,
+/// temp and return variables are not part of the struct
+fn get_struct_member_index(index: &Index, container_name: &str, member_name: &str) -> Option<u32> {
+    index
+        .get_container_members(container_name)
+        .iter()
+        .filter(|it| !it.is_temp() && !it.is_return())
+        .position(|it| it.get_name().eq_ignore_ascii_case(member_name))
+        .map(|it| it as u32)
+}
+
 impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
     /// creates a new PouGenerator
     ///
     /// the PouGenerator needs a mutable index to register the generated pou
+    /// - `options` the checks and the overflow behavior of the generated code
     pub fn new(
         llvm: Llvm<'ink>,
         index: &'cg Index,
         annotations: &'cg AstAnnotations,
         llvm_index: &'cg LlvmTypedIndex<'ink>,
+        options: &'cg CodegenOptions,
+        debug: Option<&'cg DebugBuilder<'ink>>,
     ) -> PouGenerator<'ink, 'cg> {
         PouGenerator {
             llvm,
             index,
             annotations,
             llvm_index,
+            options,
+            debug,
+        }
+    }
+
+    /// returns the check to generate for sub-range assignments without a check function
+    pub fn get_range_checks(&self) -> Option<RangeCheckMode> {
+        self.options.range_checks
+    }
+
+    /// attributes the instructions generated next to the given location, if debug information
+    /// is generated
+    pub fn set_debug_location(&self, function: FunctionValue<'ink>, location: &SourceRange) {
+        if let Some(debug) = self.debug {
+            debug.set_location(&self.llvm.builder, function, location);
         }
     }
 
@@ -170,11 +339,15 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
             );
         }
 
-        let instance_struct_type: StructType = self
-            .llvm_index
-            .get_associated_pou_type(implementation.get_type_name())
-            .map(|it| it.into_struct_type())?;
-        parameters.push(instance_struct_type.ptr_type(AddressSpace::Generic).into());
+        if implementation.is_foreign() {
+            parameters.extend(self.get_foreign_parameter_types(implementation.get_type_name())?);
+        } else {
+            let instance_struct_type: StructType = self
+                .llvm_index
+                .get_associated_pou_type(implementation.get_type_name())
+                .map(|it| it.into_struct_type())?;
+            parameters.push(instance_struct_type.ptr_type(AddressSpace::Generic).into());
+        }
 
         let return_type = match global_index.find_return_type(implementation.get_type_name()) {
             Some(r_type) => Some(self.llvm_index.get_associated_type(r_type.get_name())?),
@@ -193,10 +366,37 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
         Ok(curr_f)
     }
 
+    /// returns the parameter types of a foreign function following the C calling convention:
+    /// inputs are passed by value, aggregate inputs (arrays, strings, structs), outputs and
+    /// in_outs are passed by reference
+    fn get_foreign_parameter_types(
+        &self,
+        pou_name: &str,
+    ) -> Result<Vec<BasicMetadataTypeEnum<'ink>>, Diagnostic> {
+        self.index
+            .get_container_members(pou_name)
+            .into_iter()
+            .filter(|it| it.is_parameter())
+            .map(|it| {
+                let parameter_type = self.llvm_index.get_associated_type(it.get_type_name())?;
+                let by_reference = it.get_variable_type() == VariableType::Output
+                    || (it.get_variable_type() == VariableType::Input
+                        && (parameter_type.is_array_type() || parameter_type.is_struct_type()));
+                Ok(if by_reference {
+                    parameter_type.ptr_type(AddressSpace::Generic).into()
+                } else {
+                    parameter_type.into()
+                })
+            })
+            .collect()
+    }
+
     /// generates a function for the given pou
+    /// generates the body of the given implementation of the given unit
     pub fn generate_implementation(
         &self,
         implementation: &Implementation,
+        unit: &CompilationUnit,
     ) -> Result<(), Diagnostic> {
         let context = self.llvm.context;
         let mut local_index = LlvmTypedIndex::create_child(self.llvm_index);
@@ -216,11 +416,15 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
         //generate the body
         let block = context.append_basic_block(current_function, "entry");
         self.llvm.builder.position_at_end(block);
+        if let Some(debug) = self.debug {
+            debug.create_function(current_function, implementation);
+            self.set_debug_location(current_function, &implementation.location);
+        }
 
         let mut param_index = 0;
 
-        if let PouType::Method { .. } = implementation.pou_type {
-            let class_name = implementation.type_name.split('.').collect::<Vec<&str>>()[0];
+        if let PouType::Method { owner_class } = &implementation.pou_type {
+            let class_name = owner_class.as_str();
             let class_members = self.index.get_container_members(class_name);
             self.generate_local_variable_accessors(
                 param_index,
@@ -232,6 +436,26 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
             param_index += 1;
         }
 
+        // the instance-struct passed as the first parameter is available as THIS
+        if let Some(instance_type) = self
+            .index
+            .find_implementation(&implementation.name)
+            .and_then(ImplementationIndexEntry::get_instance_type_name)
+        {
+            if let Some(instance_ptr) = current_function.get_first_param() {
+                local_index.associate_loaded_local_variable(
+                    instance_type,
+                    THIS,
+                    instance_ptr.into_pointer_value(),
+                )?;
+                self.generate_inherited_member_accessors(
+                    &mut local_index,
+                    instance_type,
+                    instance_ptr.into_pointer_value(),
+                )?;
+            }
+        }
+
         // generate loads for all the parameters
         let pou_members = self.index.get_container_members(&implementation.type_name);
         self.generate_local_variable_accessors(
@@ -245,6 +469,10 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
         let function_context = FunctionContext {
             linking_context: implementation.into(),
             function: current_function,
+            division_checks: self.options.division_checks,
+            bounds_checks: self.options.bounds_checks,
+            unit,
+            overflow: self.options.overflow,
         };
         {
             //if this is a function, we need to initilialize the VAR-variables
@@ -261,20 +489,235 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
                     .collect::<Vec<&VariableIndexEntry>>();
                 self.generate_initialization_of_local_vars(&members, &local_index)?;
             }
-            let statement_gen = StatementCodeGenerator::new(
-                &self.llvm,
-                self.index,
-                self.annotations,
-                self,
-                &local_index,
-                &function_context,
-            );
-            statement_gen.generate_body(&implementation.statements)?
+            let interface_name = implementation
+                .pou_type
+                .get_optional_owner_class()
+                .filter(|it| self.index.is_interface(it));
+            if let Some(interface_name) = interface_name {
+                self.generate_interface_dispatch(
+                    implementation,
+                    current_function,
+                    &local_index,
+                    &interface_name,
+                )?;
+            } else {
+                let statement_gen = StatementCodeGenerator::new(
+                    &self.llvm,
+                    self.index,
+                    self.annotations,
+                    self,
+                    &local_index,
+                    &function_context,
+                );
+                statement_gen.generate_body(&implementation.statements)?
+            }
         }
 
         // generate return statement
         self.generate_return_statement(&function_context, &local_index)?;
+        self.llvm.builder.unset_current_debug_location();
+
+        Ok(())
+    }
+
+    /// generates a thunk forwarding a call of the given method declaration (of an interface or a
+    /// super class) to the class' implementation of the method and returns a pointer to it,
+    /// ready to be stored in a vtable.
+    ///
+    /// The thunk takes an untyped pointer to the class' instance and the declared method's struct.
+    /// It copies the parameters into the implementing method's struct, calls the implementing
+    /// method and copies the outputs back.
+    fn generate_thunk(
+        &self,
+        module: &Module<'ink>,
+        class_name: &str,
+        declaration: &ImplementationIndexEntry,
+    ) -> Result<PointerValue<'ink>, Diagnostic> {
+        let context = self.llvm.context;
+        let builder = &self.llvm.builder;
+        let byte_pointer = context.i8_type().ptr_type(AddressSpace::Generic);
+
+        let declaration_name = declaration.get_call_name();
+        let implementation = self
+            .index
+            .find_method(class_name, Pou::calc_return_name(declaration_name));
+        let (declared_function, implementation, implementing_function) = match (
+            self.llvm_index
+                .find_associated_implementation(declaration_name),
+            implementation,
+        ) {
+            (Some(declared_function), Some(implementation)) => {
+                match self
+                    .llvm_index
+                    .find_associated_implementation(implementation.get_call_name())
+                {
+                    Some(it) => (declared_function, implementation, it),
+                    None => return Ok(byte_pointer.const_null()),
+                }
+            }
+            //a missing method is reported by the validation
+            _ => return Ok(byte_pointer.const_null()),
+        };
+        let implementation_name = implementation.get_call_name();
+
+        let declared_struct_type = self
+            .llvm_index
+            .get_associated_pou_type(declaration_name)?
+            .into_struct_type();
+        let thunk_type = self.create_llvm_function_type(
+            vec![
+                byte_pointer.into(),
+                declared_struct_type.ptr_type(AddressSpace::Generic).into(),
+            ],
+            false,
+            declared_function.get_type().get_return_type(),
+        )?;
+        let thunk = module.add_function(
+            &format!("__{}__{}", class_name, declaration_name),
+            thunk_type,
+            Some(Linkage::Private),
+        );
+        builder.position_at_end(context.append_basic_block(thunk, "entry"));
+
+        //the implementation may be inherited from a super class embedded in the instance
+        let class_type = self.llvm_index.get_associated_type(
+            implementation
+                .get_associated_class_name()
+                .map(String::as_str)
+                .unwrap_or(class_name),
+        )?;
+        let instance = builder.build_bitcast(
+            thunk.get_nth_param(0).expect(INTERNAL_LLVM_ERROR),
+            class_type.ptr_type(AddressSpace::Generic),
+            "instance",
+        );
+        let declared_struct = thunk
+            .get_nth_param(1)
+            .expect(INTERNAL_LLVM_ERROR)
+            .into_pointer_value();
+        let implementation_struct = self.llvm.create_local_variable(
+            implementation_name,
+            &self
+                .llvm_index
+                .get_associated_pou_type(implementation_name)?,
+        );
 
+        let parameters = get_parameter_pointers(
+            builder,
+            self.index,
+            (declaration_name, declared_struct),
+            (implementation_name, implementation_struct),
+        );
+        for (_, from, to) in &parameters {
+            builder.build_store(*to, builder.build_load(*from, ""));
+        }
+        let result = builder
+            .build_call(
+                implementing_function,
+                &[instance.into(), implementation_struct.into()],
+                "call",
+            )
+            .try_as_basic_value()
+            .left();
+        for (_, from, to) in parameters
+            .iter()
+            .filter(|(variable_type, ..)| *variable_type == VariableType::Output)
+        {
+            builder.build_store(*from, builder.build_load(*to, ""));
+        }
+        builder.build_return(result.as_ref().map(|it| it as &dyn BasicValue));
+
+        Ok(thunk
+            .as_global_value()
+            .as_pointer_value()
+            .const_cast(byte_pointer))
+    }
+
+    /// generates the body of an interface's method. It looks up the thunk of the implementing
+    /// method in the vtable of the interface instance and calls it with the referenced instance.
+    fn generate_interface_dispatch(
+        &self,
+        implementation: &Implementation,
+        current_function: FunctionValue<'ink>,
+        local_index: &LlvmTypedIndex<'ink>,
+        interface_name: &str,
+    ) -> Result<(), Diagnostic> {
+        let builder = &self.llvm.builder;
+        let byte_pointer = self.llvm.context.i8_type().ptr_type(AddressSpace::Generic);
+        let slot = self
+            .index
+            .get_interface_methods(interface_name)
+            .iter()
+            .position(|it| {
+                it.get_call_name()
+                    .eq_ignore_ascii_case(&implementation.name)
+            })
+            .unwrap_or_default();
+
+        let interface_instance = current_function
+            .get_nth_param(0)
+            .expect(INTERNAL_LLVM_ERROR)
+            .into_pointer_value();
+        let method_struct = current_function
+            .get_nth_param(1)
+            .expect(INTERNAL_LLVM_ERROR);
+        let load_member = |member_name: &str| {
+            let location = get_struct_member_index(self.index, interface_name, member_name)
+                .expect(INTERNAL_LLVM_ERROR);
+            let member = builder
+                .build_struct_gep(interface_instance, location, member_name)
+                .expect(INTERNAL_LLVM_ERROR);
+            builder.build_load(member, member_name)
+        };
+        let instance = load_member(INTERFACE_INSTANCE_MEMBER);
+        let vtable = builder
+            .build_bitcast(
+                load_member(INTERFACE_VTABLE_MEMBER),
+                byte_pointer.ptr_type(AddressSpace::Generic),
+                "",
+            )
+            .into_pointer_value();
+
+        let thunk_type = self.create_llvm_function_type(
+            vec![byte_pointer.into(), method_struct.get_type().into()],
+            false,
+            current_function.get_type().get_return_type(),
+        )?;
+        let thunk_slot = unsafe {
+            builder.build_in_bounds_gep(
+                vtable,
+                &[self.llvm.i32_type().const_int(slot as u64, false)],
+                "",
+            )
+        };
+        let thunk = builder
+            .build_bitcast(
+                builder.build_load(thunk_slot, "thunk"),
+                thunk_type.ptr_type(AddressSpace::Generic),
+                "",
+            )
+            .into_pointer_value();
+        let thunk = CallableValue::try_from(thunk).map_err(|_| {
+            Diagnostic::codegen_error(
+                &format!("Cannot call {} through its interface", implementation.name),
+                implementation.location.clone(),
+            )
+        })?;
+        let result = builder
+            .build_call(thunk, &[instance.into(), method_struct.into()], "call")
+            .try_as_basic_value()
+            .left();
+
+        //store the result in the return variable, it is returned by the return statement
+        if let Some((result, return_variable)) = result.zip(
+            self.index
+                .find_return_variable(&implementation.type_name)
+                .and_then(|it| {
+                    local_index.find_loaded_associated_variable_value(it.get_qualified_name())
+                }),
+        ) {
+            builder.build_store(return_variable, result);
+        }
         Ok(())
     }
 
@@ -312,6 +755,58 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
         }
     }
 
+    /// generates the accessors for the members inherited by the given instance type and the
+    /// SUPER pointer referencing the embedded instance of the direct super class
+    fn generate_inherited_member_accessors(
+        &self,
+        index: &mut LlvmTypedIndex<'ink>,
+        instance_type: &str,
+        instance_ptr: PointerValue<'ink>,
+    ) -> Result<(), Diagnostic> {
+        let builder = &self.llvm.builder;
+        for (level, super_class) in self
+            .index
+            .get_super_classes(instance_type)
+            .into_iter()
+            .enumerate()
+        {
+            //the super class is embedded at the start of the instance
+            let super_ptr = builder
+                .build_bitcast(
+                    instance_ptr,
+                    index
+                        .get_associated_pou_type(super_class)?
+                        .ptr_type(AddressSpace::Generic),
+                    super_class,
+                )
+                .into_pointer_value();
+            if level == 0 {
+                let super_variable = self
+                    .llvm
+                    .create_local_variable(SUPER, &super_ptr.get_type().into());
+                builder.build_store(super_variable, super_ptr);
+                index.associate_loaded_local_variable(instance_type, SUPER, super_variable)?;
+            }
+
+            let members = self.index.get_container_members(super_class);
+            for member in members.iter().filter(|it| !it.is_temp() && !it.is_return()) {
+                if let Some(location) =
+                    get_struct_member_index(self.index, super_class, member.get_name())
+                {
+                    let member_ptr = builder
+                        .build_struct_gep(super_ptr, location, member.get_name())
+                        .expect(INTERNAL_LLVM_ERROR);
+                    index.associate_loaded_local_variable(
+                        super_class,
+                        member.get_name(),
+                        member_ptr,
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// generates a load-statement for the given member
     fn generate_local_variable_accessors(
         &self,
@@ -328,9 +823,10 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
             let parameter_name = m.get_name();
 
             let (name, variable) = if m.is_return() {
+                //the return variable is not necessarily named like the pou (e.g. property getters)
                 let return_type = index.get_associated_type(m.get_type_name())?;
                 (
-                    Pou::calc_return_name(type_name),
+                    parameter_name,
                     self.llvm.create_local_variable(type_name, &return_type),
                 )
             } else if m.is_temp() {
@@ -356,6 +852,16 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
                 (parameter_name, ptr)
             };
 
+            if let Some(debug) = self.debug {
+                debug.declare_variable(
+                    &self.llvm.builder,
+                    current_function,
+                    m,
+                    variable,
+                    self.index,
+                    self.llvm_index,
+                );
+            }
             index.associate_loaded_local_variable(type_name, name, variable)?;
         }
 
@@ -473,7 +979,7 @@ impl<'ink, 'cg> PouGenerator<'ink, 'cg> {
     /// a function returns the value of the local variable that has the function's name
     pub fn generate_return_statement(
         &self,
-        function_context: &FunctionContext<'ink>,
+        function_context: &FunctionContext<'ink, '_>,
         local_index: &LlvmTypedIndex<'ink>,
     ) -> Result<(), Diagnostic> {
         if let Some(ret_v) = self
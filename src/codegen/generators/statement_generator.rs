@@ -5,31 +5,44 @@ use super::{
     pou_generator::PouGenerator,
 };
 use crate::{
-    ast::{flatten_expression_list, AstStatement, ConditionalBlock, Operator, SourceRange},
+    ast::{
+        flatten_expression_list, AstStatement, CompilationUnit, ConditionalBlock, Operator,
+        SourceRange,
+    },
     codegen::llvm_typesystem,
     codegen::LlvmTypedIndex,
     diagnostics::{Diagnostic, INTERNAL_LLVM_ERROR},
     index::{ImplementationIndexEntry, Index},
-    resolver::AstAnnotations,
+    resolver::{const_evaluator, AnnotationMap, AstAnnotations, StatementAnnotation},
     typesystem::{
         self, DataTypeInformation, RANGE_CHECK_LS_FN, RANGE_CHECK_LU_FN, RANGE_CHECK_S_FN,
-        RANGE_CHECK_U_FN,
+        RANGE_CHECK_TRAP_FN, RANGE_CHECK_U_FN,
     },
+    DivisionCheckMode, OverflowMode, RangeCheckMode,
 };
 use inkwell::{
     basic_block::BasicBlock,
     builder::Builder,
     context::Context,
-    values::{BasicValueEnum, FunctionValue},
+    values::{BasicValueEnum, FunctionValue, IntValue},
+    IntPredicate,
 };
-use std::ops::Range;
+use std::{cmp::Ordering, ops::Range};
 
 /// the full context when generating statements inside a POU
-pub struct FunctionContext<'a> {
+pub struct FunctionContext<'a, 'b> {
     /// the current pou's name. This means that a variable x may refer to "`linking_context`.x"
     pub linking_context: ImplementationIndexEntry,
     /// the llvm function to generate statements into
     pub function: FunctionValue<'a>,
+    /// the check to generate for integer divisions by zero
+    pub division_checks: Option<DivisionCheckMode>,
+    /// check the array indexes against the bounds of their dimensions
+    pub bounds_checks: bool,
+    /// the unit of the function, its file name and lines locate a failing check
+    pub unit: &'b CompilationUnit,
+    /// the behavior of integer arithmetic whose result does not fit its type
+    pub overflow: OverflowMode,
 }
 
 /// the StatementCodeGenerator is used to generate statements (For, If, etc.) or expressions (references, literals, etc.)
@@ -39,7 +52,7 @@ pub struct StatementCodeGenerator<'a, 'b> {
     annotations: &'b AstAnnotations,
     pou_generator: &'b PouGenerator<'a, 'b>,
     llvm_index: &'b LlvmTypedIndex<'a>,
-    function_context: &'b FunctionContext<'a>,
+    function_context: &'b FunctionContext<'a, 'b>,
 
     pub load_prefix: String,
     pub load_suffix: String,
@@ -58,7 +71,7 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
         annotations: &'b AstAnnotations,
         pou_generator: &'b PouGenerator<'a, 'b>,
         llvm_index: &'b LlvmTypedIndex<'a>,
-        linking_context: &'b FunctionContext<'a>,
+        linking_context: &'b FunctionContext<'a, 'b>,
     ) -> StatementCodeGenerator<'a, 'b> {
         StatementCodeGenerator {
             llvm,
@@ -110,6 +123,8 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
     ///
     /// - `statement` the statement to be generated
     pub fn generate_statement(&self, statement: &AstStatement) -> Result<(), Diagnostic> {
+        self.pou_generator
+            .set_debug_location(self.function_context.function, &statement.get_location());
         match statement {
             AstStatement::EmptyStatement { .. } => {
                 //nothing to generate
@@ -199,6 +214,22 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
             return self.generate_direct_access_assignment(left_statement, right_statement);
         }
         let exp_gen = self.create_expr_generator();
+        //assigning a property calls its setter
+        if let Some(StatementAnnotation::Property { setter, .. }) =
+            self.annotations.get(left_statement)
+        {
+            let setter = setter
+                .as_deref()
+                .and_then(|it| self.index.find_implementation(it))
+                .ok_or_else(|| {
+                    Diagnostic::codegen_error(
+                        "Cannot write a property without a SET accessor",
+                        left_statement.get_location(),
+                    )
+                })?;
+            exp_gen.generate_call(left_statement, setter, &Some(right_statement.clone()))?;
+            return Ok(());
+        }
         let left = exp_gen.generate_element_pointer(left_statement)?;
         let left_type = exp_gen.get_type_hint_info_for(left_statement)?;
         // if the lhs-type is a subrange type we may need to generate a check-call
@@ -222,12 +253,200 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
                 None
             };
 
+        if let (DataTypeInformation::SubRange { .. }, None, Some(mode)) = (
+            left_type,
+            &range_checked_right_side,
+            self.pou_generator.get_range_checks(),
+        ) {
+            // there is no check function, so we generate the check ourselves
+            let value = self.generate_range_check(left_type, right_statement, mode)?;
+            self.llvm.builder.build_store(left, value);
+            return Ok(());
+        }
+
         let right_statement = range_checked_right_side.as_ref().unwrap_or(right_statement);
 
+        if let Some(value) = self.generate_overflow_check(left_type, right_statement)? {
+            self.llvm.builder.build_store(left, value);
+            return Ok(());
+        }
+
         exp_gen.generate_store(left_type, right_statement, left)?;
         Ok(())
     }
 
+    /// generates a compiler-provided check of the given value against the bounds of the given sub-range type
+    ///
+    /// - `Trap` aborts the program if the value lies outside of the range
+    /// - `Saturate` limits the value to the range's bounds
+    fn generate_range_check(
+        &self,
+        range_type: &DataTypeInformation,
+        value: &AstStatement,
+        mode: RangeCheckMode,
+    ) -> Result<BasicValueEnum<'a>, Diagnostic> {
+        let (sub_range, referenced_type) = if let DataTypeInformation::SubRange {
+            sub_range,
+            referenced_type,
+            ..
+        } = range_type
+        {
+            (sub_range, referenced_type)
+        } else {
+            unreachable!("range checks are only generated for sub-range types")
+        };
+        let is_signed = self
+            .index
+            .find_effective_type_info(referenced_type)
+            .map_or(false, DataTypeInformation::is_signed_int);
+
+        let exp_gen = self.create_expr_generator();
+        let value = exp_gen.generate_expression(value)?.into_int_value();
+        let lower = exp_gen
+            .generate_expression(&sub_range.start)?
+            .into_int_value();
+        let upper = exp_gen
+            .generate_expression(&sub_range.end)?
+            .into_int_value();
+
+        let (builder, current_function, context) = self.get_llvm_deps();
+        let (below_predicate, above_predicate) = if is_signed {
+            (IntPredicate::SLT, IntPredicate::SGT)
+        } else {
+            (IntPredicate::ULT, IntPredicate::UGT)
+        };
+        let is_below = builder.build_int_compare(below_predicate, value, lower, "is_below");
+        let is_above = builder.build_int_compare(above_predicate, value, upper, "is_above");
+
+        match mode {
+            RangeCheckMode::Saturate => {
+                let limited = builder.build_select(is_above, upper, value, "");
+                Ok(builder.build_select(is_below, lower, limited.into_int_value(), ""))
+            }
+            RangeCheckMode::Trap => {
+                let trap = self
+                    .llvm_index
+                    .find_associated_implementation(RANGE_CHECK_TRAP_FN)
+                    .ok_or_else(|| {
+                        Diagnostic::codegen_error(
+                            "Cannot find the trap for range checks",
+                            sub_range.start.get_location(),
+                        )
+                    })?;
+                let out_of_range = context.append_basic_block(current_function, "out_of_range");
+                let in_range = context.append_basic_block(current_function, "in_range");
+                let is_out_of_range = builder.build_or(is_below, is_above, "");
+                builder.build_conditional_branch(is_out_of_range, out_of_range, in_range);
+
+                builder.position_at_end(out_of_range);
+                builder.build_call(trap, &[], "");
+                builder.build_unreachable();
+
+                builder.position_at_end(in_range);
+                Ok(value.into())
+            }
+        }
+    }
+
+    /// generates the arithmetic assigned to the given integer type with the function's overflow
+    /// mode. Integer arithmetic is computed in at least a DINT, so its result overflows if it
+    /// does not fit the assigned type (e.g. `i := i + 1` for an INT). Returns None if the
+    /// assigned value cannot overflow or overflows wrap around.
+    ///
+    /// - `Trap` aborts the program if the result does not fit the type
+    /// - `Saturate` limits the result to the type's bounds
+    fn generate_overflow_check(
+        &self,
+        target_type: &DataTypeInformation,
+        statement: &AstStatement,
+    ) -> Result<Option<BasicValueEnum<'a>>, Diagnostic> {
+        let overflow = self.function_context.overflow;
+        let is_arithmetic = matches!(
+            statement,
+            AstStatement::BinaryExpression {
+                operator: Operator::Plus | Operator::Minus | Operator::Multiplication,
+                ..
+            }
+        );
+        if overflow == OverflowMode::Wrap || !is_arithmetic {
+            return Ok(None);
+        }
+        let value_type = self
+            .annotations
+            .get_type_or_void(statement, self.index)
+            .get_type_information();
+        let (is_signed, size, is_value_signed) = match (target_type, value_type) {
+            (
+                DataTypeInformation::Integer {
+                    signed,
+                    size,
+                    semantic_size: None,
+                    ..
+                },
+                DataTypeInformation::Integer {
+                    signed: value_signed,
+                    size: value_size,
+                    ..
+                },
+            ) if value_size > size => (*signed, *size, *value_signed),
+            _ => return Ok(None),
+        };
+
+        let exp_gen = self.create_expr_generator();
+        let value = exp_gen.generate_expression(statement)?.into_int_value();
+        let (builder, current_function, context) = self.get_llvm_deps();
+        let (min, max) = if is_signed {
+            (-(1_i64 << (size - 1)), (1_i64 << (size - 1)) - 1)
+        } else {
+            (0, (1_i64 << size) - 1)
+        };
+        let lower = value.get_type().const_int(min as u64, true);
+        let upper = value.get_type().const_int(max as u64, true);
+        let (below_predicate, above_predicate) = if is_value_signed {
+            (IntPredicate::SLT, IntPredicate::SGT)
+        } else {
+            (IntPredicate::ULT, IntPredicate::UGT)
+        };
+        let is_below = builder.build_int_compare(below_predicate, value, lower, "is_below");
+        let is_above = builder.build_int_compare(above_predicate, value, upper, "is_above");
+
+        let value = match overflow {
+            OverflowMode::Saturate => {
+                let limited = builder.build_select(is_above, upper, value, "");
+                builder
+                    .build_select(is_below, lower, limited.into_int_value(), "")
+                    .into_int_value()
+            }
+            _ => {
+                let trap = self
+                    .llvm_index
+                    .find_associated_implementation(RANGE_CHECK_TRAP_FN)
+                    .ok_or_else(|| {
+                        Diagnostic::codegen_error(
+                            "Cannot find the trap for overflow checks",
+                            statement.get_location(),
+                        )
+                    })?;
+                let overflowed = context.append_basic_block(current_function, "overflow");
+                let no_overflow = context.append_basic_block(current_function, "no_overflow");
+                let is_overflow = builder.build_or(is_below, is_above, "is_overflow");
+                builder.build_conditional_branch(is_overflow, overflowed, no_overflow);
+
+                builder.position_at_end(overflowed);
+                builder.build_call(trap, &[], "");
+                builder.build_unreachable();
+
+                builder.position_at_end(no_overflow);
+                value
+            }
+        };
+        Ok(Some(
+            builder
+                .build_int_truncate(value, context.custom_width_int_type(size), "")
+                .into(),
+        ))
+    }
+
     fn generate_direct_access_assignment(
         &self,
         left_statement: &AstStatement,
@@ -371,15 +590,14 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
             DataTypeInformation::Integer { signed, size, .. } if !*signed && *size > 32 => {
                 self.index.find_implementation(RANGE_CHECK_LU_FN)
             }
-            DataTypeInformation::Alias { name, .. }
-            | DataTypeInformation::SubRange {
-                referenced_type: name,
-                ..
-            } => {
-                //traverse to the primitive type
-                self.index
-                    .find_effective_type_info(name)
-                    .and_then(|info| self.find_range_check_implementation_for(info))
+            DataTypeInformation::Alias { .. } | DataTypeInformation::SubRange { .. } => {
+                //traverse to the primitive type (cyclic definitions are not resolved)
+                match self.index.find_intrinsic_type(range_type) {
+                    info @ DataTypeInformation::Integer { .. } => {
+                        self.find_range_check_implementation_for(info)
+                    }
+                    _ => None,
+                }
             }
             _ => None,
         }
@@ -417,16 +635,8 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
         let exp_gen = self.create_expr_generator();
         let counter_statement = exp_gen.generate_expression(counter)?;
 
-        //.                                                           /            and_2                \
-        //.                  /             and 1               \
-        //.                   (counter_end_le && counter_start_ge) || (counter_end_ge && counter_start_le)
-        let or_eval = self.generate_compare_expression(counter, end, start, &exp_gen)?;
-
-        builder.build_conditional_branch(
-            to_i1(or_eval.into_int_value(), builder),
-            for_body,
-            continue_block,
-        );
+        let condition = self.generate_for_condition(counter, end, by_step, &exp_gen)?;
+        builder.build_conditional_branch(condition, for_body, continue_block);
 
         //Enter the for loop
         builder.position_at_end(for_body);
@@ -472,57 +682,72 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
         Ok(())
     }
 
-    fn generate_compare_expression(
+    /// generates the condition that keeps a FOR loop running
+    ///
+    /// an ascending loop runs while `counter <= end`, a descending loop while `counter >= end`.
+    /// If the sign of the step is known at compile time, only the matching comparison is
+    /// generated, otherwise the direction is selected at runtime.
+    fn generate_for_condition(
         &'a self,
         counter: &AstStatement,
         end: &AstStatement,
-        start: &AstStatement,
+        by_step: &Option<Box<AstStatement>>,
         exp_gen: &'a ExpressionCodeGenerator,
-    ) -> Result<BasicValueEnum<'a>, Diagnostic> {
-        let counter_end_ge = AstStatement::BinaryExpression {
-            id: self.annotations.get_bool_id(),
-            operator: Operator::GreaterOrEqual,
-            left: Box::new(counter.to_owned()),
-            right: Box::new(end.to_owned()),
-        };
-        let counter_start_ge = AstStatement::BinaryExpression {
-            id: self.annotations.get_bool_id(),
-            operator: Operator::GreaterOrEqual,
-            left: Box::new(counter.to_owned()),
-            right: Box::new(start.to_owned()),
-        };
-        let counter_end_le = AstStatement::BinaryExpression {
-            id: self.annotations.get_bool_id(),
-            operator: Operator::LessOrEqual,
-            left: Box::new(counter.to_owned()),
-            right: Box::new(end.to_owned()),
-        };
-        let counter_start_le = AstStatement::BinaryExpression {
-            id: self.annotations.get_bool_id(),
-            operator: Operator::LessOrEqual,
-            left: Box::new(counter.to_owned()),
-            right: Box::new(start.to_owned()),
-        };
-        let and_1 = AstStatement::BinaryExpression {
-            id: self.annotations.get_bool_id(),
-            operator: Operator::And,
-            left: Box::new(counter_end_le),
-            right: Box::new(counter_start_ge),
-        };
-        let and_2 = AstStatement::BinaryExpression {
-            id: self.annotations.get_bool_id(),
-            operator: Operator::And,
-            left: Box::new(counter_end_ge),
-            right: Box::new(counter_start_le),
+    ) -> Result<IntValue<'a>, Diagnostic> {
+        let builder = &self.llvm.builder;
+        let compare = |operator: Operator| {
+            let comparison = AstStatement::BinaryExpression {
+                id: self.annotations.get_bool_id(),
+                operator,
+                left: Box::new(counter.to_owned()),
+                right: Box::new(end.to_owned()),
+            };
+            exp_gen
+                .generate_expression(&comparison)
+                .map(|it| to_i1(it.into_int_value(), builder))
         };
-        let or = AstStatement::BinaryExpression {
-            id: self.annotations.get_bool_id(),
-            operator: Operator::Or,
-            left: Box::new(and_1),
-            right: Box::new(and_2),
+
+        let step = match by_step {
+            Some(step) => step,
+            None => return compare(Operator::LessOrEqual),
         };
-        let or_eval = exp_gen.generate_expression(&or)?;
-        Ok(or_eval)
+        match self.get_constant_step_direction(step) {
+            Some(Ordering::Less) => compare(Operator::GreaterOrEqual),
+            Some(_) => compare(Operator::LessOrEqual),
+            None => {
+                let step_value = exp_gen.generate_expression(step)?.into_int_value();
+                let is_descending = builder.build_int_compare(
+                    IntPredicate::SLT,
+                    step_value,
+                    step_value.get_type().const_zero(),
+                    "is_descending",
+                );
+                let ascending = compare(Operator::LessOrEqual)?;
+                let descending = compare(Operator::GreaterOrEqual)?;
+                Ok(builder
+                    .build_select(is_descending, descending, ascending, "")
+                    .into_int_value())
+            }
+        }
+    }
+
+    /// returns the sign of the given FOR-step if it can be decided at compile time
+    ///
+    /// unsigned steps are always ascending, signed steps are only known if they resolve to a constant
+    fn get_constant_step_direction(&self, step: &AstStatement) -> Option<Ordering> {
+        let step_type = self
+            .annotations
+            .get_type_or_void(step, self.index)
+            .get_type_information();
+        if let DataTypeInformation::Integer { signed: false, .. } = step_type {
+            return Some(Ordering::Greater);
+        }
+
+        let scope = self.function_context.linking_context.get_type_name();
+        match const_evaluator::evaluate(step, Some(scope), self.index) {
+            Ok(Some(AstStatement::LiteralInteger { value, .. })) => Some(value.cmp(&0)),
+            _ => None,
+        }
     }
 
     /// genertes a case statement
@@ -663,7 +888,7 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
     ) -> Result<Option<BasicValueEnum<'a>>, Diagnostic> {
         let builder = &self.llvm.builder;
         let basic_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
-        let (condition_block, _) = self.generate_base_while_statement(condition, body)?;
+        let (condition_block, _) = self.generate_base_while_statement(condition, body, true)?;
 
         let continue_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
 
@@ -690,7 +915,8 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
     ) -> Result<Option<BasicValueEnum<'a>>, Diagnostic> {
         let builder = &self.llvm.builder;
         let basic_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
-        let (_, while_block) = self.generate_base_while_statement(condition, body)?;
+        //a repeat loop runs until its condition becomes true
+        let (_, while_block) = self.generate_base_while_statement(condition, body, false)?;
 
         let continue_block = builder.get_insert_block().expect(INTERNAL_LLVM_ERROR);
 
@@ -702,10 +928,13 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
     }
 
     /// utility method for while and repeat loops
+    ///
+    /// - `loop_while` whether the loop keeps running while the condition is true (WHILE) or until it becomes true (REPEAT)
     fn generate_base_while_statement(
         &self,
         condition: &AstStatement,
         body: &[AstStatement],
+        loop_while: bool,
     ) -> Result<(BasicBlock, BasicBlock), Diagnostic> {
         let (builder, current_function, context) = self.get_llvm_deps();
         let condition_check = context.append_basic_block(current_function, "condition_check");
@@ -717,10 +946,15 @@ impl<'a, 'b> StatementCodeGenerator<'a, 'b> {
         let condition_value = self
             .create_expr_generator()
             .generate_expression(condition)?;
+        let (then_block, else_block) = if loop_while {
+            (while_body, continue_block)
+        } else {
+            (continue_block, while_body)
+        };
         builder.build_conditional_branch(
             to_i1(condition_value.into_int_value(), builder),
-            while_body,
-            continue_block,
+            then_block,
+            else_block,
         );
 
         //Enter the for loop
@@ -4,10 +4,11 @@
 use crate::{
     ast::SourceRange,
     diagnostics::{Diagnostic, ErrNo},
-    index::Index,
+    index::{Index, RetentionType},
     resolver::AstAnnotations,
+    SectionNames,
 };
-use inkwell::{module::Module, values::GlobalValue};
+use inkwell::{module::Module, types::AnyType, values::GlobalValue};
 
 use crate::{codegen::llvm_index::LlvmTypedIndex, index::VariableIndexEntry};
 
@@ -23,6 +24,7 @@ pub fn generate_global_variables<'ctx, 'b>(
     global_index: &'b Index,
     annotations: &'b AstAnnotations,
     types_index: &'b LlvmTypedIndex<'ctx>,
+    sections: &'b SectionNames,
 ) -> Result<LlvmTypedIndex<'ctx>, Diagnostic> {
     let mut index = LlvmTypedIndex::default();
     let globals = global_index.get_globals();
@@ -40,6 +42,7 @@ pub fn generate_global_variables<'ctx, 'b>(
             annotations,
             types_index,
             variable,
+            sections,
         )
         .map_err(|err| match err.get_type() {
             ErrNo::codegen__missing_function | ErrNo::reference__unresolved => {
@@ -58,6 +61,7 @@ pub fn generate_global_variables<'ctx, 'b>(
 /// - `llvm` the struct used to generate IR-code
 /// - `index` the global symbol table, the global variable will be registerd as a new symbol
 /// - `global_variable` the variable to generate
+/// - `sections` the sections to place retained variables in
 pub fn generate_global_variable<'ctx, 'b>(
     module: &'b Module<'ctx>,
     llvm: &'b Llvm<'ctx>,
@@ -65,10 +69,30 @@ pub fn generate_global_variable<'ctx, 'b>(
     annotations: &'b AstAnnotations,
     index: &'b LlvmTypedIndex<'ctx>,
     global_variable: &VariableIndexEntry,
+    sections: &SectionNames,
 ) -> Result<GlobalValue<'ctx>, Diagnostic> {
     let type_name = global_variable.get_type_name();
     let variable_type = index.get_associated_type(type_name)?;
 
+    //located variables (e.g. `x AT %IX1.2`) are generated as a symbol named after their address
+    let symbol_name = global_variable
+        .get_hardware_binding()
+        .and_then(|it| it.get_symbol_name(global_index.get_const_expressions()));
+    if let Some(existing) = symbol_name.as_deref().and_then(|it| module.get_global(it)) {
+        //another variable is located at the same address, so they share the symbol
+        return if existing.get_type().get_element_type() == variable_type.as_any_type_enum() {
+            Ok(existing)
+        } else {
+            Err(Diagnostic::codegen_error(
+                &format!(
+                    "Cannot locate '{}' at an address with a variable of a different type",
+                    global_variable.get_name()
+                ),
+                global_variable.source_location.clone(),
+            ))
+        };
+    }
+
     let initial_value = if let Some(initializer) = global_index
         .get_const_expressions()
         .maybe_get_constant_statement(&global_variable.initial_value)
@@ -87,8 +111,13 @@ pub fn generate_global_variable<'ctx, 'b>(
         None
     };
 
-    let mut global_ir_variable =
-        llvm.create_global_variable(module, global_variable.get_name(), variable_type);
+    let mut global_ir_variable = llvm.create_global_variable(
+        module,
+        symbol_name
+            .as_deref()
+            .unwrap_or_else(|| global_variable.get_name()),
+        variable_type,
+    );
     if global_variable.is_external() {
         global_ir_variable = global_ir_variable.make_external();
     } else {
@@ -98,6 +127,11 @@ pub fn generate_global_variable<'ctx, 'b>(
             // 3rd try: get the compiler's default for the given type (zero-initializer)
             .or_else(|| index.find_associated_type(type_name).map(get_default_for));
         global_ir_variable.set_initial_value(initial_value, variable_type);
+        match global_variable.get_retention() {
+            RetentionType::Retain => global_ir_variable.set_section(&sections.retain),
+            RetentionType::Persistent => global_ir_variable.set_section(&sections.persistent),
+            RetentionType::NonRetain => {}
+        }
         if global_variable.is_constant() {
             global_ir_variable = global_ir_variable.make_constant();
             if initial_value.is_none() {
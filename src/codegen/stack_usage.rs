@@ -0,0 +1,265 @@
+// Copyright (c) 2022 Ghaith Hachem and Mathias Rieder
+
+//! Estimates the stack used by the generated functions, so the stacks of the tasks calling them
+//! can be sized.
+//!
+//! The frame of a function is the size of the variables it allocates on the stack. The worst
+//! case of a function adds the frames of the deepest chain of functions it calls. Calls of a
+//! recursion are not followed, the functions of a recursion are flagged instead. Functions
+//! called through pointers (e.g. methods called through an interface) and external functions
+//! are not included either.
+use std::collections::{HashMap, HashSet};
+
+use inkwell::{
+    module::Module,
+    targets::TargetData,
+    values::{BasicValueEnum, FunctionValue, InstructionOpcode},
+};
+
+/// the stack used by a generated function
+#[derive(Debug, PartialEq)]
+pub struct StackUsage {
+    /// the name of the function
+    pub name: String,
+    /// the size of the variables the function allocates on the stack in bytes
+    pub frame_size: u64,
+    /// the size of the function's frame and the frames of the deepest chain of functions it
+    /// calls in bytes
+    pub worst_case: u64,
+    /// the deepest chain of calls, starting with the function
+    pub call_path: Vec<String>,
+    /// the function is part of a recursion, the stack used by the recursion is not included
+    pub recursive: bool,
+    /// the function calls functions through pointers, their stack usage is not included
+    pub indirect_calls: bool,
+}
+
+/// the frame and the direct callees of a function
+struct FunctionFrame {
+    frame_size: u64,
+    callees: Vec<String>,
+    indirect_calls: bool,
+}
+
+/// the call graph of the defined functions, the worst cases are computed on demand
+struct CallGraph {
+    functions: HashMap<String, FunctionFrame>,
+    worst_cases: HashMap<String, (u64, Vec<String>)>,
+    recursive: HashSet<String>,
+}
+
+/// returns the stack used by every function defined by the given module, sorted by name
+pub fn collect_stack_usage(module: &Module) -> Vec<StackUsage> {
+    let target_data = super::get_target_data(module);
+    let functions = module
+        .get_functions()
+        .filter(|it| it.count_basic_blocks() > 0)
+        .map(|it| {
+            (
+                get_name(it),
+                collect_function_frame(module, &target_data, it),
+            )
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut names = functions.keys().cloned().collect::<Vec<_>>();
+    names.sort_unstable();
+    let mut call_graph = CallGraph {
+        functions,
+        worst_cases: HashMap::new(),
+        recursive: HashSet::new(),
+    };
+    for name in &names {
+        call_graph.visit(name, &mut vec![]);
+    }
+
+    names
+        .into_iter()
+        .map(|name| {
+            let frame = &call_graph.functions[&name];
+            let (worst_case, call_path) = call_graph.worst_cases[&name].clone();
+            StackUsage {
+                frame_size: frame.frame_size,
+                worst_case,
+                call_path,
+                recursive: call_graph.recursive.contains(&name),
+                indirect_calls: frame.indirect_calls,
+                name,
+            }
+        })
+        .collect()
+}
+
+/// returns the report listing the stack usage of the given functions
+pub fn generate_stack_report(usages: &[StackUsage]) -> String {
+    let name_width = usages
+        .iter()
+        .map(|it| it.name.len())
+        .chain(std::iter::once("POU".len()))
+        .max()
+        .unwrap_or_default();
+    let mut report = String::from(
+        "Stack usage in bytes, the stack used by external functions is not included\n\n",
+    );
+    report.push_str(&format!(
+        "{:<width$}  {:>10}  {:>10}  DEEPEST CALL PATH\n",
+        "POU",
+        "FRAME",
+        "WORST CASE",
+        width = name_width
+    ));
+    for usage in usages {
+        let mut notes = vec![];
+        if usage.recursive {
+            notes.push("recursive");
+        }
+        if usage.indirect_calls {
+            notes.push("indirect calls");
+        }
+        let notes = if notes.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", notes.join(", "))
+        };
+        report.push_str(&format!(
+            "{:<width$}  {:>10}  {:>10}  {}{}\n",
+            usage.name,
+            usage.frame_size,
+            usage.worst_case,
+            usage.call_path.join(" -> "),
+            notes,
+            width = name_width
+        ));
+    }
+
+    let recursive = usages
+        .iter()
+        .filter(|it| it.recursive)
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>();
+    if !recursive.is_empty() {
+        report.push_str(&format!(
+            "\nThe worst cases of the recursive POUs exclude the recursion: {}\n",
+            recursive.join(", ")
+        ));
+    }
+    let indirect = usages
+        .iter()
+        .filter(|it| it.indirect_calls)
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>();
+    if !indirect.is_empty() {
+        report.push_str(&format!(
+            "\nThe worst cases of the POUs calling functions through pointers exclude these calls: {}\n",
+            indirect.join(", ")
+        ));
+    }
+    report
+}
+
+impl CallGraph {
+    /// returns the worst case and the deepest call path of the given function, `stack` holds
+    /// the chain of calls leading to the function
+    fn visit(&mut self, name: &str, stack: &mut Vec<String>) -> (u64, Vec<String>) {
+        if let Some(worst_case) = self.worst_cases.get(name) {
+            return worst_case.clone();
+        }
+        let (frame_size, callees) = match self.functions.get(name) {
+            Some(frame) => (frame.frame_size, frame.callees.clone()),
+            //external functions are not included
+            None => return (0, vec![]),
+        };
+
+        stack.push(name.to_string());
+        let mut deepest = (0, vec![]);
+        for callee in callees {
+            if let Some(position) = stack.iter().position(|it| *it == callee) {
+                //every function of the recursion is flagged
+                self.recursive.extend(stack[position..].iter().cloned());
+                continue;
+            }
+            let callee_worst_case = self.visit(&callee, stack);
+            if callee_worst_case.0 > deepest.0 || deepest.1.is_empty() {
+                deepest = callee_worst_case;
+            }
+        }
+        stack.pop();
+
+        let (callees_size, mut call_path) = deepest;
+        call_path.insert(0, name.to_string());
+        let worst_case = (frame_size + callees_size, call_path);
+        self.worst_cases
+            .insert(name.to_string(), worst_case.clone());
+        worst_case
+    }
+}
+
+/// returns the size of the given function's stack allocations and the functions it calls
+fn collect_function_frame(
+    module: &Module,
+    target_data: &TargetData,
+    function: FunctionValue,
+) -> FunctionFrame {
+    let mut frame = FunctionFrame {
+        frame_size: 0,
+        callees: vec![],
+        indirect_calls: false,
+    };
+    let instructions = function.get_basic_blocks().into_iter().flat_map(|block| {
+        std::iter::successors(block.get_first_instruction(), |it| {
+            it.get_next_instruction()
+        })
+    });
+    for instruction in instructions {
+        match instruction.get_opcode() {
+            InstructionOpcode::Alloca => {
+                let element_size = target_data.get_store_size(
+                    &instruction
+                        .get_type()
+                        .into_pointer_type()
+                        .get_element_type(),
+                );
+                //the number of allocated elements, a dynamic number cannot be estimated
+                let count = instruction
+                    .get_operand(0)
+                    .and_then(|it| it.left())
+                    .and_then(|it| match it {
+                        BasicValueEnum::IntValue(count) => count.get_zero_extended_constant(),
+                        _ => None,
+                    })
+                    .unwrap_or(1);
+                frame.frame_size += element_size * count;
+            }
+            InstructionOpcode::Call => {
+                //the called function is the last operand of a call
+                let callee = instruction
+                    .get_operand(instruction.get_num_operands().saturating_sub(1))
+                    .and_then(|it| it.left());
+                let function = match callee {
+                    Some(BasicValueEnum::PointerValue(pointer)) => {
+                        pointer.get_name().to_str().ok().and_then(|name| {
+                            module
+                                .get_function(name)
+                                .filter(|it| it.as_global_value().as_pointer_value() == pointer)
+                        })
+                    }
+                    _ => None,
+                };
+                match function {
+                    //intrinsics are no calls
+                    Some(function) if !get_name(function).starts_with("llvm.") => {
+                        frame.callees.push(get_name(function))
+                    }
+                    Some(_) => {}
+                    None => frame.indirect_calls = true,
+                }
+            }
+            _ => {}
+        }
+    }
+    frame
+}
+
+fn get_name(function: FunctionValue) -> String {
+    function.get_name().to_string_lossy().into_owned()
+}
@@ -1,10 +1,12 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 mod code_gen_tests;
 mod codegen_error_messages_tests;
+mod debug_test;
 mod directaccess_test;
 mod expression_tests;
 mod generics_test;
 mod initialization_test;
+mod stack_usage_tests;
 mod statement_codegen_test;
 mod string_tests;
 mod typesystem_test;
@@ -1,5 +1,13 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
-use crate::test_utils::tests::{codegen, generate_with_empty_program};
+use inkwell::context::Context;
+
+use crate::{
+    ast::SourceRange,
+    compile_module,
+    diagnostics::{Diagnostic, Diagnostician},
+    test_utils::tests::{codegen, codegen_with_options, generate_with_empty_program},
+    CodegenOptions, DivisionCheckMode, OptimizationLevel, OverflowMode, RangeCheckMode, SourceCode,
+};
 
 #[test]
 fn program_with_variables_and_references_generates_void_function_and_struct_and_body() {
@@ -74,6 +82,43 @@ fn two_global_variables_generates_in_separate_global_variables() {
     insta::assert_snapshot!(result);
 }
 
+#[test]
+fn retain_and_persistent_global_variables_are_generated_into_sections() {
+    let result = generate_with_empty_program(
+        "VAR_GLOBAL RETAIN gX : INT; END_VAR
+        VAR_GLOBAL PERSISTENT RETAIN gY : BOOL; END_VAR
+        VAR_GLOBAL gA : INT; END_VAR",
+    );
+    insta::assert_snapshot!(result);
+}
+
+#[test]
+fn located_global_variables_are_generated_as_address_symbols() {
+    let result = generate_with_empty_program(
+        "VAR_GLOBAL
+            a AT %IX1.2 : BOOL;
+            b AT %QW5 : WORD;
+            c AT %MD1.3 : DWORD;
+            d AT %I* : DWORD;
+            e AT %IX1.2 : BOOL;
+        END_VAR",
+    );
+    insta::assert_snapshot!(result);
+}
+
+#[test]
+fn located_global_variables_of_different_sizes_at_the_same_address_get_their_own_symbols() {
+    let result = generate_with_empty_program(
+        "VAR_GLOBAL
+            a AT %IW1 : WORD;
+            b AT %ID1 : DWORD;
+        END_VAR",
+    );
+
+    assert!(result.contains("@__PI_W_1 = global i16 0"));
+    assert!(result.contains("@__PI_D_1 = global i32 0"));
+}
+
 #[test]
 fn global_variable_reference_is_generated() {
     let function = codegen(
@@ -468,6 +513,29 @@ fn date_comparisons() {
     insta::assert_snapshot!(result);
 }
 
+#[test]
+fn date_and_time_arithmetic_and_conversions() {
+    let result = codegen(
+        r#"PROGRAM prg
+        VAR
+          d : DATE;
+          dt : DT;
+          tod : TOD;
+          t : TIME;
+          i : DINT;
+        END_VAR
+
+          t := dt - dt;
+          dt := dt + t;
+          t := t * i;
+          d := DT_TO_DATE(dt);
+          tod := DT_TO_TOD(dt);
+          i := TIME_TO_DINT(t);
+        END_PROGRAM"#,
+    );
+    insta::assert_snapshot!(result);
+}
+
 #[test]
 fn program_with_string_assignment() {
     let result = codegen(
@@ -2012,6 +2080,32 @@ fn structs_members_can_be_referenced() {
     insta::assert_snapshot!(result);
 }
 
+#[test]
+fn union_members_are_accessed_through_bitcasts() {
+    let result = codegen(
+        "
+        TYPE MyUnion: UNION
+          i: DINT;
+          b: ARRAY[0..5] OF BYTE;
+          r: REAL;
+        END_UNION
+        END_TYPE
+
+        PROGRAM MainProg 
+        VAR
+          u: MyUnion; 
+          x: BYTE;
+        END_VAR
+          u.i := 7;
+          x := u.b[1];
+        END_PROGRAM
+        ",
+    );
+
+    //the union is as large as its largest member and aligned like its strictest member
+    insta::assert_snapshot!(result);
+}
+
 #[test]
 fn enums_are_generated() {
     let result = codegen(
@@ -2551,6 +2645,597 @@ fn sub_range_type_calls_check_function_on_assigment() {
     insta::assert_snapshot!(result);
 }
 
+#[test]
+fn sub_range_type_saturates_on_assignment_without_check_function() {
+    let source = "
+            TYPE MyInt: INT(0..100); END_TYPE
+
+            PROGRAM Main
+            VAR
+              x : MyInt;
+              y : INT;
+            END_VAR
+
+            x := y;
+            END_PROGRAM
+           ";
+    let result = codegen_with_options(
+        source,
+        CodegenOptions {
+            range_checks: Some(RangeCheckMode::Saturate),
+            ..CodegenOptions::default()
+        },
+    )
+    .unwrap();
+
+    // we expect the value to be limited to 0..100 before it is stored
+    insta::assert_snapshot!(result);
+}
+
+#[test]
+fn sub_range_type_traps_on_assignment_without_check_function() {
+    let source = "
+            TYPE MyInt: INT(0..100); END_TYPE
+
+            PROGRAM Main
+            VAR
+              x : MyInt;
+              y : INT;
+            END_VAR
+
+            x := y;
+            END_PROGRAM
+           ";
+    let result = codegen_with_options(
+        source,
+        CodegenOptions {
+            range_checks: Some(RangeCheckMode::Trap),
+            ..CodegenOptions::default()
+        },
+    )
+    .unwrap();
+
+    // we expect a call to llvm.trap if the value is outside of 0..100
+    insta::assert_snapshot!(result);
+}
+
+#[test]
+fn generated_main_calls_the_program_cyclically() {
+    let source = "
+            PROGRAM PLC_PRG
+            VAR x : INT; END_VAR
+            x := x + 1;
+            END_PROGRAM
+           ";
+    let result = codegen_with_options(
+        source,
+        CodegenOptions {
+            main_program: Some("PLC_PRG".to_string()),
+            ..CodegenOptions::default()
+        },
+    )
+    .unwrap();
+
+    // main loops over the call of the program's instance
+    assert!(result.contains("define i32 @main()"));
+    assert!(result.contains("call void @PLC_PRG(%PLC_PRG_interface* @PLC_PRG_instance)"));
+    assert!(result.contains("cycle:"));
+    assert!(result.contains("br label %cycle"));
+}
+
+#[test]
+fn generated_main_must_call_a_program() {
+    let source = "
+            FUNCTION foo : INT END_FUNCTION
+            PROGRAM main END_PROGRAM
+           ";
+    let options = |program: &str| CodegenOptions {
+        main_program: Some(program.to_string()),
+        ..CodegenOptions::default()
+    };
+
+    assert_eq!(
+        codegen_with_options(source, options("foo")),
+        Err(Diagnostic::param_error(
+            "Cannot generate a main calling 'foo', it is not a PROGRAM"
+        ))
+    );
+    assert_eq!(
+        codegen_with_options(source, options("main")),
+        Err(Diagnostic::param_error(
+            "Cannot generate a main, a POU named 'main' already exists"
+        ))
+    );
+}
+
+#[test]
+fn symbols_defined_by_the_sources_are_prefixed() {
+    let source = "
+            VAR_GLOBAL
+                counter : DINT;
+                input AT %IX1.2 : BOOL;
+            END_VAR
+            @EXTERNAL FUNCTION log : DINT END_FUNCTION
+            FUNCTION_BLOCK fb END_FUNCTION_BLOCK
+            PROGRAM prg
+            VAR inst : fb; END_VAR
+                inst();
+                counter := log();
+            END_PROGRAM
+           ";
+    let result = codegen_with_options(
+        source,
+        CodegenOptions {
+            main_program: Some("prg".to_string()),
+            symbol_prefix: Some("lib1_".to_string()),
+            ..CodegenOptions::default()
+        },
+    )
+    .unwrap();
+
+    // POUs, program instances, global variables and initial values are prefixed
+    assert!(result.contains("@lib1_counter = global i32 0"));
+    assert!(result.contains("@lib1_prg_instance = global %prg_interface"));
+    assert!(result.contains("@lib1___fb__init = "));
+    assert!(result.contains("define void @lib1_fb(%fb_interface* %0)"));
+    assert!(result.contains("define void @lib1_prg(%prg_interface* %0)"));
+    assert!(result.contains("call void @lib1_fb(%fb_interface* %inst)"));
+    // external POUs, hardware addresses and the generated main keep their names
+    assert!(result.contains("@__PI_X_1_2 = "));
+    assert!(result.contains("declare i32 @log("));
+    assert!(result.contains("define i32 @main()"));
+    assert!(result.contains("call void @lib1_prg(%prg_interface* @lib1_prg_instance)"));
+}
+
+#[test]
+fn code_is_generated_for_the_target_triple_and_data_layout() {
+    let source = "
+            TYPE MyUnion : UNION a : DWORD; b : POINTER TO BYTE; END_UNION END_TYPE
+            VAR_GLOBAL x : MyUnion; END_VAR
+           ";
+    let result = codegen_with_options(
+        source,
+        CodegenOptions {
+            triple: Some("arm-linux-gnueabihf".to_string()),
+            data_layout: Some("e-m:e-p:32:32-i64:64-n32-S64".to_string()),
+            ..CodegenOptions::default()
+        },
+    )
+    .unwrap();
+
+    assert!(result.contains(r#"target datalayout = "e-m:e-p:32:32-i64:64-n32-S64""#));
+    assert!(result.contains(r#"target triple = "arm-linux-gnueabihf""#));
+    // the pointer is as big as the DWORD on the 32-bit target, so the union starts with the DWORD
+    assert!(result.contains("%MyUnion = type { i32 }"));
+}
+
+#[test]
+fn optimization_level_runs_llvm_passes() {
+    let source = "
+            FUNCTION foo : DINT
+            VAR x : DINT; END_VAR
+            x := 5;
+            foo := x * 2;
+            END_FUNCTION
+           ";
+    let unoptimized = codegen_with_options(source, CodegenOptions::default()).unwrap();
+    let optimized = codegen_with_options(
+        source,
+        CodegenOptions {
+            optimization: OptimizationLevel::Default,
+            ..CodegenOptions::default()
+        },
+    )
+    .unwrap();
+
+    // the unoptimized code loads x and multiplies it, the optimized code returns the constant
+    assert!(unoptimized.contains("mul i32"));
+    assert!(!optimized.contains("mul i32"));
+    assert!(optimized.contains("ret i32 10"));
+}
+
+#[test]
+fn unsigned_integers_are_divided_as_unsigned_values() {
+    let result = codegen(
+        "
+            PROGRAM Main
+            VAR
+              x, y : ULINT;
+              a, b : DINT;
+            END_VAR
+            x := x / y;
+            x := x MOD y;
+            a := a / b;
+            a := a MOD b;
+            END_PROGRAM
+           ",
+    );
+
+    assert!(result.contains("udiv i64"));
+    assert!(result.contains("urem i64"));
+    assert!(result.contains("sdiv i32"));
+    assert!(result.contains("srem i32"));
+}
+
+#[test]
+fn integer_division_by_zero_traps_or_results_in_zero() {
+    let source = "
+            PROGRAM Main
+            VAR
+              x, y : DINT;
+            END_VAR
+            x := x / y;
+            END_PROGRAM
+           ";
+    let trapping = codegen_with_options(
+        source,
+        CodegenOptions {
+            division_checks: Some(DivisionCheckMode::Trap),
+            ..CodegenOptions::default()
+        },
+    )
+    .unwrap();
+    let zeroing = codegen_with_options(
+        source,
+        CodegenOptions {
+            division_checks: Some(DivisionCheckMode::Zero),
+            ..CodegenOptions::default()
+        },
+    )
+    .unwrap();
+
+    // we expect a call to llvm.trap if the divisor is zero
+    assert!(trapping.contains("division_by_zero:"));
+    assert!(trapping.contains("call void @llvm.trap()"));
+    // or the division by one to be replaced by zero
+    assert!(!zeroing.contains("@llvm.trap"));
+    assert!(zeroing.contains("select i1 %is_zero, i32 1, i32 %load_y"));
+    assert!(zeroing.contains("select i1 %is_zero, i32 0, i32 %tmpVar"));
+}
+
+#[test]
+fn integer_division_by_zero_calls_the_fault_handler() {
+    let source = "
+            PROGRAM Main
+            VAR
+              x, y : DINT;
+            END_VAR
+            x := x / y;
+            END_PROGRAM
+           ";
+    let options = || CodegenOptions {
+        division_checks: Some(DivisionCheckMode::Fault),
+        ..CodegenOptions::default()
+    };
+    let result = codegen_with_options(source, options()).unwrap();
+
+    // a divisor of zero calls the handler with the location of the division
+    assert!(result.contains("division_by_zero:"));
+    assert!(result.contains(r#"c"main.st:6:18\00""#));
+    assert!(result.contains("call void @rusty_division_fault(i8*"));
+    // the division results in zero if the handler returns
+    assert!(result.contains("select i1 %is_zero, i32 0, i32 %tmpVar"));
+    // the default handler is a weak definition aborting the program
+    assert!(result.contains("define weak void @rusty_division_fault(i8*"));
+
+    // a handler declared by the sources must be external
+    let handler = "
+            FUNCTION rusty_division_fault
+            VAR_INPUT
+              location : REF_TO BYTE;
+            END_VAR
+            END_FUNCTION
+           ";
+    let declared =
+        codegen_with_options(&format!("{{external}}{}{}", handler, source), options()).unwrap();
+    assert!(!declared.contains("define weak void @rusty_division_fault("));
+    assert!(declared.contains("call void @rusty_division_fault(i8*"));
+    assert_eq!(
+        codegen_with_options(&format!("{}{}", handler, source), options()),
+        Err(Diagnostic::codegen_error(
+            "The fault handler rusty_division_fault must be declared {external}",
+            SourceRange::undefined()
+        ))
+    );
+}
+
+#[test]
+fn array_indexes_are_checked_against_their_bounds() {
+    let source = "
+            PROGRAM Main
+            VAR
+              arr : ARRAY[1..3] OF DINT;
+              i : INT;
+            END_VAR
+            arr[i] := 1;
+            END_PROGRAM
+           ";
+    let unchecked = codegen(source);
+    let checked = codegen_with_options(
+        source,
+        CodegenOptions {
+            bounds_checks: true,
+            ..CodegenOptions::default()
+        },
+    )
+    .unwrap();
+
+    assert!(!unchecked.contains("rusty_bounds_fault"));
+    // the index is compared to the bounds as an i64
+    assert!(checked.contains("sext i16 %load_i to i64"));
+    assert!(checked.contains("icmp slt i64"));
+    assert!(checked.contains("icmp sgt i64"));
+    assert!(checked.contains("out_of_bounds:"));
+    // an index out of bounds calls the handler with the location of the access
+    assert!(checked.contains(r#"c"main.st:7:17\00""#));
+    assert!(checked.contains("call void @rusty_bounds_fault(i64"));
+    assert!(checked.contains("call void @llvm.trap()"));
+    // the default handler is a weak definition aborting the program
+    assert!(checked.contains("define weak void @rusty_bounds_fault("));
+}
+
+#[test]
+fn bounds_checks_call_the_handler_declared_by_the_sources() {
+    let result = codegen_with_options(
+        "
+            {external}
+            FUNCTION rusty_bounds_fault
+            VAR_INPUT
+              index, lower, upper : DINT;
+              location : REF_TO BYTE;
+            END_VAR
+            END_FUNCTION
+
+            PROGRAM Main
+            VAR
+              arr : ARRAY[1..3] OF DINT;
+              i : DINT;
+            END_VAR
+            arr[i] := 1;
+            END_PROGRAM
+           ",
+        CodegenOptions {
+            bounds_checks: true,
+            ..CodegenOptions::default()
+        },
+    )
+    .unwrap();
+
+    // the index and the bounds are converted to the declared parameter types
+    assert!(!result.contains("define weak void @rusty_bounds_fault("));
+    assert!(result.contains("trunc i64"));
+    assert!(result.contains("call void @rusty_bounds_fault(i32"));
+}
+
+#[test]
+fn integer_arithmetic_follows_the_overflow_mode() {
+    let source = "
+            PROGRAM Main
+            VAR
+              d, e : DINT;
+              u : UDINT;
+              i : INT;
+            END_VAR
+            d := d + e;
+            u := u * u;
+            i := i + 1;
+            END_PROGRAM
+           ";
+    let options = |overflow| CodegenOptions {
+        overflow,
+        ..CodegenOptions::default()
+    };
+    let wrapping = codegen_with_options(source, options(OverflowMode::Wrap)).unwrap();
+    let trapping = codegen_with_options(source, options(OverflowMode::Trap)).unwrap();
+    let saturating = codegen_with_options(source, options(OverflowMode::Saturate)).unwrap();
+
+    assert!(!wrapping.contains("@llvm.sadd"));
+    assert!(wrapping.contains("add i32"));
+
+    // an overflowing result aborts the program
+    assert!(trapping.contains("call { i32, i1 } @llvm.sadd.with.overflow.i32(i32"));
+    assert!(trapping.contains("call { i32, i1 } @llvm.umul.with.overflow.i32(i32"));
+    assert!(trapping.contains("overflow:"));
+    assert!(trapping.contains("call void @llvm.trap()"));
+    // the INT result computed as DINT is checked against the bounds of INT
+    assert!(trapping.contains("icmp sgt i32"));
+    assert!(trapping.contains("i32 32767"));
+    assert!(trapping.contains("trunc i32"));
+    // unused intrinsics are not declared
+    assert!(!trapping.contains("@llvm.ssub.with.overflow"));
+
+    // an overflowing result is limited to the bounds of its type
+    assert!(saturating.contains("call i32 @llvm.sadd.sat.i32(i32"));
+    assert!(saturating.contains("call i32 @llvm.umul.fix.sat.i32(i32"));
+    assert!(saturating.contains("i32 0)"));
+    assert!(saturating.contains("i32 32767"));
+    assert!(saturating.contains("i32 -32768"));
+    assert!(!saturating.contains("@llvm.trap"));
+}
+
+#[test]
+fn exponentiations_of_reals_call_the_pow_intrinsic() {
+    let result = codegen(
+        "
+            PROGRAM Main
+            VAR
+              r, s : REAL;
+              l : LREAL;
+            END_VAR
+            r := r ** s;
+            l := EXPT(l, r);
+            END_PROGRAM
+           ",
+    );
+
+    assert!(result.contains("call float @llvm.pow.f32(float %load_r, float %load_s)"));
+    assert!(result.contains("call double @llvm.pow.f64(double %load_l,"));
+    assert!(result.contains("declare float @llvm.pow.f32(float, float)"));
+    assert!(result.contains("declare double @llvm.pow.f64(double, double)"));
+}
+
+#[test]
+fn expt_calls_with_named_arguments_call_the_pow_intrinsic() {
+    let result = codegen(
+        "
+            PROGRAM Main
+            VAR
+              r : REAL;
+              l : LREAL;
+            END_VAR
+            l := EXPT(IN1 := l, IN2 := r);
+            r := EXPT(IN2 := 2, IN1 := r);
+            END_PROGRAM
+           ",
+    );
+
+    assert!(result.contains("call double @llvm.pow.f64(double %load_l,"));
+    assert!(result.contains("call float @llvm.pow.f32(float %load_r,"));
+}
+
+#[test]
+fn a_user_defined_expt_function_is_called_instead_of_the_exponentiation() {
+    let result = codegen(
+        "
+            FUNCTION EXPT : DINT
+            VAR_INPUT IN1, IN2 : DINT; END_VAR
+                EXPT := IN1 * IN2;
+            END_FUNCTION
+
+            PROGRAM Main
+            VAR
+              x : DINT;
+            END_VAR
+            x := EXPT(x, 2);
+            END_PROGRAM
+           ",
+    );
+
+    assert!(result.contains("call i32 @EXPT(%EXPT_interface* %EXPT_instance)"));
+    assert!(!result.contains("llvm.pow"));
+}
+
+#[test]
+fn unused_pow_intrinsics_are_not_declared() {
+    let result = codegen(
+        "
+            PROGRAM Main
+            VAR
+              r, s : REAL;
+            END_VAR
+            r := r * s;
+            END_PROGRAM
+           ",
+    );
+
+    assert!(!result.contains("llvm.pow"));
+}
+
+#[test]
+fn exponentiations_of_integers_multiply_in_a_loop() {
+    let result = codegen(
+        "
+            PROGRAM Main
+            VAR
+              x, y : DINT;
+              u : ULINT;
+            END_VAR
+            x := x ** y;
+            u := u ** u;
+            END_PROGRAM
+           ",
+    );
+
+    assert!(result.contains("power_condition:"));
+    assert!(result.contains("power_body:"));
+    assert!(result.contains("mul i32 %result, %factor"));
+    assert!(result.contains("mul i64 %result"));
+    assert!(!result.contains("llvm.pow"));
+}
+
+#[test]
+fn numeric_functions_call_intrinsics_or_libm_functions() {
+    let result = codegen(
+        "
+            PROGRAM Main
+            VAR
+              i : INT;
+              r : REAL;
+              l : LREAL;
+            END_VAR
+            r := SQRT(r);
+            l := LN(l);
+            r := TAN(i);
+            l := ATAN2(l, r);
+            r := ABS(r);
+            i := ABS(i);
+            END_PROGRAM
+           ",
+    );
+
+    assert!(result.contains("call float @llvm.sqrt.f32(float %load_r)"));
+    assert!(result.contains("call double @llvm.log.f64(double %load_l)"));
+    assert!(result.contains("call float @tanf(float"));
+    assert!(result.contains("call double @atan2(double %load_l,"));
+    assert!(result.contains("call float @llvm.fabs.f32(float %load_r"));
+    // ABS of an integer negates negative values
+    assert!(result.contains("sub i16 0, %load_i"));
+    assert!(result.contains("%abs = select i1"));
+    assert!(result.contains("declare float @tanf(float)"));
+    assert!(result.contains("declare double @atan2(double, double)"));
+    // functions that are not called are not declared
+    assert!(!result.contains("@tan("));
+    assert!(!result.contains("llvm.cos"));
+}
+
+#[test]
+fn numeric_functions_declared_by_the_user_take_precedence() {
+    let result = codegen(
+        "
+            FUNCTION sqrt : REAL
+            VAR_INPUT x : REAL; END_VAR
+            sqrt := x;
+            END_FUNCTION
+
+            PROGRAM Main
+            VAR
+              r : REAL;
+            END_VAR
+            r := SQRT(r);
+            END_PROGRAM
+           ",
+    );
+
+    assert!(result.contains("@sqrt("));
+    assert!(!result.contains("llvm.sqrt"));
+}
+
+#[test]
+fn assignments_of_arrays_and_structs_are_copied_with_memcpy() {
+    let result = codegen(
+        "
+            TYPE Point : STRUCT x, y : DINT; END_STRUCT END_TYPE
+
+            PROGRAM Main
+            VAR
+              a, b : ARRAY[0..999] OF DINT;
+              m : ARRAY[0..1] OF ARRAY[0..999] OF DINT;
+              p, q : Point;
+            END_VAR
+            a := b;
+            m[1] := a;
+            p := q;
+            p := p;
+            END_PROGRAM
+           ",
+    );
+
+    // the aggregates are copied without loading them
+    assert_eq!(4, result.matches("call void @llvm.memcpy").count());
+    assert!(!result.contains("load [1000 x i32]"));
+    assert!(!result.contains("load %Point"));
+}
+
 #[test]
 fn using_global_consts_in_expressions() {
     //GIVEN some constants used in an expression
@@ -2822,3 +3507,145 @@ fn optional_output_assignment() {
     // codegen should be successful
     insta::assert_snapshot!(result);
 }
+
+#[test]
+fn structs_with_a_small_pack_mode_are_generated_packed() {
+    let result = codegen(
+        "
+        {attribute 'pack_mode' := '1'}
+        TYPE Packed : STRUCT a : BYTE; b : DINT; END_STRUCT END_TYPE
+        {attribute 'pack_mode' := '8'}
+        TYPE Natural : STRUCT a : BYTE; b : DINT; END_STRUCT END_TYPE
+        VAR_GLOBAL p : Packed; n : Natural; END_VAR
+        ",
+    );
+
+    assert!(result.contains("%Packed = type <{ i8, i32 }>"));
+    assert!(result.contains("%Natural = type { i8, i32 }"));
+}
+
+#[test]
+fn signatures_of_the_pous_and_the_project_are_generated_as_constants() {
+    let src: SourceCode = "
+        FUNCTION foo : INT
+        foo := 1;
+        END_FUNCTION
+
+        PROGRAM prg
+        VAR x : INT; END_VAR
+        x := foo();
+        END_PROGRAM
+        "
+    .into();
+    let context = Context::create();
+    let (_, code_gen) = compile_module(
+        &context,
+        vec![src],
+        vec![],
+        None,
+        Diagnostician::null_diagnostician(),
+        CodegenOptions {
+            signatures: Some("signatures.json".to_string()),
+            symbol_prefix: Some("lib_".to_string()),
+            ..CodegenOptions::default()
+        },
+    )
+    .unwrap();
+    let signatures = code_gen.generate_project_signature();
+    let result = code_gen.module.print_to_string().to_string();
+
+    // the signatures are prefixed like the POUs and listed without the prefix
+    let names = signatures
+        .pous
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, vec!["foo", "prg"]);
+    for (name, signature) in &signatures.pous {
+        assert!(result.contains(&format!(
+            "@lib___signature_{} = constant i32 {}",
+            name, *signature as i32
+        )));
+    }
+    assert!(result.contains(&format!(
+        "@lib___signature = constant i32 {}",
+        signatures.project as i32
+    )));
+}
+
+#[test]
+fn pous_are_generated_into_their_own_sections() {
+    let result = codegen_with_options(
+        "
+        VAR_GLOBAL RETAIN
+            counter : INT;
+        END_VAR
+
+        FUNCTION foo : INT
+        foo := 1;
+        END_FUNCTION
+
+        FUNCTION_BLOCK fb
+        VAR x : INT := 3; END_VAR
+        END_FUNCTION_BLOCK
+
+        PROGRAM prg
+        VAR
+            instance : fb;
+            name : STRING := 'pump';
+        END_VAR
+        instance();
+        END_PROGRAM
+
+        ACTIONS prg
+        ACTION act
+        END_ACTION
+        END_ACTIONS
+        ",
+        CodegenOptions {
+            pou_sections: true,
+            ..CodegenOptions::default()
+        },
+    )
+    .unwrap();
+
+    assert!(result.contains(r#"define i16 @foo(%foo_interface* %0) section ".text.foo""#));
+    assert!(result.contains(r#"define void @fb(%fb_interface* %0) section ".text.fb""#));
+    assert!(result.contains(r#"define void @prg(%prg_interface* %0) section ".text.prg""#));
+    // actions are POUs of their own
+    assert!(result.contains(r#"define void @prg.act(%prg_interface* %0) section ".text.prg.act""#));
+    // the instance of a program and the initial values are the data of their POU
+    assert!(result.contains(r#"@prg_instance = global %prg_interface {"#));
+    assert!(result.contains(r#"section ".data.prg""#));
+    assert!(result.contains(r#"@fb__init = global %fb_interface { i16 3 }, section ".data.fb""#));
+    assert!(result.contains(r#"@prg.name__init = unnamed_addr constant [81 x i8] c"pump"#));
+    assert!(result.contains(r#"section ".rodata.prg""#));
+    // retained variables stay in their section
+    assert!(result.contains(r#"section ".retain""#));
+}
+
+#[test]
+fn adr_and_ref_calls_are_generated_as_the_address_unless_the_user_defines_them() {
+    let result = codegen(
+        "
+        FUNCTION REF : DINT
+        VAR_INPUT x : DINT; END_VAR
+            REF := x + 1;
+        END_FUNCTION
+
+        PROGRAM prg
+        VAR
+            x, y : DINT;
+            p : REF_TO DINT;
+        END_VAR
+            p := ADR(x);
+            y := REF(x);
+        END_PROGRAM
+        ",
+    );
+
+    // ADR is generated as the address of its argument
+    assert!(result.contains("store i32* %x, i32** %p"));
+    // the user's REF function is called
+    assert!(result.contains("call i32 @REF(%REF_interface* %REF_instance)"));
+}
@@ -529,3 +529,18 @@ fn pointer_binary_expression_modulo() {
         panic!("expected code-gen error but got none")
     }
 }
+
+#[test]
+fn cyclic_alias_types_are_reported_instead_of_overflowing() {
+    let result = codegen_without_unwrap(
+        "
+        TYPE A : B; END_TYPE
+        TYPE B : A; END_TYPE
+        ",
+    );
+    if let Err(msg) = result {
+        assert_eq!(Diagnostic::unknown_type("B", SourceRange::undefined()), msg);
+    } else {
+        panic!("expected code-gen error but got none")
+    }
+}
@@ -0,0 +1,105 @@
+use crate::{test_utils::tests::codegen_with_options, CodegenOptions};
+
+fn codegen_with_debug(src: &str) -> String {
+    codegen_with_options(
+        src,
+        CodegenOptions {
+            debug: true,
+            ..CodegenOptions::default()
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn no_debug_information_is_generated_by_default() {
+    let result = codegen_with_options(
+        "
+        FUNCTION foo : INT
+        VAR_INPUT x : INT; END_VAR
+            foo := x;
+        END_FUNCTION
+        ",
+        CodegenOptions::default(),
+    )
+    .unwrap();
+
+    assert!(!result.contains("!dbg"));
+    assert!(!result.contains("DICompileUnit"));
+}
+
+#[test]
+fn pous_are_described_by_subprograms() {
+    let result = codegen_with_debug(
+        "
+        FUNCTION foo : INT
+        VAR_INPUT x : INT; END_VAR
+            foo := x;
+        END_FUNCTION
+
+        PROGRAM main
+        VAR y : INT; END_VAR
+            y := foo(2);
+        END_PROGRAM
+        ",
+    );
+
+    assert!(result.contains(r#"!DICompileUnit(language: DW_LANG_C"#));
+    assert!(result.contains(r#"!"Debug Info Version", i32 3}"#));
+    assert!(result.contains(r#"!DIFile(filename: "main.st""#));
+    assert!(result.contains(r#"define i16 @foo(%foo_interface* %0) !dbg"#));
+    assert!(result.contains(r#"!DISubprogram(name: "foo", linkageName: "foo""#));
+    assert!(result.contains(r#"!DISubprogram(name: "main", linkageName: "main""#));
+}
+
+#[test]
+fn statements_are_attributed_to_their_lines() {
+    let result = codegen_with_debug(
+        "
+        PROGRAM main
+        VAR x, y : DINT; END_VAR
+            x := 1;
+            y := x + 2;
+        END_PROGRAM
+        ",
+    );
+
+    assert!(result.contains("store i32 1, i32* %x, align 4, !dbg"));
+    assert!(result.contains("!DILocation(line: 4, column: 13"));
+    assert!(result.contains("!DILocation(line: 5, column: 13"));
+}
+
+#[test]
+fn variables_are_described_with_their_types() {
+    let result = codegen_with_debug(
+        "
+        TYPE Point : STRUCT x, y : REAL; END_STRUCT END_TYPE
+
+        VAR_GLOBAL
+            origin : Point;
+            flag : BOOL;
+        END_VAR
+
+        FUNCTION foo : DINT
+        VAR_INPUT a : ARRAY[1..3] OF UINT; END_VAR
+        VAR_TEMP s : STRING[10]; END_VAR
+        END_FUNCTION
+        ",
+    );
+
+    // the global variables
+    assert!(result.contains(r#"!DIGlobalVariable(name: "origin""#));
+    assert!(result.contains(r#"!DIGlobalVariable(name: "flag""#));
+    assert!(result.contains(r#"!DICompositeType(tag: DW_TAG_structure_type, name: "Point""#));
+    assert!(result.contains(r#"!DIDerivedType(tag: DW_TAG_member, name: "y""#));
+    assert!(result.contains(r#"!DIBasicType(name: "BOOL", size: 8, encoding: DW_ATE_boolean"#));
+    // the local variables
+    assert!(result.contains(r#"!DILocalVariable(name: "a""#));
+    assert!(result.contains(r#"!DILocalVariable(name: "s""#));
+    assert!(result.contains(r#"!DISubrange(count: 3, lowerBound: 1)"#));
+    assert!(result.contains(r#"!DIBasicType(name: "UINT", size: 16, encoding: DW_ATE_unsigned"#));
+    assert!(
+        result.contains(r#"!DIBasicType(name: "CHAR", size: 8, encoding: DW_ATE_unsigned_char"#)
+    );
+    assert!(result.contains("call void @llvm.dbg.declare("));
+}
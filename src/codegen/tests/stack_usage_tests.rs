@@ -0,0 +1,80 @@
+use inkwell::context::Context;
+
+use crate::{
+    codegen::stack_usage::{collect_stack_usage, generate_stack_report},
+    compile_module,
+    diagnostics::Diagnostician,
+    CodegenOptions, SourceCode,
+};
+
+#[test]
+fn stack_usage_follows_the_deepest_call_path() {
+    let src: SourceCode = "
+        FUNCTION small : DINT
+        VAR
+            x : DINT;
+        END_VAR
+        small := x;
+        END_FUNCTION
+
+        FUNCTION large : DINT
+        VAR
+            buffer : ARRAY[0..99] OF DINT;
+        END_VAR
+        large := buffer[0];
+        END_FUNCTION
+
+        FUNCTION countdown : DINT
+        VAR_INPUT
+            n : DINT;
+        END_VAR
+        IF n > 0 THEN
+            countdown := countdown(n - 1);
+        END_IF
+        END_FUNCTION
+
+        PROGRAM main
+        VAR
+            y : DINT;
+        END_VAR
+        y := small() + large() + countdown(3);
+        END_PROGRAM
+        "
+    .into();
+    let context = Context::create();
+    let (_, code_gen) = compile_module(
+        &context,
+        vec![src],
+        vec![],
+        None,
+        Diagnostician::null_diagnostician(),
+        CodegenOptions::default(),
+    )
+    .unwrap();
+
+    let usages = collect_stack_usage(&code_gen.module);
+    let usage = |name: &str| usages.iter().find(|it| it.name == name).unwrap();
+
+    // the frame holds the local variables
+    assert!(usage("large").frame_size >= 400);
+    assert!(usage("small").frame_size < usage("large").frame_size);
+    assert_eq!(usage("large").worst_case, usage("large").frame_size);
+    // the worst case of main follows the call of the largest function
+    let main = usage("main");
+    assert_eq!(
+        main.call_path,
+        vec!["main".to_string(), "large".to_string()]
+    );
+    assert_eq!(main.worst_case, main.frame_size + usage("large").worst_case);
+    assert!(!main.recursive);
+    // the recursion is flagged instead of followed
+    let countdown = usage("countdown");
+    assert!(countdown.recursive);
+    assert_eq!(countdown.call_path, vec!["countdown".to_string()]);
+    assert_eq!(countdown.worst_case, countdown.frame_size);
+
+    let report = generate_stack_report(&usages);
+    assert!(report.contains("main -> large"));
+    assert!(report.contains("countdown (recursive)"));
+    assert!(report.contains("exclude the recursion: countdown"));
+}
@@ -0,0 +1,448 @@
+// Copyright (c) 2022 Ghaith Hachem and Mathias Rieder
+//! Collects the cross-reference of a compilation as JSON: the locations every variable is read
+//! and written at and every POU is called at, so the uses of a signal can be followed through
+//! the sources without searching them.
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::{
+    ast::{AstStatement, CompilationUnit, Implementation, SourceRange},
+    diagnostics::{Diagnostic, ErrNo},
+    index::Index,
+    resolver::{AnnotationMap, AnnotationMapImpl, StatementAnnotation},
+};
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct CrossReference {
+    variables: Vec<Symbol>,
+    pous: Vec<Symbol>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Symbol {
+    /// the qualified name of the symbol, e.g. `prg.x` or `fb.method`
+    name: String,
+    /// the symbols declared by precompiled libraries have no declaration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    declaration: Option<Location>,
+    /// the reads, writes and calls of the symbol in the order of the sources
+    references: Vec<Reference>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Reference {
+    kind: ReferenceKind,
+    #[serde(flatten)]
+    location: Location,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum ReferenceKind {
+    Read,
+    Write,
+    Call,
+}
+
+/// a 1-based line and column in a source file
+#[derive(Debug, PartialEq, Serialize)]
+struct Location {
+    file: String,
+    line: usize,
+    column: usize,
+}
+
+/// Collects the variables and POUs declared by the given units and the locations they are
+/// used at. The instances of generic functions are reported as their generic function
+pub fn collect_cross_reference<'u>(
+    units: impl IntoIterator<Item = &'u CompilationUnit>,
+    annotations: &AnnotationMapImpl,
+    index: &Index,
+) -> CrossReference {
+    let units = units.into_iter().collect::<Vec<_>>();
+    let mut collector = Collector {
+        annotations,
+        index,
+        variables: IndexMap::new(),
+        pous: IndexMap::new(),
+    };
+    //the symbols are listed in the order of their declarations
+    for unit in &units {
+        for pou in &unit.units {
+            collector.declare_pou(&pou.name, &pou.location, unit);
+            for variable in pou
+                .variable_blocks
+                .iter()
+                .flat_map(|it| it.variables.iter())
+            {
+                let name = format!("{}.{}", pou.name, variable.name);
+                collector.declare_variable(&name, &variable.location, unit);
+            }
+        }
+        for variable in unit.global_vars.iter().flat_map(|it| it.variables.iter()) {
+            collector.declare_variable(&variable.name, &variable.location, unit);
+        }
+        //actions are implemented without a declaration
+        for implementation in collector.get_implementations(unit) {
+            collector.declare_pou(&implementation.name, &implementation.location, unit);
+        }
+    }
+
+    for unit in &units {
+        let initializers = unit
+            .global_vars
+            .iter()
+            .chain(unit.units.iter().flat_map(|it| it.variable_blocks.iter()))
+            .flat_map(|it| it.variables.iter())
+            .filter_map(|it| it.initializer.as_ref());
+        for initializer in initializers {
+            collector.visit(initializer, unit);
+        }
+        for implementation in collector.get_implementations(unit) {
+            for statement in &implementation.statements {
+                collector.visit(statement, unit);
+            }
+        }
+    }
+
+    CrossReference {
+        variables: collector.variables.into_values().collect(),
+        pous: collector.pous.into_values().collect(),
+    }
+}
+
+pub fn generate_cross_reference(cross_reference: &CrossReference) -> Result<String, Diagnostic> {
+    serde_json::to_string_pretty(cross_reference).map_err(|e| Diagnostic::GeneralError {
+        message: e.to_string(),
+        err_no: ErrNo::general__io_err,
+    })
+}
+
+/// collects the symbols by their lower case qualified names
+struct Collector<'a> {
+    annotations: &'a AnnotationMapImpl,
+    index: &'a Index,
+    variables: IndexMap<String, Symbol>,
+    pous: IndexMap<String, Symbol>,
+}
+
+impl Collector<'_> {
+    /// returns the implementations of the given unit, without the instances of generic functions
+    fn get_implementations<'u>(
+        &self,
+        unit: &'u CompilationUnit,
+    ) -> impl Iterator<Item = &'u Implementation> + 'u {
+        let instances = self
+            .annotations
+            .generic_instances
+            .keys()
+            .map(|it| it.to_lowercase())
+            .collect::<Vec<_>>();
+        unit.implementations
+            .iter()
+            .filter(move |it| !instances.contains(&it.name.to_lowercase()))
+    }
+
+    fn declare_variable(&mut self, name: &str, location: &SourceRange, unit: &CompilationUnit) {
+        declare(&mut self.variables, name, location, unit);
+    }
+
+    fn declare_pou(&mut self, name: &str, location: &SourceRange, unit: &CompilationUnit) {
+        declare(&mut self.pous, name, location, unit);
+    }
+
+    fn visit(&mut self, statement: &AstStatement, unit: &CompilationUnit) {
+        match statement {
+            AstStatement::Assignment {
+                left: target,
+                right: value,
+                ..
+            }
+            | AstStatement::OutputAssignment {
+                left: value,
+                right: target,
+                ..
+            } => {
+                self.visit_assignment_target(target, unit);
+                self.visit(value, unit);
+            }
+            AstStatement::CallStatement {
+                operator,
+                parameters,
+                ..
+            } => {
+                if let Some(implementation) = self
+                    .annotations
+                    .get_call_name(operator)
+                    .and_then(|it| self.index.find_implementation(it))
+                {
+                    let name = implementation.get_call_name();
+                    //the instances of generic functions are called as their generic function
+                    let name = self
+                        .annotations
+                        .generic_instances
+                        .get(name)
+                        .map_or(name, String::as_str);
+                    let location = operator.get_location();
+                    add_reference(&mut self.pous, name, ReferenceKind::Call, &location, unit);
+                }
+                self.visit(operator, unit);
+                if let Some(parameters) = parameters.as_ref() {
+                    self.visit(parameters, unit);
+                }
+            }
+            //the elements of a qualified reference are annotated like the reference itself
+            AstStatement::QualifiedReference { elements, .. } => {
+                elements.iter().for_each(|it| self.visit(it, unit))
+            }
+            _ => {
+                let location = statement.get_location();
+                match self.annotations.get(statement) {
+                    Some(StatementAnnotation::Variable { qualified_name, .. }) => add_reference(
+                        &mut self.variables,
+                        qualified_name,
+                        ReferenceKind::Read,
+                        &location,
+                        unit,
+                    ),
+                    //reading a property calls its getter
+                    Some(StatementAnnotation::Property {
+                        getter: Some(getter),
+                        ..
+                    }) => {
+                        add_reference(&mut self.pous, getter, ReferenceKind::Call, &location, unit)
+                    }
+                    _ => {}
+                }
+                for child in statement.get_children() {
+                    self.visit(child, unit);
+                }
+            }
+        }
+    }
+
+    /// visits the target of an assignment, the assigned variable is written. The variables
+    /// accessed to get to the assigned element are read
+    fn visit_assignment_target(&mut self, target: &AstStatement, unit: &CompilationUnit) {
+        match target {
+            AstStatement::Reference { .. } => self.visit_written(target, unit),
+            AstStatement::QualifiedReference { elements, .. } => {
+                if let Some((written, accessed)) = elements.split_last() {
+                    accessed.iter().for_each(|it| self.visit(it, unit));
+                    self.visit_written(written, unit);
+                }
+            }
+            AstStatement::ArrayAccess {
+                reference, access, ..
+            } => {
+                self.visit_assignment_target(reference, unit);
+                self.visit(access, unit);
+            }
+            _ => self.visit(target, unit),
+        }
+    }
+
+    fn visit_written(&mut self, target: &AstStatement, unit: &CompilationUnit) {
+        let location = target.get_location();
+        match self.annotations.get(target) {
+            Some(StatementAnnotation::Variable { qualified_name, .. }) => add_reference(
+                &mut self.variables,
+                qualified_name,
+                ReferenceKind::Write,
+                &location,
+                unit,
+            ),
+            //writing a property calls its setter
+            Some(StatementAnnotation::Property {
+                setter: Some(setter),
+                ..
+            }) => add_reference(&mut self.pous, setter, ReferenceKind::Call, &location, unit),
+            _ => self.visit(target, unit),
+        }
+    }
+}
+
+fn declare(
+    symbols: &mut IndexMap<String, Symbol>,
+    name: &str,
+    location: &SourceRange,
+    unit: &CompilationUnit,
+) {
+    get_symbol(symbols, name)
+        .declaration
+        .get_or_insert_with(|| get_location(location, unit));
+}
+
+fn add_reference(
+    symbols: &mut IndexMap<String, Symbol>,
+    name: &str,
+    kind: ReferenceKind,
+    location: &SourceRange,
+    unit: &CompilationUnit,
+) {
+    get_symbol(symbols, name).references.push(Reference {
+        kind,
+        location: get_location(location, unit),
+    });
+}
+
+fn get_symbol<'s>(symbols: &'s mut IndexMap<String, Symbol>, name: &str) -> &'s mut Symbol {
+    symbols
+        .entry(name.to_lowercase())
+        .or_insert_with(|| Symbol {
+            name: name.to_string(),
+            declaration: None,
+            references: vec![],
+        })
+}
+
+fn get_location(location: &SourceRange, unit: &CompilationUnit) -> Location {
+    let (line, column) = location.get_line_and_column(&unit.new_lines);
+    Location {
+        file: unit.file_name.clone(),
+        line,
+        column,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        cross_reference::{collect_cross_reference, ReferenceKind},
+        resolver::{const_evaluator::evaluate_constants, TypeAnnotator},
+        test_utils::tests::index,
+    };
+
+    use super::CrossReference;
+
+    fn cross_reference(src: &str) -> CrossReference {
+        let (unit, index) = index(src);
+        let (mut index, ..) = evaluate_constants(index);
+        let (mut annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+        index.import(std::mem::take(&mut annotations.new_index));
+        collect_cross_reference(std::iter::once(&unit), &annotations, &index)
+    }
+
+    #[test]
+    fn variables_are_listed_with_their_reads_and_writes() {
+        let cross_reference = cross_reference(
+            "
+            VAR_GLOBAL
+                level : INT;
+            END_VAR
+
+            PROGRAM prg
+            VAR
+                x : INT;
+                values : ARRAY[0..2] OF INT;
+            END_VAR
+            x := level;
+            values[x] := x + 1;
+            level := values[0];
+            END_PROGRAM
+            ",
+        );
+
+        let references = cross_reference
+            .variables
+            .iter()
+            .map(|symbol| {
+                (
+                    symbol.name.as_str(),
+                    symbol.declaration.as_ref().map(|it| it.line),
+                    symbol
+                        .references
+                        .iter()
+                        .map(|it| (it.kind, it.location.line, it.location.column))
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            references,
+            vec![
+                (
+                    "prg.x",
+                    Some(8),
+                    vec![
+                        (ReferenceKind::Write, 11, 13),
+                        (ReferenceKind::Read, 12, 20),
+                        (ReferenceKind::Read, 12, 26),
+                    ]
+                ),
+                (
+                    "prg.values",
+                    Some(9),
+                    vec![
+                        (ReferenceKind::Write, 12, 13),
+                        (ReferenceKind::Read, 13, 22),
+                    ]
+                ),
+                (
+                    "level",
+                    Some(3),
+                    vec![
+                        (ReferenceKind::Read, 11, 18),
+                        (ReferenceKind::Write, 13, 13),
+                    ]
+                ),
+            ]
+        );
+        assert_eq!(
+            cross_reference.variables[0]
+                .declaration
+                .as_ref()
+                .unwrap()
+                .file,
+            "main.st"
+        );
+    }
+
+    #[test]
+    fn pous_are_listed_with_their_calls() {
+        let cross_reference = cross_reference(
+            "
+            FUNCTION foo : INT
+            END_FUNCTION
+
+            FUNCTION_BLOCK fb
+            END_FUNCTION_BLOCK
+
+            PROGRAM prg
+            VAR
+                instance : fb;
+                x : INT;
+            END_VAR
+            x := foo();
+            instance(); instance();
+            END_PROGRAM
+            ",
+        );
+
+        let calls = cross_reference
+            .pous
+            .iter()
+            .map(|symbol| {
+                (
+                    symbol.name.as_str(),
+                    symbol
+                        .references
+                        .iter()
+                        .map(|it| (it.kind, it.location.line, it.location.column))
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            calls,
+            vec![
+                ("foo", vec![(ReferenceKind::Call, 13, 18)]),
+                (
+                    "fb",
+                    vec![(ReferenceKind::Call, 14, 13), (ReferenceKind::Call, 14, 25)]
+                ),
+                ("prg", vec![]),
+            ]
+        );
+    }
+}
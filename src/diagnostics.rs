@@ -1,4 +1,4 @@
-use std::ops::Range;
+use std::{cell::Cell, collections::HashMap, ops::Range, sync::Arc};
 
 use codespan_reporting::{
     diagnostic::Label,
@@ -6,8 +6,9 @@ use codespan_reporting::{
     term::termcolor::{ColorChoice, StandardStream},
 };
 use inkwell::support::LLVMString;
+use serde::{Deserialize, Serialize};
 
-use crate::ast::{DataTypeDeclaration, PouType, SourceRange};
+use crate::ast::{DataTypeDeclaration, NewLines, PouType, SourceRange, WarningPragma};
 
 pub const INTERNAL_LLVM_ERROR: &str = "internal llvm codegen error";
 
@@ -36,11 +37,15 @@ pub enum ErrNo {
     //general
     general__io_err,
     general__param_err,
+    general__compilation_aborted,
 
     //syntax
     syntax__generic_error,
     syntax__missing_token,
     syntax__unexpected_token,
+    syntax__invalid_pragma,
+    syntax__invalid_directive,
+    syntax__unmatched_directive,
 
     //semantic
     // pou related
@@ -49,6 +54,17 @@ pub enum ErrNo {
     pou__unsupported_return_type,
     pou__empty_variable_block,
     pou__missing_action_container,
+    pou__invalid_action_container,
+    pou__invalid_interface,
+    pou__missing_interface_method,
+    pou__incompatible_interface_method,
+    pou__invalid_super_class,
+    pou__cyclic_inheritance,
+    pou__incompatible_override,
+    pou__invalid_program_instance,
+    pou__unresolved_task,
+    pou__unused_pou,
+    pou__recursive_call,
 
     //variable related
     var__unresolved_constant,
@@ -56,9 +72,29 @@ pub enum ErrNo {
     var__invalid_constant,
     var__cannot_assign_to_const,
     var__invalid_assignment,
+    var__invalid_in_out_argument,
+    var__duplicate_parameter_assignment,
+    var__invalid_output_assignment,
+    var__invalid_argument_type,
+    var__duplicate_global_variable,
+    var__unresolved_external,
+    var__incompatible_external,
+    var__partial_initializer,
+    var__too_many_initializers,
+    var__invalid_config_variable,
+    var__unused_variable,
+    var__read_before_assignment,
 
     //reference related
     reference__unresolved,
+    reference__illegal_access,
+    reference__invalid_property_access,
+    reference__duplicate_declaration,
+
+    //statement related
+    case__duplicate_condition,
+    loop__control_outside_loop,
+    statement__unreachable_code,
 
     //type related
     type__cast_error,
@@ -71,10 +107,14 @@ pub enum ErrNo {
     type__incompatible_arrayaccess_range,
     type__incompatible_arrayaccess_variable,
     type__incompatible_arrayaccess_type,
+    type__invalid_array_range,
+    type__cyclic_definition,
     type__expected_literal,
     type__invalid_nature,
     type__unknown_nature,
     type__unresolved_generic,
+    type__invalid_attribute,
+    type__invalid_layout,
 
     //codegen related
     codegen__general,
@@ -105,6 +145,39 @@ impl Diagnostic {
         }
     }
 
+    pub fn invalid_pragma(pragma: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid pragma {}, expected {{attribute 'name'}}, {{attribute 'name' := 'value'}} or {{name}}",
+                pragma
+            ),
+            range,
+            err_no: ErrNo::syntax__invalid_pragma,
+        }
+    }
+
+    pub fn invalid_directive(directive: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid directive {}, expected a condition of defined(name), NOT, AND, OR and parentheses or a name to define",
+                directive
+            ),
+            range,
+            err_no: ErrNo::syntax__invalid_directive,
+        }
+    }
+
+    pub fn unmatched_directive(directive: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Unmatched directive {}, every {{IF ...}} must be closed by an {{END_IF}} and may contain a single {{ELSE}} after its {{ELSIF ...}} branches",
+                directive
+            ),
+            range,
+            err_no: ErrNo::syntax__unmatched_directive,
+        }
+    }
+
     pub fn unexpected_initializer_on_function_return(range: SourceRange) -> Diagnostic {
         Diagnostic::SyntaxError {
             message: "Return types cannot have a default value".into(),
@@ -174,6 +247,112 @@ impl Diagnostic {
         }
     }
 
+    pub fn invalid_action_container(container: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid action container '{:}', actions can only be declared for PROGRAMs and FUNCTION_BLOCKs",
+                container
+            ),
+            range,
+            err_no: ErrNo::pou__invalid_action_container,
+        }
+    }
+
+    pub fn invalid_interface(name: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("'{:}' is not an INTERFACE and cannot be implemented", name),
+            range,
+            err_no: ErrNo::pou__invalid_interface,
+        }
+    }
+
+    pub fn missing_interface_method(
+        pou: &str,
+        method: &str,
+        interface: &str,
+        range: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "{:} does not implement method {:} of interface {:}",
+                pou, method, interface
+            ),
+            range,
+            err_no: ErrNo::pou__missing_interface_method,
+        }
+    }
+
+    pub fn incompatible_interface_method(
+        method: &str,
+        interface: &str,
+        range: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "The signature of {:} does not match the declaration in interface {:}",
+                method, interface
+            ),
+            range,
+            err_no: ErrNo::pou__incompatible_interface_method,
+        }
+    }
+
+    pub fn invalid_super_class(pou: &str, super_class: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "{:} cannot extend '{:}', a CLASS can only extend a CLASS and a FUNCTION_BLOCK only a FUNCTION_BLOCK",
+                pou, super_class
+            ),
+            range,
+            err_no: ErrNo::pou__invalid_super_class,
+        }
+    }
+
+    pub fn cyclic_inheritance(pou: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("{:} cannot extend itself", pou),
+            range,
+            err_no: ErrNo::pou__cyclic_inheritance,
+        }
+    }
+
+    pub fn incompatible_override(method: &str, overridden: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "The signature of {:} does not match the overridden method {:}",
+                method, overridden
+            ),
+            range,
+            err_no: ErrNo::pou__incompatible_override,
+        }
+    }
+
+    pub fn invalid_program_instance(
+        instance: &str,
+        program: &str,
+        range: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Program instance '{:}' cannot be of type '{:}', only PROGRAMs can be instantiated by a resource",
+                instance, program
+            ),
+            range,
+            err_no: ErrNo::pou__invalid_program_instance,
+        }
+    }
+
+    pub fn unresolved_task(task: &str, resource: &str, range: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Task '{:}' is not declared in resource '{:}'",
+                task, resource
+            ),
+            range,
+            err_no: ErrNo::pou__unresolved_task,
+        }
+    }
+
     pub fn missing_action_container(range: SourceRange) -> Diagnostic {
         Diagnostic::SyntaxError {
             message: "Missing Actions Container Name".to_string(),
@@ -190,6 +369,29 @@ impl Diagnostic {
         }
     }
 
+    pub fn illegal_access(reference: &str, owner: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "{} is PRIVATE and cannot be accessed outside of {}",
+                reference, owner
+            ),
+            range: location,
+            err_no: ErrNo::reference__illegal_access,
+        }
+    }
+
+    pub fn invalid_property_access(
+        property: &str,
+        accessor: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Property {} has no {} accessor", property, accessor),
+            range: location,
+            err_no: ErrNo::reference__invalid_property_access,
+        }
+    }
+
     pub fn unresolved_generic_type(
         symbol: &str,
         nature: &str,
@@ -280,6 +482,94 @@ impl Diagnostic {
         }
     }
 
+    pub fn invalid_array_range(range: Range<i128>, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid array range {}..{}, the lower bound must not exceed the upper bound",
+                range.start, range.end
+            ),
+            range: location,
+            err_no: ErrNo::type__invalid_array_range,
+        }
+    }
+
+    pub fn invalid_attribute_value(
+        attribute: &str,
+        value: Option<&str>,
+        expected: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid value {} for attribute '{}', expected {}",
+                value
+                    .map(|it| format!("'{}'", it))
+                    .unwrap_or_else(|| "<none>".into()),
+                attribute,
+                expected
+            ),
+            range: location,
+            err_no: ErrNo::type__invalid_attribute,
+        }
+    }
+
+    pub fn pack_mode_requires_padding(
+        type_name: &str,
+        pack_mode: u32,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Struct {} requires padding with pack mode {}, packed structs cannot contain padding",
+                type_name, pack_mode
+            ),
+            range: location,
+            err_no: ErrNo::type__invalid_layout,
+        }
+    }
+
+    pub fn member_offset_mismatch(
+        member_name: &str,
+        expected_offset: u32,
+        actual_offset: u32,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Member '{}' is expected at byte offset {} but is located at byte offset {}",
+                member_name, expected_offset, actual_offset
+            ),
+            range: location,
+            err_no: ErrNo::type__invalid_layout,
+        }
+    }
+
+    pub fn cyclic_type_definition(type_names: &[&str], location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Cyclic type definition: {} (the type refers back to itself)",
+                type_names.join(" -> ")
+            ),
+            range: location,
+            err_no: ErrNo::type__cyclic_definition,
+        }
+    }
+
+    pub fn union_member_size_mismatch(
+        member_name: &str,
+        member_size: u32,
+        union_size: u32,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::ImprovementSuggestion {
+            message: format!(
+                "Union member '{}' ({} bits) is smaller than its union ({} bits), accessing it reinterprets only part of a larger member (type punning)",
+                member_name, member_size, union_size
+            ),
+            range: location,
+        }
+    }
+
     pub fn incompatible_array_access_variable(
         access_type: &str,
         location: SourceRange,
@@ -340,6 +630,119 @@ impl Diagnostic {
         }
     }
 
+    pub fn invalid_bit_shift_argument(
+        function_name: &str,
+        type_name: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid argument for {:}, {:} is no integer",
+                function_name, type_name
+            ),
+            range: location,
+            err_no: ErrNo::type__invalid_nature,
+        }
+    }
+
+    pub fn invalid_selection_argument(
+        function_name: &str,
+        type_name: &str,
+        expected: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid argument for {:}, {:} is no {:}",
+                function_name, type_name, expected
+            ),
+            range: location,
+            err_no: ErrNo::type__invalid_nature,
+        }
+    }
+
+    pub fn invalid_numeric_argument(
+        function_name: &str,
+        type_name: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid argument for {:}, {:} is no number",
+                function_name, type_name
+            ),
+            range: location,
+            err_no: ErrNo::type__invalid_nature,
+        }
+    }
+
+    pub fn invalid_operand(
+        operator: &str,
+        type_name: &str,
+        expected: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid operand for {:}, {:} is no {:}",
+                operator, type_name, expected
+            ),
+            range: location,
+            err_no: ErrNo::type__invalid_nature,
+        }
+    }
+
+    pub fn bit_shift_exceeds_width(
+        function_name: &str,
+        amount: &str,
+        type_name: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::ImprovementSuggestion {
+            message: format!(
+                "{:} by {:} bits exceeds the bit-width of {:}",
+                function_name, amount, type_name
+            ),
+            range: location,
+        }
+    }
+
+    pub fn lossy_conversion(
+        function_name: &str,
+        value: &str,
+        type_name: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::ImprovementSuggestion {
+            message: format!(
+                "{:}({:}) loses information, the value cannot be represented as {:}",
+                function_name, value, type_name
+            ),
+            range: location,
+        }
+    }
+
+    pub fn literal_precision_loss(
+        literal: &str,
+        type_name: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::ImprovementSuggestion {
+            message: format!(
+                "Literal {:} loses precision when stored as {:}",
+                literal, type_name
+            ),
+            range: location,
+        }
+    }
+
+    pub fn division_by_zero(location: SourceRange) -> Diagnostic {
+        Diagnostic::ImprovementSuggestion {
+            message: "Division by zero".into(),
+            range: location,
+        }
+    }
+
     pub fn empty_variable_block(location: SourceRange) -> Diagnostic {
         Diagnostic::SyntaxError {
             message: "Variable block is empty".into(),
@@ -366,6 +769,41 @@ impl Diagnostic {
         }
     }
 
+    pub fn partial_initializer(
+        expected: usize,
+        actual: usize,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Initializer provides {:} of {:} elements, the remaining elements are initialized with their default value",
+                actual, expected
+            ),
+            range: location,
+            err_no: ErrNo::var__partial_initializer,
+        }
+    }
+
+    pub fn too_many_initializers(expected: usize, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Too many initial values, expected {:} elements", expected),
+            range: location,
+            err_no: ErrNo::var__too_many_initializers,
+        }
+    }
+
+    pub fn invalid_config_variable(
+        reference: &str,
+        reason: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Invalid VAR_CONFIG entry for '{:}': {:}", reference, reason),
+            range: location,
+            err_no: ErrNo::var__invalid_config_variable,
+        }
+    }
+
     pub fn invalid_constant_block(location: SourceRange) -> Diagnostic {
         Diagnostic::SyntaxError {
             message: "This variable block does not support the CONSTANT modifier".to_string(),
@@ -457,6 +895,186 @@ impl Diagnostic {
         )
     }
 
+    pub fn loop_control_outside_loop(statement: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("{:} is only allowed inside of a loop", statement),
+            range: location,
+            err_no: ErrNo::loop__control_outside_loop,
+        }
+    }
+
+    pub fn unreachable_code(statement: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Unreachable code after {:}", statement),
+            range: location,
+            err_no: ErrNo::statement__unreachable_code,
+        }
+    }
+
+    pub fn unused_variable(name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Variable '{:}' is never read", name),
+            range: location,
+            err_no: ErrNo::var__unused_variable,
+        }
+    }
+
+    /// a variable read before it is assigned, the path lists the branches taken on a path on
+    /// which the variable is not assigned
+    pub fn read_before_assignment(
+        name: &str,
+        path: &[String],
+        location: SourceRange,
+    ) -> Diagnostic {
+        let message = if path.is_empty() {
+            format!("Variable '{:}' is read before it is assigned", name)
+        } else {
+            format!(
+                "Variable '{:}' may be read before it is assigned, it is not assigned if {:}",
+                name,
+                path.join(" and ")
+            )
+        };
+        Diagnostic::SyntaxError {
+            message,
+            range: location,
+            err_no: ErrNo::var__read_before_assignment,
+        }
+    }
+
+    pub fn unused_pou(name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Private method '{:}' is never called", name),
+            range: location,
+            err_no: ErrNo::pou__unused_pou,
+        }
+    }
+
+    pub fn recursive_call(path: &[&str], location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Recursive call: {}", path.join(" -> ")),
+            range: location,
+            err_no: ErrNo::pou__recursive_call,
+        }
+    }
+
+    pub fn duplicate_case_condition(value: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Duplicate condition value: {:}. Occurred more than once",
+                value
+            ),
+            range: location,
+            err_no: ErrNo::case__duplicate_condition,
+        }
+    }
+
+    pub fn duplicate_parameter_assignment(
+        parameter_name: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Parameter '{:}' is assigned more than once", parameter_name),
+            range: location,
+            err_no: ErrNo::var__duplicate_parameter_assignment,
+        }
+    }
+
+    pub fn duplicate_global_variable(name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!("Global variable '{:}' is declared more than once", name),
+            range: location,
+            err_no: ErrNo::var__duplicate_global_variable,
+        }
+    }
+
+    /// - `name` the repeated declaration
+    /// - `previous_name` the first declaration, which may only differ by case
+    pub fn duplicate_declaration(
+        name: &str,
+        previous_name: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        let message = if name == previous_name {
+            format!("'{:}' is declared more than once", name)
+        } else {
+            format!(
+                "'{:}' is declared more than once, identifiers are case-insensitive ('{:}' was declared before)",
+                name, previous_name
+            )
+        };
+        Diagnostic::SyntaxError {
+            message,
+            range: location,
+            err_no: ErrNo::reference__duplicate_declaration,
+        }
+    }
+
+    pub fn unresolved_external_variable(name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "External variable '{:}' has no matching global variable",
+                name
+            ),
+            range: location,
+            err_no: ErrNo::var__unresolved_external,
+        }
+    }
+
+    pub fn incompatible_external_variable(
+        name: &str,
+        external_type: &str,
+        global_type: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "External variable '{:}' of type '{:}' does not match the global variable of type '{:}'",
+                name, external_type, global_type
+            ),
+            range: location,
+            err_no: ErrNo::var__incompatible_external,
+        }
+    }
+
+    pub fn invalid_output_assignment(parameter_name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid output assignment: '{:}' is not declared as VAR_OUTPUT",
+                parameter_name
+            ),
+            range: location,
+            err_no: ErrNo::var__invalid_output_assignment,
+        }
+    }
+
+    pub fn invalid_in_out_argument(parameter_name: &str, location: SourceRange) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid argument for VAR_IN_OUT '{:}': expected an assignable variable",
+                parameter_name
+            ),
+            range: location,
+            err_no: ErrNo::var__invalid_in_out_argument,
+        }
+    }
+
+    pub fn invalid_argument_type(
+        parameter_name: &str,
+        argument_type: &str,
+        parameter_type: &str,
+        location: SourceRange,
+    ) -> Diagnostic {
+        Diagnostic::SyntaxError {
+            message: format!(
+                "Invalid argument for '{:}': cannot pass '{:}' as '{:}'",
+                parameter_name, argument_type, parameter_type
+            ),
+            range: location,
+            err_no: ErrNo::var__invalid_argument_type,
+        }
+    }
+
     pub fn invalid_assignment(
         right_type: &str,
         left_type: &str,
@@ -491,6 +1109,17 @@ impl Diagnostic {
         }
     }
 
+    pub fn compilation_aborted(errors: usize) -> Diagnostic {
+        Diagnostic::GeneralError {
+            message: format!(
+                "Compilation aborted due to {} error{}",
+                errors,
+                if errors == 1 { "" } else { "s" }
+            ),
+            err_no: ErrNo::general__compilation_aborted,
+        }
+    }
+
     pub fn link_error(error: &str) -> Diagnostic {
         Diagnostic::GeneralError {
             err_no: ErrNo::linker__generic_error,
@@ -546,6 +1175,17 @@ impl Diagnostic {
     }
 }
 
+/// the level a warning is reported with, configured on the command line (`--allow`, `--warn`,
+/// `--error`) or for a region of a file by a `{warning <level> <code>}` pragma
+#[derive(PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
+pub enum WarningLevel {
+    /// the warning is not reported
+    Allow,
+    Warn,
+    /// the warning is reported as an error
+    Error,
+}
+
 /// a diagnostics severity
 pub enum Severity {
     Error,
@@ -564,10 +1204,15 @@ pub trait DiagnosticAssessor {
     }
 }
 
-/// the default assessor will treat ImprovementSuggestions as warnings
-/// and everything else as errors
+/// the default assessor will treat ImprovementSuggestions, partial initializers, unused
+/// declarations, reads of unassigned variables and unreachable code as warnings and everything else as errors
 #[derive(Default)]
-pub struct DefaultDiagnosticAssessor {}
+pub struct DefaultDiagnosticAssessor {
+    /// report initializers that do not provide a value for every element as errors
+    pub deny_partial_initializers: bool,
+    /// report recursive calls as warnings
+    pub warn_recursion: bool,
+}
 
 pub struct AssessedDiagnostic {
     pub diagnostic: Diagnostic,
@@ -579,6 +1224,25 @@ impl DiagnosticAssessor for DefaultDiagnosticAssessor {
         let severity = match d {
             // improvements become warnings
             Diagnostic::ImprovementSuggestion { .. } => Severity::Warning,
+            // missing elements are initialized with their default value
+            Diagnostic::SyntaxError {
+                err_no: ErrNo::var__partial_initializer,
+                ..
+            } if !self.deny_partial_initializers => Severity::Warning,
+            // most PLC runtimes cannot bound the stack used by recursions
+            Diagnostic::SyntaxError {
+                err_no: ErrNo::pou__recursive_call,
+                ..
+            } if self.warn_recursion => Severity::Warning,
+            // unused declarations, unassigned reads and unreachable code do not prevent the compilation
+            Diagnostic::SyntaxError {
+                err_no:
+                    ErrNo::var__unused_variable
+                    | ErrNo::var__read_before_assignment
+                    | ErrNo::pou__unused_pou
+                    | ErrNo::statement__unreachable_code,
+                ..
+            } => Severity::Warning,
             // everything else becomes an error
             _ => Severity::Error,
         };
@@ -597,13 +1261,13 @@ pub trait DiagnosticReporter {
     fn report(&self, diagnostics: &[AssessedDiagnostic], file_id: usize);
     /// register the given path & src and returns an ID to indicate
     /// a relationship the given src (diagnostics for this src need
-    /// to use this id)
-    fn register(&mut self, path: String, src: String) -> usize;
+    /// to use this id). The src is shared with the lexer, so it is not copied
+    fn register(&mut self, path: String, src: Arc<str>) -> usize;
 }
 
 /// a DiagnosticReporter that reports diagnostics using codespan_reporting
 pub struct CodeSpanDiagnosticReporter {
-    files: SimpleFiles<String, String>,
+    files: SimpleFiles<String, Arc<str>>,
     config: codespan_reporting::term::Config,
     writer: StandardStream,
 }
@@ -650,6 +1314,7 @@ impl DiagnosticReporter for CodeSpanDiagnosticReporter {
 
             let diag = diagnostic_factory
                 .with_message(d.get_message())
+                .with_code(format!("{:?}", d.get_type()))
                 .with_labels(vec![Label::primary(
                     file_id,
                     location.get_start()..location.get_end(),
@@ -666,11 +1331,86 @@ impl DiagnosticReporter for CodeSpanDiagnosticReporter {
         }
     }
 
-    fn register(&mut self, path: String, src: String) -> usize {
+    fn register(&mut self, path: String, src: Arc<str>) -> usize {
         self.files.add(path, src)
     }
 }
 
+/// a DiagnosticReporter that reports every diagnostic as a line of JSON to std-err,
+/// so IDEs and other tools can parse them
+#[derive(Default)]
+pub struct JsonDiagnosticReporter {
+    /// the paths and line breaks of the registered files, by their id
+    files: Vec<(String, NewLines)>,
+}
+
+#[derive(Serialize)]
+struct JsonDiagnostic<'a> {
+    code: String,
+    severity: &'static str,
+    message: &'a str,
+    file: Option<&'a str>,
+    range: Option<JsonRange>,
+    /// the related notes of the diagnostic
+    notes: Vec<String>,
+}
+
+/// the byte-range of a diagnostic and the (1-based) line and column it starts at
+#[derive(Serialize)]
+struct JsonRange {
+    start: usize,
+    end: usize,
+    line: usize,
+    column: usize,
+}
+
+impl JsonDiagnosticReporter {
+    /// returns the given diagnostic of the file with the given id as a line of JSON
+    fn to_json(&self, diagnostic: &AssessedDiagnostic, file_id: usize) -> String {
+        let d = &diagnostic.diagnostic;
+        let file = self.files.get(file_id);
+        let range = match (d, file) {
+            (Diagnostic::GeneralError { .. }, _) | (_, None) => None,
+            (_, Some((_, new_lines))) => {
+                let location = d.get_location();
+                let (line, column) = location.get_line_and_column(new_lines);
+                Some(JsonRange {
+                    start: location.get_start(),
+                    end: location.get_end(),
+                    line,
+                    column,
+                })
+            }
+        };
+        let json = JsonDiagnostic {
+            code: format!("{:?}", d.get_type()),
+            severity: match diagnostic.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                Severity::_Info => "info",
+            },
+            message: d.get_message(),
+            file: file.map(|(path, _)| path.as_str()),
+            range,
+            notes: vec![],
+        };
+        serde_json::to_string(&json).expect("diagnostics can be serialized")
+    }
+}
+
+impl DiagnosticReporter for JsonDiagnosticReporter {
+    fn report(&self, diagnostics: &[AssessedDiagnostic], file_id: usize) {
+        for diagnostic in diagnostics {
+            eprintln!("{}", self.to_json(diagnostic, file_id));
+        }
+    }
+
+    fn register(&mut self, path: String, src: Arc<str>) -> usize {
+        self.files.push((path, NewLines::new(&src)));
+        self.files.len() - 1
+    }
+}
+
 /// a DiagnosticReporter that swallows all diagnostics
 #[derive(Default)]
 pub struct NullDiagnosticReporter {
@@ -682,7 +1422,7 @@ impl DiagnosticReporter for NullDiagnosticReporter {
         //ignore
     }
 
-    fn register(&mut self, _path: String, _src: String) -> usize {
+    fn register(&mut self, _path: String, _src: Arc<str>) -> usize {
         // at least provide some unique ids
         self.last_id += 1;
         self.last_id
@@ -694,37 +1434,165 @@ impl DiagnosticReporter for NullDiagnosticReporter {
 pub struct Diagnostician {
     pub reporter: Box<dyn DiagnosticReporter>,
     pub assessor: Box<dyn DiagnosticAssessor>,
+    /// the number of errors handled so far, `None` if errors are ignored
+    errors: Option<Cell<usize>>,
+    /// the levels of warnings configured for all files, a later level overrides an earlier one
+    warning_levels: Vec<(ErrNo, WarningLevel)>,
+    /// report the warnings without a configured level as errors
+    warnings_as_errors: bool,
+    /// the levels set by the warning pragmas of the registered files with the offset of their
+    /// pragma, `None` restores the configured level
+    warning_pragmas: HashMap<usize, Vec<(ErrNo, Option<WarningLevel>, usize)>>,
 }
 
 impl Diagnostician {
     /// registers the given source-code at the diagnostician, so it can
     /// preview errors in the source
     /// returns the id to use to reference the given file
-    pub fn register_file(&mut self, id: String, src: String) -> usize {
+    pub fn register_file(&mut self, id: String, src: Arc<str>) -> usize {
         self.reporter.register(id, src)
     }
 
-    /// creates a null-diagnostician that does not report diagnostics
+    /// creates a null-diagnostician that does not report diagnostics, errors do not abort
+    /// the compilation
     pub fn null_diagnostician() -> Diagnostician {
         Diagnostician {
             assessor: Box::new(DefaultDiagnosticAssessor::default()),
             reporter: Box::new(NullDiagnosticReporter::default()),
+            errors: None,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            warning_pragmas: HashMap::new(),
+        }
+    }
+
+    /// creates a diagnostician that reports diagnostics as lines of JSON
+    pub fn json_diagnostician() -> Diagnostician {
+        Diagnostician {
+            reporter: Box::new(JsonDiagnosticReporter::default()),
+            ..Diagnostician::default()
+        }
+    }
+
+    /// returns this diagnostician, determining the severity of diagnostics with the given assessor
+    pub fn with_assessor(self, assessor: Box<dyn DiagnosticAssessor>) -> Diagnostician {
+        Diagnostician { assessor, ..self }
+    }
+
+    /// returns this diagnostician reporting the diagnostics with the given reporter
+    pub fn with_reporter(self, reporter: Box<dyn DiagnosticReporter>) -> Diagnostician {
+        Diagnostician { reporter, ..self }
+    }
+
+    /// returns this diagnostician, reporting the warnings with the given codes with the given
+    /// levels. A later level for the same code overrides an earlier one
+    pub fn with_warning_levels(self, warning_levels: Vec<(ErrNo, WarningLevel)>) -> Diagnostician {
+        Diagnostician {
+            warning_levels,
+            ..self
+        }
+    }
+
+    /// returns this diagnostician, reporting the warnings without a configured level as errors
+    pub fn with_warnings_as_errors(self, warnings_as_errors: bool) -> Diagnostician {
+        Diagnostician {
+            warnings_as_errors,
+            ..self
         }
     }
 
+    /// registers the warning pragmas of the given file, they set the level of their warning
+    /// from their position to the next pragma for the same code
+    pub fn register_warning_pragmas(&mut self, file_id: usize, pragmas: &[WarningPragma]) {
+        if !pragmas.is_empty() {
+            let mut pragmas = pragmas
+                .iter()
+                .map(|it| (it.code.get_err_no(), it.level, it.location.get_start()))
+                .collect::<Vec<_>>();
+            pragmas.sort_by_key(|(_, _, offset)| *offset);
+            self.warning_pragmas.insert(file_id, pragmas);
+        }
+    }
+
+    /// returns the level of the given warning, set by the last preceding pragma of its file or
+    /// by the configured levels
+    fn get_warning_level(&self, diagnostic: &Diagnostic, file_id: usize) -> WarningLevel {
+        let code = diagnostic.get_type();
+        let location = diagnostic.get_location();
+        let pragma_level = self
+            .warning_pragmas
+            .get(&location.get_file().unwrap_or(file_id))
+            .and_then(|pragmas| {
+                pragmas
+                    .iter()
+                    .filter(|(it, _, offset)| it == code && *offset <= location.get_start())
+                    .last()
+            })
+            .and_then(|(_, level, _)| *level);
+        pragma_level
+            .or_else(|| {
+                self.warning_levels
+                    .iter()
+                    .rev()
+                    .find(|(it, _)| it == code)
+                    .map(|(_, level)| *level)
+            })
+            .unwrap_or(if self.warnings_as_errors {
+                WarningLevel::Error
+            } else {
+                WarningLevel::Warn
+            })
+    }
+
     /// assess and reports the given diagnostics
     pub fn handle(&self, diagnostics: Vec<Diagnostic>, file_id: usize) {
-        self.report(&self.assess_all(diagnostics), file_id);
+        let diagnostics = self
+            .assess_all(diagnostics)
+            .into_iter()
+            .filter_map(|it| match it.severity {
+                Severity::Warning => match self.get_warning_level(&it.diagnostic, file_id) {
+                    WarningLevel::Allow => None,
+                    WarningLevel::Warn => Some(it),
+                    WarningLevel::Error => Some(AssessedDiagnostic {
+                        severity: Severity::Error,
+                        ..it
+                    }),
+                },
+                _ => Some(it),
+            })
+            .collect::<Vec<_>>();
+        if let Some(errors) = &self.errors {
+            let new_errors = diagnostics
+                .iter()
+                .filter(|it| matches!(it.severity, Severity::Error))
+                .count();
+            errors.set(errors.get() + new_errors);
+        }
+        self.report(&diagnostics, file_id);
+    }
+
+    /// returns the number of errors handled so far
+    pub fn error_count(&self) -> usize {
+        self.errors.as_ref().map(Cell::get).unwrap_or_default()
     }
 }
 
 impl DiagnosticReporter for Diagnostician {
     fn report(&self, diagnostics: &[AssessedDiagnostic], file_id: usize) {
-        //delegate to reporter
-        self.reporter.report(diagnostics, file_id);
+        //delegate to reporter, a diagnostic is reported against the file of its location (e.g. a
+        //declaration of another file) and against the given file if its location's file is unknown
+        for diagnostic in diagnostics {
+            let file_id = diagnostic
+                .diagnostic
+                .get_location()
+                .get_file()
+                .unwrap_or(file_id);
+            self.reporter
+                .report(std::slice::from_ref(diagnostic), file_id);
+        }
     }
 
-    fn register(&mut self, path: String, src: String) -> usize {
+    fn register(&mut self, path: String, src: Arc<str>) -> usize {
         //delegate to reporter
         self.reporter.register(path, src)
     }
@@ -742,6 +1610,240 @@ impl Default for Diagnostician {
         Self {
             reporter: Box::new(CodeSpanDiagnosticReporter::default()),
             assessor: Box::new(DefaultDiagnosticAssessor::default()),
+            errors: Some(Cell::new(0)),
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            warning_pragmas: HashMap::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, sync::Arc};
+
+    use super::{
+        AssessedDiagnostic, DefaultDiagnosticAssessor, Diagnostic, DiagnosticReporter,
+        Diagnostician, ErrNo, JsonDiagnosticReporter, Severity, WarningLevel,
+    };
+    use crate::{
+        ast::{SourceRange, WarningPragma},
+        WarningCode,
+    };
+
+    /// records the file ids diagnostics are reported against
+    struct FileRecorder {
+        files: Rc<RefCell<Vec<usize>>>,
+    }
+
+    impl DiagnosticReporter for FileRecorder {
+        fn report(&self, diagnostics: &[AssessedDiagnostic], file_id: usize) {
+            self.files
+                .borrow_mut()
+                .extend(diagnostics.iter().map(|_| file_id));
+        }
+
+        fn register(&mut self, _path: String, _src: Arc<str>) -> usize {
+            0
         }
     }
+
+    #[test]
+    fn diagnostics_are_reported_against_the_file_of_their_location() {
+        let files = Rc::new(RefCell::new(vec![]));
+        let diagnostician = Diagnostician {
+            reporter: Box::new(FileRecorder {
+                files: files.clone(),
+            }),
+            ..Diagnostician::default()
+        };
+        diagnostician.handle(
+            vec![
+                Diagnostic::syntax_error("in the other file", SourceRange::in_file(0..1, Some(3))),
+                Diagnostic::syntax_error("in an unknown file", (0..1).into()),
+            ],
+            1,
+        );
+
+        assert_eq!(*files.borrow(), vec![3, 1]);
+    }
+
+    #[test]
+    fn errors_are_counted_but_warnings_are_not() {
+        let diagnostician = Diagnostician::default();
+        diagnostician.handle(
+            vec![
+                Diagnostic::syntax_error("first", (0..1).into()),
+                Diagnostic::ImprovementSuggestion {
+                    message: "a warning".to_string(),
+                    range: (1..2).into(),
+                },
+            ],
+            0,
+        );
+        diagnostician.handle(vec![Diagnostic::syntax_error("second", (2..3).into())], 0);
+
+        assert_eq!(diagnostician.error_count(), 2);
+    }
+
+    #[test]
+    fn recursive_calls_are_errors_unless_reported_as_warnings() {
+        let recursive_call = || Diagnostic::recursive_call(&["a", "b", "a"], (0..1).into());
+
+        let diagnostician = Diagnostician::default();
+        diagnostician.handle(vec![recursive_call()], 0);
+        assert_eq!(diagnostician.error_count(), 1);
+
+        let diagnostician =
+            Diagnostician::default().with_assessor(Box::new(DefaultDiagnosticAssessor {
+                warn_recursion: true,
+                ..DefaultDiagnosticAssessor::default()
+            }));
+        diagnostician.handle(vec![recursive_call()], 0);
+        assert_eq!(diagnostician.error_count(), 0);
+    }
+
+    #[test]
+    fn partial_initializers_are_errors_if_denied() {
+        let partial_initializer = || Diagnostic::partial_initializer(4, 2, (0..1).into());
+
+        let diagnostician = Diagnostician::default();
+        diagnostician.handle(vec![partial_initializer()], 0);
+        assert_eq!(diagnostician.error_count(), 0);
+
+        let diagnostician =
+            Diagnostician::default().with_assessor(Box::new(DefaultDiagnosticAssessor {
+                deny_partial_initializers: true,
+                ..DefaultDiagnosticAssessor::default()
+            }));
+        diagnostician.handle(vec![partial_initializer()], 0);
+        assert_eq!(diagnostician.error_count(), 1);
+    }
+
+    #[test]
+    fn allowed_warnings_are_not_reported() {
+        let files = Rc::new(RefCell::new(vec![]));
+        let diagnostician = Diagnostician {
+            reporter: Box::new(FileRecorder {
+                files: files.clone(),
+            }),
+            ..Diagnostician::default()
+        }
+        .with_warning_levels(vec![(ErrNo::var__unused_variable, WarningLevel::Allow)]);
+        diagnostician.handle(
+            vec![
+                Diagnostic::unused_variable("x", (0..1).into()),
+                Diagnostic::unreachable_code("RETURN", (1..2).into()),
+            ],
+            0,
+        );
+
+        assert_eq!(files.borrow().len(), 1);
+        assert_eq!(diagnostician.error_count(), 0);
+    }
+
+    #[test]
+    fn warnings_are_reported_as_errors_unless_configured_otherwise() {
+        let diagnostician = Diagnostician::default()
+            .with_warnings_as_errors(true)
+            .with_warning_levels(vec![
+                (ErrNo::pou__unused_pou, WarningLevel::Allow),
+                (ErrNo::pou__unused_pou, WarningLevel::Warn),
+            ]);
+        diagnostician.handle(
+            vec![
+                Diagnostic::unused_variable("x", (0..1).into()),
+                Diagnostic::unused_pou("foo", (1..2).into()),
+            ],
+            0,
+        );
+
+        assert_eq!(diagnostician.error_count(), 1);
+    }
+
+    #[test]
+    fn warning_pragmas_set_the_level_of_the_following_warnings() {
+        let files = Rc::new(RefCell::new(vec![]));
+        let mut diagnostician = Diagnostician {
+            reporter: Box::new(FileRecorder {
+                files: files.clone(),
+            }),
+            ..Diagnostician::default()
+        };
+        let pragma = |level, offset| WarningPragma {
+            code: WarningCode::UnusedVariable,
+            level,
+            location: (offset..offset + 1).into(),
+        };
+        diagnostician.register_warning_pragmas(
+            0,
+            &[
+                pragma(Some(WarningLevel::Allow), 10),
+                pragma(Some(WarningLevel::Error), 20),
+                pragma(None, 30),
+            ],
+        );
+        diagnostician.handle(
+            vec![
+                //allowed
+                Diagnostic::unused_variable("a", (15..16).into()),
+                //an error
+                Diagnostic::unused_variable("b", (25..26).into()),
+                //restored to a warning
+                Diagnostic::unused_variable("c", (35..36).into()),
+                //the pragmas do not apply to other codes
+                Diagnostic::unreachable_code("RETURN", (15..16).into()),
+            ],
+            0,
+        );
+
+        assert_eq!(files.borrow().len(), 3);
+        assert_eq!(diagnostician.error_count(), 1);
+    }
+
+    #[test]
+    fn the_null_diagnostician_ignores_errors() {
+        let diagnostician = Diagnostician::null_diagnostician();
+        diagnostician.handle(vec![Diagnostic::syntax_error("error", (0..1).into())], 0);
+
+        assert_eq!(diagnostician.error_count(), 0);
+    }
+
+    #[test]
+    fn compilation_aborted_message_counts_the_errors() {
+        assert_eq!(
+            Diagnostic::compilation_aborted(1).get_message(),
+            "Compilation aborted due to 1 error"
+        );
+        assert_eq!(
+            Diagnostic::compilation_aborted(3).get_message(),
+            "Compilation aborted due to 3 errors"
+        );
+    }
+
+    #[test]
+    fn diagnostics_are_reported_as_json() {
+        let mut reporter = JsonDiagnosticReporter::default();
+        let file_id = reporter.register(
+            "main.st".to_string(),
+            "PROGRAM main\n  x := 1;\nEND_PROGRAM".into(),
+        );
+        let error = AssessedDiagnostic {
+            diagnostic: Diagnostic::unresolved_reference("x", (15..16).into()),
+            severity: Severity::Error,
+        };
+        assert_eq!(
+            reporter.to_json(&error, file_id),
+            r#"{"code":"reference__unresolved","severity":"error","message":"Could not resolve reference to x","file":"main.st","range":{"start":15,"end":16,"line":2,"column":3},"notes":[]}"#
+        );
+
+        let general_error = AssessedDiagnostic {
+            diagnostic: Diagnostic::param_error("invalid parameter"),
+            severity: Severity::Error,
+        };
+        assert_eq!(
+            reporter.to_json(&general_error, file_id),
+            r#"{"code":"general__param_err","severity":"error","message":"invalid parameter","file":"main.st","range":null,"notes":[]}"#
+        );
+    }
 }
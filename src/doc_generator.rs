@@ -0,0 +1,815 @@
+// Copyright (c) 2022 Ghaith Hachem and Mathias Rieder
+//! Generates the API documentation of structured text sources from the doc comments of their
+//! POUs, types and global variables.
+//!
+//! Doc comments are block comments starting with `(**` and line comments starting with `///`
+//! that precede a declaration. A comment following a variable on its line documents the
+//! variable too. The doc comment of a POU may describe its parameters with
+//! `@param <name> <description>` lines and its result with a `@return <description>` line.
+//!
+//! The types of the documented variables are resolved with the index, the names of documented
+//! POUs and types link to their documentation.
+use std::collections::HashMap;
+
+use crate::{
+    ast::{Comment, CompilationUnit, DataType, Pou, PouType, Variable, VariableBlockType},
+    index::Index,
+    typesystem::{DataTypeInformation, StringEncoding},
+    DocFormat,
+};
+
+#[derive(Debug, PartialEq)]
+pub struct Documentation {
+    pous: Vec<Entry>,
+    types: Vec<Entry>,
+    globals: Vec<Member>,
+}
+
+/// a documented POU or type
+#[derive(Debug, PartialEq)]
+struct Entry {
+    name: String,
+    /// the keyword declaring the entry, e.g. `FUNCTION_BLOCK` or `TYPE`
+    kind: &'static str,
+    description: String,
+    /// the definition of a type that is neither a struct nor an enum, e.g. `ARRAY[0..9] OF INT`
+    definition: Option<TypeReference>,
+    /// the variables of a POU, the members of a struct or the elements of an enum
+    members: Vec<Member>,
+    /// the result of a function and its description
+    result: Option<(TypeReference, String)>,
+}
+
+#[derive(Debug, PartialEq)]
+struct Member {
+    name: String,
+    /// the block declaring the variable, e.g. `VAR_INPUT`, empty for the members of a type
+    kind: &'static str,
+    data_type: Option<TypeReference>,
+    description: String,
+}
+
+/// the resolved type of a variable, e.g. `ARRAY[0..9] OF Point` with the name `Point` linking
+/// to the documentation of `Point`
+#[derive(Debug, PartialEq)]
+struct TypeReference {
+    /// the text preceding the referenced name, e.g. `ARRAY[0..9] OF `
+    prefix: String,
+    name: String,
+    /// the anchor of the documentation of the referenced name
+    link: Option<String>,
+}
+
+impl TypeReference {
+    fn with_prefix(mut self, prefix: &str) -> TypeReference {
+        self.prefix.insert_str(0, prefix);
+        self
+    }
+}
+
+/// the content of the doc comments of a declaration
+#[derive(Default)]
+struct DocComment {
+    description: String,
+    /// the descriptions of the `@param` lines by their lowercase parameter names
+    params: HashMap<String, String>,
+    /// the description of the `@return` line
+    result: Option<String>,
+}
+
+/// collects the documentation of the POUs, types and global variables of the given units
+pub fn collect_documentation(units: &[&CompilationUnit], index: &Index) -> Documentation {
+    let anchors = units
+        .iter()
+        .flat_map(|unit| {
+            unit.units
+                .iter()
+                .map(|it| it.name.as_str())
+                .chain(unit.types.iter().filter_map(|it| it.data_type.get_name()))
+        })
+        .filter(|it| !is_internal(it))
+        .map(|it| (it.to_lowercase(), get_anchor(it)))
+        .collect();
+    let collector = DocumentationCollector { index, anchors };
+
+    let mut documentation = Documentation {
+        pous: vec![],
+        types: vec![],
+        globals: vec![],
+    };
+    for unit in units {
+        documentation
+            .pous
+            .extend(unit.units.iter().map(|it| collector.collect_pou(it)));
+        for user_type in unit.types.iter() {
+            if let Some(entry) = collector.collect_type(&user_type.data_type, &user_type.comments) {
+                documentation.types.push(entry);
+            }
+        }
+        for variable in unit.global_vars.iter().flat_map(|it| it.variables.iter()) {
+            let type_name = index
+                .find_global_variable(&variable.name)
+                .map(|it| it.get_type_name());
+            documentation.globals.push(collector.collect_variable(
+                variable,
+                "",
+                type_name,
+                &HashMap::new(),
+            ));
+        }
+    }
+    documentation
+}
+
+struct DocumentationCollector<'i> {
+    index: &'i Index,
+    /// the anchors of the documented POUs and types by their lowercase names
+    anchors: HashMap<String, String>,
+}
+
+impl<'i> DocumentationCollector<'i> {
+    fn collect_pou(&self, pou: &Pou) -> Entry {
+        let doc = parse_doc_comments(pou.comments.iter().filter(|it| is_doc_comment(&it.text)));
+        let params = &doc.params;
+        let members = pou
+            .variable_blocks
+            .iter()
+            .flat_map(|block| {
+                block.variables.iter().map(move |variable| {
+                    let type_name = self
+                        .index
+                        .find_member(&pou.name, &variable.name)
+                        .map(|it| it.get_type_name());
+                    self.collect_variable(
+                        variable,
+                        get_block_keyword(&block.variable_block_type),
+                        type_name,
+                        params,
+                    )
+                })
+            })
+            .collect();
+        let result = self
+            .index
+            .find_return_type(&pou.name)
+            .filter(|it| it.get_type_information() != &DataTypeInformation::Void)
+            .map(|it| {
+                (
+                    self.get_type_reference(it.get_name()),
+                    doc.result.clone().unwrap_or_default(),
+                )
+            });
+        Entry {
+            name: pou.name.clone(),
+            kind: get_pou_keyword(&pou.pou_type),
+            description: doc.description,
+            definition: None,
+            members,
+            result,
+        }
+    }
+
+    /// returns the documentation of the given user-defined type, the types generated for inline
+    /// declarations are not documented
+    fn collect_type(&self, data_type: &DataType, comments: &[Comment]) -> Option<Entry> {
+        let name = data_type.get_name().filter(|it| !is_internal(it))?;
+        let doc = parse_doc_comments(comments.iter().filter(|it| is_doc_comment(&it.text)));
+        let information = self.index.get_type(name).ok()?.get_type_information();
+        let (definition, members) = match (data_type, information) {
+            (DataType::StructType { variables, .. }, _)
+            | (DataType::UnionType { variables, .. }, _) => {
+                let members = variables
+                    .iter()
+                    .map(|variable| {
+                        let type_name = self
+                            .index
+                            .find_member(name, &variable.name)
+                            .map(|it| it.get_type_name());
+                        self.collect_variable(variable, "", type_name, &doc.params)
+                    })
+                    .collect();
+                (None, members)
+            }
+            (_, DataTypeInformation::Enum { elements, .. }) => {
+                let members = elements
+                    .iter()
+                    .map(|it| Member {
+                        name: it.clone(),
+                        kind: "",
+                        data_type: None,
+                        description: doc
+                            .params
+                            .get(&it.to_lowercase())
+                            .cloned()
+                            .unwrap_or_default(),
+                    })
+                    .collect();
+                (None, members)
+            }
+            _ => (Some(self.expand_type(name)), vec![]),
+        };
+        Some(Entry {
+            name: name.to_string(),
+            kind: "TYPE",
+            description: doc.description,
+            definition,
+            members,
+            result: None,
+        })
+    }
+
+    /// returns the documentation of the given variable, the variable is described by its doc
+    /// comments, by its trailing comment or by the given parameter descriptions
+    fn collect_variable(
+        &self,
+        variable: &Variable,
+        kind: &'static str,
+        type_name: Option<&str>,
+        params: &HashMap<String, String>,
+    ) -> Member {
+        let comments = variable.comments.iter().filter(|it| {
+            is_doc_comment(&it.text) || it.location.get_start() >= variable.location.get_end()
+        });
+        let mut description = parse_doc_comments(comments).description;
+        if description.is_empty() {
+            description = params
+                .get(&variable.name.to_lowercase())
+                .cloned()
+                .unwrap_or_default();
+        }
+        Member {
+            name: variable.name.clone(),
+            kind,
+            data_type: type_name.map(|it| self.get_type_reference(it)),
+            description,
+        }
+    }
+
+    /// returns the reference to the type with the given name, the types generated for inline
+    /// declarations and in-out variables are expanded into their definition
+    fn get_type_reference(&self, type_name: &str) -> TypeReference {
+        let is_in_out = matches!(
+            self.index
+                .get_type(type_name)
+                .map(|it| it.get_type_information()),
+            Some(DataTypeInformation::Pointer {
+                auto_deref: true,
+                ..
+            })
+        );
+        if is_internal(type_name) || is_in_out {
+            self.expand_type(type_name)
+        } else {
+            TypeReference {
+                prefix: String::new(),
+                name: type_name.to_string(),
+                link: self.anchors.get(&type_name.to_lowercase()).cloned(),
+            }
+        }
+    }
+
+    /// returns the definition of the type with the given name, e.g. `ARRAY[0..9] OF INT`
+    fn expand_type(&self, type_name: &str) -> TypeReference {
+        let information = self
+            .index
+            .get_type(type_name)
+            .ok()
+            .map(|it| it.get_type_information());
+        match information {
+            Some(DataTypeInformation::Array {
+                inner_type_name,
+                dimensions,
+                ..
+            }) => {
+                let dimensions = dimensions
+                    .iter()
+                    .map(|it| {
+                        it.get_range_inclusive(self.index)
+                            .map(|range| format!("{}..{}", range.start(), range.end()))
+                            .unwrap_or_else(|_| "*".to_string())
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                self.get_type_reference(inner_type_name)
+                    .with_prefix(&format!("ARRAY[{}] OF ", dimensions))
+            }
+            //in-out variables are passed by a pointer to their type
+            Some(DataTypeInformation::Pointer {
+                inner_type_name,
+                auto_deref: true,
+                ..
+            }) => self.get_type_reference(inner_type_name),
+            Some(DataTypeInformation::Pointer {
+                inner_type_name, ..
+            }) => self
+                .get_type_reference(inner_type_name)
+                .with_prefix("REF_TO "),
+            Some(DataTypeInformation::Alias {
+                referenced_type, ..
+            })
+            | Some(DataTypeInformation::SubRange {
+                referenced_type, ..
+            }) => self.get_type_reference(referenced_type),
+            Some(DataTypeInformation::String { size, encoding }) => {
+                let name = match encoding {
+                    StringEncoding::Utf8 => "STRING",
+                    StringEncoding::Utf16 => "WSTRING",
+                };
+                //the size of a string includes its terminator
+                let name = size
+                    .as_int_value(self.index)
+                    .map(|it| format!("{}[{}]", name, it - 1))
+                    .unwrap_or_else(|_| name.to_string());
+                TypeReference {
+                    prefix: String::new(),
+                    name,
+                    link: None,
+                }
+            }
+            _ => TypeReference {
+                prefix: String::new(),
+                name: type_name.to_string(),
+                link: None,
+            },
+        }
+    }
+}
+
+/// returns true if the given name was generated by the compiler
+fn is_internal(name: &str) -> bool {
+    name.starts_with("__")
+}
+
+fn is_doc_comment(comment: &str) -> bool {
+    (comment.starts_with("(**") && comment != "(**)") || comment.starts_with("///")
+}
+
+/// returns the text of the given comment without its delimiters and the `*` leading its lines
+fn get_comment_text(comment: &str) -> String {
+    let content = if let Some(line) = comment.strip_prefix("//") {
+        line.trim_start_matches('/')
+    } else {
+        //a block comment is enclosed in `(* *)` or `/* */`
+        comment[2..comment.len() - 2].trim_start_matches('*')
+    };
+    content
+        .lines()
+        .map(|line| {
+            let line = line.trim();
+            line.strip_prefix('*').map(str::trim_start).unwrap_or(line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+fn parse_doc_comments<'c>(comments: impl Iterator<Item = &'c Comment>) -> DocComment {
+    let mut doc = DocComment::default();
+    let mut description = vec![];
+    for text in comments.map(|it| get_comment_text(&it.text)) {
+        for line in text.lines() {
+            if let Some(param) = line.strip_prefix("@param") {
+                let mut parts = param.trim().splitn(2, char::is_whitespace);
+                if let Some(name) = parts.next().filter(|it| !it.is_empty()) {
+                    let text = parts.next().unwrap_or_default().trim();
+                    doc.params.insert(name.to_lowercase(), text.to_string());
+                }
+            } else if let Some(result) = line.strip_prefix("@return") {
+                doc.result = Some(result.trim().to_string());
+            } else {
+                description.push(line.to_string());
+            }
+        }
+    }
+    doc.description = description.join("\n").trim().to_string();
+    doc
+}
+
+fn get_pou_keyword(pou_type: &PouType) -> &'static str {
+    match pou_type {
+        PouType::Program => "PROGRAM",
+        PouType::Function => "FUNCTION",
+        PouType::FunctionBlock => "FUNCTION_BLOCK",
+        PouType::Action => "ACTION",
+        PouType::Class => "CLASS",
+        PouType::Interface => "INTERFACE",
+        PouType::Method { .. } => "METHOD",
+    }
+}
+
+fn get_block_keyword(block_type: &VariableBlockType) -> &'static str {
+    match block_type {
+        VariableBlockType::Local => "VAR",
+        VariableBlockType::Temp => "VAR_TEMP",
+        VariableBlockType::Input => "VAR_INPUT",
+        VariableBlockType::Output => "VAR_OUTPUT",
+        VariableBlockType::Global => "VAR_GLOBAL",
+        VariableBlockType::InOut => "VAR_IN_OUT",
+        VariableBlockType::External => "VAR_EXTERNAL",
+    }
+}
+
+/// returns the anchor of the documentation of the given name (e.g. `fb-method` for `fb.method`)
+fn get_anchor(name: &str) -> String {
+    name.chars()
+        .map(|it| {
+            if it.is_alphanumeric() || it == '_' {
+                it.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// returns the given documentation rendered in the given format
+pub fn generate_documentation(documentation: &Documentation, format: DocFormat) -> String {
+    match format {
+        DocFormat::Markdown => render(documentation, Markdown::default()),
+        DocFormat::Html => render(documentation, Html::default()),
+    }
+}
+
+fn render<R: Renderer>(documentation: &Documentation, mut renderer: R) -> String {
+    renderer.heading(1, "API documentation", None);
+    if !documentation.pous.is_empty() {
+        renderer.heading(2, "POUs", None);
+        for pou in documentation.pous.iter() {
+            render_entry(pou, &mut renderer);
+        }
+    }
+    if !documentation.types.is_empty() {
+        renderer.heading(2, "Types", None);
+        for user_type in documentation.types.iter() {
+            render_entry(user_type, &mut renderer);
+        }
+    }
+    if !documentation.globals.is_empty() {
+        renderer.heading(2, "Global variables", None);
+        render_members(&documentation.globals, &mut renderer);
+    }
+    renderer.finish()
+}
+
+fn render_entry<R: Renderer>(entry: &Entry, renderer: &mut R) {
+    renderer.heading(
+        3,
+        &format!("{} {}", entry.kind, entry.name),
+        Some(&get_anchor(&entry.name)),
+    );
+    if !entry.description.is_empty() {
+        renderer.description(&entry.description);
+    }
+    if let Some(definition) = &entry.definition {
+        let definition = format!(
+            "{} {}",
+            renderer.bold("Definition:"),
+            renderer.type_reference(definition)
+        );
+        renderer.paragraph(&definition);
+    }
+    if !entry.members.is_empty() {
+        render_members(&entry.members, renderer);
+    }
+    if let Some((data_type, description)) = &entry.result {
+        let mut result = format!(
+            "{} {}",
+            renderer.bold("Returns:"),
+            renderer.type_reference(data_type)
+        );
+        if !description.is_empty() {
+            result.push_str(&format!(" - {}", renderer.text(description)));
+        }
+        renderer.paragraph(&result);
+    }
+}
+
+/// renders the given members as a table, the columns without values are omitted
+fn render_members<R: Renderer>(members: &[Member], renderer: &mut R) {
+    let has_kind = members.iter().any(|it| !it.kind.is_empty());
+    let has_type = members.iter().any(|it| it.data_type.is_some());
+    let mut header = vec!["Name"];
+    if has_kind {
+        header.push("Kind");
+    }
+    if has_type {
+        header.push("Type");
+    }
+    header.push("Description");
+    let rows = members
+        .iter()
+        .map(|member| {
+            let mut row = vec![renderer.text(&member.name)];
+            if has_kind {
+                row.push(renderer.text(member.kind));
+            }
+            if has_type {
+                row.push(
+                    member
+                        .data_type
+                        .as_ref()
+                        .map(|it| renderer.type_reference(it))
+                        .unwrap_or_default(),
+                );
+            }
+            row.push(renderer.text(&member.description));
+            row
+        })
+        .collect::<Vec<_>>();
+    renderer.table(&header, &rows);
+}
+
+/// renders the parts of the documentation, the inline parts (e.g. `text`) are returned to be
+/// combined into blocks (e.g. a `paragraph`), the blocks are appended to the output
+trait Renderer {
+    fn heading(&mut self, level: usize, text: &str, anchor: Option<&str>);
+    /// appends a paragraph of rendered inline parts
+    fn paragraph(&mut self, content: &str);
+    /// appends the description of a doc comment
+    fn description(&mut self, text: &str);
+    /// appends a table of rendered inline parts
+    fn table(&mut self, header: &[&str], rows: &[Vec<String>]);
+    fn text(&self, text: &str) -> String;
+    fn bold(&self, text: &str) -> String;
+    fn type_reference(&self, reference: &TypeReference) -> String;
+    fn finish(self) -> String;
+}
+
+#[derive(Default)]
+struct Markdown {
+    output: String,
+}
+
+impl Renderer for Markdown {
+    fn heading(&mut self, level: usize, text: &str, anchor: Option<&str>) {
+        let anchor = anchor
+            .map(|it| format!("<a id=\"{}\"></a>", it))
+            .unwrap_or_default();
+        self.output.push_str(&format!(
+            "{} {}{}\n\n",
+            "#".repeat(level),
+            anchor,
+            self.text(text)
+        ));
+    }
+
+    fn paragraph(&mut self, content: &str) {
+        self.output.push_str(&format!("{}\n\n", content));
+    }
+
+    fn description(&mut self, text: &str) {
+        //the doc comments are written in markdown
+        self.paragraph(text);
+    }
+
+    fn table(&mut self, header: &[&str], rows: &[Vec<String>]) {
+        let row = |cells: &[String]| format!("| {} |\n", cells.join(" | "));
+        self.output.push_str(&row(&header
+            .iter()
+            .map(|it| it.to_string())
+            .collect::<Vec<_>>()));
+        self.output
+            .push_str(&row(&vec!["---".to_string(); header.len()]));
+        for cells in rows {
+            self.output.push_str(&row(cells));
+        }
+        self.output.push('\n');
+    }
+
+    fn text(&self, text: &str) -> String {
+        text.replace('|', "\\|").replace('\n', "<br>")
+    }
+
+    fn bold(&self, text: &str) -> String {
+        format!("**{}**", text)
+    }
+
+    fn type_reference(&self, reference: &TypeReference) -> String {
+        match &reference.link {
+            Some(link) => format!(
+                "{}[{}](#{})",
+                self.text(&reference.prefix),
+                self.text(&reference.name),
+                link
+            ),
+            None => self.text(&format!("{}{}", reference.prefix, reference.name)),
+        }
+    }
+
+    fn finish(self) -> String {
+        self.output
+    }
+}
+
+#[derive(Default)]
+struct Html {
+    output: String,
+}
+
+impl Renderer for Html {
+    fn heading(&mut self, level: usize, text: &str, anchor: Option<&str>) {
+        let id = anchor
+            .map(|it| format!(" id=\"{}\"", it))
+            .unwrap_or_default();
+        self.output.push_str(&format!(
+            "<h{level}{}>{}</h{level}>\n",
+            id,
+            self.text(text),
+            level = level
+        ));
+    }
+
+    fn paragraph(&mut self, content: &str) {
+        self.output.push_str(&format!("<p>{}</p>\n", content));
+    }
+
+    fn description(&mut self, text: &str) {
+        for paragraph in text.split("\n\n") {
+            let content = self.text(paragraph.trim());
+            self.paragraph(&content);
+        }
+    }
+
+    fn table(&mut self, header: &[&str], rows: &[Vec<String>]) {
+        self.output.push_str("<table>\n<tr>");
+        for cell in header {
+            self.output.push_str(&format!("<th>{}</th>", cell));
+        }
+        self.output.push_str("</tr>\n");
+        for cells in rows {
+            self.output.push_str("<tr>");
+            for cell in cells {
+                self.output.push_str(&format!("<td>{}</td>", cell));
+            }
+            self.output.push_str("</tr>\n");
+        }
+        self.output.push_str("</table>\n");
+    }
+
+    fn text(&self, text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+            .replace('\n', "<br>")
+    }
+
+    fn bold(&self, text: &str) -> String {
+        format!("<strong>{}</strong>", self.text(text))
+    }
+
+    fn type_reference(&self, reference: &TypeReference) -> String {
+        match &reference.link {
+            Some(link) => format!(
+                "{}<a href=\"#{}\">{}</a>",
+                self.text(&reference.prefix),
+                link,
+                self.text(&reference.name)
+            ),
+            None => self.text(&format!("{}{}", reference.prefix, reference.name)),
+        }
+    }
+
+    fn finish(self) -> String {
+        format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+            <title>API documentation</title>\n</head>\n<body>\n{}</body>\n</html>\n",
+            self.output
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{collect_documentation, generate_documentation, Member, TypeReference};
+    use crate::{
+        resolver::const_evaluator::evaluate_constants, test_utils::tests::index, DocFormat,
+    };
+
+    const SOURCE: &str = "
+        (**
+         * Drives a motor.
+         * @param speed the requested speed
+         *)
+        FUNCTION_BLOCK Motor
+        VAR_INPUT
+            speed : INT;
+            /// the position to drive to
+            target : Point;
+        END_VAR
+        VAR_IN_OUT
+            positions : ARRAY[0..LAST] OF Point; // the reached positions
+        END_VAR
+        END_FUNCTION_BLOCK
+
+        /// the distance between two points
+        FUNCTION distance : REAL
+        VAR_INPUT a, b : Point; END_VAR
+        END_FUNCTION
+
+        (** a point on the plane *)
+        TYPE Point : STRUCT
+            x : INT; (* the x coordinate *)
+            y : INT;
+        END_STRUCT END_TYPE
+
+        (* not documented *)
+        TYPE Color : (red, green) := red; END_TYPE
+
+        VAR_GLOBAL CONSTANT
+            LAST : INT := 9; // the last position
+        END_VAR
+        ";
+
+    #[test]
+    fn doc_comments_describe_the_declarations() {
+        let (unit, index) = index(SOURCE);
+        let (index, _) = evaluate_constants(index);
+        let documentation = collect_documentation(&[&unit], &index);
+
+        let motor = &documentation.pous[0];
+        assert_eq!(
+            (motor.kind, motor.name.as_str()),
+            ("FUNCTION_BLOCK", "Motor")
+        );
+        assert_eq!(motor.description, "Drives a motor.");
+        let members = motor
+            .members
+            .iter()
+            .map(|it| (it.name.as_str(), it.kind, it.description.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            members,
+            vec![
+                ("speed", "VAR_INPUT", "the requested speed"),
+                ("target", "VAR_INPUT", "the position to drive to"),
+                ("positions", "VAR_IN_OUT", "the reached positions"),
+            ]
+        );
+
+        let distance = &documentation.pous[1];
+        assert_eq!(distance.description, "the distance between two points");
+        assert_eq!(
+            distance.result.as_ref().map(|(it, _)| it.name.as_str()),
+            Some("REAL")
+        );
+
+        let point = &documentation.types[0];
+        assert_eq!(point.description, "a point on the plane");
+        assert_eq!(point.members[0].description, "the x coordinate");
+        //only doc comments describe a declaration
+        let color = &documentation.types[1];
+        assert_eq!(color.description, "");
+        assert_eq!(
+            color
+                .members
+                .iter()
+                .map(|it| it.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["red", "green"]
+        );
+
+        assert_eq!(
+            documentation.globals,
+            vec![Member {
+                name: "LAST".to_string(),
+                kind: "",
+                data_type: Some(TypeReference {
+                    prefix: String::new(),
+                    name: "INT".to_string(),
+                    link: None
+                }),
+                description: "the last position".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn types_are_resolved_and_linked() {
+        let (unit, index) = index(SOURCE);
+        let (index, _) = evaluate_constants(index);
+        let documentation = collect_documentation(&[&unit], &index);
+
+        let motor = &documentation.pous[0];
+        assert_eq!(
+            motor.members[2].data_type,
+            Some(TypeReference {
+                prefix: "ARRAY[0..9] OF ".to_string(),
+                name: "Point".to_string(),
+                link: Some("point".to_string())
+            })
+        );
+
+        let markdown = generate_documentation(&documentation, DocFormat::Markdown);
+        assert!(markdown.contains("### <a id=\"motor\"></a>FUNCTION_BLOCK Motor"));
+        assert!(markdown.contains(
+            "| positions | VAR_IN_OUT | ARRAY[0..9] OF [Point](#point) | the reached positions |"
+        ));
+        assert!(markdown.contains("**Returns:** REAL"));
+
+        let html = generate_documentation(&documentation, DocFormat::Html);
+        assert!(html.contains("<h3 id=\"point\">TYPE Point</h3>"));
+        assert!(html.contains("<td>ARRAY[0..9] OF <a href=\"#point\">Point</a></td>"));
+    }
+}
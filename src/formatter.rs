@@ -0,0 +1,600 @@
+// Copyright (c) 2022 Ghaith Hachem and Mathias Rieder
+//! Formats structured text sources (`rusty fmt`).
+//!
+//! The formatter re-prints the tokens of a source with normalized spacing, indents the lines by
+//! the nesting of their blocks (POUs, variable blocks, types and control statements), applies
+//! the configured case to the keywords and wraps lines exceeding the configured width. The line
+//! breaks of the source are kept (consecutive blank lines are reduced to one), so are comments,
+//! pragmas and the regions excluded by conditional compilation directives.
+use std::ops::Range;
+
+use crate::{
+    lexer::{self, IdProvider, Token},
+    KeywordCase,
+};
+
+pub struct FormatOptions {
+    /// the text a nested line is indented with per level
+    pub indent: String,
+    pub keyword_case: KeywordCase,
+    /// the width lines are wrapped at, if they contain a place to break them
+    pub line_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            indent: "    ".to_string(),
+            keyword_case: KeywordCase::Upper,
+            line_width: 100,
+        }
+    }
+}
+
+/// returns the formatted source, or None if the formatted source would not consist of the same
+/// tokens as the given source
+pub fn format(source: &str, options: &FormatOptions) -> Option<String> {
+    let tokens = lex(source);
+    let mut formatter = Formatter::new(options);
+    let mut position = 0;
+    for (token, range) in &tokens {
+        formatter.write_gap(&source[position..range.start]);
+        formatter.write_token(token, &source[range.clone()]);
+        position = range.end;
+    }
+    formatter.write_gap(&source[position..]);
+    let formatted = formatter.finish();
+
+    if has_same_tokens(source, &formatted) {
+        Some(formatted)
+    } else {
+        None
+    }
+}
+
+/// returns true if the formatted source consists of the same tokens as the given source. The
+/// formatting may only change the whitespace and the case of keywords, all other tokens (e.g.
+/// identifiers and string literals) have to be the same, including their case
+fn has_same_tokens(source: &str, formatted: &str) -> bool {
+    let slices = |source: &str| {
+        lex(source)
+            .into_iter()
+            .map(|(token, range)| {
+                let slice = &source[range];
+                if is_keyword(&token, slice) {
+                    (token, slice.to_lowercase())
+                } else {
+                    (token, slice.to_string())
+                }
+            })
+            .collect::<Vec<_>>()
+    };
+    slices(source) == slices(formatted)
+}
+
+/// returns true if the given token is a keyword, whose case is applied by the formatter
+fn is_keyword(token: &Token, slice: &str) -> bool {
+    token != &Token::Identifier && slice.chars().all(|it| it.is_alphabetic() || it == '_')
+}
+
+/// returns the tokens of the given source with their ranges. Comments, pragmas and the regions
+/// excluded by conditional compilation directives are not tokens
+fn lex(source: &str) -> Vec<(Token, Range<usize>)> {
+    let mut lexer = lexer::lex_with_ids(source, IdProvider::default());
+    let mut tokens = vec![];
+    while !lexer.is_end_of_stream() {
+        tokens.push((lexer.token.clone(), lexer.range()));
+        lexer.advance();
+    }
+    tokens
+}
+
+struct Formatter<'a> {
+    options: &'a FormatOptions,
+    output: String,
+    /// the tokens opening the blocks enclosing the current position
+    blocks: Vec<Token>,
+    /// the indentation level of the current line
+    line_level: usize,
+    /// the line breaks preceding the next token or comment
+    pending_line_breaks: usize,
+    /// true if nothing was written on the current line yet
+    at_line_start: bool,
+    /// the last written token and whether it is a unary operator
+    last: Option<(Token, bool)>,
+    /// true if a comment or pragma was written after the last token
+    after_trivia: bool,
+}
+
+impl<'a> Formatter<'a> {
+    fn new(options: &'a FormatOptions) -> Self {
+        Formatter {
+            options,
+            output: String::new(),
+            blocks: vec![],
+            line_level: 0,
+            pending_line_breaks: 0,
+            at_line_start: true,
+            last: None,
+            after_trivia: false,
+        }
+    }
+
+    /// writes the comments and pragmas of the text between two tokens
+    fn write_gap(&mut self, gap: &str) {
+        let mut rest = gap;
+        loop {
+            let trimmed = rest.trim_start();
+            self.pending_line_breaks += rest[..rest.len() - trimmed.len()].matches('\n').count();
+            if trimmed.is_empty() {
+                break;
+            }
+            let (trivia, remainder) = trimmed.split_at(get_trivia_length(trimmed));
+            let level = self.blocks.len();
+            self.start_item(level);
+            if !self.at_line_start {
+                self.output.push(' ');
+            }
+            self.output.push_str(trivia.trim_end());
+            self.at_line_start = false;
+            self.after_trivia = true;
+            rest = remainder;
+        }
+    }
+
+    fn write_token(&mut self, token: &Token, slice: &str) {
+        let closes_block = is_block_end(token);
+        let level = if closes_block || continues_block(token) {
+            self.blocks.len().saturating_sub(1)
+        } else {
+            self.blocks.len()
+        };
+        self.start_item(level);
+
+        let is_unary = matches!(token, Token::OperatorMinus | Token::OperatorPlus)
+            && !self.last.as_ref().map_or(false, |(it, _)| ends_operand(it));
+        if !self.at_line_start {
+            let needs_space = self.after_trivia
+                || self
+                    .last
+                    .as_ref()
+                    .map_or(false, |(last, unary)| !unary && needs_space(last, token));
+            if needs_space {
+                //a line exceeding the width is continued on the next line
+                let line_start = self.output.rfind('\n').map_or(0, |it| it + 1);
+                let width = self.output[line_start..].chars().count();
+                if width + 1 + slice.chars().count() > self.options.line_width {
+                    self.break_line(1);
+                    self.indent(self.line_level + 1);
+                } else {
+                    self.output.push(' ');
+                }
+            }
+        }
+        if is_keyword(token, slice) {
+            match self.options.keyword_case {
+                KeywordCase::Upper => self.output.push_str(&slice.to_uppercase()),
+                KeywordCase::Lower => self.output.push_str(&slice.to_lowercase()),
+                KeywordCase::Preserve => self.output.push_str(slice),
+            }
+        } else {
+            self.output.push_str(slice);
+        }
+        self.at_line_start = false;
+        self.after_trivia = false;
+
+        if closes_block {
+            self.blocks.pop();
+        } else if self.opens_block(token, slice) {
+            self.blocks.push(token.clone());
+        }
+        self.last = Some((token.clone(), is_unary));
+    }
+
+    /// starts a new line at the given level if the source breaks the line before the next item
+    fn start_item(&mut self, level: usize) {
+        if self.pending_line_breaks > 0 {
+            self.break_line(self.pending_line_breaks);
+            self.pending_line_breaks = 0;
+        }
+        if self.at_line_start {
+            self.indent(level);
+        }
+    }
+
+    /// ends the current line, at most one blank line is kept
+    fn break_line(&mut self, line_breaks: usize) {
+        self.trim_line_end();
+        if !self.output.is_empty() {
+            for _ in 0..line_breaks.min(2) {
+                self.output.push('\n');
+            }
+        }
+        self.at_line_start = true;
+    }
+
+    fn indent(&mut self, level: usize) {
+        self.line_level = level;
+        for _ in 0..level {
+            self.output.push_str(&self.options.indent);
+        }
+    }
+
+    fn trim_line_end(&mut self) {
+        let length = self
+            .output
+            .trim_end_matches(|it| it == ' ' || it == '\t')
+            .len();
+        self.output.truncate(length);
+    }
+
+    fn opens_block(&self, token: &Token, slice: &str) -> bool {
+        match token {
+            //the programs of a resource have no body
+            Token::KeywordProgram => self.blocks.last() != Some(&Token::KeywordResource),
+            Token::KeywordFunction
+            | Token::KeywordFunctionBlock
+            | Token::KeywordClass
+            | Token::KeywordInterface
+            | Token::KeywordMethod
+            | Token::KeywordProperty
+            | Token::KeywordAction
+            | Token::KeywordActions
+            | Token::KeywordNamespace
+            | Token::KeywordConfiguration
+            | Token::KeywordResource
+            | Token::KeywordType
+            | Token::KeywordStruct
+            | Token::KeywordUnion
+            | Token::KeywordVar
+            | Token::KeywordVarInput
+            | Token::KeywordVarOutput
+            | Token::KeywordVarInOut
+            | Token::KeywordVarGlobal
+            | Token::KeywordVarTemp
+            | Token::KeywordVarExternal
+            | Token::KeywordVarConfig
+            | Token::KeywordIf
+            | Token::KeywordFor
+            | Token::KeywordWhile
+            | Token::KeywordRepeat
+            | Token::KeywordCase => true,
+            //the accessors of a property are no keywords
+            Token::Identifier => {
+                self.blocks.last() == Some(&Token::KeywordProperty)
+                    && (slice.eq_ignore_ascii_case("GET") || slice.eq_ignore_ascii_case("SET"))
+            }
+            _ => false,
+        }
+    }
+
+    fn finish(mut self) -> String {
+        self.trim_line_end();
+        if !self.output.is_empty() {
+            self.output.push('\n');
+        }
+        self.output
+    }
+}
+
+fn is_block_end(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::KeywordEndProgram
+            | Token::KeywordEndFunction
+            | Token::KeywordEndFunctionBlock
+            | Token::KeywordEndClass
+            | Token::KeywordEndInterface
+            | Token::KeywordEndMethod
+            | Token::KeywordEndProperty
+            | Token::KeywordEndGet
+            | Token::KeywordEndSet
+            | Token::KeywordEndAction
+            | Token::KeywordEndActions
+            | Token::KeywordEndNamespace
+            | Token::KeywordEndConfiguration
+            | Token::KeywordEndResource
+            | Token::KeywordEndType
+            | Token::KeywordEndStruct
+            | Token::KeywordEndUnion
+            | Token::KeywordEndVar
+            | Token::KeywordEndIf
+            | Token::KeywordEndFor
+            | Token::KeywordEndWhile
+            | Token::KeywordEndRepeat
+            | Token::KeywordEndCase
+    )
+}
+
+/// returns true if the given token starts another branch of its block, it is indented like
+/// the token opening the block
+fn continues_block(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::KeywordElse | Token::KeywordElseIf | Token::KeywordUntil
+    )
+}
+
+/// returns true if the given token may end an operand, a following `-` or `+` is a binary
+/// operator
+fn ends_operand(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Identifier
+            | Token::KeywordParensClose
+            | Token::KeywordSquareParensClose
+            | Token::OperatorDeref
+            | Token::LiteralIntegerHex
+            | Token::LiteralIntegerOct
+            | Token::LiteralIntegerBin
+            | Token::LiteralInteger
+            | Token::LiteralNull
+            | Token::LiteralTrue
+            | Token::LiteralFalse
+            | Token::LiteralDate
+            | Token::LiteralDateAndTime
+            | Token::LiteralTimeOfDay
+            | Token::LiteralTime
+            | Token::LiteralString
+            | Token::LiteralWideString
+    )
+}
+
+/// returns true if the given tokens are separated by a space
+fn needs_space(last: &Token, next: &Token) -> bool {
+    match (last, next) {
+        (
+            _,
+            Token::KeywordSemicolon
+            | Token::KeywordComma
+            | Token::KeywordParensClose
+            | Token::KeywordSquareParensClose
+            | Token::KeywordDot
+            | Token::KeywordDotDot
+            | Token::OperatorDeref,
+        ) => false,
+        (
+            Token::KeywordParensOpen
+            | Token::KeywordSquareParensOpen
+            | Token::KeywordDot
+            | Token::KeywordDotDot
+            | Token::TypeCastPrefix
+            | Token::DirectAccess(..)
+            | Token::HardwareAccess(..),
+            _,
+        ) => false,
+        //calls, array accesses and the sizes of strings
+        (
+            Token::Identifier
+            | Token::KeywordSquareParensClose
+            | Token::OperatorDeref
+            | Token::KeywordArray
+            | Token::KeywordString
+            | Token::KeywordWideString,
+            Token::KeywordParensOpen | Token::KeywordSquareParensOpen,
+        ) => false,
+        _ => true,
+    }
+}
+
+/// returns the length of the comment, pragma or excluded code at the start of the given text
+fn get_trivia_length(text: &str) -> usize {
+    let line_length = text.find('\n').unwrap_or(text.len());
+    if text.starts_with("(*") || text.starts_with("/*") {
+        get_comment_length(text)
+    } else if text.starts_with('{') {
+        text.find('}').map_or(line_length, |it| it + 1)
+    } else {
+        //line comments and excluded code are kept to the end of the line
+        line_length
+    }
+}
+
+/// returns the length of the (nested) comment at the start of the given text, like the lexer
+/// an unclosed comment reaches to the end of the text
+fn get_comment_length(text: &str) -> usize {
+    let (open, close) = if text.starts_with("(*") {
+        ('(', ')')
+    } else {
+        ('/', '/')
+    };
+    let mut unclosed = 1;
+    let mut prev = ' ';
+    for (position, c) in text.char_indices().skip(2) {
+        if c == '*' && prev == open {
+            unclosed += 1;
+            prev = ' ';
+        } else if c == close && prev == '*' {
+            unclosed -= 1;
+            prev = c;
+        } else {
+            prev = c;
+        }
+        if unclosed == 0 {
+            return position + c.len_utf8();
+        }
+    }
+    text.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format, has_same_tokens, FormatOptions};
+    use crate::KeywordCase;
+
+    fn format_default(source: &str) -> String {
+        format(source, &FormatOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn statements_are_indented_by_their_blocks() {
+        let source = "
+function foo : int
+var_input  a:INT;END_VAR
+    IF a>0 THEN
+foo:=a*-1;
+  ELSIF a = 0 then foo := 1;
+else
+foo:=BAR(a,2);
+end_if
+END_FUNCTION
+";
+        assert_eq!(
+            format_default(source),
+            "FUNCTION foo : int
+    VAR_INPUT a : INT; END_VAR
+    IF a > 0 THEN
+        foo := a * -1;
+    ELSIF a = 0 THEN foo := 1;
+    ELSE
+        foo := BAR(a, 2);
+    END_IF
+END_FUNCTION
+"
+        );
+    }
+
+    #[test]
+    fn comments_and_pragmas_are_kept() {
+        let source = "PROGRAM prg // the program
+VAR
+(* a nested (* comment *) *)
+    {attribute 'hidden'}
+    x   : INT; (* inline *) y : INT;
+END_VAR
+
+
+// before the body
+x := 1;
+END_PROGRAM";
+        assert_eq!(
+            format_default(source),
+            "PROGRAM prg // the program
+    VAR
+        (* a nested (* comment *) *)
+        {attribute 'hidden'}
+        x : INT; (* inline *) y : INT;
+    END_VAR
+
+    // before the body
+    x := 1;
+END_PROGRAM
+"
+        );
+    }
+
+    #[test]
+    fn literals_accesses_and_types_keep_their_tokens_together() {
+        let source = "PROGRAM prg
+VAR a : ARRAY [ 0 .. 2 ] OF REAL; s : STRING [ 10 ]; p : REF_TO INT; END_VAR
+a [ 1 ] := 1.5 + INT#-3 - 2;
+p ^ := 16#FF;
+x . %X1 := t#5s > T#1ms;
+END_PROGRAM
+";
+        assert_eq!(
+            format_default(source),
+            "PROGRAM prg
+    VAR a : ARRAY[0..2] OF REAL; s : STRING[10]; p : REF_TO INT; END_VAR
+    a[1] := 1.5 + INT#-3 - 2;
+    p^ := 16#FF;
+    x.%X1 := t#5s > T#1ms;
+END_PROGRAM
+"
+        );
+    }
+
+    #[test]
+    fn keywords_are_cased_as_configured() {
+        let source = "Program prg x := TRUE AND Not y; End_Program";
+        let options = |keyword_case| FormatOptions {
+            keyword_case,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format(source, &options(KeywordCase::Lower)).unwrap(),
+            "program prg x := true and not y; end_program\n"
+        );
+        assert_eq!(
+            format(source, &options(KeywordCase::Preserve)).unwrap(),
+            "Program prg x := TRUE AND Not y; End_Program\n"
+        );
+    }
+
+    #[test]
+    fn long_lines_are_wrapped_with_the_configured_indentation() {
+        let source = "PROGRAM prg
+x := foo(first_argument, second_argument) + bar;
+END_PROGRAM
+";
+        let options = FormatOptions {
+            indent: "\t".to_string(),
+            line_width: 30,
+            ..FormatOptions::default()
+        };
+        assert_eq!(
+            format(source, &options).unwrap(),
+            "PROGRAM prg
+\tx := foo(first_argument,
+\t\tsecond_argument) + bar;
+END_PROGRAM
+"
+        );
+    }
+
+    #[test]
+    fn excluded_regions_are_kept() {
+        let source = "PROGRAM prg
+{IF defined(PLC_A)}
+    x := 1;
+{ELSE}
+x:=2;
+{END_IF}
+END_PROGRAM
+";
+        assert_eq!(
+            format_default(source),
+            "PROGRAM prg
+    {IF defined(PLC_A)}
+    x := 1;
+    {ELSE}
+    x := 2;
+    {END_IF}
+END_PROGRAM
+"
+        );
+    }
+
+    #[test]
+    fn string_literals_keep_their_case() {
+        let source = "program prg
+s := 'Hello World';ws := \"MiXeD CaSe\"; x := Foo.Bar;
+end_program
+";
+        let options = FormatOptions {
+            keyword_case: KeywordCase::Lower,
+            ..FormatOptions::default()
+        };
+        let formatted = format(source, &options).unwrap();
+        assert_eq!(
+            formatted,
+            "program prg
+    s := 'Hello World'; ws := \"MiXeD CaSe\"; x := Foo.Bar;
+end_program
+"
+        );
+        //formatting the formatted source again keeps it as is
+        assert_eq!(format(&formatted, &options).unwrap(), formatted);
+    }
+
+    #[test]
+    fn only_the_case_of_keywords_may_change() {
+        assert!(has_same_tokens(
+            "IF x THEN s := 'Text'; END_IF",
+            "if x then s := 'Text'; end_if"
+        ));
+        assert!(!has_same_tokens("s := 'Text';", "s := 'TEXT';"));
+        assert!(!has_same_tokens("s := \"Text\";", "s := \"text\";"));
+        assert!(!has_same_tokens("x := Foo;", "x := foo;"));
+    }
+}
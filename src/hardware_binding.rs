@@ -6,8 +6,8 @@ use serde::{
 use crate::{
     ast::{DirectAccessType, HardwareAccessType},
     diagnostics::{Diagnostic, ErrNo},
-    expression_path::ExpressionPath,
-    index::Index,
+    expression_path::{ExpressionPath, ExpressionPathElement},
+    index::{self, Index},
     ConfigFormat,
 };
 
@@ -125,33 +125,65 @@ impl SerializeWithContext for HardwareBinding<'_> {
     }
 }
 
+fn evaluate_address(
+    index: &Index,
+    binding: &index::HardwareBinding,
+) -> Result<Vec<String>, String> {
+    binding
+        .entries
+        .iter()
+        .map(|it| {
+            index
+                .get_const_expressions()
+                .get_constant_int_statement_value(it)
+        })
+        .map(|it| it.map(|it| it.to_string()))
+        .collect()
+}
+
 /// Retrieves hardware bindings from all defined instances in the program
+/// Template bindings (`AT %I*`) that are assigned an address in a `VAR_CONFIG` block
+/// are reported with the configured address instead
 pub fn collect_hardware_configuration(index: &Index) -> Result<HardwareConfiguration, Diagnostic> {
+    let config_variables = index.get_config_variables();
+    let is_configured = |name: &ExpressionPath| {
+        matches!(name.expand(index).as_slice(), [name] if config_variables
+            .iter()
+            .any(|it| it.get_reference().eq_ignore_ascii_case(name)))
+    };
     let conf: Result<Vec<HardwareBinding>, String> = index
         //Avoid arrays that are not representing structural types
         .find_instances()
         .filter(|(_, instance)| instance.has_hardware_binding())
+        .filter(|(name, instance)| {
+            !matches!(instance.get_hardware_binding(),
+                Some(binding) if binding.access == DirectAccessType::Template && is_configured(name))
+        })
         .map(|(name, instance)| {
             let binding = instance
                 .get_hardware_binding()
                 .expect("Instance should have a binding");
-            binding
-                .entries
-                .iter()
-                .map(|it| {
-                    index
-                        .get_const_expressions()
-                        .get_constant_int_statement_value(it)
-                })
-                .map(|it| it.map(|it| it.to_string()))
-                .collect::<Result<Vec<String>, String>>()
-                .map(|address| HardwareBinding {
-                    qualifed_name: name,
-                    access_type: binding.access,
-                    address,
-                    direction: binding.direction,
-                })
+            evaluate_address(index, binding).map(|address| HardwareBinding {
+                qualifed_name: name,
+                access_type: binding.access,
+                address,
+                direction: binding.direction,
+            })
         })
+        .chain(config_variables.iter().map(|variable| {
+            let binding = variable.get_binding();
+            evaluate_address(index, binding).map(|address| HardwareBinding {
+                qualifed_name: variable
+                    .get_reference()
+                    .split('.')
+                    .map(ExpressionPathElement::Name)
+                    .collect::<Vec<_>>()
+                    .into(),
+                access_type: binding.access,
+                address,
+                direction: binding.direction,
+            })
+        }))
         .collect();
 
     conf.map(|hardware_binding| HardwareConfiguration {
@@ -312,4 +344,39 @@ mod tests {
         let res = generate_hardware_configuration(&config, ConfigFormat::TOML).unwrap();
         insta::assert_snapshot!(res);
     }
+
+    #[test]
+    fn configured_addresses_replace_template_bindings() {
+        let (_, index) = index(
+            "
+        FUNCTION_BLOCK fb
+        VAR
+            a AT %I*: BOOL;
+            b AT %Q*: WORD;
+        END_VAR
+        END_FUNCTION_BLOCK
+        PROGRAM prg
+        VAR
+            inst : fb;
+        END_VAR
+        END_PROGRAM
+        CONFIGURATION plc
+            VAR_CONFIG
+                prg.inst.a AT %IX1.2 : BOOL;
+            END_VAR
+        END_CONFIGURATION",
+        );
+        let config = collect_hardware_configuration(&index).unwrap();
+        let res = generate_hardware_configuration(&config, ConfigFormat::JSON).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&res).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "HardwareConfiguration": [
+                    [{ "name": "prg.inst.b", "direction": "Output", "type": "Template", "address": [] }],
+                    [{ "name": "prg.inst.a", "direction": "Input", "type": "Bit", "address": ["1", "2"] }]
+                ]
+            })
+        );
+    }
 }
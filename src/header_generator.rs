@@ -0,0 +1,543 @@
+// Copyright (c) 2022 Ghaith Hachem and Mathias Rieder
+
+//! Generates a C header declaring the compiled POUs, their instance structs and the global
+//! variables, so they can be used from C without maintaining the declarations by hand.
+//!
+//! The POUs are declared with the calling convention of the generated code: programs, function
+//! blocks and classes take a pointer to their instance, functions take a pointer to a struct
+//! holding their parameters and methods take a pointer to their class's instance followed by
+//! the pointer to the parameters.
+use std::collections::HashSet;
+
+use crate::{
+    ast::LinkageType,
+    codegen::{generators::data_type_generator::is_vtable_carrier, get_symbol_name},
+    index::{ImplementationIndexEntry, ImplementationType, Index, VariableIndexEntry},
+    typesystem::{self, DataType, DataTypeInformation, StringEncoding},
+};
+
+/// returns the C header declaring the implementations and global variables of the given index
+/// - `name` the name of the header, used for its include guard
+/// - `prefix` the symbol prefix the sources were compiled with
+pub fn generate_header(index: &Index, name: &str, prefix: Option<&str>) -> String {
+    let guard = format!("{}_H", to_c_identifier(name).to_uppercase());
+    let generator = HeaderGenerator { index, prefix };
+
+    let implementations = index
+        .get_implementations()
+        .values()
+        .filter(|it| it.get_linkage() == LinkageType::Internal && generator.is_declared(it))
+        .collect::<Vec<_>>();
+    let mut globals = vec![];
+    let mut symbols = HashSet::new();
+    for variable in index
+        .get_globals()
+        .values()
+        .chain(index.get_global_initializers().values())
+    {
+        //variables located at the same address share their symbol
+        if symbols.insert(generator.get_symbol_name(variable).to_lowercase()) {
+            globals.push(variable);
+        }
+    }
+
+    //the structs used by the declarations, every struct follows the structs it contains
+    let mut structs = StructCollector {
+        index,
+        visited: HashSet::new(),
+        structs: vec![],
+    };
+    for implementation in implementations.iter() {
+        structs.collect_type(implementation.get_type_name());
+        if let Some(class) = implementation.get_associated_class_name() {
+            structs.collect_type(class);
+        }
+    }
+    for variable in globals.iter() {
+        structs.collect_type(variable.get_type_name());
+    }
+
+    let mut header = format!(
+        "#ifndef {guard}\n#define {guard}\n\n#include <stdbool.h>\n#include <stdint.h>\n",
+        guard = guard
+    );
+    if !structs.structs.is_empty() {
+        header.push('\n');
+        for data_type in structs.structs.iter() {
+            let (keyword, name) = get_struct_name(data_type);
+            header.push_str(&format!("typedef {0} {1} {1};\n", keyword, name));
+        }
+        for data_type in structs.structs.iter() {
+            header.push('\n');
+            header.push_str(&generator.generate_struct(data_type));
+        }
+    }
+    let declarations = globals
+        .iter()
+        .filter_map(|it| generator.generate_global_variable(it))
+        .collect::<Vec<_>>();
+    if !declarations.is_empty() {
+        header.push('\n');
+        header.push_str(&declarations.join(""));
+    }
+    let declarations = implementations
+        .iter()
+        .filter_map(|it| generator.generate_implementation(it))
+        .collect::<Vec<_>>();
+    if !declarations.is_empty() {
+        header.push('\n');
+        header.push_str(&declarations.join(""));
+    }
+    header.push_str(&format!("\n#endif /* {} */\n", guard));
+    header
+}
+
+struct HeaderGenerator<'idx> {
+    index: &'idx Index,
+    prefix: Option<&'idx str>,
+}
+
+impl HeaderGenerator<'_> {
+    /// generic implementations are only declared for their instances (e.g. `foo__INT`)
+    fn is_declared(&self, implementation: &ImplementationIndexEntry) -> bool {
+        !self
+            .index
+            .get_type_information_or_void(implementation.get_type_name())
+            .is_generic()
+            && implementation.get_implementation_type() != &ImplementationType::Interface
+    }
+
+    fn get_symbol_name(&self, variable: &VariableIndexEntry) -> String {
+        variable
+            .get_hardware_binding()
+            .and_then(|it| it.get_symbol_name(self.index.get_const_expressions()))
+            .unwrap_or_else(|| {
+                if variable.is_external() {
+                    variable.get_name().to_string()
+                } else {
+                    get_symbol_name(self.prefix, variable.get_name())
+                }
+            })
+    }
+
+    /// returns the definition of the given struct or union, every member is annotated with its
+    /// byte offset
+    fn generate_struct(&self, data_type: &DataType) -> String {
+        let (keyword, name) = get_struct_name(data_type);
+        let keyword = if typesystem::is_packed_struct(data_type.get_name(), self.index) {
+            format!("{} __attribute__((packed))", keyword)
+        } else {
+            keyword.to_string()
+        };
+        let mut members = self
+            .index
+            .get_container_members(data_type.get_name())
+            .into_iter()
+            .filter(|it| !it.is_temp() && !it.is_return())
+            .map(|it| {
+                self.declare(it.get_type_name(), &to_c_identifier(it.get_name()))
+                    .unwrap_or_else(|| format!("/* {} cannot be declared */", it.get_name()))
+            })
+            .collect::<Vec<_>>();
+        if is_vtable_carrier(self.index, data_type) {
+            members.push("void *__vtable".into());
+        }
+        let layout = typesystem::get_struct_layout(
+            data_type.get_name(),
+            typesystem::get_pack_mode(data_type.get_name(), self.index),
+            self.index,
+        );
+        let members = members
+            .iter()
+            .enumerate()
+            .map(
+                |(position, it)| match layout.as_ref().and_then(|it| it.offsets.get(position)) {
+                    Some(offset) => format!("    {}; /* offset {} */\n", it, offset),
+                    None => format!("    {};\n", it),
+                },
+            )
+            .collect::<String>();
+        format!("{} {} {{\n{}}};\n", keyword, name, members)
+    }
+
+    fn generate_global_variable(&self, variable: &VariableIndexEntry) -> Option<String> {
+        let symbol_name = self.get_symbol_name(variable);
+        let declaration = self.declare(variable.get_type_name(), &to_c_identifier(&symbol_name))?;
+        let constant = if variable.is_constant() { "const " } else { "" };
+        Some(format!(
+            "extern {}{}{};\n",
+            constant,
+            declaration,
+            get_symbol_label(&symbol_name)
+        ))
+    }
+
+    fn generate_implementation(&self, implementation: &ImplementationIndexEntry) -> Option<String> {
+        let symbol_name = get_symbol_name(self.prefix, implementation.get_call_name());
+        let instance = format!(
+            "{} *{}",
+            self.get_struct_type_name(implementation.get_type_name())?,
+            match implementation.get_implementation_type() {
+                ImplementationType::Function | ImplementationType::Method => "parameters",
+                _ => "instance",
+            }
+        );
+        let parameters = match implementation.get_associated_class_name() {
+            Some(class)
+                if implementation.get_implementation_type() == &ImplementationType::Method =>
+            {
+                format!("{} *self, {}", self.get_struct_type_name(class)?, instance)
+            }
+            _ => instance,
+        };
+        let function = format!("{}({})", to_c_identifier(&symbol_name), parameters);
+        let return_type = self
+            .index
+            .find_return_type(implementation.get_type_name())
+            .map(DataType::get_name);
+        let declaration =
+            match return_type.map(|it| (it, self.index.get_type_information_or_void(it))) {
+                //arrays cannot be returned in C
+                Some((_, DataTypeInformation::Array { .. }))
+                | Some((_, DataTypeInformation::String { .. })) => return None,
+                Some((return_type, _)) => self.declare(return_type, &function)?,
+                None => format!("void {}", function),
+            };
+        Some(format!(
+            "{}{};\n",
+            declaration,
+            get_symbol_label(&symbol_name)
+        ))
+    }
+
+    /// returns the name of the struct generated for the given POU or struct type
+    fn get_struct_type_name(&self, type_name: &str) -> Option<String> {
+        match self.index.find_effective_type(type_name) {
+            Some(
+                data_type @ DataType {
+                    information: DataTypeInformation::Struct { .. },
+                    ..
+                },
+            ) => Some(get_struct_name(data_type).1),
+            _ => None,
+        }
+    }
+
+    /// returns the C declaration of the given declarator (e.g. a variable's name) with the given
+    /// type, or None if the type cannot be represented in C (e.g. generic types)
+    fn declare(&self, type_name: &str, declarator: &str) -> Option<String> {
+        let data_type = self.index.find_effective_type(type_name)?;
+        let c_type = match data_type.get_type_information() {
+            DataTypeInformation::Integer {
+                semantic_size: Some(1),
+                ..
+            } => "bool".to_string(),
+            DataTypeInformation::Integer { signed, size, .. } => {
+                format!("{}int{}_t", if *signed { "" } else { "u" }, size)
+            }
+            DataTypeInformation::Float { size, .. } => {
+                if *size > 32 { "double" } else { "float" }.to_string()
+            }
+            DataTypeInformation::String { size, encoding } => {
+                let length = size.as_int_value(self.index).ok()?;
+                let char_type = if *encoding == StringEncoding::Utf8 {
+                    "char"
+                } else {
+                    "uint16_t"
+                };
+                return Some(format!("{} {}[{}]", char_type, declarator, length));
+            }
+            DataTypeInformation::Array {
+                inner_type_name,
+                dimensions,
+                ..
+            } => {
+                let lengths = dimensions
+                    .iter()
+                    .map(|it| {
+                        it.get_length(self.index)
+                            .ok()
+                            .map(|len| format!("[{}]", len))
+                    })
+                    .collect::<Option<String>>()?;
+                return self.declare(inner_type_name, &format!("{}{}", declarator, lengths));
+            }
+            DataTypeInformation::Pointer {
+                inner_type_name, ..
+            } => {
+                let declarator = match self.index.find_effective_type_info(inner_type_name) {
+                    Some(DataTypeInformation::Array { .. })
+                    | Some(DataTypeInformation::String { .. }) => format!("(*{})", declarator),
+                    _ => format!("*{}", declarator),
+                };
+                return self
+                    .declare(inner_type_name, &declarator)
+                    .or_else(|| Some(format!("void {}", declarator)));
+            }
+            DataTypeInformation::Struct { .. } => get_struct_name(data_type).1,
+            DataTypeInformation::Enum {
+                referenced_type, ..
+            }
+            | DataTypeInformation::SubRange {
+                referenced_type, ..
+            }
+            | DataTypeInformation::Alias {
+                referenced_type, ..
+            } => return self.declare(referenced_type, declarator),
+            DataTypeInformation::Generic { .. } | DataTypeInformation::Void => return None,
+        };
+        Some(format!("{} {}", c_type, declarator))
+    }
+}
+
+/// collects the structs used by the declared types, a struct follows all structs it contains
+/// and precedes the structs it only references (which are declared upfront)
+struct StructCollector<'idx> {
+    index: &'idx Index,
+    visited: HashSet<String>,
+    structs: Vec<&'idx DataType>,
+}
+
+impl<'idx> StructCollector<'idx> {
+    fn collect_type(&mut self, type_name: &str) {
+        let mut referenced_types = vec![];
+        self.collect_contained_type(type_name, &mut referenced_types);
+        for referenced_type in referenced_types {
+            self.collect_type(&referenced_type);
+        }
+    }
+
+    /// collects the structs contained in the given type, the types referenced by pointers are
+    /// added to `referenced_types`
+    fn collect_contained_type(&mut self, type_name: &str, referenced_types: &mut Vec<String>) {
+        let data_type = match self.index.find_effective_type(type_name) {
+            Some(it) => it,
+            None => return,
+        };
+        match data_type.get_type_information() {
+            DataTypeInformation::Struct { .. } => self.collect_struct(data_type),
+            DataTypeInformation::Array {
+                inner_type_name, ..
+            } => self.collect_contained_type(inner_type_name, referenced_types),
+            DataTypeInformation::Pointer {
+                inner_type_name, ..
+            } => referenced_types.push(inner_type_name.clone()),
+            _ => {}
+        }
+    }
+
+    fn collect_struct(&mut self, data_type: &'idx DataType) {
+        if data_type.get_type_information().is_generic()
+            || !self.visited.insert(data_type.get_name().to_lowercase())
+        {
+            return;
+        }
+        let mut referenced_types = vec![];
+        for member in self
+            .index
+            .get_container_members(data_type.get_name())
+            .into_iter()
+            .filter(|it| !it.is_temp() && !it.is_return())
+        {
+            self.collect_contained_type(member.get_type_name(), &mut referenced_types);
+        }
+        self.structs.push(data_type);
+        for referenced_type in referenced_types {
+            self.collect_type(&referenced_type);
+        }
+    }
+}
+
+/// returns the keyword (struct or union) and the C name of the given struct type
+fn get_struct_name(data_type: &DataType) -> (&'static str, String) {
+    let keyword = if data_type.get_type_information().is_union() {
+        "union"
+    } else {
+        "struct"
+    };
+    (
+        keyword,
+        to_c_identifier(data_type.get_type_information().get_name()),
+    )
+}
+
+/// qualified names (e.g. `Lib.foo` or `MyClass.method`) are not valid C identifiers,
+/// their dots are replaced by double underscores (e.g. `Lib__foo`), other invalid characters
+/// by an underscore
+fn to_c_identifier(name: &str) -> String {
+    name.split('.')
+        .map(|part| {
+            part.chars()
+                .map(|it| if it.is_ascii_alphanumeric() { it } else { '_' })
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join("__")
+}
+
+/// returns the asm label binding a declaration to its symbol if the symbol is not a valid
+/// C identifier (e.g. ` __asm__("Lib.foo")`)
+fn get_symbol_label(symbol_name: &str) -> String {
+    if to_c_identifier(symbol_name) == symbol_name {
+        String::new()
+    } else {
+        format!(" __asm__(\"{}\")", symbol_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{header_generator::generate_header, test_utils::tests::index};
+
+    #[test]
+    fn header_declares_structs_globals_and_implementations() {
+        let (_, index) = index(
+            "
+        TYPE Point : STRUCT x, y : REAL; END_STRUCT END_TYPE
+
+        VAR_GLOBAL
+            origin : Point;
+            input AT %IW1 : INT;
+        END_VAR
+
+        FUNCTION_BLOCK Counter
+        VAR_INPUT step : DINT; END_VAR
+        VAR count : DINT; last : Point; name : STRING[10]; END_VAR
+        END_FUNCTION_BLOCK
+
+        PROGRAM prg
+        VAR counters : ARRAY[0..1] OF Counter; position : REF_TO Point; flag : BOOL; END_VAR
+        END_PROGRAM
+
+        FUNCTION add : LINT
+        VAR_INPUT a : SINT; b : UDINT; END_VAR
+        END_FUNCTION
+
+        @EXTERNAL FUNCTION log : DINT END_FUNCTION
+        ",
+        );
+
+        let header = generate_header(&index, "out", None);
+
+        assert_eq!(
+            header,
+            r#"#ifndef OUT_H
+#define OUT_H
+
+#include <stdbool.h>
+#include <stdint.h>
+
+typedef struct Point Point;
+typedef struct Counter_interface Counter_interface;
+typedef struct prg_interface prg_interface;
+typedef struct add_interface add_interface;
+
+struct Point {
+    float x; /* offset 0 */
+    float y; /* offset 4 */
+};
+
+struct Counter_interface {
+    int32_t step; /* offset 0 */
+    int32_t count; /* offset 4 */
+    Point last; /* offset 8 */
+    char name[11]; /* offset 16 */
+};
+
+struct prg_interface {
+    Counter_interface counters[2]; /* offset 0 */
+    Point *position; /* offset 56 */
+    bool flag; /* offset 64 */
+};
+
+struct add_interface {
+    int8_t a; /* offset 0 */
+    uint32_t b; /* offset 4 */
+};
+
+extern Point origin;
+extern int16_t __PI_W_1;
+extern prg_interface prg_instance;
+extern const Counter_interface Counter__init;
+
+void Counter(Counter_interface *instance);
+void prg(prg_interface *instance);
+int64_t add(add_interface *parameters);
+
+#endif /* OUT_H */
+"#
+        );
+    }
+
+    #[test]
+    fn qualified_names_are_bound_to_their_symbols() {
+        let (_, index) = index(
+            "
+        NAMESPACE Lib
+            VAR_GLOBAL counter : DINT; END_VAR
+            CLASS Shape
+            VAR size : INT; END_VAR
+                METHOD grow : INT
+                VAR_INPUT by : INT; END_VAR
+                END_METHOD
+            END_CLASS
+        END_NAMESPACE
+        ",
+        );
+
+        let header = generate_header(&index, "lib", None);
+
+        assert!(header.contains("extern int32_t Lib__counter __asm__(\"Lib.counter\");\n"));
+        assert!(header.contains(
+            "int16_t Lib__Shape__grow(Lib__Shape_interface *self, Lib__Shape__grow_interface *parameters) __asm__(\"Lib.Shape.grow\");\n"
+        ));
+    }
+
+    #[test]
+    fn symbols_are_declared_with_their_prefix() {
+        let (_, index) = index(
+            "
+        VAR_GLOBAL
+            counter : DINT;
+            input AT %IW1 : INT;
+        END_VAR
+        NAMESPACE Lib
+            FUNCTION foo : INT END_FUNCTION
+        END_NAMESPACE
+        PROGRAM prg END_PROGRAM
+        ",
+        );
+
+        let header = generate_header(&index, "lib", Some("lib1_"));
+
+        assert!(header.contains("extern int32_t lib1_counter;\n"));
+        assert!(header.contains("extern int16_t __PI_W_1;\n"));
+        assert!(header.contains("extern prg_interface lib1_prg_instance;\n"));
+        assert!(header.contains("void lib1_prg(prg_interface *instance);\n"));
+        assert!(header.contains(
+            "int16_t lib1_Lib__foo(Lib__foo_interface *parameters) __asm__(\"lib1_Lib.foo\");\n"
+        ));
+    }
+
+    #[test]
+    fn packed_structs_are_declared_packed() {
+        let (_, index) = index(
+            "
+        {attribute 'pack_mode' := '1'}
+        TYPE Frame : STRUCT id : BYTE; value : DINT; flags : WORD; END_STRUCT END_TYPE
+        VAR_GLOBAL frame : Frame; END_VAR
+        ",
+        );
+
+        let header = generate_header(&index, "frames", None);
+
+        assert!(header.contains(
+            r#"struct __attribute__((packed)) Frame {
+    uint8_t id; /* offset 0 */
+    int32_t value; /* offset 1 */
+    uint16_t flags; /* offset 5 */
+};
+"#
+        ));
+    }
+}
@@ -1,12 +1,17 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 use crate::{
     ast::{
-        AstStatement, DirectAccessType, HardwareAccessType, Implementation, LinkageType, PouType,
-        SourceRange, TypeNature,
+        AccessModifier, AstStatement, Attribute, DirectAccessType, HardwareAccessType,
+        Implementation, LinkageType, Pou, PouType, SourceRange, TypeNature, PROPERTY_GETTER_PREFIX,
+        PROPERTY_SETTER_PREFIX,
     },
     diagnostics::Diagnostic,
+    lexer::IdProvider,
+    resolver::generics,
     typesystem::{self, *},
 };
 
@@ -15,13 +20,26 @@ use self::{
     instance_iterator::InstanceIterator,
 };
 
+pub mod call_graph;
 pub mod const_expressions;
 mod instance_iterator;
+pub mod namespaces;
 #[cfg(test)]
 mod tests;
 pub mod visitor;
 
+/// a property of a class or function block with the methods accessing it
 #[derive(Debug, PartialEq, Clone)]
+pub struct PropertyIndexEntry<'idx> {
+    /// the property's datatype
+    pub data_type_name: &'idx str,
+    /// the method reading the property (GET)
+    pub getter: Option<&'idx ImplementationIndexEntry>,
+    /// the method writing the property (SET)
+    pub setter: Option<&'idx ImplementationIndexEntry>,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct VariableIndexEntry {
     /// the name of this variable (e.g. 'x' for 'PLC_PRG.x')
     name: String,
@@ -39,13 +57,25 @@ pub struct VariableIndexEntry {
     location_in_parent: u32,
     /// Wether the variable is externally or internally available
     linkage: LinkageType,
+    /// Wether the variable's value survives a restart
+    retention: RetentionType,
     /// A binding to a hardware or external location
     binding: Option<HardwareBinding>,
     /// the location in the original source-file
     pub source_location: SourceRange,
 }
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum RetentionType {
+    /// The variable is re-initialized on every start
+    NonRetain,
+    /// The variable keeps its value across a warm restart (`VAR RETAIN`)
+    Retain,
+    /// The variable keeps its value across a cold restart (`VAR PERSISTENT`)
+    Persistent,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
 pub struct HardwareBinding {
     /// Specifies if the binding is an In/Out or Memory binding
     pub direction: HardwareAccessType,
@@ -86,6 +116,34 @@ impl HardwareBinding {
             None
         }
     }
+
+    /// returns the name of the symbol this binding is resolved to (e.g. `__PI_X_1_2` for `%IX1.2`)
+    /// or None if the address is not fully specified (e.g. `%I*`) or not a literal
+    pub fn get_symbol_name(&self, const_expressions: &ConstExpressions) -> Option<String> {
+        let direction = match self.direction {
+            HardwareAccessType::Input => "I",
+            HardwareAccessType::Output => "Q",
+            HardwareAccessType::Memory => "M",
+        };
+        let size = match self.access {
+            DirectAccessType::Bit => "X",
+            DirectAccessType::Byte => "B",
+            DirectAccessType::Word => "W",
+            DirectAccessType::DWord => "D",
+            DirectAccessType::Template => return None,
+        };
+        let address = self
+            .entries
+            .iter()
+            .map(|it| {
+                const_expressions
+                    .get_constant_int_statement_value(it)
+                    .map(|it| it.to_string())
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .ok()?;
+        Some(format!("__P{}_{}_{}", direction, size, address.join("_")))
+    }
 }
 
 #[derive(Debug)]
@@ -116,6 +174,7 @@ impl VariableIndexEntry {
             data_type_name: data_type_name.to_string(),
             location_in_parent,
             linkage: LinkageType::Internal,
+            retention: RetentionType::NonRetain,
             binding: None,
             source_location,
         }
@@ -136,6 +195,7 @@ impl VariableIndexEntry {
             data_type_name: data_type_name.to_string(),
             location_in_parent: 0,
             linkage: LinkageType::Internal,
+            retention: RetentionType::NonRetain,
             binding: None,
             source_location,
         }
@@ -156,6 +216,11 @@ impl VariableIndexEntry {
         self
     }
 
+    pub fn set_retention(mut self, retention: RetentionType) -> Self {
+        self.retention = retention;
+        self
+    }
+
     pub fn set_hardware_binding(mut self, binding: Option<HardwareBinding>) -> Self {
         self.binding = binding;
         self
@@ -212,6 +277,10 @@ impl VariableIndexEntry {
         self.linkage == LinkageType::External
     }
 
+    pub fn get_retention(&self) -> RetentionType {
+        self.retention
+    }
+
     pub fn get_variable_type(&self) -> VariableType {
         self.variable_type
     }
@@ -233,7 +302,7 @@ impl VariableIndexEntry {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub enum VariableType {
     Local,
     Temp,
@@ -267,22 +336,25 @@ pub enum DataTypeType {
     AliasType,     // a Custom-Alias-dataType
 }
 
-#[derive(Clone, PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 pub enum ImplementationType {
     Program,
     Function,
     FunctionBlock,
     Action,
     Class,
+    Interface,
     Method,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ImplementationIndexEntry {
     pub(crate) call_name: String,
     pub(crate) type_name: String,
     pub(crate) associated_class: Option<String>,
     pub(crate) implementation_type: ImplementationType,
+    pub(crate) access: Option<AccessModifier>,
+    pub(crate) linkage: LinkageType,
 }
 
 impl ImplementationIndexEntry {
@@ -298,6 +370,30 @@ impl ImplementationIndexEntry {
     pub fn get_implementation_type(&self) -> &ImplementationType {
         &self.implementation_type
     }
+    pub fn get_access(&self) -> Option<AccessModifier> {
+        self.access
+    }
+    pub fn get_linkage(&self) -> LinkageType {
+        self.linkage
+    }
+
+    /// returns whether this implementation is called with its parameters instead of an
+    /// instance of its parameter-struct (see `LinkageType::Foreign`)
+    pub fn is_foreign(&self) -> bool {
+        self.linkage == LinkageType::Foreign
+    }
+
+    /// returns the name of the instance available as `THIS` inside this implementation,
+    /// which is the owning class of a method or the function block (or class) itself
+    pub fn get_instance_type_name(&self) -> Option<&str> {
+        match self.implementation_type {
+            ImplementationType::Method => self.associated_class.as_deref(),
+            ImplementationType::FunctionBlock | ImplementationType::Class => {
+                Some(self.type_name.as_str())
+            }
+            _ => None,
+        }
+    }
 }
 
 impl From<&Implementation> for ImplementationIndexEntry {
@@ -308,10 +404,78 @@ impl From<&Implementation> for ImplementationIndexEntry {
             type_name: implementation.type_name.clone(),
             associated_class: pou_type.get_optional_owner_class(),
             implementation_type: pou_type.into(),
+            access: implementation.access,
+            linkage: implementation.linkage,
         }
     }
 }
 
+/// a task of a configuration's resource scheduling the program instances associated with it
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TaskIndexEntry {
+    pub(crate) name: String,
+    /// the qualified name of the resource declaring the task (e.g. `plc.cpu`)
+    pub(crate) resource: String,
+    /// the cycle time of a periodic task
+    pub(crate) interval: Option<ConstId>,
+    pub(crate) priority: Option<ConstId>,
+    /// the global variable triggering an event task
+    pub(crate) single: Option<String>,
+    /// the names of the program instances scheduled by this task
+    pub(crate) programs: Vec<String>,
+    pub(crate) source_location: SourceRange,
+}
+
+impl TaskIndexEntry {
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+    pub fn get_resource(&self) -> &str {
+        &self.resource
+    }
+    pub fn get_interval(&self) -> Option<&ConstId> {
+        self.interval.as_ref()
+    }
+    pub fn get_priority(&self) -> Option<&ConstId> {
+        self.priority.as_ref()
+    }
+    pub fn get_single(&self) -> Option<&str> {
+        self.single.as_deref()
+    }
+    pub fn get_programs(&self) -> &[String] {
+        &self.programs
+    }
+    pub fn get_location(&self) -> &SourceRange {
+        &self.source_location
+    }
+}
+
+/// an address assigned to an instance variable by a configuration's `VAR_CONFIG` block
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConfigVariableIndexEntry {
+    /// the path to the instance variable (e.g. `prg.inst.input`)
+    pub(crate) reference: String,
+    /// the declared type of the instance variable
+    pub(crate) data_type_name: String,
+    pub(crate) binding: HardwareBinding,
+    pub(crate) source_location: SourceRange,
+}
+
+impl ConfigVariableIndexEntry {
+    pub fn get_reference(&self) -> &str {
+        &self.reference
+    }
+    pub fn get_type_name(&self) -> &str {
+        &self.data_type_name
+    }
+    pub fn get_binding(&self) -> &HardwareBinding {
+        &self.binding
+    }
+    pub fn get_location(&self) -> &SourceRange {
+        &self.source_location
+    }
+}
+
 impl From<&PouType> for ImplementationType {
     fn from(it: &PouType) -> Self {
         match it {
@@ -320,6 +484,7 @@ impl From<&PouType> for ImplementationType {
             PouType::FunctionBlock => ImplementationType::FunctionBlock,
             PouType::Action => ImplementationType::Action,
             PouType::Class => ImplementationType::Class,
+            PouType::Interface => ImplementationType::Interface,
             PouType::Method { .. } => ImplementationType::Method,
         }
     }
@@ -328,7 +493,7 @@ impl From<&PouType> for ImplementationType {
 /// the TypeIndex carries all types.
 /// it is extracted into its seaprate struct so it can be
 /// internally borrowed individually from the other maps
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct TypeIndex {
     /// all types (structs, enums, type, POUs, etc.)
     types: IndexMap<String, DataType>,
@@ -385,21 +550,26 @@ impl TypeIndex {
         &'ret self,
         data_type: &'ret DataType,
     ) -> Option<&'ret DataType> {
-        match data_type.get_type_information() {
-            DataTypeInformation::Alias {
-                referenced_type, ..
-            } => self
-                .find_type(referenced_type)
-                .and_then(|it| self.find_effective_type(it)),
-            _ => Some(data_type),
+        let mut visited = HashSet::new();
+        let mut effective_type = data_type;
+        while let DataTypeInformation::Alias {
+            referenced_type, ..
+        } = effective_type.get_type_information()
+        {
+            //a cyclic alias has no effective type
+            if !visited.insert(effective_type.get_name()) {
+                return None;
+            }
+            effective_type = self.find_type(referenced_type)?;
         }
+        Some(effective_type)
     }
 }
 
 /// The global index of the rusty-compiler
 ///
 /// The index contains information about all referencable elements.
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Index {
     /// all global variables
     global_variables: IndexMap<String, VariableIndexEntry>,
@@ -419,6 +589,21 @@ pub struct Index {
     /// all implementations
     implementations: IndexMap<String, ImplementationIndexEntry>,
 
+    /// the interfaces implemented by a class or function block, grouped by the POU's name
+    implemented_interfaces: IndexMap<String, Vec<String>>,
+
+    /// the class or function block extended by a class or function block, grouped by the POU's name
+    super_classes: IndexMap<String, String>,
+
+    /// all tasks of the configurations' resources, by their qualified name (e.g. `plc.cpu.fast`)
+    tasks: IndexMap<String, TaskIndexEntry>,
+
+    /// all addresses assigned to instance variables by the configurations' `VAR_CONFIG` blocks
+    config_variables: Vec<ConfigVariableIndexEntry>,
+
+    /// the attributes declared by pragmas, by the qualified name of the declaration (e.g. `PLC_PRG.x`)
+    attributes: IndexMap<String, Vec<Attribute>>,
+
     /// an index with all type-information
     type_index: TypeIndex,
 
@@ -499,6 +684,32 @@ impl Index {
 
         //implementations
         self.implementations.extend(other.implementations);
+        self.implemented_interfaces
+            .extend(other.implemented_interfaces);
+        self.super_classes.extend(other.super_classes);
+
+        //tasks
+        for (name, mut e) in other.tasks.drain(..) {
+            e.interval = self.maybe_import_const_expr(&mut other.constant_expressions, &e.interval);
+            e.priority = self.maybe_import_const_expr(&mut other.constant_expressions, &e.priority);
+            self.tasks.insert(name, e);
+        }
+
+        //config variables
+        for mut e in other.config_variables.drain(..) {
+            e.binding.entries = e
+                .binding
+                .entries
+                .iter()
+                .filter_map(|it| {
+                    self.maybe_import_const_expr(&mut other.constant_expressions, &Some(*it))
+                })
+                .collect();
+            self.config_variables.push(e);
+        }
+
+        //attributes
+        self.attributes.extend(other.attributes);
 
         //Constant expressions are intentionally not imported
         // self.constant_expressions.import(other.constant_expressions)
@@ -583,6 +794,16 @@ impl Index {
         self.member_variables
             .get(&container_name.to_lowercase())
             .and_then(|map| map.get(&variable_name.to_lowercase()))
+            .or_else(|| {
+                //check inherited members
+                self.get_super_classes(container_name)
+                    .into_iter()
+                    .find_map(|base| {
+                        self.member_variables
+                            .get(&base.to_lowercase())
+                            .and_then(|map| map.get(&variable_name.to_lowercase()))
+                    })
+            })
             .or_else(|| {
                 //check qualifier
                 container_name
@@ -598,6 +819,13 @@ impl Index {
         &self,
         fully_qualified_name: &str,
     ) -> Option<&VariableIndexEntry> {
+        //the container's name may be qualified by its namespace (e.g. "Lib.PLC_PRG.x")
+        let member = fully_qualified_name
+            .rsplit_once('.')
+            .and_then(|(container, name)| self.find_member(container, name));
+        if member.is_some() {
+            return member;
+        }
         let segments: Vec<&str> = fully_qualified_name.split('.').collect();
         let (q, segments) = if segments.len() > 1 {
             (
@@ -610,6 +838,17 @@ impl Index {
         self.find_variable(q, &segments[..])
     }
 
+    /// returns the variable addressed by the given instance path (e.g. `prg.inst.input`)
+    /// the path starts with a global variable or the name of a program
+    pub fn find_instance_variable(&self, path: &str) -> Option<&VariableIndexEntry> {
+        let mut segments = path.split('.');
+        let mut result = self.find_global_variable(segments.next()?)?;
+        for segment in segments {
+            result = self.find_member(&result.data_type_name, segment)?;
+        }
+        Some(result)
+    }
+
     pub fn find_variable(
         &self,
         context: Option<&str>,
@@ -710,16 +949,21 @@ impl Index {
     /// returns the real type behind aliases and subRanges (while effective_types will only
     /// resolve aliases)
     pub fn get_intrinsic_type_by_name(&self, type_name: &str) -> &DataType {
-        let effective_type = self.type_index.get_effective_type_by_name(type_name);
-
-        match effective_type.get_type_information() {
-            DataTypeInformation::SubRange {
-                referenced_type, ..
-            } => self.get_intrinsic_type_by_name(referenced_type.as_str()),
-            DataTypeInformation::Enum {
-                referenced_type, ..
-            } => self.get_intrinsic_type_by_name(referenced_type),
-            _ => effective_type,
+        //remember the visited types to stop at cyclic definitions (reported by the validator)
+        let mut visited = HashSet::new();
+        let mut effective_type = self.type_index.get_effective_type_by_name(type_name);
+        loop {
+            match effective_type.get_type_information() {
+                DataTypeInformation::SubRange {
+                    referenced_type, ..
+                }
+                | DataTypeInformation::Enum {
+                    referenced_type, ..
+                } if visited.insert(effective_type.get_name()) => {
+                    effective_type = self.type_index.get_effective_type_by_name(referenced_type)
+                }
+                _ => return effective_type,
+            }
         }
     }
 
@@ -769,6 +1013,19 @@ impl Index {
         &self.global_variables
     }
 
+    /// returns true if any global or member variable, or the inner type of any array, is of the given type
+    pub fn is_type_referenced(&self, type_name: &str) -> bool {
+        let is_type = |name: &str| name.eq_ignore_ascii_case(type_name);
+        self.global_variables
+            .values()
+            .chain(self.member_variables.values().flat_map(|it| it.values()))
+            .any(|it| is_type(it.get_type_name()))
+            || self.type_index.types.values().any(|it| {
+                matches!(it.get_type_information(),
+                    DataTypeInformation::Array { inner_type_name, .. } if is_type(inner_type_name))
+            })
+    }
+
     pub fn get_global_initializers(&self) -> &IndexMap<String, VariableIndexEntry> {
         &self.global_initializers
     }
@@ -785,12 +1042,252 @@ impl Index {
         &self.implementations
     }
 
+    /// registers the interfaces the given class or function block implements
+    pub fn register_implemented_interfaces(&mut self, pou_name: &str, interfaces: Vec<String>) {
+        self.implemented_interfaces
+            .insert(pou_name.to_lowercase(), interfaces);
+    }
+
+    /// returns the interfaces the given class or function block implements
+    pub fn get_implemented_interfaces(&self, pou_name: &str) -> &[String] {
+        self.implemented_interfaces
+            .get(&pou_name.to_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// returns the interfaces the given class or function block and its super classes implement
+    pub fn get_all_implemented_interfaces(&self, pou_name: &str) -> Vec<&String> {
+        std::iter::once(pou_name)
+            .chain(self.get_super_classes(pou_name))
+            .flat_map(|it| self.get_implemented_interfaces(it))
+            .collect()
+    }
+
+    /// returns true if the given pou (or one of its super classes) implements the given interface
+    pub fn implements_interface(&self, pou_name: &str, interface_name: &str) -> bool {
+        self.get_all_implemented_interfaces(pou_name)
+            .iter()
+            .any(|it| it.eq_ignore_ascii_case(interface_name))
+    }
+
+    /// returns true if the given type is an INTERFACE
+    pub fn is_interface(&self, type_name: &str) -> bool {
+        matches!(
+            self.type_index
+                .find_pou_type(type_name)
+                .map(DataType::get_type_information),
+            Some(DataTypeInformation::Struct {
+                source: StructSource::Pou(PouType::Interface),
+                ..
+            })
+        )
+    }
+
+    /// returns the methods declared by the given interface in the order of their declaration
+    pub fn get_interface_methods(&self, interface_name: &str) -> Vec<&ImplementationIndexEntry> {
+        self.implementations
+            .values()
+            .filter(|it| {
+                it.get_associated_class_name()
+                    .map(|class| class.eq_ignore_ascii_case(interface_name))
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    pub fn register_super_class(&mut self, pou_name: &str, super_class: &str) {
+        self.super_classes
+            .insert(pou_name.to_lowercase(), super_class.to_string());
+    }
+
+    /// returns the class or function block the given pou directly extends
+    pub fn find_super_class(&self, pou_name: &str) -> Option<&str> {
+        self.super_classes
+            .get(&pou_name.to_lowercase())
+            .map(String::as_str)
+    }
+
+    /// returns all classes or function blocks the given pou extends, starting with the direct
+    /// super class. The chain stops at the first type that appears twice (cyclic inheritance)
+    pub fn get_super_classes(&self, pou_name: &str) -> Vec<&str> {
+        let mut super_classes: Vec<&str> = vec![];
+        let mut current = self.find_super_class(pou_name);
+        while let Some(super_class) = current {
+            if super_class.eq_ignore_ascii_case(pou_name)
+                || super_classes
+                    .iter()
+                    .any(|it| it.eq_ignore_ascii_case(super_class))
+            {
+                break;
+            }
+            super_classes.push(super_class);
+            current = self.find_super_class(super_class);
+        }
+        super_classes
+    }
+
+    /// returns true if the given pou (indirectly) extends itself
+    pub fn has_cyclic_inheritance(&self, pou_name: &str) -> bool {
+        //the chain of super classes only stops early on a cycle
+        self.get_super_classes(pou_name)
+            .last()
+            .and_then(|it| self.find_super_class(it))
+            .is_some()
+    }
+
+    /// returns the chain of types that embed the given struct, function block, class or
+    /// program back into itself by value (e.g. `A -> B -> A`), or `None` if every path
+    /// back to the given type passes through a pointer
+    pub fn find_cyclic_struct_definition<'idx>(
+        &'idx self,
+        type_name: &'idx str,
+    ) -> Option<Vec<&'idx str>> {
+        let mut visited = HashSet::new();
+        self.find_embedding_path(type_name, type_name, &mut visited)
+            .map(|path| std::iter::once(type_name).chain(path).collect())
+    }
+
+    /// returns the path of struct types from the given type to the `target` type, arrays and
+    /// aliases in between are followed but do not show up in the path
+    fn find_embedding_path<'idx>(
+        &'idx self,
+        target: &str,
+        current: &str,
+        visited: &mut HashSet<String>,
+    ) -> Option<Vec<&'idx str>> {
+        for embedded in self.get_embedded_types(current) {
+            let is_target = embedded.eq_ignore_ascii_case(target);
+            let path = if is_target {
+                Some(vec![])
+            } else if visited.insert(embedded.to_lowercase()) {
+                self.find_embedding_path(target, embedded, visited)
+            } else {
+                None
+            };
+            if let Some(mut path) = path {
+                let is_struct = self
+                    .find_effective_type_info(embedded)
+                    .map(DataTypeInformation::is_struct)
+                    .unwrap_or(false);
+                if is_target || is_struct {
+                    path.insert(0, embedded);
+                }
+                return Some(path);
+            }
+        }
+        None
+    }
+
+    /// returns the names of the types that are stored by value inside of the given type
+    fn get_embedded_types(&self, type_name: &str) -> Vec<&str> {
+        match self
+            .find_type(type_name)
+            .map(DataType::get_type_information)
+        {
+            Some(DataTypeInformation::Struct { name, .. }) => self
+                .get_container_members(name)
+                .into_iter()
+                .filter(|it| !it.is_temp() && !it.is_return())
+                //cyclic inheritance is reported on its own
+                .filter(|it| {
+                    it.get_name() != typesystem::BASE_MEMBER || !self.has_cyclic_inheritance(name)
+                })
+                .map(VariableIndexEntry::get_type_name)
+                .collect(),
+            Some(DataTypeInformation::Array {
+                inner_type_name, ..
+            }) => vec![inner_type_name.as_str()],
+            Some(DataTypeInformation::Alias {
+                referenced_type, ..
+            })
+            | Some(DataTypeInformation::SubRange {
+                referenced_type, ..
+            }) => vec![referenced_type.as_str()],
+            _ => vec![],
+        }
+    }
+
+    /// returns true if the given pou extends another pou or is extended by another pou.
+    /// Instances of polymorphic pous carry a vtable to dispatch their methods
+    pub fn is_polymorphic(&self, pou_name: &str) -> bool {
+        self.find_super_class(pou_name).is_some()
+            || self
+                .super_classes
+                .values()
+                .any(|it| it.eq_ignore_ascii_case(pou_name))
+    }
+
+    /// returns the method (or action) with the given name of the given pou, if the pou does not
+    /// declare it, the method is looked up in its super classes
+    pub fn find_method(
+        &self,
+        pou_name: &str,
+        method_name: &str,
+    ) -> Option<&ImplementationIndexEntry> {
+        std::iter::once(pou_name)
+            .chain(self.get_super_classes(pou_name))
+            .find_map(|it| self.find_implementation(&format!("{}.{}", it, method_name)))
+    }
+
+    /// returns the type and the accessors of the property with the given name of the given
+    /// class or function block, the accessors may be inherited from its super classes
+    pub fn find_property(&self, pou_name: &str, property_name: &str) -> Option<PropertyIndexEntry> {
+        let getter = self.find_method(
+            pou_name,
+            &format!("{}{}", PROPERTY_GETTER_PREFIX, property_name),
+        );
+        let setter = self.find_method(
+            pou_name,
+            &format!("{}{}", PROPERTY_SETTER_PREFIX, property_name),
+        );
+        let data_type_name = getter
+            .and_then(|it| self.find_return_variable(it.get_call_name()))
+            .or_else(|| setter.and_then(|it| self.find_member(it.get_call_name(), property_name)))
+            .map(VariableIndexEntry::get_type_name)?;
+        Some(PropertyIndexEntry {
+            data_type_name,
+            getter,
+            setter,
+        })
+    }
+
+    /// returns the methods that can be overridden in the given class or function block in the
+    /// order of their slots in its vtable. Every method is represented by its first declaration
+    /// in the inheritance chain, the methods of the topmost super class come first
+    pub fn get_virtual_methods(&self, pou_name: &str) -> Vec<&ImplementationIndexEntry> {
+        let mut methods: Vec<&ImplementationIndexEntry> = vec![];
+        let mut classes = self.get_super_classes(pou_name);
+        classes.reverse();
+        classes.push(pou_name);
+        for class in classes {
+            for method in self.implementations.values().filter(|it| {
+                it.get_implementation_type() == &ImplementationType::Method
+                    && it
+                        .get_associated_class_name()
+                        .map(|owner| owner.eq_ignore_ascii_case(class))
+                        .unwrap_or(false)
+            }) {
+                let name = Pou::calc_return_name(method.get_call_name());
+                if !methods
+                    .iter()
+                    .any(|it| Pou::calc_return_name(it.get_call_name()).eq_ignore_ascii_case(name))
+                {
+                    methods.push(method);
+                }
+            }
+        }
+        methods
+    }
+
     pub fn register_implementation(
         &mut self,
         call_name: &str,
         type_name: &str,
         associated_class_name: Option<&String>,
         impl_type: ImplementationType,
+        access: Option<AccessModifier>,
+        linkage: LinkageType,
     ) {
         self.implementations.insert(
             call_name.to_lowercase(),
@@ -799,6 +1296,8 @@ impl Index {
                 type_name: type_name.into(),
                 associated_class: associated_class_name.map(|str| str.into()),
                 implementation_type: impl_type,
+                access,
+                linkage,
             },
         );
     }
@@ -880,11 +1379,99 @@ impl Index {
         self.global_variables.insert(name.to_lowercase(), variable);
     }
 
+    pub fn register_task(&mut self, task: TaskIndexEntry) {
+        self.tasks.insert(
+            format!("{}.{}", task.resource, task.name).to_lowercase(),
+            task,
+        );
+    }
+
+    /// returns all tasks of the configurations' resources
+    pub fn get_tasks(&self) -> &IndexMap<String, TaskIndexEntry> {
+        &self.tasks
+    }
+
+    pub fn register_config_variable(&mut self, variable: ConfigVariableIndexEntry) {
+        self.config_variables.push(variable);
+    }
+
+    /// returns all addresses assigned to instance variables by `VAR_CONFIG` blocks
+    pub fn get_config_variables(&self) -> &[ConfigVariableIndexEntry] {
+        &self.config_variables
+    }
+
+    /// registers the attributes of the declaration with the given qualified name
+    pub fn register_attributes(&mut self, qualified_name: &str, attributes: &[Attribute]) {
+        if !attributes.is_empty() {
+            self.attributes
+                .insert(qualified_name.to_lowercase(), attributes.to_vec());
+        }
+    }
+
+    /// returns the attributes of the declaration with the given qualified name
+    /// (e.g. `PLC_PRG` or `PLC_PRG.x`)
+    pub fn get_attributes(&self, qualified_name: &str) -> &[Attribute] {
+        self.attributes
+            .get(&qualified_name.to_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// returns the attribute with the given name of the declaration with the given qualified name
+    pub fn find_attribute(&self, qualified_name: &str, attribute: &str) -> Option<&Attribute> {
+        self.get_attributes(qualified_name)
+            .iter()
+            .find(|it| it.name.eq_ignore_ascii_case(attribute))
+    }
+
+    /// returns the task with the given name declared in the given resource (e.g. `plc.cpu`)
+    pub fn find_task(&self, resource: &str, name: &str) -> Option<&TaskIndexEntry> {
+        self.tasks
+            .get(&format!("{}.{}", resource, name).to_lowercase())
+    }
+
     pub fn register_global_initializer(&mut self, name: &str, variable: VariableIndexEntry) {
         self.global_initializers
             .insert(name.to_lowercase(), variable);
     }
 
+    /// declares the initializers of all structs and function blocks as external, for the
+    /// declarations of included files whose initializers are defined by their library
+    pub fn make_initializers_external(&mut self) {
+        for initializer in self.global_initializers.values_mut() {
+            initializer.linkage = LinkageType::External;
+        }
+    }
+
+    /// declares all implementations, global variables and initializers as external, for the
+    /// index of a precompiled library whose code is linked from its object
+    pub fn make_external(&mut self) {
+        self.make_initializers_external();
+        for variable in self.global_variables.values_mut() {
+            variable.linkage = LinkageType::External;
+        }
+        //foreign implementations keep their calling convention
+        for implementation in self.implementations.values_mut() {
+            if implementation.linkage == LinkageType::Internal {
+                implementation.linkage = LinkageType::External;
+            }
+        }
+    }
+
+    /// gives all expressions of this index new ids, so the expressions of a precompiled library
+    /// can be annotated together with the compiled sources
+    pub fn renew_ids(&mut self, id_provider: &IdProvider) {
+        for statement in self.constant_expressions.statements_mut() {
+            generics::renew_ids(statement, id_provider.clone());
+        }
+        for data_type in self.type_index.types.values_mut() {
+            if let DataTypeInformation::SubRange { sub_range, .. } = &mut data_type.information {
+                generics::renew_ids(&mut sub_range.start, id_provider.clone());
+                generics::renew_ids(&mut sub_range.end, id_provider.clone());
+            }
+        }
+    }
+
     pub fn register_type(&mut self, datatype: DataType) {
         self.type_index
             .types
@@ -925,26 +1512,34 @@ impl Index {
         &'idx self,
         initial_type: &'idx DataTypeInformation,
     ) -> &'idx DataTypeInformation {
-        match initial_type {
-            DataTypeInformation::SubRange { .. } | DataTypeInformation::Alias { .. } => {
-                let inner_type_name = match initial_type {
-                    DataTypeInformation::SubRange {
-                        referenced_type, ..
-                    } => referenced_type,
-                    _ => initial_type.get_name(),
-                };
-                if let Some(inner_type) = self.find_effective_type_info(inner_type_name) {
-                    self.find_intrinsic_type(inner_type)
-                } else {
-                    initial_type
+        //remember the visited types to stop at cyclic definitions (reported by the validator)
+        let mut visited = HashSet::new();
+        let mut current_type = initial_type;
+        loop {
+            match current_type {
+                DataTypeInformation::SubRange { .. } | DataTypeInformation::Alias { .. } => {
+                    let inner_type_name = match current_type {
+                        DataTypeInformation::SubRange {
+                            referenced_type, ..
+                        } => referenced_type,
+                        _ => current_type.get_name(),
+                    };
+                    match self.find_effective_type_info(inner_type_name) {
+                        Some(inner_type) if visited.insert(current_type.get_name()) => {
+                            current_type = inner_type
+                        }
+                        _ => return current_type,
+                    }
                 }
+                DataTypeInformation::Enum {
+                    referenced_type, ..
+                } => {
+                    return self
+                        .find_effective_type_info(referenced_type)
+                        .unwrap_or(current_type)
+                }
+                _ => return current_type,
             }
-            DataTypeInformation::Enum {
-                referenced_type, ..
-            } => self
-                .find_effective_type_info(referenced_type)
-                .unwrap_or(initial_type),
-            _ => initial_type,
         }
     }
 
@@ -0,0 +1,146 @@
+// Copyright (c) 2022 Ghaith Hachem and Mathias Rieder
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+
+use crate::{
+    ast::{AstStatement, CompilationUnit, SourceRange},
+    resolver::{AnnotationMap, AnnotationMapImpl},
+};
+
+use super::Index;
+
+/// a call of a POU
+#[derive(Debug, PartialEq)]
+pub struct Call {
+    /// the qualified name of the calling POU
+    pub caller: String,
+    /// the qualified name of the called POU
+    pub callee: String,
+    /// the location of the call
+    pub location: SourceRange,
+}
+
+/// a call that closes a recursion
+#[derive(Debug, PartialEq)]
+pub struct Recursion<'g> {
+    pub call: &'g Call,
+    /// the chain of calls starting and ending with the recursive POU (e.g. `[a, b, a]`)
+    pub path: Vec<&'g str>,
+}
+
+/// the calls between the POUs of the annotated units. A call is resolved to the called
+/// implementation like it is generated, methods called through an interface are resolved to the
+/// interface's method
+#[derive(Debug, Default)]
+pub struct CallGraph {
+    /// the calls of every POU by the POU's lower case qualified name
+    calls: IndexMap<String, Vec<Call>>,
+}
+
+impl CallGraph {
+    /// collects the calls of the implementations of the given units
+    pub fn new<'u>(
+        units: impl IntoIterator<Item = &'u CompilationUnit>,
+        annotations: &AnnotationMapImpl,
+        index: &Index,
+    ) -> CallGraph {
+        let mut call_graph = CallGraph::default();
+        for implementation in units.into_iter().flat_map(|it| it.implementations.iter()) {
+            let mut calls = vec![];
+            for statement in &implementation.statements {
+                collect_calls(
+                    &implementation.name,
+                    statement,
+                    annotations,
+                    index,
+                    &mut calls,
+                );
+            }
+            call_graph
+                .calls
+                .entry(implementation.name.to_lowercase())
+                .or_default()
+                .extend(calls);
+        }
+        call_graph
+    }
+
+    /// returns the calls of the POU with the given qualified name
+    pub fn get_calls(&self, pou: &str) -> &[Call] {
+        self.calls
+            .get(&pou.to_lowercase())
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// returns the direct (`a -> a`) and indirect (`a -> b -> a`) recursions of the POUs.
+    /// Every POU calling itself through a chain of calls is part of at least one of the returned
+    /// recursions
+    pub fn find_recursions(&self) -> Vec<Recursion> {
+        let mut recursions = vec![];
+        let mut visited = HashSet::new();
+        for calls in self.calls.values() {
+            if let Some(call) = calls.first() {
+                self.visit(&call.caller, &mut visited, &mut vec![], &mut recursions);
+            }
+        }
+        recursions
+    }
+
+    /// follows the calls of the given POU depth-first, a call of a POU on the `stack` of calls
+    /// leading to it closes a recursion
+    fn visit<'g>(
+        &'g self,
+        pou: &'g str,
+        visited: &mut HashSet<String>,
+        stack: &mut Vec<&'g str>,
+        recursions: &mut Vec<Recursion<'g>>,
+    ) {
+        if !visited.insert(pou.to_lowercase()) {
+            return;
+        }
+        stack.push(pou);
+        for call in self.get_calls(pou) {
+            if let Some(position) = stack
+                .iter()
+                .position(|it| it.eq_ignore_ascii_case(&call.callee))
+            {
+                let mut path = stack[position..].to_vec();
+                path.push(call.callee.as_str());
+                recursions.push(Recursion { call, path });
+            } else {
+                self.visit(&call.callee, visited, stack, recursions);
+            }
+        }
+        stack.pop();
+    }
+}
+
+/// collects the calls of the given statement and its children
+fn collect_calls(
+    caller: &str,
+    statement: &AstStatement,
+    annotations: &AnnotationMapImpl,
+    index: &Index,
+    calls: &mut Vec<Call>,
+) {
+    if let AstStatement::CallStatement {
+        operator, location, ..
+    } = statement
+    {
+        if let Some(implementation) = annotations
+            .get_call_name(operator)
+            .and_then(|it| index.find_implementation(it))
+        {
+            calls.push(Call {
+                caller: caller.to_string(),
+                callee: implementation.get_call_name().to_string(),
+                location: location.clone(),
+            });
+        }
+    }
+    for child in statement.get_children() {
+        collect_calls(caller, child, annotations, index, calls);
+    }
+}
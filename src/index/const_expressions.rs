@@ -2,13 +2,14 @@
 
 use crate::ast::AstStatement;
 use generational_arena::{Arena, Iter};
+use serde::{Deserialize, Serialize};
 
 pub type ConstId = generational_arena::Index;
 
 /// wrapper around ConstExpression stored in the arena
 /// changing expr allows to change the referenced const-expression
 /// without aquiring a new ID in the arena
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 struct ConstWrapper {
     /// the constant expression
     expr: ConstExpression,
@@ -24,12 +25,20 @@ impl ConstWrapper {
             ConstExpression::Unresolvable { statement, .. } => statement,
         }
     }
+
+    pub fn get_statement_mut(&mut self) -> &mut AstStatement {
+        match &mut self.expr {
+            ConstExpression::Unresolved { statement, .. } => statement,
+            ConstExpression::Resolved(statement) => statement,
+            ConstExpression::Unresolvable { statement, .. } => statement,
+        }
+    }
 }
 
 /// constant expressions registered here are wrapped behind this enum to indicate
 /// whether this expression was already (potentially) resolved or not, or if a
 /// resolving failed.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub enum ConstExpression {
     Unresolved {
         statement: AstStatement,
@@ -65,7 +74,7 @@ impl ConstExpression {
     }
 }
 
-#[derive(Default, Debug)]
+#[derive(Default, Debug, Serialize, Deserialize)]
 pub struct ConstExpressions {
     expressions: Arena<ConstWrapper>,
 }
@@ -203,6 +212,13 @@ impl ConstExpressions {
             })
     }
 
+    /// returns all expressions for modification (e.g. to give them new ids)
+    pub fn statements_mut(&mut self) -> impl Iterator<Item = &mut AstStatement> {
+        self.expressions
+            .iter_mut()
+            .map(|(_, it)| it.get_statement_mut())
+    }
+
     pub fn import(&mut self, other: ConstExpressions) {
         self.expressions.extend(other.expressions)
     }
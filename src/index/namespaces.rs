@@ -0,0 +1,455 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+
+use crate::ast::{
+    AstStatement, CompilationUnit, DataType, DataTypeDeclaration, Pou, Variable, VariableBlockType,
+};
+
+/// the hierarchy of the declared namespaces with the POUs, types and global variables declared
+/// in every namespace. It is used to qualify the names referencing namespaced declarations
+/// before the units are indexed.
+#[derive(Debug, Default)]
+pub struct NamespaceIndex {
+    /// the nested namespaces by their lowercase name
+    namespaces: IndexMap<String, NamespaceIndex>,
+    /// the qualified names of the declarations in this namespace by their lowercase name
+    declarations: IndexMap<String, String>,
+}
+
+/// qualifies all references to namespaced POUs, types and global variables in the given units.
+/// A name is resolved in the namespaces enclosing the declaration using it (from the innermost
+/// outwards), then in the namespaces it is `USING` and finally in the global namespace.
+pub fn resolve_namespaces(units: &mut [&mut CompilationUnit]) {
+    let index = NamespaceIndex::new(units);
+    //units without namespaces do not need to be touched
+    if index.namespaces.is_empty() {
+        return;
+    }
+    for unit in units.iter_mut() {
+        index.resolve_unit(unit);
+    }
+}
+
+impl NamespaceIndex {
+    /// creates the index of the namespaces and declarations of the given units
+    pub fn new(units: &[&mut CompilationUnit]) -> NamespaceIndex {
+        let mut index = NamespaceIndex::default();
+        for namespace in units.iter().flat_map(|it| it.namespaces.iter()) {
+            namespace.split('.').fold(&mut index, |parent, segment| {
+                parent.namespaces.entry(segment.to_lowercase()).or_default()
+            });
+        }
+
+        for unit in units.iter() {
+            //methods and actions are not referenced through their namespace
+            let pous = unit
+                .units
+                .iter()
+                .filter(|it| it.pou_type.get_optional_owner_class().is_none())
+                .map(|it| it.name.as_str());
+            let types = unit.types.iter().filter_map(|it| it.data_type.get_name());
+            let globals = unit
+                .global_vars
+                .iter()
+                .flat_map(|it| it.variables.iter())
+                .map(|it| it.name.as_str());
+            for name in pous.chain(types).chain(globals) {
+                index.register_declaration(name);
+            }
+        }
+        index
+    }
+
+    /// registers the declaration with the given qualified name in its namespace
+    fn register_declaration(&mut self, qualified_name: &str) {
+        let mut namespace = self;
+        let mut segments = qualified_name.split('.').peekable();
+        while let Some(segment) = segments.next() {
+            if segments.peek().is_none() {
+                namespace
+                    .declarations
+                    .insert(segment.to_lowercase(), qualified_name.to_string());
+                return;
+            }
+            match namespace.namespaces.get_mut(&segment.to_lowercase()) {
+                Some(nested) => namespace = nested,
+                //a name qualified by something else than a namespace
+                None => return,
+            }
+        }
+    }
+
+    /// returns the qualified name of the declaration with the given name, the name
+    /// is qualified by the namespaces (e.g. `Lib.Sub.MyFb`) starting from this namespace
+    fn find_declaration(&self, name: &str) -> Option<&str> {
+        let segments = name.split('.').collect::<Vec<_>>();
+        let (last, namespaces) = segments.split_last()?;
+        namespaces
+            .iter()
+            .try_fold(self, |namespace, segment| {
+                namespace.namespaces.get(&segment.to_lowercase())
+            })?
+            .declarations
+            .get(&last.to_lowercase())
+            .map(String::as_str)
+    }
+
+    /// returns the qualified name of the declaration referenced by `name` in a declaration
+    /// with the given lookup scopes or None if the name does not reference a namespaced
+    /// declaration
+    pub fn resolve(&self, name: &str, lookup_scopes: &[String]) -> Option<&str> {
+        lookup_scopes
+            .iter()
+            .find_map(|scope| self.find_declaration(&format!("{}.{}", scope, name)))
+            .or_else(|| {
+                //an unqualified name in the global namespace needs no qualification
+                if name.contains('.') {
+                    self.find_declaration(name)
+                } else {
+                    None
+                }
+            })
+    }
+
+    fn resolve_unit(&self, unit: &mut CompilationUnit) {
+        let lookup_scopes = std::mem::take(&mut unit.lookup_scopes);
+
+        for pou in unit.units.iter_mut() {
+            let scopes = get_lookup_scopes(&lookup_scopes, &pou.name);
+            for name in pou.super_class.iter_mut().chain(pou.interfaces.iter_mut()) {
+                self.resolve_name(name, scopes);
+            }
+            if let Some(return_type) = pou.return_type.as_mut() {
+                self.resolve_data_type_declaration(return_type, scopes);
+            }
+            for block in pou.variable_blocks.iter_mut() {
+                let is_external = block.variable_block_type == VariableBlockType::External;
+                for variable in block.variables.iter_mut() {
+                    self.resolve_variable(variable, scopes);
+                    //external variables name the global variable they reference
+                    if is_external {
+                        self.resolve_name(&mut variable.name, scopes);
+                    }
+                }
+            }
+        }
+
+        for implementation in unit.implementations.iter_mut() {
+            let scopes = get_lookup_scopes(&lookup_scopes, &implementation.type_name);
+            let locals = get_local_names(&unit.units, &implementation.type_name);
+            for statement in implementation.statements.iter_mut() {
+                self.resolve_statement(statement, scopes, &locals);
+            }
+        }
+
+        for variable in unit
+            .global_vars
+            .iter_mut()
+            .flat_map(|it| it.variables.iter_mut())
+        {
+            let scopes = get_lookup_scopes(&lookup_scopes, &variable.name);
+            self.resolve_variable(variable, scopes);
+        }
+
+        for user_type in unit.types.iter_mut() {
+            let scopes = get_lookup_scopes(
+                &lookup_scopes,
+                user_type.data_type.get_name().unwrap_or_default(),
+            );
+            self.resolve_data_type(&mut user_type.data_type, scopes);
+            if let Some(initializer) = user_type.initializer.as_mut() {
+                self.resolve_statement(initializer, scopes, &HashSet::new());
+            }
+        }
+        unit.lookup_scopes = lookup_scopes;
+    }
+
+    fn resolve_name(&self, name: &mut String, scopes: &[String]) {
+        if let Some(qualified_name) = self.resolve(name, scopes) {
+            *name = qualified_name.to_string();
+        }
+    }
+
+    fn resolve_variable(&self, variable: &mut Variable, scopes: &[String]) {
+        self.resolve_data_type_declaration(&mut variable.data_type, scopes);
+        if let Some(initializer) = variable.initializer.as_mut() {
+            self.resolve_statement(initializer, scopes, &HashSet::new());
+        }
+    }
+
+    fn resolve_data_type_declaration(
+        &self,
+        declaration: &mut DataTypeDeclaration,
+        scopes: &[String],
+    ) {
+        match declaration {
+            DataTypeDeclaration::DataTypeReference {
+                referenced_type, ..
+            } => self.resolve_name(referenced_type, scopes),
+            DataTypeDeclaration::DataTypeDefinition { data_type, .. } => {
+                self.resolve_data_type(data_type, scopes)
+            }
+        }
+    }
+
+    fn resolve_data_type(&self, data_type: &mut DataType, scopes: &[String]) {
+        match data_type {
+            DataType::StructType { variables, .. } | DataType::UnionType { variables, .. } => {
+                for variable in variables.iter_mut() {
+                    self.resolve_variable(variable, scopes);
+                }
+            }
+            DataType::EnumType { numeric_type, .. } => self.resolve_name(numeric_type, scopes),
+            DataType::SubRangeType {
+                referenced_type, ..
+            } => self.resolve_name(referenced_type, scopes),
+            DataType::ArrayType {
+                bounds,
+                referenced_type,
+                ..
+            } => {
+                self.resolve_statement(bounds, scopes, &HashSet::new());
+                self.resolve_data_type_declaration(referenced_type, scopes);
+            }
+            DataType::PointerType {
+                referenced_type, ..
+            }
+            | DataType::VarArgs {
+                referenced_type: Some(referenced_type),
+            } => self.resolve_data_type_declaration(referenced_type, scopes),
+            _ => {}
+        }
+    }
+
+    /// qualifies the references to namespaced declarations in the given statement,
+    /// references to the given local names are left untouched
+    fn resolve_statement(
+        &self,
+        statement: &mut AstStatement,
+        scopes: &[String],
+        locals: &HashSet<String>,
+    ) {
+        match statement {
+            AstStatement::Reference { name, .. } => {
+                if !locals.contains(&name.to_lowercase()) {
+                    self.resolve_name(name, scopes);
+                }
+            }
+            AstStatement::QualifiedReference { elements, .. } => {
+                self.merge_namespace_qualifiers(elements, scopes, locals);
+                if let Some(head) = elements.first_mut() {
+                    self.resolve_statement(head, scopes, locals);
+                }
+                //only the expressions in the accessors of members may reference declarations
+                for element in elements.iter_mut().skip(1) {
+                    if let AstStatement::ArrayAccess { access, .. } = element {
+                        self.resolve_statement(access, scopes, locals);
+                    }
+                }
+                let single_element = (elements.len() == 1).then(|| elements.remove(0));
+                if let Some(element) = single_element {
+                    *statement = element;
+                }
+            }
+            AstStatement::CallStatement {
+                operator,
+                parameters,
+                ..
+            } => {
+                self.resolve_statement(operator, scopes, locals);
+                if let Some(parameters) = parameters.as_mut() {
+                    self.resolve_parameters(parameters, scopes, locals);
+                }
+            }
+            AstStatement::ArrayAccess {
+                reference, access, ..
+            } => {
+                self.resolve_statement(reference, scopes, locals);
+                self.resolve_statement(access, scopes, locals);
+            }
+            AstStatement::PointerAccess { reference, .. } => {
+                self.resolve_statement(reference, scopes, locals)
+            }
+            AstStatement::BinaryExpression { left, right, .. }
+            | AstStatement::Assignment { left, right, .. }
+            | AstStatement::OutputAssignment { left, right, .. } => {
+                self.resolve_statement(left, scopes, locals);
+                self.resolve_statement(right, scopes, locals);
+            }
+            AstStatement::RangeStatement { start, end, .. } => {
+                self.resolve_statement(start, scopes, locals);
+                self.resolve_statement(end, scopes, locals);
+            }
+            AstStatement::UnaryExpression { value, .. } => {
+                self.resolve_statement(value, scopes, locals)
+            }
+            AstStatement::CastStatement { target, .. } => {
+                self.resolve_statement(target, scopes, locals)
+            }
+            AstStatement::MultipliedStatement { element, .. } => {
+                self.resolve_statement(element, scopes, locals)
+            }
+            AstStatement::CaseCondition { condition, .. } => {
+                self.resolve_statement(condition, scopes, locals)
+            }
+            AstStatement::LiteralArray {
+                elements: Some(elements),
+                ..
+            } => self.resolve_statement(elements, scopes, locals),
+            AstStatement::ExpressionList { expressions, .. } => {
+                self.resolve_statements(expressions, scopes, locals)
+            }
+            AstStatement::IfStatement {
+                blocks, else_block, ..
+            } => {
+                for block in blocks.iter_mut() {
+                    self.resolve_statement(&mut block.condition, scopes, locals);
+                    self.resolve_statements(&mut block.body, scopes, locals);
+                }
+                self.resolve_statements(else_block, scopes, locals);
+            }
+            AstStatement::CaseStatement {
+                selector,
+                case_blocks,
+                else_block,
+                ..
+            } => {
+                self.resolve_statement(selector, scopes, locals);
+                for block in case_blocks.iter_mut() {
+                    self.resolve_statement(&mut block.condition, scopes, locals);
+                    self.resolve_statements(&mut block.body, scopes, locals);
+                }
+                self.resolve_statements(else_block, scopes, locals);
+            }
+            AstStatement::ForLoopStatement {
+                counter,
+                start,
+                end,
+                by_step,
+                body,
+                ..
+            } => {
+                self.resolve_statement(counter, scopes, locals);
+                self.resolve_statement(start, scopes, locals);
+                self.resolve_statement(end, scopes, locals);
+                if let Some(by_step) = by_step.as_mut() {
+                    self.resolve_statement(by_step, scopes, locals);
+                }
+                self.resolve_statements(body, scopes, locals);
+            }
+            AstStatement::WhileLoopStatement {
+                condition, body, ..
+            }
+            | AstStatement::RepeatLoopStatement {
+                condition, body, ..
+            } => {
+                self.resolve_statement(condition, scopes, locals);
+                self.resolve_statements(body, scopes, locals);
+            }
+            _ => {}
+        }
+    }
+
+    fn resolve_statements(
+        &self,
+        statements: &mut [AstStatement],
+        scopes: &[String],
+        locals: &HashSet<String>,
+    ) {
+        for statement in statements.iter_mut() {
+            self.resolve_statement(statement, scopes, locals);
+        }
+    }
+
+    /// resolves the parameters of a call, the left side of a named parameter assignment
+    /// is the name of a parameter and is left untouched
+    fn resolve_parameters(
+        &self,
+        parameters: &mut AstStatement,
+        scopes: &[String],
+        locals: &HashSet<String>,
+    ) {
+        match parameters {
+            AstStatement::ExpressionList { expressions, .. } => {
+                for parameter in expressions.iter_mut() {
+                    self.resolve_parameters(parameter, scopes, locals);
+                }
+            }
+            AstStatement::Assignment { right, .. }
+            | AstStatement::OutputAssignment { right, .. } => {
+                self.resolve_statement(right, scopes, locals)
+            }
+            _ => self.resolve_statement(parameters, scopes, locals),
+        }
+    }
+
+    /// replaces the leading elements of a qualified reference naming a namespaced declaration
+    /// (e.g. `Lib.Sub.MyPrg` of `Lib.Sub.MyPrg.x`) by a single reference to the declaration
+    fn merge_namespace_qualifiers(
+        &self,
+        elements: &mut Vec<AstStatement>,
+        scopes: &[String],
+        locals: &HashSet<String>,
+    ) {
+        let names = elements
+            .iter()
+            .take_while(|it| matches!(it, AstStatement::Reference { .. }))
+            .map(|it| match it {
+                AstStatement::Reference { name, .. } => name.as_str(),
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+        if names.is_empty() || locals.contains(&names[0].to_lowercase()) {
+            return;
+        }
+
+        //prefer the longest qualified name naming a declaration
+        let merged = (2..=names.len()).rev().find_map(|len| {
+            self.resolve(&names[..len].join("."), scopes)
+                .map(|qualified_name| (len, qualified_name.to_string()))
+        });
+        if let Some((len, name)) = merged {
+            let merged_elements = elements.drain(..len).collect::<Vec<_>>();
+            let last = &merged_elements[len - 1];
+            elements.insert(
+                0,
+                AstStatement::Reference {
+                    name,
+                    location: merged_elements[0].get_location().span(&last.get_location()),
+                    id: last.get_id(),
+                },
+            );
+        }
+    }
+}
+
+fn get_lookup_scopes<'a>(
+    lookup_scopes: &'a IndexMap<String, Vec<String>>,
+    name: &str,
+) -> &'a [String] {
+    lookup_scopes
+        .get(&name.to_lowercase())
+        .map(Vec::as_slice)
+        .unwrap_or(&[])
+}
+
+/// returns the lowercase names of the variables visible in the body of the given pou
+/// (its own variables, the variables of the class owning it and its return variable)
+/// external variables are not local, they reference global variables
+fn get_local_names(pous: &[Pou], pou_name: &str) -> HashSet<String> {
+    let find_pou = |name: &str| pous.iter().find(|it| it.name.eq_ignore_ascii_case(name));
+    let pou = find_pou(pou_name);
+    let owner = pou
+        .and_then(|it| it.pou_type.get_optional_owner_class())
+        .and_then(|it| find_pou(&it));
+    pou.iter()
+        .chain(owner.iter())
+        .flat_map(|it| it.variable_blocks.iter())
+        .filter(|it| it.variable_block_type != VariableBlockType::External)
+        .flat_map(|it| it.variables.iter())
+        .map(|it| it.name.to_lowercase())
+        .chain(pou.map(|it| it.get_return_name().to_lowercase()))
+        .collect()
+}
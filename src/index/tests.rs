@@ -2,3 +2,4 @@
 mod generic_tests;
 mod index_tests;
 mod instance_resolver_tests;
+mod namespace_tests;
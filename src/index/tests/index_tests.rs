@@ -693,6 +693,8 @@ fn pre_processing_generates_inline_structs_global() {
                 location: (54..55).into(),
                 initializer: None,
                 address: None,
+                attributes: vec![],
+                comments: vec![],
             },],
         },
         new_struct_type
@@ -772,6 +774,8 @@ fn pre_processing_generates_inline_structs() {
                 location: (67..68).into(),
                 initializer: None,
                 address: None,
+                attributes: vec![],
+                comments: vec![],
             }],
         },
         new_struct_type
@@ -815,6 +819,8 @@ fn pre_processing_generates_inline_pointers() {
         location: SourceRange::undefined(),
         initializer: None,
         scope: Some("foo".into()),
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", new_pointer_type));
 
@@ -852,6 +858,8 @@ fn pre_processing_generates_pointer_to_pointer_type() {
         location: SourceRange::undefined(),
         initializer: None,
         scope: None,
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", new_pointer_type));
 
@@ -868,6 +876,8 @@ fn pre_processing_generates_pointer_to_pointer_type() {
         location: SourceRange::undefined(),
         initializer: None,
         scope: None,
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", original));
 }
@@ -900,6 +910,8 @@ fn pre_processing_generates_inline_pointer_to_pointer() {
         location: SourceRange::undefined(),
         initializer: None,
         scope: Some("foo".into()),
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", new_pointer_type));
 
@@ -916,6 +928,8 @@ fn pre_processing_generates_inline_pointer_to_pointer() {
         location: SourceRange::undefined(),
         initializer: None,
         scope: Some("foo".into()),
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", new_pointer_type));
 
@@ -969,6 +983,8 @@ fn pre_processing_generates_inline_arrays() {
         initializer: None,
         location: (59..77).into(),
         scope: Some("foo".into()),
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", new_array_type));
 
@@ -1024,6 +1040,8 @@ fn pre_processing_generates_inline_array_of_array() {
         initializer: None,
         location: (59..92).into(),
         scope: Some("foo".into()),
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", new_array_type));
 
@@ -1053,6 +1071,8 @@ fn pre_processing_generates_inline_array_of_array() {
         initializer: None,
         location: (59..92).into(),
         scope: Some("foo".into()),
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", new_array_type));
 
@@ -1093,6 +1113,8 @@ fn pre_processing_generates_array_of_array_type() {
         location: SourceRange::undefined(),
         initializer: None,
         scope: None,
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", new_type));
 
@@ -1114,6 +1136,8 @@ fn pre_processing_generates_array_of_array_type() {
         location: SourceRange::undefined(),
         initializer: None,
         scope: None,
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", original));
 }
@@ -1153,11 +1177,15 @@ fn pre_processing_nested_array_in_struct() {
                 location: SourceRange::undefined(),
                 initializer: None,
                 address: None,
+                attributes: vec![],
+                comments: vec![],
             }],
         },
         initializer: None,
         location: (14..97).into(),
         scope: None,
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", new_array_type));
 
@@ -1187,6 +1215,8 @@ fn pre_processing_nested_array_in_struct() {
         initializer: None,
         location: (59..77).into(),
         scope: None,
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", new_array_type));
 }
@@ -1232,6 +1262,8 @@ fn pre_processing_generates_inline_array_of_array_of_array() {
         initializer: None,
         location: (74..107).into(),
         scope: Some("foo".into()),
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", new_array_type));
 
@@ -1261,6 +1293,8 @@ fn pre_processing_generates_inline_array_of_array_of_array() {
         initializer: None,
         location: (59..107).into(),
         scope: Some("foo".into()),
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", new_array_type));
 
@@ -1290,6 +1324,8 @@ fn pre_processing_generates_inline_array_of_array_of_array() {
         initializer: None,
         location: (59..107).into(),
         scope: Some("foo".into()),
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:?}", expected), format!("{:?}", new_array_type));
 
@@ -1328,6 +1364,8 @@ fn pre_processing_generates_generic_types() {
         initializer: None,
         location: SourceRange::undefined(),
         scope: Some("myFunc".into()),
+        attributes: vec![],
+        comments: vec![],
     };
 
     assert_eq!(format!("{:?}", expected), format!("{:?}", ast.types[0]));
@@ -1377,6 +1415,8 @@ fn pre_processing_generates_nested_generic_types() {
         initializer: None,
         location: SourceRange::undefined(),
         scope: Some("myFunc".into()),
+        attributes: vec![],
+        comments: vec![],
     };
 
     assert_eq!(format!("{:?}", expected), format!("{:?}", ast.types[0]));
@@ -1866,3 +1906,226 @@ fn pointer_and_in_out_pointer_should_not_conflict_2() {
         }
     );
 }
+
+#[test]
+fn types_referenced_by_variables_and_arrays_are_found() {
+    // GIVEN variables of different types, some of them only used as array elements
+    let (_, index) = index(
+        "
+        VAR_GLOBAL
+            g : R_TRIG;
+        END_VAR
+        PROGRAM main
+        VAR
+            a : ARRAY[0..1] OF F_TRIG;
+            b : INT;
+        END_VAR
+        END_PROGRAM
+        ",
+    );
+
+    // THEN the types of all variables and array elements are referenced
+    assert!(index.is_type_referenced("r_trig"));
+    assert!(index.is_type_referenced("F_TRIG"));
+    assert!(index.is_type_referenced("INT"));
+    // AND types no one uses are not
+    assert!(!index.is_type_referenced("TON"));
+}
+
+#[test]
+fn interfaces_and_their_implementations_are_indexed() {
+    // GIVEN an interface with two methods and a function block implementing it
+    let (_, index) = index(
+        "
+        INTERFACE IShape
+            METHOD area : DINT END_METHOD
+            METHOD scale VAR_INPUT factor : DINT; END_VAR END_METHOD
+        END_INTERFACE
+
+        FUNCTION_BLOCK square IMPLEMENTS IShape
+            METHOD scale VAR_INPUT factor : DINT; END_VAR END_METHOD
+            METHOD area : DINT END_METHOD
+        END_FUNCTION_BLOCK
+        ",
+    );
+
+    // THEN the interface is an interface with an instance and a vtable member
+    assert!(index.is_interface("ishape"));
+    assert!(!index.is_interface("square"));
+    let members = index
+        .get_container_members("IShape")
+        .iter()
+        .map(|it| it.get_name())
+        .collect::<Vec<_>>();
+    assert_eq!(members, vec!["__instance", "__vtable"]);
+    // AND its methods are found in the order of their declaration
+    let methods = index
+        .get_interface_methods("IShape")
+        .iter()
+        .map(|it| it.get_call_name())
+        .collect::<Vec<_>>();
+    assert_eq!(methods, vec!["IShape.area", "IShape.scale"]);
+    // AND the function block implements it
+    assert!(index.implements_interface("SQUARE", "ishape"));
+    assert!(!index.implements_interface("IShape", "IShape"));
+}
+
+#[test]
+fn inherited_members_and_methods_are_found() {
+    // GIVEN a class hierarchy overriding a method
+    let (_, index) = index(
+        "
+        CLASS base
+            VAR x : INT; END_VAR
+            METHOD a END_METHOD
+            METHOD b END_METHOD
+        END_CLASS
+
+        CLASS derived EXTENDS base
+            VAR y : INT; END_VAR
+            METHOD c END_METHOD
+            METHOD a END_METHOD
+        END_CLASS
+
+        CLASS standalone
+            METHOD a END_METHOD
+        END_CLASS
+        ",
+    );
+
+    // THEN the derived class embeds its super class as its first member
+    let members = index
+        .get_container_members("derived")
+        .iter()
+        .map(|it| (it.get_name(), it.get_type_name()))
+        .collect::<Vec<_>>();
+    assert_eq!(members, vec![("__BASE", "base"), ("y", "INT")]);
+    // AND inherited members and methods are found
+    assert_eq!(
+        index
+            .find_member("derived", "x")
+            .map(|it| it.get_qualified_name()),
+        Some("base.x")
+    );
+    assert_eq!(
+        index
+            .find_method("derived", "b")
+            .map(|it| it.get_call_name()),
+        Some("base.b")
+    );
+    assert_eq!(
+        index
+            .find_method("derived", "a")
+            .map(|it| it.get_call_name()),
+        Some("derived.a")
+    );
+    // AND the vtable slots start with the super class' methods
+    let methods = index
+        .get_virtual_methods("derived")
+        .iter()
+        .map(|it| it.get_call_name())
+        .collect::<Vec<_>>();
+    assert_eq!(methods, vec!["base.a", "base.b", "derived.c"]);
+    // AND only classes of a hierarchy are polymorphic
+    assert!(index.is_polymorphic("base"));
+    assert!(index.is_polymorphic("derived"));
+    assert!(!index.is_polymorphic("standalone"));
+}
+
+#[test]
+fn cyclic_inheritance_is_detected() {
+    // GIVEN classes extending each other and a valid hierarchy
+    let (_, index) = index(
+        "
+        CLASS a EXTENDS b END_CLASS
+        CLASS b EXTENDS a END_CLASS
+        CLASS c END_CLASS
+        CLASS d EXTENDS c END_CLASS
+        ",
+    );
+
+    // THEN the cycles are detected and the chain of super classes ends
+    assert!(index.has_cyclic_inheritance("a"));
+    assert!(index.has_cyclic_inheritance("b"));
+    assert_eq!(index.get_super_classes("a"), vec!["b"]);
+    assert!(!index.has_cyclic_inheritance("d"));
+    assert_eq!(index.get_super_classes("d"), vec!["c"]);
+}
+
+#[test]
+fn properties_are_found_by_their_accessors() {
+    // GIVEN a function block with properties and a function block extending it
+    let (_, index) = index(
+        "
+        FUNCTION_BLOCK base
+            PROPERTY Level : INT
+                GET END_GET
+                SET END_SET
+            END_PROPERTY
+            PROPERTY WriteOnly : REAL
+                SET END_SET
+            END_PROPERTY
+        END_FUNCTION_BLOCK
+        FUNCTION_BLOCK derived EXTENDS base END_FUNCTION_BLOCK
+        ",
+    );
+
+    // THEN the property's type and accessors are found
+    let level = index.find_property("base", "level").unwrap();
+    assert_eq!(level.data_type_name, "INT");
+    assert_eq!(
+        level.getter.map(|it| it.get_call_name()),
+        Some("base.__get_Level")
+    );
+    assert_eq!(
+        level.setter.map(|it| it.get_call_name()),
+        Some("base.__set_Level")
+    );
+    // AND properties without a getter take their type from the setter
+    let write_only = index.find_property("base", "WriteOnly").unwrap();
+    assert_eq!(write_only.data_type_name, "REAL");
+    assert_eq!(write_only.getter, None);
+    // AND inherited properties are found
+    assert_eq!(index.find_property("derived", "Level"), Some(level));
+    assert_eq!(index.find_property("base", "unknown"), None);
+}
+
+#[test]
+fn attributes_are_registered_by_the_qualified_name_of_their_declaration() {
+    // GIVEN declarations with pragmas
+    let (_, index) = index(
+        "
+        {attribute 'export'}
+        FUNCTION_BLOCK fb
+        VAR {retain} x : INT; y : INT; END_VAR
+        END_FUNCTION_BLOCK
+        VAR_GLOBAL {attribute 'symbol' := 'g_sym'} g : INT; END_VAR
+        {pack 1}
+        TYPE MyStruct : STRUCT {attribute 'offset' := '4'} a : BYTE; END_STRUCT END_TYPE
+        ",
+    );
+
+    // THEN the attributes are found case-insensitively by the declaration's qualified name
+    assert!(index.find_attribute("FB", "EXPORT").is_some());
+    assert!(index.find_attribute("fb.x", "retain").is_some());
+    assert_eq!(index.get_attributes("fb.y"), &[]);
+    assert_eq!(
+        index
+            .find_attribute("g", "symbol")
+            .and_then(|it| it.value.as_deref()),
+        Some("g_sym")
+    );
+    assert_eq!(
+        index
+            .find_attribute("MyStruct", "pack")
+            .and_then(|it| it.value.as_deref()),
+        Some("1")
+    );
+    assert_eq!(
+        index
+            .find_attribute("mystruct.a", "offset")
+            .and_then(|it| it.value.as_deref()),
+        Some("4")
+    );
+    assert_eq!(index.find_attribute("fb", "retain"), None);
+}
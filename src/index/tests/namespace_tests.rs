@@ -0,0 +1,113 @@
+use crate::{
+    ast::AstStatement,
+    index::namespaces::resolve_namespaces,
+    test_utils::tests::{index, parse},
+};
+
+#[test]
+fn references_to_namespaced_declarations_are_qualified() {
+    // GIVEN declarations in nested namespaces and references to them
+    let (mut unit, diagnostics) = parse(
+        "
+        NAMESPACE Lib
+            FUNCTION helper : INT END_FUNCTION
+            NAMESPACE Sub
+                FUNCTION_BLOCK MyFb END_FUNCTION_BLOCK
+                FUNCTION_BLOCK Other
+                VAR fb : MyFb; helper : INT; END_VAR
+                    helper := 1;
+                END_FUNCTION_BLOCK
+            END_NAMESPACE
+        END_NAMESPACE
+
+        USING Lib;
+        PROGRAM main
+        VAR
+            a : Lib.Sub.MyFb;
+            b : Sub.MyFb;
+            x : INT;
+        END_VAR
+            x := helper();
+            x := Lib.helper();
+        END_PROGRAM
+        ",
+    );
+    assert_eq!(diagnostics, vec![]);
+
+    // WHEN the namespaces are resolved
+    resolve_namespaces(&mut [&mut unit]);
+
+    // THEN the declarations are qualified by their namespaces
+    let pous = unit
+        .units
+        .iter()
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        pous,
+        vec!["Lib.helper", "Lib.Sub.MyFb", "Lib.Sub.Other", "main"]
+    );
+    // AND the types are resolved in the enclosing and in the used namespaces
+    let types = unit
+        .units
+        .iter()
+        .flat_map(|it| it.variable_blocks.iter())
+        .flat_map(|it| it.variables.iter())
+        .map(|it| it.data_type.get_name().unwrap_or_default())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        vec!["Lib.Sub.MyFb", "INT", "Lib.Sub.MyFb", "Lib.Sub.MyFb", "INT"]
+    );
+    // AND local variables hide namespaced declarations
+    if let AstStatement::Assignment { left, .. } = &unit.implementations[2].statements[0] {
+        assert_eq!(format!("{:?}", left), r#"Reference { name: "helper" }"#);
+    } else {
+        panic!("expected an assignment");
+    }
+    // AND unqualified and qualified calls reference the namespaced function
+    for statement in unit.implementations[3].statements.iter() {
+        if let AstStatement::Assignment { right, .. } = statement {
+            if let AstStatement::CallStatement { operator, .. } = right.as_ref() {
+                assert_eq!(
+                    format!("{:?}", operator),
+                    r#"Reference { name: "Lib.helper" }"#
+                );
+                continue;
+            }
+        }
+        panic!("expected a call assignment, found {:?}", statement);
+    }
+}
+
+#[test]
+fn namespaced_declarations_are_indexed_by_their_qualified_name() {
+    // GIVEN namespaced types, function blocks and global variables
+    let (_, index) = index(
+        "
+        NAMESPACE Lib
+            TYPE Point : STRUCT x, y : INT; END_STRUCT END_TYPE
+            VAR_GLOBAL origin : Point; END_VAR
+            FUNCTION_BLOCK Shape
+            VAR center : Point; END_VAR
+            END_FUNCTION_BLOCK
+        END_NAMESPACE
+        ",
+    );
+
+    // THEN they are indexed by their qualified names
+    assert!(index.find_effective_type("Lib.Point").is_some());
+    assert_eq!(
+        index
+            .find_global_variable("Lib.origin")
+            .map(|it| it.get_type_name()),
+        Some("Lib.Point")
+    );
+    assert_eq!(
+        index
+            .find_member("Lib.Shape", "center")
+            .map(|it| it.get_type_name()),
+        Some("Lib.Point")
+    );
+    assert!(index.find_effective_type("Point").is_none());
+}
@@ -1,8 +1,12 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
-use super::{HardwareBinding, VariableIndexEntry, VariableType};
+use super::{
+    ConfigVariableIndexEntry, HardwareBinding, RetentionType, TaskIndexEntry, VariableIndexEntry,
+    VariableType,
+};
 use crate::ast::{
-    self, AstStatement, CompilationUnit, DataType, DataTypeDeclaration, Implementation, Pou,
-    PouType, SourceRange, TypeNature, UserTypeDeclaration, VariableBlock, VariableBlockType,
+    self, AstStatement, CompilationUnit, Configuration, DataType, DataTypeDeclaration,
+    Implementation, Pou, PouType, SourceRange, TypeNature, UserTypeDeclaration, VariableBlock,
+    VariableBlockType,
 };
 use crate::diagnostics::Diagnostic;
 use crate::index::{Index, MemberInfo};
@@ -36,18 +40,124 @@ pub fn visit(unit: &CompilationUnit, mut id_provider: IdProvider) -> Index {
     for implementation in &unit.implementations {
         visit_implementation(&mut index, implementation);
     }
+
+    for configuration in &unit.configurations {
+        visit_configuration(&mut index, configuration);
+    }
     index
 }
 
+/// registers the tasks of the configuration's resources and a global variable for every
+/// program instance, the instances are scheduled by their task. The addresses assigned by
+/// `VAR_CONFIG` blocks are registered as config variables
+fn visit_configuration(index: &mut Index, configuration: &Configuration) {
+    for variable in &configuration.config_variables {
+        if let Some(binding) = HardwareBinding::from_statement(index, &variable.address, None) {
+            index.register_config_variable(ConfigVariableIndexEntry {
+                reference: variable.reference.clone(),
+                data_type_name: variable.data_type.clone(),
+                binding,
+                source_location: variable.location.clone(),
+            });
+        }
+    }
+    for resource in &configuration.resources {
+        let resource_name = format!("{}.{}", configuration.name, resource.name);
+        for task in &resource.tasks {
+            let const_expressions = index.get_mut_const_expressions();
+            let interval = const_expressions.maybe_add_constant_expression(
+                task.interval.clone(),
+                TIME_TYPE,
+                None,
+            );
+            let priority = const_expressions.maybe_add_constant_expression(
+                task.priority.clone(),
+                UINT_TYPE,
+                None,
+            );
+            let programs = resource
+                .programs
+                .iter()
+                .filter(|it| {
+                    it.task
+                        .as_deref()
+                        .map_or(false, |it| it.eq_ignore_ascii_case(&task.name))
+                })
+                .map(|it| it.name.clone())
+                .collect();
+            index.register_task(TaskIndexEntry {
+                name: task.name.clone(),
+                resource: resource_name.clone(),
+                interval,
+                priority,
+                single: task.single.as_ref().and_then(get_reference_name),
+                programs,
+                source_location: task.location.clone(),
+            });
+        }
+
+        for program in &resource.programs {
+            let variable = VariableIndexEntry::create_global(
+                &program.name,
+                &program.name,
+                &program.program,
+                program.location.clone(),
+            );
+            index.register_global_variable(&program.name, variable);
+        }
+    }
+}
+
+/// returns the name of the referenced variable (e.g. `a.b` for `a.b`)
+fn get_reference_name(statement: &AstStatement) -> Option<String> {
+    match statement {
+        AstStatement::Reference { name, .. } => Some(name.clone()),
+        AstStatement::QualifiedReference { elements, .. } => elements
+            .iter()
+            .map(get_reference_name)
+            .collect::<Option<Vec<_>>>()
+            .map(|it| it.join(".")),
+        _ => None,
+    }
+}
+
 pub fn visit_pou(index: &mut Index, pou: &Pou) {
     let interface_name = format!("{}_interface", &pou.name);
+    index.register_attributes(&pou.name, &pou.attributes);
 
     let mut member_names = vec![];
 
     //register the pou's member variables
     let mut count = 0;
     let mut varargs = None;
-    for block in &pou.variable_blocks {
+
+    //a derived pou embeds the instance of its super class as its first member
+    if let Some(super_class) = &pou.super_class {
+        member_names.push(typesystem::BASE_MEMBER.into());
+        index.register_member_variable(
+            MemberInfo {
+                container_name: &pou.name,
+                variable_name: typesystem::BASE_MEMBER,
+                variable_linkage: VariableType::Local,
+                variable_type_name: super_class,
+                is_constant: false,
+                binding: None,
+            },
+            None,
+            pou.location.clone(),
+            count,
+        );
+        count += 1;
+        index.register_super_class(&pou.name, super_class);
+        //SUPER points to the embedded instance
+        register_pointer_type(index, super_class);
+    }
+    //external variables are references to global variables, not members of the pou
+    for block in pou
+        .variable_blocks
+        .iter()
+        .filter(|it| it.variable_block_type != VariableBlockType::External)
+    {
         let block_type = get_variable_type_from_block(block);
         for var in &block.variables {
             if let DataTypeDeclaration::DataTypeDefinition {
@@ -65,6 +175,7 @@ pub fn visit_pou(index: &mut Index, pou: &Pou) {
                 continue;
             }
             member_names.push(var.name.clone());
+            index.register_attributes(&format!("{}.{}", pou.name, var.name), &var.attributes);
 
             let var_type_name = var.data_type.get_name().expect("named datatype");
             let type_name = if block_type == VariableType::InOut {
@@ -103,10 +214,42 @@ pub fn visit_pou(index: &mut Index, pou: &Pou) {
         }
     }
 
+    //an interface instance references the implementing instance and its vtable
+    if pou.pou_type == PouType::Interface {
+        let pointer_type = register_pointer_type(index, typesystem::BYTE_TYPE);
+        for name in [
+            typesystem::INTERFACE_INSTANCE_MEMBER,
+            typesystem::INTERFACE_VTABLE_MEMBER,
+        ] {
+            member_names.push(name.into());
+            index.register_member_variable(
+                MemberInfo {
+                    container_name: &pou.name,
+                    variable_name: name,
+                    variable_linkage: VariableType::Local,
+                    variable_type_name: &pointer_type,
+                    is_constant: false,
+                    binding: None,
+                },
+                None,
+                pou.location.clone(),
+                count,
+            );
+            count += 1;
+        }
+    }
+
+    if !pou.interfaces.is_empty() {
+        index.register_implemented_interfaces(&pou.name, pou.interfaces.clone());
+    }
+
     //register a function's return type as a member variable
     if let Some(return_type) = &pou.return_type {
         member_names.push(pou.get_return_name().into());
-        let source_location = SourceRange::new(pou.location.get_end()..pou.location.get_end());
+        let source_location = SourceRange::in_file(
+            pou.location.get_end()..pou.location.get_end(),
+            pou.location.get_file(),
+        );
         index.register_member_variable(
             MemberInfo {
                 container_name: &pou.name,
@@ -171,6 +314,8 @@ fn visit_implementation(index: &mut Index, implementation: &Implementation) {
         &implementation.type_name,
         pou_type.get_optional_owner_class().as_ref(),
         pou_type.into(),
+        implementation.access,
+        implementation.linkage,
     );
     //if we are registing an action, also register a datatype for it
     if pou_type == &PouType::Action {
@@ -206,6 +351,23 @@ fn register_inout_pointer_type_for(index: &mut Index, inner_type_name: &str) ->
     type_name
 }
 
+/// registers a pointer to the given type, a pointer to BYTE is used to reference instances
+/// and vtables of unknown types
+fn register_pointer_type(index: &mut Index, inner_type_name: &str) -> String {
+    let type_name = typesystem::get_pointer_type_name(inner_type_name);
+    index.register_type(typesystem::DataType {
+        name: type_name.clone(),
+        initial_value: None,
+        information: DataTypeInformation::Pointer {
+            name: type_name.clone(),
+            inner_type_name: inner_type_name.to_string(),
+            auto_deref: false,
+        },
+        nature: TypeNature::Any,
+    });
+    type_name
+}
+
 fn visit_global_var_block(index: &mut Index, block: &VariableBlock) {
     let linkage = block.linkage;
     for var in &block.variables {
@@ -222,12 +384,24 @@ fn visit_global_var_block(index: &mut Index, block: &VariableBlock) {
         .set_initial_value(initializer)
         .set_constant(block.constant)
         .set_linkage(linkage)
+        .set_retention(get_retention_from_block(block))
         .set_hardware_binding(
             var.address
                 .as_ref()
                 .and_then(|it| HardwareBinding::from_statement(index, it, None)),
         );
         index.register_global_variable(&var.name, variable);
+        index.register_attributes(&var.name, &var.attributes);
+    }
+}
+
+fn get_retention_from_block(block: &VariableBlock) -> RetentionType {
+    if block.persistent {
+        RetentionType::Persistent
+    } else if block.retain {
+        RetentionType::Retain
+    } else {
+        RetentionType::NonRetain
     }
 }
 
@@ -237,7 +411,7 @@ fn get_variable_type_from_block(block: &VariableBlock) -> VariableType {
         VariableBlockType::Temp => VariableType::Temp,
         VariableBlockType::Input => VariableType::Input,
         VariableBlockType::Output => VariableType::Output,
-        VariableBlockType::Global => VariableType::Global,
+        VariableBlockType::Global | VariableBlockType::External => VariableType::Global,
         VariableBlockType::InOut => VariableType::InOut,
     }
 }
@@ -249,11 +423,18 @@ fn visit_data_type(
 ) {
     let data_type = &type_declaration.data_type;
     let scope = &type_declaration.scope;
+    if let Some(name) = data_type.get_name() {
+        index.register_attributes(name, &type_declaration.attributes);
+    }
     //names should not be empty
     match data_type {
         DataType::StructType {
             name: Some(name),
             variables,
+        }
+        | DataType::UnionType {
+            name: Some(name),
+            variables,
         } => {
             let struct_name = name.as_str();
 
@@ -261,11 +442,16 @@ fn visit_data_type(
                 variables.iter().map(|it| it.name.to_string()).collect();
 
             let type_name = name.clone();
+            let source = if matches!(data_type, DataType::UnionType { .. }) {
+                StructSource::Union
+            } else {
+                StructSource::OriginalDeclaration
+            };
             let information = DataTypeInformation::Struct {
                 name: type_name.clone(),
                 member_names,
                 varargs: None,
-                source: StructSource::OriginalDeclaration,
+                source,
                 generics: vec![],
             };
 
@@ -307,6 +493,8 @@ fn visit_data_type(
                             initializer: None,
                             location: SourceRange::undefined(),
                             scope: scope.clone(),
+                            attributes: vec![],
+                            comments: vec![],
                         },
                     )
                 }
@@ -325,6 +513,8 @@ fn visit_data_type(
                     .as_ref()
                     .and_then(|it| HardwareBinding::from_statement(index, it, scope.clone()));
 
+                index
+                    .register_attributes(&format!("{}.{}", struct_name, var.name), &var.attributes);
                 index.register_member_variable(
                     MemberInfo {
                         container_name: struct_name,
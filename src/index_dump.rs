@@ -0,0 +1,332 @@
+// Copyright (c) 2022 Ghaith Hachem and Mathias Rieder
+//! Dumps the index of a compilation as JSON, so tools can consume the compiled POUs with their
+//! signatures, the types with the layout of their members and the global variables with their
+//! resolved initial values without parsing the sources themselves.
+use serde::Serialize;
+
+use crate::{
+    ast::{AstStatement, LinkageType},
+    diagnostics::{Diagnostic, ErrNo},
+    index::{Index, VariableIndexEntry},
+    typesystem::{self, DataType, DataTypeInformation},
+};
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct IndexDump {
+    pous: Vec<Pou>,
+    types: Vec<Type>,
+    globals: Vec<Global>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Pou {
+    name: String,
+    /// the kind of the POU, e.g. `Function` or `FunctionBlock`
+    kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    return_type: Option<String>,
+    /// the POU is defined by another library or file
+    external: bool,
+    /// the members of the POU in declaration order
+    members: Vec<Member>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Type {
+    name: String,
+    /// the kind of the type, e.g. `struct` or `array`
+    kind: &'static str,
+    /// the size of an instance in bytes, if it can be determined
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<u32>,
+    /// the members of a struct in declaration order
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    members: Vec<Member>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Member {
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+    /// the kind of the member, e.g. `Input` or `Local`
+    kind: String,
+    /// the byte offset of the member in its instance, members that are not stored in the
+    /// instance (temporary variables and return values) have no offset
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    initial_value: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Global {
+    name: String,
+    #[serde(rename = "type")]
+    type_name: String,
+    constant: bool,
+    /// the resolved initial value of the variable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    initial_value: Option<String>,
+}
+
+/// Collects the POUs, the user-defined types and the global variables of the index
+pub fn collect_index_dump(index: &Index) -> IndexDump {
+    let builtins = typesystem::get_builtin_types()
+        .into_iter()
+        .map(|it| it.name.to_lowercase())
+        .collect::<Vec<_>>();
+    let pous = index
+        .get_implementations()
+        .values()
+        .map(|it| {
+            let name = it.get_call_name();
+            Pou {
+                name: name.to_string(),
+                kind: format!("{:?}", it.get_implementation_type()),
+                return_type: index
+                    .find_return_type(name)
+                    .filter(|it| it.get_type_information() != &DataTypeInformation::Void)
+                    .map(|it| it.get_name().to_string()),
+                external: it.get_linkage() == LinkageType::External,
+                members: collect_members(index, it.get_type_name()),
+            }
+        })
+        .collect();
+    let types = index
+        .get_types()
+        .values()
+        .filter(|it| !builtins.contains(&it.get_name().to_lowercase()))
+        .map(|it| collect_type(index, it))
+        .collect();
+    let globals = index
+        .get_globals()
+        .values()
+        .map(|it| Global {
+            name: it.get_qualified_name().to_string(),
+            type_name: it.get_type_name().to_string(),
+            constant: it.is_constant(),
+            initial_value: get_initial_value(index, it),
+        })
+        .collect();
+    IndexDump {
+        pous,
+        types,
+        globals,
+    }
+}
+
+fn collect_type(index: &Index, data_type: &DataType) -> Type {
+    let information = data_type.get_type_information();
+    let kind = match information {
+        DataTypeInformation::Struct { .. } => "struct",
+        DataTypeInformation::Array { .. } => "array",
+        DataTypeInformation::Pointer { .. } => "pointer",
+        DataTypeInformation::Integer { .. } => "integer",
+        DataTypeInformation::Enum { .. } => "enum",
+        DataTypeInformation::Float { .. } => "float",
+        DataTypeInformation::String { .. } => "string",
+        DataTypeInformation::SubRange { .. } => "subrange",
+        DataTypeInformation::Alias { .. } => "alias",
+        DataTypeInformation::Generic { .. } => "generic",
+        DataTypeInformation::Void => "void",
+    };
+    let members = match information {
+        DataTypeInformation::Struct { name, .. } => collect_members(index, name),
+        _ => vec![],
+    };
+    Type {
+        name: data_type.get_name().to_string(),
+        kind,
+        size: typesystem::get_size_in_bytes(data_type.get_name(), index),
+        members,
+    }
+}
+
+/// returns the members of the given container with their offsets in the container's instances
+fn collect_members(index: &Index, container: &str) -> Vec<Member> {
+    let members = index.get_container_members(container);
+    //the layout only contains the members stored in the instance
+    let mut offsets = typesystem::get_struct_layout(
+        container,
+        typesystem::get_pack_mode(container, index),
+        index,
+    )
+    .map(|it| it.offsets)
+    .unwrap_or_default()
+    .into_iter();
+    members
+        .into_iter()
+        .map(|it| {
+            let offset = if it.is_temp() || it.is_return() {
+                None
+            } else {
+                offsets.next()
+            };
+            Member {
+                name: it.get_name().to_string(),
+                type_name: it.get_type_name().to_string(),
+                kind: format!("{:?}", it.get_variable_type()),
+                offset,
+                initial_value: get_initial_value(index, it),
+            }
+        })
+        .collect()
+}
+
+fn get_initial_value(index: &Index, variable: &VariableIndexEntry) -> Option<String> {
+    variable
+        .initial_value
+        .as_ref()
+        .and_then(|it| index.get_const_expressions().get_constant_statement(it))
+        .map(format_value)
+}
+
+/// returns the given (resolved) initial value in the syntax of structured text
+fn format_value(value: &AstStatement) -> String {
+    let format_all = |values: &[AstStatement]| {
+        values
+            .iter()
+            .map(format_value)
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match value {
+        AstStatement::LiteralInteger { value, .. } => value.to_string(),
+        AstStatement::LiteralReal { value, .. } => value.clone(),
+        AstStatement::LiteralBool { value, .. } => value.to_string().to_uppercase(),
+        AstStatement::LiteralString {
+            value,
+            is_wide: true,
+            ..
+        } => format!(r#""{}""#, value),
+        AstStatement::LiteralString { value, .. } => format!("'{}'", value),
+        AstStatement::LiteralArray { elements, .. } => format!(
+            "[{}]",
+            elements.as_deref().map(format_value).unwrap_or_default()
+        ),
+        AstStatement::ExpressionList { expressions, .. } => format_all(expressions),
+        AstStatement::MultipliedStatement {
+            multiplier,
+            element,
+            ..
+        } => format!("{}({})", multiplier, format_value(element)),
+        AstStatement::Assignment { left, right, .. } => {
+            format!("{} := {}", format_value(left), format_value(right))
+        }
+        AstStatement::Reference { name, .. } => name.clone(),
+        AstStatement::QualifiedReference { elements, .. } => elements
+            .iter()
+            .map(format_value)
+            .collect::<Vec<_>>()
+            .join("."),
+        _ => format!("{:?}", value),
+    }
+}
+
+pub fn generate_index_dump(dump: &IndexDump) -> Result<String, Diagnostic> {
+    serde_json::to_string_pretty(dump).map_err(|e| Diagnostic::GeneralError {
+        message: e.to_string(),
+        err_no: ErrNo::general__io_err,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        index_dump::{collect_index_dump, Member},
+        resolver::const_evaluator::evaluate_constants,
+        test_utils::tests::index,
+    };
+
+    #[test]
+    fn pous_are_dumped_with_their_signature() {
+        let (_, index) = index(
+            "
+            FUNCTION foo : INT
+            VAR_INPUT a : INT; END_VAR
+            VAR_TEMP t : DINT; END_VAR
+            END_FUNCTION
+            ",
+        );
+        let dump = collect_index_dump(&index);
+
+        let foo = dump.pous.iter().find(|it| it.name == "foo").unwrap();
+        assert_eq!(foo.kind, "Function");
+        assert_eq!(foo.return_type.as_deref(), Some("INT"));
+        assert!(!foo.external);
+        let members = foo
+            .members
+            .iter()
+            .map(|it| (it.name.as_str(), it.kind.as_str(), it.offset))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            members,
+            vec![
+                ("a", "Input", Some(0)),
+                ("t", "Temp", None),
+                ("foo", "Return", None)
+            ]
+        );
+    }
+
+    #[test]
+    fn types_are_dumped_with_their_layout() {
+        let (_, index) = index(
+            "
+            TYPE Point : STRUCT x : SINT; y : DINT := 7; END_STRUCT END_TYPE
+            ",
+        );
+        let (index, _) = evaluate_constants(index);
+        let dump = collect_index_dump(&index);
+
+        //the builtin types are not dumped
+        assert_eq!(dump.types.len(), 1);
+        let point = &dump.types[0];
+        assert_eq!((point.name.as_str(), point.kind), ("Point", "struct"));
+        assert_eq!(point.size, Some(8));
+        assert_eq!(
+            point.members[1],
+            Member {
+                name: "y".to_string(),
+                type_name: "DINT".to_string(),
+                kind: "Local".to_string(),
+                offset: Some(4),
+                initial_value: Some("7".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn globals_are_dumped_with_their_resolved_initial_values() {
+        let (_, index) = index(
+            "
+            VAR_GLOBAL CONSTANT
+                base : INT := 2;
+                limit : INT := base * 10;
+            END_VAR
+            VAR_GLOBAL
+                values : ARRAY[0..2] OF INT := [1, 2(3)];
+                flag : BOOL;
+            END_VAR
+            ",
+        );
+        let (index, _) = evaluate_constants(index);
+        let dump = collect_index_dump(&index);
+
+        let globals = dump
+            .globals
+            .iter()
+            .map(|it| (it.name.as_str(), it.constant, it.initial_value.as_deref()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            globals,
+            vec![
+                ("base", true, Some("2")),
+                ("limit", true, Some("20")),
+                ("values", false, Some("[1, 2(3)]")),
+                ("flag", false, None),
+            ]
+        );
+    }
+}
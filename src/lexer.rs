@@ -1,6 +1,5 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 use core::ops::Range;
-use logos::Filter;
 use logos::Lexer;
 use logos::Logos;
 use std::sync::atomic::AtomicUsize;
@@ -9,11 +8,20 @@ use std::sync::Arc;
 pub use tokens::Token;
 
 use crate::ast::AstId;
+use crate::ast::Attribute;
+use crate::ast::Comment;
 use crate::ast::DirectAccessType;
 use crate::ast::HardwareAccessType;
 use crate::ast::SourceRange;
+use crate::ast::WarningPragma;
+use crate::diagnostics::WarningLevel;
 use crate::Diagnostic;
+use crate::WarningCode;
+use clap::ArgEnum;
 
+use self::directives::ConditionalRegions;
+
+mod directives;
 #[cfg(test)]
 mod tests;
 mod tokens;
@@ -30,6 +38,18 @@ pub struct ParseSession<'a> {
     pub parse_progress: usize,
     id_provider: IdProvider,
     pub scope: Option<String>,
+    /// the attributes of the pragmas preceding the current token
+    attributes: Vec<Attribute>,
+    /// the comments preceding the current token that were not taken by a declaration yet
+    comments: Vec<Comment>,
+    /// the comments that were passed without being taken by a declaration
+    unattached_comments: Vec<Comment>,
+    /// the warning pragmas parsed so far
+    warning_pragmas: Vec<WarningPragma>,
+    /// the conditional compilation regions enclosing the current token
+    conditions: ConditionalRegions,
+    /// the id of the lexed file, see `SourceRange::get_file`
+    file: Option<usize>,
 }
 
 #[macro_export]
@@ -48,6 +68,18 @@ macro_rules! expect_token {
 
 impl<'a> ParseSession<'a> {
     pub fn new(l: Lexer<'a, Token>, id_provider: IdProvider) -> ParseSession<'a> {
+        ParseSession::with_defines(l, id_provider, &[], None)
+    }
+
+    /// creates a session parsing the regions of the conditional compilation directives that
+    /// are selected by the given defined names. The locations of the session are located in
+    /// the file with the given id
+    pub fn with_defines(
+        l: Lexer<'a, Token>,
+        id_provider: IdProvider,
+        defines: &[String],
+        file: Option<usize>,
+    ) -> ParseSession<'a> {
         let mut lexer = ParseSession {
             lexer: l,
             token: Token::KeywordBy,
@@ -58,6 +90,12 @@ impl<'a> ParseSession<'a> {
             parse_progress: 0,
             id_provider,
             scope: None,
+            attributes: vec![],
+            comments: vec![],
+            unattached_comments: vec![],
+            warning_pragmas: vec![],
+            conditions: ConditionalRegions::new(defines),
+            file,
         };
         lexer.advance();
         lexer
@@ -99,6 +137,18 @@ impl<'a> ParseSession<'a> {
         }
     }
 
+    /// consumes the given token or reports the current token as unexpected, the parser then
+    /// continues as if the token was present
+    pub fn consume_or_report_unexpected(&mut self, token: Token) {
+        if !self.allow(&token) {
+            self.accept_diagnostic(Diagnostic::unexpected_token_found(
+                format!("{:?}", token).as_str(),
+                self.slice(),
+                self.location(),
+            ));
+        }
+    }
+
     pub fn slice_and_advance(&mut self) -> String {
         let slice = self.slice().to_string();
         self.advance();
@@ -115,8 +165,22 @@ impl<'a> ParseSession<'a> {
 
     pub fn advance(&mut self) {
         self.last_range = self.range();
-        self.last_token =
-            std::mem::replace(&mut self.token, self.lexer.next().unwrap_or(Token::End));
+        let next = loop {
+            match self.lexer.next().unwrap_or(Token::End) {
+                Token::Pragma => self.handle_pragma(),
+                Token::Comment if self.conditions.is_active() => self.handle_comment(),
+                Token::End => {
+                    for diagnostic in self.conditions.close() {
+                        self.accept_diagnostic(diagnostic);
+                    }
+                    break Token::End;
+                }
+                //tokens in regions excluded by a conditional directive are skipped
+                _ if !self.conditions.is_active() => {}
+                token => break token,
+            }
+        };
+        self.last_token = std::mem::replace(&mut self.token, next);
         self.parse_progress += 1;
 
         match self.token {
@@ -134,13 +198,19 @@ impl<'a> ParseSession<'a> {
             | Token::KeywordFunctionBlock
             | Token::KeywordEndFunctionBlock
             | Token::KeywordEndStruct
+            | Token::KeywordEndUnion
             | Token::KeywordEndAction
             | Token::KeywordEndActions
             | Token::KeywordEndIf
             | Token::KeywordEndFor
             | Token::KeywordEndRepeat
             | Token::KeywordEndMethod
-            | Token::KeywordEndClass => {
+            | Token::KeywordEndClass
+            | Token::KeywordEndInterface
+            | Token::KeywordEndNamespace
+            | Token::KeywordEndProperty
+            | Token::KeywordEndGet
+            | Token::KeywordEndSet => {
                 if !self.slice().to_string().contains('_') {
                     self.accept_diagnostic(Diagnostic::ImprovementSuggestion {
                         message: format!(
@@ -155,16 +225,107 @@ impl<'a> ParseSession<'a> {
         }
     }
 
+    /// applies the conditional directive of the current pragma, collects its warning levels or
+    /// collects its attribute for the following declaration
+    fn handle_pragma(&mut self) {
+        let result = self
+            .conditions
+            .apply(self.lexer.slice(), self.location())
+            .and_then(|is_directive| {
+                if is_directive || !self.conditions.is_active() {
+                    Ok(())
+                } else if let Some(pragmas) =
+                    parse_warning_pragma(self.lexer.slice(), self.location())
+                {
+                    pragmas.map(|pragmas| self.warning_pragmas.extend(pragmas))
+                } else {
+                    parse_pragma(self.lexer.slice(), self.location())
+                        .map(|attribute| self.attributes.push(attribute))
+                }
+            });
+        if let Err(diagnostic) = result {
+            self.accept_diagnostic(diagnostic);
+        }
+    }
+
+    /// returns the attributes of the pragmas parsed since the last call, they belong to the
+    /// declaration starting at the current token
+    pub fn take_attributes(&mut self) -> Vec<Attribute> {
+        std::mem::take(&mut self.attributes)
+    }
+
+    /// returns the warning pragmas parsed so far, they belong to the compilation unit
+    pub fn take_warning_pragmas(&mut self) -> Vec<WarningPragma> {
+        std::mem::take(&mut self.warning_pragmas)
+    }
+
+    /// collects the current comment, a line comment ends before the line break
+    fn handle_comment(&mut self) {
+        let text = self.lexer.slice().trim_end_matches('\r');
+        let start = self.range().start;
+        let location = self.source_range(start..start + text.len());
+        self.comments.push(Comment {
+            text: text.to_string(),
+            location,
+        });
+    }
+
+    /// returns the comments between the previous and the current token, they belong to the
+    /// declaration starting at the current token. Older comments that were not taken are left
+    /// to the compilation unit
+    pub fn take_leading_comments(&mut self) -> Vec<Comment> {
+        let (leading, older): (Vec<_>, Vec<_>) = std::mem::take(&mut self.comments)
+            .into_iter()
+            .partition(|it| it.location.get_start() >= self.last_range.end);
+        self.unattached_comments.extend(older);
+        leading
+    }
+
+    /// returns the comments on the same line following the previous token, e.g. the comment
+    /// describing a variable after its declaration
+    pub fn take_trailing_comments(&mut self) -> Vec<Comment> {
+        let line_end = self.lexer.source()[self.last_range.end..]
+            .find('\n')
+            .map(|it| it + self.last_range.end)
+            .unwrap_or(usize::MAX);
+        let (trailing, others) = std::mem::take(&mut self.comments)
+            .into_iter()
+            .partition(|it| {
+                it.location.get_start() >= self.last_range.end && it.location.get_start() < line_end
+            });
+        self.comments = others;
+        trailing
+    }
+
+    /// returns all comments parsed since the last call, e.g. the comments of a body
+    pub fn take_comments(&mut self) -> Vec<Comment> {
+        std::mem::take(&mut self.comments)
+    }
+
+    /// returns the comments that were not taken by a declaration, they belong to the
+    /// compilation unit
+    pub fn take_unattached_comments(&mut self) -> Vec<Comment> {
+        let mut comments = std::mem::take(&mut self.unattached_comments);
+        comments.append(&mut self.comments);
+        comments.sort_by_key(|it| it.location.get_start());
+        comments
+    }
+
     pub fn slice(&self) -> &str {
         self.lexer.slice()
     }
 
     pub fn location(&self) -> SourceRange {
-        SourceRange::new(self.range())
+        self.source_range(self.range())
     }
 
     pub fn last_location(&self) -> SourceRange {
-        SourceRange::new(self.last_range.clone())
+        self.source_range(self.last_range.clone())
+    }
+
+    /// returns the given range of the lexed file
+    pub fn source_range(&self, range: Range<usize>) -> SourceRange {
+        SourceRange::in_file(range, self.file)
     }
 
     pub fn range(&self) -> Range<usize> {
@@ -202,15 +363,48 @@ impl<'a> ParseSession<'a> {
     }
 
     /// returns true if the given token closes an open region
+    /// the end of the file and the start of a new declaration close all open regions
     pub fn closes_open_region(&self, token: &Token) -> bool {
-        token == &Token::End || self.get_close_region_level(token).is_some()
+        token == &Token::End
+            || starts_declaration(token)
+            || self.get_close_region_level(token).is_some()
+    }
+
+    /// skips the tokens up to and including the next `;` and reports them as unexpected,
+    /// stops early at a token closing an open region
+    pub fn recover_until_semicolon(&mut self) {
+        let start = self.location().get_start();
+        let mut end = start;
+        while self.token != Token::KeywordSemicolon && !self.closes_open_region(&self.token) {
+            end = self.location().get_end();
+            self.advance();
+        }
+        if self.token == Token::KeywordSemicolon {
+            end = self.location().get_end();
+            self.advance();
+        }
+
+        if end > start {
+            self.accept_diagnostic(Diagnostic::unexpected_token_found(
+                format!(
+                    "{:?}",
+                    self.closing_keywords
+                        .last()
+                        .and_then(|it| it.first())
+                        .unwrap_or(&Token::End)
+                )
+                .as_str(),
+                format!("'{}'", self.slice_region(start..end)).as_str(),
+                self.source_range(start..end),
+            ));
+        }
     }
 
     pub fn recover_until_close(&mut self) {
         let mut hit = self.get_close_region_level(&self.token);
         let start = self.location();
         let mut end = self.location().get_end();
-        while self.token != Token::End && hit.is_none() {
+        while self.token != Token::End && !starts_declaration(&self.token) && hit.is_none() {
             end = self.location().get_end();
             self.advance();
             hit = self
@@ -232,7 +426,7 @@ impl<'a> ParseSession<'a> {
                 )
                 .as_str(),
                 format!("'{}'", self.slice_region(range.clone())).as_str(),
-                SourceRange::new(range),
+                self.source_range(range),
             ));
         }
 
@@ -252,7 +446,25 @@ impl<'a> ParseSession<'a> {
     }
 }
 
-fn parse_comments(lexer: &mut Lexer<Token>) -> Filter<()> {
+/// returns true if the given token starts a new declaration on the top level of a file (e.g. a
+/// POU or a TYPE), the parser recovers at these tokens if the previous declaration was not closed
+fn starts_declaration(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::PropertyExternal
+            | Token::PragmaExternal
+            | Token::KeywordProgram
+            | Token::KeywordFunction
+            | Token::KeywordFunctionBlock
+            | Token::KeywordClass
+            | Token::KeywordInterface
+            | Token::KeywordType
+            | Token::KeywordConfiguration
+    )
+}
+
+/// consumes the remainder of a nested block comment, returns false if the comment is not closed
+fn parse_comments(lexer: &mut Lexer<Token>) -> bool {
     let (open, close) = get_closing_tag(lexer.slice());
     let remainder = lexer.remainder();
     let mut unclosed = 1;
@@ -274,11 +486,10 @@ fn parse_comments(lexer: &mut Lexer<Token>) -> Filter<()> {
         traversed += c.len_utf8();
         if unclosed == 0 {
             lexer.bump(traversed);
-            //This is a well formed comment, treat it as whitespace
-            return Filter::Skip;
+            return true;
         }
     }
-    Filter::Emit(())
+    false
 }
 
 fn get_closing_tag(open_tag: &str) -> (char, char) {
@@ -288,6 +499,92 @@ fn get_closing_tag(open_tag: &str) -> (char, char) {
         _ => unreachable!(),
     }
 }
+/// parses the attribute declared by the given pragma, either `{attribute 'name'}`,
+/// `{attribute 'name' := 'value'}` or `{name}` followed by an optional value (e.g. `{pack 1}`)
+fn parse_pragma(pragma: &str, location: SourceRange) -> Result<Attribute, Diagnostic> {
+    let content = pragma[1..pragma.len() - 1].trim();
+    let invalid = || Diagnostic::invalid_pragma(pragma, location.clone());
+    let attribute = content
+        .get(..9)
+        .filter(|it| it.eq_ignore_ascii_case("attribute"))
+        .map(|_| content[9..].trim_start())
+        .filter(|it| it.starts_with('\''));
+    let (name, value) = if let Some(attribute) = attribute {
+        let (name, remainder) = parse_quoted(attribute).ok_or_else(invalid)?;
+        let remainder = remainder.trim();
+        if remainder.is_empty() {
+            (name, None)
+        } else {
+            let value = remainder
+                .strip_prefix(":=")
+                .and_then(|it| parse_quoted(it.trim_start()))
+                .filter(|(_, remainder)| remainder.trim().is_empty())
+                .ok_or_else(invalid)?
+                .0;
+            (name, Some(value))
+        }
+    } else {
+        let (name, value) = content
+            .split_once(char::is_whitespace)
+            .map(|(name, value)| (name, Some(value.trim())))
+            .unwrap_or((content, None));
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|it| it.is_ascii_alphanumeric() || it == '_')
+        {
+            return Err(invalid());
+        }
+        (name, value)
+    };
+    Ok(Attribute {
+        name: name.to_string(),
+        value: value.map(str::to_string),
+        location,
+    })
+}
+
+/// parses the warning pragma `{warning <level> <codes>}` setting the level (`allow`, `warn`,
+/// `error` or `restore`) of the comma separated warning codes. Returns None if the pragma is no
+/// warning pragma (e.g. the attribute `{warning 'message'}`)
+fn parse_warning_pragma(
+    pragma: &str,
+    location: SourceRange,
+) -> Option<Result<Vec<WarningPragma>, Diagnostic>> {
+    let content = pragma[1..pragma.len() - 1].trim();
+    let (keyword, content) = content.split_once(char::is_whitespace)?;
+    let (level, codes) = content.trim_start().split_once(char::is_whitespace)?;
+    if !keyword.eq_ignore_ascii_case("warning") {
+        return None;
+    }
+    let level = match level.to_lowercase().as_str() {
+        "allow" => Some(WarningLevel::Allow),
+        "warn" => Some(WarningLevel::Warn),
+        "error" => Some(WarningLevel::Error),
+        "restore" => None,
+        _ => return None,
+    };
+    let pragmas = codes
+        .split(',')
+        .map(|code| {
+            WarningCode::from_str(code.trim(), true)
+                .map(|code| WarningPragma {
+                    code,
+                    level,
+                    location: location.clone(),
+                })
+                .map_err(|_| Diagnostic::invalid_pragma(pragma, location.clone()))
+        })
+        .collect();
+    Some(pragmas)
+}
+
+/// returns the content of the single-quoted string the given text starts with and the text
+/// following it
+fn parse_quoted(text: &str) -> Option<(&str, &str)> {
+    text.strip_prefix('\'')?.split_once('\'')
+}
+
 fn parse_access_type(lexer: &mut Lexer<Token>) -> Option<DirectAccessType> {
     //Percent is at position 0
     //Find the size from position 1
@@ -368,6 +665,24 @@ pub fn lex_with_ids(source: &str, id_provider: IdProvider) -> ParseSession {
     ParseSession::new(Token::lexer(source), id_provider)
 }
 
+pub fn lex_with_defines<'a>(
+    source: &'a str,
+    id_provider: IdProvider,
+    defines: &[String],
+) -> ParseSession<'a> {
+    ParseSession::with_defines(Token::lexer(source), id_provider, defines, None)
+}
+
+/// lexes the source of the file with the given id (see `Diagnostician::register_file`)
+pub fn lex_file<'a>(
+    source: &'a str,
+    file_id: usize,
+    id_provider: IdProvider,
+    defines: &[String],
+) -> ParseSession<'a> {
+    ParseSession::with_defines(Token::lexer(source), id_provider, defines, Some(file_id))
+}
+
 #[cfg(test)]
 mod id_tests {
     use super::IdProvider;
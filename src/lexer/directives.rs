@@ -0,0 +1,269 @@
+// Copyright (c) 2022 Ghaith Hachem and Mathias Rieder
+
+//! The conditional compilation directives select the regions of a file that are parsed,
+//! depending on the names defined on the command line (`-D name`) or by `{define name}`:
+//!
+//! ```iecst
+//! {IF defined(PLC_A)}
+//!     ...
+//! {ELSIF defined(PLC_B) AND NOT defined(SIMULATION)}
+//!     ...
+//! {ELSE}
+//!     ...
+//! {END_IF}
+//! ```
+use std::collections::HashSet;
+
+use crate::{ast::SourceRange, Diagnostic};
+
+/// a conditional compilation directive with its evaluated condition
+#[derive(Debug, PartialEq)]
+pub enum Directive {
+    If(bool),
+    Elsif(bool),
+    Else,
+    EndIf,
+    Define(String),
+    Undefine(String),
+}
+
+/// a region opened by an `{IF ...}` directive
+#[derive(Debug)]
+pub struct ConditionalRegion {
+    /// the directive opening the region, reported if the region is not closed
+    directive: String,
+    location: SourceRange,
+    /// true if the region containing this region is parsed
+    parent_active: bool,
+    /// true if the current branch of the region is parsed
+    active: bool,
+    /// true if one of the region's branches was parsed
+    taken: bool,
+    /// true if the region reached its `{ELSE}` branch
+    has_else: bool,
+}
+
+/// the conditional regions enclosing the current position of the lexer
+#[derive(Debug, Default)]
+pub struct ConditionalRegions {
+    /// the defined names in lower case
+    defines: HashSet<String>,
+    regions: Vec<ConditionalRegion>,
+}
+
+impl ConditionalRegions {
+    pub fn new(defines: &[String]) -> ConditionalRegions {
+        ConditionalRegions {
+            defines: defines.iter().map(|it| it.to_lowercase()).collect(),
+            regions: vec![],
+        }
+    }
+
+    /// returns true if the tokens at the current position are parsed
+    pub fn is_active(&self) -> bool {
+        self.regions.last().map_or(true, |it| it.active)
+    }
+
+    /// applies the directive of the given pragma. Returns false if the pragma is no directive
+    pub fn apply(&mut self, pragma: &str, location: SourceRange) -> Result<bool, Diagnostic> {
+        let directive = match parse_directive(pragma, &self.defines) {
+            Some(directive) => {
+                directive.map_err(|_| Diagnostic::invalid_directive(pragma, location.clone()))?
+            }
+            None => return Ok(false),
+        };
+        let is_active = self.is_active();
+        match directive {
+            Directive::If(condition) => self.regions.push(ConditionalRegion {
+                directive: pragma.to_string(),
+                location,
+                parent_active: is_active,
+                active: is_active && condition,
+                taken: is_active && condition,
+                has_else: false,
+            }),
+            Directive::Elsif(condition) => {
+                let region = self.find_open_region(pragma, &location)?;
+                region.active = region.parent_active && !region.taken && condition;
+                region.taken |= region.active;
+            }
+            Directive::Else => {
+                let region = self.find_open_region(pragma, &location)?;
+                region.active = region.parent_active && !region.taken;
+                region.taken = true;
+                region.has_else = true;
+            }
+            Directive::EndIf => {
+                self.regions
+                    .pop()
+                    .ok_or_else(|| Diagnostic::unmatched_directive(pragma, location))?;
+            }
+            Directive::Define(name) if is_active => {
+                self.defines.insert(name.to_lowercase());
+            }
+            Directive::Undefine(name) if is_active => {
+                self.defines.remove(&name.to_lowercase());
+            }
+            Directive::Define(..) | Directive::Undefine(..) => {}
+        }
+        Ok(true)
+    }
+
+    /// returns the innermost region that can still be continued by an `{ELSIF}` or `{ELSE}`
+    fn find_open_region(
+        &mut self,
+        pragma: &str,
+        location: &SourceRange,
+    ) -> Result<&mut ConditionalRegion, Diagnostic> {
+        self.regions
+            .last_mut()
+            .filter(|it| !it.has_else)
+            .ok_or_else(|| Diagnostic::unmatched_directive(pragma, location.clone()))
+    }
+
+    /// closes all open regions and reports their missing `{END_IF}`
+    pub fn close(&mut self) -> Vec<Diagnostic> {
+        self.regions
+            .drain(..)
+            .map(|it| Diagnostic::unmatched_directive(&it.directive, it.location))
+            .collect()
+    }
+}
+
+/// parses the directive of the given pragma (e.g. `{IF defined(a)}`) and evaluates its
+/// condition with the given defined names. Returns None if the pragma is no directive
+fn parse_directive(pragma: &str, defines: &HashSet<String>) -> Option<Result<Directive, ()>> {
+    let content = pragma[1..pragma.len() - 1].trim();
+    let (keyword, arguments) = content
+        .split_once(char::is_whitespace)
+        .unwrap_or((content, ""));
+    let directive = match keyword.to_uppercase().as_str() {
+        "IF" => evaluate_condition(arguments, defines).map(Directive::If),
+        "ELSIF" => evaluate_condition(arguments, defines).map(Directive::Elsif),
+        "ELSE" if arguments.trim().is_empty() => Ok(Directive::Else),
+        "END_IF" if arguments.trim().is_empty() => Ok(Directive::EndIf),
+        "DEFINE" => parse_name(arguments).map(Directive::Define),
+        "UNDEFINE" => parse_name(arguments).map(Directive::Undefine),
+        "ELSE" | "END_IF" => Err(()),
+        _ => return None,
+    };
+    Some(directive)
+}
+
+fn parse_name(text: &str) -> Result<String, ()> {
+    match split_words(text).as_slice() {
+        [name] if is_identifier(name) => Ok(name.to_string()),
+        _ => Err(()),
+    }
+}
+
+/// evaluates a condition made of `defined(name)`, `NOT`, `AND`, `OR` and parentheses
+fn evaluate_condition(condition: &str, defines: &HashSet<String>) -> Result<bool, ()> {
+    let words = split_words(condition);
+    let mut position = 0;
+    let result = evaluate_or(&words, &mut position, defines)?;
+    if position == words.len() {
+        Ok(result)
+    } else {
+        Err(())
+    }
+}
+
+fn evaluate_or(
+    words: &[&str],
+    position: &mut usize,
+    defines: &HashSet<String>,
+) -> Result<bool, ()> {
+    let mut result = evaluate_and(words, position, defines)?;
+    while is_keyword(words, *position, "OR") {
+        *position += 1;
+        //both sides are evaluated to detect invalid conditions
+        result |= evaluate_and(words, position, defines)?;
+    }
+    Ok(result)
+}
+
+fn evaluate_and(
+    words: &[&str],
+    position: &mut usize,
+    defines: &HashSet<String>,
+) -> Result<bool, ()> {
+    let mut result = evaluate_unary(words, position, defines)?;
+    while is_keyword(words, *position, "AND") {
+        *position += 1;
+        result &= evaluate_unary(words, position, defines)?;
+    }
+    Ok(result)
+}
+
+fn evaluate_unary(
+    words: &[&str],
+    position: &mut usize,
+    defines: &HashSet<String>,
+) -> Result<bool, ()> {
+    if is_keyword(words, *position, "NOT") {
+        *position += 1;
+        return evaluate_unary(words, position, defines).map(|it| !it);
+    }
+    if is_keyword(words, *position, "(") {
+        *position += 1;
+        let result = evaluate_or(words, position, defines)?;
+        return expect(words, position, ")").map(|_| result);
+    }
+    if is_keyword(words, *position, "defined") {
+        *position += 1;
+        expect(words, position, "(")?;
+        let name = words
+            .get(*position)
+            .filter(|it| is_identifier(it))
+            .ok_or(())?;
+        *position += 1;
+        expect(words, position, ")")?;
+        return Ok(defines.contains(&name.to_lowercase()));
+    }
+    Err(())
+}
+
+fn expect(words: &[&str], position: &mut usize, word: &str) -> Result<(), ()> {
+    if is_keyword(words, *position, word) {
+        *position += 1;
+        Ok(())
+    } else {
+        Err(())
+    }
+}
+
+fn is_keyword(words: &[&str], position: usize, keyword: &str) -> bool {
+    words
+        .get(position)
+        .map_or(false, |it| it.eq_ignore_ascii_case(keyword))
+}
+
+fn is_identifier(word: &str) -> bool {
+    !word.starts_with(|it: char| it.is_ascii_digit())
+        && word
+            .chars()
+            .all(|it| it.is_ascii_alphanumeric() || it == '_')
+}
+
+/// splits the given text into words and parentheses (e.g. `NOT (a)` into `NOT`, `(`, `a`, `)`)
+fn split_words(text: &str) -> Vec<&str> {
+    let mut words = vec![];
+    let mut start = None;
+    for (position, character) in text.char_indices() {
+        if character.is_whitespace() || character == '(' || character == ')' {
+            if let Some(start) = start.take() {
+                words.push(&text[start..position]);
+            }
+            if !character.is_whitespace() {
+                words.push(&text[position..position + 1]);
+            }
+        } else if start.is_none() {
+            start = Some(position);
+        }
+    }
+    if let Some(start) = start {
+        words.push(&text[start..]);
+    }
+    words
+}
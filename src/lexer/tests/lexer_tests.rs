@@ -2,8 +2,10 @@
 use pretty_assertions::{assert_eq, assert_ne};
 
 use crate::{
-    ast::{DirectAccessType, HardwareAccessType, SourceRange},
-    lexer::{lex, Token::*},
+    ast::{Attribute, DirectAccessType, HardwareAccessType, SourceRange, WarningPragma},
+    diagnostics::WarningLevel,
+    lexer::{lex, lex_file, lex_with_defines, IdProvider, Token::*},
+    Diagnostic, WarningCode,
 };
 
 #[test]
@@ -12,6 +14,181 @@ fn generic_properties() {
     assert_eq!(lexer.token, PropertyExternal);
 }
 
+#[test]
+fn external_pragma() {
+    let mut lexer = lex("{external} {EXTERNAL}");
+    assert_eq!(lexer.token, PragmaExternal);
+    lexer.advance();
+    assert_eq!(lexer.token, PragmaExternal);
+}
+
+#[test]
+fn pragmas_are_collected_as_attributes_of_the_next_token() {
+    let mut lexer = lex("{attribute 'pack' := '1'} {retain} {symbol export C} x {attribute 'y} y");
+    assert_eq!(lexer.token, Identifier);
+    assert_eq!(
+        lexer.take_attributes(),
+        vec![
+            Attribute {
+                name: "pack".into(),
+                value: Some("1".into()),
+                location: SourceRange::new(0..25),
+            },
+            Attribute {
+                name: "retain".into(),
+                value: None,
+                location: SourceRange::new(26..34),
+            },
+            Attribute {
+                name: "symbol".into(),
+                value: Some("export C".into()),
+                location: SourceRange::new(35..52),
+            },
+        ]
+    );
+    lexer.advance();
+    assert_eq!(lexer.slice(), "y");
+    assert_eq!(lexer.take_attributes(), vec![]);
+    assert_eq!(
+        lexer.diagnostics,
+        vec![Diagnostic::invalid_pragma(
+            "{attribute 'y}",
+            SourceRange::new(55..69)
+        )]
+    );
+}
+
+#[test]
+fn warning_pragmas_are_collected_for_the_unit() {
+    let mut lexer = lex(
+        "{warning allow var__unused_variable, pou__unused_pou} x {WARNING Restore var__unused_variable} {warning 'message'} {warning error var__unknown} y",
+    );
+    assert_eq!(lexer.slice(), "x");
+    lexer.advance();
+    assert_eq!(lexer.slice(), "y");
+    assert_eq!(
+        lexer.take_warning_pragmas(),
+        vec![
+            WarningPragma {
+                code: WarningCode::UnusedVariable,
+                level: Some(WarningLevel::Allow),
+                location: SourceRange::new(0..53),
+            },
+            WarningPragma {
+                code: WarningCode::UnusedPou,
+                level: Some(WarningLevel::Allow),
+                location: SourceRange::new(0..53),
+            },
+            WarningPragma {
+                code: WarningCode::UnusedVariable,
+                level: None,
+                location: SourceRange::new(56..94),
+            },
+        ]
+    );
+    //pragmas without a level are attributes
+    assert_eq!(
+        lexer.take_attributes(),
+        vec![Attribute {
+            name: "warning".into(),
+            value: Some("'message'".into()),
+            location: SourceRange::new(95..114),
+        }]
+    );
+    assert_eq!(
+        lexer.diagnostics,
+        vec![Diagnostic::invalid_pragma(
+            "{warning error var__unknown}",
+            SourceRange::new(115..143)
+        )]
+    );
+}
+
+#[test]
+fn locations_of_a_lexed_file_reference_the_file() {
+    let mut lexer = lex_file("PROGRAM prg", 3, IdProvider::default(), &[]);
+    lexer.advance();
+    assert_eq!(lexer.location(), SourceRange::in_file(8..11, Some(3)));
+    assert_eq!(lexer.last_location(), SourceRange::in_file(0..7, Some(3)));
+
+    //locations of sources lexed without a file do not reference a file
+    let lexer = lex("PROGRAM prg");
+    assert_eq!(lexer.location().get_file(), None);
+}
+
+/// returns the slices of the tokens parsed with the given defines
+fn lex_defined(source: &str, defines: &[&str]) -> Vec<String> {
+    let defines = defines.iter().map(|it| it.to_string()).collect::<Vec<_>>();
+    let mut lexer = lex_with_defines(source, IdProvider::default(), &defines);
+    let mut slices = vec![];
+    while !lexer.is_end_of_stream() {
+        slices.push(lexer.slice_and_advance());
+    }
+    assert_eq!(lexer.diagnostics, vec![]);
+    slices
+}
+
+#[test]
+fn conditional_regions_are_selected_by_the_defines() {
+    let source = "{IF defined(a)} x {ELSIF defined(B) AND NOT defined(c)} y {ELSE} z {END_IF} w";
+    assert_eq!(lex_defined(source, &["A", "b"]), vec!["x", "w"]);
+    assert_eq!(lex_defined(source, &["b"]), vec!["y", "w"]);
+    assert_eq!(lex_defined(source, &["b", "c"]), vec!["z", "w"]);
+    assert_eq!(lex_defined(source, &[]), vec!["z", "w"]);
+}
+
+#[test]
+fn nested_conditional_regions_and_defines() {
+    let source = "
+        {define d}
+        {IF NOT defined(d) OR (defined(e))}
+            a
+            {IF defined(d)} b {END_IF}
+        {ELSE}
+            {define e} {undefine d}
+            {IF defined(d)} c {ELSIF defined(e)} {attribute 'x'} d {END_IF}
+        {END_IF}";
+    assert_eq!(lex_defined(source, &[]), vec!["d"]);
+    assert_eq!(lex_defined(source, &["E"]), vec!["a", "b"]);
+}
+
+#[test]
+fn invalid_and_unmatched_directives_are_reported() {
+    let mut lexer = lex("{IF defined(a} x {END_IF} {ELSE} {IF defined(a)} y");
+    assert_eq!(lexer.slice_and_advance(), "x");
+    assert!(lexer.is_end_of_stream());
+    assert_eq!(
+        lexer.diagnostics,
+        vec![
+            Diagnostic::invalid_directive("{IF defined(a}", SourceRange::new(0..14)),
+            Diagnostic::unmatched_directive("{END_IF}", SourceRange::new(17..25)),
+            Diagnostic::unmatched_directive("{ELSE}", SourceRange::new(26..32)),
+            Diagnostic::unmatched_directive("{IF defined(a)}", SourceRange::new(33..48)),
+        ]
+    );
+}
+
+#[test]
+fn configuration_keywords() {
+    let mut lexer = lex("CONFIGURATION RESOURCE ON TASK WITH END_RESOURCE ENDRESOURCE VAR_CONFIG VARCONFIG END_CONFIGURATION ENDCONFIGURATION");
+    for token in [
+        KeywordConfiguration,
+        KeywordResource,
+        KeywordOn,
+        KeywordTask,
+        KeywordWith,
+        KeywordEndResource,
+        KeywordEndResource,
+        KeywordVarConfig,
+        KeywordVarConfig,
+        KeywordEndConfiguration,
+        KeywordEndConfiguration,
+    ] {
+        assert_eq!(lexer.token, token, "Token : {}", lexer.slice());
+        lexer.advance();
+    }
+}
+
 #[test]
 fn windows_and_linux_line_separators_ignored() {
     let mut lexer = lex("PROGRAM\r\nEND_PROGRAM");
@@ -101,6 +278,34 @@ fn unicode_chars_in_comments() {
     lexer.advance();
 }
 
+#[test]
+fn comments_are_collected_by_the_lexer() {
+    let mut lexer = lex("
+        (* block (* nested *) *) PROGRAM // line
+        /* other */ END_PROGRAM
+        ");
+    assert_eq!(lexer.token, KeywordProgram);
+    //the comment following the program on its line
+    lexer.advance();
+    let trailing = lexer.take_trailing_comments();
+    assert_eq!(
+        trailing
+            .iter()
+            .map(|it| it.text.as_str())
+            .collect::<Vec<_>>(),
+        vec!["// line"]
+    );
+    let comments = lexer.take_unattached_comments();
+    assert_eq!(
+        comments
+            .iter()
+            .map(|it| it.text.as_str())
+            .collect::<Vec<_>>(),
+        vec!["(* block (* nested *) *)", "/* other */"]
+    );
+    assert_eq!(comments[1].location, SourceRange::new(58..69));
+}
+
 #[test]
 fn pou_tokens() {
     let mut lexer =
@@ -196,13 +401,15 @@ fn comma() {
 
 #[test]
 fn operator_test() {
-    let mut lexer = lex("+ - * / MOD = <> < > <= >=");
+    let mut lexer = lex("+ - * ** / MOD = <> < > <= >=");
     assert_eq!(lexer.token, OperatorPlus);
     lexer.advance();
     assert_eq!(lexer.token, OperatorMinus);
     lexer.advance();
     assert_eq!(lexer.token, OperatorMultiplication);
     lexer.advance();
+    assert_eq!(lexer.token, OperatorExponent);
+    lexer.advance();
     assert_eq!(lexer.token, OperatorDivision);
     lexer.advance();
     assert_eq!(lexer.token, OperatorModulo);
@@ -222,7 +429,7 @@ fn operator_test() {
 
 #[test]
 fn boolean_expression_test() {
-    let mut lexer = lex("AND XOR OR NOT");
+    let mut lexer = lex("AND XOR OR NOT AND_THEN or_else");
     assert_eq!(lexer.token, OperatorAnd);
     lexer.advance();
     assert_eq!(lexer.token, OperatorXor);
@@ -230,6 +437,10 @@ fn boolean_expression_test() {
     assert_eq!(lexer.token, OperatorOr);
     lexer.advance();
     assert_eq!(lexer.token, OperatorNot);
+    lexer.advance();
+    assert_eq!(lexer.token, OperatorAndThen);
+    lexer.advance();
+    assert_eq!(lexer.token, OperatorOrElse);
 }
 
 #[test]
@@ -341,6 +552,28 @@ fn time_literals_test() {
     }
 }
 
+#[test]
+fn long_date_and_time_literals_test() {
+    let mut lexer = lex(r#"
+    LTIME#1d2h3.5s LT#12ms4us LDATE#1984-10-01 LD#1-1-1
+    LDATE_AND_TIME#1984-10-01-20:15:12 LDT#1-1-1-1:1:1.123456789
+    LTIME_OF_DAY#20:15:12 LTOD#1:1:1.123456789
+    "#);
+    for expected in [
+        LiteralTime,
+        LiteralTime,
+        LiteralDate,
+        LiteralDate,
+        LiteralDateAndTime,
+        LiteralDateAndTime,
+        LiteralTimeOfDay,
+        LiteralTimeOfDay,
+    ] {
+        assert_eq!(lexer.token, expected, "{}", lexer.slice());
+        lexer.advance();
+    }
+}
+
 #[test]
 fn a_full_program_generates_correct_token_sequence() {
     let mut lexer = lex(r"
@@ -490,6 +723,21 @@ fn struct_enum_datatype() {
     assert_eq!(lexer.token, KeywordEndType);
 }
 
+#[test]
+fn union_datatype() {
+    let mut lexer = lex(r"TYPE UNION END_UNION ENDUNION END_TYPE");
+
+    assert_eq!(lexer.token, KeywordType);
+    lexer.advance();
+    assert_eq!(lexer.token, KeywordUnion);
+    lexer.advance();
+    assert_eq!(lexer.token, KeywordEndUnion);
+    lexer.advance();
+    assert_eq!(lexer.token, KeywordEndUnion);
+    lexer.advance();
+    assert_eq!(lexer.token, KeywordEndType);
+}
+
 #[test]
 fn array_parsing() {
     let mut lexer = lex(r"ARRAY OF x[5]");
@@ -746,13 +994,16 @@ fn multi_named_keywords_without_underscore_test() {
 #[test]
 fn lowercase_keywords_accepted() {
     let mut result = lex(r###"
-        program class end_class endclass var_input varinput var_output
-        varoutput var abstract final method constant retain non_retain 
+        program class end_class endclass interface end_interface endinterface implements
+        extends namespace end_namespace endnamespace using
+        property end_property endproperty end_get endget end_set endset
+        var_input varinput var_output
+        varoutput var abstract final method constant retain non_retain persistent
         nonretain var_temp vartemp end_method endmethod
         public private internal protected override
         var_global varglobal var_in_out varinout end_var endvar
         end_program endprogram end_function endfunction end_function_block endfunctionblock
-        type struct end_type endtype end_struct endstruct 
+        type struct end_type endtype end_struct endstruct union end_union endunion 
         actions action end_action endaction end_actions endactions 
         if then elsif else endif end_if
         for to by do end_for endfor
@@ -5,15 +5,23 @@ use crate::ast::{DirectAccessType, HardwareAccessType};
 #[derive(Debug, PartialEq, Logos, Clone)]
 pub enum Token {
     #[error]
-    #[regex(r"\(\*", |lex| super::parse_comments(lex))]
-    #[regex(r"/\*", |lex| super::parse_comments(lex))]
-    #[regex(r"//.*", logos::skip)]
     #[regex(r"(?m)\r", logos::skip)]
     Error,
 
+    #[regex(r"\(\*", |lex| super::parse_comments(lex))]
+    #[regex(r"/\*", |lex| super::parse_comments(lex))]
+    #[regex(r"//.*")]
+    Comment,
+
     #[token("@EXTERNAL")]
     PropertyExternal,
 
+    #[token("{external}", ignore(case))]
+    PragmaExternal,
+
+    #[regex(r"\{[^}]*\}")]
+    Pragma,
+
     #[token("PROGRAM", ignore(case))]
     KeywordProgram,
 
@@ -24,6 +32,67 @@ pub enum Token {
     #[token("ENDCLASS", ignore(case))]
     KeywordEndClass,
 
+    #[token("INTERFACE", ignore(case))]
+    KeywordInterface,
+
+    #[token("END_INTERFACE", ignore(case))]
+    #[token("ENDINTERFACE", ignore(case))]
+    KeywordEndInterface,
+
+    #[token("IMPLEMENTS", ignore(case))]
+    KeywordImplements,
+
+    #[token("EXTENDS", ignore(case))]
+    KeywordExtends,
+
+    #[token("PROPERTY", ignore(case))]
+    KeywordProperty,
+
+    #[token("END_PROPERTY", ignore(case))]
+    #[token("ENDPROPERTY", ignore(case))]
+    KeywordEndProperty,
+
+    #[token("END_GET", ignore(case))]
+    #[token("ENDGET", ignore(case))]
+    KeywordEndGet,
+
+    #[token("END_SET", ignore(case))]
+    #[token("ENDSET", ignore(case))]
+    KeywordEndSet,
+
+    #[token("NAMESPACE", ignore(case))]
+    KeywordNamespace,
+
+    #[token("END_NAMESPACE", ignore(case))]
+    #[token("ENDNAMESPACE", ignore(case))]
+    KeywordEndNamespace,
+
+    #[token("USING", ignore(case))]
+    KeywordUsing,
+
+    #[token("CONFIGURATION", ignore(case))]
+    KeywordConfiguration,
+
+    #[token("END_CONFIGURATION", ignore(case))]
+    #[token("ENDCONFIGURATION", ignore(case))]
+    KeywordEndConfiguration,
+
+    #[token("RESOURCE", ignore(case))]
+    KeywordResource,
+
+    #[token("END_RESOURCE", ignore(case))]
+    #[token("ENDRESOURCE", ignore(case))]
+    KeywordEndResource,
+
+    #[token("ON", ignore(case))]
+    KeywordOn,
+
+    #[token("TASK", ignore(case))]
+    KeywordTask,
+
+    #[token("WITH", ignore(case))]
+    KeywordWith,
+
     #[token("VAR_INPUT", ignore(case))]
     #[token("VARINPUT", ignore(case))]
     KeywordVarInput,
@@ -50,6 +119,9 @@ pub enum Token {
     #[token("RETAIN", ignore(case))]
     KeywordRetain,
 
+    #[token("PERSISTENT", ignore(case))]
+    KeywordPersistent,
+
     #[token("NON_RETAIN", ignore(case))]
     #[token("NONRETAIN", ignore(case))]
     KeywordNonRetain,
@@ -85,6 +157,14 @@ pub enum Token {
     #[token("VARINOUT", ignore(case))]
     KeywordVarInOut,
 
+    #[token("VAR_EXTERNAL", ignore(case))]
+    #[token("VAREXTERNAL", ignore(case))]
+    KeywordVarExternal,
+
+    #[token("VAR_CONFIG", ignore(case))]
+    #[token("VARCONFIG", ignore(case))]
+    KeywordVarConfig,
+
     #[token("END_VAR", ignore(case))]
     #[token("ENDVAR", ignore(case))]
     KeywordEndVar,
@@ -122,6 +202,13 @@ pub enum Token {
     #[token("ENDSTRUCT", ignore(case))]
     KeywordEndStruct,
 
+    #[token("UNION", ignore(case))]
+    KeywordUnion,
+
+    #[token("END_UNION", ignore(case))]
+    #[token("ENDUNION", ignore(case))]
+    KeywordEndUnion,
+
     #[token("ACTIONS", ignore(case))]
     KeywordActions,
 
@@ -270,6 +357,9 @@ pub enum Token {
     #[token("*")]
     OperatorMultiplication,
 
+    #[token("**")]
+    OperatorExponent,
+
     #[token("/")]
     OperatorDivision,
 
@@ -303,9 +393,15 @@ pub enum Token {
     #[token("AND", ignore(case))]
     OperatorAnd,
 
+    #[token("AND_THEN", ignore(case))]
+    OperatorAndThen,
+
     #[token("OR", ignore(case))]
     OperatorOr,
 
+    #[token("OR_ELSE", ignore(case))]
+    OperatorOrElse,
+
     #[token("XOR", ignore(case))]
     OperatorXor,
 
@@ -338,19 +434,19 @@ pub enum Token {
     #[token("FALSE", ignore(case))]
     LiteralFalse,
 
-    #[regex("D(ATE)?#\\d+-\\d+-\\d+", ignore(case))]
+    #[regex("L?D(ATE)?#\\d+-\\d+-\\d+", ignore(case))]
     LiteralDate,
 
     #[regex(
-        "(DATE_AND_TIME|DT)#\\d+-\\d+-\\d+-\\d+:\\d+(:\\d+(\\.\\d+)?)?",
+        "L?(DATE_AND_TIME|DT)#\\d+-\\d+-\\d+-\\d+:\\d+(:\\d+(\\.\\d+)?)?",
         ignore(case)
     )]
     LiteralDateAndTime,
 
-    #[regex("(TIME_OF_DAY|TOD)#\\d+:\\d+(:\\d+(\\.\\d+)?)?", ignore(case))]
+    #[regex("L?(TIME_OF_DAY|TOD)#\\d+:\\d+(:\\d+(\\.\\d+)?)?", ignore(case))]
     LiteralTimeOfDay,
 
-    #[regex("T(IME)?#-?(\\d+(\\.\\d+)?(d|h|ms|m|s|us|ns))+", ignore(case))]
+    #[regex("L?T(IME)?#-?(\\d+(\\.\\d+)?(d|h|ms|m|s|us|ns))+", ignore(case))]
     LiteralTime,
 
     #[regex("%(B|b|D|d|W|w|X|x)", super::parse_access_type)]
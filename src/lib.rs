@@ -7,7 +7,7 @@
 //! ## Standard language support
 //! Most of the [`IEC61131-3`] standard for ST and general programing is supported.
 //! ## Native compilation
-//! A (currently) single ST files into object code using LLVM.
+//! One or more ST files are compiled into object code using LLVM.
 //! A compiled object can be linked statically or dynamically linked
 //!     with other programs using standard compiler linkers (ld, clang, gcc)
 //! ## IR Output
@@ -20,17 +20,22 @@
 use std::fs;
 use std::io::Write;
 use std::str::FromStr;
+use std::sync::Arc;
 
 use clap::ArgEnum;
 use codegen::CodeGen;
 use glob::glob;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::path::Path;
 
 use ast::{LinkageType, PouType, SourceRange};
-use cli::CompileParameters;
+use cache::UnitCache;
+use cli::{CompileParameters, SubCommand};
 use diagnostics::Diagnostic;
 use encoding_rs::Encoding;
 use encoding_rs_io::DecodeReaderBytesBuilder;
+use formatter::FormatOptions;
 use index::Index;
 use inkwell::context::Context;
 use inkwell::targets::{
@@ -39,22 +44,33 @@ use inkwell::targets::{
 use lexer::IdProvider;
 use resolver::{AstAnnotations, StringLiterals};
 use std::{fs::File, io::Read};
-use validation::Validator;
+use validation::{FlowValidator, RecursionValidator, UsageValidator, Validator};
 
 use crate::ast::CompilationUnit;
-use crate::diagnostics::Diagnostician;
+use crate::diagnostics::{DefaultDiagnosticAssessor, Diagnostician, ErrNo, WarningLevel};
 use crate::resolver::{AnnotationMapImpl, TypeAnnotator};
 mod ast;
+mod builtins;
+mod cache;
 pub mod cli;
 mod codegen;
+mod cross_reference;
 pub mod diagnostics;
+mod doc_generator;
 pub mod expression_path;
+mod formatter;
 mod hardware_binding;
+mod header_generator;
 pub mod index;
+mod index_dump;
 mod lexer;
+mod library;
 mod linker;
+mod lsp;
 mod parser;
 mod resolver;
+mod signature;
+mod task_table;
 mod test_utils;
 mod typesystem;
 mod validation;
@@ -73,6 +89,8 @@ pub enum FormatOption {
     Relocatable,
     Bitcode,
     IR,
+    /// a precompiled library (`.stlib`) with the index of its declarations and its object code
+    Library,
 }
 
 #[derive(PartialEq, Debug, Clone, Copy, ArgEnum)]
@@ -97,6 +115,297 @@ pub struct CompileOptions {
     pub format: FormatOption,
     pub output: String,
     pub target: Option<String>,
+    pub error_format: ErrorFormat,
+    /// report initializers that do not provide a value for every element as errors
+    pub deny_partial_initializers: bool,
+    /// report recursive calls as warnings instead of errors
+    pub warn_recursion: bool,
+    /// the levels of the warnings configured by `--allow`, `--warn` and `--error`
+    pub warning_levels: Vec<(WarningCode, WarningLevel)>,
+    /// report the warnings without a configured level as errors
+    pub warnings_as_errors: bool,
+    /// the file the report of the stack used by the generated POUs is written to
+    pub stack_report: Option<String>,
+    pub codegen: CodegenOptions,
+}
+
+impl CompileOptions {
+    /// creates the diagnostician reporting and assessing diagnostics as configured
+    fn create_diagnostician(&self) -> Diagnostician {
+        self.error_format
+            .create_diagnostician()
+            .with_assessor(Box::new(DefaultDiagnosticAssessor {
+                deny_partial_initializers: self.deny_partial_initializers,
+                warn_recursion: self.warn_recursion,
+            }))
+            .with_warning_levels(
+                self.warning_levels
+                    .iter()
+                    .map(|(code, level)| (code.get_err_no(), *level))
+                    .collect(),
+            )
+            .with_warnings_as_errors(self.warnings_as_errors)
+    }
+}
+
+/// the warnings whose level can be configured by `--allow`, `--warn`, `--error` and the
+/// `{warning <level> <code>}` pragmas, named like the codes they are reported with
+#[derive(PartialEq, Debug, Clone, Copy, ArgEnum, Serialize, Deserialize)]
+pub enum WarningCode {
+    /// a local variable that is never read
+    #[clap(name = "var__unused_variable")]
+    UnusedVariable,
+    /// a temporary variable that may be read before it is assigned
+    #[clap(name = "var__read_before_assignment")]
+    ReadBeforeAssignment,
+    /// a private method that is never called
+    #[clap(name = "pou__unused_pou")]
+    UnusedPou,
+    /// statements following a RETURN, EXIT or CONTINUE
+    #[clap(name = "statement__unreachable_code")]
+    UnreachableCode,
+    /// an initializer that does not provide a value for every element
+    #[clap(name = "var__partial_initializer")]
+    PartialInitializer,
+}
+
+impl WarningCode {
+    fn get_err_no(self) -> ErrNo {
+        match self {
+            WarningCode::UnusedVariable => ErrNo::var__unused_variable,
+            WarningCode::ReadBeforeAssignment => ErrNo::var__read_before_assignment,
+            WarningCode::UnusedPou => ErrNo::pou__unused_pou,
+            WarningCode::UnreachableCode => ErrNo::statement__unreachable_code,
+            WarningCode::PartialInitializer => ErrNo::var__partial_initializer,
+        }
+    }
+}
+
+/// the format the diagnostics of the compilation are reported in
+#[derive(PartialEq, Debug, Clone, Copy, ArgEnum)]
+pub enum ErrorFormat {
+    /// human readable diagnostics, showing the affected source code
+    Rich,
+    /// one line of JSON per diagnostic, to be parsed by IDEs and other tools
+    Json,
+}
+
+impl ErrorFormat {
+    /// creates the diagnostician reporting diagnostics in this format
+    fn create_diagnostician(self) -> Diagnostician {
+        match self {
+            ErrorFormat::Rich => Diagnostician::default(),
+            ErrorFormat::Json => Diagnostician::json_diagnostician(),
+        }
+    }
+}
+
+/// the format the parsed units are printed in by `--print-ast`
+#[derive(PartialEq, Debug, Clone, Copy, ArgEnum)]
+pub enum AstFormat {
+    /// the pretty-printed debug representation of the units
+    Text,
+    /// the units serialized as JSON, to be consumed by tools
+    Json,
+}
+
+impl AstFormat {
+    /// returns the given units printed in this format
+    fn print(self, units: &[&CompilationUnit]) -> String {
+        match self {
+            AstFormat::Text => units
+                .iter()
+                .map(|it| format!("{:#?}", it))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            AstFormat::Json => {
+                serde_json::to_string_pretty(units).expect("the AST can be serialized")
+            }
+        }
+    }
+}
+
+/// the format of the documentation generated by `--doc-out`
+#[derive(PartialEq, Debug, Clone, Copy, ArgEnum)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+impl DocFormat {
+    /// returns the name of the file the documentation is generated into
+    fn get_file_name(self) -> &'static str {
+        match self {
+            DocFormat::Markdown => "index.md",
+            DocFormat::Html => "index.html",
+        }
+    }
+}
+
+/// the case `rusty fmt` applies to the keywords
+#[derive(PartialEq, Debug, Clone, Copy, ArgEnum)]
+pub enum KeywordCase {
+    Upper,
+    Lower,
+    /// the keywords keep their case
+    Preserve,
+}
+
+/// options controlling the generated code
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CodegenOptions {
+    /// the sections retained global variables are generated into
+    pub sections: SectionNames,
+    /// the check generated for assignments to sub-range types without a check function
+    pub range_checks: Option<RangeCheckMode>,
+    /// the check generated for integer divisions (`/` and `MOD`) by zero
+    pub division_checks: Option<DivisionCheckMode>,
+    /// check every array index against the bounds of its dimension
+    pub bounds_checks: bool,
+    /// the behavior of integer arithmetic (`+`, `-` and `*`) whose result does not fit its type
+    pub overflow: OverflowMode,
+    /// generate the debug information describing the POUs and variables of the sources
+    pub debug: bool,
+    /// the optimizations applied to the generated code
+    pub optimization: OptimizationLevel,
+    /// the PROGRAM (or configured program instance) called cyclically by a generated `main`
+    pub main_program: Option<String>,
+    /// the target-triple the code is generated for
+    pub triple: Option<String>,
+    /// the data layout of the target (e.g. the width and alignment of pointers), defaults to the
+    /// layout of the target-triple
+    pub data_layout: Option<String>,
+    /// compile every source file into its own module and link them with link-time optimization
+    pub lto: Option<LtoMode>,
+    /// the prefix of the symbols defined by the compiled sources, so the objects of several
+    /// libraries can be linked together (see `codegen::get_symbol_name`)
+    pub symbol_prefix: Option<String>,
+    /// the names defined for the conditional compilation directives (e.g. `{IF defined(name)}`)
+    pub defines: Vec<String>,
+    /// the precompiled libraries (`.stlib`) whose declarations are used by the compiled sources
+    pub libraries: Vec<String>,
+    /// the directory caching the parsed source files, so only changed files are parsed again
+    pub cache_dir: Option<String>,
+    /// generate the code of every POU into `.text.<pou>` and its instance data into `.data.<pou>`
+    pub pou_sections: bool,
+    /// generate position-independent code for static and relocatable objects, bitcode and IR
+    /// are generated independent of the relocation model
+    pub position_independent: bool,
+    /// the file the cross-reference of the variables and POUs is written to
+    pub cross_reference: Option<String>,
+    /// the file the signatures of the generated POUs and the project are written to, the
+    /// signatures are generated into the module too
+    pub signatures: Option<String>,
+}
+
+/// the link-time optimization applied to the modules of the source files
+#[derive(PartialEq, Debug, Clone, Copy, ArgEnum)]
+pub enum LtoMode {
+    /// optimizes the modules individually, importing the functions they call from other modules
+    Thin,
+    /// links all modules into one module before optimizing it
+    Full,
+}
+
+/// the optimization level of the generated code (`-O0` to `-O3`)
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum OptimizationLevel {
+    /// no optimizations, the generated code is emitted as is
+    None,
+    Less,
+    Default,
+    Aggressive,
+}
+
+impl Default for OptimizationLevel {
+    fn default() -> Self {
+        OptimizationLevel::None
+    }
+}
+
+impl FromStr for OptimizationLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "0" => Ok(OptimizationLevel::None),
+            "1" => Ok(OptimizationLevel::Less),
+            "2" => Ok(OptimizationLevel::Default),
+            "3" => Ok(OptimizationLevel::Aggressive),
+            _ => Err(format!(
+                "Invalid optimization level {}, expected 0, 1, 2 or 3",
+                s
+            )),
+        }
+    }
+}
+
+impl From<OptimizationLevel> for inkwell::OptimizationLevel {
+    fn from(level: OptimizationLevel) -> Self {
+        match level {
+            OptimizationLevel::None => inkwell::OptimizationLevel::None,
+            OptimizationLevel::Less => inkwell::OptimizationLevel::Less,
+            OptimizationLevel::Default => inkwell::OptimizationLevel::Default,
+            OptimizationLevel::Aggressive => inkwell::OptimizationLevel::Aggressive,
+        }
+    }
+}
+
+/// the runtime check generated for assignments to sub-range types
+#[derive(PartialEq, Debug, Clone, Copy, ArgEnum)]
+pub enum RangeCheckMode {
+    /// aborts the program if the assigned value lies outside of the range
+    Trap,
+    /// limits the assigned value to the range's bounds
+    Saturate,
+}
+
+/// the runtime check generated for integer divisions (`/` and `MOD`) by zero
+#[derive(PartialEq, Debug, Clone, Copy, ArgEnum)]
+pub enum DivisionCheckMode {
+    /// aborts the program if the divisor is zero
+    Trap,
+    /// results in zero if the divisor is zero
+    Zero,
+    /// calls the fault handler `rusty_division_fault(location)` if the divisor is zero, the
+    /// division results in zero if the handler returns
+    Fault,
+}
+
+/// the behavior of integer arithmetic (`+`, `-` and `*`) whose result does not fit its type,
+/// applied to the generated code and to the constants evaluated by the compiler
+#[derive(PartialEq, Debug, Clone, Copy, ArgEnum)]
+pub enum OverflowMode {
+    /// the result wraps around (e.g. `INT#32767 + 1 = -32768`)
+    Wrap,
+    /// aborts the program if the result overflows
+    Trap,
+    /// limits the result to the type's bounds (e.g. `INT#32767 + 1 = 32767`)
+    Saturate,
+}
+
+impl Default for OverflowMode {
+    fn default() -> Self {
+        OverflowMode::Wrap
+    }
+}
+
+/// the names of the object-file sections used for retained global variables
+#[derive(Debug, Clone, PartialEq)]
+pub struct SectionNames {
+    /// the section for variables declared in a `VAR_GLOBAL RETAIN` block
+    pub retain: String,
+    /// the section for variables declared in a `VAR_GLOBAL PERSISTENT` block
+    pub persistent: String,
+}
+
+impl Default for SectionNames {
+    fn default() -> Self {
+        SectionNames {
+            retain: ".retain".to_string(),
+            persistent: ".persistent".to_string(),
+        }
+    }
 }
 
 pub struct LinkOptions {
@@ -119,7 +428,7 @@ pub struct CompileResult {
 
 /// SourceContainers offer source-code to be compiled via the load_source function.
 /// Furthermore it offers a location-String used when reporting diagnostics.
-pub trait SourceContainer {
+pub trait SourceContainer: Send {
     /// loads and returns the SourceEntry that contains the SourceCode and the path it was loaded from
     fn load_source(self, encoding: Option<&'static Encoding>) -> Result<SourceCode, String>;
     /// returns the location of this source-container. Used when reporting diagnostics.
@@ -159,7 +468,9 @@ impl SourceContainer for FilePath {
             Err(format!("{} is not a source file", &self.path))
         } else {
             let mut file = File::open(&self.path).map_err(|err| err.to_string())?;
-            let source = create_source_code(&mut file, encoding)?;
+            //every file is read into its own buffer, allocated once with the size of the file
+            let size = file.metadata().map_or(0, |it| it.len() as usize);
+            let source = create_source_code(&mut file, encoding, size)?;
 
             Ok(SourceCode {
                 source,
@@ -211,11 +522,17 @@ impl From<String> for SourceCode {
     }
 }
 
+/// decodes the source read from the given reader, `size` is the expected size of the source
+/// in bytes (e.g. the size of the file) or 0 if it is not known. A buffer of the expected size
+/// is allocated once instead of growing while it is read, which avoids copying it on every
+/// growth and leaving up to half of it unused. This only lowers the memory used while reading,
+/// the sources themselves are kept for the diagnostics until the compilation ends
 fn create_source_code<T: Read>(
     reader: &mut T,
     encoding: Option<&'static Encoding>,
+    size: usize,
 ) -> Result<String, String> {
-    let mut buffer = String::new();
+    let mut buffer = String::with_capacity(size);
     let mut decoder = DecodeReaderBytesBuilder::new()
         .encoding(encoding)
         .build(reader);
@@ -240,37 +557,46 @@ fn persist_to_obj(
     reloc: RelocMode,
     triple: &TargetTriple,
 ) -> Result<(), Diagnostic> {
+    let machine = create_target_machine(triple, codegen.get_optimization_level(), reloc);
+
+    machine.and_then(|it| {
+        it.write_to_file(&codegen.module, FileType::Object, Path::new(output))
+            .map_err(|it| Diagnostic::llvm_error(output, &it))
+    })
+}
+
+///
+/// Creates the machine generating code for the given target-triple
+/// returns an error if the target is not supported by LLVM
+///
+fn create_target_machine(
+    triple: &TargetTriple,
+    optimization: OptimizationLevel,
+    reloc: RelocMode,
+) -> Result<TargetMachine, Diagnostic> {
     let initialization_config = &InitializationConfig::default();
     Target::initialize_all(initialization_config);
 
     let target = Target::from_triple(triple).map_err(|it| {
-        Diagnostic::codegen_error(
-            &format!("Invalid target-tripple '{:}' - {:?}", triple, it),
-            SourceRange::undefined(),
-        )
+        Diagnostic::param_error(&format!("Unsupported target-triple '{}' - {}", triple, it))
     })?;
-    let machine = target
+    target
         .create_target_machine(
             triple,
             //TODO : Add cpu features as optionals
             "generic", //TargetMachine::get_host_cpu_name().to_string().as_str(),
             "",        //TargetMachine::get_host_cpu_features().to_string().as_str(),
-            //TODO Optimisation as parameter
-            inkwell::OptimizationLevel::Default,
+            optimization.into(),
             reloc,
             CodeModel::Default,
         )
         .ok_or_else(|| {
             Diagnostic::codegen_error("Cannot create target machine.", SourceRange::undefined())
-        });
-
-    machine.and_then(|it| {
-        it.write_to_file(&codegen.module, FileType::Object, Path::new(output))
-            .map_err(|it| Diagnostic::llvm_error(output, &it))
-    })
+        })
 }
 
-/// Persists a given LLVM module to a static object and saves the output.
+/// Persists a given LLVM module to a static object and saves the output. The object is
+/// position-independent if the module was generated with `position_independent`.
 ///
 /// # Arguments
 ///
@@ -283,7 +609,12 @@ pub fn persist_as_static_obj(
     output: &str,
     target: &TargetTriple,
 ) -> Result<(), Diagnostic> {
-    persist_to_obj(codegen, output, RelocMode::Default, target)
+    let reloc = if codegen.is_position_independent() {
+        RelocMode::PIC
+    } else {
+        RelocMode::Default
+    };
+    persist_to_obj(codegen, output, reloc, target)
 }
 
 /// Persists a given LLVM module to a shared postiion indepedent object and saves the output.
@@ -349,67 +680,125 @@ pub fn persist_to_ir(codegen: CodeGen, output: &str) -> Result<(), Diagnostic> {
         .map_err(|err| Diagnostic::io_write_error(output, err.to_string().as_str()))
 }
 
-///
-/// Compiles the given source into a `codegen::CodeGen` using the provided context
-///
-/// # Arguments
-///
-/// * `context` - the LLVM Context to be used for the compilation
-/// * `sources` - the source to be compiled
-/// * `encoding` - The encoding to parse the files, None for UTF-8
-pub fn compile_module<'c, T: SourceContainer>(
-    context: &'c Context,
+/// the units of a compilation after the front end parsed, indexed, annotated and validated them
+#[derive(Default)]
+pub(crate) struct AnalysedSources {
+    /// the annotated units with the id of their file, including the generic instances
+    pub units: Vec<(usize, CompilationUnit)>,
+    pub index: Index,
+    pub annotations: AnnotationMapImpl,
+    pub literals: StringLiterals,
+    pub id_provider: IdProvider,
+}
+
+/// Runs the front end over the given sources: parses and indexes them together with the
+/// included files, the precompiled libraries and the standard function blocks they rely on,
+/// evaluates the constants, instantiates the generic functions and annotates and validates all
+/// units. The diagnostics are reported to the given diagnostician, the caller decides whether
+/// the units are fit to be generated.
+pub(crate) fn analyse_sources<T: SourceContainer, I: SourceContainer>(
     sources: Vec<T>,
-    includes: Vec<T>,
+    includes: Vec<I>,
     encoding: Option<&'static Encoding>,
-    mut diagnostician: Diagnostician,
-) -> Result<(Index, CodeGen<'c>), Diagnostic> {
+    diagnostician: &mut Diagnostician,
+    options: &CodegenOptions,
+) -> Result<AnalysedSources, Diagnostic> {
     let mut full_index = Index::default();
-    let mut id_provider = IdProvider::default();
+    let id_provider = IdProvider::default();
 
     let mut all_units = Vec::new();
 
     // ### PHASE 1 ###
+    // import the declarations of the precompiled libraries, their expressions are annotated
+    // here because they are not part of a unit
+    let mut library_annotations = Vec::new();
+    for library in &options.libraries {
+        let library_index = library::read_library_index(library, &id_provider)?;
+        library_annotations.push(TypeAnnotator::visit_const_expressions(&library_index));
+        full_index.import(library_index);
+    }
+
     // parse & index everything
-    let (index, mut units) = parse_and_index(
+    let cache = options
+        .cache_dir
+        .as_deref()
+        .map(UnitCache::new)
+        .transpose()?;
+    let mut units = parse_sources(
         sources,
         encoding,
         &id_provider,
-        &mut diagnostician,
+        diagnostician,
         LinkageType::Internal,
+        &options.defines,
+        cache.as_ref(),
     )?;
-    full_index.import(index);
-    all_units.append(&mut units);
-
-    let (includes_index, mut includes_units) = parse_and_index(
+    let mut includes_units = parse_sources(
         includes,
         encoding,
         &id_provider,
-        &mut diagnostician,
+        diagnostician,
         LinkageType::External,
+        &options.defines,
+        cache.as_ref(),
     )?;
+
+    // qualify the references to namespaced declarations across all files
+    index::namespaces::resolve_namespaces(
+        &mut units
+            .iter_mut()
+            .chain(includes_units.iter_mut())
+            .map(|(_, _, unit)| unit)
+            .collect::<Vec<_>>(),
+    );
+
+    full_index.import(pre_process_and_index(&mut units, &id_provider));
+    all_units.append(&mut units);
+    let mut includes_index = pre_process_and_index(&mut includes_units, &id_provider);
+    //the bodies and initializers of the included declarations are defined by their library
+    includes_index.make_initializers_external();
     full_index.import(includes_index);
     all_units.append(&mut includes_units);
 
+    // add the standard function blocks the user relies on without declaring them
+    let mut builtins_units = parse_sources(
+        builtins::get_missing_function_blocks(&full_index),
+        encoding,
+        &id_provider,
+        diagnostician,
+        LinkageType::Internal,
+        &[],
+        None,
+    )?;
+    full_index.import(pre_process_and_index(&mut builtins_units, &id_provider));
+    all_units.append(&mut builtins_units);
+
     // ### PHASE 1.1 resolve constant literal values
     let (mut full_index, _unresolvables) =
-        resolver::const_evaluator::evaluate_constants(full_index);
+        resolver::const_evaluator::evaluate_constants_with_overflow(full_index, options.overflow);
 
     // ### PHASE 2 ###
     // annotation & validation everything
-    let mut annotated_units: Vec<CompilationUnit> = Vec::new();
+    let mut annotated_units: Vec<(usize, CompilationUnit)> = Vec::new();
     let mut all_annotations = AnnotationMapImpl::default();
     let mut all_literals = StringLiterals::default();
+    for (annotations, string_literals) in library_annotations {
+        all_annotations.import(annotations);
+        all_literals.import(string_literals);
+    }
     for (file_id, syntax_errors, unit) in all_units.into_iter() {
         let (annotations, string_literals) = TypeAnnotator::visit_unit(&full_index, &unit);
 
         let mut validator = Validator::new();
         validator.visit_unit(&annotations, &full_index, &unit);
+        let mut flow_validator = FlowValidator::new();
+        flow_validator.visit_unit(&annotations, &full_index, &unit);
         //log errors
         diagnostician.handle(syntax_errors, file_id);
         diagnostician.handle(validator.diagnostics(), file_id);
+        diagnostician.handle(flow_validator.diagnostics, file_id);
 
-        annotated_units.push(unit);
+        annotated_units.push((file_id, unit));
         all_annotations.import(annotations);
         all_literals.import(string_literals);
     }
@@ -417,51 +806,232 @@ pub fn compile_module<'c, T: SourceContainer>(
     //Merge the new indices with the full index
     full_index.import(std::mem::take(&mut all_annotations.new_index));
 
+    // ### PHASE 2.1 ###
+    // instantiate the generic functions for the concrete types they are called with,
+    // an instance may call further generic functions
+    loop {
+        let instances = resolver::generics::create_generic_instances(
+            &annotated_units,
+            &all_annotations.generic_instances,
+            id_provider.clone(),
+        );
+        if instances.is_empty() {
+            break;
+        }
+        for (file_id, unit) in instances {
+            let (annotations, string_literals) = TypeAnnotator::visit_unit(&full_index, &unit);
+
+            let mut validator = Validator::new();
+            validator.visit_unit(&annotations, &full_index, &unit);
+            diagnostician.handle(validator.diagnostics(), file_id);
+
+            annotated_units.push((file_id, unit));
+            all_annotations.import(annotations);
+            all_literals.import(string_literals);
+        }
+        full_index.import(std::mem::take(&mut all_annotations.new_index));
+    }
+
+    // ### PHASE 2.2 ###
+    // report the unused declarations, a declaration may be used by any unit
+    let usage_validator =
+        UsageValidator::new(annotated_units.iter().map(|(_, it)| it), &all_annotations);
+    for (file_id, unit) in &annotated_units {
+        diagnostician.handle(usage_validator.validate_unit(unit, &full_index), *file_id);
+    }
+
+    // ### PHASE 2.3 ###
+    // report the recursions, a recursion may span the POUs of several units
+    let recursion_validator = RecursionValidator::new(
+        annotated_units.iter().map(|(_, it)| it),
+        &all_annotations,
+        &full_index,
+    );
+    for (file_id, unit) in &annotated_units {
+        diagnostician.handle(recursion_validator.validate_unit(unit), *file_id);
+    }
+
+    Ok(AnalysedSources {
+        units: annotated_units,
+        index: full_index,
+        annotations: all_annotations,
+        literals: all_literals,
+        id_provider,
+    })
+}
+
+///
+/// Compiles the given source into a `codegen::CodeGen` using the provided context
+///
+/// # Arguments
+///
+/// * `context` - the LLVM Context to be used for the compilation
+/// * `sources` - the source to be compiled
+/// * `encoding` - The encoding to parse the files, None for UTF-8
+/// * `options` - the options controlling the generated code
+pub fn compile_module<'c, T: SourceContainer>(
+    context: &'c Context,
+    sources: Vec<T>,
+    includes: Vec<T>,
+    encoding: Option<&'static Encoding>,
+    mut diagnostician: Diagnostician,
+    options: CodegenOptions,
+) -> Result<(Index, CodeGen<'c>), Diagnostic> {
+    let AnalysedSources {
+        units: annotated_units,
+        index: full_index,
+        annotations: all_annotations,
+        literals: all_literals,
+        mut id_provider,
+    } = analyse_sources(sources, includes, encoding, &mut diagnostician, &options)?;
+
+    //the code of erroneous sources is not generated
+    let errors = diagnostician.error_count();
+    if errors > 0 {
+        return Err(Diagnostic::compilation_aborted(errors));
+    }
+
+    // ### PHASE 2.4 ###
+    // write the cross-reference of the sources, the standard function blocks are not listed
+    if let Some(output) = &options.cross_reference {
+        let cross_reference = cross_reference::collect_cross_reference(
+            annotated_units
+                .iter()
+                .map(|(_, it)| it)
+                .filter(|it| !it.file_name.starts_with(builtins::BUILTINS_PATH)),
+            &all_annotations,
+            &full_index,
+        );
+        let generated = cross_reference::generate_cross_reference(&cross_reference)?;
+        File::create(output)
+            .and_then(|mut it| it.write_all(generated.as_bytes()))
+            .map_err(|it| Diagnostic::io_write_error(output, &it.to_string()))?;
+    }
+
+    let signatures = options.signatures.is_some().then(|| {
+        signature::calculate_signatures(
+            annotated_units.iter().map(|(_, it)| it),
+            &all_annotations,
+            &full_index,
+        )
+    });
+
     // ### PHASE 3 ###
     // - codegen
-    let code_generator = codegen::CodeGen::new(context, "main");
+    //the modules compiled for link-time optimization are prefixed once they are linked
+    let prefix_symbols = options.lto.is_none();
+    let code_generator = codegen::CodeGen::new(context, "main").with_options(options);
 
     let annotations = AstAnnotations::new(all_annotations, id_provider.next_id());
     //Associate the index type with LLVM types
     let llvm_index = code_generator.generate_llvm_index(&annotations, all_literals, &full_index)?;
-    for unit in annotated_units {
-        code_generator.generate(&unit, &annotations, &full_index, &llvm_index)?;
+    for (file_id, unit) in annotated_units {
+        code_generator
+            .generate(&unit, &annotations, &full_index, &llvm_index)
+            .map_err(|err| {
+                //report the error against the file it originated from, once it is reported the
+                //compilation is aborted like for the errors of the validation
+                diagnostician.handle(vec![err.clone()], file_id);
+                match diagnostician.error_count() {
+                    0 => err,
+                    errors => Diagnostic::compilation_aborted(errors),
+                }
+            })?;
+    }
+    if let Some(signatures) = &signatures {
+        code_generator.generate_pou_signatures(signatures);
     }
+    code_generator.assign_pou_sections(&full_index);
+    if prefix_symbols {
+        code_generator.prefix_symbols(&full_index);
+    }
+    code_generator.generate_main(&full_index, &llvm_index)?;
+    code_generator.finalize_debug_info();
+    code_generator.remove_unused_intrinsics();
+    code_generator.optimize();
     Ok((full_index, code_generator))
 }
 
 type Units = Vec<(usize, Vec<Diagnostic>, CompilationUnit)>;
-fn parse_and_index<T: SourceContainer>(
+fn parse_sources<T: SourceContainer>(
     source: Vec<T>,
     encoding: Option<&'static Encoding>,
     id_provider: &IdProvider,
     diagnostician: &mut Diagnostician,
     linkage: LinkageType,
-) -> Result<(Index, Units), Diagnostic> {
-    let mut index = Index::default();
-    let mut units = Vec::new();
-
-    for container in source {
-        let location: String = container.get_location().into();
-        let e = container
-            .load_source(encoding)
-            .map_err(|err| Diagnostic::io_read_error(location.as_str(), err.as_str()))?;
-
-        let (mut parse_result, diagnostics) = parser::parse(
-            lexer::lex_with_ids(e.source.as_str(), id_provider.clone()),
-            linkage,
-        );
-
-        //pre-process the ast (create inlined types)
-        ast::pre_process(&mut parse_result, id_provider.clone());
-        //index the pou
-        index.import(index::visitor::visit(&parse_result, id_provider.clone()));
+    defines: &[String],
+    cache: Option<&UnitCache>,
+) -> Result<Units, Diagnostic> {
+    //the files are loaded in parallel
+    let sources = source
+        .into_par_iter()
+        .map(|container| {
+            let location: String = container.get_location().into();
+            container
+                .load_source(encoding)
+                .map_err(|err| Diagnostic::io_read_error(location.as_str(), err.as_str()))
+        })
+        .collect::<Result<Vec<_>, Diagnostic>>()?;
+
+    //register the files with the diagnstician, so diagnostics are later able to show snippets from the code
+    //the files are registered before they are parsed, so the parsed locations reference their file
+    //the diagnostician shares the source with the lexer instead of keeping a copy of it
+    let files = sources
+        .into_iter()
+        .map(|e| {
+            let source: Arc<str> = e.source.into();
+            let file_id = diagnostician.register_file(e.path.clone(), source.clone());
+            (file_id, e.path, source)
+        })
+        .collect::<Vec<_>>();
+
+    //the files are lexed and parsed in parallel
+    let units = files
+        .into_par_iter()
+        .map(|(file_id, path, source)| {
+            let cached_unit =
+                cache.and_then(|it| it.load(&source, file_id, linkage, defines, id_provider));
+            let (mut parse_result, diagnostics) = match cached_unit {
+                Some(unit) => (unit, vec![]),
+                None => {
+                    let (unit, diagnostics) = parser::parse(
+                        lexer::lex_file(&source, file_id, id_provider.clone(), defines),
+                        linkage,
+                    );
+                    //units with syntax errors are parsed again, so their errors are reported again
+                    if let Some(cache) = cache.filter(|_| diagnostics.is_empty()) {
+                        cache.store(&source, linkage, defines, &unit);
+                    }
+                    (unit, diagnostics)
+                }
+            };
+            parse_result.file_name = path;
+            parse_result.new_lines = ast::NewLines::new(&source);
+            (file_id, diagnostics, parse_result)
+        })
+        .collect::<Units>();
+    for (file_id, _, unit) in &units {
+        diagnostician.register_warning_pragmas(*file_id, &unit.warning_pragmas);
+    }
+    Ok(units)
+}
 
-        //register the file with the diagnstician, so diagnostics are later able to show snippets from the code
-        let file_id = diagnostician.register_file(location.clone(), e.source);
-        units.push((file_id, diagnostics, parse_result));
+fn pre_process_and_index(units: &mut Units, id_provider: &IdProvider) -> Index {
+    //the units are pre-processed and indexed in parallel, their indexes are imported in order
+    let unit_indexes = units
+        .par_iter_mut()
+        .map(|(_, _, unit)| {
+            //pre-process the ast (create inlined types)
+            ast::pre_process(unit, id_provider.clone());
+            //index the pou
+            index::visitor::visit(unit, id_provider.clone())
+        })
+        .collect::<Vec<_>>();
+    let mut index = Index::default();
+    for unit_index in unit_indexes {
+        index.import(unit_index);
     }
-    Ok((index, units))
+    index
 }
 
 fn create_file_paths(inputs: &[String]) -> Result<Vec<FilePath>, Diagnostic> {
@@ -495,6 +1065,28 @@ fn create_file_paths(inputs: &[String]) -> Result<Vec<FilePath>, Diagnostic> {
 /// Links any provided libraries
 /// Returns the location of the output file
 pub fn build_with_params(parameters: CompileParameters) -> Result<(), Diagnostic> {
+    if parameters.lsp {
+        //the open documents are analysed like the sources of a compilation
+        let options = lsp::AnalysisOptions {
+            includes: if parameters.includes.is_empty() {
+                vec![]
+            } else {
+                create_file_paths(&parameters.includes)?
+            },
+            encoding: parameters.encoding,
+            codegen: CodegenOptions {
+                overflow: parameters.overflow,
+                defines: parameters.defines,
+                libraries: parameters.precompiled_libraries,
+                ..CodegenOptions::default()
+            },
+        };
+        return lsp::run_server(std::io::stdin().lock(), std::io::stdout(), options);
+    }
+    if let Some(SubCommand::Fmt(format)) = &parameters.command {
+        let files = create_file_paths(&format.input)?;
+        return format_files(files, &format.format_options(), format.check);
+    }
     let files = create_file_paths(&parameters.input)?;
     let includes = if parameters.includes.is_empty() {
         vec![]
@@ -516,12 +1108,65 @@ pub fn build_with_params(parameters: CompileParameters) -> Result<(), Diagnostic
             output: config.to_owned(),
         });
 
+    let task_config_options = parameters
+        .task_config
+        .as_ref()
+        .map(|config| ConfigurationOptions {
+            format: parameters
+                .task_config_format()
+                .expect("Never none for valid parameters"),
+            output: config.to_owned(),
+        });
+
     let compile_options = CompileOptions {
         output,
         target: parameters.target,
         format: out_format,
+        error_format: parameters.error_format,
+        deny_partial_initializers: parameters.deny_partial_initializers,
+        warn_recursion: parameters.warn_recursion,
+        warning_levels: parameters.warning_levels(),
+        warnings_as_errors: parameters.werror,
+        stack_report: parameters.stack_report,
+        codegen: CodegenOptions {
+            sections: parameters.section_names(),
+            range_checks: parameters.range_checks,
+            division_checks: parameters.division_checks,
+            bounds_checks: parameters.bounds_checks,
+            overflow: parameters.overflow,
+            debug: parameters.debug,
+            optimization: parameters.optimization,
+            main_program: parameters.main_program,
+            triple: None,
+            data_layout: parameters.data_layout,
+            lto: parameters.lto,
+            symbol_prefix: parameters.symbol_prefix,
+            defines: parameters.defines,
+            libraries: parameters.precompiled_libraries,
+            cache_dir: parameters.cache_dir,
+            cross_reference: parameters.xref_out,
+            signatures: parameters.signatures,
+            pou_sections: parameters.pou_sections,
+            position_independent: parameters.position_independent,
+        },
     };
 
+    //the parsed units are printed instead of compiling them
+    if let Some(format) = parameters.print_ast {
+        return print_ast(files, &compile_options, parameters.encoding, format);
+    }
+
+    //the documentation of the sources is generated instead of compiling them
+    if let Some(doc_out) = parameters.doc_out {
+        return document_files(
+            files,
+            &compile_options,
+            parameters.encoding,
+            &doc_out,
+            parameters.doc_format,
+        );
+    }
+
     let link_options = if !parameters.skip_linking {
         Some(LinkOptions {
             libraries: parameters.libraries,
@@ -533,7 +1178,7 @@ pub fn build_with_params(parameters: CompileParameters) -> Result<(), Diagnostic
     };
 
     let target = get_target_triple(compile_options.target.as_deref());
-    let compile_result = build(
+    let mut compile_result = build(
         files,
         includes,
         &compile_options,
@@ -542,6 +1187,12 @@ pub fn build_with_params(parameters: CompileParameters) -> Result<(), Diagnostic
     )?;
 
     if let Some(link_options) = link_options {
+        //the object code of the precompiled libraries is linked with the compiled sources
+        for library in &compile_options.codegen.libraries {
+            compile_result
+                .objects
+                .push(library::extract_library_object(library)?);
+        }
         link(
             &compile_options.output,
             compile_options.format,
@@ -566,14 +1217,183 @@ pub fn build_with_params(parameters: CompileParameters) -> Result<(), Diagnostic
             })?;
     }
 
+    if let Some(config) = task_config_options {
+        let task_table = task_table::collect_task_table(&compile_result.index)?;
+        let generated_conf = task_table::generate_task_table(&task_table, config.format)?;
+
+        File::create(&config.output)
+            .and_then(|mut it| it.write_all(generated_conf.as_bytes()))
+            .map_err(|it| Diagnostic::io_write_error(&config.output, &it.to_string()))?;
+    }
+
+    if let Some(header_out) = parameters.header_out {
+        let name = Path::new(&header_out)
+            .file_stem()
+            .and_then(|it| it.to_str())
+            .unwrap_or("header");
+        let header = header_generator::generate_header(
+            &compile_result.index,
+            name,
+            compile_options.codegen.symbol_prefix.as_deref(),
+        );
+        File::create(&header_out)
+            .and_then(|mut it| it.write_all(header.as_bytes()))
+            .map_err(|it| Diagnostic::io_write_error(&header_out, &it.to_string()))?;
+    }
+
+    if parameters.print_index {
+        let dump = index_dump::collect_index_dump(&compile_result.index);
+        let generated_dump = index_dump::generate_index_dump(&dump)?;
+        std::io::stdout()
+            .write_all(generated_dump.as_bytes())
+            .map_err(|it| Diagnostic::io_write_error("stdout", &it.to_string()))?;
+    }
+
     Ok(())
 }
 
+/// Parses the given source files and prints their units in the given format to std-out.
+/// The units are printed as they are parsed, they are not indexed, validated or generated
+pub fn print_ast(
+    files: Vec<FilePath>,
+    compile_options: &CompileOptions,
+    encoding: Option<&'static Encoding>,
+    format: AstFormat,
+) -> Result<(), Diagnostic> {
+    let sources = files.into_iter().filter(|it| !it.is_object()).collect();
+    let mut diagnostician = compile_options.create_diagnostician();
+    let units = parse_sources(
+        sources,
+        encoding,
+        &IdProvider::default(),
+        &mut diagnostician,
+        LinkageType::Internal,
+        &compile_options.codegen.defines,
+        None,
+    )?;
+    //the units of files with syntax errors are printed too, to debug the parser
+    for (file_id, syntax_errors, _) in &units {
+        diagnostician.handle(syntax_errors.clone(), *file_id);
+    }
+    let units = units.iter().map(|(_, _, unit)| unit).collect::<Vec<_>>();
+    std::io::stdout()
+        .write_all(format.print(&units).as_bytes())
+        .map_err(|it| Diagnostic::io_write_error("stdout", &it.to_string()))
+}
+
+/// Generates the API documentation of the given source files from their doc comments into the
+/// given directory. The sources are parsed and indexed to resolve the documented types, they
+/// are not validated or generated
+pub fn document_files(
+    files: Vec<FilePath>,
+    compile_options: &CompileOptions,
+    encoding: Option<&'static Encoding>,
+    output: &str,
+    format: DocFormat,
+) -> Result<(), Diagnostic> {
+    let sources = files.into_iter().filter(|it| !it.is_object()).collect();
+    let mut diagnostician = compile_options.create_diagnostician();
+    let id_provider = IdProvider::default();
+    let mut units = parse_sources(
+        sources,
+        encoding,
+        &id_provider,
+        &mut diagnostician,
+        LinkageType::Internal,
+        &compile_options.codegen.defines,
+        None,
+    )?;
+    //the declarations of files with syntax errors are documented as far as they were parsed
+    for (file_id, syntax_errors, _) in &units {
+        diagnostician.handle(syntax_errors.clone(), *file_id);
+    }
+    index::namespaces::resolve_namespaces(
+        &mut units
+            .iter_mut()
+            .map(|(_, _, unit)| unit)
+            .collect::<Vec<_>>(),
+    );
+    let index = pre_process_and_index(&mut units, &id_provider);
+    let (index, _) = resolver::const_evaluator::evaluate_constants(index);
+
+    let units = units.iter().map(|(_, _, unit)| unit).collect::<Vec<_>>();
+    let documentation = doc_generator::collect_documentation(&units, &index);
+    let content = doc_generator::generate_documentation(&documentation, format);
+    let path = Path::new(output).join(format.get_file_name());
+    fs::create_dir_all(output)
+        .and_then(|_| fs::write(&path, content))
+        .map_err(|it| Diagnostic::io_write_error(&path.to_string_lossy(), &it.to_string()))
+}
+
+/// Formats the given source files in place, files with syntax errors are reported and not
+/// formatted. With `check` the files are not changed, the files that are not formatted are
+/// printed to std-out and reported as an error instead
+pub fn format_files(
+    files: Vec<FilePath>,
+    options: &FormatOptions,
+    check: bool,
+) -> Result<(), Diagnostic> {
+    let sources = files
+        .into_iter()
+        .filter(|it| !it.is_object())
+        .map(|it| {
+            let path = it.path.clone();
+            it.load_source(None)
+                .map_err(|err| Diagnostic::io_read_error(&path, &err))
+        })
+        .collect::<Result<Vec<_>, Diagnostic>>()?;
+    let mut diagnostician = Diagnostician::default();
+    let units = parse_sources(
+        sources.clone(),
+        None,
+        &IdProvider::default(),
+        &mut diagnostician,
+        LinkageType::Internal,
+        &[],
+        None,
+    )?;
+    let mut unformatted = 0;
+    for ((file_id, syntax_errors, _), source) in units.into_iter().zip(sources) {
+        let errors = diagnostician.error_count();
+        diagnostician.handle(syntax_errors, file_id);
+        if diagnostician.error_count() > errors {
+            continue;
+        }
+        let formatted = formatter::format(&source.source, options).ok_or_else(|| {
+            Diagnostic::io_write_error(&source.path, "the formatted source would change its code")
+        })?;
+        if formatted == source.source {
+            continue;
+        }
+        if check {
+            println!("{}", source.path);
+            unformatted += 1;
+        } else {
+            fs::write(&source.path, formatted)
+                .map_err(|it| Diagnostic::io_write_error(&source.path, &it.to_string()))?;
+        }
+    }
+    let errors = diagnostician.error_count();
+    if errors > 0 {
+        Err(Diagnostic::compilation_aborted(errors))
+    } else if unformatted > 0 {
+        Err(Diagnostic::param_error(&format!(
+            "{} file{} not formatted",
+            unformatted,
+            if unformatted == 1 { " is" } else { "s are" }
+        )))
+    } else {
+        Ok(())
+    }
+}
+
 /// The builder function for the compilation
 /// Sorts files that need compilation
 /// Parses, validates and generates code for the given source files
 /// Persists the generated code to output location
 /// Returns a compilation result with the index, and a list of object files
+/// The code of all source files is generated serially into one module, unless link-time
+/// optimization is requested (see `compile_with_lto`)
 pub fn build(
     files: Vec<FilePath>,
     includes: Vec<FilePath>,
@@ -591,11 +1411,53 @@ pub fn build(
         }
     });
 
+    //lay the generated code out for the target, unless the layout is overridden
+    let mut codegen_options = compile_options.codegen.clone();
+    let machine = create_target_machine(target, codegen_options.optimization, RelocMode::Default)?;
+    codegen_options.triple = Some(target.as_str().to_string_lossy().into_owned());
+    codegen_options.data_layout.get_or_insert_with(|| {
+        let target_data = machine.get_target_data();
+        let data_layout = target_data.get_data_layout();
+        data_layout.as_str().to_string_lossy().into_owned()
+    });
+
     let context = Context::create();
-    let diagnostician = Diagnostician::default();
-    let (index, codegen) = compile_module(&context, sources, includes, encoding, diagnostician)?;
+    let (index, codegen) = if let Some(lto) = codegen_options.lto {
+        compile_with_lto(
+            &context,
+            sources,
+            includes,
+            encoding,
+            compile_options,
+            codegen_options,
+            lto,
+        )?
+    } else {
+        let diagnostician = compile_options.create_diagnostician();
+        compile_module(
+            &context,
+            sources,
+            includes,
+            encoding,
+            diagnostician,
+            codegen_options,
+        )?
+    };
+    if let Some(output) = &compile_options.codegen.signatures {
+        let signatures = codegen.generate_project_signature();
+        let generated = signature::generate_signature_file(&signatures)?;
+        File::create(output)
+            .and_then(|mut it| it.write_all(generated.as_bytes()))
+            .map_err(|it| Diagnostic::io_write_error(output, &it.to_string()))?;
+    }
+    if let Some(stack_report) = &compile_options.stack_report {
+        File::create(stack_report)
+            .and_then(|mut it| it.write_all(codegen.generate_stack_report().as_bytes()))
+            .map_err(|it| Diagnostic::io_write_error(stack_report, &it.to_string()))?;
+    }
     objects.push(persist(
         codegen,
+        &index,
         &compile_options.output,
         compile_options.format,
         target,
@@ -604,8 +1466,79 @@ pub fn build(
     Ok(CompileResult { index, objects })
 }
 
+/// Compiles every source file into its own module, declaring the POUs and global variables of
+/// the other files, and links the modules with link-time optimization, so POUs can be inlined
+/// into the POUs of other files. The modules are compiled in parallel
+fn compile_with_lto<'c>(
+    context: &'c Context,
+    sources: Vec<FilePath>,
+    includes: Vec<FilePath>,
+    encoding: Option<&'static Encoding>,
+    compile_options: &CompileOptions,
+    options: CodegenOptions,
+    lto: LtoMode,
+) -> Result<(Index, CodeGen<'c>), Diagnostic> {
+    if lto == LtoMode::Thin {
+        return Err(Diagnostic::param_error(
+            "ThinLTO requires module summaries, which cannot be generated yet. Use --lto=full instead",
+        ));
+    }
+
+    //every unit is compiled on a worker thread with its own context and passed as bitcode
+    let units = sources
+        .par_iter()
+        .enumerate()
+        .map(|(unit_index, source)| {
+            //the other source files are declared like included files
+            let other_units = sources
+                .iter()
+                .enumerate()
+                .filter(|(other_index, _)| *other_index != unit_index)
+                .map(|(_, it)| it.clone())
+                .chain(includes.iter().cloned())
+                .collect();
+            //only the first module defines the generated main
+            let mut unit_options = options.clone();
+            //the other files are compiled into the first module too, so it lists all references
+            if unit_index > 0 {
+                unit_options.main_program = None;
+                unit_options.cross_reference = None;
+            }
+            let unit_context = Context::create();
+            let (index, unit) = compile_module(
+                &unit_context,
+                vec![source.clone()],
+                other_units,
+                encoding,
+                compile_options.create_diagnostician(),
+                unit_options,
+            )?;
+            unit.make_unit_constants_private(&index);
+            Ok((index, unit.to_bitcode()))
+        })
+        .collect::<Result<Vec<_>, Diagnostic>>()?;
+
+    let mut units = units.into_iter();
+    let (index, bitcode) = units
+        .next()
+        .ok_or_else(|| Diagnostic::param_error("No source files to compile"))?;
+    //the modules of the units already contain their debug information
+    let codegen = CodeGen::new(context, "main").with_options(CodegenOptions {
+        debug: false,
+        ..options
+    });
+    codegen.link_bitcode(&bitcode)?;
+    for (_, bitcode) in units {
+        codegen.link_bitcode(&bitcode)?;
+    }
+    codegen.prefix_symbols(&index);
+    codegen.optimize_lto();
+    Ok((index, codegen))
+}
+
 pub fn persist(
     input: codegen::CodeGen,
+    index: &Index,
     output: &str,
     out_format: FormatOption,
     target: &TargetTriple,
@@ -618,6 +1551,7 @@ pub fn persist(
         FormatOption::PIC => persist_to_shared_pic_object(input, output, target),
         FormatOption::Bitcode => persist_to_bitcode(input, output),
         FormatOption::IR => persist_to_ir(input, output),
+        FormatOption::Library => library::persist_to_library(index, input, output, target),
     }?;
 
     Ok(output.into())
@@ -677,7 +1611,25 @@ mod tests {
 
     use inkwell::targets::TargetMachine;
 
-    use crate::{create_source_code, get_target_triple};
+    use crate::{
+        ast::CompilationUnit, create_source_code, create_target_machine, diagnostics::Diagnostic,
+        get_target_triple, test_utils::tests::parse, AstFormat, OptimizationLevel,
+    };
+    use inkwell::targets::RelocMode;
+
+    #[test]
+    fn parsed_units_are_printed_as_text_and_json() {
+        let (unit, _) = parse("PROGRAM prg VAR x : INT; END_VAR x := 1; END_PROGRAM");
+
+        let text = AstFormat::Text.print(&[&unit]);
+        assert!(text.starts_with("CompilationUnit {"));
+        assert!(text.contains("name: \"prg\""));
+
+        //the printed JSON describes the same unit
+        let json = AstFormat::Json.print(&[&unit]);
+        let printed: Vec<CompilationUnit> = serde_json::from_str(&json).unwrap();
+        assert_eq!(printed, vec![unit]);
+    }
 
     #[test]
     fn test_get_target_triple() {
@@ -694,6 +1646,31 @@ mod tests {
         assert_eq!(triple.as_str().to_str().unwrap(), "x86_64-pc-linux-gnu");
     }
 
+    #[test]
+    fn unsupported_target_triples_are_reported() {
+        let triple = get_target_triple(Some("unknownarch-linux-gnu"));
+        let result = create_target_machine(&triple, OptimizationLevel::None, RelocMode::Default);
+
+        match result {
+            Err(Diagnostic::GeneralError { message, .. }) => assert!(
+                message.starts_with("Unsupported target-triple 'unknownarch-linux-gnu'"),
+                "{}",
+                message
+            ),
+            _ => panic!("expected an unsupported target"),
+        }
+    }
+
+    #[test]
+    fn target_machines_for_foreign_triples_are_created() {
+        let triple = get_target_triple(Some("arm-linux-gnueabihf"));
+        let machine =
+            create_target_machine(&triple, OptimizationLevel::None, RelocMode::Default).unwrap();
+
+        // pointers of the 32-bit target are 4 bytes wide
+        assert_eq!(machine.get_target_data().get_pointer_byte_size(None), 4);
+    }
+
     #[test]
     fn windows_encoded_file_content_read() {
         let expected = r"PROGRAM ä
@@ -702,7 +1679,7 @@ END_PROGRAM
 ";
         let mut source = &b"\x50\x52\x4f\x47\x52\x41\x4d\x20\xe4\x0a\x28\x2a\x20\x43\xf6\x6d\x65\x6e\x74\x20\x2a\x29\x0a\x45\x4e\x44\x5f\x50\x52\x4f\x47\x52\x41\x4d\x0a"[..];
         // let read = std::io::Read()
-        let source = create_source_code(&mut source, Some(encoding_rs::WINDOWS_1252)).unwrap();
+        let source = create_source_code(&mut source, Some(encoding_rs::WINDOWS_1252), 0).unwrap();
 
         assert_eq!(expected, &source);
     }
@@ -716,10 +1693,25 @@ END_PROGRAM
 
         let mut source = &b"\xff\xfe\x50\x00\x52\x00\x4f\x00\x47\x00\x52\x00\x41\x00\x4d\x00\x20\x00\xe4\x00\x0a\x00\x28\x00\x2a\x00\x20\x00\x43\x00\xf6\x00\x6d\x00\x6d\x00\x65\x00\x6e\x00\x74\x00\x20\x00\x2a\x00\x29\x00\x0a\x00\x45\x00\x4e\x00\x44\x00\x5f\x00\x50\x00\x52\x00\x4f\x00\x47\x00\x52\x00\x41\x00\x4d\x00\x0a\x00" [..];
 
-        let source = create_source_code(&mut source, None).unwrap();
+        let source = create_source_code(&mut source, None, 0).unwrap();
         assert_eq!(expected, &source);
     }
 
+    #[test]
+    fn sources_of_a_known_size_are_read_without_growing_their_buffer() {
+        let expected = "PROGRAM prg\nEND_PROGRAM\n".repeat(1000);
+
+        //the buffer sized to the source is allocated once and filled completely
+        let source = create_source_code(&mut expected.as_bytes(), None, expected.len()).unwrap();
+        assert_eq!(expected, source);
+        assert_eq!(source.capacity(), expected.len());
+
+        //a buffer of an unknown size doubles until it holds the source (32768 bytes for 24000)
+        let source = create_source_code(&mut expected.as_bytes(), None, 0).unwrap();
+        assert_eq!(expected, source);
+        assert!(source.capacity() > expected.len());
+    }
+
     #[test]
     fn utf_8_encoded_file_content_read() {
         let expected = r"PROGRAM ä
@@ -728,7 +1720,7 @@ END_PROGRAM
 ";
 
         let mut source = &b"\x50\x52\x4f\x47\x52\x41\x4d\x20\xc3\xa4\x0a\x28\x2a\x20\x43\xc3\xb6\x6d\x65\x6e\x74\x20\x2a\x29\x0a\x45\x4e\x44\x5f\x50\x52\x4f\x47\x52\x41\x4d\x0a" [..];
-        let source = create_source_code(&mut source, None).unwrap();
+        let source = create_source_code(&mut source, None, 0).unwrap();
         assert_eq!(expected, &source);
     }
 }
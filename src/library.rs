@@ -0,0 +1,190 @@
+// Copyright (c) 2022 Ghaith Hachem and Mathias Rieder
+//! Precompiled libraries (`.stlib`) contain the index of a library's declarations (its types,
+//! POU signatures, global variables and constants) together with the library's object code.
+//! A compilation using the library (`--lib mylib.stlib`) imports the index instead of parsing
+//! the library's sources and links the object code with the compiled sources.
+//!
+//! A library file starts with the `STLIB` marker and the format version, followed by the length
+//! of the serialized index (a little-endian u64), the index serialized as JSON and the object code.
+use std::{fs, path::Path};
+
+use inkwell::targets::{FileType, RelocMode, TargetTriple};
+
+use crate::{
+    codegen::CodeGen, create_target_machine, diagnostics::Diagnostic, index::Index,
+    lexer::IdProvider, FilePath,
+};
+
+const MARKER: &[u8] = b"STLIB";
+const VERSION: u8 = 1;
+/// the length of the marker, the version and the index's length
+const HEADER_SIZE: usize = MARKER.len() + 1 + 8;
+
+/// Persists the given index and the object code of the given LLVM module as a precompiled
+/// library and saves the output.
+///
+/// # Arguments
+///
+/// * `index` - the index of the compiled sources
+/// * `codegen` - The generated LLVM module to persist
+/// * `output` - the location on disk to save the output
+/// * `target` - the llvm target triple the object code is generated for
+pub fn persist_to_library(
+    index: &Index,
+    codegen: CodeGen,
+    output: &str,
+    target: &TargetTriple,
+) -> Result<(), Diagnostic> {
+    //the modules using the library define their own constants
+    codegen.make_unit_constants_private(index);
+    //the library may be linked into executables and shared objects
+    let machine = create_target_machine(target, codegen.get_optimization_level(), RelocMode::PIC)?;
+    let object = machine
+        .write_to_memory_buffer(&codegen.module, FileType::Object)
+        .map_err(|it| Diagnostic::llvm_error(output, &it))?;
+    let serialized_index = serde_json::to_vec(index)
+        .map_err(|it| Diagnostic::io_write_error(output, &it.to_string()))?;
+
+    let mut content = MARKER.to_vec();
+    content.push(VERSION);
+    content.extend((serialized_index.len() as u64).to_le_bytes());
+    content.extend(serialized_index);
+    content.extend(object.as_slice());
+    fs::write(output, content).map_err(|it| Diagnostic::io_write_error(output, &it.to_string()))
+}
+
+/// reads the index of the given library. The library's implementations and global variables
+/// are declared as external and its expressions get new ids from the given id provider.
+pub fn read_library_index(path: &str, id_provider: &IdProvider) -> Result<Index, Diagnostic> {
+    let content = read_library(path)?;
+    let (serialized_index, _) = split_library(path, &content)?;
+    let mut index: Index = serde_json::from_slice(serialized_index)
+        .map_err(|it| Diagnostic::io_read_error(path, &it.to_string()))?;
+    index.make_external();
+    index.renew_ids(id_provider);
+    Ok(index)
+}
+
+/// extracts the object code of the given library into a temporary object file to be linked
+/// and returns the object file's path
+pub fn extract_library_object(path: &str) -> Result<FilePath, Diagnostic> {
+    let content = read_library(path)?;
+    let (_, object) = split_library(path, &content)?;
+    let name = Path::new(path)
+        .file_stem()
+        .and_then(|it| it.to_str())
+        .unwrap_or("library");
+    let object_path = std::env::temp_dir()
+        .join(format!("{}_{}.o", name, std::process::id()))
+        .to_string_lossy()
+        .to_string();
+    fs::write(&object_path, object)
+        .map_err(|it| Diagnostic::io_write_error(&object_path, &it.to_string()))?;
+    Ok(object_path.into())
+}
+
+fn read_library(path: &str) -> Result<Vec<u8>, Diagnostic> {
+    fs::read(path).map_err(|it| Diagnostic::io_read_error(path, &it.to_string()))
+}
+
+/// splits the content of a library file into the serialized index and the object code
+fn split_library<'c>(path: &str, content: &'c [u8]) -> Result<(&'c [u8], &'c [u8]), Diagnostic> {
+    let invalid_library = || {
+        Diagnostic::io_read_error(
+            path,
+            "not a precompiled library of this version of the compiler",
+        )
+    };
+    if content.len() < HEADER_SIZE
+        || !content.starts_with(MARKER)
+        || content[MARKER.len()] != VERSION
+    {
+        return Err(invalid_library());
+    }
+    let index_size = content[MARKER.len() + 1..HEADER_SIZE]
+        .try_into()
+        .map(u64::from_le_bytes)
+        .map_err(|_| invalid_library())?;
+    let index_end = usize::try_from(index_size)
+        .ok()
+        .and_then(|it| it.checked_add(HEADER_SIZE))
+        .filter(|it| *it <= content.len())
+        .ok_or_else(invalid_library)?;
+    Ok((&content[HEADER_SIZE..index_end], &content[index_end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{split_library, HEADER_SIZE, MARKER, VERSION};
+    use crate::{ast::LinkageType, index::Index, test_utils::tests::index};
+
+    #[test]
+    fn the_index_is_restored_with_external_declarations() {
+        let (_, index) = index(
+            "
+            TYPE Point : STRUCT x : INT := 1; y : INT; END_STRUCT END_TYPE
+            VAR_GLOBAL limit : INT := 10; END_VAR
+            FUNCTION foo : INT VAR_INPUT a : Point; END_VAR END_FUNCTION
+            ",
+        );
+
+        let serialized = serde_json::to_vec(&index).unwrap();
+        let mut restored: Index = serde_json::from_slice(&serialized).unwrap();
+        restored.make_external();
+
+        assert_eq!(
+            restored.find_effective_type("Point"),
+            index.find_effective_type("Point")
+        );
+        assert_eq!(
+            restored
+                .find_member("foo", "a")
+                .map(|it| it.get_type_name()),
+            Some("Point")
+        );
+        let limit = restored.find_global_variable("limit").unwrap();
+        assert!(limit.is_external());
+        assert_eq!(
+            restored
+                .get_const_expressions()
+                .get_constant_int_statement_value(&limit.initial_value.unwrap()),
+            Ok(10)
+        );
+        assert_eq!(
+            restored.find_implementation("foo").unwrap().get_linkage(),
+            LinkageType::External
+        );
+    }
+
+    #[test]
+    fn library_files_are_split_into_index_and_object() {
+        let mut content = MARKER.to_vec();
+        content.push(VERSION);
+        content.extend(2_u64.to_le_bytes());
+        content.extend(b"{}object");
+
+        let (index, object) = split_library("lib.stlib", &content).unwrap();
+        assert_eq!(index, b"{}");
+        assert_eq!(object, b"object");
+    }
+
+    #[test]
+    fn invalid_library_files_are_reported() {
+        //not a library
+        assert!(split_library("lib.stlib", b"PROGRAM prg END_PROGRAM").is_err());
+
+        //another version
+        let mut content = MARKER.to_vec();
+        content.push(VERSION + 1);
+        content.extend(0_u64.to_le_bytes());
+        assert!(split_library("lib.stlib", &content).is_err());
+
+        //a truncated index
+        let mut content = MARKER.to_vec();
+        content.push(VERSION);
+        content.extend(10_u64.to_le_bytes());
+        content.extend(b"{}");
+        assert_eq!(content.len(), HEADER_SIZE + 2);
+        assert!(split_library("lib.stlib", &content).is_err());
+    }
+}
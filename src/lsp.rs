@@ -0,0 +1,804 @@
+// Copyright (c) 2022 Ghaith Hachem and Mathias Rieder
+//! A language server (`rusty --lsp`) for structured text files, communicating with the editor
+//! over std-in and std-out using the Language Server Protocol.
+//!
+//! Every change of an open document runs the front end of a compilation over all open documents
+//! and the included files again (recovering from syntax errors like a compilation does) and
+//! publishes the diagnostics of the documents. The server further offers hovering (the resolved type of the expression under
+//! the cursor), go-to-definition and the symbols declared in a document.
+use std::{
+    cell::RefCell,
+    io::{BufRead, Write},
+    rc::Rc,
+    sync::Arc,
+};
+
+use encoding_rs::Encoding;
+use indexmap::IndexMap;
+use serde_json::{json, Value};
+
+use crate::{
+    ast::{AstStatement, CompilationUnit, DataType, PouType, SourceRange, Variable, VariableBlock},
+    diagnostics::{
+        AssessedDiagnostic, Diagnostic, DiagnosticReporter, Diagnostician, ErrNo, Severity,
+    },
+    index::Index,
+    resolver::{AnnotationMap, AnnotationMapImpl, StatementAnnotation},
+    CodegenOptions, FilePath, SourceCode,
+};
+
+/// the error code of a request the server does not support
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// the options the open documents are analysed with, they are given on the command line
+#[derive(Default)]
+pub struct AnalysisOptions {
+    /// the files declaring the POUs and variables of libraries the documents use
+    pub includes: Vec<FilePath>,
+    pub encoding: Option<&'static Encoding>,
+    /// the defines, the precompiled libraries and the overflow behaviour of constants
+    pub codegen: CodegenOptions,
+}
+
+/// runs the language server on the given input and output until the editor sends `exit`
+/// or closes the input
+pub fn run_server(
+    mut input: impl BufRead,
+    mut output: impl Write,
+    options: AnalysisOptions,
+) -> Result<(), Diagnostic> {
+    let mut server = Server {
+        options,
+        ..Server::default()
+    };
+    while let Some(message) = read_message(&mut input).map_err(to_diagnostic)? {
+        for response in server.handle(&message) {
+            write_message(&mut output, &response).map_err(to_diagnostic)?;
+        }
+        if server.exited {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn to_diagnostic(error: std::io::Error) -> Diagnostic {
+    Diagnostic::GeneralError {
+        message: error.to_string(),
+        err_no: ErrNo::general__io_err,
+    }
+}
+
+/// reads the next message, returns None if the input is closed
+fn read_message(input: &mut impl BufRead) -> std::io::Result<Option<Value>> {
+    let mut content_length = None;
+    loop {
+        let mut line = String::new();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("Content-Length") {
+                content_length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+    let content_length = content_length.ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, "missing Content-Length")
+    })?;
+    let mut content = vec![0; content_length];
+    input.read_exact(&mut content)?;
+    serde_json::from_slice(&content)
+        .map(Some)
+        .map_err(|it| std::io::Error::new(std::io::ErrorKind::InvalidData, it))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> std::io::Result<()> {
+    let content = message.to_string();
+    write!(
+        output,
+        "Content-Length: {}\r\n\r\n{}",
+        content.len(),
+        content
+    )?;
+    output.flush()
+}
+
+#[derive(Default)]
+struct Server {
+    /// the text of the open documents by their uri, a document's position is its file id
+    documents: IndexMap<String, String>,
+    options: AnalysisOptions,
+    analysis: Analysis,
+    exited: bool,
+}
+
+impl Server {
+    /// handles the given message and returns the messages to send to the editor
+    fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let method = message["method"].as_str().unwrap_or_default();
+        let params = &message["params"];
+        match message.get("id") {
+            Some(id) => {
+                let result = match method {
+                    "initialize" => Ok(json!({
+                        "capabilities": {
+                            "textDocumentSync": 1,
+                            "hoverProvider": true,
+                            "definitionProvider": true,
+                            "documentSymbolProvider": true,
+                        },
+                        "serverInfo": { "name": "rusty", "version": env!("CARGO_PKG_VERSION") },
+                    })),
+                    "shutdown" => Ok(Value::Null),
+                    "textDocument/hover" => Ok(self.hover(params)),
+                    "textDocument/definition" => Ok(self.definition(params)),
+                    "textDocument/documentSymbol" => Ok(self.document_symbols(params)),
+                    _ => Err(format!("unsupported request {}", method)),
+                };
+                let response = match result {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err(message) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": METHOD_NOT_FOUND, "message": message },
+                    }),
+                };
+                vec![response]
+            }
+            None => self.handle_notification(method, params),
+        }
+    }
+
+    fn handle_notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        match method {
+            "textDocument/didOpen" => {
+                let text = params["textDocument"]["text"].as_str().unwrap_or_default();
+                self.documents.insert(uri, text.to_string());
+            }
+            "textDocument/didChange" => {
+                //the documents are synchronized in full, so the last change contains the text
+                let text = params["contentChanges"]
+                    .as_array()
+                    .and_then(|it| it.last())
+                    .and_then(|it| it["text"].as_str());
+                if let Some(text) = text {
+                    self.documents.insert(uri, text.to_string());
+                }
+            }
+            "textDocument/didClose" => {
+                self.documents.shift_remove(&uri);
+                self.analysis = Analysis::new(&self.documents, &self.options);
+                let mut notifications = self.publish_diagnostics();
+                //the diagnostics of the closed document are cleared
+                notifications.push(diagnostics_notification(&uri, vec![]));
+                return notifications;
+            }
+            "exit" => {
+                self.exited = true;
+                return vec![];
+            }
+            _ => return vec![],
+        }
+        self.analysis = Analysis::new(&self.documents, &self.options);
+        self.publish_diagnostics()
+    }
+
+    fn publish_diagnostics(&self) -> Vec<Value> {
+        self.documents
+            .iter()
+            .enumerate()
+            .map(|(file_id, (uri, text))| {
+                let diagnostics = self
+                    .analysis
+                    .diagnostics
+                    .iter()
+                    .filter(|(file, ..)| *file == file_id)
+                    .map(|(_, diagnostic, severity)| {
+                        json!({
+                            "range": to_range(text, &diagnostic.get_location()),
+                            "severity": severity,
+                            "source": "rusty",
+                            "message": diagnostic.get_message(),
+                        })
+                    })
+                    .collect();
+                diagnostics_notification(uri, diagnostics)
+            })
+            .collect()
+    }
+
+    /// returns the document and the offset of the given text document position
+    fn get_position(&self, params: &Value) -> Option<(usize, &str, usize)> {
+        let uri = params["textDocument"]["uri"].as_str()?;
+        let (file_id, _, text) = self.documents.get_full(uri)?;
+        Some((file_id, text, to_offset(text, &params["position"])))
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let (file_id, text, offset) = match self.get_position(params) {
+            Some(position) => position,
+            None => return Value::Null,
+        };
+        let analysis = &self.analysis;
+        let hover = analysis
+            .find_annotated_statement(file_id, offset)
+            .map(|(statement, annotation)| {
+                (statement.get_location(), describe_annotation(annotation))
+            })
+            .or_else(|| {
+                analysis
+                    .find_declared_variable(file_id, offset)
+                    .map(|it| (it.location.clone(), describe_variable(it)))
+            });
+        match hover {
+            Some((location, description)) => json!({
+                "contents": {
+                    "kind": "markdown",
+                    "value": format!("```iecst\n{}\n```", description),
+                },
+                "range": to_range(text, &location),
+            }),
+            None => Value::Null,
+        }
+    }
+
+    fn definition(&self, params: &Value) -> Value {
+        self.get_position(params)
+            .and_then(|(file_id, _, offset)| {
+                let (_, annotation) = self.analysis.find_annotated_statement(file_id, offset)?;
+                let location = self.analysis.find_definition(annotation)?;
+                let (uri, text) = self.documents.get_index(location.get_file()?)?;
+                Some(json!({ "uri": uri, "range": to_range(text, &location) }))
+            })
+            .unwrap_or(Value::Null)
+    }
+
+    fn document_symbols(&self, params: &Value) -> Value {
+        let (file_id, text) = match params["textDocument"]["uri"]
+            .as_str()
+            .and_then(|it| self.documents.get_full(it))
+        {
+            Some((file_id, _, text)) => (file_id, text),
+            None => return Value::Null,
+        };
+        let symbol = |name: &str, kind: u32, location: &SourceRange, children: Vec<Value>| {
+            let range = to_range(text, location);
+            json!({
+                "name": name,
+                "kind": kind,
+                "range": range,
+                "selectionRange": range,
+                "children": children,
+            })
+        };
+        let variables = |blocks: &[VariableBlock], kind: u32| {
+            blocks
+                .iter()
+                .flat_map(|it| it.variables.iter())
+                .map(|it| symbol(&it.name, kind, &it.location, vec![]))
+                .collect::<Vec<_>>()
+        };
+        let mut symbols = vec![];
+        for unit in self.analysis.get_units(file_id) {
+            for pou in &unit.units {
+                let kind = match pou.pou_type {
+                    PouType::Program => 2,
+                    PouType::Function => 12,
+                    PouType::FunctionBlock | PouType::Class => 5,
+                    PouType::Method { .. } => 6,
+                    PouType::Interface => 11,
+                    PouType::Action => 24,
+                };
+                let members = variables(&pou.variable_blocks, 13);
+                symbols.push(symbol(&pou.name, kind, &pou.location, members));
+            }
+            //the types of inline declarations are generated and not shown
+            for user_type in unit.types.iter().filter(|it| {
+                it.data_type
+                    .get_name()
+                    .map_or(false, |name| !name.starts_with("__"))
+            }) {
+                let (kind, members) = match &user_type.data_type {
+                    DataType::StructType { variables, .. }
+                    | DataType::UnionType { variables, .. } => (
+                        23,
+                        variables
+                            .iter()
+                            .map(|it| symbol(&it.name, 8, &it.location, vec![]))
+                            .collect(),
+                    ),
+                    DataType::EnumType { elements, .. } => (
+                        10,
+                        get_enum_elements(elements)
+                            .into_iter()
+                            .map(|(name, location)| symbol(name, 22, &location, vec![]))
+                            .collect(),
+                    ),
+                    _ => (26, vec![]),
+                };
+                let name = user_type.data_type.get_name().unwrap_or_default();
+                symbols.push(symbol(name, kind, &user_type.location, members));
+            }
+            symbols.extend(variables(&unit.global_vars, 13));
+        }
+        Value::Array(symbols)
+    }
+}
+
+fn diagnostics_notification(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": { "uri": uri, "diagnostics": diagnostics },
+    })
+}
+
+/// the result of compiling the open documents up to their validation
+#[derive(Default)]
+struct Analysis {
+    /// the annotated units with the id of their file
+    units: Vec<(usize, CompilationUnit)>,
+    index: Index,
+    annotations: AnnotationMapImpl,
+    /// the reported diagnostics with their file and their LSP severity
+    diagnostics: Vec<(usize, Diagnostic, u8)>,
+}
+
+/// a reporter collecting the diagnostics instead of printing them. The files are registered in
+/// order, so the id of a document is its position in the open documents
+#[derive(Default)]
+struct CollectingReporter {
+    diagnostics: Rc<RefCell<Vec<(usize, Diagnostic, u8)>>>,
+    file_count: usize,
+}
+
+impl DiagnosticReporter for CollectingReporter {
+    fn report(&self, diagnostics: &[AssessedDiagnostic], file_id: usize) {
+        let mut collected = self.diagnostics.borrow_mut();
+        for it in diagnostics {
+            let severity = match it.severity {
+                Severity::Error => 1,
+                Severity::Warning => 2,
+                Severity::_Info => 3,
+            };
+            collected.push((file_id, it.diagnostic.clone(), severity));
+        }
+    }
+
+    fn register(&mut self, _path: String, _src: Arc<str>) -> usize {
+        self.file_count += 1;
+        self.file_count - 1
+    }
+}
+
+impl Analysis {
+    fn new(documents: &IndexMap<String, String>, options: &AnalysisOptions) -> Analysis {
+        let reporter = CollectingReporter::default();
+        let diagnostics = reporter.diagnostics.clone();
+        let mut diagnostician = Diagnostician::default().with_reporter(Box::new(reporter));
+        let sources = documents
+            .iter()
+            .map(|(uri, text)| SourceCode {
+                source: text.clone(),
+                path: uri.clone(),
+            })
+            .collect::<Vec<_>>();
+        //the documents are in memory, only an include or a library that cannot be read leaves
+        //them unanalysed
+        let analysed = crate::analyse_sources(
+            sources,
+            options.includes.clone(),
+            options.encoding,
+            &mut diagnostician,
+            &options.codegen,
+        )
+        .unwrap_or_default();
+
+        let diagnostics = diagnostics.take();
+        Analysis {
+            units: analysed.units,
+            index: analysed.index,
+            annotations: analysed.annotations,
+            diagnostics,
+        }
+    }
+
+    fn get_units(&self, file_id: usize) -> impl Iterator<Item = &CompilationUnit> {
+        self.units
+            .iter()
+            .filter(move |(file, _)| *file == file_id)
+            .map(|(_, unit)| unit)
+    }
+
+    /// returns the innermost annotated statement of the given file at the given offset
+    fn find_annotated_statement(
+        &self,
+        file_id: usize,
+        offset: usize,
+    ) -> Option<(&AstStatement, &StatementAnnotation)> {
+        let roots = self
+            .get_units(file_id)
+            .flat_map(|unit| {
+                let implementations = unit
+                    .implementations
+                    .iter()
+                    .flat_map(|it| it.statements.iter());
+                let initializers = get_variables(unit)
+                    .filter_map(|it| it.initializer.as_ref())
+                    .chain(unit.types.iter().filter_map(|it| it.initializer.as_ref()));
+                implementations.chain(initializers)
+            })
+            .collect::<Vec<_>>();
+        self.find_innermost(&roots, offset)
+    }
+
+    fn find_innermost<'a>(
+        &'a self,
+        statements: &[&'a AstStatement],
+        offset: usize,
+    ) -> Option<(&'a AstStatement, &'a StatementAnnotation)> {
+        statements
+            .iter()
+            .copied()
+            .filter(|it| contains(&it.get_location(), offset))
+            .find_map(|it| {
+                self.find_innermost(&it.get_children(), offset)
+                    .or_else(|| self.annotations.get(it).map(|annotation| (it, annotation)))
+            })
+    }
+
+    /// returns the variable of the given file declared at the given offset
+    fn find_declared_variable(&self, file_id: usize, offset: usize) -> Option<&Variable> {
+        self.get_units(file_id)
+            .flat_map(get_variables)
+            .find(|it| contains(&it.location, offset))
+    }
+
+    /// returns the location of the declaration the given annotation refers to
+    fn find_definition(&self, annotation: &StatementAnnotation) -> Option<SourceRange> {
+        let units = self.units.iter().map(|(_, unit)| unit);
+        match annotation {
+            StatementAnnotation::Variable { qualified_name, .. } => self
+                .index
+                .find_fully_qualified_variable(qualified_name)
+                .map(|it| it.source_location.clone()),
+            StatementAnnotation::Function { qualified_name, .. }
+            | StatementAnnotation::Program { qualified_name } => units
+                .flat_map(|it| it.units.iter())
+                .find(|it| it.name.eq_ignore_ascii_case(qualified_name))
+                .map(|it| it.location.clone()),
+            StatementAnnotation::Type { type_name } => units
+                .flat_map(|it| it.types.iter())
+                .find(|it| {
+                    it.data_type
+                        .get_name()
+                        .map_or(false, |name| name.eq_ignore_ascii_case(type_name))
+                })
+                .map(|it| it.location.clone()),
+            StatementAnnotation::Value { .. } | StatementAnnotation::Property { .. } => None,
+        }
+    }
+}
+
+/// returns the global variables and the members of the POUs of the given unit
+fn get_variables(unit: &CompilationUnit) -> impl Iterator<Item = &Variable> {
+    unit.global_vars
+        .iter()
+        .chain(unit.units.iter().flat_map(|it| it.variable_blocks.iter()))
+        .flat_map(|it| it.variables.iter())
+}
+
+fn contains(location: &SourceRange, offset: usize) -> bool {
+    //a cursor placed right behind a name still refers to it
+    location.get_start() <= offset && offset <= location.get_end()
+}
+
+fn describe_annotation(annotation: &StatementAnnotation) -> String {
+    match annotation {
+        StatementAnnotation::Value { resulting_type } => resulting_type.clone(),
+        StatementAnnotation::Variable {
+            resulting_type,
+            qualified_name,
+            ..
+        } => format!("{} : {}", qualified_name, resulting_type),
+        StatementAnnotation::Function {
+            return_type,
+            qualified_name,
+        } => format!("FUNCTION {} : {}", qualified_name, return_type),
+        StatementAnnotation::Type { type_name } => format!("TYPE {}", type_name),
+        StatementAnnotation::Program { qualified_name } => format!("PROGRAM {}", qualified_name),
+        StatementAnnotation::Property { resulting_type, .. } => {
+            format!("PROPERTY : {}", resulting_type)
+        }
+    }
+}
+
+fn describe_variable(variable: &Variable) -> String {
+    format!(
+        "{} : {}",
+        variable.name,
+        variable.data_type.get_name().unwrap_or_default()
+    )
+}
+
+/// returns the names of the elements of an enum with their locations
+fn get_enum_elements(elements: &AstStatement) -> Vec<(&str, SourceRange)> {
+    match elements {
+        AstStatement::ExpressionList { expressions, .. } => {
+            expressions.iter().flat_map(get_enum_elements).collect()
+        }
+        AstStatement::Reference { name, location, .. } => vec![(name.as_str(), location.clone())],
+        AstStatement::Assignment { left, .. } => get_enum_elements(left),
+        _ => vec![],
+    }
+}
+
+/// converts the given byte offset into an LSP position (a line and a UTF-16 column)
+fn to_position(text: &str, offset: usize) -> Value {
+    let offset = offset.min(text.len());
+    let before = &text[..offset];
+    let line = before.matches('\n').count();
+    let line_start = before.rfind('\n').map_or(0, |it| it + 1);
+    let character = text[line_start..offset].encode_utf16().count();
+    json!({ "line": line, "character": character })
+}
+
+/// converts the given LSP position into a byte offset of the given text
+fn to_offset(text: &str, position: &Value) -> usize {
+    let line = position["line"].as_u64().unwrap_or_default() as usize;
+    let character = position["character"].as_u64().unwrap_or_default() as usize;
+    let line_start = if line == 0 {
+        0
+    } else {
+        match text.match_indices('\n').nth(line - 1) {
+            Some((index, _)) => index + 1,
+            None => return text.len(),
+        }
+    };
+    let mut column = 0;
+    for (index, c) in text[line_start..].char_indices() {
+        if column >= character || c == '\n' {
+            return line_start + index;
+        }
+        column += c.len_utf16();
+    }
+    text.len()
+}
+
+fn to_range(text: &str, location: &SourceRange) -> Value {
+    json!({
+        "start": to_position(text, location.get_start()),
+        "end": to_position(text, location.get_end()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{json, Value};
+
+    use crate::FilePath;
+
+    use super::{read_message, to_offset, to_position, write_message, AnalysisOptions, Server};
+
+    #[test]
+    fn messages_are_written_and_read_with_their_content_length() {
+        let message = json!({ "jsonrpc": "2.0", "method": "initialized", "params": {} });
+        let mut buffer = vec![];
+        write_message(&mut buffer, &message).unwrap();
+        assert!(buffer.starts_with(b"Content-Length: "));
+
+        let mut input = buffer.as_slice();
+        assert_eq!(read_message(&mut input).unwrap(), Some(message));
+        //the input is closed
+        assert_eq!(read_message(&mut input).unwrap(), None);
+    }
+
+    #[test]
+    fn positions_count_utf16_characters() {
+        let text = "x := 'ä';\ny := 1;";
+        assert_eq!(to_position(text, 0), json!({ "line": 0, "character": 0 }));
+        //'ä' takes two bytes but a single UTF-16 unit
+        assert_eq!(to_position(text, 9), json!({ "line": 0, "character": 8 }));
+        assert_eq!(to_position(text, 12), json!({ "line": 1, "character": 1 }));
+
+        assert_eq!(to_offset(text, &json!({ "line": 0, "character": 8 })), 9);
+        assert_eq!(to_offset(text, &json!({ "line": 1, "character": 1 })), 12);
+        //positions behind a line end at the line's end
+        assert_eq!(to_offset(text, &json!({ "line": 0, "character": 99 })), 10);
+        assert_eq!(
+            to_offset(text, &json!({ "line": 9, "character": 0 })),
+            text.len()
+        );
+    }
+
+    fn request(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params })
+    }
+
+    fn open(server: &mut Server, uri: &str, text: &str) -> Vec<Value> {
+        server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didOpen",
+            "params": { "textDocument": { "uri": uri, "languageId": "st", "version": 1, "text": text } },
+        }))
+    }
+
+    fn position_params(uri: &str, line: u32, character: u32) -> Value {
+        json!({
+            "textDocument": { "uri": uri },
+            "position": { "line": line, "character": character },
+        })
+    }
+
+    const LIB: &str = "FUNCTION foo : INT\nVAR_INPUT a : INT; END_VAR\nfoo := a;\nEND_FUNCTION\n";
+    const MAIN: &str =
+        "PROGRAM prg\nVAR x : INT; END_VAR\nx := foo(x);\nEND_PROGRAM\nTYPE Color : (red, green); END_TYPE\n";
+
+    #[test]
+    fn diagnostics_are_published_for_every_open_document() {
+        let mut server = Server::default();
+        let notifications = open(
+            &mut server,
+            "file:///main.st",
+            "PROGRAM prg\nx := 1;\nEND_PROGRAM\n",
+        );
+        assert_eq!(notifications.len(), 1);
+        let params = &notifications[0]["params"];
+        assert_eq!(params["uri"], "file:///main.st");
+        let diagnostic = &params["diagnostics"][0];
+        assert_eq!(diagnostic["severity"], 1);
+        assert_eq!(
+            diagnostic["range"]["start"],
+            json!({ "line": 1, "character": 0 })
+        );
+
+        //the fixed document publishes no diagnostics
+        let notifications = server.handle(&json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/didChange",
+            "params": {
+                "textDocument": { "uri": "file:///main.st", "version": 2 },
+                "contentChanges": [{ "text": "PROGRAM prg\nVAR x : INT; END_VAR\nx := 1;\nEND_PROGRAM\n" }],
+            },
+        }));
+        assert_eq!(notifications[0]["params"]["diagnostics"], json!([]));
+    }
+
+    #[test]
+    fn hover_shows_the_resolved_type() {
+        let mut server = Server::default();
+        open(&mut server, "file:///lib.st", LIB);
+        open(&mut server, "file:///main.st", MAIN);
+
+        //x in `x := foo(x)`
+        let response = server.handle(&request(
+            "textDocument/hover",
+            position_params("file:///main.st", 2, 0),
+        ));
+        assert_eq!(
+            response[0]["result"]["contents"]["value"],
+            "```iecst\nprg.x : INT\n```"
+        );
+        assert_eq!(
+            response[0]["result"]["range"],
+            json!({ "start": { "line": 2, "character": 0 }, "end": { "line": 2, "character": 1 } })
+        );
+
+        //foo in `x := foo(x)`
+        let response = server.handle(&request(
+            "textDocument/hover",
+            position_params("file:///main.st", 2, 6),
+        ));
+        assert_eq!(
+            response[0]["result"]["contents"]["value"],
+            "```iecst\nFUNCTION foo : INT\n```"
+        );
+    }
+
+    #[test]
+    fn definitions_are_found_across_documents() {
+        let mut server = Server::default();
+        open(&mut server, "file:///lib.st", LIB);
+        open(&mut server, "file:///main.st", MAIN);
+
+        let response = server.handle(&request(
+            "textDocument/definition",
+            position_params("file:///main.st", 2, 6),
+        ));
+        let result = &response[0]["result"];
+        assert_eq!(result["uri"], "file:///lib.st");
+        assert_eq!(
+            result["range"]["start"],
+            json!({ "line": 0, "character": 0 })
+        );
+
+        //the variable x is declared in the program
+        let response = server.handle(&request(
+            "textDocument/definition",
+            position_params("file:///main.st", 2, 9),
+        ));
+        let result = &response[0]["result"];
+        assert_eq!(result["uri"], "file:///main.st");
+        assert_eq!(
+            result["range"]["start"],
+            json!({ "line": 1, "character": 4 })
+        );
+    }
+
+    #[test]
+    fn document_symbols_contain_pous_types_and_their_members() {
+        let mut server = Server::default();
+        open(&mut server, "file:///main.st", MAIN);
+
+        let response = server.handle(&request(
+            "textDocument/documentSymbol",
+            json!({ "textDocument": { "uri": "file:///main.st" } }),
+        ));
+        let symbols = response[0]["result"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|it| {
+                let children = it["children"]
+                    .as_array()
+                    .unwrap()
+                    .iter()
+                    .map(|it| it["name"].as_str().unwrap())
+                    .collect::<Vec<_>>();
+                (
+                    it["name"].as_str().unwrap(),
+                    it["kind"].as_u64().unwrap(),
+                    children,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            symbols,
+            vec![("prg", 2, vec!["x"]), ("Color", 10, vec!["red", "green"]),]
+        );
+    }
+
+    #[test]
+    fn documents_are_analysed_with_the_included_files() {
+        let include = std::env::temp_dir().join(format!("lsp_include_{}.st", std::process::id()));
+        std::fs::write(&include, "VAR_GLOBAL g : INT; END_VAR\n").unwrap();
+        let mut server = Server {
+            options: AnalysisOptions {
+                includes: vec![FilePath {
+                    path: include.to_string_lossy().to_string(),
+                }],
+                ..AnalysisOptions::default()
+            },
+            ..Server::default()
+        };
+
+        let notifications = open(
+            &mut server,
+            "file:///main.st",
+            "PROGRAM prg\ng := 1;\nEND_PROGRAM\n",
+        );
+        std::fs::remove_file(&include).unwrap();
+        //only the open documents publish diagnostics, the global variable is declared
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0]["params"]["diagnostics"], json!([]));
+    }
+
+    #[test]
+    fn unsupported_requests_are_answered_with_an_error() {
+        let mut server = Server::default();
+        let response = server.handle(&request("textDocument/formatting", json!({})));
+        assert_eq!(response[0]["error"]["code"], -32601);
+        assert!(!server.exited);
+        server.handle(&json!({ "jsonrpc": "2.0", "method": "exit" }));
+        assert!(server.exited);
+    }
+}
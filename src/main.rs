@@ -28,8 +28,8 @@ fn main() {
         CompileParameters::parse(args);
     match compile_parameters {
         Ok(cp) => {
-            if let Err(msg) = build_with_params(cp) {
-                println!("Error: {:?}", msg);
+            if let Err(diagnostic) = build_with_params(cp) {
+                eprintln!("error: {}", diagnostic.get_message());
                 std::process::exit(1);
             }
         }
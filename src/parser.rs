@@ -6,6 +6,8 @@ use crate::{
     typesystem::DINT_TYPE,
     Diagnostic,
 };
+//the comment of the ast, not the lexer's token of the same name
+use crate::ast::Comment;
 
 use self::{
     control_parser::parse_control_statement,
@@ -23,7 +25,12 @@ pub fn parse(mut lexer: ParseSession, lnk: LinkageType) -> ParsedAst {
     let mut unit = CompilationUnit::default();
 
     let mut linkage = lnk;
+    // the namespaces enclosing the current position, the innermost namespace last
+    let mut namespaces: Vec<String> = vec![];
+    // the namespaces used in the file and in every enclosing namespace
+    let mut usings: Vec<Vec<String>> = vec![vec![]];
     loop {
+        let lookup_scopes = get_lookup_scopes(&namespaces, &usings);
         match lexer.token {
             PropertyExternal => {
                 linkage = LinkageType::External;
@@ -31,27 +38,83 @@ pub fn parse(mut lexer: ParseSession, lnk: LinkageType) -> ParsedAst {
                 //Don't reset linkage
                 continue;
             }
-            KeywordVarGlobal => unit.global_vars.push(parse_variable_block(
-                &mut lexer,
-                VariableBlockType::Global,
-                linkage,
-            )),
-            KeywordProgram | KeywordClass | KeywordFunction | KeywordFunctionBlock => {
+            PragmaExternal => {
+                linkage = LinkageType::Foreign;
+                lexer.advance();
+                //Don't reset linkage
+                continue;
+            }
+            KeywordNamespace => {
+                lexer.advance();
+                let name = parse_qualified_identifier(&mut lexer).unwrap_or_default();
+                let namespace = qualify(namespaces.last(), &name);
+                unit.namespaces.push(namespace.clone());
+                namespaces.push(namespace);
+                usings.push(vec![]);
+            }
+            KeywordEndNamespace => {
+                if namespaces.pop().is_some() {
+                    usings.pop();
+                } else {
+                    lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+                        "StartKeyword",
+                        lexer.slice(),
+                        lexer.location(),
+                    ));
+                }
+                lexer.advance();
+            }
+            KeywordUsing => {
+                lexer.advance();
+                loop {
+                    if let Some(name) = parse_qualified_identifier(&mut lexer) {
+                        usings.last_mut().expect("file level usings").push(name);
+                    }
+                    if !lexer.allow(&KeywordComma) {
+                        break;
+                    }
+                }
+                lexer.consume_or_report(KeywordSemicolon);
+            }
+            KeywordVarGlobal => {
+                let mut block = parse_variable_block(
+                    &mut lexer,
+                    VariableBlockType::Global,
+                    get_non_function_linkage(linkage),
+                );
+                for variable in block.variables.iter_mut() {
+                    variable.name = qualify(namespaces.last(), &variable.name);
+                    register_lookup_scopes(&mut unit, &variable.name, &lookup_scopes);
+                }
+                unit.global_vars.push(block)
+            }
+            KeywordProgram | KeywordClass | KeywordInterface | KeywordFunction
+            | KeywordFunctionBlock => {
                 let params = match lexer.token {
                     KeywordProgram => (PouType::Program, KeywordEndProgram),
                     KeywordClass => (PouType::Class, KeywordEndClass),
+                    KeywordInterface => (PouType::Interface, KeywordEndInterface),
                     KeywordFunction => (PouType::Function, KeywordEndFunction),
                     _ => (PouType::FunctionBlock, KeywordEndFunctionBlock),
                 };
 
                 let (mut pou, mut implementation) =
-                    parse_pou(&mut lexer, params.0, linkage, params.1);
+                    parse_pou(&mut lexer, params.0, linkage, params.1, namespaces.last());
 
+                for pou in pou.iter() {
+                    register_lookup_scopes(&mut unit, &pou.name, &lookup_scopes);
+                }
                 unit.units.append(&mut pou);
                 unit.implementations.append(&mut implementation);
             }
+            KeywordConfiguration => {
+                let configuration = parse_configuration(&mut lexer, &mut unit.global_vars);
+                unit.configurations.push(configuration);
+            }
             KeywordAction => {
-                if let Some(implementation) = parse_action(&mut lexer, linkage, None) {
+                if let Some(implementation) =
+                    parse_action(&mut lexer, get_non_function_linkage(linkage), None)
+                {
                     unit.implementations.push(implementation);
                 }
             }
@@ -61,22 +124,42 @@ pub fn parse(mut lexer: ParseSession, lnk: LinkageType) -> ParsedAst {
                     .last()
                     .map(|it| it.name.as_str())
                     .unwrap_or("__unknown__");
-                let mut actions = parse_actions(&mut lexer, linkage, last_pou);
+                let mut actions =
+                    parse_actions(&mut lexer, get_non_function_linkage(linkage), last_pou);
                 unit.implementations.append(&mut actions);
             }
             KeywordType => {
-                if let Some(unit_type) = parse_type(&mut lexer) {
+                if let Some(unit_type) = parse_type(&mut lexer, namespaces.last()) {
+                    if let Some(name) = unit_type.data_type.get_name() {
+                        register_lookup_scopes(&mut unit, name, &lookup_scopes);
+                    }
                     unit.types.push(unit_type);
                 }
             }
-            KeywordEndActions | End => return (unit, lexer.diagnostics),
+            KeywordEndActions | End => {
+                if !namespaces.is_empty() {
+                    lexer.accept_diagnostic(Diagnostic::missing_token(
+                        format!("{:?}", KeywordEndNamespace).as_str(),
+                        lexer.location(),
+                    ));
+                }
+                unit.comments = lexer.take_unattached_comments();
+                unit.warning_pragmas = lexer.take_warning_pragmas();
+                return (unit, lexer.diagnostics);
+            }
             _ => {
+                //skip everything up to the next declaration
+                let start = lexer.location().get_start();
+                let mut end = start;
+                while !is_top_level_keyword(&lexer.token) {
+                    end = lexer.location().get_end();
+                    lexer.advance();
+                }
                 lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
                     "StartKeyword",
-                    lexer.slice(),
-                    lexer.location(),
+                    lexer.slice_region(start..end),
+                    lexer.source_range(start..end),
                 ));
-                lexer.advance();
             }
         };
         linkage = lnk;
@@ -84,6 +167,235 @@ pub fn parse(mut lexer: ParseSession, lnk: LinkageType) -> ParsedAst {
     //the match in the loop will always return
 }
 
+/// returns true if the given token is handled on the top level of a file
+fn is_top_level_keyword(token: &Token) -> bool {
+    matches!(
+        token,
+        PropertyExternal
+            | PragmaExternal
+            | KeywordNamespace
+            | KeywordEndNamespace
+            | KeywordUsing
+            | KeywordVarGlobal
+            | KeywordProgram
+            | KeywordClass
+            | KeywordInterface
+            | KeywordFunction
+            | KeywordFunctionBlock
+            | KeywordConfiguration
+            | KeywordAction
+            | KeywordActions
+            | KeywordType
+            | KeywordEndActions
+            | End
+    )
+}
+
+/// returns the linkage of a declaration that is not a function, only functions can be
+/// implemented in a foreign language, other declarations are external instead
+fn get_non_function_linkage(linkage: LinkageType) -> LinkageType {
+    if linkage == LinkageType::Foreign {
+        LinkageType::External
+    } else {
+        linkage
+    }
+}
+
+/// returns the namespaces to search for unqualified names at the current position: the
+/// enclosing namespaces from the innermost outwards followed by the namespaces used (`USING`)
+fn get_lookup_scopes(namespaces: &[String], usings: &[Vec<String>]) -> Vec<String> {
+    namespaces
+        .iter()
+        .rev()
+        .chain(usings.iter().rev().flatten())
+        .cloned()
+        .collect()
+}
+
+/// prefixes the given name with the given namespace (e.g. `Lib.Sub.name`)
+fn qualify(namespace: Option<&String>, name: &str) -> String {
+    namespace
+        .map(|it| format!("{}.{}", it, name))
+        .unwrap_or_else(|| name.to_string())
+}
+
+fn register_lookup_scopes(unit: &mut CompilationUnit, name: &str, lookup_scopes: &[String]) {
+    if !lookup_scopes.is_empty() {
+        unit.lookup_scopes
+            .insert(name.to_lowercase(), lookup_scopes.to_vec());
+    }
+}
+
+/// parses a `CONFIGURATION`, the global variables declared in the configuration and in its
+/// resources are added to the given global variable blocks
+fn parse_configuration(
+    lexer: &mut ParseSession,
+    global_vars: &mut Vec<VariableBlock>,
+) -> Configuration {
+    let start = lexer.range().start;
+    lexer.advance(); //Consume the Configuration keyword
+    parse_any_in_region(lexer, vec![KeywordEndConfiguration], |lexer| {
+        let name = parse_identifier(lexer).unwrap_or_default();
+        let mut resources = vec![];
+        let mut config_variables = vec![];
+        loop {
+            match lexer.token {
+                KeywordVarGlobal => global_vars.push(parse_variable_block(
+                    lexer,
+                    VariableBlockType::Global,
+                    LinkageType::Internal,
+                )),
+                KeywordVarConfig => config_variables.extend(parse_config_variables(lexer)),
+                KeywordResource => resources.push(parse_resource(lexer, global_vars)),
+                _ => break,
+            }
+        }
+        Configuration {
+            name,
+            resources,
+            config_variables,
+            location: lexer.source_range(start..lexer.range().end),
+        }
+    })
+}
+
+fn parse_resource(lexer: &mut ParseSession, global_vars: &mut Vec<VariableBlock>) -> Resource {
+    let start = lexer.range().start;
+    lexer.advance(); //Consume the Resource keyword
+    parse_any_in_region(lexer, vec![KeywordEndResource], |lexer| {
+        let name = parse_identifier(lexer).unwrap_or_default();
+        let processing_unit = if lexer.allow(&KeywordOn) {
+            parse_identifier(lexer)
+        } else {
+            None
+        };
+        let mut tasks = vec![];
+        let mut programs = vec![];
+        loop {
+            match lexer.token {
+                KeywordVarGlobal => global_vars.push(parse_variable_block(
+                    lexer,
+                    VariableBlockType::Global,
+                    LinkageType::Internal,
+                )),
+                KeywordTask => tasks.push(parse_task(lexer)),
+                KeywordProgram => programs.push(parse_program_configuration(lexer)),
+                _ => break,
+            }
+        }
+        Resource {
+            name,
+            processing_unit,
+            tasks,
+            programs,
+            location: lexer.source_range(start..lexer.range().end),
+        }
+    })
+}
+
+/// parses a `VAR_CONFIG` block assigning addresses to instance variables
+/// (e.g. `VAR_CONFIG prg.inst.input AT %IX0.0 : BOOL; END_VAR`)
+fn parse_config_variables(lexer: &mut ParseSession) -> Vec<ConfigVariable> {
+    lexer.advance(); //Consume the VarConfig keyword
+    parse_any_in_region(lexer, vec![KeywordEndVar], |lexer| {
+        let mut variables = vec![];
+        while lexer.token == Identifier {
+            if let Some(variable) = parse_config_variable(lexer) {
+                variables.push(variable);
+            }
+        }
+        variables
+    })
+}
+
+/// parses a single entry of a `VAR_CONFIG` block (e.g. `prg.inst.input AT %IX0.0 : BOOL;`)
+fn parse_config_variable(lexer: &mut ParseSession) -> Option<ConfigVariable> {
+    let start = lexer.range().start;
+    let reference = parse_qualified_identifier(lexer)?;
+    lexer.consume_or_report(KeywordAt);
+    let address = parse_hardware_access(lexer);
+    lexer.consume_or_report(KeywordColon);
+    let data_type = parse_qualified_identifier(lexer);
+    let location = lexer.source_range(start..lexer.last_range.end);
+    lexer.consume_or_report(KeywordSemicolon);
+    Some(ConfigVariable {
+        reference,
+        data_type: data_type?,
+        address: address?,
+        location,
+    })
+}
+
+/// parses a task and its properties (e.g. `TASK fast(INTERVAL := T#10ms, PRIORITY := 1);`)
+fn parse_task(lexer: &mut ParseSession) -> Task {
+    let start = lexer.range().start;
+    lexer.advance(); //Consume the Task keyword
+    let name = parse_identifier(lexer).unwrap_or_default();
+    let mut task = Task {
+        name,
+        interval: None,
+        single: None,
+        priority: None,
+        location: SourceRange::undefined(),
+    };
+
+    lexer.consume_or_report(KeywordParensOpen);
+    let properties = parse_any_in_region(lexer, vec![KeywordParensClose], parse_expression);
+    task.location = lexer.source_range(start..lexer.last_range.end);
+    lexer.consume_or_report(KeywordSemicolon);
+
+    let properties = match properties {
+        AstStatement::ExpressionList { expressions, .. } => expressions,
+        property => vec![property],
+    };
+    for property in properties {
+        let location = property.get_location();
+        let value = match property {
+            AstStatement::Assignment { left, right, .. } => match *left {
+                AstStatement::Reference { name, .. } => Some((name.to_uppercase(), *right)),
+                _ => None,
+            },
+            _ => None,
+        };
+        match value {
+            Some((name, value)) if name == "INTERVAL" => task.interval = Some(value),
+            Some((name, value)) if name == "SINGLE" => task.single = Some(value),
+            Some((name, value)) if name == "PRIORITY" => task.priority = Some(value),
+            _ => lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+                "INTERVAL, SINGLE or PRIORITY",
+                lexer.slice_region(location.to_range()),
+                location,
+            )),
+        }
+    }
+    if task.priority.is_none() {
+        lexer.accept_diagnostic(Diagnostic::missing_token("PRIORITY", task.location.clone()));
+    }
+    task
+}
+
+/// parses a program instance of a resource (e.g. `PROGRAM fast_main WITH fast : main;`)
+fn parse_program_configuration(lexer: &mut ParseSession) -> ProgramConfiguration {
+    let start = lexer.range().start;
+    lexer.advance(); //Consume the Program keyword
+    let name = parse_identifier(lexer).unwrap_or_default();
+    let task = if lexer.allow(&KeywordWith) {
+        parse_identifier(lexer)
+    } else {
+        None
+    };
+    lexer.consume_or_report(KeywordColon);
+    let program = parse_qualified_identifier(lexer).unwrap_or_default();
+    let location = lexer.source_range(start..lexer.last_range.end);
+    lexer.consume_or_report(KeywordSemicolon);
+    ProgramConfiguration {
+        name,
+        task,
+        program,
+        location,
+    }
+}
+
 fn parse_actions(
     lexer: &mut ParseSession,
     linkage: LinkageType,
@@ -129,15 +441,24 @@ fn parse_actions(
 /// * `pou_type`    - the type of the pou currently parsed
 /// * `linkage`     - internal, external ?
 /// * `expected_end_token` - the token that ends this pou
+/// * `namespace`   - the namespace the pou is declared in
 ///
 fn parse_pou(
     lexer: &mut ParseSession,
     pou_type: PouType,
     linkage: LinkageType,
     expected_end_token: lexer::Token,
+    namespace: Option<&String>,
 ) -> (Vec<Pou>, Vec<Implementation>) {
     let start = lexer.range().start;
+    let attributes = lexer.take_attributes();
+    let comments = lexer.take_leading_comments();
     lexer.advance(); //Consume ProgramKeyword
+    let linkage = if pou_type == PouType::Function {
+        linkage
+    } else {
+        get_non_function_linkage(linkage)
+    };
     let closing_tokens = vec![
         expected_end_token.clone(),
         KeywordEndAction,
@@ -154,34 +475,49 @@ fn parse_pou(
             _ => None,
         };
 
-        let name = parse_identifier(lexer).unwrap_or_else(|| "".to_string()); // parse POU name
+        let name = qualify(
+            namespace,
+            &parse_identifier(lexer).unwrap_or_else(|| "".to_string()),
+        ); // parse POU name
 
         let generics = parse_generics(lexer);
 
         with_scope(lexer, name.clone(), |lexer| {
             // TODO: Parse USING directives
-            // TODO: Parse EXTENDS specifier
-            // TODO: Parse IMPLEMENTS specifier
+            let (super_class, interfaces) = match pou_type {
+                PouType::Class | PouType::FunctionBlock => {
+                    let super_class = if lexer.allow(&KeywordExtends) {
+                        parse_qualified_identifier(lexer)
+                    } else {
+                        None
+                    };
+                    (super_class, parse_implemented_interfaces(lexer))
+                }
+                _ => (None, vec![]),
+            };
 
-            let return_type = if pou_type != PouType::Class {
+            let return_type = if !matches!(pou_type, PouType::Class | PouType::Interface) {
                 // parse an optional return type
                 parse_return_type(lexer, &pou_type)
             } else {
-                // classes do not have a return type
+                // classes and interfaces do not have a return type
                 None
             };
 
             // parse variable declarations. note that var in/out/inout
-            // blocks are not allowed inside of class declarations.
+            // blocks are not allowed inside of class declarations and
+            // interfaces may not declare any variables.
             let mut variable_blocks = vec![];
             let allowed_var_types = match pou_type {
                 PouType::Class => vec![KeywordVar],
+                PouType::Interface => vec![],
                 _ => vec![
                     KeywordVar,
                     KeywordVarInput,
                     KeywordVarOutput,
                     KeywordVarInOut,
                     KeywordVarTemp,
+                    KeywordVarExternal,
                 ],
             };
             while allowed_var_types.contains(&lexer.token) {
@@ -192,23 +528,40 @@ fn parse_pou(
                 ));
             }
 
+            let mut generics = generics;
+            if pou_type == PouType::Function {
+                add_implicit_generics(&mut generics, return_type.as_ref(), &variable_blocks);
+            }
+
             let mut impl_pous = vec![];
             let mut implementations = vec![];
-            if pou_type == PouType::Class || pou_type == PouType::FunctionBlock {
-                // classes and function blocks can have methods. methods consist of a Pou part
+            if matches!(
+                pou_type,
+                PouType::Class | PouType::FunctionBlock | PouType::Interface
+            ) {
+                // classes, interfaces and function blocks can have methods. methods consist of a Pou part
                 // and an implementation part. That's why we get another (Pou, Implementation)
                 // tuple out of parse_method() that has to be added to the list of Pous and
                 // implementations. Note that function blocks have to start with the method
                 // declarations before their implementation.
-                while lexer.token == KeywordMethod {
-                    if let Some((pou, implementation)) = parse_method(lexer, &name, linkage) {
-                        impl_pous.push(pou);
-                        implementations.push(implementation);
+                loop {
+                    if lexer.token == KeywordMethod {
+                        if let Some((pou, implementation)) = parse_method(lexer, &name, linkage) {
+                            impl_pous.push(pou);
+                            implementations.push(implementation);
+                        }
+                    } else if lexer.token == KeywordProperty {
+                        for (pou, implementation) in parse_property(lexer, &name, linkage) {
+                            impl_pous.push(pou);
+                            implementations.push(implementation);
+                        }
+                    } else {
+                        break;
                     }
                 }
             }
-            if pou_type != PouType::Class {
-                // a class may not contain an implementation
+            if !matches!(pou_type, PouType::Class | PouType::Interface) {
+                // a class or an interface may not contain an implementation
                 implementations.push(parse_implementation(
                     lexer,
                     linkage,
@@ -223,10 +576,14 @@ fn parse_pou(
                 pou_type,
                 variable_blocks,
                 return_type,
-                location: SourceRange::new(start..lexer.range().end),
+                location: lexer.source_range(start..lexer.range().end),
                 poly_mode,
                 generics,
                 linkage,
+                interfaces,
+                super_class,
+                attributes,
+                comments,
             }];
             pous.append(&mut impl_pous);
 
@@ -239,12 +596,28 @@ fn parse_pou(
         lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
             format!("{:?}", expected_end_token).as_str(),
             lexer.slice_region(lexer.last_range.clone()),
-            SourceRange::new(lexer.last_range.clone()),
+            lexer.last_location(),
         ));
     }
     pou
 }
 
+/// parses the optional list of implemented interfaces (e.g. `IMPLEMENTS a, b`)
+fn parse_implemented_interfaces(lexer: &mut ParseSession) -> Vec<String> {
+    let mut interfaces = vec![];
+    if lexer.allow(&KeywordImplements) {
+        loop {
+            if let Some(name) = parse_qualified_identifier(lexer) {
+                interfaces.push(name);
+            }
+            if !lexer.allow(&KeywordComma) {
+                break;
+            }
+        }
+    }
+    interfaces
+}
+
 fn parse_generics(lexer: &mut ParseSession) -> Vec<GenericBinding> {
     if lexer.allow(&Token::OperatorLess) {
         parse_any_in_region(lexer, vec![Token::OperatorGreater], |lexer| {
@@ -275,25 +648,76 @@ fn parse_generics(lexer: &mut ParseSession) -> Vec<GenericBinding> {
 }
 
 fn parse_type_nature(lexer: &mut ParseSession, nature: &str) -> TypeNature {
+    get_type_nature(nature).unwrap_or_else(|| {
+        lexer.accept_diagnostic(Diagnostic::unknown_type_nature(nature, lexer.location()));
+        TypeNature::Any
+    })
+}
+
+/// returns the type nature of the given generic class (e.g. `ANY_INT`)
+fn get_type_nature(nature: &str) -> Option<TypeNature> {
     match nature {
-        "ANY" => TypeNature::Any,
-        "ANY_DERIVED" => TypeNature::Derived,
-        "ANY_ELEMENTARY" => TypeNature::Elementary,
-        "ANY_MAGNITUDE" => TypeNature::Magnitude,
-        "ANY_NUM" => TypeNature::Num,
-        "ANY_REAL" => TypeNature::Real,
-        "ANY_INT" => TypeNature::Int,
-        "ANY_SIGNED" => TypeNature::Signed,
-        "ANY_UNSIGNED" => TypeNature::Unsigned,
-        "ANY_DURATION" => TypeNature::Duration,
-        "ANY_BIT" => TypeNature::Bit,
-        "ANY_CHARS" => TypeNature::Chars,
-        "ANY_STRING" => TypeNature::String,
-        "ANY_CHAR" => TypeNature::Char,
-        "ANY_DATE" => TypeNature::Date,
-        _ => {
-            lexer.accept_diagnostic(Diagnostic::unknown_type_nature(nature, lexer.location()));
-            TypeNature::Any
+        "ANY" => Some(TypeNature::Any),
+        "ANY_DERIVED" => Some(TypeNature::Derived),
+        "ANY_ELEMENTARY" => Some(TypeNature::Elementary),
+        "ANY_MAGNITUDE" => Some(TypeNature::Magnitude),
+        "ANY_NUM" => Some(TypeNature::Num),
+        "ANY_REAL" => Some(TypeNature::Real),
+        "ANY_INT" => Some(TypeNature::Int),
+        "ANY_SIGNED" => Some(TypeNature::Signed),
+        "ANY_UNSIGNED" => Some(TypeNature::Unsigned),
+        "ANY_DURATION" => Some(TypeNature::Duration),
+        "ANY_BIT" => Some(TypeNature::Bit),
+        "ANY_CHARS" => Some(TypeNature::Chars),
+        "ANY_STRING" => Some(TypeNature::String),
+        "ANY_CHAR" => Some(TypeNature::Char),
+        "ANY_DATE" => Some(TypeNature::Date),
+        _ => None,
+    }
+}
+
+/// binds the generic classes (e.g. `ANY_INT`) used as the types of a function's variables or
+/// as its return type implicitly. All usages of the same generic class refer to the same binding,
+/// so they resolve to the same type for a call (e.g. `x : ANY_INT` is treated like `x : T` of
+/// `FUNCTION foo<T : ANY_INT>`)
+fn add_implicit_generics(
+    generics: &mut Vec<GenericBinding>,
+    return_type: Option<&DataTypeDeclaration>,
+    variable_blocks: &[VariableBlock],
+) {
+    fn get_referenced_type(declaration: &DataTypeDeclaration) -> Option<&str> {
+        match declaration {
+            DataTypeDeclaration::DataTypeReference {
+                referenced_type, ..
+            } => Some(referenced_type.as_str()),
+            DataTypeDeclaration::DataTypeDefinition {
+                data_type:
+                    DataType::ArrayType {
+                        referenced_type, ..
+                    }
+                    | DataType::PointerType {
+                        referenced_type, ..
+                    },
+                ..
+            } => get_referenced_type(referenced_type),
+            _ => None,
+        }
+    }
+
+    let declarations = return_type.into_iter().chain(
+        variable_blocks
+            .iter()
+            .flat_map(|it| it.variables.iter())
+            .map(|it| &it.data_type),
+    );
+    for name in declarations.filter_map(get_referenced_type) {
+        if let Some(nature) = get_type_nature(&name.to_uppercase()) {
+            if !generics.iter().any(|it| it.name.eq_ignore_ascii_case(name)) {
+                generics.push(GenericBinding {
+                    name: name.to_string(),
+                    nature,
+                });
+            }
         }
     }
 }
@@ -332,14 +756,16 @@ fn parse_return_type(lexer: &mut ParseSession, pou_type: &PouType) -> Option<Dat
             if !matches!(pou_type, PouType::Function | PouType::Method { .. }) {
                 lexer.accept_diagnostic(Diagnostic::return_type_not_supported(
                     pou_type,
-                    SourceRange::new(start_return_type..lexer.last_range.end),
+                    lexer.source_range(start_return_type..lexer.last_range.end),
                 ));
             }
 
             if let DataTypeDeclaration::DataTypeDefinition { data_type, .. } = &declaration {
                 if matches!(
                     data_type,
-                    DataType::EnumType { .. } | DataType::StructType { .. }
+                    DataType::EnumType { .. }
+                        | DataType::StructType { .. }
+                        | DataType::UnionType { .. }
                 ) {
                     lexer.accept_diagnostic(Diagnostic::function_unsupported_return_type(
                         &declaration,
@@ -352,7 +778,7 @@ fn parse_return_type(lexer: &mut ParseSession, pou_type: &PouType) -> Option<Dat
             lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
                 "Datatype",
                 lexer.slice(),
-                SourceRange::new(lexer.range()),
+                lexer.location(),
             ));
             None
         }
@@ -373,6 +799,8 @@ fn parse_method(
         // END_METHOD
 
         let method_start = lexer.location().get_start();
+        let attributes = lexer.take_attributes();
+        let comments = lexer.take_leading_comments();
         lexer.advance(); // eat METHOD keyword
 
         let access = Some(parse_access_modifier(lexer));
@@ -391,6 +819,7 @@ fn parse_method(
             || lexer.token == KeywordVarOutput
             || lexer.token == KeywordVarInOut
             || lexer.token == KeywordVarTemp
+            || lexer.token == KeywordVarExternal
         {
             variable_blocks.push(parse_variable_block(
                 lexer,
@@ -425,16 +854,140 @@ fn parse_method(
                 pou_type,
                 variable_blocks,
                 return_type,
-                location: SourceRange::new(method_start..method_end),
+                location: lexer.source_range(method_start..method_end),
                 poly_mode,
                 generics,
                 linkage,
+                interfaces: vec![],
+                super_class: None,
+                attributes,
+                comments,
             },
             implementation,
         ))
     })
 }
 
+/// parses a property with its accessors. The accessors are parsed as methods of the class,
+/// the GET accessor named `__get_<property>` returns the property, the SET accessor named
+/// `__set_<property>` receives the new value in an input named like the property.
+fn parse_property(
+    lexer: &mut ParseSession,
+    class_name: &str,
+    linkage: LinkageType,
+) -> Vec<(Pou, Implementation)> {
+    parse_any_in_region(lexer, vec![KeywordEndProperty], |lexer| {
+        // Property declarations look like this:
+        // PROPERTY [AccessModifier] name : type
+        //    [GET ... END_GET]
+        //    [SET ... END_SET]
+        // END_PROPERTY
+        //attributes are not supported on properties
+        lexer.take_attributes();
+        //the comments of the property belong to each of its accessors
+        let comments = lexer.take_leading_comments();
+        lexer.advance(); // eat PROPERTY keyword
+
+        let access = parse_access_modifier(lexer);
+        let name = match parse_identifier(lexer) {
+            Some(name) => name,
+            None => return vec![],
+        };
+        let pou_type = PouType::Method {
+            owner_class: class_name.into(),
+        };
+        let data_type = match parse_return_type(lexer, &pou_type) {
+            Some(data_type) => data_type,
+            None => {
+                lexer.accept_diagnostic(Diagnostic::missing_token("Datatype", lexer.location()));
+                return vec![];
+            }
+        };
+
+        let mut accessors = vec![];
+        //GET and SET are no keywords outside of properties
+        while lexer.token == Identifier {
+            let (accessor_name, end_token, return_type) =
+                match lexer.slice().to_uppercase().as_str() {
+                    "GET" => (
+                        format!("{}{}", PROPERTY_GETTER_PREFIX, name),
+                        KeywordEndGet,
+                        Some(data_type.clone()),
+                    ),
+                    "SET" => (
+                        format!("{}{}", PROPERTY_SETTER_PREFIX, name),
+                        KeywordEndSet,
+                        None,
+                    ),
+                    _ => break,
+                };
+            let accessor = parse_any_in_region(lexer, vec![end_token], |lexer| {
+                let start = lexer.location().get_start();
+                let mut accessor_comments = comments.clone();
+                accessor_comments.append(&mut lexer.take_leading_comments());
+                lexer.advance(); // eat GET or SET
+
+                let mut variable_blocks = vec![];
+                if return_type.is_none() {
+                    //the setter receives the new value
+                    variable_blocks.push(VariableBlock {
+                        access: AccessModifier::Protected,
+                        constant: false,
+                        retain: false,
+                        persistent: false,
+                        variables: vec![Variable {
+                            name: name.clone(),
+                            data_type: data_type.clone(),
+                            initializer: None,
+                            address: None,
+                            location: data_type.get_location(),
+                            attributes: vec![],
+                            comments: vec![],
+                        }],
+                        variable_block_type: VariableBlockType::Input,
+                        linkage: LinkageType::Internal,
+                        location: data_type.get_location(),
+                    });
+                }
+                while lexer.token == KeywordVar || lexer.token == KeywordVarTemp {
+                    variable_blocks.push(parse_variable_block(
+                        lexer,
+                        parse_variable_block_type(&lexer.token),
+                        LinkageType::Internal,
+                    ));
+                }
+
+                let call_name = format!("{}.{}", class_name, accessor_name);
+                let implementation =
+                    parse_implementation(lexer, linkage, pou_type.clone(), &call_name, &call_name);
+                let implementation = Implementation {
+                    access: Some(access),
+                    ..implementation
+                };
+                (
+                    Pou {
+                        name: call_name,
+                        pou_type: pou_type.clone(),
+                        variable_blocks,
+                        return_type,
+                        location: lexer.source_range(start..lexer.location().get_end()),
+                        poly_mode: None,
+                        generics: vec![],
+                        linkage,
+                        interfaces: vec![],
+                        super_class: None,
+                        attributes: vec![],
+                        comments: accessor_comments,
+                    },
+                    implementation,
+                )
+            });
+            accessors.push(accessor);
+        }
+        accessors
+    })
+}
+
 fn parse_access_modifier(lexer: &mut ParseSession) -> AccessModifier {
     if lexer.allow(&KeywordAccessPublic) {
         AccessModifier::Public
@@ -465,6 +1018,16 @@ fn parse_identifier(lexer: &mut ParseSession) -> Option<String> {
     }
 }
 
+/// parses an identifier that may be qualified by namespaces (e.g. `Lib.Sub.name`)
+fn parse_qualified_identifier(lexer: &mut ParseSession) -> Option<String> {
+    let mut name = parse_identifier(lexer)?;
+    while lexer.allow(&KeywordDot) {
+        name.push('.');
+        name.push_str(&parse_identifier(lexer)?);
+    }
+    Some(name)
+}
+
 fn parse_implementation(
     lexer: &mut ParseSession,
     linkage: LinkageType,
@@ -473,16 +1036,19 @@ fn parse_implementation(
     type_name: &str,
 ) -> Implementation {
     let start = lexer.range().start;
+    let mut comments = lexer.take_leading_comments();
     let statements = parse_body_standalone(lexer);
+    comments.append(&mut lexer.take_comments());
     Implementation {
         name: call_name.into(),
         type_name: type_name.into(),
         linkage,
         pou_type,
         statements,
-        location: SourceRange::new(start..lexer.range().end),
+        location: lexer.source_range(start..lexer.range().end),
         overriding: false,
         access: None,
+        comments,
     }
 }
 
@@ -531,10 +1097,12 @@ fn parse_action(
 }
 
 // TYPE ... END_TYPE
-fn parse_type(lexer: &mut ParseSession) -> Option<UserTypeDeclaration> {
+fn parse_type(lexer: &mut ParseSession, namespace: Option<&String>) -> Option<UserTypeDeclaration> {
+    let attributes = lexer.take_attributes();
+    let comments = lexer.take_leading_comments();
     lexer.advance(); // consume the TYPE
     let start = lexer.location().get_start();
-    let name = lexer.slice_and_advance();
+    let name = qualify(namespace, &lexer.slice_and_advance());
     lexer.consume_or_report(KeywordColon);
 
     let result = parse_full_data_type_definition(lexer, Some(name));
@@ -545,8 +1113,10 @@ fn parse_type(lexer: &mut ParseSession) -> Option<UserTypeDeclaration> {
         Some(UserTypeDeclaration {
             data_type,
             initializer,
-            location: (start..end).into(),
+            location: lexer.source_range(start..end),
             scope: lexer.scope.clone(),
+            attributes,
+            comments,
         })
     } else {
         None
@@ -559,10 +1129,10 @@ fn parse_full_data_type_definition(
     lexer: &mut ParseSession,
     name: Option<String>,
 ) -> Option<DataTypeWithInitializer> {
-    let end_keyword = if lexer.token == KeywordStruct {
-        KeywordEndStruct
-    } else {
-        KeywordSemicolon
+    let end_keyword = match lexer.token {
+        KeywordStruct => KeywordEndStruct,
+        KeywordUnion => KeywordEndUnion,
+        _ => KeywordSemicolon,
     };
     parse_any_in_region(lexer, vec![end_keyword], |lexer| {
         if lexer.allow(&KeywordDotDotDot) {
@@ -597,7 +1167,7 @@ fn parse_full_data_type_definition(
     })
 }
 
-// TYPE xxx : 'STRUCT' | '(' | IDENTIFIER
+// TYPE xxx : 'STRUCT' | 'UNION' | '(' | IDENTIFIER
 fn parse_data_type_definition(
     lexer: &mut ParseSession,
     name: Option<String>,
@@ -605,11 +1175,22 @@ fn parse_data_type_definition(
     let start = lexer.location().get_start();
     if lexer.allow(&KeywordStruct) {
         // Parse struct
-        let variables = parse_variable_list(lexer);
+        let variables = parse_variable_list(lexer, &[], vec![]);
         Some((
             DataTypeDeclaration::DataTypeDefinition {
                 data_type: DataType::StructType { name, variables },
-                location: (start..lexer.range().end).into(),
+                location: lexer.source_range(start..lexer.range().end),
+                scope: lexer.scope.clone(),
+            },
+            None,
+        ))
+    } else if lexer.allow(&KeywordUnion) {
+        // Parse union
+        let variables = parse_variable_list(lexer, &[], vec![]);
+        Some((
+            DataTypeDeclaration::DataTypeDefinition {
+                data_type: DataType::UnionType { name, variables },
+                location: lexer.source_range(start..lexer.range().end),
                 scope: lexer.scope.clone(),
             },
             None,
@@ -661,7 +1242,7 @@ fn parse_pointer_definition(
                     name,
                     referenced_type: Box::new(decl),
                 },
-                location: (start_pos..lexer.last_range.end).into(),
+                location: lexer.source_range(start_pos..lexer.last_range.end),
                 scope: lexer.scope.clone(),
             },
             initializer,
@@ -675,7 +1256,13 @@ fn parse_type_reference_type_definition(
 ) -> Option<(DataTypeDeclaration, Option<AstStatement>)> {
     let start = lexer.location().get_start();
     //Subrange
-    let referenced_type = lexer.slice_and_advance();
+    let mut referenced_type = lexer.slice_and_advance();
+    //the type may be qualified by its namespace
+    while lexer.token == KeywordDot {
+        lexer.advance();
+        referenced_type.push('.');
+        referenced_type.push_str(&lexer.slice_and_advance());
+    }
 
     let bounds = if lexer.allow(&KeywordParensOpen) {
         // INT (..) :=
@@ -704,7 +1291,7 @@ fn parse_type_reference_type_definition(
                         numeric_type: referenced_type,
                         elements: AstStatement::ExpressionList { expressions, id },
                     },
-                    location: (start..end).into(),
+                    location: lexer.source_range(start..end),
                     scope: lexer.scope.clone(),
                 }
             }
@@ -716,7 +1303,7 @@ fn parse_type_reference_type_definition(
                         numeric_type: referenced_type,
                         elements: bounds.unwrap(),
                     },
-                    location: (start..end).into(),
+                    location: lexer.source_range(start..end),
                     scope: lexer.scope.clone(),
                 }
             }
@@ -727,7 +1314,7 @@ fn parse_type_reference_type_definition(
                     referenced_type,
                     bounds,
                 },
-                location: (start..end).into(),
+                location: lexer.source_range(start..end),
                 scope: lexer.scope.clone(),
             },
         };
@@ -736,7 +1323,7 @@ fn parse_type_reference_type_definition(
         Some((
             DataTypeDeclaration::DataTypeReference {
                 referenced_type,
-                location: (start..end).into(),
+                location: lexer.source_range(start..end),
             },
             initial_value,
         ))
@@ -750,7 +1337,7 @@ fn parse_string_size_expression(lexer: &mut ParseSession) -> Option<AstStatement
         let closing_tokens = vec![KeywordSquareParensClose, KeywordParensClose];
         parse_any_in_region(lexer, closing_tokens, |lexer| {
             let size_expr = parse_expression(lexer);
-            let error_range = SourceRange::new(opening_location..lexer.location().get_end());
+            let error_range = lexer.source_range(opening_location..lexer.location().get_end());
 
             if (opening_token == KeywordParensOpen && lexer.token == KeywordSquareParensClose)
                 || (opening_token == KeywordSquareParensOpen && lexer.token == KeywordParensClose)
@@ -792,7 +1379,7 @@ fn parse_string_type_definition(
                 is_wide,
                 size,
             },
-            location: (start..end).into(),
+            location: lexer.source_range(start..end),
             scope: lexer.scope.clone(),
         },
         lexer
@@ -819,7 +1406,7 @@ fn parse_enum_type_definition(
                 elements,
                 numeric_type: DINT_TYPE.to_string(),
             },
-            location: (start..lexer.last_range.end).into(),
+            location: lexer.source_range(start..lexer.last_range.end),
             scope: lexer.scope.clone(),
         },
         None,
@@ -847,7 +1434,7 @@ fn parse_array_type_definition(
 
     let inner_type_defintion = parse_data_type_definition(lexer, None);
     inner_type_defintion.map(|(reference, initializer)| {
-        let location = SourceRange::new(start..reference.get_location().get_end());
+        let location = lexer.source_range(start..reference.get_location().get_end());
         (
             DataTypeDeclaration::DataTypeDefinition {
                 data_type: DataType::ArrayType {
@@ -873,6 +1460,8 @@ fn parse_body_standalone(lexer: &mut ParseSession) -> Vec<AstStatement> {
     while !lexer.closes_open_region(&lexer.token) {
         statements.push(parse_control(lexer));
     }
+    //pragmas inside of a body do not belong to a declaration
+    lexer.take_attributes();
     statements
 }
 
@@ -944,6 +1533,7 @@ fn parse_variable_block_type(block_type: &Token) -> VariableBlockType {
         KeywordVarOutput => VariableBlockType::Output,
         KeywordVarGlobal => VariableBlockType::Global,
         KeywordVarInOut => VariableBlockType::InOut,
+        KeywordVarExternal => VariableBlockType::External,
         _ => VariableBlockType::Local,
     }
 }
@@ -954,23 +1544,38 @@ fn parse_variable_block(
     linkage: LinkageType,
 ) -> VariableBlock {
     let location = lexer.location();
+    //the attributes preceding the block apply to all of its variables
+    let attributes = lexer.take_attributes();
+    //the comments preceding the block belong to its first variable
+    let comments = lexer.take_leading_comments();
     //Consume the type keyword
     lexer.advance();
 
     let constant = lexer.allow(&KeywordConstant);
 
-    let retain = lexer.allow(&KeywordRetain);
-    lexer.allow(&KeywordNonRetain);
+    //RETAIN and PERSISTENT may be combined in any order
+    let mut retain = false;
+    let mut persistent = false;
+    loop {
+        if lexer.allow(&KeywordRetain) {
+            retain = true;
+        } else if lexer.allow(&KeywordPersistent) {
+            persistent = true;
+        } else if !lexer.allow(&KeywordNonRetain) {
+            break;
+        }
+    }
 
     let access = parse_access_modifier(lexer);
 
     let variables = parse_any_in_region(lexer, vec![KeywordEndVar], |lexer| {
-        parse_variable_list(lexer)
+        parse_variable_list(lexer, &attributes, comments)
     });
     VariableBlock {
         access,
         constant,
         retain,
+        persistent,
         variables,
         variable_block_type,
         linkage,
@@ -978,16 +1583,35 @@ fn parse_variable_block(
     }
 }
 
-fn parse_variable_list(lexer: &mut ParseSession) -> Vec<Variable> {
+fn parse_variable_list(
+    lexer: &mut ParseSession,
+    attributes: &[Attribute],
+    mut comments: Vec<Comment>,
+) -> Vec<Variable> {
     let mut variables = vec![];
-    while lexer.token == Identifier {
-        let mut line_vars = parse_variable_line(lexer);
-        variables.append(&mut line_vars);
+    while !lexer.closes_open_region(&lexer.token) {
+        if lexer.token == Identifier {
+            let mut line_vars =
+                parse_variable_line(lexer, attributes, std::mem::take(&mut comments));
+            variables.append(&mut line_vars);
+        } else {
+            //skip the invalid declaration and continue with the next one
+            lexer.recover_until_semicolon();
+        }
     }
+    //pragmas at the end of the list do not belong to a declaration
+    lexer.take_attributes();
     variables
 }
 
-fn parse_variable_line(lexer: &mut ParseSession) -> Vec<Variable> {
+fn parse_variable_line(
+    lexer: &mut ParseSession,
+    block_attributes: &[Attribute],
+    mut comments: Vec<Comment>,
+) -> Vec<Variable> {
+    let mut attributes = block_attributes.to_vec();
+    attributes.append(&mut lexer.take_attributes());
+    comments.append(&mut lexer.take_leading_comments());
     // read in a comma separated list of variable names
     let mut var_names: Vec<(String, SourceRange)> = vec![];
     while lexer.token == Identifier {
@@ -1003,56 +1627,14 @@ fn parse_variable_line(lexer: &mut ParseSession) -> Vec<Variable> {
             let next_token_start = lexer.location().get_start();
             lexer.accept_diagnostic(Diagnostic::missing_token(
                 format!("{:?} or {:?}", KeywordColon, KeywordComma).as_str(),
-                SourceRange::new(identifier_end..next_token_start),
+                lexer.source_range(identifier_end..next_token_start),
             ));
         }
     }
 
     //See if there's an AT keyword
     let address = if lexer.allow(&KeywordAt) {
-        let start_location = lexer.last_range.start;
-        //Look for a hardware address
-        if let HardwareAccess((direction, access_type)) = lexer.token {
-            lexer.advance();
-            //Folowed by an integer
-            if access_type == DirectAccessType::Template || lexer.token == LiteralInteger {
-                let mut address = vec![];
-                if lexer.token == LiteralInteger {
-                    loop {
-                        let int = expressions_parser::parse_strict_literal_integer(lexer);
-                        match int {
-                            Ok(statement) => address.push(statement),
-                            Err(err) => {
-                                lexer.accept_diagnostic(err);
-                                break;
-                            }
-                        }
-                        if !lexer.allow(&KeywordDot) {
-                            break;
-                        }
-                    }
-                }
-                Some(AstStatement::HardwareAccess {
-                    access: access_type,
-                    direction,
-                    address,
-                    location: (start_location..lexer.last_range.end).into(),
-                    id: lexer.next_id(),
-                })
-            } else {
-                lexer.accept_diagnostic(Diagnostic::missing_token(
-                    "LiteralInteger",
-                    lexer.location(),
-                ));
-                None
-            }
-        } else {
-            lexer.accept_diagnostic(Diagnostic::missing_token(
-                "Hardware Access",
-                lexer.location(),
-            ));
-            None
-        }
+        parse_hardware_access(lexer)
     } else {
         None
     };
@@ -1068,6 +1650,7 @@ fn parse_variable_line(lexer: &mut ParseSession) -> Vec<Variable> {
     // create variables with the same data type for each of the names
     let mut variables = vec![];
     if let Some((data_type, initializer)) = parse_full_data_type_definition(lexer, None) {
+        comments.append(&mut lexer.take_trailing_comments());
         for (name, location) in var_names {
             variables.push(Variable {
                 name,
@@ -1075,8 +1658,57 @@ fn parse_variable_line(lexer: &mut ParseSession) -> Vec<Variable> {
                 location,
                 initializer: initializer.clone(),
                 address: address.clone(),
+                attributes: attributes.clone(),
+                comments: comments.clone(),
             });
         }
     }
     variables
 }
+
+/// parses the hardware address following an `AT` keyword (e.g. `%IX1.2` or `%Q*`)
+fn parse_hardware_access(lexer: &mut ParseSession) -> Option<AstStatement> {
+    let start_location = lexer.last_range.start;
+    //Look for a hardware address
+    if let HardwareAccess((direction, access_type)) = lexer.token {
+        lexer.advance();
+        //Folowed by an integer
+        if access_type == DirectAccessType::Template || lexer.token == LiteralInteger {
+            let mut address = vec![];
+            if lexer.token == LiteralInteger {
+                loop {
+                    let int = expressions_parser::parse_strict_literal_integer(lexer);
+                    match int {
+                        Ok(statement) => address.push(statement),
+                        Err(err) => {
+                            lexer.accept_diagnostic(err);
+                            break;
+                        }
+                    }
+                    if !lexer.allow(&KeywordDot) {
+                        break;
+                    }
+                }
+            }
+            Some(AstStatement::HardwareAccess {
+                access: access_type,
+                direction,
+                address,
+                location: lexer.source_range(start_location..lexer.last_range.end),
+                id: lexer.next_id(),
+            })
+        } else {
+            lexer.accept_diagnostic(Diagnostic::missing_token(
+                "LiteralInteger",
+                lexer.location(),
+            ));
+            None
+        }
+    } else {
+        lexer.accept_diagnostic(Diagnostic::missing_token(
+            "Hardware Access",
+            lexer.location(),
+        ));
+        None
+    }
+}
@@ -1,7 +1,6 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 use crate::{
     ast::*,
-    expect_token,
     lexer::Token::*,
     parser::{parse_any_in_region, parse_body_in_region},
     Diagnostic,
@@ -58,15 +57,7 @@ fn parse_if_statement(lexer: &mut ParseSession) -> AstStatement {
 
     while lexer.last_token == KeywordElseIf || lexer.last_token == KeywordIf {
         let condition = parse_expression(lexer);
-        expect_token!(
-            lexer,
-            KeywordThen,
-            AstStatement::EmptyStatement {
-                location: lexer.location(),
-                id: lexer.next_id(),
-            }
-        );
-        lexer.advance();
+        lexer.consume_or_report_unexpected(KeywordThen);
 
         let condition_block = ConditionalBlock {
             condition: Box::new(condition),
@@ -87,7 +78,7 @@ fn parse_if_statement(lexer: &mut ParseSession) -> AstStatement {
     AstStatement::IfStatement {
         blocks: conditional_blocks,
         else_block,
-        location: SourceRange::new(start..end),
+        location: lexer.source_range(start..end),
         id: lexer.next_id(),
     }
 }
@@ -97,26 +88,10 @@ fn parse_for_statement(lexer: &mut ParseSession) -> AstStatement {
     lexer.advance(); // FOR
 
     let counter_expression = parse_reference(lexer);
-    expect_token!(
-        lexer,
-        KeywordAssignment,
-        AstStatement::EmptyStatement {
-            location: lexer.location(),
-            id: lexer.next_id()
-        }
-    );
-    lexer.advance();
+    lexer.consume_or_report_unexpected(KeywordAssignment);
 
     let start_expression = parse_expression(lexer);
-    expect_token!(
-        lexer,
-        KeywordTo,
-        AstStatement::EmptyStatement {
-            location: lexer.location(),
-            id: lexer.next_id(),
-        }
-    );
-    lexer.advance();
+    lexer.consume_or_report_unexpected(KeywordTo);
     let end_expression = parse_expression(lexer);
 
     let step = if lexer.token == KeywordBy {
@@ -134,7 +109,7 @@ fn parse_for_statement(lexer: &mut ParseSession) -> AstStatement {
         end: Box::new(end_expression),
         by_step: step,
         body: parse_body_in_region(lexer, vec![KeywordEndFor]),
-        location: SourceRange::new(start..lexer.last_range.end),
+        location: lexer.source_range(start..lexer.last_range.end),
         id: lexer.next_id(),
     }
 }
@@ -149,7 +124,7 @@ fn parse_while_statement(lexer: &mut ParseSession) -> AstStatement {
     AstStatement::WhileLoopStatement {
         condition: Box::new(condition),
         body: parse_body_in_region(lexer, vec![KeywordEndWhile]),
-        location: SourceRange::new(start..lexer.last_range.end),
+        location: lexer.source_range(start..lexer.last_range.end),
         id: lexer.next_id(),
     }
 }
@@ -173,7 +148,7 @@ fn parse_repeat_statement(lexer: &mut ParseSession) -> AstStatement {
     AstStatement::RepeatLoopStatement {
         condition: Box::new(condition),
         body,
-        location: SourceRange::new(start..lexer.range().end),
+        location: lexer.source_range(start..lexer.range().end),
         id: lexer.next_id(),
     }
 }
@@ -183,17 +158,7 @@ fn parse_case_statement(lexer: &mut ParseSession) -> AstStatement {
     lexer.advance(); // CASE
 
     let selector = Box::new(parse_expression(lexer));
-
-    expect_token!(
-        lexer,
-        KeywordOf,
-        AstStatement::EmptyStatement {
-            location: lexer.location(),
-            id: lexer.next_id()
-        }
-    );
-
-    lexer.advance();
+    lexer.consume_or_report_unexpected(KeywordOf);
 
     let mut case_blocks = Vec::new();
     if lexer.token != KeywordEndCase && lexer.token != KeywordElse {
@@ -247,7 +212,7 @@ fn parse_case_statement(lexer: &mut ParseSession) -> AstStatement {
         selector,
         case_blocks,
         else_block,
-        location: SourceRange::new(start..end),
+        location: lexer.source_range(start..end),
         id: lexer.next_id(),
     }
 }
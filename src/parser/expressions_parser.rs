@@ -5,12 +5,16 @@ use crate::{
     lexer::Token::*,
     lexer::{ParseSession, Token},
     parser::parse_any_in_region,
+    typesystem::ULINT_TYPE,
     Diagnostic,
 };
+use chrono::NaiveDate;
 use core::str::Split;
 use regex::{Captures, Regex};
 use std::str::FromStr;
 
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
 macro_rules! parse_left_associative_expression {
     ($lexer: expr, $action : expr,
         $( $pattern:pat_param )|+,
@@ -83,9 +87,9 @@ pub(crate) fn parse_range_statement(lexer: &mut ParseSession) -> AstStatement {
     start
 }
 
-// OR
+// OR, OR_ELSE
 fn parse_or_expression(lexer: &mut ParseSession) -> AstStatement {
-    parse_left_associative_expression!(lexer, parse_xor_expression, OperatorOr,)
+    parse_left_associative_expression!(lexer, parse_xor_expression, OperatorOr | OperatorOrElse,)
 }
 
 // XOR
@@ -93,9 +97,13 @@ fn parse_xor_expression(lexer: &mut ParseSession) -> AstStatement {
     parse_left_associative_expression!(lexer, parse_and_expression, OperatorXor,)
 }
 
-// AND
+// AND, AND_THEN
 fn parse_and_expression(lexer: &mut ParseSession) -> AstStatement {
-    parse_left_associative_expression!(lexer, parse_equality_expression, OperatorAnd,)
+    parse_left_associative_expression!(
+        lexer,
+        parse_equality_expression,
+        OperatorAnd | OperatorAndThen,
+    )
 }
 
 //EQUALITY  =, <>
@@ -129,11 +137,16 @@ fn parse_additive_expression(lexer: &mut ParseSession) -> AstStatement {
 fn parse_multiplication_expression(lexer: &mut ParseSession) -> AstStatement {
     parse_left_associative_expression!(
         lexer,
-        parse_unary_expression,
+        parse_exponent_expression,
         OperatorMultiplication | OperatorDivision | OperatorModulo,
     )
 }
 
+// Exponentiation **
+fn parse_exponent_expression(lexer: &mut ParseSession) -> AstStatement {
+    parse_left_associative_expression!(lexer, parse_unary_expression, OperatorExponent,)
+}
+
 // UNARY -x, NOT x
 fn parse_unary_expression(lexer: &mut ParseSession) -> AstStatement {
     let operator = match lexer.token {
@@ -148,7 +161,7 @@ fn parse_unary_expression(lexer: &mut ParseSession) -> AstStatement {
         lexer.advance();
         let expression = parse_parenthesized_expression(lexer);
         let expression_location = expression.get_location();
-        let location = SourceRange::new(start..expression_location.get_end());
+        let location = lexer.source_range(start..expression_location.get_end());
 
         if let (AstStatement::LiteralInteger { value, .. }, Operator::Minus) =
             (&expression, &operator)
@@ -191,6 +204,7 @@ fn to_operator(token: &Token) -> Option<Operator> {
         OperatorPlus => Some(Operator::Plus),
         OperatorMinus => Some(Operator::Minus),
         OperatorMultiplication => Some(Operator::Multiplication),
+        OperatorExponent => Some(Operator::Exponentiation),
         OperatorDivision => Some(Operator::Division),
         OperatorEqual => Some(Operator::Equal),
         OperatorNotEqual => Some(Operator::NotEqual),
@@ -200,7 +214,9 @@ fn to_operator(token: &Token) -> Option<Operator> {
         OperatorGreaterOrEqual => Some(Operator::GreaterOrEqual),
         OperatorModulo => Some(Operator::Modulo),
         OperatorAnd => Some(Operator::And),
+        OperatorAndThen => Some(Operator::AndThen),
         OperatorOr => Some(Operator::Or),
+        OperatorOrElse => Some(Operator::OrElse),
         OperatorXor => Some(Operator::Xor),
         OperatorNot => Some(Operator::Not),
         _ => None,
@@ -266,7 +282,8 @@ fn parse_leaf_expression(lexer: &mut ParseSession) -> AstStatement {
 
             Ok(AstStatement::CastStatement {
                 id: lexer.next_id(),
-                location: (location.get_start()..statement.get_location().get_end()).into(),
+                location: lexer
+                    .source_range(location.get_start()..statement.get_location().get_end()),
                 target: Box::new(statement),
                 type_name: cast,
             })
@@ -316,7 +333,7 @@ fn parse_array_literal(lexer: &mut ParseSession) -> Result<AstStatement, Diagnos
     lexer.advance();
     Ok(AstStatement::LiteralArray {
         elements,
-        location: SourceRange::new(start..end),
+        location: lexer.source_range(start..end),
         id: lexer.next_id(),
     })
 }
@@ -384,7 +401,7 @@ pub fn parse_qualified_reference(lexer: &mut ParseSession) -> Result<AstStatemen
             AstStatement::CallStatement {
                 operator: Box::new(reference),
                 parameters: Box::new(None),
-                location: SourceRange::new(start..lexer.range().end),
+                location: lexer.source_range(start..lexer.range().end),
                 id: lexer.next_id(),
             }
         } else {
@@ -392,7 +409,7 @@ pub fn parse_qualified_reference(lexer: &mut ParseSession) -> Result<AstStatemen
                 AstStatement::CallStatement {
                     operator: Box::new(reference),
                     parameters: Box::new(Some(parse_expression_list(lexer))),
-                    location: SourceRange::new(start..lexer.range().end),
+                    location: lexer.source_range(start..lexer.range().end),
                     id: lexer.next_id(),
                 }
             })
@@ -421,7 +438,7 @@ fn parse_direct_access(
         )),
     }?;
 
-    let location = (location.get_start()..lexer.last_location().get_end()).into();
+    let location = lexer.source_range(location.get_start()..lexer.last_location().get_end());
     Ok(AstStatement::DirectAccess {
         access,
         index: Box::new(index),
@@ -470,15 +487,21 @@ fn parse_literal_number_with_modifier(
     radix: u32,
     is_negative: bool,
 ) -> Result<AstStatement, Diagnostic> {
+    //correct the location if we just parsed a minus before
+    let location = if is_negative {
+        lexer.source_range(lexer.last_range.start..lexer.location().get_end())
+    } else {
+        lexer.location()
+    };
     // we can safely unwrap the number string, since the token has
     // been matched using regular expressions
-    let location = lexer.location();
     let token = lexer.slice_and_advance();
     let number_str = token.split('#').last().expect("token with '#'");
     let number_str = number_str.replace("_", "");
 
-    // again, the parsed number can be safely unwrapped.
-    let value = i128::from_str_radix(number_str.as_str(), radix).expect("valid i128");
+    let value = i128::from_str_radix(number_str.as_str(), radix).map_err(|_| {
+        Diagnostic::literal_out_of_range(token.as_str(), ULINT_TYPE, location.clone())
+    })?;
     let value = if is_negative { -value } else { value };
     Ok(AstStatement::LiteralInteger {
         value,
@@ -494,7 +517,7 @@ fn parse_literal_number(
 ) -> Result<AstStatement, Diagnostic> {
     //correct the location if we just parsed a minus before
     let location = if is_negative {
-        (lexer.last_range.start..lexer.location().get_end()).into()
+        lexer.source_range(lexer.last_range.start..lexer.location().get_end())
     } else {
         lexer.location()
     };
@@ -520,15 +543,14 @@ fn parse_literal_number(
         return Ok(AstStatement::MultipliedStatement {
             multiplier,
             element: Box::new(element),
-            location: SourceRange::new(location.get_start()..end),
+            location: lexer.source_range(location.get_start()..end),
             id: lexer.next_id(),
         });
     }
 
-    // parsed number value can be safely unwrapped
-    let result = result.replace("_", "");
-
-    let value = result.parse::<i128>().expect("valid i128");
+    let value = result.replace("_", "").parse::<i128>().map_err(|_| {
+        Diagnostic::literal_out_of_range(result.as_str(), ULINT_TYPE, location.clone())
+    })?;
     let value = if is_negative { -value } else { value };
 
     Ok(AstStatement::LiteralInteger {
@@ -590,6 +612,7 @@ fn parse_date_from_string(
         .next()
         .map(|s| parse_number::<u32>(s, &location))
         .expect("day-segment - tokenizer broken?")?;
+    validate_date_time(year, month, day, 0, 0, 0, 0, &location)?;
 
     Ok(AstStatement::LiteralDate {
         year,
@@ -627,7 +650,8 @@ fn parse_literal_date_and_time(lexer: &mut ParseSession) -> Result<AstStatement,
 
     //we can safely expect 3 numbers
     let mut segments = time.split(':');
-    let (hour, min, sec, milli) = parse_time_of_day(&mut segments, &location)?;
+    let (hour, min, sec, nano) = parse_time_of_day(&mut segments, &location)?;
+    validate_date_time(year, month, day, hour, min, sec, nano, &location)?;
 
     Ok(AstStatement::LiteralDateAndTime {
         location,
@@ -637,7 +661,7 @@ fn parse_literal_date_and_time(lexer: &mut ParseSession) -> Result<AstStatement,
         hour,
         min,
         sec,
-        milli,
+        nano,
         id: lexer.next_id(),
     })
 }
@@ -660,13 +684,14 @@ fn parse_literal_time_of_day(lexer: &mut ParseSession) -> Result<AstStatement, D
     let (_, slice) = slice.split_at(hash_location + 1); //get rid of the prefix
 
     let mut segments = slice.split(':');
-    let (hour, min, sec, milli) = parse_time_of_day(&mut segments, &location)?;
+    let (hour, min, sec, nano) = parse_time_of_day(&mut segments, &location)?;
+    validate_date_time(1970, 1, 1, hour, min, sec, nano, &location)?;
 
     Ok(AstStatement::LiteralTimeOfDay {
         hour,
         min,
         sec,
-        milli,
+        nano,
         location,
         id: lexer.next_id(),
     })
@@ -680,14 +705,57 @@ fn parse_time_of_day(
     let min = parse_number::<u32>(time.next().expect("valid u32"), location)?;
 
     // doesn't necessarily have to have seconds, e.g [12:00] is also valid
-    let sec = match time.next() {
-        Some(v) => parse_number::<f64>(v, location)?,
-        None => 0.0,
+    let (sec, nano) = match time.next().map(|it| it.split_once('.').unwrap_or((it, ""))) {
+        Some((sec, fraction)) => {
+            //the fraction has nanosecond precision, further digits are cut off
+            let fraction = format!("{:0<9.9}", fraction);
+            (
+                parse_number::<u32>(sec, location)?,
+                parse_number::<u32>(fraction.as_str(), location)?,
+            )
+        }
+        None => (0, 0),
     };
 
-    let milli = (sec.fract() * 1000_f64) as u32;
+    Ok((hour, min, sec, nano))
+}
 
-    Ok((hour, min, sec.floor() as u32, milli))
+/// makes sure the given point in time exists and can be stored as nanoseconds
+/// since 1970-01-01-00:00:00
+#[allow(clippy::too_many_arguments)]
+fn validate_date_time(
+    year: i32,
+    month: u32,
+    day: u32,
+    hour: u32,
+    min: u32,
+    sec: u32,
+    nano: u32,
+    location: &SourceRange,
+) -> Result<(), Diagnostic> {
+    let date_time = NaiveDate::from_ymd_opt(year, month, day)
+        .and_then(|date| date.and_hms_nano_opt(hour, min, sec, nano))
+        .ok_or_else(|| {
+            Diagnostic::syntax_error(
+                format!(
+                    "Invalid date or time {}-{:02}-{:02}-{:02}:{:02}:{:02}.{:09}",
+                    year, month, day, hour, min, sec, nano
+                )
+                .as_str(),
+                location.clone(),
+            )
+        })?;
+    date_time
+        .timestamp()
+        .checked_mul(NANOS_PER_SECOND)
+        .and_then(|nanos| nanos.checked_add(nano as i64))
+        .map(|_| ())
+        .ok_or_else(|| {
+            Diagnostic::syntax_error(
+                format!("Date {} exceeds the range of nanoseconds", date_time).as_str(),
+                location.clone(),
+            )
+        })
 }
 
 fn parse_literal_time(lexer: &mut ParseSession) -> Result<AstStatement, Diagnostic> {
@@ -779,6 +847,19 @@ fn parse_literal_time(lexer: &mut ParseSession) -> Result<AstStatement, Diagnost
         }
     }
 
+    //the value must fit into 64 bits of nanoseconds
+    let nanos = values
+        .iter()
+        .zip([86_400e9, 3_600e9, 60e9, 1e9, 1e6, 1e3, 1.0])
+        .map(|(value, factor)| value.unwrap_or_default() * factor)
+        .sum::<f64>();
+    if nanos >= i64::MAX as f64 {
+        return Err(Diagnostic::syntax_error(
+            "Invalid TIME Literal: value exceeds the range of nanoseconds",
+            location,
+        ));
+    }
+
     Ok(AstStatement::LiteralTime {
         day: values[POS_D].unwrap_or_default(),
         hour: values[POS_H].unwrap_or_default(),
@@ -879,8 +960,9 @@ fn parse_literal_real(
             if is_negative { "-" } else { "" },
             integer,
             fractional
-        );
-        let new_location = SourceRange::new(start..end);
+        )
+        .replace('_', "");
+        let new_location = lexer.source_range(start..end);
         Ok(AstStatement::LiteralReal {
             value: result,
             location: new_location,
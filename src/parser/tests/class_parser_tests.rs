@@ -320,3 +320,121 @@ fn fb_method_with_return_type_can_be_parsed() {
     assert_ne!(method_pou.return_type, None);
     assert_eq!(method.overriding, true);
 }
+
+#[test]
+fn interface_with_methods_can_be_parsed() {
+    let src = "
+        INTERFACE MyInterface
+            METHOD foo : INT
+                VAR_INPUT a : INT; END_VAR
+            END_METHOD
+            METHOD bar
+            END_METHOD
+        END_INTERFACE";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    let interface = &unit.units[0];
+    assert_eq!(interface.pou_type, PouType::Interface);
+    assert_eq!(interface.name, "MyInterface");
+    assert_eq!(interface.return_type, None);
+    assert!(interface.variable_blocks.is_empty());
+
+    // the methods are prototypes with an empty body
+    assert_eq!(unit.units[1].name, "MyInterface.foo");
+    assert_eq!(unit.units[2].name, "MyInterface.bar");
+    assert_eq!(unit.implementations.len(), 2);
+    assert!(unit
+        .implementations
+        .iter()
+        .all(|it| it.statements.is_empty()));
+}
+
+#[test]
+fn implemented_interfaces_can_be_parsed() {
+    let src = "
+        CLASS MyClass IMPLEMENTS a, b END_CLASS
+        FUNCTION_BLOCK fb IMPLEMENTS c END_FUNCTION_BLOCK
+        FUNCTION_BLOCK fb2 END_FUNCTION_BLOCK";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(unit.units[0].interfaces, vec!["a", "b"]);
+    assert_eq!(unit.units[1].interfaces, vec!["c"]);
+    assert!(unit.units[2].interfaces.is_empty());
+}
+
+#[test]
+fn extended_classes_and_function_blocks_can_be_parsed() {
+    let src = "
+        CLASS MyClass EXTENDS base IMPLEMENTS a END_CLASS
+        FUNCTION_BLOCK fb EXTENDS base_fb END_FUNCTION_BLOCK
+        FUNCTION_BLOCK fb2 END_FUNCTION_BLOCK";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(unit.units[0].super_class.as_deref(), Some("base"));
+    assert_eq!(unit.units[0].interfaces, vec!["a"]);
+    assert_eq!(unit.units[1].super_class.as_deref(), Some("base_fb"));
+    assert_eq!(unit.units[2].super_class, None);
+}
+
+#[test]
+fn properties_can_be_parsed() {
+    let src = "
+        FUNCTION_BLOCK fb
+            PROPERTY PUBLIC Level : INT
+                GET
+                    VAR tmp : INT; END_VAR
+                    Level := tmp;
+                END_GET
+                SET
+                END_SET
+            END_PROPERTY
+            PROPERTY ReadOnly : BOOL
+                GET END_GET
+            END_PROPERTY
+        END_FUNCTION_BLOCK";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    // the accessors are methods of the function block
+    let names = unit
+        .units
+        .iter()
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "fb",
+            "fb.__get_Level",
+            "fb.__set_Level",
+            "fb.__get_ReadOnly"
+        ]
+    );
+    assert!(unit.units[1..].iter().all(|it| it.pou_type
+        == PouType::Method {
+            owner_class: "fb".into()
+        }));
+
+    // the getter returns the property's type and may declare local variables
+    let getter = &unit.units[1];
+    assert_eq!(
+        getter.return_type.as_ref().and_then(|it| it.get_name()),
+        Some("INT")
+    );
+    assert_eq!(getter.variable_blocks[0].variables[0].name, "tmp");
+    assert_eq!(getter.get_return_name(), "Level");
+    assert_eq!(unit.implementations[0].statements.len(), 1);
+    assert_eq!(unit.implementations[0].access, Some(AccessModifier::Public));
+
+    // the setter receives the new value in an input named like the property
+    let setter = &unit.units[2];
+    assert_eq!(setter.return_type, None);
+    assert_eq!(
+        setter.variable_blocks[0].variable_block_type,
+        VariableBlockType::Input
+    );
+    assert_eq!(setter.variable_blocks[0].variables[0].name, "Level");
+}
@@ -0,0 +1,135 @@
+use crate::{ast::*, test_utils::tests::parse, Diagnostic};
+use pretty_assertions::*;
+
+#[test]
+fn configurations_with_resources_tasks_and_programs_can_be_parsed() {
+    let src = "
+        CONFIGURATION plc
+            VAR_GLOBAL a : INT; END_VAR
+            RESOURCE cpu ON PLC
+                VAR_GLOBAL trigger : BOOL; END_VAR
+                TASK fast(INTERVAL := T#10ms, PRIORITY := 1);
+                TASK event(SINGLE := trigger, PRIORITY := 2);
+                PROGRAM fast_main WITH fast : main;
+                PROGRAM idle : Lib.main;
+            END_RESOURCE
+            RESOURCE other
+            END_RESOURCE
+        END_CONFIGURATION
+        ";
+    let (unit, diagnostics) = parse(src);
+    assert_eq!(diagnostics, vec![]);
+
+    // the global variables of the configuration and its resources are global variables
+    let globals = unit
+        .global_vars
+        .iter()
+        .flat_map(|it| it.variables.iter())
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(globals, vec!["a", "trigger"]);
+
+    let configuration = &unit.configurations[0];
+    assert_eq!(configuration.name, "plc");
+    let resources = configuration
+        .resources
+        .iter()
+        .map(|it| (it.name.as_str(), it.processing_unit.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(resources, vec![("cpu", Some("PLC")), ("other", None)]);
+
+    let resource = &configuration.resources[0];
+    let fast = &resource.tasks[0];
+    assert_eq!(fast.name, "fast");
+    assert!(matches!(
+        fast.interval,
+        Some(AstStatement::LiteralTime { .. })
+    ));
+    assert!(matches!(
+        fast.priority,
+        Some(AstStatement::LiteralInteger { value: 1, .. })
+    ));
+    let event = &resource.tasks[1];
+    assert_eq!(event.interval, None);
+    assert_eq!(
+        format!("{:?}", event.single),
+        r#"Some(Reference { name: "trigger" })"#
+    );
+
+    let programs = resource
+        .programs
+        .iter()
+        .map(|it| (it.name.as_str(), it.task.as_deref(), it.program.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        programs,
+        vec![
+            ("fast_main", Some("fast"), "main"),
+            ("idle", None, "Lib.main")
+        ]
+    );
+}
+
+#[test]
+fn tasks_with_unknown_properties_or_without_priority_are_reported() {
+    let src = "
+        CONFIGURATION plc
+            RESOURCE cpu ON PLC
+                TASK fast(INTERVAL := T#10ms, CYCLE := 1);
+            END_RESOURCE
+        END_CONFIGURATION
+        ";
+    let (_, diagnostics) = parse(src);
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unexpected_token_found(
+                "INTERVAL, SINGLE or PRIORITY",
+                "CYCLE := 1",
+                SourceRange::new(105..115)
+            ),
+            Diagnostic::missing_token("PRIORITY", SourceRange::new(75..116)),
+        ]
+    );
+}
+
+#[test]
+fn var_config_blocks_can_be_parsed() {
+    let src = "
+        CONFIGURATION plc
+            VAR_CONFIG
+                main.inst.input AT %IX1.2 : BOOL;
+                main.out AT %QW4 : WORD;
+            END_VAR
+        END_CONFIGURATION
+        ";
+    let (unit, diagnostics) = parse(src);
+    assert_eq!(diagnostics, vec![]);
+
+    let variables = &unit.configurations[0].config_variables;
+    let entries = variables
+        .iter()
+        .map(|it| (it.reference.as_str(), it.data_type.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        vec![("main.inst.input", "BOOL"), ("main.out", "WORD")]
+    );
+    assert!(matches!(
+        variables[0].address,
+        AstStatement::HardwareAccess {
+            direction: HardwareAccessType::Input,
+            access: DirectAccessType::Bit,
+            ..
+        }
+    ));
+    assert!(matches!(
+        variables[1].address,
+        AstStatement::HardwareAccess {
+            direction: HardwareAccessType::Output,
+            access: DirectAccessType::Word,
+            ..
+        }
+    ));
+    assert_eq!(variables[1].location, SourceRange::new(116..139));
+}
@@ -989,43 +989,43 @@ fn literal_time_of_day_test() {
         hour: 12,
         min: 0,
         sec: 0,
-        milli: 0,
+        nano: 0,
     },
     LiteralTimeOfDay {
         hour: 0,
         min: 12,
         sec: 0,
-        milli: 0,
+        nano: 0,
     },
     LiteralTimeOfDay {
         hour: 0,
         min: 0,
         sec: 12,
-        milli: 0,
+        nano: 0,
     },
     LiteralTimeOfDay {
         hour: 4,
         min: 16,
         sec: 22,
-        milli: 0,
+        nano: 0,
     },
     LiteralTimeOfDay {
         hour: 4,
         min: 16,
         sec: 22,
-        milli: 100,
+        nano: 100000000,
     },
     LiteralTimeOfDay {
         hour: 4,
         min: 16,
         sec: 22,
-        milli: 1,
+        nano: 1000000,
     },
     LiteralTimeOfDay {
         hour: 4,
         min: 16,
         sec: 0,
-        milli: 0,
+        nano: 0,
     },
 ]"#;
     assert_eq!(ast_string, expected_ast);
@@ -1051,7 +1051,7 @@ fn literal_date_and_time_test() {
         hour: 16,
         min: 40,
         sec: 22,
-        milli: 0,
+        nano: 0,
     },
     LiteralDateAndTime {
         year: 2021,
@@ -1060,7 +1060,7 @@ fn literal_date_and_time_test() {
         hour: 22,
         min: 33,
         sec: 14,
-        milli: 0,
+        nano: 0,
     },
     LiteralDateAndTime {
         year: 2021,
@@ -1069,7 +1069,7 @@ fn literal_date_and_time_test() {
         hour: 22,
         min: 33,
         sec: 14,
-        milli: 999,
+        nano: 999000000,
     },
     LiteralDateAndTime {
         year: 2000,
@@ -1078,7 +1078,54 @@ fn literal_date_and_time_test() {
         hour: 20,
         min: 15,
         sec: 0,
-        milli: 0,
+        nano: 0,
+    },
+]"#;
+    assert_eq!(ast_string, expected_ast);
+}
+
+#[test]
+fn long_date_and_time_literals_keep_nanoseconds_test() {
+    let src = "
+        PROGRAM exp 
+            LTIME#1d2h3.5s;
+            LTOD#12:00:00.123456789;
+            LDT#2021-04-20-22:33:14.0000001;
+            LD#2021-04-20;
+        END_PROGRAM
+        ";
+    let result = parse(src).0;
+    let ast_string = format!("{:#?}", &result.implementations[0].statements);
+    let expected_ast = r#"[
+    LiteralTime {
+        day: 1.0,
+        hour: 2.0,
+        min: 0.0,
+        sec: 3.5,
+        milli: 0.0,
+        micro: 0.0,
+        nano: 0,
+        negative: false,
+    },
+    LiteralTimeOfDay {
+        hour: 12,
+        min: 0,
+        sec: 0,
+        nano: 123456789,
+    },
+    LiteralDateAndTime {
+        year: 2021,
+        month: 4,
+        day: 20,
+        hour: 22,
+        min: 33,
+        sec: 14,
+        nano: 100,
+    },
+    LiteralDate {
+        year: 2021,
+        month: 4,
+        day: 20,
     },
 ]"#;
     assert_eq!(ast_string, expected_ast);
@@ -1120,6 +1167,32 @@ fn literal_real_test() {
     assert_eq!(ast_string, expected_ast);
 }
 
+#[test]
+fn literal_real_with_exponents_and_underscores_test() {
+    let src = "
+        PROGRAM exp 
+        1.5E-3;
+        3e8;
+        1_000.000_1;
+        END_PROGRAM
+        ";
+    let result = parse(src).0;
+
+    let ast_string = format!("{:#?}", &result.implementations[0].statements);
+    let expected_ast = r#"[
+    LiteralReal {
+        value: "1.5E-3",
+    },
+    LiteralReal {
+        value: "3e8",
+    },
+    LiteralReal {
+        value: "1000.0001",
+    },
+]"#;
+    assert_eq!(ast_string, expected_ast);
+}
+
 fn literal_int_cast(data_type: &str, value: i128) -> AstStatement {
     AstStatement::CastStatement {
         id: 0,
@@ -1599,6 +1672,96 @@ fn boolean_priority_test() {
     assert_eq!(ast_string, expected_ast);
 }
 
+#[test]
+fn short_circuit_operators_priority_test() {
+    let src = "
+        PROGRAM exp 
+        a AND_THEN b XOR c OR_ELSE d;
+        END_PROGRAM
+        ";
+    let result = parse(src).0;
+
+    let prg = &result.implementations[0];
+    let statement = &prg.statements[0];
+
+    let ast_string = format!("{:#?}", statement);
+    let expected_ast = r#"BinaryExpression {
+    operator: OrElse,
+    left: BinaryExpression {
+        operator: Xor,
+        left: BinaryExpression {
+            operator: AndThen,
+            left: Reference {
+                name: "a",
+            },
+            right: Reference {
+                name: "b",
+            },
+        },
+        right: Reference {
+            name: "c",
+        },
+    },
+    right: Reference {
+        name: "d",
+    },
+}"#;
+    assert_eq!(ast_string, expected_ast);
+}
+
+#[test]
+fn exponent_priority_test() {
+    let src = "
+        PROGRAM exp 
+        a * b ** c ** -2;
+        -x ** 2;
+        END_PROGRAM
+        ";
+    let result = parse(src).0;
+
+    let prg = &result.implementations[0];
+
+    let ast_string = format!("{:#?}", &prg.statements[0]);
+    let expected_ast = r#"BinaryExpression {
+    operator: Multiplication,
+    left: Reference {
+        name: "a",
+    },
+    right: BinaryExpression {
+        operator: Exponentiation,
+        left: BinaryExpression {
+            operator: Exponentiation,
+            left: Reference {
+                name: "b",
+            },
+            right: Reference {
+                name: "c",
+            },
+        },
+        right: LiteralInteger {
+            value: -2,
+        },
+    },
+}"#;
+    assert_eq!(ast_string, expected_ast);
+
+    //the negation binds stronger than the exponentiation
+    let ast_string = format!("{:#?}", &prg.statements[1]);
+    let expected_ast = r#"BinaryExpression {
+    operator: Exponentiation,
+    left: UnaryExpression {
+        operator: Minus,
+        value: Reference {
+            name: "x",
+        },
+    },
+    right: LiteralInteger {
+        value: 2,
+    },
+}"#;
+    assert_eq!(ast_string, expected_ast);
+}
+
 #[test]
 fn comparison_priority_test() {
     let src = "
@@ -1932,6 +2095,8 @@ fn string_can_be_parsed() {
                     size: None,
                 },
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Local,
@@ -1984,6 +2149,8 @@ fn wide_string_can_be_parsed() {
                     size: None,
                 },
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Local,
@@ -2049,6 +2216,8 @@ fn arrays_can_be_parsed() {
                     },
                 },
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Local,
@@ -2137,6 +2306,8 @@ fn nested_arrays_can_be_parsed() {
                     },
                 },
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Local,
@@ -2234,6 +2405,8 @@ fn multidim_arrays_can_be_parsed() {
                     },
                 },
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Local,
@@ -2581,6 +2754,10 @@ fn sized_string_as_function_return() {
         location: SourceRange::undefined(),
         generics: vec![],
         linkage: crate::ast::LinkageType::Internal,
+        interfaces: vec![],
+        super_class: None,
+        attributes: vec![],
+        comments: vec![],
     };
 
     assert_eq!(format!("{:?}", ast.units[0]), format!("{:?}", expected));
@@ -2628,6 +2805,10 @@ fn array_type_as_function_return() {
         location: SourceRange::undefined(),
         generics: vec![],
         linkage: crate::ast::LinkageType::Internal,
+        interfaces: vec![],
+        super_class: None,
+        attributes: vec![],
+        comments: vec![],
     };
 
     assert_eq!(format!("{:?}", ast.units[0]), format!("{:?}", expected));
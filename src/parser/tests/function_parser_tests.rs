@@ -46,6 +46,8 @@ fn a_function_with_varargs_can_be_parsed() {
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
         Variable {
             name: "y",
@@ -54,6 +56,8 @@ fn a_function_with_varargs_can_be_parsed() {
                     referenced_type: None,
                 },
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Input,
@@ -76,6 +80,8 @@ fn a_function_with_typed_varargs_can_be_parsed() {
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
         Variable {
             name: "y",
@@ -88,6 +94,8 @@ fn a_function_with_typed_varargs_can_be_parsed() {
                     ),
                 },
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Input,
@@ -124,6 +132,7 @@ fn varargs_parameters_can_be_parsed() {
             constant: false,
             access: AccessModifier::Protected,
             retain: false,
+            persistent: false,
             variable_block_type: VariableBlockType::Input,
             location: SourceRange::undefined(),
             linkage: LinkageType::Internal,
@@ -140,6 +149,8 @@ fn varargs_parameters_can_be_parsed() {
                     initializer: None,
                     address: None,
                     location: SourceRange::undefined(),
+                    attributes: vec![],
+                    comments: vec![],
                 },
                 Variable {
                     name: "args2".into(),
@@ -158,6 +169,8 @@ fn varargs_parameters_can_be_parsed() {
                     initializer: None,
                     address: None,
                     location: SourceRange::undefined(),
+                    attributes: vec![],
+                    comments: vec![],
                 },
             ],
         }],
@@ -165,6 +178,10 @@ fn varargs_parameters_can_be_parsed() {
         poly_mode: None,
         generics: vec![],
         linkage: crate::ast::LinkageType::Internal,
+        interfaces: vec![],
+        super_class: None,
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:#?}", expected), format!("{:#?}", x).as_str());
 }
@@ -336,6 +353,8 @@ fn function_inline_struct_return_unsupported() {
                             },
                             initializer: None,
                             address: None,
+                            attributes: vec![],
+                            comments: vec![],
                         },
                         Variable {
                             name: "y".into(),
@@ -346,6 +365,8 @@ fn function_inline_struct_return_unsupported() {
                             },
                             initializer: None,
                             address: None,
+                            attributes: vec![],
+                            comments: vec![],
                         }
                     ],
                 },
@@ -371,6 +392,8 @@ fn simple_fb_with_var_temp_can_be_parsed() {
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Temp,
@@ -393,6 +416,8 @@ fn simple_function_with_var_temp_can_be_parsed() {
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Temp,
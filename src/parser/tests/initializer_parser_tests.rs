@@ -37,6 +37,8 @@ fn initial_scalar_values_can_be_parsed() {
             value: 7,
         },
     ),
+    attributes: vec![],
+    comments: vec![],
 }"#;
     assert_eq!(expected, format!("{:#?}", x).as_str());
 
@@ -57,6 +59,8 @@ fn initial_scalar_values_can_be_parsed() {
                         value: 69,
                     },
                 ),
+                attributes: vec![],
+                comments: vec![],
             },
             Variable {
                 name: "b",
@@ -68,6 +72,8 @@ fn initial_scalar_values_can_be_parsed() {
                         value: true,
                     },
                 ),
+                attributes: vec![],
+                comments: vec![],
             },
             Variable {
                 name: "c",
@@ -79,11 +85,15 @@ fn initial_scalar_values_can_be_parsed() {
                         value: "5.25",
                     },
                 ),
+                attributes: vec![],
+                comments: vec![],
             },
         ],
     },
     initializer: None,
     scope: None,
+    attributes: vec![],
+    comments: vec![],
 }"#;
     assert_eq!(expected, format!("{:#?}", struct_type).as_str());
 
@@ -102,6 +112,8 @@ fn initial_scalar_values_can_be_parsed() {
         },
     ),
     scope: None,
+    attributes: vec![],
+    comments: vec![],
 }"#;
     assert_eq!(expected, format!("{:#?}", my_int_type).as_str());
 
@@ -116,6 +128,8 @@ fn initial_scalar_values_can_be_parsed() {
             value: "11.3",
         },
     ),
+    attributes: vec![],
+    comments: vec![],
 }"#;
 
     assert_eq!(expected, format!("{:#?}", y).as_str());
@@ -167,6 +181,8 @@ fn array_initializer_can_be_parsed() {
             ),
         },
     ),
+    attributes: vec![],
+    comments: vec![],
 }"#;
     assert_eq!(expected, format!("{:#?}", x).as_str());
 }
@@ -237,6 +253,8 @@ fn multi_dim_array_initializer_can_be_parsed() {
             ),
         },
     ),
+    attributes: vec![],
+    comments: vec![],
 }"#;
     assert_eq!(expected, format!("{:#?}", x).as_str());
 }
@@ -280,6 +298,8 @@ fn array_initializer_multiplier_can_be_parsed() {
             ),
         },
     ),
+    attributes: vec![],
+    comments: vec![],
 }"#;
     assert_eq!(expected, format!("{:#?}", x).as_str());
 }
@@ -320,6 +340,8 @@ fn struct_initializer_can_be_parsed() {
             ],
         },
     ),
+    attributes: vec![],
+    comments: vec![],
 }"#;
     assert_eq!(expected, format!("{:#?}", x).as_str());
 }
@@ -20,6 +20,29 @@ fn programs_can_be_external() {
     assert_eq!(LinkageType::External, implementation.linkage);
 }
 
+#[test]
+fn functions_marked_with_the_external_pragma_are_foreign() {
+    let src = "
+        {external} FUNCTION foo : DINT END_FUNCTION
+        {external} PROGRAM prg END_PROGRAM
+        FUNCTION bar : DINT END_FUNCTION
+        ";
+    let parse_result = parse(src).0;
+    let linkages = parse_result
+        .implementations
+        .iter()
+        .map(|it| it.linkage)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        linkages,
+        vec![
+            LinkageType::Foreign,
+            LinkageType::External,
+            LinkageType::Internal
+        ]
+    );
+}
+
 #[test]
 fn exponent_literals_parsed_as_variables() {
     let src = "
@@ -47,6 +70,7 @@ fn exponent_literals_parsed_as_variables() {
             access: AccessModifier::Internal,
             constant: false,
             retain: false,
+            persistent: false,
             location: SourceRange::undefined(),
             linkage: LinkageType::Internal,
             variables: vec![Variable {
@@ -58,11 +82,17 @@ fn exponent_literals_parsed_as_variables() {
                 initializer: None,
                 address: None,
                 location: SourceRange::undefined(),
+                attributes: vec![],
+                comments: vec![],
             }],
         }],
         location: SourceRange::undefined(),
         generics: vec![],
         linkage: crate::ast::LinkageType::Internal,
+        interfaces: vec![],
+        super_class: None,
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:#?}", expected), format!("{:#?}", pou).as_str());
     let implementation = &parse_result.implementations[0];
@@ -87,6 +117,7 @@ fn exponent_literals_parsed_as_variables() {
         access: None,
         overriding: false,
         location: (105..142).into(),
+        comments: vec![],
     };
     assert_eq!(
         format!("{:#?}", expected),
@@ -630,7 +661,7 @@ fn id_implementation_for_all_statements() {
             month: 0,
             year: 0,
             hour: 0,
-            milli: 0,
+            nano: 0,
             min: 0,
             sec: 0,
             location: (1..5).into(),
@@ -679,7 +710,7 @@ fn id_implementation_for_all_statements() {
             hour: 0,
             min: 0,
             sec: 0,
-            milli: 0,
+            nano: 0,
             location: (1..5).into(),
             id: 7
         }
@@ -912,7 +943,7 @@ fn location_implementation_for_all_statements() {
             month: 0,
             year: 0,
             hour: 0,
-            milli: 0,
+            nano: 0,
             min: 0,
             sec: 0,
             location: (1..5).into(),
@@ -961,7 +992,7 @@ fn location_implementation_for_all_statements() {
             hour: 0,
             min: 0,
             sec: 0,
-            milli: 0,
+            nano: 0,
             location: (1..5).into(),
             id: 7
         }
@@ -1044,3 +1075,161 @@ fn location_implementation_for_all_statements() {
         (1..5).into()
     );
 }
+
+#[test]
+fn namespaced_declarations_are_qualified_by_their_namespace() {
+    let src = "
+        USING Base;
+        NAMESPACE Lib
+            NAMESPACE Sub
+                USING Other.Lib;
+                FUNCTION_BLOCK fb
+                    METHOD m END_METHOD
+                END_FUNCTION_BLOCK
+            END_NAMESPACE
+            TYPE MyType : INT; END_TYPE
+            VAR_GLOBAL g : INT; END_VAR
+        END_NAMESPACE
+        PROGRAM main END_PROGRAM";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(diagnostics, vec![]);
+    assert_eq!(unit.namespaces, vec!["Lib", "Lib.Sub"]);
+    let pous = unit
+        .units
+        .iter()
+        .map(|it| it.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(pous, vec!["Lib.Sub.fb", "Lib.Sub.fb.m", "main"]);
+    assert_eq!(unit.types[0].data_type.get_name(), Some("Lib.MyType"));
+    assert_eq!(unit.global_vars[0].variables[0].name, "Lib.g");
+
+    // the enclosing namespaces are searched before the used ones
+    assert_eq!(
+        unit.lookup_scopes.get("lib.sub.fb"),
+        Some(&vec![
+            "Lib.Sub".to_string(),
+            "Lib".to_string(),
+            "Other.Lib".to_string(),
+            "Base".to_string()
+        ])
+    );
+    assert_eq!(
+        unit.lookup_scopes.get("lib.g"),
+        Some(&vec!["Lib".to_string(), "Base".to_string()])
+    );
+    assert_eq!(
+        unit.lookup_scopes.get("main"),
+        Some(&vec!["Base".to_string()])
+    );
+}
+
+#[test]
+fn unclosed_namespaces_are_reported() {
+    let (_, diagnostics) = parse("NAMESPACE Lib PROGRAM main END_PROGRAM");
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::missing_token(
+            "KeywordEndNamespace",
+            SourceRange::new(38..38)
+        )]
+    );
+}
+
+#[test]
+fn pragmas_are_attached_to_the_following_declaration() {
+    let src = "
+        {attribute 'export'}
+        PROGRAM main
+        {retain}
+        VAR
+            a : INT;
+            {attribute 'pack' := '1'}
+            b, c : INT;
+        END_VAR
+            {ignored}
+            a := b;
+        END_PROGRAM
+        {pack 1}
+        TYPE MyStruct : STRUCT
+            {attribute 'offset' := '4'} x : INT;
+        END_STRUCT END_TYPE";
+    let (unit, diagnostics) = parse(src);
+    assert_eq!(diagnostics, vec![]);
+
+    fn names(attributes: &[Attribute]) -> Vec<(&str, Option<&str>)> {
+        attributes
+            .iter()
+            .map(|it| (it.name.as_str(), it.value.as_deref()))
+            .collect()
+    }
+    let pou = &unit.units[0];
+    assert_eq!(names(&pou.attributes), vec![("export", None)]);
+    let variables = &pou.variable_blocks[0].variables;
+    assert_eq!(names(&variables[0].attributes), vec![("retain", None)]);
+    for variable in &variables[1..] {
+        assert_eq!(
+            names(&variable.attributes),
+            vec![("retain", None), ("pack", Some("1"))]
+        );
+    }
+
+    let user_type = &unit.types[0];
+    assert_eq!(names(&user_type.attributes), vec![("pack", Some("1"))]);
+    if let DataType::StructType { variables, .. } = &user_type.data_type {
+        assert_eq!(names(&variables[0].attributes), vec![("offset", Some("4"))]);
+    } else {
+        panic!("expected a struct, found {:#?}", user_type.data_type);
+    }
+}
+
+#[test]
+fn comments_are_attached_to_the_declarations() {
+    let src = "
+        (* the main program *)
+        PROGRAM main
+        VAR
+            // the counter
+            a : INT; (* counts up *)
+            b, c : INT;
+        END_VAR
+            // count
+            a := a + 1;
+        END_PROGRAM
+        // the point
+        TYPE Point : STRUCT
+            x : INT; // the x coordinate
+        END_STRUCT END_TYPE
+        (* the end *)";
+    let (unit, diagnostics) = parse(src);
+    assert_eq!(diagnostics, vec![]);
+
+    fn texts(comments: &[Comment]) -> Vec<&str> {
+        comments.iter().map(|it| it.text.as_str()).collect()
+    }
+    let pou = &unit.units[0];
+    assert_eq!(texts(&pou.comments), vec!["(* the main program *)"]);
+    let variables = &pou.variable_blocks[0].variables;
+    assert_eq!(
+        texts(&variables[0].comments),
+        vec!["// the counter", "(* counts up *)"]
+    );
+    assert!(variables[1].comments.is_empty());
+    assert_eq!(texts(&unit.implementations[0].comments), vec!["// count"]);
+
+    let user_type = &unit.types[0];
+    assert_eq!(texts(&user_type.comments), vec!["// the point"]);
+    if let DataType::StructType { variables, .. } = &user_type.data_type {
+        assert_eq!(texts(&variables[0].comments), vec!["// the x coordinate"]);
+    } else {
+        panic!("expected a struct, found {:#?}", user_type.data_type);
+    }
+
+    //the comment at the end of the file belongs to no declaration
+    assert_eq!(texts(&unit.comments), vec!["(* the end *)"]);
+    let location = &unit.comments[0].location;
+    assert_eq!(
+        &src[location.get_start()..location.get_end()],
+        "(* the end *)"
+    );
+}
@@ -255,6 +255,7 @@ fn unclosed_var_container() {
                 constant: false,
                 access: AccessModifier::Protected,
                 retain: false,
+                persistent: false,
                 variable_block_type: VariableBlockType::Local,
                 location: SourceRange::undefined(),
                 variables: vec![Variable {
@@ -266,6 +267,8 @@ fn unclosed_var_container() {
                     initializer: None,
                     address: None,
                     location: SourceRange::undefined(),
+                    attributes: vec![],
+                    comments: vec![],
                 }],
                 linkage: LinkageType::Internal,
             }
@@ -288,12 +291,9 @@ fn test_unexpected_type_declaration_error_message() {
                 "KeywordProgram",
                 (29..36).into(),
             ),
-            Diagnostic::unexpected_token_found(
-                "KeywordSemicolon",
-                "'PROGRAM\n                END_PROGRAM\n            END_TYPE'",
-                (29..85).into(),
-            ),
-            Diagnostic::unexpected_token_found("KeywordSemicolon", "''", (90..90).into(),),
+            Diagnostic::unexpected_token_found("KeywordSemicolon", "'PROGRAM'", (29..36).into()),
+            Diagnostic::unexpected_token_found("Identifier", "END_PROGRAM", (53..64).into()),
+            Diagnostic::unexpected_token_found("StartKeyword", "END_TYPE", (77..85).into()),
         ],
         diagnostics
     );
@@ -326,3 +326,50 @@ fn a_variable_declaration_block_needs_to_end_with_endvar() {
         ]
     );
 }
+
+#[test]
+fn parsing_continues_after_invalid_variable_declarations() {
+    let src = "PROGRAM p VAR 4 : INT; a : INT; 5; b : BOOL; END_VAR END_PROGRAM";
+    let (compilation_unit, diagnostics) = parse(src);
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unexpected_token_found("KeywordEndVar", "'4 : INT;'", (14..22).into()),
+            Diagnostic::unexpected_token_found("KeywordEndVar", "'5;'", (32..34).into()),
+        ]
+    );
+    let variables = &compilation_unit.units[0].variable_blocks[0].variables;
+    assert_eq!(
+        variables
+            .iter()
+            .map(|it| it.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["a", "b"]
+    );
+}
+
+#[test]
+fn an_unclosed_pou_is_closed_by_the_next_pou() {
+    let src = "PROGRAM a x := 1; FUNCTION foo : INT END_FUNCTION";
+    let (compilation_unit, diagnostics) = parse(src);
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::unexpected_token_found(
+            "KeywordEndProgram",
+            "'FUNCTION'",
+            (18..26).into()
+        )]
+    );
+    //check if both POUs were parsed
+    assert_eq!(
+        compilation_unit
+            .units
+            .iter()
+            .map(|it| it.name.as_str())
+            .collect::<Vec<_>>(),
+        vec!["a", "foo"]
+    );
+    assert_eq!(compilation_unit.implementations[0].statements.len(), 1);
+}
@@ -75,6 +75,87 @@ fn illegal_literal_time_out_of_order_segments_test() {
     );
 }
 
+#[test]
+fn literal_time_exceeding_the_range_of_nanoseconds_test() {
+    let src = "
+        PROGRAM exp 
+            LTIME#110000d;
+        END_PROGRAM
+        ";
+
+    let (_, diagnostics) = parse(src);
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::syntax_error(
+            "Invalid TIME Literal: value exceeds the range of nanoseconds",
+            SourceRange::new(34..47),
+        )]
+    );
+}
+
+#[test]
+fn illegal_literal_date_and_time_values_test() {
+    let src = "
+        PROGRAM exp 
+            D#2020-13-01;
+            TOD#24:00:00;
+            DT#2021-02-29-12:00;
+        END_PROGRAM
+        ";
+
+    let (_, diagnostics) = parse(src);
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::syntax_error(
+                "Invalid date or time 2020-13-01-00:00:00.000000000",
+                SourceRange::new(34..46),
+            ),
+            Diagnostic::syntax_error(
+                "Invalid date or time 1970-01-01-24:00:00.000000000",
+                SourceRange::new(60..72),
+            ),
+            Diagnostic::syntax_error(
+                "Invalid date or time 2021-02-29-12:00:00.000000000",
+                SourceRange::new(86..105),
+            ),
+        ]
+    );
+}
+
+#[test]
+fn literal_date_exceeding_the_range_of_nanoseconds_test() {
+    let src = "
+        PROGRAM exp 
+            LDT#2300-01-01-00:00;
+        END_PROGRAM
+        ";
+
+    let (_, diagnostics) = parse(src);
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::syntax_error(
+            "Date 2300-01-01 00:00:00 exceeds the range of nanoseconds",
+            SourceRange::new(34..54),
+        )]
+    );
+}
+
+#[test]
+fn literal_hex_number_exceeding_the_largest_type() {
+    let src = "PROGRAM exp 16#1_0000_0000_0000_0000_0000_0000_0000_0000; END_PROGRAM";
+    let result = parse(src).1;
+
+    assert_eq!(
+        result.first().unwrap(),
+        &Diagnostic::literal_out_of_range(
+            "16#1_0000_0000_0000_0000_0000_0000_0000_0000",
+            "ULINT",
+            (12..56).into()
+        )
+    );
+}
+
 #[test]
 fn literal_hex_number_with_double_underscores() {
     let src = "PROGRAM exp 16#DEAD__beef; END_PROGRAM";
@@ -165,6 +246,8 @@ fn string_with_round_parens_can_be_parsed() {
                 initializer: None,
                 location: (18..42).into(),
                 scope: None,
+                attributes: vec![],
+                comments: vec![],
             },
             UserTypeDeclaration {
                 data_type: DataType::StringType {
@@ -184,6 +267,8 @@ fn string_with_round_parens_can_be_parsed() {
                 }),
                 location: SourceRange::undefined(),
                 scope: None,
+                attributes: vec![],
+                comments: vec![],
             },
             UserTypeDeclaration {
                 data_type: DataType::StringType {
@@ -198,6 +283,8 @@ fn string_with_round_parens_can_be_parsed() {
                 initializer: None,
                 location: SourceRange::undefined(),
                 scope: None,
+                attributes: vec![],
+                comments: vec![],
             }
         ]
     );
@@ -38,6 +38,21 @@ fn test_unexpected_token_error_message2() {
     );
 }
 
+#[test]
+fn unexpected_tokens_between_declarations_are_reported_once() {
+    let src = "SOME JUNK TOKENS PROGRAM prg END_PROGRAM";
+    let (unit, diagnostics) = parse(src);
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::unexpected_token_found(
+            "StartKeyword",
+            "SOME JUNK TOKENS",
+            (0..16).into()
+        )]
+    );
+    assert_eq!(unit.units[0].name, "prg");
+}
+
 #[test]
 fn for_with_unexpected_token_1() {
     let src = "
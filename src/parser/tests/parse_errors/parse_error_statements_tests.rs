@@ -272,6 +272,7 @@ fn invalid_variable_name_error_recovery() {
                 constant: false,
                 access: AccessModifier::Protected,
                 retain: false,
+                persistent: false,
                 location: SourceRange::undefined(),
                 variables: vec![Variable {
                     name: "c".into(),
@@ -282,6 +283,8 @@ fn invalid_variable_name_error_recovery() {
                     initializer: None,
                     address: None,
                     location: SourceRange::undefined(),
+                    attributes: vec![],
+                    comments: vec![],
                 },],
                 variable_block_type: VariableBlockType::Local,
                 linkage: LinkageType::Internal,
@@ -323,6 +326,8 @@ fn invalid_variable_data_type_error_recovery() {
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Local,
@@ -446,6 +451,46 @@ fn test_nested_if_with_missing_end_if() {
     );
 }
 
+#[test]
+fn test_if_with_missing_then_continues_with_the_body() {
+    let src = "PROGRAM p
+    IF x y := 1; END_IF
+    z := 2;
+END_PROGRAM
+";
+    let (unit, diagnostics) = parse(src);
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::unexpected_token_found(
+            "KeywordThen",
+            "y",
+            (19..20).into()
+        )]
+    );
+    let statements = &unit.implementations[0].statements;
+    assert_eq!(statements.len(), 2);
+    if let AstStatement::IfStatement { blocks, .. } = &statements[0] {
+        assert_eq!(
+            format!("{:#?}", blocks[0].body),
+            format!(
+                "{:#?}",
+                vec![AstStatement::Assignment {
+                    left: Box::new(ref_to("y")),
+                    right: Box::new(AstStatement::LiteralInteger {
+                        value: 1,
+                        location: SourceRange::undefined(),
+                        id: 0
+                    }),
+                    id: 0
+                }]
+            )
+        );
+    } else {
+        panic!("expected an IF statement, found {:?}", statements[0]);
+    }
+}
+
 #[test]
 fn test_for_with_missing_semicolon_in_body() {
     //regress, this used to end in an endless loop
@@ -1036,6 +1081,8 @@ fn pointer_type_without_to_test() {
         location: SourceRange::undefined(),
         initializer: None,
         scope: None,
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(
         format!("{:#?}", expected),
@@ -1074,6 +1121,8 @@ fn pointer_type_with_wrong_keyword_to_test() {
         location: SourceRange::undefined(),
         initializer: None,
         scope: None,
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(
         format!("{:#?}", expected),
@@ -190,6 +190,8 @@ fn generic_parameters_are_datatypes() {
                 initializer: None,
                 address: None,
                 location: SourceRange::new(49..50),
+                attributes: vec![],
+                comments: vec![],
             },
             Variable {
                 name: "y".into(),
@@ -200,6 +202,8 @@ fn generic_parameters_are_datatypes() {
                 initializer: None,
                 address: None,
                 location: SourceRange::new(56..57),
+                attributes: vec![],
+                comments: vec![],
             },
         ],
         variables
@@ -223,6 +227,8 @@ fn generic_method_parameters_are_datatypes() {
                 initializer: None,
                 address: None,
                 location: SourceRange::new(57..58),
+                attributes: vec![],
+                comments: vec![],
             },
             Variable {
                 name: "y".into(),
@@ -234,8 +240,47 @@ fn generic_method_parameters_are_datatypes() {
                 address: None,
 
                 location: SourceRange::new(64..65),
+                attributes: vec![],
+                comments: vec![],
             },
         ],
         variables
     );
 }
+
+#[test]
+fn generic_classes_as_parameter_types_are_bound_implicitly() {
+    let src = "
+        FUNCTION test<T : ANY> : ANY_NUM
+        VAR_INPUT
+            a : ANY_INT;
+            b : ANY_NUM;
+            c : REF_TO ANY_INT;
+            d : T;
+        END_VAR
+        END_FUNCTION
+        PROGRAM prg VAR x : INT; END_VAR END_PROGRAM";
+    let (parse_result, diagnostics) = parse(src);
+    assert_eq!(diagnostics, vec![]);
+
+    // every generic class is bound once, the return type shares the parameter's binding
+    assert_eq!(
+        parse_result.units[0].generics,
+        vec![
+            GenericBinding {
+                name: "T".into(),
+                nature: TypeNature::Any,
+            },
+            GenericBinding {
+                name: "ANY_NUM".into(),
+                nature: TypeNature::Num,
+            },
+            GenericBinding {
+                name: "ANY_INT".into(),
+                nature: TypeNature::Int,
+            },
+        ]
+    );
+    // only functions are generic
+    assert!(parse_result.units[1].generics.is_empty());
+}
@@ -39,6 +39,8 @@ fn simple_program_with_variable_can_be_parsed() {
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Local,
@@ -61,6 +63,8 @@ fn simple_program_with_var_input_can_be_parsed() {
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Input,
@@ -83,6 +87,8 @@ fn simple_program_with_var_output_can_be_parsed() {
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Output,
@@ -105,6 +111,8 @@ fn simple_program_with_var_inout_can_be_parsed() {
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: InOut,
@@ -127,6 +135,8 @@ fn simple_program_with_var_temp_can_be_parsed() {
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Temp,
@@ -117,22 +117,30 @@ fn inline_struct_declaration_can_be_parsed() {
                     data_type: DataTypeReference {
                         referenced_type: "INT",
                     },
+                    attributes: vec![],
+                    comments: vec![],
                 },
                 Variable {
                     name: "Two",
                     data_type: DataTypeReference {
                         referenced_type: "INT",
                     },
+                    attributes: vec![],
+                    comments: vec![],
                 },
                 Variable {
                     name: "Three",
                     data_type: DataTypeReference {
                         referenced_type: "INT",
                     },
+                    attributes: vec![],
+                    comments: vec![],
                 },
             ],
         },
     },
+    attributes: vec![],
+    comments: vec![],
 }"#;
 
     assert_eq!(ast_string, expected_ast);
@@ -167,6 +175,8 @@ fn inline_enum_declaration_can_be_parsed() {
         initializer: None,
         address: None,
         location: SourceRange::undefined(),
+        attributes: vec![],
+        comments: vec![],
     };
     let expected_ast = format!("{:#?}", &v);
     assert_eq!(ast_string, expected_ast);
@@ -212,6 +222,8 @@ fn string_variable_declaration_can_be_parsed() {
             size: None,
         },
     },
+    attributes: vec![],
+    comments: vec![],
 }"#;
     assert_eq!(expected, format!("{:#?}", x).as_str());
 
@@ -229,6 +241,8 @@ fn string_variable_declaration_can_be_parsed() {
             ),
         },
     },
+    attributes: vec![],
+    comments: vec![],
 }"#;
     assert_eq!(expected, format!("{:#?}", x).as_str());
 
@@ -242,6 +256,8 @@ fn string_variable_declaration_can_be_parsed() {
             size: None,
         },
     },
+    attributes: vec![],
+    comments: vec![],
 }"#;
     assert_eq!(expected, format!("{:#?}", x).as_str());
 
@@ -259,6 +275,8 @@ fn string_variable_declaration_can_be_parsed() {
             ),
         },
     },
+    attributes: vec![],
+    comments: vec![],
 }"#;
     assert_eq!(expected, format!("{:#?}", x).as_str());
 }
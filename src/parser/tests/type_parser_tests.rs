@@ -32,6 +32,8 @@ fn simple_struct_type_can_be_parsed() {
                         initializer: None,
                         address: None,
                         location: SourceRange::undefined(),
+                        attributes: vec![],
+                        comments: vec![],
                     },
                     Variable {
                         name: "Two".to_string(),
@@ -42,6 +44,8 @@ fn simple_struct_type_can_be_parsed() {
                         initializer: None,
                         address: None,
                         location: SourceRange::undefined(),
+                        attributes: vec![],
+                        comments: vec![],
                     },
                     Variable {
                         name: "Three".to_string(),
@@ -52,12 +56,74 @@ fn simple_struct_type_can_be_parsed() {
                         initializer: None,
                         address: None,
                         location: SourceRange::undefined(),
+                        attributes: vec![],
+                        comments: vec![],
                     },
                 ),
             },
             initializer: None,
             location: SourceRange::undefined(),
             scope: None,
+            attributes: vec![],
+            comments: vec![],
+        }
+    );
+    assert_eq!(ast_string, expected_ast);
+}
+
+#[test]
+fn simple_union_type_can_be_parsed() {
+    let (result, diagnostics) = parse(
+        r#"
+        TYPE SampleUnion :
+            UNION
+                One:DINT;
+                Two:REAL;
+            END_UNION
+        END_TYPE 
+        "#,
+    );
+
+    assert_eq!(diagnostics, vec![]);
+    let ast_string = format!("{:#?}", &result.types[0]);
+
+    let expected_ast = format!(
+        "{:#?}",
+        &UserTypeDeclaration {
+            data_type: DataType::UnionType {
+                name: Some("SampleUnion".to_string(),),
+                variables: vec!(
+                    Variable {
+                        name: "One".to_string(),
+                        data_type: DataTypeDeclaration::DataTypeReference {
+                            referenced_type: "DINT".to_string(),
+                            location: SourceRange::undefined(),
+                        },
+                        initializer: None,
+                        address: None,
+                        location: SourceRange::undefined(),
+                        attributes: vec![],
+                        comments: vec![],
+                    },
+                    Variable {
+                        name: "Two".to_string(),
+                        data_type: DataTypeDeclaration::DataTypeReference {
+                            referenced_type: "REAL".to_string(),
+                            location: SourceRange::undefined(),
+                        },
+                        initializer: None,
+                        address: None,
+                        location: SourceRange::undefined(),
+                        attributes: vec![],
+                        comments: vec![],
+                    },
+                ),
+            },
+            initializer: None,
+            location: SourceRange::undefined(),
+            scope: None,
+            attributes: vec![],
+            comments: vec![],
         }
     );
     assert_eq!(ast_string, expected_ast);
@@ -143,6 +209,8 @@ fn type_alias_can_be_parsed() {
             initializer: None,
             location: SourceRange::undefined(),
             scope: None,
+            attributes: vec![],
+            comments: vec![],
         }
     );
 
@@ -185,6 +253,8 @@ fn array_type_can_be_parsed_test() {
             initializer: None,
             location: SourceRange::undefined(),
             scope: None,
+            attributes: vec![],
+            comments: vec![],
         }
     );
 
@@ -218,6 +288,8 @@ fn string_type_can_be_parsed_test() {
                 initializer: None,
                 location: SourceRange::undefined(),
                 scope: None,
+                attributes: vec![],
+                comments: vec![],
             },
             UserTypeDeclaration {
                 data_type: DataType::StringType {
@@ -237,6 +309,8 @@ fn string_type_can_be_parsed_test() {
                 }),
                 location: SourceRange::undefined(),
                 scope: None,
+                attributes: vec![],
+                comments: vec![],
             }
         ]
     );
@@ -269,6 +343,8 @@ fn wide_string_type_can_be_parsed_test() {
             initializer: None,
             location: SourceRange::undefined(),
             scope: None,
+            attributes: vec![],
+            comments: vec![],
         }
     );
 
@@ -311,6 +387,8 @@ fn subrangetype_can_be_parsed() {
         initializer: None,
         address: None,
         location: (0..0).into(),
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(format!("{:#?}", expected), format!("{:#?}", x).as_str());
 }
@@ -353,11 +431,15 @@ fn struct_with_inline_array_can_be_parsed() {
                         },
                     },
                 },
+                attributes: vec![],
+                comments: vec![],
             },
         ],
     },
     initializer: None,
     scope: None,
+    attributes: vec![],
+    comments: vec![],
 }"#;
     assert_eq!(ast_string, expected_ast);
 }
@@ -383,6 +465,8 @@ fn pointer_type_test() {
         location: SourceRange::undefined(),
         initializer: None,
         scope: None,
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(
         format!("{:#?}", expected),
@@ -417,6 +501,8 @@ fn ref_type_test() {
         location: SourceRange::undefined(),
         initializer: None,
         scope: None,
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(
         format!("{:#?}", expected),
@@ -452,6 +538,8 @@ fn global_pointer_declaration() {
         initializer: None,
         address: None,
         location: (0..0).into(),
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(
         format!("{:#?}", expected),
@@ -474,6 +562,8 @@ fn global_pointer_declaration() {
         initializer: None,
         address: None,
         location: (0..0).into(),
+        attributes: vec![],
+        comments: vec![],
     };
     assert_eq!(
         format!("{:#?}", expected),
@@ -31,12 +31,16 @@ fn global_vars_can_be_parsed() {
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
         Variable {
             name: "y",
             data_type: DataTypeReference {
                 referenced_type: "BOOL",
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Global,
@@ -58,12 +62,16 @@ fn external_global_vars_can_be_parsed() {
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
         Variable {
             name: "y",
             data_type: DataTypeReference {
                 referenced_type: "BOOL",
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Global,
@@ -92,36 +100,48 @@ fn global_single_line_vars_can_be_parsed() {
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
         Variable {
             name: "y",
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
         Variable {
             name: "z",
             data_type: DataTypeReference {
                 referenced_type: "INT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
         Variable {
             name: "f",
             data_type: DataTypeReference {
                 referenced_type: "BOOL",
             },
+            attributes: vec![],
+            comments: vec![],
         },
         Variable {
             name: "b",
             data_type: DataTypeReference {
                 referenced_type: "SINT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
         Variable {
             name: "c",
             data_type: DataTypeReference {
                 referenced_type: "SINT",
             },
+            attributes: vec![],
+            comments: vec![],
         },
     ],
     variable_block_type: Global,
@@ -144,6 +164,8 @@ fn two_global_vars_can_be_parsed() {
                 data_type: DataTypeReference {
                     referenced_type: "INT",
                 },
+                attributes: vec![],
+                comments: vec![],
             },
         ],
         variable_block_type: Global,
@@ -155,12 +177,16 @@ fn two_global_vars_can_be_parsed() {
                 data_type: DataTypeReference {
                     referenced_type: "INT",
                 },
+                attributes: vec![],
+                comments: vec![],
             },
             Variable {
                 name: "y",
                 data_type: DataTypeReference {
                     referenced_type: "BOOL",
                 },
+                attributes: vec![],
+                comments: vec![],
             },
         ],
         variable_block_type: Global,
@@ -223,3 +249,55 @@ fn struct_with_address() {
     assert_eq!(diag, vec![]);
     insta::assert_snapshot!(format!("{:?}", result));
 }
+
+#[test]
+fn retain_and_persistent_global_vars_can_be_parsed() {
+    let src = "
+        VAR_GLOBAL RETAIN a : INT; END_VAR
+        VAR_GLOBAL PERSISTENT b : INT; END_VAR
+        VAR_GLOBAL CONSTANT PERSISTENT RETAIN c : INT := 1; END_VAR
+        VAR_GLOBAL NON_RETAIN d : INT; END_VAR
+        ";
+    let (result, diagnostics) = parse(src);
+    assert_eq!(diagnostics, vec![]);
+
+    let flags = result
+        .global_vars
+        .iter()
+        .map(|it| (it.retain, it.persistent))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        flags,
+        vec![(true, false), (false, true), (true, true), (false, false)]
+    );
+}
+
+#[test]
+fn external_variables_can_be_parsed() {
+    let src = "
+        PROGRAM prg
+        VAR_EXTERNAL x : INT; END_VAR
+        VAR_EXTERNAL CONSTANT y : BOOL; END_VAR
+        END_PROGRAM
+        ";
+    let (result, diagnostics) = parse(src);
+    assert_eq!(diagnostics, vec![]);
+
+    let blocks = &result.units[0].variable_blocks;
+    let ast_string = format!("{:#?}", blocks[0]);
+    let expected_ast = r#"VariableBlock {
+    variables: [
+        Variable {
+            name: "x",
+            data_type: DataTypeReference {
+                referenced_type: "INT",
+            },
+            attributes: vec![],
+            comments: vec![],
+        },
+    ],
+    variable_block_type: External,
+}"#;
+    assert_eq!(ast_string, expected_ast);
+    assert!(blocks[1].constant);
+}
@@ -10,17 +10,20 @@ use std::collections::{HashMap, HashSet};
 use indexmap::IndexMap;
 
 pub mod const_evaluator;
+pub mod generics;
 
 use crate::{
     ast::{
         self, AstId, AstStatement, CompilationUnit, DataType, DataTypeDeclaration, GenericBinding,
         Operator, Pou, TypeNature, UserTypeDeclaration, Variable,
     },
-    index::{ImplementationIndexEntry, ImplementationType, Index, VariableIndexEntry},
+    index::{
+        ImplementationIndexEntry, ImplementationType, Index, PropertyIndexEntry, VariableIndexEntry,
+    },
     typesystem::{
         self, get_bigger_type, DataTypeInformation, StringEncoding, BOOL_TYPE, BYTE_TYPE,
         DATE_AND_TIME_TYPE, DATE_TYPE, DINT_TYPE, DWORD_TYPE, LINT_TYPE, REAL_TYPE,
-        TIME_OF_DAY_TYPE, TIME_TYPE, VOID_TYPE, WORD_TYPE,
+        TIME_OF_DAY_TYPE, TIME_TYPE, ULINT_TYPE, VOID_TYPE, WORD_TYPE,
     },
 };
 
@@ -141,6 +144,13 @@ pub enum StatementAnnotation {
     Type { type_name: String },
     /// a reference to a program call or reference (e.g. `PLC_PRG`)
     Program { qualified_name: String },
+    /// a reference to a property of a class or function block (e.g. `fb.Level`), reading it
+    /// calls the getter, writing it calls the setter
+    Property {
+        resulting_type: String,
+        getter: Option<String>,
+        setter: Option<String>,
+    },
 }
 
 impl StatementAnnotation {
@@ -168,6 +178,7 @@ fn get_type_for_annotation<'a>(
         StatementAnnotation::Function { .. } => None,
         StatementAnnotation::Type { .. } => None,
         StatementAnnotation::Program { .. } => None,
+        StatementAnnotation::Property { resulting_type, .. } => Some(resulting_type.as_str()),
     }
     .and_then(|type_name| index.get_type(type_name).ok())
 }
@@ -287,6 +298,10 @@ pub struct AnnotationMapImpl {
     /// A map from a call to the generic function name of that call
     generic_nature_map: IndexMap<AstId, TypeNature>,
 
+    /// maps the instances of generic functions called with concrete types (e.g. `foo__INT`)
+    /// to the generic function they instantiate (e.g. `foo`)
+    pub generic_instances: IndexMap<String, String>,
+
     //An index of newly created types
     pub new_index: Index,
 }
@@ -300,6 +315,7 @@ impl AnnotationMapImpl {
     pub fn import(&mut self, other: AnnotationMapImpl) {
         self.type_map.extend(other.type_map);
         self.type_hint_map.extend(other.type_hint_map);
+        self.generic_instances.extend(other.generic_instances);
         self.new_index.import(other.new_index);
     }
 
@@ -425,6 +441,42 @@ impl<'i> TypeAnnotator<'i> {
         (visitor.annotation_map, visitor.string_literals)
     }
 
+    /// annotates the constant expressions of the given index, which are not declared by a unit
+    /// (e.g. the initial values declared by a precompiled library).
+    /// Returns an AnnotationMap with the resulting types for the visited expressions.
+    pub fn visit_const_expressions(index: &Index) -> (AnnotationMapImpl, StringLiterals) {
+        let mut visitor = TypeAnnotator::new(index);
+        let ctx = &VisitorContext {
+            pou: None,
+            qualifier: None,
+            call: None,
+            constant: false,
+            in_body: false,
+        };
+
+        let const_expressions = index.get_const_expressions();
+        for (id, statement) in const_expressions {
+            match const_expressions.find_expression(&id).1 {
+                Some(scope) => visitor.visit_statement(&ctx.with_pou(scope), statement),
+                None => visitor.visit_statement(ctx, statement),
+            }
+
+            //the expression should result in its target type
+            if let Some(expected_type) = const_expressions
+                .find_expression_target_type(&id)
+                .and_then(|it| index.find_effective_type(it))
+            {
+                visitor.annotation_map.annotate_type_hint(
+                    statement,
+                    StatementAnnotation::value(expected_type.get_name()),
+                );
+                visitor.update_expected_types(expected_type, statement);
+            }
+        }
+
+        (visitor.annotation_map, visitor.string_literals)
+    }
+
     fn visit_user_type_declaration(
         &mut self,
         user_data_type: &UserTypeDeclaration,
@@ -656,6 +708,11 @@ impl<'i> TypeAnnotator<'i> {
                 name: Some(name),
                 variables,
                 ..
+            }
+            | DataType::UnionType {
+                name: Some(name),
+                variables,
+                ..
             } => {
                 let ctx = ctx.with_qualifier(name.clone());
                 variables.iter().for_each(|v| self.visit_variable(&ctx, v))
@@ -867,6 +924,11 @@ impl<'i> TypeAnnotator<'i> {
                 ..
             } => {
                 visit_all_statements!(self, ctx, left, right);
+                //a string-literal compared to a character is a character-literal (e.g. `c = 'A'`)
+                if operator.is_comparison_operator() {
+                    self.annotate_character_literal(left, right);
+                    self.annotate_character_literal(right, left);
+                }
                 let statement_type = {
                     let left_type = self
                         .annotation_map
@@ -880,21 +942,39 @@ impl<'i> TypeAnnotator<'i> {
                     if left_type.get_type_information().is_numerical()
                         && right_type.get_type_information().is_numerical()
                     {
-                        let bigger_type = if left_type.get_type_information().is_bool()
-                            && right_type.get_type_information().is_bool()
+                        let is_character_comparison = operator.is_comparison_operator()
+                            && left_type.get_type_information().is_character()
+                            && left_type == right_type;
+                        let bigger_type = if (left_type.get_type_information().is_bool()
+                            && right_type.get_type_information().is_bool())
+                            || is_character_comparison
                         {
                             left_type
                         } else {
                             let dint = self.index.get_type_or_panic(DINT_TYPE);
-                            get_bigger_type(
-                                get_bigger_type(left_type, right_type, self.index),
-                                dint,
-                                self.index,
-                            )
+                            //mixing signed and unsigned variables needs a signed type that holds both
+                            let operands_type = if is_int_literal(left) || is_int_literal(right) {
+                                None
+                            } else {
+                                typesystem::get_mixed_sign_promotion(
+                                    left_type, right_type, self.index,
+                                )
+                            }
+                            .unwrap_or_else(|| get_bigger_type(left_type, right_type, self.index));
+                            get_bigger_type(operands_type, dint, self.index)
                         };
 
                         let target_name = if operator.is_bool_type() {
                             BOOL_TYPE.to_string()
+                        } else if let Some(date_time_type) =
+                            typesystem::get_date_time_arithmetic_type(
+                                left_type.get_type_information(),
+                                operator,
+                                right_type.get_type_information(),
+                                self.index,
+                            )
+                        {
+                            date_time_type.to_string()
                         } else {
                             bigger_type.get_name().to_string()
                         };
@@ -981,16 +1061,56 @@ impl<'i> TypeAnnotator<'i> {
                         .find_member(qualifier, name)
                         // 2nd try an enum-element qualifier#name
                         .or_else(|| self.index.find_enum_element(qualifier, name.as_str()))
-                        // 3rd try - look for a method qualifier.name
                         .map_or_else(
                             || {
-                                find_implementation_annotation(
-                                    format!("{}.{}", qualifier, name).as_str(),
-                                    self.index,
-                                )
+                                // 3rd try - look for a property qualifier.name
+                                self.index
+                                    .find_property(qualifier, name)
+                                    .map(to_property_annotation)
+                                    // 4th try - look for a method qualifier.name (which may be inherited)
+                                    .or_else(|| {
+                                        self.index
+                                            .find_method(qualifier, name)
+                                            .map(ImplementationIndexEntry::get_call_name)
+                                            .and_then(|it| {
+                                                find_implementation_annotation(it, self.index)
+                                            })
+                                    })
+                                    .or_else(|| {
+                                        find_implementation_annotation(
+                                            format!("{}.{}", qualifier, name).as_str(),
+                                            self.index,
+                                        )
+                                    })
                             },
                             |v| Some(to_variable_annotation(v, self.index, ctx.constant)),
                         )
+                } else if name.eq_ignore_ascii_case(ast::THIS) {
+                    // THIS references the instance of the current method's class or function block
+                    ctx.pou
+                        .and_then(|pou_name| self.index.find_implementation(pou_name))
+                        .and_then(ImplementationIndexEntry::get_instance_type_name)
+                        .map(|instance_type| StatementAnnotation::Variable {
+                            resulting_type: instance_type.to_string(),
+                            qualified_name: format!("{}.{}", instance_type, ast::THIS),
+                            constant: false,
+                            is_auto_deref: false,
+                        })
+                } else if name.eq_ignore_ascii_case(ast::SUPER) {
+                    // SUPER points to the instance of the super class embedded in the current instance
+                    ctx.pou
+                        .and_then(|pou_name| self.index.find_implementation(pou_name))
+                        .and_then(ImplementationIndexEntry::get_instance_type_name)
+                        .and_then(|instance_type| {
+                            self.index
+                                .find_super_class(instance_type)
+                                .map(|super_class| StatementAnnotation::Variable {
+                                    resulting_type: typesystem::get_pointer_type_name(super_class),
+                                    qualified_name: format!("{}.{}", instance_type, ast::SUPER),
+                                    constant: false,
+                                    is_auto_deref: false,
+                                })
+                        })
                 } else {
                     // if we see no qualifier, we try some strategies ...
                     ctx.pou
@@ -1031,7 +1151,12 @@ impl<'i> TypeAnnotator<'i> {
 
                             //TODO introduce qualified names!
                             let call_name = class_name
-                                .map(|it| format!("{}.{}", it, name))
+                                .map(|it| {
+                                    self.index
+                                        .find_method(it, name)
+                                        .map(|method| method.get_call_name().to_string())
+                                        .unwrap_or_else(|| format!("{}.{}", it, name))
+                                })
                                 .unwrap_or_else(|| name.into());
                             find_implementation_annotation(&call_name, self.index)
                         })
@@ -1055,8 +1180,9 @@ impl<'i> TypeAnnotator<'i> {
                         .annotation_map
                         .get(s)
                         .map(|it| match it {
+                            //the elements of a constant array are constant as well
                             StatementAnnotation::Value { resulting_type } => {
-                                (resulting_type.as_str(), false)
+                                (resulting_type.as_str(), self.is_constant_array_element(s))
                             }
                             StatementAnnotation::Variable {
                                 resulting_type,
@@ -1068,6 +1194,9 @@ impl<'i> TypeAnnotator<'i> {
                             StatementAnnotation::Program { qualified_name } => {
                                 (qualified_name.as_str(), false)
                             }
+                            StatementAnnotation::Property { resulting_type, .. } => {
+                                (resulting_type.as_str(), false)
+                            }
                         })
                         .unwrap_or_else(|| (VOID_TYPE, false));
                     let mut new_ctx = ctx.with_qualifier(qualifier.to_string());
@@ -1110,6 +1239,234 @@ impl<'i> TypeAnnotator<'i> {
                 }
                 self.update_right_hand_side_expected_type(left, right);
             }
+            AstStatement::CallStatement {
+                parameters,
+                operator,
+                ..
+            } if self.find_selection_call(operator, parameters).is_some() => {
+                //selections are generated by the compiler and result in the bigger type of their inputs
+                if let Some((name, selector, inputs)) =
+                    self.find_selection_call(operator, parameters)
+                {
+                    if let Some(selector) = selector {
+                        self.visit_statement(ctx, selector);
+                    }
+                    inputs
+                        .iter()
+                        .for_each(|input| self.visit_statement(ctx, input));
+
+                    let result_type = inputs
+                        .iter()
+                        .map(|input| self.annotation_map.get_type_or_void(input, self.index))
+                        .reduce(|bigger, current| get_bigger_type(bigger, current, self.index))
+                        .unwrap_or_else(|| self.index.get_void_type())
+                        .clone(); // clone here, so we release the borrow on self
+                    if let (Some(selector), true) = (selector, name.eq_ignore_ascii_case("SEL")) {
+                        let bool_type = self.index.get_type_or_panic(BOOL_TYPE);
+                        self.update_expected_types(bool_type, selector);
+                    }
+                    inputs
+                        .iter()
+                        .for_each(|input| self.update_expected_types(&result_type, input));
+                    self.annotation_map.annotate(
+                        operator,
+                        StatementAnnotation::Function {
+                            return_type: result_type.get_name().to_string(),
+                            qualified_name: name.to_string(),
+                        },
+                    );
+                    self.annotation_map.annotate(
+                        statement,
+                        StatementAnnotation::value(result_type.get_name()),
+                    );
+                }
+            }
+            AstStatement::CallStatement {
+                parameters,
+                operator,
+                ..
+            } if self.find_bit_shift_call(operator, parameters).is_some() => {
+                //bit-shifts are generated by the compiler and result in the type of the shifted value
+                if let Some((name, value, amount)) = self.find_bit_shift_call(operator, parameters)
+                {
+                    self.visit_statement(ctx, value);
+                    self.visit_statement(ctx, amount);
+                    let value_type = self
+                        .annotation_map
+                        .get_type_or_void(value, self.index)
+                        .get_name()
+                        .to_string();
+                    self.annotation_map.annotate(
+                        operator,
+                        StatementAnnotation::Function {
+                            return_type: value_type.clone(),
+                            qualified_name: name.to_string(),
+                        },
+                    );
+                    self.annotation_map
+                        .annotate(statement, StatementAnnotation::new_value(value_type));
+                }
+            }
+            AstStatement::CallStatement {
+                parameters,
+                operator,
+                ..
+            } if self.find_numeric_call(operator, parameters).is_some() => {
+                //numeric functions are generated by the compiler, ABS results in the type of its
+                //argument, all other functions result in the bigger real type of their arguments
+                if let Some((name, arguments)) = self.find_numeric_call(operator, parameters) {
+                    arguments
+                        .iter()
+                        .for_each(|argument| self.visit_statement(ctx, argument));
+                    let argument_type = arguments
+                        .iter()
+                        .map(|argument| self.annotation_map.get_type_or_void(argument, self.index))
+                        .reduce(|bigger, current| get_bigger_type(bigger, current, self.index))
+                        .unwrap_or_else(|| self.index.get_void_type());
+                    let result_type = if name.eq_ignore_ascii_case("ABS") {
+                        argument_type
+                    } else {
+                        let real_type = self.index.get_type_or_panic(REAL_TYPE);
+                        get_bigger_type(argument_type, real_type, self.index)
+                    }
+                    .clone(); // clone here, so we release the borrow on self
+                    arguments
+                        .iter()
+                        .for_each(|argument| self.update_expected_types(&result_type, argument));
+                    self.annotation_map.annotate(
+                        operator,
+                        StatementAnnotation::Function {
+                            return_type: result_type.get_name().to_string(),
+                            qualified_name: name.to_string(),
+                        },
+                    );
+                    self.annotation_map.annotate(
+                        statement,
+                        StatementAnnotation::value(result_type.get_name()),
+                    );
+                }
+            }
+            AstStatement::CallStatement {
+                parameters,
+                operator,
+                ..
+            } if self.find_sizeof_call(operator, parameters).is_some() => {
+                //SIZEOF is generated by the compiler and accepts a variable or the name of a type
+                if let Some((name, argument)) = self.find_sizeof_call(operator, parameters) {
+                    self.visit_statement(ctx, argument);
+                    if let AstStatement::Reference {
+                        name: type_name, ..
+                    } = argument
+                    {
+                        if self.annotation_map.get(argument).is_none() {
+                            if let Some(data_type) = self.index.find_type(type_name) {
+                                self.annotation_map
+                                    .annotate(argument, to_type_annotation(data_type.get_name()));
+                            }
+                        }
+                    }
+                    self.annotation_map.annotate(
+                        operator,
+                        StatementAnnotation::Function {
+                            return_type: ULINT_TYPE.to_string(),
+                            qualified_name: name.to_string(),
+                        },
+                    );
+                    self.annotation_map
+                        .annotate(statement, StatementAnnotation::value(ULINT_TYPE));
+                }
+            }
+            AstStatement::CallStatement {
+                parameters,
+                operator,
+                ..
+            } if self.find_address_call(operator, parameters).is_some() => {
+                //ADR and REF are generated by the compiler and result in a pointer to their argument
+                if let Some((name, argument)) = self.find_address_call(operator, parameters) {
+                    self.visit_statement(ctx, argument);
+                    let inner_type = self
+                        .annotation_map
+                        .get_type_or_void(argument, self.index)
+                        .get_name()
+                        .to_string();
+                    let pointer_type =
+                        add_pointer_type(&mut self.annotation_map.new_index, inner_type);
+                    self.annotation_map.annotate(
+                        operator,
+                        StatementAnnotation::Function {
+                            return_type: pointer_type.clone(),
+                            qualified_name: name.to_string(),
+                        },
+                    );
+                    self.annotation_map
+                        .annotate(statement, StatementAnnotation::new_value(pointer_type));
+                }
+            }
+            AstStatement::CallStatement {
+                parameters,
+                operator,
+                ..
+            } if self.find_exponent_call(operator, parameters).is_some() => {
+                //EXPT is generated by the compiler and results in the type of `base ** exponent`
+                if let Some((name, base, exponent)) = self.find_exponent_call(operator, parameters)
+                {
+                    visit_all_statements!(self, ctx, base, exponent);
+                    let base_type = self.annotation_map.get_type_or_void(base, self.index);
+                    let exponent_type = self.annotation_map.get_type_or_void(exponent, self.index);
+                    let result_type = get_bigger_type(
+                        get_bigger_type(base_type, exponent_type, self.index),
+                        self.index.get_type_or_panic(DINT_TYPE),
+                        self.index,
+                    )
+                    .clone(); // clone here, so we release the borrow on self
+                    self.update_expected_types(&result_type, base);
+                    self.update_expected_types(&result_type, exponent);
+                    //the parameters of named arguments (`IN1 := x`) are not declared anywhere
+                    if let Some(parameters) = parameters.as_ref() {
+                        for argument in ast::flatten_expression_list(parameters) {
+                            if let AstStatement::Assignment { left, .. } = argument {
+                                self.annotation_map.annotate(
+                                    left,
+                                    StatementAnnotation::value(result_type.get_name()),
+                                );
+                            }
+                        }
+                    }
+                    self.annotation_map.annotate(
+                        operator,
+                        StatementAnnotation::Function {
+                            return_type: result_type.get_name().to_string(),
+                            qualified_name: name.to_string(),
+                        },
+                    );
+                    self.annotation_map.annotate(
+                        statement,
+                        StatementAnnotation::value(result_type.get_name()),
+                    );
+                }
+            }
+            AstStatement::CallStatement {
+                parameters,
+                operator,
+                ..
+            } if self.find_conversion_call(operator, parameters).is_some() => {
+                //conversions between elementary types are generated by the compiler
+                if let Some((name, argument, source, target)) =
+                    self.find_conversion_call(operator, parameters)
+                {
+                    self.visit_statement(ctx, argument);
+                    self.update_expected_types(source, argument);
+                    self.annotation_map.annotate(
+                        operator,
+                        StatementAnnotation::Function {
+                            return_type: target.get_name().to_string(),
+                            qualified_name: name.to_string(),
+                        },
+                    );
+                    self.annotation_map
+                        .annotate(statement, StatementAnnotation::value(target.get_name()));
+                }
+            }
             AstStatement::CallStatement {
                 parameters,
                 operator,
@@ -1239,6 +1596,164 @@ impl<'i> TypeAnnotator<'i> {
             }
         }
     }
+    /// annotates the given literal with the type of the given character-statement, if the
+    /// literal is a string-literal and the statement results in a `CHAR` or `WCHAR`
+    fn annotate_character_literal(&mut self, character: &AstStatement, literal: &AstStatement) {
+        if let AstStatement::LiteralString { .. } = literal {
+            let character_type = self.annotation_map.get_type_or_void(character, self.index);
+            if character_type.get_type_information().is_character() {
+                let type_name = character_type.get_name().to_string();
+                self.annotation_map
+                    .annotate_type_hint(literal, StatementAnnotation::new_value(type_name));
+            }
+        }
+    }
+
+    /// returns the name, the argument, the source- and the target-type if the given call
+    /// calls a conversion function that is generated by the compiler (e.g. `DT_TO_DATE(x)`).
+    /// Declared POUs with the same name take precedence over the generated conversion.
+    fn find_conversion_call<'s>(
+        &self,
+        operator: &'s AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<(
+        &'s str,
+        &'s AstStatement,
+        &'i typesystem::DataType,
+        &'i typesystem::DataType,
+    )> {
+        if let AstStatement::Reference { name, .. } = operator {
+            if self.index.find_implementation(name).is_none() {
+                let argument = ast::get_single_argument(parameters)?;
+                return typesystem::find_conversion(name, self.index)
+                    .map(|(source, target)| (name.as_str(), argument, source, target));
+            }
+        }
+        None
+    }
+
+    /// returns the name, the selector and the inputs if the given call calls a selection function
+    /// that is generated by the compiler (e.g. `MAX(x, y)` or `SEL(g, x, y)`)
+    fn find_selection_call<'s>(
+        &self,
+        operator: &'s AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<(&'s str, Option<&'s AstStatement>, Vec<&'s AstStatement>)> {
+        if let AstStatement::Reference { name, .. } = operator {
+            if typesystem::is_selection_function(name)
+                && self.index.find_implementation(name).is_none()
+            {
+                let arguments = ast::get_implicit_arguments(parameters)?;
+                let (selector, inputs) = typesystem::split_selection_arguments(name, &arguments)?;
+                return Some((name.as_str(), selector.copied(), inputs.to_vec()));
+            }
+        }
+        None
+    }
+
+    /// returns the name, the shifted value and the shift amount if the given call calls a
+    /// bit-shift function that is generated by the compiler (e.g. `SHL(x, 2)`)
+    fn find_bit_shift_call<'s>(
+        &self,
+        operator: &'s AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<(&'s str, &'s AstStatement, &'s AstStatement)> {
+        if let AstStatement::Reference { name, .. } = operator {
+            if typesystem::is_bit_shift_function(name)
+                && self.index.find_implementation(name).is_none()
+            {
+                let (value, amount) = ast::get_argument_pair(parameters)?;
+                return Some((name.as_str(), value, amount));
+            }
+        }
+        None
+    }
+
+    /// returns true if the given statement accesses an element of a constant array
+    /// (e.g. `c[1]` or `c[1][2]` for a constant `c`)
+    fn is_constant_array_element(&self, statement: &AstStatement) -> bool {
+        if let AstStatement::ArrayAccess { reference, .. } = statement {
+            match self.annotation_map.get(reference) {
+                Some(StatementAnnotation::Variable { constant, .. }) => *constant,
+                _ => self.is_constant_array_element(reference),
+            }
+        } else {
+            false
+        }
+    }
+
+    /// returns the name and the arguments if the given call calls a numeric function that is
+    /// generated by the compiler (e.g. `SQRT(x)` or `ATAN2(y, x)`)
+    fn find_numeric_call<'s>(
+        &self,
+        operator: &'s AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<(&'s str, Vec<&'s AstStatement>)> {
+        if let AstStatement::Reference { name, .. } = operator {
+            if typesystem::is_numeric_function(name)
+                && self.index.find_implementation(name).is_none()
+            {
+                let arguments = typesystem::get_numeric_function_arguments(name, parameters)?;
+                return Some((name.as_str(), arguments));
+            }
+        }
+        None
+    }
+
+    /// returns the name and the argument if the given call calls the `SIZEOF` function that is
+    /// generated by the compiler (e.g. `SIZEOF(x)` or `SIZEOF(INT)`)
+    fn find_sizeof_call<'s>(
+        &self,
+        operator: &'s AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<(&'s str, &'s AstStatement)> {
+        if let AstStatement::Reference { name, .. } = operator {
+            if name.eq_ignore_ascii_case(typesystem::SIZEOF_FUNCTION)
+                && self.index.find_implementation(name).is_none()
+            {
+                let argument = ast::get_single_argument(parameters)?;
+                return Some((name.as_str(), argument));
+            }
+        }
+        None
+    }
+
+    /// returns the name and the argument if the given call calls one of the address functions
+    /// that are generated by the compiler (e.g. `ADR(x)` or `REF(x)`)
+    fn find_address_call<'s>(
+        &self,
+        operator: &'s AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<(&'s str, &'s AstStatement)> {
+        if let AstStatement::Reference { name, .. } = operator {
+            if typesystem::is_address_function(name)
+                && self.index.find_implementation(name).is_none()
+            {
+                let argument = ast::get_single_argument(parameters)?;
+                return Some((name.as_str(), argument));
+            }
+        }
+        None
+    }
+
+    /// returns the name, the base and the exponent if the given call calls the `EXPT` function
+    /// that is generated by the compiler (e.g. `EXPT(x, 2)` or `EXPT(IN1 := x, IN2 := 2)`)
+    fn find_exponent_call<'s>(
+        &self,
+        operator: &'s AstStatement,
+        parameters: &'s Option<AstStatement>,
+    ) -> Option<(&'s str, &'s AstStatement, &'s AstStatement)> {
+        if let AstStatement::Reference { name, .. } = operator {
+            if name.eq_ignore_ascii_case(typesystem::EXPT_FUNCTION)
+                && self.index.find_implementation(name).is_none()
+            {
+                let (base, exponent) = typesystem::get_exponent_arguments(parameters)?;
+                return Some((name.as_str(), base, exponent));
+            }
+        }
+        None
+    }
+
     // Returns a possible generic for the current statement
     fn get_generic_candidate<'idx>(
         index: &'idx Index,
@@ -1297,19 +1812,29 @@ impl<'i> TypeAnnotator<'i> {
                         return_type,
                         generic_map,
                     );
+                    //calls inside of generic functions may not resolve to concrete types
+                    let is_concrete = !generic_map
+                        .values()
+                        .any(|it| self.index.get_type_information_or_void(it).is_generic());
                     //Create a new pou and implementation for the function
                     if let Some((pou, implementation)) = self
                         .index
                         .find_effective_type(qualified_name)
                         .zip(self.index.find_implementation(qualified_name))
+                        .filter(|_| is_concrete)
                     {
                         self.annotation_map.new_index.register_implementation(
                             &name,
                             &name,
                             implementation.get_associated_class_name(),
                             implementation.get_implementation_type().clone(),
+                            implementation.get_access(),
+                            implementation.get_linkage(),
                         );
                         self.index_generic_type(pou, &name, generic_map);
+                        self.annotation_map
+                            .generic_instances
+                            .insert(name, implementation.get_call_name().into());
                     }
                     self.annotation_map.annotate(operator, annotation);
                 }
@@ -1434,8 +1959,9 @@ impl<'i> TypeAnnotator<'i> {
                     | AstStatement::OutputAssignment { left, right, .. } => {
                         if let AstStatement::Reference { name, .. } = &**left {
                             //Find the member with that name
-                            if let Some((_, Some(TypeAndNature { datatype, nature }))) =
-                                members.iter().find(|(it, _)| it.get_name() == name)
+                            if let Some((_, Some(TypeAndNature { datatype, nature }))) = members
+                                .iter()
+                                .find(|(it, _)| it.get_name().eq_ignore_ascii_case(name))
                             {
                                 self.annotation_map.add_generic_nature(p, *nature);
                                 self.annotation_map.annotate(
@@ -1556,10 +2082,11 @@ impl<'i> TypeAnnotator<'i> {
                 self.annotation_map
                     .annotate(statement, StatementAnnotation::value(DATE_AND_TIME_TYPE));
             }
-            AstStatement::LiteralReal { .. } => {
-                //TODO when do we need a LREAL literal?
-                self.annotation_map
-                    .annotate(statement, StatementAnnotation::value(REAL_TYPE));
+            AstStatement::LiteralReal { value, .. } => {
+                self.annotation_map.annotate(
+                    statement,
+                    StatementAnnotation::value(typesystem::get_real_type_name_for(value)),
+                );
             }
             AstStatement::LiteralArray {
                 elements: Some(elements),
@@ -1710,6 +2237,14 @@ fn to_variable_annotation(
     }
 }
 
+fn to_property_annotation(it: PropertyIndexEntry) -> StatementAnnotation {
+    StatementAnnotation::Property {
+        resulting_type: it.data_type_name.into(),
+        getter: it.getter.map(|it| it.get_call_name().into()),
+        setter: it.setter.map(|it| it.get_call_name().into()),
+    }
+}
+
 fn to_function_annotation(it: &ImplementationIndexEntry, index: &Index) -> StatementAnnotation {
     StatementAnnotation::Function {
         qualified_name: it.get_call_name().into(),
@@ -1721,11 +2256,23 @@ fn to_function_annotation(it: &ImplementationIndexEntry, index: &Index) -> State
     }
 }
 
+/// returns true if the given statement is an integer literal, optionally preceeded by a sign
+fn is_int_literal(statement: &AstStatement) -> bool {
+    match statement {
+        AstStatement::LiteralInteger { .. } => true,
+        AstStatement::UnaryExpression { value, .. } => is_int_literal(value),
+        _ => false,
+    }
+}
+
 fn get_int_type_name_for(value: i128) -> &'static str {
     if i32::MIN as i128 <= value && i32::MAX as i128 >= value {
         DINT_TYPE
-    } else {
+    } else if i64::MIN as i128 <= value && i64::MAX as i128 >= value {
         LINT_TYPE
+    } else {
+        //only an unsigned 64-bit integer can hold values beyond LINT's range
+        ULINT_TYPE
     }
 }
 
@@ -1,16 +1,20 @@
-use std::collections::VecDeque;
+use std::{
+    cmp::Ordering,
+    collections::{HashSet, VecDeque},
+};
 
 use crate::{
-    ast::{AstStatement, Operator, SourceRange},
+    ast::{self, AstId, AstStatement, Operator, SourceRange},
     index::{
         const_expressions::{ConstExpression, ConstId},
-        Index,
+        Index, VariableIndexEntry,
     },
     typesystem::{
-        DataType, DataTypeInformation, NativeByteType, NativeDintType, NativeDwordType,
-        NativeIntType, NativeLintType, NativeLwordType, NativeSintType, NativeWordType,
-        StringEncoding, DINT_SIZE, INT_SIZE, LINT_SIZE, SINT_SIZE,
+        self, DataType, DataTypeInformation, NativeByteType, NativeDintType, NativeDwordType,
+        NativeIntType, NativeLintType, NativeLwordType, NativeRealType, NativeSintType,
+        NativeWordType, StringEncoding, DINT_SIZE, INT_SIZE, LINT_SIZE, REAL_SIZE, SINT_SIZE,
     },
+    OverflowMode,
 };
 
 macro_rules! cannot_eval_error {
@@ -28,7 +32,7 @@ macro_rules! arithmetic_expression {
             (   AstStatement::LiteralInteger{value: lvalue, location: loc_left, ..},
                 AstStatement::LiteralInteger{value: rvalue, location: loc_right, ..}) => {
                 Ok(AstStatement::LiteralInteger{
-                    id: $resulting_id, value: lvalue $op rvalue, location: SourceRange::new(loc_left.get_start() .. loc_right.get_start())
+                    id: $resulting_id, value: lvalue $op rvalue, location: SourceRange::in_file(loc_left.get_start() .. loc_right.get_start(), loc_left.get_file())
                 })
             },
             (   AstStatement::LiteralInteger{value: lvalue, location: loc_left, ..},
@@ -36,7 +40,7 @@ macro_rules! arithmetic_expression {
                     let rvalue = rvalue.parse::<f64>()
                         .map_err(|err| err.to_string())?;
                 Ok(AstStatement::LiteralReal{
-                    id: $resulting_id, value: (*lvalue as f64 $op rvalue).to_string(), location: SourceRange::new(loc_left.get_start() .. loc_right.get_start())
+                    id: $resulting_id, value: (*lvalue as f64 $op rvalue).to_string(), location: SourceRange::in_file(loc_left.get_start() .. loc_right.get_start(), loc_left.get_file())
                 })
             },
             (   AstStatement::LiteralReal{value: lvalue, location: loc_left, ..},
@@ -44,7 +48,7 @@ macro_rules! arithmetic_expression {
                     let lvalue = lvalue.parse::<f64>()
                         .map_err(|err| err.to_string())?;
                 Ok(AstStatement::LiteralReal{
-                    id: $resulting_id, value: (lvalue $op *rvalue as f64).to_string(), location: SourceRange::new(loc_left.get_start() .. loc_right.get_start())
+                    id: $resulting_id, value: (lvalue $op *rvalue as f64).to_string(), location: SourceRange::in_file(loc_left.get_start() .. loc_right.get_start(), loc_left.get_file())
                 })
             },
             (   AstStatement::LiteralReal{value: lvalue, location: loc_left, ..},
@@ -54,7 +58,7 @@ macro_rules! arithmetic_expression {
                     let rvalue = rvalue.parse::<f64>()
                         .map_err(|err| err.to_string())?;
                 Ok(AstStatement::LiteralReal{
-                    id: $resulting_id, value: (lvalue $op rvalue).to_string(), location: SourceRange::new(loc_left.get_start() .. loc_right.get_start())
+                    id: $resulting_id, value: (lvalue $op rvalue).to_string(), location: SourceRange::in_file(loc_left.get_start() .. loc_right.get_start(), loc_left.get_file())
                 })
             },
             _ => cannot_eval_error!($left, $op_text, $right),
@@ -68,13 +72,27 @@ macro_rules! bitwise_expression {
             (   AstStatement::LiteralInteger{value: lvalue, location: loc_left, ..},
                 AstStatement::LiteralInteger{value: rvalue, location: loc_right, ..}) => {
                 Ok(AstStatement::LiteralInteger{
-                    id: $resulting_id, value: lvalue $op rvalue, location: SourceRange::new(loc_left.get_start() .. loc_right.get_start())
+                    id: $resulting_id, value: lvalue $op rvalue, location: SourceRange::in_file(loc_left.get_start() .. loc_right.get_start(), loc_left.get_file())
+                })
+            },
+            (   AstStatement::LiteralBool{value: lvalue, location: loc_left, ..},
+                AstStatement::LiteralBool{value: rvalue, location: loc_right, ..}) => {
+                Ok(AstStatement::LiteralBool{
+                    id: $resulting_id, value: lvalue $op rvalue, location: SourceRange::in_file(loc_left.get_start() .. loc_right.get_start(), loc_left.get_file())
                 })
             },
+            _ => cannot_eval_error!($left, $op_text, $right),
+        }
+    };
+}
+
+macro_rules! short_circuit_expression {
+    ($left:expr, $op:tt, $right:expr, $op_text:expr, $resulting_id:expr) => {
+        match ($left, $right) {
             (   AstStatement::LiteralBool{value: lvalue, location: loc_left, ..},
                 AstStatement::LiteralBool{value: rvalue, location: loc_right, ..}) => {
                 Ok(AstStatement::LiteralBool{
-                    id: $resulting_id, value: lvalue $op rvalue, location: SourceRange::new(loc_left.get_start() .. loc_right.get_start())
+                    id: $resulting_id, value: *lvalue $op *rvalue, location: SourceRange::in_file(loc_left.get_start() .. loc_right.get_start(), loc_left.get_file())
                 })
             },
             _ => cannot_eval_error!($left, $op_text, $right),
@@ -88,7 +106,7 @@ macro_rules! compare_expression {
             (   AstStatement::LiteralInteger{value: lvalue, location: loc_left, ..},
                 AstStatement::LiteralInteger{value: rvalue, location: loc_right, ..}) => {
                 Ok(AstStatement::LiteralBool{
-                    id: $resulting_id, value: lvalue $op rvalue, location: SourceRange::new(loc_left.get_start() .. loc_right.get_start())
+                    id: $resulting_id, value: lvalue $op rvalue, location: SourceRange::in_file(loc_left.get_start() .. loc_right.get_start(), loc_left.get_file())
                 })
             },
             (   AstStatement::LiteralReal{..},
@@ -98,7 +116,7 @@ macro_rules! compare_expression {
             (   AstStatement::LiteralBool{value: lvalue, location: loc_left, ..},
                 AstStatement::LiteralBool{value: rvalue, location: loc_right, ..}) => {
                 Ok(AstStatement::LiteralBool{
-                    id: $resulting_id, value: lvalue $op rvalue, location: SourceRange::new(loc_left.get_start() .. loc_right.get_start())
+                    id: $resulting_id, value: lvalue $op rvalue, location: SourceRange::in_file(loc_left.get_start() .. loc_right.get_start(), loc_left.get_file())
                 })
             },
             _ => cannot_eval_error!($left, $op_text, $right),
@@ -134,6 +152,20 @@ impl UnresolvableConstant {
     pub fn no_initial_value(id: &ConstId) -> Self {
         UnresolvableConstant::new(*id, "No initial value")
     }
+
+    pub fn cyclic_dependency(id: &ConstId, cycle: &[&str]) -> Self {
+        UnresolvableConstant::new(
+            *id,
+            format!("Cyclic constant definition: {:}", cycle.join(" -> ")).as_str(),
+        )
+    }
+
+    pub fn unknown_reference(id: &ConstId, reference: &str) -> Self {
+        UnresolvableConstant::new(
+            *id,
+            format!("Cannot resolve unknown reference '{:}'", reference).as_str(),
+        )
+    }
 }
 
 /// returns true, if the given expression needs to be evaluated.
@@ -161,12 +193,23 @@ fn needs_evaluation(expr: &AstStatement) -> bool {
         AstStatement::ExpressionList { expressions, .. } => {
             expressions.iter().any(needs_evaluation)
         }
+        AstStatement::Assignment { right, .. } => needs_evaluation(right.as_ref()),
         _ => true,
     }
 }
 
 /// returns the resolved constants index and a Vec of qualified names of constants that could not be resolved.
-pub fn evaluate_constants(mut index: Index) -> (Index, Vec<UnresolvableConstant>) {
+pub fn evaluate_constants(index: Index) -> (Index, Vec<UnresolvableConstant>) {
+    evaluate_constants_with_overflow(index, OverflowMode::Wrap)
+}
+
+/// like `evaluate_constants`, but integer arithmetic whose result does not fit the constant's type
+/// behaves like the given overflow mode (e.g. `c : INT := 32767 + 1` saturates to 32767 or is
+/// unresolvable instead of wrapping around)
+pub fn evaluate_constants_with_overflow(
+    mut index: Index,
+    overflow: OverflowMode,
+) -> (Index, Vec<UnresolvableConstant>) {
     let mut unresolvable: Vec<UnresolvableConstant> = Vec::new();
     let constants = index.get_const_expressions();
 
@@ -200,6 +243,41 @@ pub fn evaluate_constants(mut index: Index) -> (Index, Vec<UnresolvableConstant>
                     &index,
                 );
 
+                let initial_value_literal = match (initial_value_literal, candidates_type) {
+                    (
+                        Ok(Some(AstStatement::LiteralInteger {
+                            value,
+                            id,
+                            location,
+                        })),
+                        Some(DataTypeInformation::Integer { signed, size, .. }),
+                    ) if overflow != OverflowMode::Wrap
+                        && *size > 0
+                        && *size < 128
+                        && is_arithmetic_expression(const_expr.get_statement()) =>
+                    {
+                        let (min, max) = if *signed {
+                            (-(1_i128 << (size - 1)), (1_i128 << (size - 1)) - 1)
+                        } else {
+                            (0, (1_i128 << size) - 1)
+                        };
+                        if (min..=max).contains(&value) || overflow == OverflowMode::Saturate {
+                            Ok(Some(AstStatement::LiteralInteger {
+                                value: value.clamp(min, max),
+                                id,
+                                location,
+                            }))
+                        } else {
+                            Err(format!(
+                                "Overflow: {} does not fit into {}",
+                                value,
+                                target_type.unwrap_or_default()
+                            ))
+                        }
+                    }
+                    (literal, _) => literal,
+                };
+
                 match (initial_value_literal, candidates_type) {
                     //we found an Int-Value and we found the const's datatype to be an unsigned Integer type (e.g. WORD)
                     (
@@ -233,6 +311,36 @@ pub fn evaluate_constants(mut index: Index) -> (Index, Vec<UnresolvableConstant>
                         failed_tries = 0;
                     }
 
+                    //we found an Int-Value and we found the const's datatype to be a signed Integer type (e.g. LINT)
+                    (
+                        Ok(Some(AstStatement::LiteralInteger {
+                            value,
+                            id,
+                            location,
+                        })),
+                        Some(DataTypeInformation::Integer {
+                            size, signed: true, ..
+                        }),
+                    ) if *size > 0 && *size < 128 => {
+                        // the value wraps around like it does at runtime (e.g. LINT#16#7FFF_FFFF_FFFF_FFFF + 1),
+                        // so the bits > size of data_type are replaced by its sign-bit
+                        let unused_bits = 128 - size;
+                        let wrapped_value = (value << unused_bits) >> unused_bits;
+
+                        index
+                            .get_mut_const_expressions()
+                            .mark_resolved(
+                                &candidate,
+                                AstStatement::LiteralInteger {
+                                    id,
+                                    location,
+                                    value: wrapped_value,
+                                },
+                            )
+                            .expect("unknown id for const-expression"); //panic if we dont know the id
+                        failed_tries = 0;
+                    }
+
                     // we were able to evaluate a valid statement
                     (Ok(Some(literal)), _) => {
                         let literal = cast_if_necessary(
@@ -273,16 +381,186 @@ pub fn evaluate_constants(mut index: Index) -> (Index, Vec<UnresolvableConstant>
         }
     }
 
-    //import all constants that were note resolved in the loop above
-    unresolvable.extend(
-        remaining_constants
-            .iter()
-            .map(UnresolvableConstant::incomplete_initialzation),
-    );
+    //import all constants that were note resolved in the loop above, they are marked as
+    //unresolvable so their reason can be reported to the user
+    let remaining_constants = Vec::from(remaining_constants);
+    let remaining_unresolvables = remaining_constants
+        .iter()
+        .map(|candidate| {
+            if let Some(cycle) = find_cyclic_dependency(candidate, &remaining_constants, &index) {
+                UnresolvableConstant::cyclic_dependency(candidate, &cycle)
+            } else if let Some(reference) = index
+                .get_const_expressions()
+                .find_const_expression(candidate)
+                .and_then(|it| {
+                    find_unknown_reference(it.get_statement(), it.get_qualifier(), &index)
+                })
+            {
+                UnresolvableConstant::unknown_reference(candidate, reference)
+            } else {
+                UnresolvableConstant::incomplete_initialzation(candidate)
+            }
+        })
+        .collect::<Vec<_>>();
+    for unresolvable_constant in remaining_unresolvables {
+        index
+            .get_mut_const_expressions()
+            .mark_unresolvable(
+                &unresolvable_constant.id,
+                unresolvable_constant.reason.as_str(),
+            )
+            .expect("unknown id for const-expression"); //panic if we dont know the id
+        unresolvable.push(unresolvable_constant);
+    }
 
     (index, unresolvable)
 }
 
+/// returns true if the given expression is integer arithmetic that may overflow its type
+fn is_arithmetic_expression(statement: &AstStatement) -> bool {
+    matches!(
+        statement,
+        AstStatement::BinaryExpression {
+            operator: Operator::Plus | Operator::Minus | Operator::Multiplication,
+            ..
+        }
+    )
+}
+
+/// collects all references (`x` or `POU.x`) in the given expression
+fn collect_references<'a>(statement: &'a AstStatement, references: &mut Vec<&'a AstStatement>) {
+    match statement {
+        AstStatement::Reference { .. } | AstStatement::QualifiedReference { .. } => {
+            references.push(statement)
+        }
+        AstStatement::BinaryExpression { left, right, .. } => {
+            collect_references(left, references);
+            collect_references(right, references);
+        }
+        AstStatement::UnaryExpression { value, .. } => collect_references(value, references),
+        AstStatement::ExpressionList { expressions, .. } => expressions
+            .iter()
+            .for_each(|it| collect_references(it, references)),
+        AstStatement::LiteralArray {
+            elements: Some(elements),
+            ..
+        } => collect_references(elements, references),
+        AstStatement::MultipliedStatement { element, .. } => {
+            collect_references(element, references)
+        }
+        //the left side of a struct-initializer's assignment is a member, not a variable
+        AstStatement::Assignment { right, .. } => collect_references(right, references),
+        AstStatement::CallStatement {
+            operator,
+            parameters,
+            ..
+        } => {
+            //the argument of SIZEOF may be a type
+            let is_sizeof = matches!(operator.as_ref(), AstStatement::Reference { name, .. }
+                if name.eq_ignore_ascii_case(typesystem::SIZEOF_FUNCTION));
+            if let (false, Some(parameters)) = (is_sizeof, parameters.as_ref()) {
+                collect_references(parameters, references)
+            }
+        }
+        _ => {}
+    }
+}
+
+/// returns the variable the given reference (`x` or `POU.x`) refers to
+fn find_referenced_variable<'i>(
+    reference: &AstStatement,
+    scope: Option<&str>,
+    index: &'i Index,
+) -> Option<&'i VariableIndexEntry> {
+    match reference {
+        AstStatement::Reference { name, .. } => {
+            index.find_variable(scope, std::slice::from_ref(&name.as_str()))
+        }
+        AstStatement::QualifiedReference { elements, .. } => match elements.as_slice() {
+            [AstStatement::Reference { name: pou_name, .. }, AstStatement::Reference {
+                name: variable_name,
+                ..
+            }] => index.find_member(pou_name, variable_name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// returns the name of the first reference in the given expression that cannot be found
+/// in the given scope or in the global scope
+fn find_unknown_reference<'a>(
+    statement: &'a AstStatement,
+    scope: Option<&str>,
+    index: &Index,
+) -> Option<&'a str> {
+    let mut references = vec![];
+    collect_references(statement, &mut references);
+    references
+        .into_iter()
+        .find_map(|reference| match reference {
+            AstStatement::Reference { name, .. } => {
+                find_referenced_variable(reference, scope, index)
+                    .is_none()
+                    .then(|| name.as_str())
+            }
+            _ => None,
+        })
+}
+
+/// returns the qualified names of the constants the given constant depends on until it
+/// depends on itself again (e.g. `a -> b -> a`), or None if it is not part of such a cycle.
+/// Only constants that could not be resolved (`candidates`) can be part of a cycle.
+fn find_cyclic_dependency<'i>(
+    constant: &ConstId,
+    candidates: &[ConstId],
+    index: &'i Index,
+) -> Option<Vec<&'i str>> {
+    let mut visited = vec![];
+    find_dependency_path(constant, constant, candidates, index, &mut visited).map(|path| {
+        //the last dependency is the constant itself
+        let mut cycle = path.last().copied().into_iter().collect::<Vec<_>>();
+        cycle.extend(path);
+        cycle
+    })
+}
+
+/// returns the qualified names of the constants on the way from the `current` constant to the
+/// `target` constant
+fn find_dependency_path<'i>(
+    target: &ConstId,
+    current: &ConstId,
+    candidates: &[ConstId],
+    index: &'i Index,
+    visited: &mut Vec<ConstId>,
+) -> Option<Vec<&'i str>> {
+    let (statement, scope) = index.get_const_expressions().find_expression(current);
+    let mut references = vec![];
+    collect_references(statement?, &mut references);
+    for variable in references
+        .into_iter()
+        .filter_map(|reference| find_referenced_variable(reference, scope, index))
+    {
+        let dependency = match variable.initial_value {
+            Some(dependency) if candidates.contains(&dependency) => dependency,
+            _ => continue,
+        };
+        let path = if dependency == *target {
+            Some(vec![])
+        } else if !visited.contains(&dependency) {
+            visited.push(dependency);
+            find_dependency_path(target, &dependency, candidates, index, visited)
+        } else {
+            None
+        };
+        if let Some(mut path) = path {
+            path.insert(0, variable.get_qualified_name());
+            return Some(path);
+        }
+    }
+    None
+}
+
 /// transforms the given literal to better fit the datatype of the candidate
 /// effectively this casts an IntLiteral to a RealLiteral if necessary
 fn cast_if_necessary(
@@ -380,7 +658,7 @@ pub fn evaluate(
                 if let AstStatement::Reference { name: ref_name, .. } = target.as_ref() {
                     return index
                         .find_enum_element(enum_name, ref_name)
-                        .map(|v| resolve_const_reference(Some(v), ref_name, index))
+                        .map(|v| resolve_const_reference(Some(v), ref_name, target, index))
                         .unwrap_or_else(|| {
                             Err(format!(
                                 "Cannot resolve constant enum {}#{}.",
@@ -395,7 +673,7 @@ pub fn evaluate(
         },
         AstStatement::Reference { name, .. } => {
             let variable = index.find_variable(scope, std::slice::from_ref(&name.as_str()));
-            resolve_const_reference(variable, name, index)?
+            resolve_const_reference(variable, name, initial, index)?
         }
         AstStatement::QualifiedReference { elements, .. } => {
             // we made sure that there are exactly two references
@@ -410,7 +688,7 @@ pub fn evaluate(
                 ) = (&elements[0], &elements[1])
                 {
                     let variable = index.find_member(pou_name, variable_name);
-                    return resolve_const_reference(variable, variable_name, index);
+                    return resolve_const_reference(variable, variable_name, initial, index);
                 }
             }
             return Err("Qualified references only allow references to qualified variables in the form of 'POU.variable'".to_string());
@@ -429,6 +707,7 @@ pub fn evaluate(
                     Operator::Plus => arithmetic_expression!(left, +, right, "+", *id)?,
                     Operator::Minus => arithmetic_expression!(left, -, right, "-", *id)?,
                     Operator::Multiplication => arithmetic_expression!(left, *, right, "*", *id)?,
+                    Operator::Exponentiation => evaluate_exponentiation(left, right, *id)?,
                     Operator::Division if is_zero(right) => {
                         return Err("Attempt to divide by zero".to_string())
                     }
@@ -448,6 +727,10 @@ pub fn evaluate(
                     Operator::And => bitwise_expression!(left, & , right, "AND", *id)?,
                     Operator::Or => bitwise_expression!(left, | , right, "OR", *id)?,
                     Operator::Xor => bitwise_expression!(left, ^, right, "XOR", *id)?,
+                    Operator::AndThen => {
+                        short_circuit_expression!(left, &&, right, "AND_THEN", *id)?
+                    }
+                    Operator::OrElse => short_circuit_expression!(left, ||, right, "OR_ELSE", *id)?,
                     _ => {
                         return Err(format!(
                             "Cannot resolve operator {:?} in constant evaluation",
@@ -593,33 +876,540 @@ pub fn evaluate(
                 }
             })
         }
+        // x := 1 (a member's value in a struct-initializer)
+        AstStatement::Assignment { left, right, id } => {
+            evaluate(right, scope, index)?.map(|value| AstStatement::Assignment {
+                left: left.clone(),
+                right: Box::new(value),
+                id: *id,
+            })
+        }
+        AstStatement::CallStatement {
+            operator,
+            parameters,
+            ..
+        } => match operator.as_ref() {
+            AstStatement::Reference { name, .. }
+                if typesystem::is_selection_function(name)
+                    && index.find_implementation(name).is_none() =>
+            {
+                evaluate_selection(name, parameters, scope, index)?
+            }
+            AstStatement::Reference { name, .. }
+                if typesystem::is_bit_shift_function(name)
+                    && index.find_implementation(name).is_none() =>
+            {
+                evaluate_bit_shift(name, parameters, scope, index)?
+            }
+            AstStatement::Reference { name, .. }
+                if typesystem::is_numeric_function(name)
+                    && index.find_implementation(name).is_none() =>
+            {
+                evaluate_numeric_function(name, parameters, scope, index)?
+            }
+            AstStatement::Reference { name, id, .. }
+                if name.eq_ignore_ascii_case(typesystem::EXPT_FUNCTION)
+                    && index.find_implementation(name).is_none() =>
+            {
+                let (base, exponent) = typesystem::get_exponent_arguments(parameters)
+                    .ok_or_else(|| format!("Invalid number of arguments for {}", name))?;
+                let base = evaluate(base, scope, index)?;
+                let exponent = evaluate(exponent, scope, index)?;
+                match base.zip(exponent) {
+                    Some((base, exponent)) => Some(evaluate_exponentiation(&base, &exponent, *id)?),
+                    None => None,
+                }
+            }
+            AstStatement::Reference { name, id, location }
+                if name.eq_ignore_ascii_case(typesystem::SIZEOF_FUNCTION)
+                    && index.find_implementation(name).is_none() =>
+            {
+                let argument = ast::get_single_argument(parameters)
+                    .ok_or_else(|| format!("Invalid number of arguments for {}", name))?;
+                let type_name = find_sizeof_type_name(argument, scope, index).ok_or_else(|| {
+                    format!(
+                        "Cannot resolve the argument of {}, expected a variable or a type",
+                        name
+                    )
+                })?;
+                //the size may depend on constants that are not resolved yet (e.g. array bounds)
+                typesystem::get_size_in_bytes(type_name, index).map(|size| {
+                    AstStatement::LiteralInteger {
+                        value: size as i128,
+                        id: *id,
+                        location: location.clone(),
+                    }
+                })
+            }
+            _ => return Err(format!("Cannot resolve constant: {:#?}", initial)),
+        },
         _ => return Err(format!("Cannot resolve constant: {:#?}", initial)),
     };
     Ok(literal)
 }
 
+/// evaluates a call to one of the generated selection functions (e.g. `MAX(1, 2)`) to the
+/// selected literal, returns None if not all arguments can be resolved yet
+fn evaluate_selection(
+    function_name: &str,
+    parameters: &Option<AstStatement>,
+    scope: Option<&str>,
+    index: &Index,
+) -> Result<Option<AstStatement>, String> {
+    let arguments = ast::get_implicit_arguments(parameters)
+        .ok_or_else(|| format!("Cannot resolve the arguments of {}", function_name))?
+        .into_iter()
+        .map(|argument| evaluate(argument, scope, index))
+        .collect::<Result<Option<Vec<AstStatement>>, String>>()?;
+    let arguments = match arguments {
+        Some(arguments) => arguments,
+        None => return Ok(None), //not all arguments can be resolved yet
+    };
+    let (selector, inputs) = typesystem::split_selection_arguments(function_name, &arguments)
+        .ok_or_else(|| format!("Invalid number of arguments for {}", function_name))?;
+
+    let selected = match (function_name.to_uppercase().as_str(), selector, inputs) {
+        ("SEL", Some(AstStatement::LiteralBool { value, .. }), [in0, in1]) => {
+            if *value {
+                in1
+            } else {
+                in0
+            }
+        }
+        ("MUX", Some(AstStatement::LiteralInteger { value, .. }), inputs) => {
+            usize::try_from(*value)
+                .ok()
+                .and_then(|k| inputs.get(k))
+                .ok_or_else(|| format!("MUX selector {} is out of range", value))?
+        }
+        ("MAX", None, [first, others @ ..]) => {
+            others.iter().try_fold(first, |selected, input| {
+                compare_literals(input, selected).map(|order| {
+                    if order == Ordering::Greater {
+                        input
+                    } else {
+                        selected
+                    }
+                })
+            })?
+        }
+        ("MIN", None, [first, others @ ..]) => {
+            others.iter().try_fold(first, |selected, input| {
+                compare_literals(input, selected).map(|order| {
+                    if order == Ordering::Less {
+                        input
+                    } else {
+                        selected
+                    }
+                })
+            })?
+        }
+        ("LIMIT", None, [min, value, max]) => {
+            if compare_literals(value, min)? == Ordering::Less {
+                min
+            } else if compare_literals(value, max)? == Ordering::Greater {
+                max
+            } else {
+                value
+            }
+        }
+        _ => return Err(format!("Cannot resolve constant {} call", function_name)),
+    };
+    Ok(Some(selected.clone()))
+}
+
+/// evaluates a call to one of the generated bit-shift functions (e.g. `SHL(x, 2)`) with the
+/// same semantics as the generated code. The bit-width of the shifted value is only known for
+/// typed literals (e.g. `WORD#1`) and references, untyped values are shifted without a width
+/// and cannot be rotated.
+/// returns None if not all arguments can be resolved yet
+fn evaluate_bit_shift(
+    function_name: &str,
+    parameters: &Option<AstStatement>,
+    scope: Option<&str>,
+    index: &Index,
+) -> Result<Option<AstStatement>, String> {
+    let (value, amount) = ast::get_argument_pair(parameters)
+        .ok_or_else(|| format!("Invalid number of arguments for {}", function_name))?;
+    let value_type = get_integer_type(value, scope, index);
+    let (value, id, location, amount) = match (
+        evaluate(value, scope, index)?,
+        evaluate(amount, scope, index)?,
+    ) {
+        (
+            Some(AstStatement::LiteralInteger {
+                value,
+                id,
+                location,
+            }),
+            Some(AstStatement::LiteralInteger { value: amount, .. }),
+        ) => (value, id, location, amount),
+        (Some(value), Some(amount)) => {
+            return Err(format!(
+                "Cannot evaluate {}({:?}, {:?})",
+                function_name, value, amount
+            ))
+        }
+        _ => return Ok(None), //not all arguments can be resolved yet
+    };
+    if amount < 0 {
+        return Err(format!(
+            "Cannot evaluate {} by a negative amount of bits",
+            function_name
+        ));
+    }
+
+    let function_name = function_name.to_uppercase();
+    let value = match (function_name.as_str(), value_type) {
+        ("SHL", None) => u32::try_from(amount)
+            .ok()
+            .and_then(|amount| value.checked_shl(amount))
+            .unwrap_or(0),
+        ("SHR", None) => u32::try_from(amount)
+            .ok()
+            .and_then(|amount| value.checked_shr(amount))
+            .unwrap_or(0),
+        (_, None) => {
+            return Err(format!(
+                "Cannot evaluate {} of a value without a type",
+                function_name
+            ))
+        }
+        (_, Some((width, signed))) => {
+            let width = width as i128;
+            let mask = (1_i128 << width) - 1;
+            let value = value & mask;
+            let rotation = amount % width;
+            let result = match function_name.as_str() {
+                "SHL" if amount < width => value << amount,
+                "SHR" if amount < width => value >> amount,
+                "ROL" => (value << rotation) | (value >> (width - rotation)),
+                "ROR" => (value >> rotation) | (value << (width - rotation)),
+                _ => 0,
+            } & mask;
+            //signed values are sign-extended from their bit-width again
+            if signed && result >> (width - 1) == 1 {
+                result - (1_i128 << width)
+            } else {
+                result
+            }
+        }
+    };
+    Ok(Some(AstStatement::LiteralInteger {
+        value,
+        id,
+        location,
+    }))
+}
+
+/// evaluates a call to one of the generated numeric functions (e.g. `SQRT(2.0)`). `ABS` of an
+/// integer results in an integer, all other results are reals.
+/// returns None if not all arguments can be resolved yet
+fn evaluate_numeric_function(
+    function_name: &str,
+    parameters: &Option<AstStatement>,
+    scope: Option<&str>,
+    index: &Index,
+) -> Result<Option<AstStatement>, String> {
+    let arguments = typesystem::get_numeric_function_arguments(function_name, parameters)
+        .ok_or_else(|| format!("Invalid number of arguments for {}", function_name))?;
+    let mut values = Vec::new();
+    for argument in arguments {
+        match evaluate(argument, scope, index)? {
+            Some(value) => values.push(value),
+            None => return Ok(None), //not all arguments can be resolved yet
+        }
+    }
+    let (id, location) = match values.first() {
+        Some(value) => (value.get_id(), value.get_location()),
+        None => return Err(format!("Invalid number of arguments for {}", function_name)),
+    };
+    if let [AstStatement::LiteralInteger { value, .. }] = values.as_slice() {
+        if function_name.eq_ignore_ascii_case("ABS") {
+            return Ok(Some(AstStatement::LiteralInteger {
+                value: value.abs(),
+                id,
+                location,
+            }));
+        }
+    }
+    let reals = values
+        .iter()
+        .map(|value| match value {
+            AstStatement::LiteralInteger { value, .. } => Ok(*value as f64),
+            AstStatement::LiteralReal { value, .. } => {
+                value.parse::<f64>().map_err(|err| err.to_string())
+            }
+            _ => Err(format!("Cannot evaluate {}({:?})", function_name, value)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let value = match (function_name.to_uppercase().as_str(), reals.as_slice()) {
+        ("ABS", [x]) => x.abs(),
+        ("SQRT", [x]) => x.sqrt(),
+        ("LN", [x]) => x.ln(),
+        ("LOG", [x]) => x.log10(),
+        ("EXP", [x]) => x.exp(),
+        ("SIN", [x]) => x.sin(),
+        ("COS", [x]) => x.cos(),
+        ("TAN", [x]) => x.tan(),
+        ("ASIN", [x]) => x.asin(),
+        ("ACOS", [x]) => x.acos(),
+        ("ATAN", [x]) => x.atan(),
+        ("ATAN2", [y, x]) => y.atan2(*x),
+        _ => return Err(format!("Invalid number of arguments for {}", function_name)),
+    };
+    if !value.is_finite() {
+        return Err(format!(
+            "Cannot evaluate {}({:?}), the result is no finite number",
+            function_name, reals
+        ));
+    }
+    Ok(Some(AstStatement::LiteralReal {
+        value: value.to_string(),
+        id,
+        location,
+    }))
+}
+
+/// evaluates the exponentiation `base ** exponent` with the same semantics as the generated code.
+/// Integers are raised by repeated multiplication and wrap around, a negative exponent results
+/// in the truncated reciprocal (e.g. `2 ** -1` is 0). Reals and mixed operands result in a real.
+fn evaluate_exponentiation(
+    base: &AstStatement,
+    exponent: &AstStatement,
+    id: AstId,
+) -> Result<AstStatement, String> {
+    let location = SourceRange::in_file(
+        base.get_location().get_start()..exponent.get_location().get_start(),
+        base.get_location().get_file(),
+    );
+    let to_real = |value: &AstStatement| match value {
+        AstStatement::LiteralInteger { value, .. } => Ok(*value as f64),
+        AstStatement::LiteralReal { value, .. } => {
+            value.parse::<f64>().map_err(|err| err.to_string())
+        }
+        _ => cannot_eval_error!(base, "**", exponent),
+    };
+    match (base, exponent) {
+        (
+            AstStatement::LiteralInteger { value: base, .. },
+            AstStatement::LiteralInteger {
+                value: exponent, ..
+            },
+        ) => Ok(AstStatement::LiteralInteger {
+            value: integer_power(*base, *exponent),
+            id,
+            location,
+        }),
+        _ => Ok(AstStatement::LiteralReal {
+            value: to_real(base)?.powf(to_real(exponent)?).to_string(),
+            id,
+            location,
+        }),
+    }
+}
+
+/// raises the given base to the power of the given exponent by squaring, the result wraps around
+/// on overflow. A negative exponent results in the truncated reciprocal, which is only non-zero
+/// for the bases 1 and -1
+fn integer_power(base: i128, exponent: i128) -> i128 {
+    let mut result: i128 = 1;
+    let mut factor = base;
+    let mut remaining = exponent.unsigned_abs();
+    while remaining > 0 {
+        if remaining & 1 == 1 {
+            result = result.wrapping_mul(factor);
+        }
+        factor = factor.wrapping_mul(factor);
+        remaining >>= 1;
+    }
+    match (exponent < 0, base) {
+        (false, _) | (true, 1) | (true, -1) => result,
+        _ => 0,
+    }
+}
+
+/// returns the name of the type whose size is requested by the argument of `SIZEOF`, which
+/// is either a (qualified) reference to a variable or the name of a type
+fn find_sizeof_type_name<'i>(
+    argument: &AstStatement,
+    scope: Option<&str>,
+    index: &'i Index,
+) -> Option<&'i str> {
+    let segments = match argument {
+        AstStatement::Reference { name, .. } => vec![name.as_str()],
+        AstStatement::QualifiedReference { elements, .. } => elements
+            .iter()
+            .map(|it| match it {
+                AstStatement::Reference { name, .. } => Some(name.as_str()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?,
+        _ => return None,
+    };
+    if let Some(variable) = index.find_variable(scope, &segments) {
+        //the size of a VAR_IN_OUT is the size of the passed variable
+        return match index.find_effective_type_info(variable.get_type_name()) {
+            Some(DataTypeInformation::Pointer {
+                inner_type_name,
+                auto_deref: true,
+                ..
+            }) => Some(inner_type_name.as_str()),
+            _ => Some(variable.get_type_name()),
+        };
+    }
+    match segments.as_slice() {
+        [type_name] => index.find_type(type_name).map(DataType::get_name),
+        _ => None,
+    }
+}
+
+/// returns the bit-width and the signedness of the given statement if it is a typed literal
+/// or a reference to an integer variable
+fn get_integer_type(
+    statement: &AstStatement,
+    scope: Option<&str>,
+    index: &Index,
+) -> Option<(u32, bool)> {
+    let type_name = match statement {
+        AstStatement::CastStatement { type_name, .. } => type_name.as_str(),
+        AstStatement::Reference { name, .. } => index
+            .find_variable(scope, std::slice::from_ref(&name.as_str()))?
+            .get_type_name(),
+        _ => return None,
+    };
+    match index.find_effective_type_info(type_name)? {
+        DataTypeInformation::Integer { size, signed, .. } => Some((*size, *signed)),
+        _ => None,
+    }
+}
+
+/// compares two numeric literals
+fn compare_literals(left: &AstStatement, right: &AstStatement) -> Result<Ordering, String> {
+    let to_number = |literal: &AstStatement| match literal {
+        AstStatement::LiteralInteger { value, .. } => Ok(*value as f64),
+        AstStatement::LiteralReal { value, .. } => value
+            .parse::<f64>()
+            .map_err(|err| format!("{:}: {:}", err, value)),
+        _ => Err(format!("Cannot compare {:?}", literal)),
+    };
+    match (left, right) {
+        (
+            AstStatement::LiteralInteger { value: left, .. },
+            AstStatement::LiteralInteger { value: right, .. },
+        ) => Ok(left.cmp(right)),
+        _ => to_number(left)?
+            .partial_cmp(&to_number(right)?)
+            .ok_or_else(|| format!("Cannot compare {:?} and {:?}", left, right)),
+    }
+}
+
 /// attempts to resolve the inital value of this reference's target
 /// may return Ok(None) if the variable's initial value can not be
-/// resolved yet
+/// resolved yet. A constant without an initializer resolves to its type's default value
 fn resolve_const_reference(
     variable: Option<&crate::index::VariableIndexEntry>,
     name: &str,
+    reference: &AstStatement,
     index: &Index,
 ) -> Result<Option<AstStatement>, String> {
-    if variable.filter(|it| !it.is_constant()).is_some() {
-        //the referenced variable is no const!
-        return Err(format!("'{:}' is no const reference", name));
+    let variable = match variable {
+        Some(variable) if !variable.is_constant() => {
+            //the referenced variable is no const!
+            return Err(format!("'{:}' is no const reference", name));
+        }
+        Some(variable) => variable,
+        None => return Ok(None),
+    };
+    match variable.initial_value {
+        Some(initial_value) => Ok(
+            if let Some(ConstExpression::Resolved(statement)) = index
+                .get_const_expressions()
+                .find_const_expression(&initial_value)
+            {
+                Some(statement.clone())
+            } else {
+                None
+            },
+        ),
+        None => get_default_value(variable.get_type_name(), reference, index),
+    }
+}
+
+/// returns the value of a variable of the given type that has no initializer: the initial value of
+/// the type's declaration or the default of the elementary type (`0`, `FALSE`, `0.0`, `''`, ...)
+/// may return Ok(None) if the type's initial value can not be resolved yet
+fn get_default_value(
+    type_name: &str,
+    reference: &AstStatement,
+    index: &Index,
+) -> Result<Option<AstStatement>, String> {
+    //aliases, sub-ranges and enums use the initial value of the type they refer to
+    let mut visited = HashSet::new();
+    let mut data_type = index
+        .get_type(type_name)
+        .map_err(|it| it.get_message().to_string())?;
+    loop {
+        if let Some(initial_value) = data_type.initial_value {
+            return match index
+                .get_const_expressions()
+                .find_const_expression(&initial_value)
+            {
+                Some(ConstExpression::Resolved(statement)) => Ok(Some(statement.clone())),
+                Some(ConstExpression::Unresolvable { reason, .. }) => Err(reason.clone()),
+                _ => Ok(None),
+            };
+        }
+        match data_type.get_type_information() {
+            DataTypeInformation::Alias {
+                referenced_type, ..
+            }
+            | DataTypeInformation::SubRange {
+                referenced_type, ..
+            }
+            | DataTypeInformation::Enum {
+                referenced_type, ..
+            } if visited.insert(data_type.get_name()) => {
+                data_type = index
+                    .get_type(referenced_type)
+                    .map_err(|it| it.get_message().to_string())?;
+            }
+            _ => break,
+        }
+    }
+
+    let id = reference.get_id();
+    let location = reference.get_location();
+    match data_type.get_type_information() {
+        info @ DataTypeInformation::Integer { .. } if info.is_bool() => {
+            Ok(Some(AstStatement::LiteralBool {
+                value: false,
+                id,
+                location,
+            }))
+        }
+        //dates and times are integers, their default is the epoch
+        DataTypeInformation::Integer { .. } => Ok(Some(AstStatement::LiteralInteger {
+            value: 0,
+            id,
+            location,
+        })),
+        DataTypeInformation::Float { .. } => Ok(Some(AstStatement::LiteralReal {
+            value: "0.0".to_string(),
+            id,
+            location,
+        })),
+        DataTypeInformation::String { encoding, .. } => Ok(Some(AstStatement::LiteralString {
+            value: "".to_string(),
+            is_wide: *encoding == StringEncoding::Utf16,
+            id,
+            location,
+        })),
+        _ => Err(format!(
+            "Cannot evaluate the default value of {:}",
+            type_name
+        )),
     }
-    Ok(
-        if let Some(ConstExpression::Resolved(statement)) = variable
-            .and_then(|it| it.initial_value.as_ref())
-            .and_then(|it| index.get_const_expressions().find_const_expression(it))
-        {
-            Some(statement.clone())
-        } else {
-            None
-        },
-    )
 }
 
 fn is_zero(v: &AstStatement) -> bool {
@@ -689,7 +1479,31 @@ fn get_cast_statement_literal(
             }
         }
 
-        //Some(&crate::typesystem::DataTypeInformation::Float{..}) => {},
+        Some(&crate::typesystem::DataTypeInformation::Float { size, .. }) => {
+            let value = match evaluate(cast_statement, scope, index)? {
+                Some(AstStatement::LiteralInteger { value, .. }) => value as f64,
+                Some(AstStatement::LiteralReal { value, .. }) => {
+                    value.parse::<f64>().map_err(|err| err.to_string())?
+                }
+                _ => {
+                    return Err(format!(
+                        "Cannot resolve constant: {:}#{:?}",
+                        type_name, cast_statement
+                    ))
+                }
+            };
+            //the value has the precision of the stated type
+            let value = if size == REAL_SIZE {
+                (value as NativeRealType) as f64
+            } else {
+                value
+            };
+            Ok(AstStatement::LiteralReal {
+                value: value.to_string(),
+                id: cast_statement.get_id(),
+                location: cast_statement.get_location(),
+            })
+        }
         _ => Err(format!(
             "Cannot resolve constant: {:}#{:?}",
             type_name, cast_statement
@@ -0,0 +1,312 @@
+// Copyright (c) 2021 Ghaith Hachem and Mathias Rieder
+
+//! Instantiates generic functions
+//!
+//! A call of a generic function with concrete types (e.g. `foo(INT#1)` of
+//! `FUNCTION foo : INT VAR_INPUT x : ANY_INT; END_VAR`) is resolved to an instance of the
+//! function (e.g. `foo__INT`) whose variables are indexed with the concrete types.
+//! The instances of generic functions that are implemented in ST get a copy of the generic
+//! function's body, so they can be annotated, validated and generated like any other function.
+use indexmap::IndexMap;
+
+use crate::{
+    ast::{AstStatement, CompilationUnit, ConditionalBlock, Implementation, LinkageType, Pou},
+    lexer::IdProvider,
+};
+
+/// creates the implementations of the given generic instances (instance-name -> generic function)
+/// that are not implemented in the given units yet (e.g. by a hand-written `foo__INT`).
+///
+/// returns the new implementations in a unit per file that declares the instantiated functions
+pub fn create_generic_instances(
+    units: &[(usize, CompilationUnit)],
+    generic_instances: &IndexMap<String, String>,
+    id_provider: IdProvider,
+) -> Vec<(usize, CompilationUnit)> {
+    let implementations = || {
+        units
+            .iter()
+            .flat_map(|(file_id, unit)| unit.implementations.iter().map(move |it| (*file_id, it)))
+    };
+
+    let mut instances: IndexMap<usize, CompilationUnit> = IndexMap::new();
+    for (instance_name, generic_name) in generic_instances {
+        if implementations().any(|(_, it)| it.name.eq_ignore_ascii_case(instance_name)) {
+            continue;
+        }
+        //external generic functions are implemented elsewhere
+        let generic = implementations().find(|(_, it)| {
+            it.name.eq_ignore_ascii_case(generic_name) && it.linkage == LinkageType::Internal
+        });
+        if let Some((file_id, generic)) = generic {
+            //the instances are located in the file of the generic function
+            let source_unit = units
+                .iter()
+                .find(|(id, _)| *id == file_id)
+                .map(|(_, it)| it);
+            let mut body = InstanceBody {
+                id_provider: id_provider.clone(),
+                return_variable: Pou::calc_return_name(generic_name),
+                instance_return_variable: instance_name,
+            };
+            let mut statements = generic.statements.clone();
+            body.visit_all(&mut statements);
+            instances
+                .entry(file_id)
+                .or_insert_with(|| CompilationUnit {
+                    file_name: source_unit
+                        .map(|it| it.file_name.clone())
+                        .unwrap_or_default(),
+                    new_lines: source_unit
+                        .map(|it| it.new_lines.clone())
+                        .unwrap_or_default(),
+                    ..CompilationUnit::default()
+                })
+                .implementations
+                .push(Implementation {
+                    name: instance_name.clone(),
+                    type_name: instance_name.clone(),
+                    linkage: generic.linkage,
+                    pou_type: generic.pou_type.clone(),
+                    statements,
+                    location: generic.location.clone(),
+                    overriding: generic.overriding,
+                    access: generic.access,
+                    comments: vec![],
+                });
+        }
+    }
+    instances.into_iter().collect()
+}
+
+/// gives the given statement and all of its elements a new id, e.g. the expressions of a
+/// precompiled library, whose ids were provided by the compilation of the library
+pub fn renew_ids(statement: &mut AstStatement, id_provider: IdProvider) {
+    //no reference is named like the empty return variable
+    InstanceBody {
+        id_provider,
+        return_variable: "",
+        instance_return_variable: "",
+    }
+    .visit(statement);
+}
+
+/// prepares the copy of a generic function's body for one of its instances: every statement gets
+/// a new id, so the copy can be annotated independently of the original, and the references to
+/// the function's return variable are renamed to the instance's return variable
+struct InstanceBody<'a> {
+    id_provider: IdProvider,
+    /// the name of the generic function's return variable (e.g. `foo`)
+    return_variable: &'a str,
+    /// the name of the instance's return variable (e.g. `foo__INT`)
+    instance_return_variable: &'a str,
+}
+
+impl InstanceBody<'_> {
+    fn visit_all(&mut self, statements: &mut [AstStatement]) {
+        statements.iter_mut().for_each(|it| self.visit(it))
+    }
+
+    fn visit_blocks(&mut self, blocks: &mut [ConditionalBlock]) {
+        for block in blocks.iter_mut() {
+            self.visit(&mut block.condition);
+            self.visit_all(&mut block.body);
+        }
+    }
+
+    /// visits a statement naming a member or a callable, which is never the return variable
+    /// (e.g. `x` of `s.x`, `foo` of `foo(x)` or `a` of `foo(a := x)`)
+    fn visit_member(&mut self, statement: &mut AstStatement) {
+        match statement {
+            AstStatement::Reference { id, .. } => *id = self.id_provider.next_id(),
+            AstStatement::ArrayAccess {
+                reference,
+                access,
+                id,
+            } => {
+                *id = self.id_provider.next_id();
+                self.visit_member(reference);
+                self.visit(access);
+            }
+            _ => self.visit(statement),
+        }
+    }
+
+    fn visit_parameter(&mut self, parameter: &mut AstStatement) {
+        match parameter {
+            AstStatement::ExpressionList { expressions, id } => {
+                *id = self.id_provider.next_id();
+                expressions
+                    .iter_mut()
+                    .for_each(|it| self.visit_parameter(it));
+            }
+            AstStatement::Assignment { left, right, id }
+            | AstStatement::OutputAssignment { left, right, id } => {
+                *id = self.id_provider.next_id();
+                self.visit_member(left);
+                self.visit(right);
+            }
+            _ => self.visit(parameter),
+        }
+    }
+
+    fn visit(&mut self, statement: &mut AstStatement) {
+        match statement {
+            AstStatement::Reference { name, id, .. } => {
+                *id = self.id_provider.next_id();
+                if name.eq_ignore_ascii_case(self.return_variable) {
+                    *name = self.instance_return_variable.to_string();
+                }
+            }
+            AstStatement::EmptyStatement { id, .. }
+            | AstStatement::LiteralNull { id, .. }
+            | AstStatement::LiteralInteger { id, .. }
+            | AstStatement::LiteralDate { id, .. }
+            | AstStatement::LiteralDateAndTime { id, .. }
+            | AstStatement::LiteralTimeOfDay { id, .. }
+            | AstStatement::LiteralTime { id, .. }
+            | AstStatement::LiteralReal { id, .. }
+            | AstStatement::LiteralBool { id, .. }
+            | AstStatement::LiteralString { id, .. }
+            | AstStatement::ReturnStatement { id, .. }
+            | AstStatement::ContinueStatement { id, .. }
+            | AstStatement::ExitStatement { id, .. } => *id = self.id_provider.next_id(),
+            AstStatement::LiteralArray { elements, id, .. } => {
+                *id = self.id_provider.next_id();
+                if let Some(elements) = elements.as_mut() {
+                    self.visit(elements);
+                }
+            }
+            AstStatement::CastStatement { target, id, .. } => {
+                *id = self.id_provider.next_id();
+                self.visit(target);
+            }
+            AstStatement::MultipliedStatement { element, id, .. } => {
+                *id = self.id_provider.next_id();
+                self.visit(element);
+            }
+            AstStatement::QualifiedReference { elements, id } => {
+                *id = self.id_provider.next_id();
+                if let Some((head, members)) = elements.split_first_mut() {
+                    self.visit(head);
+                    members.iter_mut().for_each(|it| self.visit_member(it));
+                }
+            }
+            AstStatement::ArrayAccess {
+                reference,
+                access,
+                id,
+            } => {
+                *id = self.id_provider.next_id();
+                self.visit(reference);
+                self.visit(access);
+            }
+            AstStatement::PointerAccess { reference, id } => {
+                *id = self.id_provider.next_id();
+                self.visit(reference);
+            }
+            AstStatement::DirectAccess { index, id, .. } => {
+                *id = self.id_provider.next_id();
+                self.visit(index);
+            }
+            AstStatement::HardwareAccess { address, id, .. } => {
+                *id = self.id_provider.next_id();
+                self.visit_all(address);
+            }
+            AstStatement::BinaryExpression {
+                left, right, id, ..
+            }
+            | AstStatement::Assignment { left, right, id }
+            | AstStatement::OutputAssignment { left, right, id } => {
+                *id = self.id_provider.next_id();
+                self.visit(left);
+                self.visit(right);
+            }
+            AstStatement::UnaryExpression { value, id, .. } => {
+                *id = self.id_provider.next_id();
+                self.visit(value);
+            }
+            AstStatement::ExpressionList { expressions, id } => {
+                *id = self.id_provider.next_id();
+                self.visit_all(expressions);
+            }
+            AstStatement::RangeStatement { start, end, id } => {
+                *id = self.id_provider.next_id();
+                self.visit(start);
+                self.visit(end);
+            }
+            AstStatement::CallStatement {
+                operator,
+                parameters,
+                id,
+                ..
+            } => {
+                *id = self.id_provider.next_id();
+                self.visit_member(operator);
+                if let Some(parameters) = &mut **parameters {
+                    self.visit_parameter(parameters);
+                }
+            }
+            AstStatement::IfStatement {
+                blocks,
+                else_block,
+                id,
+                ..
+            } => {
+                *id = self.id_provider.next_id();
+                self.visit_blocks(blocks);
+                self.visit_all(else_block);
+            }
+            AstStatement::CaseStatement {
+                selector,
+                case_blocks,
+                else_block,
+                id,
+                ..
+            } => {
+                *id = self.id_provider.next_id();
+                self.visit(selector);
+                self.visit_blocks(case_blocks);
+                self.visit_all(else_block);
+            }
+            AstStatement::ForLoopStatement {
+                counter,
+                start,
+                end,
+                by_step,
+                body,
+                id,
+                ..
+            } => {
+                *id = self.id_provider.next_id();
+                self.visit(counter);
+                self.visit(start);
+                self.visit(end);
+                if let Some(by_step) = by_step.as_mut() {
+                    self.visit(by_step);
+                }
+                self.visit_all(body);
+            }
+            AstStatement::WhileLoopStatement {
+                condition,
+                body,
+                id,
+                ..
+            }
+            | AstStatement::RepeatLoopStatement {
+                condition,
+                body,
+                id,
+                ..
+            } => {
+                *id = self.id_provider.next_id();
+                self.visit(condition);
+                self.visit_all(body);
+            }
+            AstStatement::CaseCondition { condition, id } => {
+                *id = self.id_provider.next_id();
+                self.visit(condition);
+            }
+        }
+    }
+}
@@ -2,10 +2,13 @@ use crate::ast::{AstStatement, SourceRange};
 use crate::index::const_expressions::ConstExpression;
 use crate::index::Index;
 
-use crate::resolver::const_evaluator::{evaluate_constants, UnresolvableConstant};
+use crate::resolver::const_evaluator::{
+    evaluate_constants, evaluate_constants_with_overflow, UnresolvableConstant,
+};
 use crate::resolver::AnnotationMap;
 use crate::test_utils::tests::{annotate, index};
 use crate::typesystem::DataTypeInformation;
+use crate::OverflowMode;
 
 const EMPTY: Vec<UnresolvableConstant> = vec![];
 
@@ -504,6 +507,176 @@ fn const_references_to_int_multiplications_compile_time_evaluation() {
     );
 }
 
+#[test]
+fn exponentiations_are_evaluated_at_compile_time() {
+    // GIVEN some exponentiations used as initializers
+    let (_, index) = index(
+        "VAR_GLOBAL CONSTANT
+            a : DINT := 2 ** 10;
+            b : DINT := EXPT(3, 2) ** 2;
+            c : SINT := 3 ** 5;
+            d : DINT := 2 ** -1;
+            e : DINT := -1 ** -3;
+            f : LREAL := EXPT(2.0, 3);
+            g : LREAL := 4 ** 0.5;
+        END_VAR
+        ",
+    );
+
+    // WHEN compile-time evaluation is applied
+    let (index, unresolvable) = evaluate_constants(index);
+
+    // THEN integers are raised by repeated multiplication and reals by powf
+    debug_assert_eq!(EMPTY, unresolvable);
+    debug_assert_eq!(
+        &create_int_literal(1024),
+        find_connstant_value(&index, "a").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(81),
+        find_connstant_value(&index, "b").unwrap()
+    );
+    // 243 wraps around like it does at runtime
+    debug_assert_eq!(
+        &create_int_literal(-13),
+        find_connstant_value(&index, "c").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(0),
+        find_connstant_value(&index, "d").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(-1),
+        find_connstant_value(&index, "e").unwrap()
+    );
+    debug_assert_eq!(
+        &create_real_literal(8.0),
+        find_connstant_value(&index, "f").unwrap()
+    );
+    debug_assert_eq!(
+        &create_real_literal(2.0),
+        find_connstant_value(&index, "g").unwrap()
+    );
+}
+
+#[test]
+fn overflowing_int_arithmetic_follows_the_overflow_mode() {
+    let src = "
+        VAR_GLOBAL CONSTANT
+            a : INT := 32767 + 1;
+            b : USINT := 10 - 11;
+            c : SINT := -100 * 2;
+            d : INT := 32766 + 1;
+            e : INT := -1;
+        END_VAR
+        ";
+    let evaluate = |overflow| evaluate_constants_with_overflow(index(src).1, overflow);
+
+    // WHEN the constants are evaluated with wrapping arithmetic
+    let (index, unresolvable) = evaluate(OverflowMode::Wrap);
+    // THEN the results wrap around
+    debug_assert_eq!(EMPTY, unresolvable);
+    debug_assert_eq!(
+        &create_int_literal(-32768),
+        find_connstant_value(&index, "a").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(255),
+        find_connstant_value(&index, "b").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(56),
+        find_connstant_value(&index, "c").unwrap()
+    );
+
+    // WHEN the constants are evaluated with saturating arithmetic
+    let (index, unresolvable) = evaluate(OverflowMode::Saturate);
+    // THEN the results are limited to the bounds of their types
+    debug_assert_eq!(EMPTY, unresolvable);
+    debug_assert_eq!(
+        &create_int_literal(32767),
+        find_connstant_value(&index, "a").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(0),
+        find_connstant_value(&index, "b").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(-128),
+        find_connstant_value(&index, "c").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(32767),
+        find_connstant_value(&index, "d").unwrap()
+    );
+
+    // WHEN the constants are evaluated with trapping arithmetic
+    let (index, unresolvable) = evaluate(OverflowMode::Trap);
+    // THEN the overflowing results are no constants
+    assert_eq!(
+        vec![
+            UnresolvableConstant::new(global!(index, "a"), "Overflow: 32768 does not fit into INT"),
+            UnresolvableConstant::new(global!(index, "b"), "Overflow: -1 does not fit into USINT"),
+            UnresolvableConstant::new(global!(index, "c"), "Overflow: -200 does not fit into SINT"),
+        ],
+        unresolvable
+    );
+    // AND literals are no arithmetic
+    debug_assert_eq!(
+        &create_int_literal(-1),
+        find_connstant_value(&index, "e").unwrap()
+    );
+}
+
+#[test]
+fn numeric_functions_are_evaluated_at_compile_time() {
+    // GIVEN some numeric functions used as initializers
+    let (_, index) = index(
+        "VAR_GLOBAL CONSTANT
+            a : DINT := ABS(-5);
+            b : LREAL := ABS(-2.5);
+            c : LREAL := SQRT(16);
+            d : LREAL := LOG(1000.0);
+            e : LREAL := ATAN2(1.0, 1.0) * 4;
+            f : LREAL := SQRT(-1.0);
+        END_VAR
+        ",
+    );
+
+    // WHEN compile-time evaluation is applied
+    let (index, unresolvable) = evaluate_constants(index);
+
+    // THEN the functions are evaluated like the generated code does
+    debug_assert_eq!(
+        &create_int_literal(5),
+        find_connstant_value(&index, "a").unwrap()
+    );
+    debug_assert_eq!(
+        &create_real_literal(2.5),
+        find_connstant_value(&index, "b").unwrap()
+    );
+    debug_assert_eq!(
+        &create_real_literal(4.0),
+        find_connstant_value(&index, "c").unwrap()
+    );
+    debug_assert_eq!(
+        &create_real_literal(3.0),
+        find_connstant_value(&index, "d").unwrap()
+    );
+    debug_assert_eq!(
+        &create_real_literal(std::f64::consts::PI),
+        find_connstant_value(&index, "e").unwrap()
+    );
+    // AND the square root of a negative number is no constant
+    assert_eq!(
+        vec![UnresolvableConstant::new(
+            global!(index, "f"),
+            "Cannot evaluate SQRT([-1.0]), the result is no finite number"
+        )],
+        unresolvable
+    );
+}
+
 #[test]
 fn const_references_to_int_division_compile_time_evaluation() {
     // GIVEN some INT index used as initializers
@@ -887,6 +1060,338 @@ fn const_references_int_bit_functions_behavior_evaluation() {
         find_connstant_value(&index, "e").unwrap()
     );
 }
+#[test]
+fn typed_real_literals_are_resolved_with_the_precision_of_their_type() {
+    // GIVEN typed real literals used as initializers
+    let (_, index) = index(
+        "VAR_GLOBAL CONSTANT
+            a : LREAL := REAL#0.1;
+            b : LREAL := LREAL#0.1;
+            c : REAL := REAL#7;
+            d : LREAL := LREAL#2 * 2;
+        END_VAR
+       ",
+    );
+
+    // WHEN compile-time evaluation is applied
+    let (index, unresolvable) = evaluate_constants(index);
+
+    // THEN everything got resolved
+    debug_assert_eq!(EMPTY, unresolvable);
+    // AND the literals have the precision of their stated type
+    debug_assert_eq!(
+        &create_real_literal(0.1_f32 as f64),
+        find_connstant_value(&index, "a").unwrap()
+    );
+    debug_assert_eq!(
+        &create_real_literal(0.1),
+        find_connstant_value(&index, "b").unwrap()
+    );
+    debug_assert_eq!(
+        &create_real_literal(7.0),
+        find_connstant_value(&index, "c").unwrap()
+    );
+    debug_assert_eq!(
+        &create_real_literal(4.0),
+        find_connstant_value(&index, "d").unwrap()
+    );
+}
+
+#[test]
+fn selection_functions_with_constant_arguments_are_resolved() {
+    // GIVEN selection functions with constant arguments used as initializers
+    let (_, index) = index(
+        "VAR_GLOBAL CONSTANT
+            a : INT := MAX(1, 7, 3);
+            b : INT := MIN(4, -2 * 3, 3);
+            c : INT := LIMIT(0, 120, 100);
+            d : REAL := MAX(1, 2.5);
+            e : INT := SEL(TRUE, 1, 2);
+            f : INT := MUX(2, 10, 20, 30);
+            g : INT := MUX(3, 10, 20, 30);
+            h : INT := c + MIN(a, 5);
+        END_VAR
+       ",
+    );
+
+    // WHEN compile-time evaluation is applied
+    let (index, unresolvable) = evaluate_constants(index);
+
+    // THEN everything but the MUX with a selector out of range got resolved
+    debug_assert_eq!(
+        vec![UnresolvableConstant::new(
+            global!(index, "g"),
+            "MUX selector 3 is out of range"
+        )],
+        unresolvable
+    );
+    debug_assert_eq!(
+        &create_int_literal(7),
+        find_connstant_value(&index, "a").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(-6),
+        find_connstant_value(&index, "b").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(100),
+        find_connstant_value(&index, "c").unwrap()
+    );
+    debug_assert_eq!(
+        &create_real_literal(2.5),
+        find_connstant_value(&index, "d").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(2),
+        find_connstant_value(&index, "e").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(30),
+        find_connstant_value(&index, "f").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(105),
+        find_connstant_value(&index, "h").unwrap()
+    );
+}
+
+#[test]
+fn bit_shift_functions_with_constant_arguments_are_resolved() {
+    // GIVEN bit-shift functions with constant arguments used as initializers
+    let (_, index) = index(
+        "VAR_GLOBAL CONSTANT
+            a : WORD := SHL(WORD#1, 4);
+            b : WORD := SHL(WORD#16#8001, 1);
+            c : BYTE := ROL(BYTE#16#81, 1);
+            d : BYTE := ROR(BYTE#16#81, 1);
+            e : INT := SHL(INT#1, 15);
+            f : DINT := SHR(256, 4);
+            g : WORD := SHL(a, 16);
+            h : DINT := ROL(1, 1);
+        END_VAR
+       ",
+    );
+
+    // WHEN compile-time evaluation is applied
+    let (index, unresolvable) = evaluate_constants(index);
+
+    // THEN everything but the rotation of an untyped value got resolved
+    debug_assert_eq!(
+        vec![UnresolvableConstant::new(
+            global!(index, "h"),
+            "Cannot evaluate ROL of a value without a type"
+        )],
+        unresolvable
+    );
+    debug_assert_eq!(
+        &create_int_literal(16),
+        find_connstant_value(&index, "a").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(2),
+        find_connstant_value(&index, "b").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(3),
+        find_connstant_value(&index, "c").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(192),
+        find_connstant_value(&index, "d").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(-32768),
+        find_connstant_value(&index, "e").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(16),
+        find_connstant_value(&index, "f").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(0),
+        find_connstant_value(&index, "g").unwrap()
+    );
+}
+
+#[test]
+fn sizeof_with_types_and_variables_is_resolved() {
+    // GIVEN SIZEOF calls on types and variables used as initializers
+    let (_, index) = index(
+        "TYPE MyStruct : STRUCT
+            a : BYTE;
+            b : LINT;
+            c : BOOL;
+        END_STRUCT END_TYPE
+
+        TYPE MyUnion : UNION
+            a : BYTE;
+            b : ARRAY[0..2] OF INT;
+        END_UNION END_TYPE
+
+        VAR_GLOBAL
+            arr : ARRAY[1..5] OF MyStruct;
+            str : WSTRING[9];
+        END_VAR
+
+        VAR_GLOBAL CONSTANT
+            a : ULINT := SIZEOF(INT);
+            b : ULINT := SIZEOF(MyStruct);
+            c : ULINT := SIZEOF(MyUnion);
+            d : ULINT := SIZEOF(arr);
+            e : ULINT := SIZEOF(str);
+            f : ULINT := SIZEOF(a) + 1;
+            g : ULINT := SIZEOF(unknown);
+        END_VAR
+       ",
+    );
+
+    // WHEN compile-time evaluation is applied
+    let (index, unresolvable) = evaluate_constants(index);
+
+    // THEN everything but the unknown argument got resolved
+    debug_assert_eq!(
+        vec![UnresolvableConstant::new(
+            global!(index, "g"),
+            "Cannot resolve the argument of SIZEOF, expected a variable or a type"
+        )],
+        unresolvable
+    );
+    debug_assert_eq!(
+        &create_int_literal(2),
+        find_connstant_value(&index, "a").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(24),
+        find_connstant_value(&index, "b").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(6),
+        find_connstant_value(&index, "c").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(120),
+        find_connstant_value(&index, "d").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(20),
+        find_connstant_value(&index, "e").unwrap()
+    );
+    debug_assert_eq!(
+        &create_int_literal(9),
+        find_connstant_value(&index, "f").unwrap()
+    );
+}
+
+#[test]
+fn array_and_struct_initializers_with_constants_are_resolved() {
+    // GIVEN array- and struct-initializers that use constants
+    let (_, index) = index(
+        "TYPE Point : STRUCT
+            x : DINT;
+            y : DINT;
+        END_STRUCT END_TYPE
+
+        VAR_GLOBAL CONSTANT
+            c : DINT := 3;
+        END_VAR
+
+        VAR_GLOBAL
+            arr : ARRAY[0..3] OF DINT := [2(c), c + 1, 5];
+            p : Point := (x := c, y := c * 2);
+            q : Point := (y := c);
+        END_VAR
+       ",
+    );
+
+    // WHEN compile-time evaluation is applied
+    let (index, unresolvable) = evaluate_constants(index);
+
+    // THEN all initializers got resolved
+    debug_assert_eq!(EMPTY, unresolvable);
+    let member = |name: &str| AstStatement::Reference {
+        name: name.to_string(),
+        id: 0,
+        location: SourceRange::undefined(),
+    };
+    let assignment = |name: &str, value: i128| AstStatement::Assignment {
+        left: Box::new(member(name)),
+        right: Box::new(create_int_literal(value)),
+        id: 0,
+    };
+    debug_assert_eq!(
+        &AstStatement::LiteralArray {
+            elements: Some(Box::new(AstStatement::ExpressionList {
+                expressions: vec![
+                    AstStatement::MultipliedStatement {
+                        multiplier: 2,
+                        element: Box::new(create_int_literal(3)),
+                        id: 0,
+                        location: SourceRange::undefined(),
+                    },
+                    create_int_literal(4),
+                    create_int_literal(5),
+                ],
+                id: 0,
+            })),
+            id: 0,
+            location: SourceRange::undefined(),
+        },
+        find_connstant_value(&index, "arr").unwrap()
+    );
+    debug_assert_eq!(
+        &AstStatement::ExpressionList {
+            expressions: vec![assignment("x", 3), assignment("y", 6)],
+            id: 0,
+        },
+        find_connstant_value(&index, "p").unwrap()
+    );
+    debug_assert_eq!(
+        &assignment("y", 3),
+        find_connstant_value(&index, "q").unwrap()
+    );
+}
+
+#[test]
+fn signed_integer_constants_wrap_around_like_at_runtime() {
+    // GIVEN signed constants exceeding the range of their type
+    let (_, index) = index(
+        "VAR_GLOBAL CONSTANT
+            lint_max : LINT := 9223372036854775807;
+            lint_overflow : LINT := lint_max + 1;
+            lint_minus_one : LINT := 16#FFFF_FFFF_FFFF_FFFF;
+            int_overflow : INT := 32767 + 2;
+            in_range : DINT := -5;
+            lword_max : LWORD := 16#FFFF_FFFF_FFFF_FFFF;
+        END_VAR",
+    );
+
+    // WHEN compile-time evaluation is applied
+    let (index, unresolvable) = evaluate_constants(index);
+
+    // THEN the values wrap around like the 64-bit (or 16-bit) arithmetic at runtime
+    debug_assert_eq!(EMPTY, unresolvable);
+    debug_assert_eq!(
+        find_connstant_value(&index, "lint_overflow"),
+        Some(&create_int_literal(i64::MIN as i128))
+    );
+    debug_assert_eq!(
+        find_connstant_value(&index, "lint_minus_one"),
+        Some(&create_int_literal(-1))
+    );
+    debug_assert_eq!(
+        find_connstant_value(&index, "int_overflow"),
+        Some(&create_int_literal(-32767))
+    );
+    debug_assert_eq!(
+        find_connstant_value(&index, "in_range"),
+        Some(&create_int_literal(-5))
+    );
+    debug_assert_eq!(
+        find_connstant_value(&index, "lword_max"),
+        Some(&create_int_literal(u64::MAX as i128))
+    );
+}
+
 #[test]
 fn illegal_cast_should_not_be_resolved() {
     // GIVEN some bit-functions used as initializers
@@ -1062,27 +1567,87 @@ fn const_references_to_bool_compile_time_evaluation() {
 }
 
 #[test]
-fn not_evaluatable_consts_are_reported() {
-    // GIVEN some BOOL index used as initializers
+fn uninitialized_consts_are_evaluated_with_their_default_value() {
+    // GIVEN constants referencing constants without an initializer
     let (_, index) = index(
-        "VAR_GLOBAL CONSTANT
+        "TYPE MyInt : INT := 7; END_TYPE
+        TYPE MyOtherInt : MyInt; END_TYPE
+
+        VAR_GLOBAL CONSTANT
             a : INT := 1;
             b : INT := a;
             c : INT;
             d : INT := c;
+            e : BOOL;
+            f : BOOL := e;
+            g : REAL;
+            h : REAL := g;
+            i : STRING;
+            j : STRING := i;
+            k : MyOtherInt;
+            l : INT := k + 1;
+        END_VAR",
+    );
+
+    // WHEN compile-time evaluation is applied
+    let (index, unresolvable) = evaluate_constants(index);
+
+    // THEN the uninitialized constants resolve to their type's default value
+    debug_assert_eq!(EMPTY, unresolvable);
+    debug_assert_eq!(
+        find_connstant_value(&index, "d"),
+        Some(&create_int_literal(0))
+    );
+    debug_assert_eq!(
+        find_connstant_value(&index, "f"),
+        Some(&create_bool_literal(false))
+    );
+    debug_assert_eq!(
+        find_connstant_value(&index, "h"),
+        Some(&AstStatement::LiteralReal {
+            value: "0.0".to_string(),
+            id: 0,
+            location: SourceRange::undefined(),
+        })
+    );
+    debug_assert_eq!(
+        find_connstant_value(&index, "j"),
+        Some(&create_string_literal("", false))
+    );
+    debug_assert_eq!(
+        find_connstant_value(&index, "l"),
+        Some(&create_int_literal(8))
+    );
+}
+
+#[test]
+fn consts_referencing_unknown_references_are_reported() {
+    // GIVEN constants referencing an unknown constant
+    let (_, index) = index(
+        "VAR_GLOBAL CONSTANT
+            a : INT := unknown + 1;
+            b : INT := -unknown;
         END_VAR",
     );
 
     // WHEN compile-time evaluation is applied
     let (index, unresolvable) = evaluate_constants(index);
 
-    // THEN d cannot be evaluated, c was not attempted, there is no const-expression
+    // THEN both are reported with the unknown reference and marked as unresolvable
     debug_assert_eq!(
-        vec![UnresolvableConstant::incomplete_initialzation(&global!(
-            index, "d"
-        )),],
+        vec![
+            UnresolvableConstant::unknown_reference(&global!(index, "a"), "unknown"),
+            UnresolvableConstant::unknown_reference(&global!(index, "b"), "unknown"),
+        ],
         unresolvable
     );
+    assert!(matches!(
+        index
+            .get_const_expressions()
+            .find_const_expression(&global!(index, "a")),
+        Some(ConstExpression::Unresolvable { reason, .. })
+            if reason == "Cannot resolve unknown reference 'unknown'"
+    ));
 }
 
 #[test]
@@ -1103,13 +1668,14 @@ fn evaluating_constants_can_handle_recursion() {
     // WHEN compile-time evaluation is applied
     let (index, unresolvable) = evaluate_constants(index);
 
-    // THEN a,b,c,d could not be resolved (ciruclar dependency)
+    // THEN a,b,c,d could not be resolved (ciruclar dependency), a and d are reported as part
+    // of the cycle
     debug_assert_eq!(
         vec![
-            UnresolvableConstant::incomplete_initialzation(&global!(index, "a")),
+            UnresolvableConstant::cyclic_dependency(&global!(index, "a"), &["a", "d", "a"]),
             UnresolvableConstant::incomplete_initialzation(&global!(index, "b")),
             UnresolvableConstant::incomplete_initialzation(&global!(index, "c")),
-            UnresolvableConstant::incomplete_initialzation(&global!(index, "d")),
+            UnresolvableConstant::cyclic_dependency(&global!(index, "d"), &["d", "a", "d"]),
         ],
         unresolvable
     );
@@ -6,8 +6,9 @@ use crate::{
     resolver::{AnnotationMap, AnnotationMapImpl, StatementAnnotation},
     test_utils::tests::annotate,
     typesystem::{
-        DataTypeInformation, BOOL_TYPE, BYTE_TYPE, DINT_TYPE, DWORD_TYPE, INT_TYPE, REAL_TYPE,
-        SINT_TYPE, UINT_TYPE, USINT_TYPE, VOID_TYPE,
+        self, DataTypeInformation, BOOL_TYPE, BYTE_TYPE, CHAR_TYPE, DINT_TYPE, DWORD_TYPE,
+        INT_TYPE, LINT_TYPE, LREAL_TYPE, REAL_TYPE, SINT_TYPE, UDINT_TYPE, UINT_TYPE, ULINT_TYPE,
+        USINT_TYPE, VOID_TYPE, WORD_TYPE,
     },
 };
 
@@ -50,6 +51,129 @@ fn binary_expressions_resolves_types() {
     assert_eq!(expected_types, types);
 }
 
+#[test]
+fn exponentiations_resolve_to_the_bigger_type_of_their_operands() {
+    let (unit, mut index) = index(
+        "PROGRAM PRG
+            VAR i : INT; r : REAL; l : LREAL; END_VAR
+            i ** 2;
+            r ** i;
+            EXPT(l, r);
+        END_PROGRAM",
+    );
+    let annotations = annotate(&unit, &mut index);
+    let statements = &unit.implementations[0].statements;
+
+    let expected_types = vec!["DINT", "REAL", "LREAL"];
+
+    let types: Vec<&str> = statements
+        .iter()
+        .map(|s| annotations.get_type_or_void(s, &index).get_name())
+        .collect();
+
+    assert_eq!(expected_types, types);
+    if let AstStatement::BinaryExpression { left, right, .. } = &statements[1] {
+        assert_type_and_hint!(&annotations, &index, left, REAL_TYPE, None);
+        assert_type_and_hint!(&annotations, &index, right, INT_TYPE, Some(REAL_TYPE));
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn expt_calls_with_named_arguments_resolve_to_the_bigger_type_of_their_arguments() {
+    let (unit, mut index) = index(
+        "PROGRAM PRG
+            VAR i : INT; r : REAL; END_VAR
+            EXPT(IN1 := i, IN2 := r);
+            EXPT(IN2 := i, IN1 := 2);
+        END_PROGRAM",
+    );
+    let annotations = annotate(&unit, &mut index);
+    let statements = &unit.implementations[0].statements;
+
+    let types: Vec<&str> = statements
+        .iter()
+        .map(|s| annotations.get_type_or_void(s, &index).get_name())
+        .collect();
+    assert_eq!(vec!["REAL", "DINT"], types);
+
+    if let AstStatement::CallStatement { parameters, .. } = &statements[0] {
+        let (base, exponent) = typesystem::get_exponent_arguments(parameters).unwrap();
+        assert_type_and_hint!(&annotations, &index, base, INT_TYPE, Some(REAL_TYPE));
+        assert_type_and_hint!(&annotations, &index, exponent, REAL_TYPE, None);
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn a_user_defined_expt_function_is_called_instead_of_the_exponentiation() {
+    //GIVEN a user-defined function named EXPT
+    let (unit, mut index) = index(
+        "FUNCTION EXPT : INT
+            VAR_INPUT IN1, IN2 : INT; END_VAR
+        END_FUNCTION
+
+        PROGRAM PRG
+            VAR r : REAL; END_VAR
+            EXPT(r, 2);
+        END_PROGRAM",
+    );
+
+    //WHEN the AST is annotated
+    let annotations = annotate(&unit, &mut index);
+    let call = &unit.implementations[1].statements[0];
+
+    //THEN the call resolves to the user's function
+    assert_eq!("INT", annotations.get_type_or_void(call, &index).get_name());
+    if let AstStatement::CallStatement { operator, .. } = call {
+        assert_eq!(
+            Some(&StatementAnnotation::Function {
+                return_type: "INT".to_string(),
+                qualified_name: "EXPT".to_string(),
+            }),
+            annotations.get(operator)
+        );
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn numeric_functions_resolve_to_a_real_type_or_the_type_of_abs_argument() {
+    let (unit, mut index) = index(
+        "PROGRAM PRG
+            VAR s : SINT; i : INT; li : LINT; r : REAL; l : LREAL; END_VAR
+            ABS(s);
+            ABS(-5);
+            ABS(l);
+            SQRT(i);
+            SIN(li);
+            LN(r);
+            ATAN2(r, l);
+        END_PROGRAM",
+    );
+    let annotations = annotate(&unit, &mut index);
+    let statements = &unit.implementations[0].statements;
+
+    let expected_types = vec!["SINT", "DINT", "LREAL", "REAL", "LREAL", "REAL", "LREAL"];
+
+    let types: Vec<&str> = statements
+        .iter()
+        .map(|s| annotations.get_type_or_void(s, &index).get_name())
+        .collect();
+
+    assert_eq!(expected_types, types);
+    //integer arguments are converted to the resulting real
+    if let AstStatement::CallStatement { parameters, .. } = &statements[3] {
+        let argument = parameters.as_ref().as_ref().unwrap();
+        assert_type_and_hint!(&annotations, &index, argument, INT_TYPE, Some(REAL_TYPE));
+    } else {
+        unreachable!()
+    }
+}
+
 #[test]
 fn binary_expressions_resolves_types_for_mixed_signed_ints() {
     let (unit, mut index) = index(
@@ -70,6 +194,42 @@ fn binary_expressions_resolves_types_for_mixed_signed_ints() {
     }
 }
 
+#[test]
+fn binary_expressions_promote_mixed_signed_variables_to_a_type_holding_both() {
+    let (unit, mut index) = index(
+        "PROGRAM PRG
+            VAR a : DINT; b : UDINT; c : ULINT; END_VAR
+            a + b;
+            b - a;
+            a < b;
+            b + 7;
+            a + c;
+        END_PROGRAM",
+    );
+    let annotations = annotate(&unit, &mut index);
+    let statements = &unit.implementations[0].statements;
+
+    if let AstStatement::BinaryExpression { left, right, .. } = &statements[0] {
+        assert_type_and_hint!(&annotations, &index, left, DINT_TYPE, Some(LINT_TYPE));
+        assert_type_and_hint!(&annotations, &index, right, UDINT_TYPE, Some(LINT_TYPE));
+        assert_type_and_hint!(&annotations, &index, &statements[0], LINT_TYPE, None);
+    } else {
+        unreachable!()
+    }
+    assert_type_and_hint!(&annotations, &index, &statements[1], LINT_TYPE, None);
+    if let AstStatement::BinaryExpression { left, right, .. } = &statements[2] {
+        assert_type_and_hint!(&annotations, &index, left, DINT_TYPE, Some(LINT_TYPE));
+        assert_type_and_hint!(&annotations, &index, right, UDINT_TYPE, Some(LINT_TYPE));
+        assert_type_and_hint!(&annotations, &index, &statements[2], BOOL_TYPE, None);
+    } else {
+        unreachable!()
+    }
+    //literals do not widen the expression
+    assert_type_and_hint!(&annotations, &index, &statements[3], DINT_TYPE, None);
+    //there is no signed type bigger than ULINT
+    assert_type_and_hint!(&annotations, &index, &statements[4], ULINT_TYPE, None);
+}
+
 #[test]
 fn binary_expressions_resolves_types_for_literals_directly() {
     let (unit, mut index) = index(
@@ -2522,6 +2682,58 @@ fn class_method_gets_annotated() {
     assert_type_and_hint!(&annotations, &index, &body[3], "SINT", None);
 }
 
+#[test]
+fn this_resolves_to_the_instance_of_the_current_class_or_function_block() {
+    //a class with a method and a function block both referencing THIS
+    let (unit, mut index) = index(
+        "
+    CLASS MyClass
+        VAR
+            x : BYTE;
+        END_VAR
+
+        METHOD testMethod
+            THIS;
+            THIS.x;
+        END_METHOD
+    END_CLASS
+
+    FUNCTION_BLOCK fb
+        VAR
+            y : SINT;
+        END_VAR
+        this.y;
+    END_FUNCTION_BLOCK
+
+    FUNCTION foo : INT
+        THIS;
+    END_FUNCTION
+        ",
+    );
+
+    // WHEN this code is annotated
+    let annotations = annotate(&unit, &mut index);
+    let method_body = &unit.implementations[0].statements;
+    let fb_body = &unit.implementations[1].statements;
+    let function_body = &unit.implementations[2].statements;
+
+    // THEN THIS references the class' instance in the method
+    assert_eq!(
+        annotations.get(&method_body[0]),
+        Some(&StatementAnnotation::Variable {
+            resulting_type: "MyClass".into(),
+            qualified_name: "MyClass.THIS".into(),
+            constant: false,
+            is_auto_deref: false,
+        })
+    );
+    assert_type_and_hint!(&annotations, &index, &method_body[1], "BYTE", None);
+    // AND the function block's instance in the function block's body
+    assert_type_and_hint!(&annotations, &index, &fb_body[0], "SINT", None);
+    // AND nothing in a function
+    assert_eq!(annotations.get(&function_body[0]), None);
+}
+
 #[test]
 fn nested_bitwise_access_resolves_correctly() {
     let (unit, mut index) = index(
@@ -2643,6 +2855,111 @@ fn array_accessor_in_struct_array_is_annotated() {
     }
 }
 
+#[test]
+fn every_segment_of_nested_member_and_array_accesses_is_annotated() {
+    let (unit, mut index) = index(
+        r#"
+        TYPE Status : STRUCT bits : WORD; END_STRUCT END_TYPE
+        TYPE Motor : STRUCT status : Status; END_STRUCT END_TYPE
+        TYPE Conveyor : STRUCT motors : ARRAY[0..3] OF Motor; END_STRUCT END_TYPE
+
+        PROGRAM main
+        VAR
+            conveyor : Conveyor;
+            grid : ARRAY[0..1] OF ARRAY[0..1] OF Conveyor;
+            i : INT;
+        END_VAR
+
+        conveyor.motors[i].status.bits.3;
+        grid[1][i].motors[conveyor.motors[0].status.bits].status;
+        END_PROGRAM
+        "#,
+    );
+
+    // WHEN this code is annotated
+    let annotations = annotate(&unit, &mut index);
+    let statements = &unit.implementations[0].statements;
+
+    // THEN every segment is annotated with its type
+    assert_type_and_hint!(&annotations, &index, &statements[0], BOOL_TYPE, None);
+    if let AstStatement::QualifiedReference { elements, .. } = &statements[0] {
+        assert_type_and_hint!(&annotations, &index, &elements[0], "Conveyor", None);
+        assert_type_and_hint!(&annotations, &index, &elements[1], "Motor", None);
+        if let AstStatement::ArrayAccess {
+            reference, access, ..
+        } = &elements[1]
+        {
+            assert_type_and_hint!(
+                &annotations,
+                &index,
+                reference.as_ref(),
+                "__Conveyor_motors",
+                None
+            );
+            assert_type_and_hint!(&annotations, &index, access.as_ref(), INT_TYPE, None);
+        } else {
+            unreachable!()
+        }
+        assert_type_and_hint!(&annotations, &index, &elements[2], "Status", None);
+        assert_type_and_hint!(&annotations, &index, &elements[3], WORD_TYPE, None);
+        assert_type_and_hint!(&annotations, &index, &elements[4], BOOL_TYPE, None);
+    } else {
+        unreachable!()
+    }
+
+    // AND nested array accesses and qualified indexes are resolved in their own context
+    assert_type_and_hint!(&annotations, &index, &statements[1], "Status", None);
+    if let AstStatement::QualifiedReference { elements, .. } = &statements[1] {
+        assert_type_and_hint!(&annotations, &index, &elements[0], "Conveyor", None);
+        assert_type_and_hint!(&annotations, &index, &elements[1], "Motor", None);
+        if let AstStatement::ArrayAccess { access, .. } = &elements[1] {
+            assert_type_and_hint!(&annotations, &index, access.as_ref(), WORD_TYPE, None);
+        } else {
+            unreachable!()
+        }
+    } else {
+        unreachable!()
+    }
+}
+
+#[test]
+fn const_flag_is_calculated_when_resolving_through_array_accesses() {
+    let (unit, index) = index(
+        "
+        TYPE Motor : STRUCT speed : INT; END_STRUCT END_TYPE
+
+        PROGRAM PRG
+            VAR
+                motors : ARRAY[0..1] OF Motor;
+            END_VAR
+            VAR CONSTANT
+                cmotors : ARRAY[0..1] OF Motor;
+                cgrid : ARRAY[0..1] OF ARRAY[0..1] OF Motor;
+            END_VAR
+
+            motors[0].speed;
+            cmotors[0].speed;
+            cgrid[0][1].speed;
+        END_PROGRAM",
+    );
+
+    let (annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+    let statements = &unit.implementations[0].statements;
+
+    let actual_consts: Vec<bool> = statements
+        .iter()
+        .map(|s| {
+            if let Some(StatementAnnotation::Variable { constant, .. }) = annotations.get(s) {
+                *constant
+            } else {
+                unreachable!()
+            }
+        })
+        .collect();
+
+    assert_eq!(vec![false, true, true], actual_consts);
+}
+
 #[test]
 fn type_hint_should_not_hint_to_the_effective_type_but_to_the_original() {
     //GIVEN a aliased type to INT and a variable declared as myInt
@@ -3047,3 +3364,388 @@ fn and_statement_of_dints_results_in_dint() {
         None
     );
 }
+
+#[test]
+fn date_and_time_arithmetic_resolves_to_iec_result_types() {
+    //GIVEN arithmetic operations on date and time values
+    let (unit, index) = index(
+        "PROGRAM PRG
+            VAR
+                t : TIME;
+                d : DATE;
+                dt : DT;
+                tod : TOD;
+                i : INT;
+            END_VAR
+            t + t;
+            tod + t;
+            t + tod;
+            dt - t;
+            t + dt;
+            dt - dt;
+            tod - tod;
+            d - d;
+            t * i;
+            2 * t;
+            t / 2;
+            dt < dt;
+        END_PROGRAM",
+    );
+
+    //WHEN the AST is annotated
+    let (annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+    let statements = &unit.implementations[0].statements;
+
+    //THEN the results are typed as defined by IEC 61131-3
+    let types: Vec<&str> = statements
+        .iter()
+        .map(|s| annotations.get_type_or_void(s, &index).get_name())
+        .collect();
+    assert_eq!(
+        vec![
+            "TIME",
+            "TIME_OF_DAY",
+            "TIME_OF_DAY",
+            "DATE_AND_TIME",
+            "DATE_AND_TIME",
+            "TIME",
+            "TIME",
+            "TIME",
+            "TIME",
+            "TIME",
+            "TIME",
+            "BOOL"
+        ],
+        types
+    );
+
+    //AND integer operands are widened to the size of a TIME
+    if let AstStatement::BinaryExpression { right, .. } = &statements[8] {
+        assert_type_and_hint!(&annotations, &index, right, INT_TYPE, Some("TIME"));
+    } else {
+        unreachable!();
+    }
+}
+
+#[test]
+fn date_and_time_conversions_resolve_to_their_target_type() {
+    //GIVEN calls to conversion functions between date and time types
+    let (unit, index) = index(
+        "PROGRAM PRG
+            VAR
+                dt : DT;
+                t : TIME;
+            END_VAR
+            DT_TO_DATE(dt);
+            dt_to_tod(dt);
+            TIME_TO_DINT(t);
+            INT_TO_TIME(7);
+        END_PROGRAM",
+    );
+
+    //WHEN the AST is annotated
+    let (annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+    let statements = &unit.implementations[0].statements;
+
+    //THEN the calls result in the target type
+    let types: Vec<&str> = statements
+        .iter()
+        .map(|s| annotations.get_type_or_void(s, &index).get_name())
+        .collect();
+    assert_eq!(vec!["DATE", "TIME_OF_DAY", "DINT", "TIME"], types);
+
+    //AND the arguments are expected to be of the source type
+    if let AstStatement::CallStatement {
+        parameters: Some(argument),
+        ..
+    } = &statements[3]
+    {
+        assert_type_and_hint!(&annotations, &index, argument, DINT_TYPE, Some(INT_TYPE));
+    } else {
+        unreachable!();
+    }
+}
+
+#[test]
+fn elementary_conversions_resolve_to_their_target_type() {
+    //GIVEN calls to conversion functions between numeric and bit types
+    let (unit, index) = index(
+        "PROGRAM PRG
+            VAR
+                i : INT;
+                r : REAL;
+            END_VAR
+            INT_TO_REAL(i);
+            real_to_int(r);
+            LREAL_TO_BOOL(r);
+            BOOL_TO_WORD(TRUE);
+            REAL_TRUNC_SINT(r);
+            TRUNC(r);
+            REAL_TO_TIME(r);
+            STRING_TO_INT(i);
+        END_PROGRAM",
+    );
+
+    //WHEN the AST is annotated
+    let (annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+    let statements = &unit.implementations[0].statements;
+
+    //THEN the calls result in the target type, reals cannot be converted to times
+    let types: Vec<&str> = statements
+        .iter()
+        .map(|s| annotations.get_type_or_void(s, &index).get_name())
+        .collect();
+    assert_eq!(
+        vec!["REAL", "INT", "BOOL", "WORD", "SINT", "DINT", "VOID", "VOID"],
+        types
+    );
+
+    //AND TRUNC expects an LREAL
+    if let AstStatement::CallStatement {
+        parameters: Some(argument),
+        ..
+    } = &statements[5]
+    {
+        assert_type_and_hint!(&annotations, &index, argument, REAL_TYPE, Some(LREAL_TYPE));
+    } else {
+        unreachable!();
+    }
+}
+
+#[test]
+fn character_conversions_and_comparisons_resolve_to_their_target_type() {
+    //GIVEN conversions and comparisons of characters
+    let (unit, mut index) = index(
+        "PROGRAM PRG
+            VAR
+                c : CHAR;
+                wc : WCHAR;
+                s : STRING;
+            END_VAR
+            CHAR_TO_STRING(c);
+            STRING_TO_CHAR(s);
+            WCHAR_TO_WSTRING(wc);
+            WSTRING_TO_WCHAR(\"abc\");
+            CHAR_TO_WSTRING(c);
+            CHAR_TO_INT(c);
+            c = 'A';
+            'B' < c;
+        END_PROGRAM",
+    );
+
+    //WHEN the AST is annotated
+    let (mut annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+    index.import(std::mem::take(&mut annotations.new_index));
+    let statements = &unit.implementations[0].statements;
+
+    //THEN characters convert to strings of the same encoding and back
+    let types: Vec<&str> = statements
+        .iter()
+        .map(|s| annotations.get_type_or_void(s, &index).get_name())
+        .collect();
+    assert_eq!(
+        vec!["STRING", "CHAR", "WSTRING", "WCHAR", "VOID", "INT", "BOOL", "BOOL"],
+        types
+    );
+
+    //AND string-literals compared to a character are character-literals
+    if let AstStatement::BinaryExpression { left, right, .. } = &statements[6] {
+        assert_type_and_hint!(&annotations, &index, left, CHAR_TYPE, None);
+        assert_type_and_hint!(&annotations, &index, right, "__STRING_1", Some(CHAR_TYPE));
+    } else {
+        unreachable!();
+    }
+    if let AstStatement::BinaryExpression { left, .. } = &statements[7] {
+        assert_type_and_hint!(&annotations, &index, left, "__STRING_1", Some(CHAR_TYPE));
+    } else {
+        unreachable!();
+    }
+}
+
+#[test]
+fn selections_resolve_to_the_bigger_type_of_their_inputs() {
+    //GIVEN calls to the selection functions
+    let (unit, index) = index(
+        "PROGRAM PRG
+            VAR
+                b : BYTE;
+                i : INT;
+                r : REAL;
+                g : BOOL;
+            END_VAR
+            SEL(g, b, i);
+            MAX(b, i, r);
+            min(b, b);
+            LIMIT(0, i, 100);
+            MUX(i, b, b, b);
+            MAX();
+        END_PROGRAM",
+    );
+
+    //WHEN the AST is annotated
+    let (annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+    let statements = &unit.implementations[0].statements;
+
+    //THEN the calls result in the bigger type of their inputs
+    let types: Vec<&str> = statements
+        .iter()
+        .map(|s| annotations.get_type_or_void(s, &index).get_name())
+        .collect();
+    assert_eq!(vec!["INT", "REAL", "BYTE", "DINT", "BYTE", "VOID"], types);
+
+    //AND the inputs are expected to be of the resulting type
+    if let AstStatement::CallStatement {
+        parameters: Some(parameters),
+        ..
+    } = &statements[0]
+    {
+        if let [g, b, i] = ast::flatten_expression_list(parameters).as_slice() {
+            assert_type_and_hint!(&annotations, &index, *g, BOOL_TYPE, Some(BOOL_TYPE));
+            assert_type_and_hint!(&annotations, &index, *b, BYTE_TYPE, Some(INT_TYPE));
+            assert_type_and_hint!(&annotations, &index, *i, INT_TYPE, Some(INT_TYPE));
+        } else {
+            unreachable!();
+        }
+    } else {
+        unreachable!();
+    }
+}
+
+#[test]
+fn bit_shifts_resolve_to_the_type_of_the_shifted_value() {
+    //GIVEN calls to the bit-shift functions
+    let (unit, index) = index(
+        "PROGRAM PRG
+            VAR
+                b : BYTE;
+                d : DWORD;
+                i : INT;
+            END_VAR
+            SHL(b, 2);
+            shr(d, i);
+            ROL(i, b);
+            ROR(16#FF, 4);
+            SHL(b);
+        END_PROGRAM",
+    );
+
+    //WHEN the AST is annotated
+    let (annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+    let statements = &unit.implementations[0].statements;
+
+    //THEN the calls result in the type of their first argument
+    let types: Vec<&str> = statements
+        .iter()
+        .map(|s| annotations.get_type_or_void(s, &index).get_name())
+        .collect();
+    assert_eq!(vec!["BYTE", "DWORD", "INT", "DINT", "VOID"], types);
+}
+
+#[test]
+fn long_date_and_time_arithmetic_resolves_to_long_result_types() {
+    //GIVEN operations on long date and time values
+    let (unit, index) = index(
+        "PROGRAM PRG
+            VAR
+                t : TIME;
+                lt : LTIME;
+                ldt : LDT;
+                ltod : LTOD;
+                ld : LDATE;
+            END_VAR
+            lt + t;
+            t + lt;
+            ldt - ldt;
+            ltod - t;
+            ld - ld;
+            lt * 2;
+            LDT_TO_LTOD(ldt);
+            LDT_TO_LDATE(ldt);
+        END_PROGRAM",
+    );
+
+    //WHEN the AST is annotated
+    let (annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+    let statements = &unit.implementations[0].statements;
+
+    //THEN the results are the long variants of the IEC 61131-3 result types
+    let types: Vec<&str> = statements
+        .iter()
+        .map(|s| annotations.get_type_or_void(s, &index).get_name())
+        .collect();
+    assert_eq!(
+        vec![
+            "LTIME",
+            "LTIME",
+            "LTIME",
+            "LTIME_OF_DAY",
+            "LTIME",
+            "LTIME",
+            "LTIME_OF_DAY",
+            "LDATE"
+        ],
+        types
+    );
+}
+
+#[test]
+fn adr_and_ref_calls_resolve_to_a_pointer_to_their_argument() {
+    //GIVEN calls to ADR and REF
+    let (unit, mut index) = index(
+        "PROGRAM PRG
+            VAR
+                x : INT;
+                s : STRING;
+            END_VAR
+            ADR(x);
+            ref(s);
+        END_PROGRAM",
+    );
+
+    //WHEN the AST is annotated
+    let annotations = annotate(&unit, &mut index);
+    let statements = &unit.implementations[0].statements;
+
+    //THEN the calls result in a pointer to the type of their argument
+    let types: Vec<&str> = statements
+        .iter()
+        .map(|s| annotations.get_type_or_void(s, &index).get_name())
+        .collect();
+    assert_eq!(vec!["POINTER_TO_INT", "POINTER_TO_STRING"], types);
+}
+
+#[test]
+fn a_user_defined_ref_function_is_called_instead_of_the_address_function() {
+    //GIVEN a user-defined function named REF
+    let (unit, mut index) = index(
+        "FUNCTION REF : DINT
+            VAR_INPUT x : INT; END_VAR
+        END_FUNCTION
+
+        PROGRAM PRG
+            VAR x : INT; END_VAR
+            REF(x);
+        END_PROGRAM",
+    );
+
+    //WHEN the AST is annotated
+    let annotations = annotate(&unit, &mut index);
+    let call = &unit.implementations[1].statements[0];
+
+    //THEN the call resolves to the user's function
+    assert_eq!(
+        "DINT",
+        annotations.get_type_or_void(call, &index).get_name()
+    );
+    if let AstStatement::CallStatement { operator, .. } = call {
+        assert_eq!(
+            Some(&StatementAnnotation::Function {
+                return_type: "DINT".to_string(),
+                qualified_name: "REF".to_string(),
+            }),
+            annotations.get(operator)
+        );
+    } else {
+        unreachable!()
+    }
+}
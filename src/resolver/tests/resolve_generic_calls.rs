@@ -1,7 +1,8 @@
 use crate::{
     assert_type_and_hint,
     ast::{self, AstStatement},
-    resolver::{AnnotationMap, TypeAnnotator},
+    lexer::IdProvider,
+    resolver::{generics::create_generic_instances, AnnotationMap, TypeAnnotator},
     test_utils::tests::index,
     typesystem::{BYTE_TYPE, DINT_TYPE, INT_TYPE, REAL_TYPE},
 };
@@ -474,3 +475,69 @@ fn call_order_of_generic_parameters_does_not_change_annotations() {
         }
     }
 }
+
+#[test]
+fn generic_functions_are_instantiated_with_a_copy_of_their_body() {
+    let (unit, index) = index(
+        "
+        FUNCTION times_two : ANY_NUM
+        VAR_INPUT
+            x : ANY_NUM;
+        END_VAR
+            times_two := x * 2;
+        END_FUNCTION
+
+        FUNCTION times_two__REAL : REAL
+        VAR_INPUT
+            x : REAL;
+        END_VAR
+        END_FUNCTION
+
+        PROGRAM PRG
+            VAR
+                a : INT;
+                b : REAL;
+            END_VAR
+            a := times_two(a);
+            b := times_two(b);
+        END_PROGRAM",
+    );
+    let (annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+
+    // the instances are recorded with the function they instantiate
+    assert_eq!(
+        annotations.generic_instances.get("times_two__INT"),
+        Some(&"times_two".to_string())
+    );
+
+    // WHEN the instances are created
+    let units = vec![(7, unit)];
+    let instances = create_generic_instances(
+        &units,
+        &annotations.generic_instances,
+        IdProvider::default(),
+    );
+
+    // THEN only the instance without an implementation is created in the generic function's file
+    assert_eq!(instances.len(), 1);
+    let (file_id, instance_unit) = &instances[0];
+    assert_eq!(*file_id, 7);
+    assert_eq!(instance_unit.implementations.len(), 1);
+    let instance = &instance_unit.implementations[0];
+    assert_eq!(instance.name, "times_two__INT");
+    assert_eq!(instance.type_name, "times_two__INT");
+
+    // AND the body references the instance's return variable
+    let generic_body = &units[0].1.implementations[0].statements[0];
+    let instance_body = &instance.statements[0];
+    if let AstStatement::Assignment { left, .. } = instance_body {
+        assert_eq!(
+            format!("{:?}", left),
+            r#"Reference { name: "times_two__INT" }"#
+        );
+    } else {
+        panic!("expected an assignment, found {:?}", instance_body);
+    }
+    // AND the copied statements have new ids
+    assert_ne!(generic_body.get_id(), instance_body.get_id());
+}
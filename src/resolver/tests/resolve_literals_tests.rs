@@ -31,6 +31,31 @@ fn bool_literals_are_annotated() {
     );
 }
 
+#[test]
+fn real_literals_exceeding_real_precision_are_annotated_as_lreal() {
+    let (unit, index) = index(
+        "PROGRAM PRG
+                1.5E-3;
+                3e8;
+                0.1;
+                3.14159265359;
+                1e39;
+                1e-50;
+            END_PROGRAM",
+    );
+    let (annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+    let statements = &unit.implementations[0].statements;
+
+    let types: Vec<&str> = statements
+        .iter()
+        .map(|s| annotations.get_type_or_void(s, &index).get_name())
+        .collect();
+    assert_eq!(
+        vec!["REAL", "REAL", "REAL", "LREAL", "LREAL", "LREAL"],
+        types
+    );
+}
+
 #[test]
 fn string_literals_are_annotated() {
     //GIVEN some string literals
@@ -87,12 +112,16 @@ fn int_literals_are_annotated() {
                 32768;
                 2147483647;
                 2147483648;
+                9223372036854775807;
+                9223372036854775808;
             END_PROGRAM",
     );
     let (annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
     let statements = &unit.implementations[0].statements;
 
-    let expected_types = vec!["DINT", "DINT", "DINT", "DINT", "DINT", "DINT", "LINT"];
+    let expected_types = vec![
+        "DINT", "DINT", "DINT", "DINT", "DINT", "DINT", "LINT", "LINT", "ULINT",
+    ];
 
     let types: Vec<&str> = statements
         .iter()
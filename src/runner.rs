@@ -3,8 +3,9 @@ use inkwell::{
     execution_engine::{ExecutionEngine, JitFunction},
 };
 
-use crate::{compile_module, diagnostics::Diagnostician, SourceCode, SourceContainer};
-
+use crate::{
+    compile_module, diagnostics::Diagnostician, CodegenOptions, SourceCode, SourceContainer,
+};
 type MainFunction<T, U> = unsafe extern "C" fn(*mut T) -> U;
 
 #[allow(dead_code)]
@@ -81,6 +82,7 @@ pub fn compile<T: Compilable>(context: &Context, source: T) -> ExecutionEngine {
         vec![],
         None,
         Diagnostician::null_diagnostician(),
+        CodegenOptions::default(),
     )
     .unwrap();
     code_gen
@@ -0,0 +1,771 @@
+// Copyright (c) 2022 Ghaith Hachem and Mathias Rieder
+//! Calculates the signatures of the generated POUs, so runtimes and audits can detect which POUs
+//! changed between two builds.
+//!
+//! The signature of a POU is the CRC-32 of a canonical encoding of its resolved declaration and
+//! body: the names, kinds, types and resolved initial values of its members and its statements
+//! with the declarations they resolve to. Locations, comments and formatting are not part of the
+//! encoding, so a signature only changes if the POU's behavior may change. The signature of the
+//! project combines the signatures of all POUs.
+use std::fmt::Display;
+
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use crate::{
+    ast::{
+        AstStatement, CompilationUnit, ConditionalBlock, DirectAccessType, HardwareAccessType,
+        Implementation, LinkageType, PouType,
+    },
+    diagnostics::{Diagnostic, ErrNo},
+    index::{Index, VariableIndexEntry, VariableType},
+    resolver::{AnnotationMap, AnnotationMapImpl, StatementAnnotation},
+    typesystem::{DataTypeInformation, StringEncoding, TypeSize},
+};
+
+/// the signatures of the project and its POUs
+#[derive(Debug, PartialEq)]
+pub struct Signatures {
+    pub project: u32,
+    /// the signatures of the POUs sorted by their names
+    pub pous: Vec<(String, u32)>,
+}
+
+impl Signatures {
+    /// combines the signatures of the given POUs into the signature of the project
+    pub fn new(mut pous: Vec<(String, u32)>) -> Signatures {
+        pous.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let content = pous
+            .iter()
+            .map(|(name, signature)| format!("{} {:08x}\n", name, signature))
+            .collect::<String>();
+        Signatures {
+            project: crc32(content.as_bytes()),
+            pous,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct SignatureFile<'a> {
+    project: String,
+    pous: IndexMap<&'a str, String>,
+}
+
+/// returns the signatures of the POUs implemented by the given units
+pub fn calculate_signatures<'u>(
+    units: impl IntoIterator<Item = &'u CompilationUnit>,
+    annotations: &AnnotationMapImpl,
+    index: &Index,
+) -> Vec<(String, u32)> {
+    units
+        .into_iter()
+        .flat_map(|it| it.implementations.iter())
+        .filter(|it| it.linkage == LinkageType::Internal)
+        .map(|implementation| {
+            let mut encoder = SignatureEncoder {
+                annotations,
+                index,
+                content: String::new(),
+            };
+            encoder.encode_implementation(implementation);
+            (
+                implementation.name.clone(),
+                crc32(encoder.content.as_bytes()),
+            )
+        })
+        .collect()
+}
+
+/// writes the canonical encoding of a POU that its signature is calculated of
+///
+/// Every value is written as its length followed by the value itself, so two different POUs
+/// cannot result in the same encoding. Nodes are written as a tag followed by their values and
+/// children, locations and ids are never written.
+struct SignatureEncoder<'a> {
+    annotations: &'a AnnotationMapImpl,
+    index: &'a Index,
+    content: String,
+}
+
+impl<'a> SignatureEncoder<'a> {
+    fn write(&mut self, value: impl Display) {
+        let value = value.to_string();
+        self.content
+            .push_str(&format!("{}:{};", value.len(), value));
+    }
+
+    fn encode_implementation(&mut self, implementation: &Implementation) {
+        self.write(match &implementation.pou_type {
+            PouType::Program => "PROGRAM",
+            PouType::Function => "FUNCTION",
+            PouType::FunctionBlock => "FUNCTION_BLOCK",
+            PouType::Action => "ACTION",
+            PouType::Class => "CLASS",
+            PouType::Interface => "INTERFACE",
+            PouType::Method { .. } => "METHOD",
+        });
+        self.write(&implementation.name);
+        let index = self.index;
+        let members = index.get_container_members(&implementation.type_name);
+        self.write(members.len());
+        for member in members {
+            self.encode_member(member);
+        }
+        self.encode_statements(&implementation.statements);
+    }
+
+    fn encode_member(&mut self, member: &VariableIndexEntry) {
+        self.write(match member.get_variable_type() {
+            VariableType::Local => "VAR",
+            VariableType::Temp => "VAR_TEMP",
+            VariableType::Input => "VAR_INPUT",
+            VariableType::Output => "VAR_OUTPUT",
+            VariableType::InOut => "VAR_IN_OUT",
+            VariableType::Global => "VAR_GLOBAL",
+            VariableType::Return => "RETURN",
+        });
+        self.write(member.get_name());
+        self.write(member.is_constant());
+        self.encode_type(member.get_type_name());
+        let index = self.index;
+        let binding = member
+            .get_hardware_binding()
+            .and_then(|it| it.get_symbol_name(index.get_const_expressions()));
+        self.encode_optional(binding.as_ref(), |encoder, it| encoder.write(it));
+        let initial_value = member
+            .initial_value
+            .as_ref()
+            .and_then(|it| index.get_const_expressions().get_constant_statement(it));
+        self.encode_optional(initial_value, Self::encode_statement);
+    }
+
+    fn encode_type(&mut self, type_name: &str) {
+        let index = self.index;
+        match index.get_type_information_or_void(type_name) {
+            DataTypeInformation::Struct { name, .. } => {
+                self.write("struct");
+                self.write(name);
+                let members = index.get_container_members(name);
+                self.write(members.len());
+                for member in members {
+                    self.write(member.get_name());
+                    self.encode_type(member.get_type_name());
+                }
+            }
+            DataTypeInformation::Array {
+                name,
+                inner_type_name,
+                dimensions,
+            } => {
+                self.write("array");
+                self.write(name);
+                self.write(dimensions.len());
+                for dimension in dimensions {
+                    self.encode_type_size(&dimension.start_offset);
+                    self.encode_type_size(&dimension.end_offset);
+                }
+                self.encode_type(inner_type_name);
+            }
+            DataTypeInformation::Pointer {
+                name,
+                inner_type_name,
+                auto_deref,
+            } => {
+                //the pointed-to type is written by name only, so recursive types terminate
+                self.write("pointer");
+                self.write(name);
+                self.write(inner_type_name);
+                self.write(auto_deref);
+            }
+            DataTypeInformation::Integer {
+                name,
+                signed,
+                size,
+                semantic_size,
+            } => {
+                self.write("integer");
+                self.write(name);
+                self.write(signed);
+                self.write(size);
+                self.encode_optional(semantic_size.as_ref(), |encoder, it| encoder.write(it));
+            }
+            DataTypeInformation::Enum {
+                name,
+                referenced_type,
+                elements,
+            } => {
+                self.write("enum");
+                self.write(name);
+                self.write(elements.len());
+                for element in elements {
+                    self.write(element);
+                    let value = index
+                        .find_enum_element(name, element)
+                        .and_then(|it| it.initial_value.as_ref())
+                        .and_then(|it| index.get_const_expressions().get_constant_statement(it));
+                    self.encode_optional(value, Self::encode_statement);
+                }
+                self.encode_type(referenced_type);
+            }
+            DataTypeInformation::Float { name, size } => {
+                self.write("float");
+                self.write(name);
+                self.write(size);
+            }
+            DataTypeInformation::String { size, encoding } => {
+                self.write("string");
+                self.write(match encoding {
+                    StringEncoding::Utf8 => "utf8",
+                    StringEncoding::Utf16 => "utf16",
+                });
+                self.encode_type_size(size);
+            }
+            DataTypeInformation::SubRange {
+                name,
+                referenced_type,
+                sub_range,
+            } => {
+                self.write("subrange");
+                self.write(name);
+                self.encode_statement(&sub_range.start);
+                self.encode_statement(&sub_range.end);
+                self.encode_type(referenced_type);
+            }
+            DataTypeInformation::Alias {
+                name,
+                referenced_type,
+            } => {
+                self.write("alias");
+                self.write(name);
+                self.encode_type(referenced_type);
+            }
+            DataTypeInformation::Generic {
+                name,
+                generic_symbol,
+                ..
+            } => {
+                self.write("generic");
+                self.write(name);
+                self.write(generic_symbol);
+            }
+            DataTypeInformation::Void => self.write("void"),
+        }
+    }
+
+    fn encode_type_size(&mut self, size: &TypeSize) {
+        let index = self.index;
+        match size.as_int_value(index) {
+            Ok(value) => self.write(value),
+            Err(_) => self.encode_optional(size.as_const_expression(index), Self::encode_statement),
+        }
+    }
+
+    fn encode_optional<T>(&mut self, value: Option<T>, encode: impl FnOnce(&mut Self, T)) {
+        match value {
+            Some(value) => {
+                self.write("some");
+                encode(self, value);
+            }
+            None => self.write("none"),
+        }
+    }
+
+    fn encode_statements(&mut self, statements: &[AstStatement]) {
+        self.write(statements.len());
+        for statement in statements {
+            self.encode_statement(statement);
+        }
+    }
+
+    fn encode_blocks(&mut self, blocks: &[ConditionalBlock]) {
+        self.write(blocks.len());
+        for block in blocks {
+            self.encode_statement(&block.condition);
+            self.encode_statements(&block.body);
+        }
+    }
+
+    /// writes the given statement followed by the declaration it resolves to
+    fn encode_statement(&mut self, statement: &AstStatement) {
+        match statement {
+            AstStatement::EmptyStatement { .. } => self.write("empty"),
+            AstStatement::LiteralInteger { value, .. } => {
+                self.write("int");
+                self.write(value);
+            }
+            AstStatement::LiteralDate {
+                year, month, day, ..
+            } => {
+                self.write("date");
+                self.write(format!("{}-{}-{}", year, month, day));
+            }
+            AstStatement::LiteralDateAndTime {
+                year,
+                month,
+                day,
+                hour,
+                min,
+                sec,
+                nano,
+                ..
+            } => {
+                self.write("date_and_time");
+                self.write(format!(
+                    "{}-{}-{}-{}:{}:{}.{}",
+                    year, month, day, hour, min, sec, nano
+                ));
+            }
+            AstStatement::LiteralTimeOfDay {
+                hour,
+                min,
+                sec,
+                nano,
+                ..
+            } => {
+                self.write("time_of_day");
+                self.write(format!("{}:{}:{}.{}", hour, min, sec, nano));
+            }
+            AstStatement::LiteralTime {
+                day,
+                hour,
+                min,
+                sec,
+                milli,
+                micro,
+                nano,
+                negative,
+                ..
+            } => {
+                self.write("time");
+                self.write(format!(
+                    "{}{}d{}h{}m{}s{}ms{}us{}ns",
+                    if *negative { "-" } else { "" },
+                    day,
+                    hour,
+                    min,
+                    sec,
+                    milli,
+                    micro,
+                    nano
+                ));
+            }
+            AstStatement::LiteralReal { value, .. } => {
+                self.write("real");
+                self.write(value);
+            }
+            AstStatement::LiteralBool { value, .. } => {
+                self.write("bool");
+                self.write(value);
+            }
+            AstStatement::LiteralString { value, is_wide, .. } => {
+                self.write(if *is_wide { "wstring" } else { "string" });
+                self.write(value);
+            }
+            AstStatement::LiteralArray { elements, .. } => {
+                self.write("array");
+                self.encode_optional(elements.as_deref(), Self::encode_statement);
+            }
+            AstStatement::CastStatement {
+                target, type_name, ..
+            } => {
+                self.write("cast");
+                self.write(type_name);
+                self.encode_statement(target);
+            }
+            AstStatement::MultipliedStatement {
+                multiplier,
+                element,
+                ..
+            } => {
+                self.write("multiplied");
+                self.write(multiplier);
+                self.encode_statement(element);
+            }
+            AstStatement::QualifiedReference { elements, .. } => {
+                self.write("qualified");
+                self.encode_statements(elements);
+            }
+            AstStatement::Reference { name, .. } => {
+                self.write("reference");
+                self.write(name);
+            }
+            AstStatement::ArrayAccess {
+                reference, access, ..
+            } => {
+                self.write("array_access");
+                self.encode_statement(reference);
+                self.encode_statement(access);
+            }
+            AstStatement::PointerAccess { reference, .. } => {
+                self.write("pointer_access");
+                self.encode_statement(reference);
+            }
+            AstStatement::DirectAccess { access, index, .. } => {
+                self.write("direct_access");
+                self.write(get_access_size(access));
+                self.encode_statement(index);
+            }
+            AstStatement::HardwareAccess {
+                direction,
+                access,
+                address,
+                ..
+            } => {
+                self.write("hardware_access");
+                self.write(match direction {
+                    HardwareAccessType::Input => "I",
+                    HardwareAccessType::Output => "Q",
+                    HardwareAccessType::Memory => "M",
+                });
+                self.write(get_access_size(access));
+                self.encode_statements(address);
+            }
+            AstStatement::BinaryExpression {
+                operator,
+                left,
+                right,
+                ..
+            } => {
+                self.write("binary");
+                self.write(operator);
+                self.encode_statement(left);
+                self.encode_statement(right);
+            }
+            AstStatement::UnaryExpression {
+                operator, value, ..
+            } => {
+                self.write("unary");
+                self.write(operator);
+                self.encode_statement(value);
+            }
+            AstStatement::ExpressionList { expressions, .. } => {
+                self.write("list");
+                self.encode_statements(expressions);
+            }
+            AstStatement::RangeStatement { start, end, .. } => {
+                self.write("range");
+                self.encode_statement(start);
+                self.encode_statement(end);
+            }
+            AstStatement::Assignment { left, right, .. } => {
+                self.write("assignment");
+                self.encode_statement(left);
+                self.encode_statement(right);
+            }
+            AstStatement::OutputAssignment { left, right, .. } => {
+                self.write("output_assignment");
+                self.encode_statement(left);
+                self.encode_statement(right);
+            }
+            AstStatement::CallStatement {
+                operator,
+                parameters,
+                ..
+            } => {
+                self.write("call");
+                self.encode_statement(operator);
+                self.encode_optional((**parameters).as_ref(), Self::encode_statement);
+            }
+            AstStatement::IfStatement {
+                blocks, else_block, ..
+            } => {
+                self.write("if");
+                self.encode_blocks(blocks);
+                self.encode_statements(else_block);
+            }
+            AstStatement::ForLoopStatement {
+                counter,
+                start,
+                end,
+                by_step,
+                body,
+                ..
+            } => {
+                self.write("for");
+                self.encode_statement(counter);
+                self.encode_statement(start);
+                self.encode_statement(end);
+                self.encode_optional(by_step.as_deref(), Self::encode_statement);
+                self.encode_statements(body);
+            }
+            AstStatement::WhileLoopStatement {
+                condition, body, ..
+            } => {
+                self.write("while");
+                self.encode_statement(condition);
+                self.encode_statements(body);
+            }
+            AstStatement::RepeatLoopStatement {
+                condition, body, ..
+            } => {
+                self.write("repeat");
+                self.encode_statement(condition);
+                self.encode_statements(body);
+            }
+            AstStatement::CaseStatement {
+                selector,
+                case_blocks,
+                else_block,
+                ..
+            } => {
+                self.write("case");
+                self.encode_statement(selector);
+                self.encode_blocks(case_blocks);
+                self.encode_statements(else_block);
+            }
+            AstStatement::CaseCondition { condition, .. } => {
+                self.write("case_condition");
+                self.encode_statement(condition);
+            }
+            AstStatement::ExitStatement { .. } => self.write("exit"),
+            AstStatement::ContinueStatement { .. } => self.write("continue"),
+            AstStatement::ReturnStatement { .. } => self.write("return"),
+            AstStatement::LiteralNull { .. } => self.write("null"),
+        }
+        let annotations = self.annotations;
+        self.encode_optional(annotations.get(statement), Self::encode_annotation);
+    }
+
+    fn encode_annotation(&mut self, annotation: &StatementAnnotation) {
+        match annotation {
+            StatementAnnotation::Value { resulting_type } => {
+                self.write("value");
+                self.write(resulting_type);
+            }
+            StatementAnnotation::Variable {
+                resulting_type,
+                qualified_name,
+                constant,
+                is_auto_deref,
+            } => {
+                self.write("variable");
+                self.write(qualified_name);
+                self.write(resulting_type);
+                self.write(constant);
+                self.write(is_auto_deref);
+            }
+            StatementAnnotation::Function {
+                return_type,
+                qualified_name,
+            } => {
+                self.write("function");
+                self.write(qualified_name);
+                self.write(return_type);
+            }
+            StatementAnnotation::Type { type_name } => {
+                self.write("type");
+                self.write(type_name);
+            }
+            StatementAnnotation::Program { qualified_name } => {
+                self.write("program");
+                self.write(qualified_name);
+            }
+            StatementAnnotation::Property {
+                resulting_type,
+                getter,
+                setter,
+            } => {
+                self.write("property");
+                self.write(resulting_type);
+                self.encode_optional(getter.as_ref(), |encoder, it| encoder.write(it));
+                self.encode_optional(setter.as_ref(), |encoder, it| encoder.write(it));
+            }
+        }
+    }
+}
+
+fn get_access_size(access: &DirectAccessType) -> &'static str {
+    match access {
+        DirectAccessType::Bit => "X",
+        DirectAccessType::Byte => "B",
+        DirectAccessType::Word => "W",
+        DirectAccessType::DWord => "D",
+        DirectAccessType::Template => "*",
+    }
+}
+
+/// returns the file listing the given signatures as JSON
+pub fn generate_signature_file(signatures: &Signatures) -> Result<String, Diagnostic> {
+    let file = SignatureFile {
+        project: format!("{:08x}", signatures.project),
+        pous: signatures
+            .pous
+            .iter()
+            .map(|(name, signature)| (name.as_str(), format!("{:08x}", signature)))
+            .collect(),
+    };
+    serde_json::to_string_pretty(&file).map_err(|e| Diagnostic::GeneralError {
+        message: e.to_string(),
+        err_no: ErrNo::general__io_err,
+    })
+}
+
+/// returns the CRC-32 (IEEE 802.3) of the given bytes
+pub fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in bytes {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        resolver::{const_evaluator::evaluate_constants, TypeAnnotator},
+        signature::{calculate_signatures, crc32, Signatures},
+        test_utils::tests::index,
+    };
+
+    fn signatures(src: &str) -> Vec<(String, u32)> {
+        let (unit, index) = index(src);
+        let (mut index, ..) = evaluate_constants(index);
+        let (mut annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+        index.import(std::mem::take(&mut annotations.new_index));
+        calculate_signatures(std::iter::once(&unit), &annotations, &index)
+    }
+
+    #[test]
+    fn crc32_of_the_check_value() {
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn signatures_ignore_formatting_and_comments() {
+        let original = signatures(
+            "
+            FUNCTION foo : INT
+            VAR_INPUT x : INT; END_VAR
+            foo := x + 1;
+            END_FUNCTION
+            ",
+        );
+        let reformatted = signatures(
+            "
+            (* increments x *)
+            FUNCTION foo : INT
+                VAR_INPUT
+                    x : INT;
+                END_VAR
+                foo := x+1; // the result
+            END_FUNCTION
+            ",
+        );
+
+        assert_eq!(original, reformatted);
+    }
+
+    #[test]
+    fn signatures_ignore_whitespace_and_comments_between_statements() {
+        let original = signatures(
+            "
+            FUNCTION foo : INT
+            VAR_INPUT x : INT; END_VAR
+            VAR i : INT; s : STRING := 'Hello'; END_VAR
+            IF x > 0 THEN foo := 1; ELSIF x < 0 THEN foo := -1; ELSE foo := 0; END_IF
+            FOR i := 1 TO x BY 2 DO foo := foo + i; END_FOR
+            CASE x OF 1, 2: foo := 3; ELSE foo := 4; END_CASE
+            END_FUNCTION
+            ",
+        );
+        let reformatted = signatures(
+            "
+            FUNCTION foo : INT
+            VAR_INPUT
+                x : INT; (* the input *)
+            END_VAR
+            VAR
+                i : INT;
+                s : STRING := 'Hello'; // a string
+            END_VAR
+
+                IF x > 0 THEN
+                    foo := 1;
+                ELSIF x < 0 THEN
+                    foo := -1;
+                ELSE
+                    (* neither positive nor negative *)
+                    foo := 0;
+                END_IF
+
+                FOR i := 1 TO x BY 2 DO
+                    foo := foo + i;
+                END_FOR
+
+                CASE x OF
+                    1, 2: foo := 3;
+                ELSE
+                    foo := 4;
+                END_CASE
+            END_FUNCTION
+            ",
+        );
+
+        assert_eq!(original, reformatted);
+        //the content of literals is not formatting
+        assert_ne!(
+            original,
+            signatures(
+                "
+            FUNCTION foo : INT
+            VAR_INPUT x : INT; END_VAR
+            VAR i : INT; s : STRING := 'hello'; END_VAR
+            IF x > 0 THEN foo := 1; ELSIF x < 0 THEN foo := -1; ELSE foo := 0; END_IF
+            FOR i := 1 TO x BY 2 DO foo := foo + i; END_FOR
+            CASE x OF 1, 2: foo := 3; ELSE foo := 4; END_CASE
+            END_FUNCTION
+            "
+            )
+        );
+    }
+
+    #[test]
+    fn signatures_change_with_the_declaration_and_the_body() {
+        let src = "
+            VAR_GLOBAL CONSTANT limit : INT := 10; END_VAR
+            FUNCTION foo : INT
+            VAR_INPUT x : INT; END_VAR
+            VAR y : INT := limit; END_VAR
+            foo := x + y;
+            END_FUNCTION
+
+            FUNCTION bar : INT
+            END_FUNCTION
+            ";
+        let original = signatures(src);
+
+        for changed in [
+            src.replace("x + y", "x - y"),
+            src.replace("x : INT", "x : DINT"),
+            src.replace("limit : INT := 10", "limit : INT := 20"),
+        ] {
+            let changed = signatures(&changed);
+            assert_ne!(changed[0], original[0]);
+            //the unchanged POU keeps its signature
+            assert_eq!(changed[1], original[1]);
+        }
+    }
+
+    #[test]
+    fn the_project_signature_combines_the_sorted_pou_signatures() {
+        let signatures = Signatures::new(vec![("foo".to_string(), 2), ("bar".to_string(), 1)]);
+
+        assert_eq!(
+            signatures.pous,
+            vec![("bar".to_string(), 1), ("foo".to_string(), 2)]
+        );
+        assert_eq!(signatures.project, crc32(b"bar 00000001\nfoo 00000002\n"));
+        assert_ne!(
+            signatures.project,
+            Signatures::new(vec![("foo".to_string(), 3), ("bar".to_string(), 1)]).project
+        );
+    }
+}
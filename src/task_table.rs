@@ -0,0 +1,258 @@
+//! Collects the tasks of the configurations into a task table, which tells a runtime which
+//! program instances to call with which interval and priority
+use serde::Serialize;
+
+use crate::{
+    ast::AstStatement,
+    codegen::generators::expression_generator::{calculate_dhm_time_seconds, calculate_time_nano},
+    diagnostics::{Diagnostic, ErrNo},
+    index::{const_expressions::ConstId, Index, TaskIndexEntry},
+    ConfigFormat,
+};
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct TaskTable {
+    #[serde(rename = "TaskConfiguration")]
+    tasks: Vec<Task>,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct Task {
+    name: String,
+    /// the qualified name of the resource declaring the task
+    resource: String,
+    /// the cycle time in nanoseconds
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interval: Option<i64>,
+    priority: u32,
+    /// the global variable triggering the task on its rising edge
+    #[serde(skip_serializing_if = "Option::is_none")]
+    single: Option<String>,
+    programs: Vec<ProgramInstance>,
+}
+
+/// a program instance, the program is called with a pointer to the instance
+#[derive(Debug, PartialEq, Serialize)]
+struct ProgramInstance {
+    instance: String,
+    program: String,
+}
+
+/// Retrieves the tasks and the program instances they schedule from the index
+pub fn collect_task_table(index: &Index) -> Result<TaskTable, Diagnostic> {
+    index
+        .get_tasks()
+        .values()
+        .map(|task| collect_task(index, task))
+        .collect::<Result<Vec<_>, String>>()
+        .map(|tasks| TaskTable { tasks })
+        .map_err(|message| Diagnostic::GeneralError {
+            err_no: ErrNo::general__io_err,
+            message,
+        })
+}
+
+fn collect_task(index: &Index, task: &TaskIndexEntry) -> Result<Task, String> {
+    let interval = task
+        .get_interval()
+        .map(|it| get_interval_value(index, it))
+        .transpose()?;
+    let priority = task
+        .get_priority()
+        .map(|it| {
+            index
+                .get_const_expressions()
+                .get_constant_int_statement_value(it)
+        })
+        .transpose()?
+        .unwrap_or_default();
+    let programs = task
+        .get_programs()
+        .iter()
+        .filter_map(|it| index.find_global_variable(it))
+        .map(|it| ProgramInstance {
+            instance: it.get_qualified_name().to_string(),
+            program: it.get_type_name().to_string(),
+        })
+        .collect();
+    Ok(Task {
+        name: task.get_name().to_string(),
+        resource: task.get_resource().to_string(),
+        interval,
+        priority: u32::try_from(priority)
+            .map_err(|_| format!("Invalid priority {} of task {}", priority, task.get_name()))?,
+        single: task.get_single().map(str::to_string),
+        programs,
+    })
+}
+
+/// returns the nanoseconds of the given interval
+fn get_interval_value(index: &Index, interval: &ConstId) -> Result<i64, String> {
+    match index
+        .get_const_expressions()
+        .get_constant_statement(interval)
+    {
+        Some(AstStatement::LiteralTime {
+            day,
+            hour,
+            min,
+            sec,
+            milli,
+            micro,
+            nano,
+            negative,
+            ..
+        }) => Ok(calculate_time_nano(
+            *negative,
+            calculate_dhm_time_seconds(*day, *hour, *min, *sec),
+            *milli,
+            *micro,
+            *nano,
+        )),
+        Some(AstStatement::LiteralInteger { value, .. }) => Ok(*value as i64),
+        it => Err(format!("Cannot extract time constant from {:#?}", it)),
+    }
+}
+
+pub fn generate_task_table(table: &TaskTable, format: ConfigFormat) -> Result<String, Diagnostic> {
+    match format {
+        ConfigFormat::JSON => {
+            serde_json::to_string_pretty(&table).map_err(|e| Diagnostic::GeneralError {
+                message: e.to_string(),
+                err_no: ErrNo::general__io_err,
+            })
+        }
+        ConfigFormat::TOML => {
+            toml::ser::to_string_pretty(&table).map_err(|e| Diagnostic::GeneralError {
+                message: e.to_string(),
+                err_no: ErrNo::general__io_err,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        resolver::const_evaluator::evaluate_constants,
+        task_table::{collect_task_table, generate_task_table},
+        test_utils::tests::index,
+        ConfigFormat,
+    };
+
+    const CONFIGURATION: &str = "
+        PROGRAM main END_PROGRAM
+        PROGRAM logger END_PROGRAM
+        VAR_GLOBAL CONSTANT
+            cycle : TIME := T#100ms;
+        END_VAR
+        CONFIGURATION plc
+            VAR_GLOBAL trigger : BOOL; END_VAR
+            RESOURCE cpu ON PLC
+                TASK fast(INTERVAL := T#10ms, PRIORITY := 1);
+                TASK slow(INTERVAL := cycle, PRIORITY := 2);
+                TASK event(SINGLE := trigger, PRIORITY := 3);
+                PROGRAM fast_main WITH fast : main;
+                PROGRAM slow_main WITH slow : main;
+                PROGRAM log WITH slow : logger;
+                PROGRAM idle : main;
+            END_RESOURCE
+        END_CONFIGURATION
+        ";
+
+    #[test]
+    fn tasks_collected_with_their_programs() {
+        let (_, index) = index(CONFIGURATION);
+        let (index, _) = evaluate_constants(index);
+        let table = collect_task_table(&index).unwrap();
+        let tasks = table
+            .tasks
+            .iter()
+            .map(|it| {
+                let programs = it
+                    .programs
+                    .iter()
+                    .map(|it| format!("{}:{}", it.instance, it.program))
+                    .collect::<Vec<_>>();
+                (it.name.as_str(), it.interval, it.priority, programs)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            tasks,
+            vec![
+                (
+                    "fast",
+                    Some(10_000_000),
+                    1,
+                    vec!["fast_main:main".to_string()]
+                ),
+                (
+                    "slow",
+                    Some(100_000_000),
+                    2,
+                    vec!["slow_main:main".to_string(), "log:logger".to_string()]
+                ),
+                ("event", None, 3, vec![]),
+            ]
+        );
+        assert_eq!(table.tasks[2].single.as_deref(), Some("trigger"));
+        //program instances are global instances of their program
+        assert_eq!(
+            index
+                .find_global_variable("idle")
+                .map(|it| it.get_type_name()),
+            Some("main")
+        );
+    }
+
+    #[test]
+    fn task_table_printed() {
+        let (_, index) = index(CONFIGURATION);
+        let (index, _) = evaluate_constants(index);
+        let table = collect_task_table(&index).unwrap();
+        let json = generate_task_table(&table, ConfigFormat::JSON).unwrap();
+        assert_eq!(
+            json,
+            r#"{
+  "TaskConfiguration": [
+    {
+      "name": "fast",
+      "resource": "plc.cpu",
+      "interval": 10000000,
+      "priority": 1,
+      "programs": [
+        {
+          "instance": "fast_main",
+          "program": "main"
+        }
+      ]
+    },
+    {
+      "name": "slow",
+      "resource": "plc.cpu",
+      "interval": 100000000,
+      "priority": 2,
+      "programs": [
+        {
+          "instance": "slow_main",
+          "program": "main"
+        },
+        {
+          "instance": "log",
+          "program": "logger"
+        }
+      ]
+    },
+    {
+      "name": "event",
+      "resource": "plc.cpu",
+      "priority": 3,
+      "single": "trigger",
+      "programs": []
+    }
+  ]
+}"#
+        );
+        assert!(generate_task_table(&table, ConfigFormat::TOML).is_ok());
+    }
+}
@@ -13,7 +13,8 @@ pub mod tests {
         resolver::{
             const_evaluator::evaluate_constants, AnnotationMapImpl, AstAnnotations, TypeAnnotator,
         },
-        SourceContainer, Validator,
+        validation::{FlowValidator, RecursionValidator, UsageValidator},
+        CodegenOptions, SourceContainer, Validator,
     };
 
     pub fn parse(src: &str) -> (CompilationUnit, Vec<Diagnostic>) {
@@ -38,6 +39,7 @@ pub mod tests {
             lexer::lex_with_ids(src, id_provider.clone()),
             ast::LinkageType::Internal,
         );
+        index::namespaces::resolve_namespaces(&mut [&mut unit]);
         ast::pre_process(&mut unit, id_provider.clone());
         let index = index::visitor::visit(&unit, id_provider);
         (unit, index)
@@ -66,19 +68,69 @@ pub mod tests {
         validator.diagnostics()
     }
 
+    /// returns the warnings about the variables of the given source that are read before they
+    /// are assigned
+    pub fn parse_and_validate_flow(src: &str) -> Vec<Diagnostic> {
+        let (mut unit, index) = index(src);
+        unit.new_lines = ast::NewLines::new(src);
+
+        let (mut index, ..) = evaluate_constants(index);
+        let (mut annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+        index.import(std::mem::take(&mut annotations.new_index));
+
+        let mut validator = FlowValidator::new();
+        validator.visit_unit(&annotations, &index, &unit);
+        validator.diagnostics
+    }
+
+    /// returns the warnings about the unused declarations of the given source
+    pub fn parse_and_validate_usages(src: &str) -> Vec<Diagnostic> {
+        let (unit, index) = index(src);
+
+        let (mut index, ..) = evaluate_constants(index);
+        let (mut annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+        index.import(std::mem::take(&mut annotations.new_index));
+
+        UsageValidator::new(std::iter::once(&unit), &annotations).validate_unit(&unit, &index)
+    }
+
+    /// returns the errors about the recursive calls of the given source
+    pub fn parse_and_validate_recursions(src: &str) -> Vec<Diagnostic> {
+        let (unit, index) = index(src);
+
+        let (mut index, ..) = evaluate_constants(index);
+        let (mut annotations, _) = TypeAnnotator::visit_unit(&index, &unit);
+        index.import(std::mem::take(&mut annotations.new_index));
+
+        RecursionValidator::new(std::iter::once(&unit), &annotations, &index).validate_unit(&unit)
+    }
+
     pub fn codegen_without_unwrap(src: &str) -> Result<String, Diagnostic> {
+        codegen_with_options(src, CodegenOptions::default())
+    }
+
+    pub fn codegen_with_options(src: &str, options: CodegenOptions) -> Result<String, Diagnostic> {
         let mut id_provider = IdProvider::default();
-        let (unit, index) = do_index(src, id_provider.clone());
+        let (mut unit, index) = do_index(src, id_provider.clone());
+        unit.file_name = "main.st".to_string();
+        unit.new_lines = crate::ast::NewLines::new(src);
 
         let (mut index, ..) = evaluate_constants(index);
         let (mut annotations, literals) = TypeAnnotator::visit_unit(&index, &unit);
         index.import(std::mem::take(&mut annotations.new_index));
 
         let context = inkwell::context::Context::create();
-        let code_generator = crate::codegen::CodeGen::new(&context, "main");
+        let code_generator = crate::codegen::CodeGen::new(&context, "main").with_options(options);
         let annotations = AstAnnotations::new(annotations, id_provider.next_id());
         let llvm_index = code_generator.generate_llvm_index(&annotations, literals, &index)?;
-        code_generator.generate(&unit, &annotations, &index, &llvm_index)
+        code_generator.generate(&unit, &annotations, &index, &llvm_index)?;
+        code_generator.assign_pou_sections(&index);
+        code_generator.prefix_symbols(&index);
+        code_generator.generate_main(&index, &llvm_index)?;
+        code_generator.finalize_debug_info();
+        code_generator.remove_unused_intrinsics();
+        code_generator.optimize();
+        Ok(code_generator.module.print_to_string().to_string())
     }
 
     pub fn codegen(src: &str) -> String {
@@ -97,7 +149,14 @@ pub mod tests {
         diagnostician: Diagnostician,
     ) -> Result<String, Diagnostic> {
         let context = Context::create();
-        let (_, cg) = crate::compile_module(&context, sources, includes, encoding, diagnostician)?;
+        let (_, cg) = crate::compile_module(
+            &context,
+            sources,
+            includes,
+            encoding,
+            diagnostician,
+            CodegenOptions::default(),
+        )?;
         Ok(cg.module.print_to_string().to_string())
     }
 }
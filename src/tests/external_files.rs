@@ -1,4 +1,5 @@
 use crate::{
+    ast::SourceRange,
     diagnostics::{Diagnostic, Diagnostician},
     test_utils::tests::compile_to_string,
     SourceCode,
@@ -83,10 +84,11 @@ fn calling_external_file_function_without_including_file_results_in_error() {
     );
 
     if let Err(msg) = res {
+        //the location references the file's id at the diagnostician
         assert_eq!(
             Diagnostic::codegen_error(
                 "cannot generate call statement for Reference { name: \"external\" }",
-                (30..38).into()
+                SourceRange::in_file(30..38, Some(1))
             ),
             msg
         )
@@ -94,3 +96,44 @@ fn calling_external_file_function_without_including_file_results_in_error() {
         panic!("expected code-gen error but got none")
     }
 }
+
+#[test]
+fn included_declarations_are_not_defined() {
+    //Given a program using a struct and a function block of an included library
+    let prog: SourceCode = "
+    PROGRAM main
+    VAR
+        p : Point;
+        c : Counter;
+    END_VAR
+        c(step := p.x);
+    END_PROGRAM
+    "
+    .into();
+
+    let lib: SourceCode = "
+    TYPE Point : STRUCT x : INT := 1; y : INT; END_STRUCT END_TYPE
+    FUNCTION_BLOCK Counter
+    VAR_INPUT step : INT; END_VAR
+    VAR count : INT; END_VAR
+        count := count + step;
+    END_FUNCTION_BLOCK
+    "
+    .into();
+    //When they are generated
+    let res = compile_to_string(
+        vec![prog],
+        vec![lib],
+        None,
+        Diagnostician::null_diagnostician(),
+    )
+    .unwrap();
+
+    //the included function block is declared but not implemented
+    assert!(res.contains("declare void @Counter(%Counter_interface*)"));
+    //and the initializers are defined by the library
+    assert!(res.contains("@Point__init = external global %Point"));
+    assert!(res.contains("@Counter__init = external global %Counter_interface"));
+    //while the program is defined
+    assert!(res.contains("define void @main(%main_interface* %0)"));
+}
@@ -1,4 +1,64 @@
-use crate::{diagnostics::Diagnostician, test_utils::tests::compile_to_string, SourceCode};
+use std::{cell::RefCell, ops::Range, rc::Rc, sync::Arc};
+
+use inkwell::context::Context;
+
+use crate::{
+    diagnostics::{AssessedDiagnostic, Diagnostic, DiagnosticReporter, Diagnostician, ErrNo},
+    test_utils::tests::compile_to_string,
+    CodegenOptions, SourceCode,
+};
+
+/// records the reported diagnostics with the path of the file they are reported against
+#[derive(Default)]
+struct DiagnosticRecorder {
+    paths: Vec<String>,
+    diagnostics: Rc<RefCell<Vec<(String, Diagnostic)>>>,
+}
+
+impl DiagnosticReporter for DiagnosticRecorder {
+    fn report(&self, diagnostics: &[AssessedDiagnostic], file_id: usize) {
+        self.diagnostics.borrow_mut().extend(
+            diagnostics
+                .iter()
+                .map(|it| (self.paths[file_id].clone(), it.diagnostic.clone())),
+        );
+    }
+
+    fn register(&mut self, path: String, _src: Arc<str>) -> usize {
+        self.paths.push(path);
+        self.paths.len() - 1
+    }
+}
+
+/// compiles the given sources with the given options and returns the path, the message and the
+/// range of every reported diagnostic
+fn compile_and_record_diagnostics(
+    sources: Vec<SourceCode>,
+    options: CodegenOptions,
+) -> Vec<(String, String, Range<usize>)> {
+    let recorder = DiagnosticRecorder::default();
+    let diagnostics = recorder.diagnostics.clone();
+    let context = Context::create();
+    let _ = crate::compile_module(
+        &context,
+        sources,
+        vec![],
+        None,
+        Diagnostician::default().with_reporter(Box::new(recorder)),
+        options,
+    );
+    let diagnostics = diagnostics.borrow();
+    diagnostics
+        .iter()
+        .map(|(path, it)| {
+            (
+                path.clone(),
+                it.get_message().to_string(),
+                it.get_location().to_range(),
+            )
+        })
+        .collect()
+}
 
 #[test]
 fn multiple_source_files_generated() {
@@ -36,3 +96,189 @@ fn multiple_source_files_generated() {
     //The functions are defined correctly
     insta::assert_snapshot!(res);
 }
+
+#[test]
+fn global_variables_and_types_are_shared_accross_files() {
+    //Given a type and a global variable declared in one file
+    let src1: SourceCode = "
+    TYPE Point : STRUCT
+        x : INT;
+        y : INT;
+    END_STRUCT
+    END_TYPE
+
+    VAR_GLOBAL
+        origin : Point;
+    END_VAR
+    "
+    .into();
+    //and a program using them in another file
+    let src2: SourceCode = "
+    PROGRAM mainProg
+    VAR
+        p : Point;
+    END_VAR
+        p := origin;
+        p.x := origin.y;
+    END_PROGRAM
+    "
+    .into();
+    //When the are generated
+    let res = compile_to_string(
+        vec![src1, src2],
+        vec![],
+        None,
+        Diagnostician::null_diagnostician(),
+    )
+    .unwrap();
+    //The type is only declared once and used by both files
+    assert_eq!(1, res.matches("%Point = type { i16, i16 }").count());
+    assert!(res.contains("@origin = global %Point zeroinitializer"));
+    assert!(res.contains("%mainProg_interface = type { %Point }"));
+}
+
+#[test]
+fn codegen_errors_are_reported_for_the_offending_file() {
+    //Given 2 sources, where the second one multiplies pointers, which passes the validation
+    //but cannot be generated
+    let src1 = SourceCode {
+        source: "
+    PROGRAM mainProg
+    END_PROGRAM
+    "
+        .into(),
+        path: "file1.st".into(),
+    };
+    let src2 = SourceCode {
+        source: "
+    PROGRAM otherProg
+    VAR
+        x : INT;
+        ptr : REF_TO INT;
+    END_VAR
+        ptr := &x;
+        ptr := ptr * ptr;
+    END_PROGRAM
+    "
+        .into(),
+        path: "file2.st".into(),
+    };
+    //When they are generated
+    let diagnostics = compile_and_record_diagnostics(vec![src1, src2], CodegenOptions::default());
+    //Then the error is reported once against the second file
+    assert_eq!(
+        diagnostics,
+        vec![(
+            "file2.st".to_string(),
+            "Operator '*' unimplemented for pointers".to_string(),
+            120..129
+        )]
+    );
+}
+
+#[test]
+fn compilation_is_aborted_if_a_source_has_errors() {
+    //Given 2 sources, where the second one references an unknown variable
+    let src1: SourceCode = "
+    PROGRAM mainProg
+    END_PROGRAM
+    "
+    .into();
+    let src2: SourceCode = "
+    PROGRAM otherProg
+        unknownVariable := 1;
+    END_PROGRAM
+    "
+    .into();
+    //When they are compiled
+    let res = compile_to_string(vec![src1, src2], vec![], None, Diagnostician::default());
+    //Then no code is generated
+    match res {
+        Err(Diagnostic::GeneralError { err_no, .. }) => {
+            assert_eq!(err_no, ErrNo::general__compilation_aborted)
+        }
+        _ => panic!("expected the compilation to be aborted"),
+    }
+}
+
+#[test]
+fn global_variables_declared_at_the_same_position_of_two_files_are_reported() {
+    //Given 2 sources declaring the same global variable at the same offset
+    let src1 = SourceCode {
+        source: "
+    VAR_GLOBAL x : INT; END_VAR
+    PROGRAM mainProg
+    END_PROGRAM
+    "
+        .into(),
+        path: "file1.st".into(),
+    };
+    let src2 = SourceCode {
+        source: "
+    VAR_GLOBAL x : INT; END_VAR
+    PROGRAM otherProg
+    END_PROGRAM
+    "
+        .into(),
+        path: "file2.st".into(),
+    };
+    //When they are compiled
+    let diagnostics = compile_and_record_diagnostics(vec![src1, src2], CodegenOptions::default());
+    //Then the declaration replaced by the second file is reported once, although the locations
+    //of the declarations only differ by their file
+    assert_eq!(
+        diagnostics,
+        vec![(
+            "file1.st".to_string(),
+            "Global variable 'x' is declared more than once".to_string(),
+            16..17
+        )]
+    );
+}
+
+#[test]
+fn diagnostics_of_cached_units_are_reported_against_their_file() {
+    //Given 2 sources declaring the same global variable at the same offset
+    let sources = || {
+        vec![
+            SourceCode {
+                source: "
+    VAR_GLOBAL x : INT; END_VAR
+    PROGRAM mainProg
+    END_PROGRAM
+    "
+                .into(),
+                path: "file1.st".into(),
+            },
+            SourceCode {
+                source: "
+    VAR_GLOBAL x : INT; END_VAR
+    PROGRAM otherProg
+    END_PROGRAM
+    "
+                .into(),
+                path: "file2.st".into(),
+            },
+        ]
+    };
+    let cache_dir = std::env::temp_dir().join(format!("cached_diagnostics_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&cache_dir);
+    let options = CodegenOptions {
+        cache_dir: Some(cache_dir.to_string_lossy().to_string()),
+        ..CodegenOptions::default()
+    };
+    //When they are compiled twice, the second compilation loads the cached units
+    let diagnostics = compile_and_record_diagnostics(sources(), options.clone());
+    let cached_diagnostics = compile_and_record_diagnostics(sources(), options);
+    let cached_units = std::fs::read_dir(&cache_dir).unwrap().count();
+    std::fs::remove_dir_all(&cache_dir).unwrap();
+    //Then the cached units report the same diagnostics against the same file
+    assert_eq!(cached_units, 2);
+    let expected = vec![(
+        "file1.st".to_string(),
+        "Global variable 'x' is declared more than once".to_string(),
+        16..17,
+    )];
+    assert_eq!(diagnostics, expected);
+    assert_eq!(cached_diagnostics, expected);
+}
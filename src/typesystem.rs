@@ -1,11 +1,12 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+use serde::{Deserialize, Serialize};
 use std::{
     mem::size_of,
     ops::{Range, RangeInclusive},
 };
 
 use crate::{
-    ast::{AstStatement, GenericBinding, Operator, PouType, TypeNature},
+    ast::{self, AstStatement, GenericBinding, Operator, PouType, TypeNature},
     index::{const_expressions::ConstId, Index},
 };
 
@@ -16,6 +17,12 @@ pub const RANGE_CHECK_S_FN: &str = "CheckRangeSigned";
 pub const RANGE_CHECK_LS_FN: &str = "CheckLRangeSigned";
 pub const RANGE_CHECK_U_FN: &str = "CheckRangeUnsigned";
 pub const RANGE_CHECK_LU_FN: &str = "CheckLRangeUnsigned";
+// Intrinsic called by compiler-generated range checks
+pub const RANGE_CHECK_TRAP_FN: &str = "llvm.trap";
+// Handler called by compiler-generated array bounds checks
+pub const BOUNDS_FAULT_FN: &str = "rusty_bounds_fault";
+// Handler called by compiler-generated division checks
+pub const DIVISION_FAULT_FN: &str = "rusty_division_fault";
 
 pub type NativeSintType = i8;
 pub type NativeIntType = i16;
@@ -39,6 +46,8 @@ pub const LINT_SIZE: u32 = (size_of::<NativeLintType>() * 8) as u32;
 pub const REAL_SIZE: u32 = (size_of::<NativeRealType>() * 8) as u32;
 pub const LREAL_SIZE: u32 = (size_of::<NativeLrealType>() * 8) as u32;
 pub const DATE_TIME_SIZE: u32 = 64;
+/// the size of a pointer on the supported 64-bit targets
+pub const POINTER_SIZE: u32 = 64;
 
 pub const U1_TYPE: &str = "__U1";
 /// used internally for forced casts to u1
@@ -62,6 +71,12 @@ pub const DATE_AND_TIME_TYPE: &str = "DATE_AND_TIME";
 pub const SHORT_DATE_AND_TIME_TYPE: &str = "DT";
 pub const TIME_OF_DAY_TYPE: &str = "TIME_OF_DAY";
 pub const SHORT_TIME_OF_DAY_TYPE: &str = "TOD";
+pub const LDATE_TYPE: &str = "LDATE";
+pub const LTIME_TYPE: &str = "LTIME";
+pub const LDATE_AND_TIME_TYPE: &str = "LDATE_AND_TIME";
+pub const SHORT_LDATE_AND_TIME_TYPE: &str = "LDT";
+pub const LTIME_OF_DAY_TYPE: &str = "LTIME_OF_DAY";
+pub const SHORT_LTIME_OF_DAY_TYPE: &str = "LTOD";
 pub const ULINT_TYPE: &str = "ULINT";
 pub const REAL_TYPE: &str = "REAL";
 pub const LREAL_TYPE: &str = "LREAL";
@@ -71,10 +86,40 @@ pub const CHAR_TYPE: &str = "CHAR";
 pub const WCHAR_TYPE: &str = "WCHAR";
 pub const VOID_TYPE: &str = "VOID";
 
+/// the generated conversion function truncating an `LREAL` to a `DINT`
+pub const TRUNC_FUNCTION: &str = "TRUNC";
+
+/// the member of an interface instance that references the implementing instance
+pub const INTERFACE_INSTANCE_MEMBER: &str = "__instance";
+/// the member of an interface instance that references the vtable of the implementing type
+pub const INTERFACE_VTABLE_MEMBER: &str = "__vtable";
+/// the member of a derived class or function block that embeds the instance of its super class
+pub const BASE_MEMBER: &str = "__BASE";
+
+/// returns the name of the (not auto-dereferenced) pointer type to the given type
+pub fn get_pointer_type_name(inner_type_name: &str) -> String {
+    format!("__pointer_to_{}", inner_type_name)
+}
+/// the generated functions shifting or rotating their first argument by the number of bits
+/// passed as their second argument
+pub const BIT_SHIFT_FUNCTIONS: [&str; 4] = ["SHL", "SHR", "ROL", "ROR"];
+/// the generated functions selecting one of their inputs
+pub const SELECTION_FUNCTIONS: [&str; 5] = ["SEL", "MAX", "MIN", "LIMIT", "MUX"];
+/// the generated numeric functions, all of them but `ABS` result in a real
+pub const NUMERIC_FUNCTIONS: [&str; 12] = [
+    "ABS", "SQRT", "LN", "LOG", "EXP", "SIN", "COS", "TAN", "ASIN", "ACOS", "ATAN", "ATAN2",
+];
+/// the generated function raising its first argument to the power of its second argument
+pub const EXPT_FUNCTION: &str = "EXPT";
+/// the generated function resulting in the number of bytes of a type or a variable
+pub const SIZEOF_FUNCTION: &str = "SIZEOF";
+/// the generated functions resulting in the address of their argument
+pub const ADDRESS_FUNCTIONS: [&str; 2] = ["ADR", "REF"];
+
 #[cfg(test)]
 mod tests;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct DataType {
     pub name: String,
     /// the initial value defined on the TYPE-declration
@@ -104,7 +149,7 @@ impl DataType {
 
 type VarArgs = Option<String>;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum StringEncoding {
     Utf8,
     Utf16,
@@ -119,7 +164,7 @@ impl StringEncoding {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum TypeSize {
     LiteralInteger(i64),
     ConstExpression(ConstId),
@@ -158,15 +203,17 @@ impl TypeSize {
 }
 
 /// indicates where this Struct origins from.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum StructSource {
     OriginalDeclaration,
     Pou(PouType),
+    /// a `UNION` declaration, all members share the same memory location
+    Union,
 }
 
 type TypeId = String;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum DataTypeInformation {
     Struct {
         name: TypeId,
@@ -288,6 +335,28 @@ impl DataTypeInformation {
         matches!(self, DataTypeInformation::Integer { signed: true, .. })
     }
 
+    /// returns true if the given value can be stored in this integer type without an overflow,
+    /// other types are not checked
+    pub fn is_in_int_range(&self, value: i128) -> bool {
+        if let DataTypeInformation::Integer {
+            signed,
+            size,
+            semantic_size,
+            ..
+        } = self
+        {
+            let bits = semantic_size.unwrap_or(*size).min(127);
+            if *signed {
+                let limit = 1_i128 << (bits - 1);
+                -limit <= value && value < limit
+            } else {
+                0 <= value && value < (1_i128 << bits)
+            }
+        } else {
+            true
+        }
+    }
+
     pub fn is_float(&self) -> bool {
         matches!(self, DataTypeInformation::Float { .. })
     }
@@ -296,6 +365,30 @@ impl DataTypeInformation {
         matches!(self, DataTypeInformation::Struct { .. })
     }
 
+    pub fn is_union(&self) -> bool {
+        matches!(
+            self,
+            DataTypeInformation::Struct {
+                source: StructSource::Union,
+                ..
+            }
+        )
+    }
+
+    pub fn is_date_or_time(&self) -> bool {
+        matches!(
+            self.get_name(),
+            DATE_TYPE
+                | DATE_AND_TIME_TYPE
+                | TIME_OF_DAY_TYPE
+                | TIME_TYPE
+                | LDATE_TYPE
+                | LDATE_AND_TIME_TYPE
+                | LTIME_OF_DAY_TYPE
+                | LTIME_TYPE
+        )
+    }
+
     pub fn is_array(&self) -> bool {
         matches!(self, DataTypeInformation::Array { .. })
     }
@@ -377,7 +470,7 @@ impl DataTypeInformation {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct Dimension {
     pub start_offset: TypeSize,
     pub end_offset: TypeSize,
@@ -620,6 +713,50 @@ pub fn get_builtin_types() -> Vec<DataType> {
             },
             nature: TypeNature::Date,
         },
+        DataType {
+            name: LDATE_TYPE.into(),
+            initial_value: None,
+            information: DataTypeInformation::Integer {
+                name: LDATE_TYPE.into(),
+                signed: true,
+                size: DATE_TIME_SIZE,
+                semantic_size: None,
+            },
+            nature: TypeNature::Date,
+        },
+        DataType {
+            name: LTIME_TYPE.into(),
+            initial_value: None,
+            information: DataTypeInformation::Integer {
+                name: LTIME_TYPE.into(),
+                signed: true,
+                size: DATE_TIME_SIZE,
+                semantic_size: None,
+            },
+            nature: TypeNature::Duration,
+        },
+        DataType {
+            name: LDATE_AND_TIME_TYPE.into(),
+            initial_value: None,
+            information: DataTypeInformation::Integer {
+                name: LDATE_AND_TIME_TYPE.into(),
+                signed: true,
+                size: DATE_TIME_SIZE,
+                semantic_size: None,
+            },
+            nature: TypeNature::Date,
+        },
+        DataType {
+            name: LTIME_OF_DAY_TYPE.into(),
+            initial_value: None,
+            information: DataTypeInformation::Integer {
+                name: LTIME_OF_DAY_TYPE.into(),
+                signed: true,
+                size: DATE_TIME_SIZE,
+                semantic_size: None,
+            },
+            nature: TypeNature::Date,
+        },
         DataType {
             name: ULINT_TYPE.into(),
             initial_value: None,
@@ -703,6 +840,24 @@ pub fn get_builtin_types() -> Vec<DataType> {
             },
             nature: TypeNature::Duration,
         },
+        DataType {
+            name: SHORT_LDATE_AND_TIME_TYPE.into(),
+            initial_value: None,
+            information: DataTypeInformation::Alias {
+                name: SHORT_LDATE_AND_TIME_TYPE.into(),
+                referenced_type: LDATE_AND_TIME_TYPE.into(),
+            },
+            nature: TypeNature::Date,
+        },
+        DataType {
+            name: SHORT_LTIME_OF_DAY_TYPE.into(),
+            initial_value: None,
+            information: DataTypeInformation::Alias {
+                name: SHORT_LTIME_OF_DAY_TYPE.into(),
+                referenced_type: LTIME_OF_DAY_TYPE.into(),
+            },
+            nature: TypeNature::Date,
+        },
         DataType {
             name: CHAR_TYPE.into(),
             initial_value: None,
@@ -802,6 +957,421 @@ pub fn get_bigger_type<
     }
 }
 
+/// returns the signed type a mixed signed/unsigned operation has to be promoted to, so the
+/// value range of both operands fits into the result (e.g. `DINT` and `UDINT` promote to `LINT`)
+///
+/// returns `None` if the operands do not mix signedness, if the signed operand is already
+/// big enough to hold the unsigned one or if the unsigned operand is a 64 bit type
+pub fn get_mixed_sign_promotion<'t>(
+    left_type: &'t DataType,
+    right_type: &'t DataType,
+    index: &'t Index,
+) -> Option<&'t DataType> {
+    let left_type = index.get_intrinsic_type_by_name(left_type.get_name());
+    let right_type = index.get_intrinsic_type_by_name(right_type.get_name());
+    let (signed, unsigned) = match (left_type.nature, right_type.nature) {
+        (TypeNature::Signed, TypeNature::Unsigned) => (left_type, right_type),
+        (TypeNature::Unsigned, TypeNature::Signed) => (right_type, left_type),
+        _ => return None,
+    };
+
+    let signed_size = signed.get_type_information().get_size();
+    let unsigned_size = unsigned.get_type_information().get_size();
+    if signed_size > unsigned_size || unsigned_size >= LINT_SIZE {
+        //either the signed type is big enough already, or there is no bigger type left
+        return None;
+    }
+    let promoted_type = match unsigned_size * 2 {
+        size if size <= INT_SIZE => INT_TYPE,
+        size if size <= DINT_SIZE => DINT_TYPE,
+        _ => LINT_TYPE,
+    };
+    Some(index.get_type_or_panic(promoted_type))
+}
+
+/// returns the name of the smallest float type that can hold the given real literal without
+/// losing precision, e.g. `REAL` for `1.5E-3` and `LREAL` for `3.14159265359` or `1e39`
+pub fn get_real_type_name_for(value: &str) -> &'static str {
+    let value = value.parse::<f64>().unwrap_or_default();
+    let real_value = value as NativeRealType;
+    //the shortest representation of a REAL must denote the same number
+    if real_value.is_finite() && real_value.to_string().parse::<f64>() == Ok(value) {
+        REAL_TYPE
+    } else {
+        LREAL_TYPE
+    }
+}
+
+/// returns the name of the type that results from an arithmetic operation on date and time
+/// values as defined by IEC 61131-3 (e.g. `DT - DT` results in a `TIME`) or None if the
+/// operation is no date and time arithmetic.
+/// All date and time types are stored in nanoseconds, so the operation itself is a plain
+/// integer operation. If one of the operands is a long type (e.g. `LTIME`), the result is
+/// the long variant of the resulting type.
+pub fn get_date_time_arithmetic_type(
+    left_type: &DataTypeInformation,
+    operator: &Operator,
+    right_type: &DataTypeInformation,
+    index: &Index,
+) -> Option<&'static str> {
+    let left_type = index.find_intrinsic_type(left_type);
+    let right_type = index.find_intrinsic_type(right_type);
+    let is_long =
+        is_long_date_or_time(left_type.get_name()) || is_long_date_or_time(right_type.get_name());
+    let is_int = |it: &DataTypeInformation| it.is_int() && !it.is_date_or_time();
+    let result = match (
+        get_date_time_base_type(left_type.get_name()),
+        operator,
+        get_date_time_base_type(right_type.get_name()),
+    ) {
+        (TIME_TYPE, Operator::Plus | Operator::Minus, TIME_TYPE) => Some(TIME_TYPE),
+        (TIME_OF_DAY_TYPE, Operator::Plus | Operator::Minus, TIME_TYPE)
+        | (TIME_TYPE, Operator::Plus, TIME_OF_DAY_TYPE) => Some(TIME_OF_DAY_TYPE),
+        (DATE_AND_TIME_TYPE, Operator::Plus | Operator::Minus, TIME_TYPE)
+        | (TIME_TYPE, Operator::Plus, DATE_AND_TIME_TYPE) => Some(DATE_AND_TIME_TYPE),
+        (DATE_AND_TIME_TYPE, Operator::Minus, DATE_AND_TIME_TYPE)
+        | (TIME_OF_DAY_TYPE, Operator::Minus, TIME_OF_DAY_TYPE)
+        | (DATE_TYPE, Operator::Minus, DATE_TYPE) => Some(TIME_TYPE),
+        (TIME_TYPE, Operator::Multiplication | Operator::Division, _) if is_int(right_type) => {
+            Some(TIME_TYPE)
+        }
+        (_, Operator::Multiplication, TIME_TYPE) if is_int(left_type) => Some(TIME_TYPE),
+        _ => None,
+    };
+    if is_long {
+        result.map(get_long_date_time_type)
+    } else {
+        result
+    }
+}
+
+fn is_long_date_or_time(type_name: &str) -> bool {
+    get_date_time_base_type(type_name) != type_name
+}
+
+/// returns the name of the date and time type that corresponds to the given long date and
+/// time type (e.g. `TIME` for `LTIME`) or the given name if it is no long date and time type.
+pub fn get_date_time_base_type(type_name: &str) -> &str {
+    match type_name {
+        LDATE_TYPE => DATE_TYPE,
+        LTIME_TYPE => TIME_TYPE,
+        LDATE_AND_TIME_TYPE => DATE_AND_TIME_TYPE,
+        LTIME_OF_DAY_TYPE => TIME_OF_DAY_TYPE,
+        _ => type_name,
+    }
+}
+
+fn get_long_date_time_type(type_name: &str) -> &str {
+    match type_name {
+        DATE_TYPE => LDATE_TYPE,
+        TIME_TYPE => LTIME_TYPE,
+        DATE_AND_TIME_TYPE => LDATE_AND_TIME_TYPE,
+        TIME_OF_DAY_TYPE => LTIME_OF_DAY_TYPE,
+        _ => type_name,
+    }
+}
+
+/// returns the source- and target-type of the conversion function with the given name
+/// (e.g. `INT_TO_REAL` or `DT_TO_DATE`) or None if the name does not describe a conversion
+/// that is generated by the compiler. Conversions are generated between all numeric, bit,
+/// date and time types, except between real and date or time types. Truncating conversions
+/// (e.g. `REAL_TRUNC_INT`) convert a real to an integer, `TRUNC` converts an `LREAL` to a `DINT`.
+pub fn find_conversion<'idx>(
+    function_name: &str,
+    index: &'idx Index,
+) -> Option<(&'idx DataType, &'idx DataType)> {
+    let upper_name = function_name.to_uppercase();
+    if upper_name == TRUNC_FUNCTION {
+        return Some((
+            index.get_type_or_panic(LREAL_TYPE),
+            index.get_type_or_panic(DINT_TYPE),
+        ));
+    }
+    upper_name
+        .match_indices("_TO_")
+        .chain(upper_name.match_indices("_TRUNC_"))
+        .filter_map(|(position, separator)| {
+            let source = index.find_effective_type(&upper_name[..position])?;
+            let target = index.find_effective_type(&upper_name[position + separator.len()..])?;
+            Some((source, target, separator == "_TRUNC_"))
+        })
+        .find(|(source, target, truncates)| {
+            let source = source.get_type_information();
+            let target = target.get_type_information();
+            if *truncates {
+                source.is_float()
+                    && target.is_int()
+                    && !target.is_bool()
+                    && !target.is_date_or_time()
+            } else {
+                ((source.is_int() || source.is_float())
+                    && (target.is_int() || target.is_float())
+                    && !(source.is_float() && target.is_date_or_time())
+                    && !(source.is_date_or_time() && target.is_float()))
+                    || is_character_of_string(source, target)
+                    || is_character_of_string(target, source)
+            }
+        })
+        .map(|(source, target, _)| (source, target))
+}
+
+/// returns true if the given character-type holds a single character of the given string-type
+/// (`CHAR` for `STRING`, `WCHAR` for `WSTRING`)
+fn is_character_of_string(character: &DataTypeInformation, string: &DataTypeInformation) -> bool {
+    match (character, string) {
+        (
+            DataTypeInformation::Integer { size, .. },
+            DataTypeInformation::String { encoding, .. },
+        ) => character.is_character() && *size == encoding.get_bytes_per_char() * 8,
+        _ => false,
+    }
+}
+
+/// returns true if the given conversion function truncates a real value instead of rounding it
+/// to the nearest integer (e.g. `TRUNC` or `REAL_TRUNC_INT`)
+pub fn is_truncating_conversion(function_name: &str) -> bool {
+    let upper_name = function_name.to_uppercase();
+    upper_name == TRUNC_FUNCTION || upper_name.contains("_TRUNC_")
+}
+
+/// returns true if the given name denotes one of the generated bit-shift functions
+/// (`SHL`, `SHR`, `ROL` or `ROR`)
+pub fn is_bit_shift_function(function_name: &str) -> bool {
+    BIT_SHIFT_FUNCTIONS.contains(&function_name.to_uppercase().as_str())
+}
+
+/// returns true if the given name denotes one of the generated selection functions
+/// (`SEL`, `MAX`, `MIN`, `LIMIT` or `MUX`)
+pub fn is_selection_function(function_name: &str) -> bool {
+    SELECTION_FUNCTIONS.contains(&function_name.to_uppercase().as_str())
+}
+
+/// returns true if the given name denotes one of the generated numeric functions
+/// (e.g. `ABS`, `SQRT` or `ATAN2`)
+pub fn is_numeric_function(function_name: &str) -> bool {
+    NUMERIC_FUNCTIONS.contains(&function_name.to_uppercase().as_str())
+}
+
+/// returns true if the given name denotes one of the generated address functions
+/// (`ADR` or `REF`)
+pub fn is_address_function(function_name: &str) -> bool {
+    ADDRESS_FUNCTIONS.contains(&function_name.to_uppercase().as_str())
+}
+
+/// returns the number of bytes an instance of the given type occupies in memory or None if its
+/// size cannot be determined (e.g. generics or structs that contain themselves). Members are
+/// laid out with the natural alignment of the supported 64-bit targets, like the generated
+/// LLVM types.
+pub fn get_size_in_bytes(type_name: &str, index: &Index) -> Option<u32> {
+    get_layout(type_name, index).map(|(size, _)| size)
+}
+
+/// returns the size and the alignment of the given type in bytes
+fn get_layout(type_name: &str, index: &Index) -> Option<(u32, u32)> {
+    let information = index.find_intrinsic_type(index.find_effective_type_info(type_name)?);
+    match information {
+        DataTypeInformation::Integer { size, .. } | DataTypeInformation::Float { size, .. } => {
+            let bytes = (size + 7) / 8;
+            Some((bytes, bytes))
+        }
+        DataTypeInformation::Enum {
+            referenced_type, ..
+        } => get_layout(referenced_type, index),
+        DataTypeInformation::Pointer { .. } => Some((POINTER_SIZE / 8, POINTER_SIZE / 8)),
+        DataTypeInformation::String { size, encoding } => {
+            let char_size = if *encoding == StringEncoding::Utf8 {
+                1
+            } else {
+                2
+            };
+            let length = size.as_int_value(index).ok()? as u32;
+            Some((length * char_size, char_size))
+        }
+        DataTypeInformation::Array {
+            inner_type_name,
+            dimensions,
+            ..
+        } => {
+            let (inner_size, alignment) = get_layout(inner_type_name, index)?;
+            let length = dimensions
+                .iter()
+                .map(|it| it.get_length(index).ok())
+                .product::<Option<u32>>()?;
+            Some((inner_size * length, alignment))
+        }
+        DataTypeInformation::Struct { name, .. } => {
+            get_struct_layout(name, get_pack_mode(name, index), index)
+                .map(|it| (it.size, it.alignment))
+        }
+        _ => None,
+    }
+}
+
+/// the name of the attribute limiting the alignment of a struct's members
+/// (e.g. `{attribute 'pack_mode' := '1'}`)
+pub const PACK_MODE_ATTRIBUTE: &str = "pack_mode";
+
+/// the name of the attribute declaring the byte offset a struct member is expected at
+/// (e.g. `{attribute 'offset' := '4'}`)
+pub const OFFSET_ATTRIBUTE: &str = "offset";
+
+/// returns the pack mode declared by the given struct type's `pack_mode` attribute, the members
+/// of a struct with pack mode `n` are aligned to at most `n` bytes. Pack mode 0 packs the members
+/// like pack mode 1. Returns None if the attribute is missing or invalid.
+pub fn get_pack_mode(type_name: &str, index: &Index) -> Option<u32> {
+    let name = match index.find_effective_type_info(type_name)? {
+        DataTypeInformation::Struct {
+            name,
+            source: StructSource::OriginalDeclaration,
+            ..
+        } => name,
+        _ => return None,
+    };
+    index
+        .find_attribute(name, PACK_MODE_ATTRIBUTE)
+        .and_then(|it| it.value.as_deref())
+        .and_then(|it| it.trim().parse::<u32>().ok())
+        .filter(|it| [0, 1, 2, 4, 8].contains(it))
+        .map(|it| it.max(1))
+}
+
+/// returns true if the given struct is generated as a packed struct, that is if its pack mode
+/// is smaller than the natural alignment of its members
+pub fn is_packed_struct(type_name: &str, index: &Index) -> bool {
+    get_pack_mode(type_name, index)
+        .zip(get_struct_layout(type_name, None, index))
+        .map_or(false, |(pack_mode, natural)| pack_mode < natural.alignment)
+}
+
+/// the memory layout of a struct, union or POU instance
+#[derive(Debug, PartialEq)]
+pub struct StructLayout {
+    /// the byte offset of every member in declaration order
+    pub offsets: Vec<u32>,
+    /// the size of an instance in bytes including the trailing padding
+    pub size: u32,
+    /// the alignment of an instance in bytes
+    pub alignment: u32,
+}
+
+/// returns the layout of the given struct with its members aligned to at most the given pack
+/// mode. Packed structs are aligned to a single byte, like the generated LLVM types.
+/// Returns None if the size of a member cannot be determined.
+pub fn get_struct_layout(
+    type_name: &str,
+    pack_mode: Option<u32>,
+    index: &Index,
+) -> Option<StructLayout> {
+    let (name, source) = match index.find_effective_type_info(type_name)? {
+        DataTypeInformation::Struct { name, source, .. } => (name, source),
+        _ => return None,
+    };
+    if index.find_cyclic_struct_definition(name).is_some() {
+        return None;
+    }
+    let mut members = index
+        .get_container_members(name)
+        .into_iter()
+        .filter(|it| !it.is_temp() && !it.is_return())
+        .map(|it| get_layout(it.get_type_name(), index))
+        .collect::<Option<Vec<_>>>()?;
+    // the topmost class of a hierarchy carries the vtable as an additional last member
+    if matches!(
+        source,
+        StructSource::Pou(PouType::Class) | StructSource::Pou(PouType::FunctionBlock)
+    ) && index.is_polymorphic(name)
+        && index.find_super_class(name).is_none()
+    {
+        members.push((POINTER_SIZE / 8, POINTER_SIZE / 8));
+    }
+    let pack_mode = pack_mode.unwrap_or(u32::MAX);
+    let natural_alignment = members.iter().map(|(_, it)| *it).max().unwrap_or(1);
+    let alignment = natural_alignment.min(pack_mode);
+    let mut offsets = vec![];
+    let mut end = 0;
+    for (size, member_alignment) in members {
+        let offset = if *source == StructSource::Union {
+            0
+        } else {
+            align_to(end, member_alignment.min(pack_mode))
+        };
+        offsets.push(offset);
+        end = end.max(offset + size);
+    }
+    Some(StructLayout {
+        offsets,
+        size: align_to(end, alignment),
+        alignment: if pack_mode < natural_alignment {
+            1
+        } else {
+            alignment
+        },
+    })
+}
+
+/// rounds the given offset up to the next multiple of the given alignment
+fn align_to(offset: u32, alignment: u32) -> u32 {
+    (offset + alignment - 1) / alignment * alignment
+}
+
+/// splits the arguments of a selection function into its selector (`G` of `SEL(G, IN0, IN1)`,
+/// `K` of `MUX(K, IN0, ...)`) and the inputs it selects from. Returns None if the number of
+/// arguments does not match the function.
+pub fn split_selection_arguments<'a, T>(
+    function_name: &str,
+    arguments: &'a [T],
+) -> Option<(Option<&'a T>, &'a [T])> {
+    match (function_name.to_uppercase().as_str(), arguments) {
+        ("SEL", [selector, inputs @ ..]) if inputs.len() == 2 => Some((Some(selector), inputs)),
+        ("MUX", [selector, inputs @ ..]) if !inputs.is_empty() => Some((Some(selector), inputs)),
+        ("LIMIT", inputs) if inputs.len() == 3 => Some((None, inputs)),
+        ("MAX", inputs) | ("MIN", inputs) if !inputs.is_empty() => Some((None, inputs)),
+        _ => None,
+    }
+}
+
+/// returns the implicitly passed arguments of a numeric function. Returns None if the number of
+/// arguments does not match the function (two for `ATAN2`, one for all others).
+pub fn get_numeric_function_arguments<'a>(
+    function_name: &str,
+    parameters: &'a Option<AstStatement>,
+) -> Option<Vec<&'a AstStatement>> {
+    let arguments = ast::get_implicit_arguments(parameters)?;
+    let expected = if function_name.eq_ignore_ascii_case("ATAN2") {
+        2
+    } else {
+        1
+    };
+    (arguments.len() == expected).then(|| arguments)
+}
+
+/// returns the base and the exponent of a call to `EXPT`, they are either passed implicitly
+/// (`EXPT(x, y)`) or assigned to the parameters `IN1` and `IN2` (`EXPT(IN1 := x, IN2 := y)`).
+/// Returns None if the arguments do not match these parameters
+pub fn get_exponent_arguments(
+    parameters: &Option<AstStatement>,
+) -> Option<(&AstStatement, &AstStatement)> {
+    if let Some(arguments) = ast::get_argument_pair(parameters) {
+        return Some(arguments);
+    }
+    let arguments = ast::flatten_expression_list(parameters.as_ref()?);
+    if arguments.len() != 2 {
+        return None;
+    }
+    let find_argument = |parameter: &str| {
+        arguments.iter().copied().find_map(|it| match it {
+            AstStatement::Assignment { left, right, .. }
+                if matches!(left.as_ref(), AstStatement::Reference { name, .. }
+                    if name.eq_ignore_ascii_case(parameter)) =>
+            {
+                Some(right.as_ref())
+            }
+            _ => None,
+        })
+    };
+    Some((find_argument("IN1")?, find_argument("IN2")?))
+}
+
 /// returns the signed version of the given data_type if its a signed int-type
 /// returns the original type if it is no signed int-type
 pub fn get_signed_type<'t>(
@@ -177,6 +177,61 @@ fn get_bigger_size_integers_mix_test() {
     );
 }
 
+#[test]
+fn mixed_sign_promotion_test() {
+    // Given an initialized index
+    let index = visit(&CompilationUnit::default(), IdProvider::default());
+    let int_type = index.get_type_or_panic(INT_TYPE);
+    let dint_type = index.get_type_or_panic(DINT_TYPE);
+    let lint_type = index.get_type_or_panic(LINT_TYPE);
+    let usint_type = index.get_type_or_panic(USINT_TYPE);
+    let uint_type = index.get_type_or_panic(UINT_TYPE);
+    let udint_type = index.get_type_or_panic(UDINT_TYPE);
+    let ulint_type = index.get_type_or_panic(ULINT_TYPE);
+    let dword_type = index.get_type_or_panic(DWORD_TYPE);
+
+    //the unsigned value does not fit into the signed type
+    assert_eq!(
+        Some(int_type),
+        typesystem::get_mixed_sign_promotion(
+            usint_type,
+            index.get_type_or_panic(SINT_TYPE),
+            &index
+        )
+    );
+    assert_eq!(
+        Some(dint_type),
+        typesystem::get_mixed_sign_promotion(int_type, uint_type, &index)
+    );
+    assert_eq!(
+        Some(lint_type),
+        typesystem::get_mixed_sign_promotion(dint_type, udint_type, &index)
+    );
+    assert_eq!(
+        Some(lint_type),
+        typesystem::get_mixed_sign_promotion(udint_type, int_type, &index)
+    );
+    //the signed type is big enough
+    assert_eq!(
+        None,
+        typesystem::get_mixed_sign_promotion(uint_type, dint_type, &index)
+    );
+    //there is no bigger signed type
+    assert_eq!(
+        None,
+        typesystem::get_mixed_sign_promotion(lint_type, ulint_type, &index)
+    );
+    //bit types are not promoted
+    assert_eq!(
+        None,
+        typesystem::get_mixed_sign_promotion(dint_type, dword_type, &index)
+    );
+    assert_eq!(
+        None,
+        typesystem::get_mixed_sign_promotion(udint_type, udint_type, &index)
+    );
+}
+
 #[test]
 fn get_bigger_size_real_test() {
     // Given an initialized index
@@ -610,3 +665,43 @@ fn any_date_type_test() {
     assert!(date_time.has_nature(TypeNature::Any, &index));
     assert!(tod.has_nature(TypeNature::Any, &index));
 }
+
+#[test]
+fn conversions_between_elementary_types_are_found() {
+    // Given an initialized index
+    let index = visit(&CompilationUnit::default(), IdProvider::default());
+    let find_conversion = |name: &str| {
+        typesystem::find_conversion(name, &index)
+            .map(|(source, target)| (source.get_name(), target.get_name()))
+    };
+
+    assert_eq!(Some((INT_TYPE, REAL_TYPE)), find_conversion("INT_TO_REAL"));
+    assert_eq!(
+        Some((LREAL_TYPE, UDINT_TYPE)),
+        find_conversion("lreal_to_udint")
+    );
+    assert_eq!(
+        Some((BOOL_TYPE, WORD_TYPE)),
+        find_conversion("BOOL_TO_WORD")
+    );
+    assert_eq!(
+        Some((DATE_AND_TIME_TYPE, DATE_TYPE)),
+        find_conversion("DT_TO_DATE")
+    );
+    assert_eq!(
+        Some((REAL_TYPE, SINT_TYPE)),
+        find_conversion("REAL_TRUNC_SINT")
+    );
+    assert_eq!(Some((LREAL_TYPE, DINT_TYPE)), find_conversion("TRUNC"));
+    //reals and times do not convert into each other
+    assert_eq!(None, find_conversion("REAL_TO_TIME"));
+    assert_eq!(None, find_conversion("TIME_TO_LREAL"));
+    //only reals can be truncated to integers
+    assert_eq!(None, find_conversion("DINT_TRUNC_INT"));
+    assert_eq!(None, find_conversion("REAL_TRUNC_BOOL"));
+    assert_eq!(None, find_conversion("STRING_TO_INT"));
+
+    assert!(typesystem::is_truncating_conversion("trunc"));
+    assert!(typesystem::is_truncating_conversion("LREAL_TRUNC_INT"));
+    assert!(!typesystem::is_truncating_conversion("REAL_TO_INT"));
+}
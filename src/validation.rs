@@ -4,19 +4,29 @@ use crate::{
         UserTypeDeclaration, Variable, VariableBlock,
     },
     index::Index,
-    resolver::AnnotationMapImpl,
+    resolver::{AnnotationMap, AnnotationMapImpl, StatementAnnotation},
     Diagnostic,
 };
 
+use std::collections::{hash_map::Entry, HashMap};
+
 use self::{
     pou_validator::PouValidator, stmt_validator::StatementValidator,
     variable_validator::VariableValidator,
 };
 
+mod flow_validator;
 mod pou_validator;
+mod recursion_validator;
 mod stmt_validator;
+mod usage_validator;
 mod variable_validator;
 
+pub use self::{
+    flow_validator::FlowValidator, recursion_validator::RecursionValidator,
+    usage_validator::UsageValidator,
+};
+
 #[cfg(test)]
 mod tests;
 
@@ -31,10 +41,54 @@ macro_rules! visit_all_statements {
      };
    }
 
+/// returns a diagnostic for every declaration whose name was already declared by a preceding
+/// declaration, identifiers are compared case-insensitively
+fn validate_unique_declarations<'a>(
+    declarations: impl IntoIterator<Item = (&'a str, &'a SourceRange)>,
+) -> Vec<Diagnostic> {
+    let mut declared: HashMap<String, &str> = HashMap::new();
+    declarations
+        .into_iter()
+        .filter_map(
+            |(name, location)| match declared.entry(name.to_lowercase()) {
+                Entry::Occupied(previous) => Some(Diagnostic::duplicate_declaration(
+                    name,
+                    previous.get(),
+                    location.clone(),
+                )),
+                Entry::Vacant(entry) => {
+                    entry.insert(name);
+                    None
+                }
+            },
+        )
+        .collect()
+}
+
 pub struct ValidationContext<'s> {
     ast_annotation: &'s AnnotationMapImpl,
     index: &'s Index,
     qualifier: Option<&'s str>,
+    /// whether the validated statements are located inside a loop's body
+    is_in_loop: bool,
+}
+
+impl<'s> ValidationContext<'s> {
+    /// returns a copy of this context for the statements of a loop's body
+    fn in_loop(&self) -> ValidationContext<'s> {
+        ValidationContext {
+            is_in_loop: true,
+            ..*self
+        }
+    }
+
+    /// returns a copy of this context that resolves members of the given container
+    fn with_qualifier<'q>(&self, qualifier: Option<&'q str>) -> ValidationContext<'q>
+    where
+        's: 'q,
+    {
+        ValidationContext { qualifier, ..*self }
+    }
 }
 
 pub struct Validator {
@@ -67,6 +121,21 @@ impl Validator {
         index: &Index,
         unit: &CompilationUnit,
     ) {
+        //the generated types are named after their declarations, which are validated instead
+        let declarations = unit
+            .units
+            .iter()
+            .map(|it| (it.name.as_str(), &it.location))
+            .chain(unit.types.iter().filter_map(|it| {
+                it.data_type
+                    .get_name()
+                    .filter(|name| !name.starts_with("__"))
+                    .map(|name| (name, &it.location))
+            }));
+        self.pou_validator
+            .diagnostics
+            .extend(validate_unique_declarations(declarations));
+
         for pou in &unit.units {
             self.visit_pou(
                 pou,
@@ -74,6 +143,7 @@ impl Validator {
                     ast_annotation: annotations,
                     index,
                     qualifier: Some(pou.name.as_str()),
+                    is_in_loop: false,
                 },
             );
         }
@@ -82,6 +152,7 @@ impl Validator {
             ast_annotation: annotations,
             index,
             qualifier: None,
+            is_in_loop: false,
         };
         for t in &unit.types {
             self.visit_user_type_declaration(t, no_context);
@@ -91,15 +162,27 @@ impl Validator {
             self.visit_variable_container(no_context, gv);
         }
 
+        for configuration in &unit.configurations {
+            self.pou_validator
+                .validate_configuration(configuration, no_context);
+        }
+
         for i in &unit.implementations {
             let context = ValidationContext {
                 ast_annotation: annotations,
                 index,
                 qualifier: Some(i.name.as_str()),
+                is_in_loop: false,
             };
-            i.statements
-                .iter()
-                .for_each(|s| self.visit_statement(s, &context));
+            self.pou_validator.validate_action_container(i, &context);
+            //the body of a generic function is validated for each of its instances
+            if index
+                .get_type_information_or_void(&i.type_name)
+                .is_generic()
+            {
+                continue;
+            }
+            self.visit_statement_list(&i.statements, &context);
         }
     }
 
@@ -109,6 +192,21 @@ impl Validator {
         context: &ValidationContext,
     ) {
         self.visit_data_type(context, &user_data_type.data_type, &user_data_type.location);
+
+        if let (Some(initializer), Some(type_name)) = (
+            &user_data_type.initializer,
+            user_data_type.data_type.get_name(),
+        ) {
+            let target_type = context
+                .index
+                .get_effective_type_by_name(type_name)
+                .get_type_information();
+            self.variable_validator.validate_array_initializer(
+                initializer,
+                target_type,
+                context.index,
+            );
+        }
     }
 
     pub fn visit_pou(&mut self, pou: &Pou, context: &ValidationContext) {
@@ -159,12 +257,16 @@ impl Validator {
         location: &SourceRange,
     ) {
         self.variable_validator
-            .validate_data_type(data_type, location);
+            .validate_data_type(data_type, location, context);
 
         match data_type {
-            DataType::StructType { variables, .. } => variables
-                .iter()
-                .for_each(|v| self.visit_variable(context, v)),
+            DataType::StructType { name, variables } | DataType::UnionType { name, variables } => {
+                //the members' initial values are resolved in the scope of the struct
+                let context = context.with_qualifier(name.as_deref());
+                variables
+                    .iter()
+                    .for_each(|v| self.visit_variable(&context, v))
+            }
             DataType::ArrayType {
                 referenced_type, ..
             } => self.visit_data_type_declaration(context, referenced_type),
@@ -177,6 +279,14 @@ impl Validator {
         }
     }
 
+    /// visits the statements of a body or block
+    fn visit_statement_list(&mut self, statements: &[AstStatement], context: &ValidationContext) {
+        self.stmt_validator.validate_reachability(statements);
+        statements
+            .iter()
+            .for_each(|s| self.visit_statement(s, context));
+    }
+
     pub fn visit_statement(&mut self, statement: &AstStatement, context: &ValidationContext) {
         match statement {
             AstStatement::LiteralArray {
@@ -205,7 +315,19 @@ impl Validator {
                 visit_all_statements!(self, context, start, end);
             }
             AstStatement::Assignment { left, right, .. } => {
-                self.visit_statement(left, context);
+                if let (
+                    Some(StatementAnnotation::Property { .. }),
+                    AstStatement::QualifiedReference { elements, .. },
+                ) = (context.ast_annotation.get(left), left.as_ref())
+                {
+                    //an assigned property is not read, only its instance is
+                    elements
+                        .iter()
+                        .take(elements.len() - 1)
+                        .for_each(|e| self.visit_statement(e, context));
+                } else {
+                    self.visit_statement(left, context);
+                }
                 self.visit_statement(right, context);
             }
             AstStatement::OutputAssignment { left, right, .. } => {
@@ -227,11 +349,9 @@ impl Validator {
             } => {
                 blocks.iter().for_each(|b| {
                     self.visit_statement(b.condition.as_ref(), context);
-                    b.body.iter().for_each(|s| self.visit_statement(s, context));
+                    self.visit_statement_list(&b.body, context);
                 });
-                else_block
-                    .iter()
-                    .for_each(|e| self.visit_statement(e, context));
+                self.visit_statement_list(else_block, context);
             }
             AstStatement::ForLoopStatement {
                 counter,
@@ -245,19 +365,19 @@ impl Validator {
                 if let Some(by_step) = by_step {
                     self.visit_statement(by_step, context);
                 }
-                body.iter().for_each(|s| self.visit_statement(s, context));
+                self.visit_statement_list(body, &context.in_loop());
             }
             AstStatement::WhileLoopStatement {
                 condition, body, ..
             } => {
                 self.visit_statement(condition, context);
-                body.iter().for_each(|s| self.visit_statement(s, context));
+                self.visit_statement_list(body, &context.in_loop());
             }
             AstStatement::RepeatLoopStatement {
                 condition, body, ..
             } => {
                 self.visit_statement(condition, context);
-                body.iter().for_each(|s| self.visit_statement(s, context));
+                self.visit_statement_list(body, &context.in_loop());
             }
             AstStatement::CaseStatement {
                 selector,
@@ -268,11 +388,9 @@ impl Validator {
                 self.visit_statement(selector, context);
                 case_blocks.iter().for_each(|b| {
                     self.visit_statement(b.condition.as_ref(), context);
-                    b.body.iter().for_each(|s| self.visit_statement(s, context));
+                    self.visit_statement_list(&b.body, context);
                 });
-                else_block
-                    .iter()
-                    .for_each(|s| self.visit_statement(s, context));
+                self.visit_statement_list(else_block, context);
             }
             AstStatement::CaseCondition { condition, .. } => {
                 self.visit_statement(condition, context)
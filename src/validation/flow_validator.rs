@@ -0,0 +1,490 @@
+use std::collections::{HashMap, HashSet};
+
+use indexmap::IndexMap;
+
+use super::ValidationContext;
+use crate::{
+    ast::{
+        AstStatement, CompilationUnit, ConditionalBlock, Implementation, NewLines, Pou, PouType,
+        SourceRange, VariableBlockType,
+    },
+    index::{Index, VariableType},
+    resolver::{AnnotationMap, AnnotationMapImpl, StatementAnnotation},
+    typesystem::DataTypeInformation,
+    Diagnostic,
+};
+
+/// the variables that may not be assigned yet at a point of a body (by their lower case
+/// qualified name), each with the branches taken on a path on which it is not assigned
+type Unassigned = IndexMap<String, Vec<String>>;
+
+/// validates that the temporary variables of a body are assigned before they are read. Every
+/// call of a POU starts with new temporary variables, as do the local variables of functions
+/// and methods
+pub struct FlowValidator {
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl FlowValidator {
+    pub fn new() -> FlowValidator {
+        FlowValidator {
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// validates the bodies of the given unit's POUs, the bodies of generic functions are not
+    /// validated
+    pub fn visit_unit(
+        &mut self,
+        annotations: &AnnotationMapImpl,
+        index: &Index,
+        unit: &CompilationUnit,
+    ) {
+        for pou in unit.units.iter().filter(|it| it.generics.is_empty()) {
+            //the actions of a POU share its variables, only its own body is analysed
+            if let Some(implementation) = unit.implementations.iter().find(|it| it.name == pou.name)
+            {
+                let context = ValidationContext {
+                    ast_annotation: annotations,
+                    index,
+                    qualifier: Some(pou.name.as_str()),
+                    is_in_loop: false,
+                };
+                self.validate_body(pou, implementation, &unit.new_lines, &context);
+            }
+        }
+    }
+
+    fn validate_body(
+        &mut self,
+        pou: &Pou,
+        implementation: &Implementation,
+        new_lines: &NewLines,
+        context: &ValidationContext,
+    ) {
+        let has_temporary_locals =
+            matches!(pou.pou_type, PouType::Function | PouType::Method { .. });
+        //the variables with an initial value and the instances of structs and function blocks
+        //(whose members may have initial values) are assigned at the start of the body
+        let variables = pou
+            .variable_blocks
+            .iter()
+            .filter(|it| {
+                it.variable_block_type == VariableBlockType::Temp
+                    || (it.variable_block_type == VariableBlockType::Local
+                        && has_temporary_locals
+                        && !it.constant)
+            })
+            .flat_map(|it| it.variables.iter())
+            .filter(|it| it.initializer.is_none() && it.address.is_none())
+            .filter_map(|it| context.index.find_member(&pou.name, &it.name))
+            .filter(|it| {
+                let data_type = context.index.get_effective_type_by_name(it.get_type_name());
+                data_type.initial_value.is_none()
+                    && !matches!(
+                        data_type.get_type_information(),
+                        DataTypeInformation::Struct { .. }
+                    )
+            })
+            .map(|it| {
+                (
+                    it.get_qualified_name().to_lowercase(),
+                    it.get_name().to_string(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        if variables.is_empty() {
+            return;
+        }
+
+        let mut analysis = FlowAnalysis {
+            context,
+            new_lines,
+            names: &variables,
+            reported: HashSet::new(),
+            loops: vec![],
+            diagnostics: vec![],
+        };
+        let unassigned = variables
+            .keys()
+            .map(|it| (it.clone(), vec![]))
+            .collect::<Unassigned>();
+        analysis.visit_statements(&implementation.statements, Some(unassigned));
+        self.diagnostics.append(&mut analysis.diagnostics);
+    }
+}
+
+/// the states at the EXIT and CONTINUE statements of a loop
+#[derive(Default)]
+struct LoopExits {
+    exits: Vec<Unassigned>,
+    continues: Vec<Unassigned>,
+}
+
+/// follows the paths through a body. The state of a point of the body is `None` if the point
+/// is never reached (e.g. after a RETURN)
+struct FlowAnalysis<'a, 'c> {
+    context: &'a ValidationContext<'c>,
+    new_lines: &'a NewLines,
+    /// the declared names of the analysed variables by their lower case qualified name
+    names: &'a HashMap<String, String>,
+    /// the variables that were reported already, a variable is reported only once
+    reported: HashSet<String>,
+    /// the exits of the loops enclosing the current statement
+    loops: Vec<LoopExits>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a, 'c> FlowAnalysis<'a, 'c> {
+    fn visit_statements(
+        &mut self,
+        statements: &[AstStatement],
+        state: Option<Unassigned>,
+    ) -> Option<Unassigned> {
+        statements
+            .iter()
+            .fold(state, |state, it| self.visit_statement(it, state?))
+    }
+
+    fn visit_statement(
+        &mut self,
+        statement: &AstStatement,
+        mut state: Unassigned,
+    ) -> Option<Unassigned> {
+        match statement {
+            AstStatement::Assignment { left, right, .. } => {
+                self.visit_expression(right, &mut state);
+                self.visit_assignment_target(left, &mut state);
+            }
+            AstStatement::IfStatement {
+                blocks,
+                else_block,
+                location,
+                ..
+            } => {
+                let line = self.get_line(location);
+                let mut branches = vec![];
+                for (i, block) in blocks.iter().enumerate() {
+                    self.visit_expression(&block.condition, &mut state);
+                    let branch = if i == 0 {
+                        format!("the IF at line {} takes the THEN branch", line)
+                    } else {
+                        format!(
+                            "the IF at line {} takes the ELSIF branch at line {}",
+                            line,
+                            self.get_line(&block.condition.get_location())
+                        )
+                    };
+                    branches.push(self.visit_branch(&block.body, &state, &branch));
+                }
+                let branch = if else_block.is_empty() {
+                    format!("the IF at line {} takes none of its branches", line)
+                } else {
+                    format!("the IF at line {} takes the ELSE branch", line)
+                };
+                branches.push(self.visit_branch(else_block, &state, &branch));
+                return merge(branches);
+            }
+            AstStatement::CaseStatement {
+                selector,
+                case_blocks,
+                else_block,
+                location,
+                ..
+            } => {
+                self.visit_expression(selector, &mut state);
+                let line = self.get_line(location);
+                let mut branches = self.visit_case_blocks(case_blocks, &state, line);
+                let branch = if else_block.is_empty() {
+                    format!("the CASE at line {} matches none of its conditions", line)
+                } else {
+                    format!("the CASE at line {} takes the ELSE branch", line)
+                };
+                branches.push(self.visit_branch(else_block, &state, &branch));
+                return merge(branches);
+            }
+            AstStatement::ForLoopStatement {
+                counter,
+                start,
+                end,
+                by_step,
+                body,
+                location,
+                ..
+            } => {
+                self.visit_expression(start, &mut state);
+                self.visit_expression(end, &mut state);
+                if let Some(by_step) = by_step {
+                    self.visit_expression(by_step, &mut state);
+                }
+                self.visit_assignment_target(counter, &mut state);
+                return self.visit_loop("FOR", body, state, location);
+            }
+            AstStatement::WhileLoopStatement {
+                condition,
+                body,
+                location,
+                ..
+            } => {
+                self.visit_expression(condition, &mut state);
+                return self.visit_loop("WHILE", body, state, location);
+            }
+            AstStatement::RepeatLoopStatement {
+                condition, body, ..
+            } => {
+                //the body is executed at least once
+                self.loops.push(LoopExits::default());
+                let end = self.visit_statements(body, Some(state));
+                let exits = self.loops.pop().unwrap_or_default();
+                let end = merge(
+                    std::iter::once(end)
+                        .chain(exits.continues.into_iter().map(Some))
+                        .collect(),
+                );
+                let end = end.map(|mut state| {
+                    self.visit_expression(condition, &mut state);
+                    state
+                });
+                return merge(
+                    std::iter::once(end)
+                        .chain(exits.exits.into_iter().map(Some))
+                        .collect(),
+                );
+            }
+            AstStatement::ExitStatement { .. } => {
+                if let Some(exits) = self.loops.last_mut() {
+                    exits.exits.push(state);
+                }
+                return None;
+            }
+            AstStatement::ContinueStatement { .. } => {
+                if let Some(exits) = self.loops.last_mut() {
+                    exits.continues.push(state);
+                }
+                return None;
+            }
+            AstStatement::ReturnStatement { .. } => return None,
+            _ => self.visit_expression(statement, &mut state),
+        }
+        Some(state)
+    }
+
+    /// visits the given statements of a branch, the branch is added to the paths of the
+    /// variables that are not assigned yet
+    fn visit_branch(
+        &mut self,
+        statements: &[AstStatement],
+        state: &Unassigned,
+        branch: &str,
+    ) -> Option<Unassigned> {
+        let state = state
+            .iter()
+            .map(|(name, path)| {
+                let mut path = path.clone();
+                path.push(branch.to_string());
+                (name.clone(), path)
+            })
+            .collect();
+        self.visit_statements(statements, Some(state))
+    }
+
+    fn visit_case_blocks(
+        &mut self,
+        case_blocks: &[ConditionalBlock],
+        state: &Unassigned,
+        line: usize,
+    ) -> Vec<Option<Unassigned>> {
+        case_blocks
+            .iter()
+            .map(|block| {
+                let branch = format!(
+                    "the CASE at line {} takes the branch at line {}",
+                    line,
+                    self.get_line(&block.condition.get_location())
+                );
+                self.visit_branch(&block.body, state, &branch)
+            })
+            .collect()
+    }
+
+    /// visits the body of a FOR or WHILE loop, which may not be entered at all
+    fn visit_loop(
+        &mut self,
+        kind: &str,
+        body: &[AstStatement],
+        state: Unassigned,
+        location: &SourceRange,
+    ) -> Option<Unassigned> {
+        let line = self.get_line(location);
+        self.loops.push(LoopExits::default());
+        let end = self.visit_branch(
+            body,
+            &state,
+            &format!("the {} loop at line {} is entered", kind, line),
+        );
+        let exits = self.loops.pop().unwrap_or_default();
+        let skipped = self.visit_branch(
+            &[],
+            &state,
+            &format!("the {} loop at line {} is not entered", kind, line),
+        );
+        merge(
+            vec![skipped, end]
+                .into_iter()
+                .chain(exits.exits.into_iter().map(Some))
+                .chain(exits.continues.into_iter().map(Some))
+                .collect(),
+        )
+    }
+
+    /// visits an expression, the variables it refers to are read
+    fn visit_expression(&mut self, expression: &AstStatement, state: &mut Unassigned) {
+        match expression {
+            AstStatement::CallStatement {
+                operator,
+                parameters,
+                ..
+            } => {
+                self.visit_expression(operator, state);
+                self.visit_arguments(operator, (**parameters).as_ref(), state);
+            }
+            _ => {
+                if let Some(StatementAnnotation::Variable { qualified_name, .. }) =
+                    self.context.ast_annotation.get(expression)
+                {
+                    self.read(&qualified_name.to_lowercase(), expression, state);
+                }
+                for child in expression.get_children() {
+                    self.visit_expression(child, state);
+                }
+            }
+        }
+    }
+
+    /// visits the arguments of a call, the variables passed to outputs and in-outs (or to REF
+    /// and ADR) are assigned by the call
+    fn visit_arguments(
+        &mut self,
+        operator: &AstStatement,
+        parameters: Option<&AstStatement>,
+        state: &mut Unassigned,
+    ) {
+        let call_name = self.context.ast_annotation.get_call_name(operator);
+        let takes_reference = call_name.map_or(false, |it| {
+            it.eq_ignore_ascii_case("REF") || it.eq_ignore_ascii_case("ADR")
+        });
+        let declared_parameters = call_name
+            .map(|it| self.context.index.get_container_members(it))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|it| {
+                matches!(
+                    it.get_variable_type(),
+                    VariableType::Input | VariableType::Output | VariableType::InOut
+                )
+            })
+            .collect::<Vec<_>>();
+        let arguments = parameters
+            .map(AstStatement::get_as_list)
+            .unwrap_or_default();
+        for (i, argument) in arguments.into_iter().enumerate() {
+            match argument {
+                AstStatement::Assignment { left, right, .. } => {
+                    let parameter_type = match self.context.ast_annotation.get(left) {
+                        Some(StatementAnnotation::Variable { qualified_name, .. }) => self
+                            .context
+                            .index
+                            .find_fully_qualified_variable(qualified_name)
+                            .map(|it| it.get_variable_type()),
+                        _ => None,
+                    };
+                    if parameter_type == Some(VariableType::InOut) {
+                        self.visit_assignment_target(right, state);
+                    } else {
+                        self.visit_expression(right, state);
+                    }
+                }
+                AstStatement::OutputAssignment { right, .. } => {
+                    self.visit_assignment_target(right, state)
+                }
+                _ => {
+                    let parameter_type =
+                        declared_parameters.get(i).map(|it| it.get_variable_type());
+                    if takes_reference
+                        || matches!(
+                            parameter_type,
+                            Some(VariableType::Output) | Some(VariableType::InOut)
+                        )
+                    {
+                        self.visit_assignment_target(argument, state);
+                    } else {
+                        self.visit_expression(argument, state);
+                    }
+                }
+            }
+        }
+    }
+
+    /// visits the target of an assignment, the assigned variable is assigned even if only an
+    /// element or a member of it is assigned
+    fn visit_assignment_target(&mut self, target: &AstStatement, state: &mut Unassigned) {
+        match target {
+            AstStatement::Reference { .. } => self.assign(target, state),
+            AstStatement::QualifiedReference { elements, .. } => {
+                if let Some((first, members)) = elements.split_first() {
+                    self.visit_assignment_target(first, state);
+                    members
+                        .iter()
+                        .for_each(|it| self.visit_expression(it, state));
+                }
+            }
+            AstStatement::ArrayAccess {
+                reference, access, ..
+            } => {
+                self.visit_expression(access, state);
+                self.visit_assignment_target(reference, state);
+            }
+            _ => self.visit_expression(target, state),
+        }
+    }
+
+    fn assign(&mut self, reference: &AstStatement, state: &mut Unassigned) {
+        if let Some(StatementAnnotation::Variable { qualified_name, .. }) =
+            self.context.ast_annotation.get(reference)
+        {
+            state.remove(&qualified_name.to_lowercase());
+        }
+    }
+
+    fn read(&mut self, qualified_name: &str, reference: &AstStatement, state: &mut Unassigned) {
+        if let Some(path) = state.remove(qualified_name) {
+            if self.reported.insert(qualified_name.to_string()) {
+                let name = self
+                    .names
+                    .get(qualified_name)
+                    .map(String::as_str)
+                    .unwrap_or(qualified_name);
+                self.diagnostics.push(Diagnostic::read_before_assignment(
+                    name,
+                    &path,
+                    reference.get_location(),
+                ));
+            }
+        }
+    }
+
+    fn get_line(&self, location: &SourceRange) -> usize {
+        self.new_lines.get_line_nr(location.get_start())
+    }
+}
+
+/// merges the states of the given paths, a variable may not be assigned if it may not be
+/// assigned on any of the paths
+fn merge(states: Vec<Option<Unassigned>>) -> Option<Unassigned> {
+    states.into_iter().flatten().reduce(|mut merged, state| {
+        for (name, path) in state {
+            merged.entry(name).or_insert(path);
+        }
+        merged
+    })
+}
@@ -1,5 +1,10 @@
-use super::ValidationContext;
-use crate::{ast::Pou, Diagnostic, PouType};
+use super::{validate_unique_declarations, ValidationContext};
+use crate::{
+    ast::{AstStatement, ConfigVariable, Configuration, DirectAccessType, Implementation, Pou},
+    index::{ImplementationType, VariableType},
+    typesystem::{DataType, DataTypeInformation, StructSource},
+    Diagnostic, PouType,
+};
 
 /// validates POUs
 pub struct PouValidator {
@@ -14,9 +19,255 @@ impl PouValidator {
     }
 
     pub fn validate_pou(&mut self, pou: &Pou, context: &ValidationContext) {
+        let members = pou
+            .variable_blocks
+            .iter()
+            .flat_map(|it| it.variables.iter())
+            .map(|it| (it.name.as_str(), &it.location));
+        self.diagnostics
+            .extend(validate_unique_declarations(members));
         if pou.pou_type == PouType::Function {
             self.validate_function(pou, context);
         };
+        for interface in &pou.interfaces {
+            self.validate_implemented_interface(pou, interface, context);
+        }
+        if let Some(super_class) = &pou.super_class {
+            self.validate_super_class(pou, super_class, context);
+        }
+        if let PouType::Method { owner_class } = &pou.pou_type {
+            self.validate_override(pou, owner_class, context);
+        }
+        if matches!(
+            pou.pou_type,
+            PouType::FunctionBlock | PouType::Class | PouType::Program
+        ) {
+            //an instance that contains an instance of itself would need infinite memory
+            if let Some(cycle) = context.index.find_cyclic_struct_definition(&pou.name) {
+                self.diagnostics.push(Diagnostic::cyclic_type_definition(
+                    &cycle,
+                    pou.location.clone(),
+                ));
+            }
+        }
+    }
+
+    /// validates that the program instances of the configuration's resources are instances of
+    /// programs scheduled by tasks of their resource, that event tasks are triggered by
+    /// global variables and that VAR_CONFIG entries address existing `AT %I*`-variables
+    pub fn validate_configuration(
+        &mut self,
+        configuration: &Configuration,
+        context: &ValidationContext,
+    ) {
+        for resource in &configuration.resources {
+            for program in &resource.programs {
+                let is_program = matches!(
+                    context
+                        .index
+                        .get_pou_types()
+                        .get(&program.program.to_lowercase())
+                        .map(DataType::get_type_information),
+                    Some(DataTypeInformation::Struct {
+                        source: StructSource::Pou(PouType::Program),
+                        ..
+                    })
+                );
+                if !is_program {
+                    self.diagnostics.push(Diagnostic::invalid_program_instance(
+                        &program.name,
+                        &program.program,
+                        program.location.clone(),
+                    ));
+                }
+                if let Some(task) = &program.task {
+                    if !resource
+                        .tasks
+                        .iter()
+                        .any(|it| it.name.eq_ignore_ascii_case(task))
+                    {
+                        self.diagnostics.push(Diagnostic::unresolved_task(
+                            task,
+                            &resource.name,
+                            program.location.clone(),
+                        ));
+                    }
+                }
+            }
+
+            for task in &resource.tasks {
+                let single = context
+                    .index
+                    .find_task(
+                        &format!("{}.{}", configuration.name, resource.name),
+                        &task.name,
+                    )
+                    .and_then(|it| it.get_single());
+                if let Some(trigger) = &task.single {
+                    if single
+                        .and_then(|it| context.index.find_global_variable(it))
+                        .is_none()
+                    {
+                        self.diagnostics.push(Diagnostic::unresolved_reference(
+                            single.unwrap_or_default(),
+                            trigger.get_location(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        for variable in &configuration.config_variables {
+            self.validate_config_variable(variable, context);
+        }
+    }
+
+    fn validate_config_variable(&mut self, variable: &ConfigVariable, context: &ValidationContext) {
+        let target = match context.index.find_instance_variable(&variable.reference) {
+            Some(target) => target,
+            None => {
+                self.diagnostics.push(Diagnostic::unresolved_reference(
+                    &variable.reference,
+                    variable.location.clone(),
+                ));
+                return;
+            }
+        };
+
+        let (direction, access) = match &variable.address {
+            AstStatement::HardwareAccess {
+                direction, access, ..
+            } => (direction, access),
+            _ => return,
+        };
+        let reason = match target.get_hardware_binding() {
+            Some(binding) if binding.access != DirectAccessType::Template => {
+                Some("the variable is already bound to an address")
+            }
+            Some(binding) if binding.direction != *direction => {
+                Some("the direction does not match the variable's declaration")
+            }
+            Some(_) if *access == DirectAccessType::Template => {
+                Some("the address must not be a template")
+            }
+            Some(_) => {
+                let declared_type = context
+                    .index
+                    .get_effective_type_by_name(&variable.data_type)
+                    .get_name();
+                let variable_type = context
+                    .index
+                    .get_effective_type_by_name(target.get_type_name())
+                    .get_name();
+                if declared_type.eq_ignore_ascii_case(variable_type) {
+                    None
+                } else {
+                    Some("the type does not match the variable's declaration")
+                }
+            }
+            None => Some("the variable is not declared with an AT %I*, %Q* or %M* template"),
+        };
+        if let Some(reason) = reason {
+            self.diagnostics.push(Diagnostic::invalid_config_variable(
+                &variable.reference,
+                reason,
+                variable.location.clone(),
+            ));
+        }
+    }
+
+    /// validates that classes only extend classes, function blocks only extend function blocks
+    /// and that no pou extends itself
+    fn validate_super_class(&mut self, pou: &Pou, super_class: &str, context: &ValidationContext) {
+        let super_type = context
+            .index
+            .get_pou_types()
+            .get(&super_class.to_lowercase())
+            .map(DataType::get_type_information);
+        let is_valid = match super_type {
+            Some(DataTypeInformation::Struct {
+                source: StructSource::Pou(super_pou_type),
+                ..
+            }) => *super_pou_type == pou.pou_type,
+            _ => false,
+        };
+        if !is_valid {
+            self.diagnostics.push(Diagnostic::invalid_super_class(
+                &pou.name,
+                super_class,
+                pou.location.clone(),
+            ));
+        } else if context.index.has_cyclic_inheritance(&pou.name) {
+            self.diagnostics.push(Diagnostic::cyclic_inheritance(
+                &pou.name,
+                pou.location.clone(),
+            ));
+        }
+    }
+
+    /// validates that a method overriding a method of a super class has the same signature
+    fn validate_override(&mut self, method: &Pou, owner_class: &str, context: &ValidationContext) {
+        let overridden = context.index.find_super_class(owner_class).and_then(|it| {
+            context
+                .index
+                .find_method(it, Pou::calc_return_name(&method.name))
+        });
+        if let Some(overridden) = overridden {
+            if get_signature(context, &method.name)
+                != get_signature(context, overridden.get_call_name())
+            {
+                self.diagnostics.push(Diagnostic::incompatible_override(
+                    &method.name,
+                    overridden.get_call_name(),
+                    method.location.clone(),
+                ));
+            }
+        }
+    }
+
+    /// validates that the given pou implements all methods of the given interface
+    /// with the interface's signature
+    fn validate_implemented_interface(
+        &mut self,
+        pou: &Pou,
+        interface: &str,
+        context: &ValidationContext,
+    ) {
+        if !context.index.is_interface(interface) {
+            self.diagnostics.push(Diagnostic::invalid_interface(
+                interface,
+                pou.location.clone(),
+            ));
+            return;
+        }
+
+        for interface_method in context.index.get_interface_methods(interface) {
+            let interface_method_name = interface_method.get_call_name();
+            //the method may be inherited from a super class
+            match context
+                .index
+                .find_method(&pou.name, Pou::calc_return_name(interface_method_name))
+            {
+                None => self.diagnostics.push(Diagnostic::missing_interface_method(
+                    &pou.name,
+                    Pou::calc_return_name(interface_method_name),
+                    interface,
+                    pou.location.clone(),
+                )),
+                Some(method)
+                    if get_signature(context, interface_method_name)
+                        != get_signature(context, method.get_call_name()) =>
+                {
+                    self.diagnostics
+                        .push(Diagnostic::incompatible_interface_method(
+                            method.get_call_name(),
+                            interface,
+                            pou.location.clone(),
+                        ))
+                }
+                _ => {}
+            }
+        }
     }
 
     pub fn validate_function(&mut self, pou: &Pou, context: &ValidationContext) {
@@ -27,4 +278,57 @@ impl PouValidator {
                 .push(Diagnostic::function_return_missing(pou.location.to_owned()));
         }
     }
+
+    /// validates that actions are only declared for PROGRAMs and FUNCTION_BLOCKs
+    pub fn validate_action_container(
+        &mut self,
+        implementation: &Implementation,
+        context: &ValidationContext,
+    ) {
+        if implementation.pou_type == PouType::Action {
+            let container_type = context
+                .index
+                .find_implementation(&implementation.type_name)
+                .map(|it| it.get_implementation_type());
+            if !matches!(
+                container_type,
+                Some(ImplementationType::Program) | Some(ImplementationType::FunctionBlock)
+            ) {
+                self.diagnostics.push(Diagnostic::invalid_action_container(
+                    &implementation.type_name,
+                    implementation.location.clone(),
+                ));
+            }
+        }
+    }
+}
+
+/// returns the parameters (variable type, name, type name) and the return type of the given pou
+fn get_signature(
+    context: &ValidationContext,
+    pou_name: &str,
+) -> (Vec<(VariableType, String, String)>, Option<String>) {
+    let parameters = context
+        .index
+        .get_container_members(pou_name)
+        .into_iter()
+        .filter(|it| {
+            matches!(
+                it.get_variable_type(),
+                VariableType::Input | VariableType::Output | VariableType::InOut
+            )
+        })
+        .map(|it| {
+            (
+                it.get_variable_type(),
+                it.get_name().to_lowercase(),
+                it.get_type_name().to_lowercase(),
+            )
+        })
+        .collect();
+    let return_type = context
+        .index
+        .find_return_type(pou_name)
+        .map(|it| it.get_name().to_lowercase());
+    (parameters, return_type)
 }
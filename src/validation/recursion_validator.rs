@@ -0,0 +1,54 @@
+use crate::{
+    ast::{CompilationUnit, SourceRange},
+    index::{call_graph::CallGraph, Index},
+    resolver::AnnotationMapImpl,
+    Diagnostic,
+};
+
+/// validates that no POU calls itself, directly or through other POUs. Most PLC runtimes
+/// prohibit recursions, since the stack they use cannot be bounded. A recursion may span the
+/// POUs of several units, so the calls of all units are collected before the units are validated
+pub struct RecursionValidator {
+    /// the name of the calling POU, the chain of calls and the location of every call closing a
+    /// recursion
+    recursive_calls: Vec<(String, Vec<String>, SourceRange)>,
+}
+
+impl RecursionValidator {
+    /// collects the recursions of the POUs of the given units
+    pub fn new<'u>(
+        units: impl IntoIterator<Item = &'u CompilationUnit>,
+        annotations: &AnnotationMapImpl,
+        index: &Index,
+    ) -> RecursionValidator {
+        let call_graph = CallGraph::new(units, annotations, index);
+        let recursive_calls = call_graph
+            .find_recursions()
+            .into_iter()
+            .map(|it| {
+                (
+                    it.call.caller.clone(),
+                    it.path.iter().map(|it| it.to_string()).collect(),
+                    it.call.location.clone(),
+                )
+            })
+            .collect();
+        RecursionValidator { recursive_calls }
+    }
+
+    /// returns an error for every call of the given unit's POUs that closes a recursion
+    pub fn validate_unit(&self, unit: &CompilationUnit) -> Vec<Diagnostic> {
+        self.recursive_calls
+            .iter()
+            .filter(|(caller, ..)| {
+                unit.implementations
+                    .iter()
+                    .any(|it| it.name.eq_ignore_ascii_case(caller))
+            })
+            .map(|(_, path, location)| {
+                let path = path.iter().map(String::as_str).collect::<Vec<_>>();
+                Diagnostic::recursive_call(&path, location.clone())
+            })
+            .collect()
+    }
+}
@@ -1,14 +1,15 @@
-use std::{convert::TryInto, mem::discriminant};
+use std::{collections::HashSet, convert::TryInto, mem::discriminant, ops::RangeInclusive};
 
 use super::ValidationContext;
 use crate::{
-    ast::{AstStatement, DirectAccessType, Operator, SourceRange},
-    index::{VariableIndexEntry, VariableType},
-    resolver::{AnnotationMap, StatementAnnotation},
+    ast::{self, AstStatement, ConditionalBlock, DirectAccessType, Operator, SourceRange},
+    index::{Index, VariableIndexEntry, VariableType},
+    resolver::{const_evaluator, AnnotationMap, StatementAnnotation},
     typesystem::{
-        DataType, DataTypeInformation, Dimension, BOOL_TYPE, DATE_AND_TIME_TYPE, DATE_TYPE,
-        DINT_TYPE, INT_TYPE, LINT_TYPE, LREAL_TYPE, SINT_TYPE, STRING_TYPE, TIME_OF_DAY_TYPE,
-        TIME_TYPE, UDINT_TYPE, UINT_TYPE, ULINT_TYPE, USINT_TYPE, VOID_TYPE, WSTRING_TYPE,
+        self, DataType, DataTypeInformation, Dimension, NativeRealType, BOOL_TYPE,
+        DATE_AND_TIME_TYPE, DATE_TYPE, DINT_TYPE, INT_TYPE, LINT_TYPE, LREAL_TYPE, REAL_SIZE,
+        REAL_TYPE, SINT_TYPE, STRING_TYPE, TIME_OF_DAY_TYPE, TIME_TYPE, UDINT_TYPE, UINT_TYPE,
+        ULINT_TYPE, USINT_TYPE, VOID_TYPE, WSTRING_TYPE,
     },
     Diagnostic,
 };
@@ -39,6 +40,16 @@ impl StatementValidator {
                 name, location, id, ..
             } => {
                 self.validate_reference(id, name, location, context);
+                self.validate_access(statement, name, location, context);
+                if let Some(StatementAnnotation::Property { getter: None, .. }) =
+                    context.ast_annotation.get(statement)
+                {
+                    self.diagnostics.push(Diagnostic::invalid_property_access(
+                        name,
+                        "GET",
+                        location.clone(),
+                    ));
+                }
             }
             AstStatement::CastStatement {
                 location,
@@ -115,6 +126,30 @@ impl StatementValidator {
                 }
             }
             AstStatement::Assignment { left, right, .. } => {
+                if let Some(StatementAnnotation::Property { setter: None, .. }) =
+                    context.ast_annotation.get(left.as_ref())
+                {
+                    let property = match left.as_ref() {
+                        AstStatement::QualifiedReference { elements, .. } => elements.last(),
+                        _ => Some(left.as_ref()),
+                    };
+                    let property = match property {
+                        Some(AstStatement::Reference { name, .. }) => name.as_str(),
+                        _ => "",
+                    };
+                    self.diagnostics.push(Diagnostic::invalid_property_access(
+                        property,
+                        "SET",
+                        left.get_location(),
+                    ));
+                }
+                // the elements of a constant array are constant as well (e.g. `c[1] := 2`)
+                if let Some(array_name) = find_constant_array_of_element(left, context) {
+                    self.diagnostics.push(Diagnostic::cannot_assign_to_constant(
+                        array_name,
+                        left.get_location(),
+                    ));
+                }
                 if let Some(StatementAnnotation::Variable {
                     constant,
                     qualified_name: l_qualified_name,
@@ -139,6 +174,28 @@ impl StatementValidator {
                         .get_type_or_void(right, context.index)
                         .get_type_information();
 
+                    self.diagnostics
+                        .extend(validate_assigned_literal(right, l_effective_type));
+
+                    // only instances implementing an interface can be assigned to it
+                    if context.index.is_interface(l_resulting_type) {
+                        let r_type_name = context
+                            .ast_annotation
+                            .get_type_or_void(right, context.index)
+                            .get_name();
+                        if !r_type_name.eq_ignore_ascii_case(l_resulting_type)
+                            && !context
+                                .index
+                                .implements_interface(r_type_name, l_resulting_type)
+                        {
+                            self.diagnostics.push(Diagnostic::invalid_assignment(
+                                r_type_name,
+                                l_resulting_type,
+                                statement.get_location(),
+                            ));
+                        }
+                    }
+
                     // valid assignments -> char := literalString, char := char
                     // check if we assign to a character variable -> char := ..
                     if l_effective_type.is_character() {
@@ -173,6 +230,21 @@ impl StatementValidator {
                             l_effective_type.get_name(),
                             statement.get_location(),
                         ));
+                    } else if !context.index.is_interface(l_resulting_type)
+                        && !is_assignment_compatible(
+                            l_effective_type,
+                            r_effective_type,
+                            right,
+                            context.index,
+                        )
+                    {
+                        // e.g. a STRING cannot be assigned to a number (this includes the
+                        // assignment of a function's return value)
+                        self.diagnostics.push(Diagnostic::invalid_assignment(
+                            r_effective_type.get_name(),
+                            l_effective_type.get_name(),
+                            statement.get_location(),
+                        ));
                     }
                 }
             }
@@ -227,6 +299,34 @@ impl StatementValidator {
                 }
                 _ => self.validate_binary_expression(context, operator, left, right, statement),
             },
+            AstStatement::ExitStatement { location, .. } if !context.is_in_loop => {
+                self.diagnostics.push(Diagnostic::loop_control_outside_loop(
+                    "EXIT",
+                    location.clone(),
+                ));
+            }
+            AstStatement::ContinueStatement { location, .. } if !context.is_in_loop => {
+                self.diagnostics.push(Diagnostic::loop_control_outside_loop(
+                    "CONTINUE",
+                    location.clone(),
+                ));
+            }
+            AstStatement::CaseStatement { case_blocks, .. } => {
+                self.validate_case_conditions(case_blocks, context);
+            }
+            AstStatement::CallStatement {
+                operator,
+                parameters,
+                ..
+            } => {
+                if let Some(parameters) = parameters.as_ref() {
+                    self.validate_call_arguments(operator, parameters, context);
+                }
+                self.validate_conversion(operator, parameters, context);
+                self.validate_bit_shift(operator, parameters, context);
+                self.validate_numeric_function(operator, parameters, context);
+                self.validate_selection(operator, parameters, context);
+            }
             _ => (),
         }
         self.validate_type_nature(statement, context);
@@ -263,6 +363,368 @@ impl StatementValidator {
         }
     }
 
+    /// validates that no statement of the given block follows a RETURN, EXIT or CONTINUE,
+    /// such statements are never executed
+    pub fn validate_reachability(&mut self, statements: &[AstStatement]) {
+        let jump = statements.iter().enumerate().find_map(|(i, it)| match it {
+            AstStatement::ReturnStatement { .. } => Some((i, "RETURN")),
+            AstStatement::ExitStatement { .. } => Some((i, "EXIT")),
+            AstStatement::ContinueStatement { .. } => Some((i, "CONTINUE")),
+            _ => None,
+        });
+        if let Some((position, jump)) = jump {
+            let mut unreachable = statements[position + 1..]
+                .iter()
+                .filter(|it| !matches!(it, AstStatement::EmptyStatement { .. }));
+            if let Some(first) = unreachable.next() {
+                let last = unreachable.last().unwrap_or(first);
+                self.diagnostics.push(Diagnostic::unreachable_code(
+                    jump,
+                    first.get_location().span(&last.get_location()),
+                ));
+            }
+        }
+    }
+
+    /// validates that no value is matched by more than one condition of a case-statement
+    fn validate_case_conditions(
+        &mut self,
+        case_blocks: &[ConditionalBlock],
+        context: &ValidationContext,
+    ) {
+        let mut covered_values: Vec<RangeInclusive<i128>> = Vec::new();
+        for condition in case_blocks
+            .iter()
+            .flat_map(|it| ast::flatten_expression_list(&it.condition))
+        {
+            let (start, end) = match condition {
+                AstStatement::RangeStatement { start, end, .. } => (start.as_ref(), end.as_ref()),
+                _ => (condition, condition),
+            };
+            //conditions that cannot be resolved to a literal are not checked here
+            if let (Some(start), Some(end)) = (
+                evaluate_to_integer(start, context),
+                evaluate_to_integer(end, context),
+            ) {
+                let values = start..=end;
+                if covered_values
+                    .iter()
+                    .any(|it| it.start() <= values.end() && values.start() <= it.end())
+                {
+                    let value = if start == end {
+                        start.to_string()
+                    } else {
+                        format!("{}..{}", start, end)
+                    };
+                    self.diagnostics.push(Diagnostic::duplicate_case_condition(
+                        value.as_str(),
+                        condition.get_location(),
+                    ));
+                }
+                covered_values.push(values);
+            }
+        }
+    }
+
+    /// validates the arguments of a call statement
+    ///
+    /// - every parameter may only be assigned once
+    /// - output assignments (`=>`) only target VAR_OUTPUT parameters
+    /// - every argument passed to a VAR_IN_OUT parameter can be referenced
+    fn validate_call_arguments(
+        &mut self,
+        operator: &AstStatement,
+        parameters: &AstStatement,
+        context: &ValidationContext,
+    ) {
+        let pou_name = match context.ast_annotation.get(operator) {
+            Some(StatementAnnotation::Function { qualified_name, .. })
+            | Some(StatementAnnotation::Program { qualified_name }) => qualified_name.as_str(),
+            Some(StatementAnnotation::Variable { resulting_type, .. }) => resulting_type.as_str(),
+            _ => return,
+        };
+        let members = context.index.get_container_members(pou_name);
+        let declared_parameters = members
+            .iter()
+            .filter(|it| it.is_parameter())
+            .collect::<Vec<_>>();
+
+        let mut assigned_parameters = HashSet::new();
+        for (i, argument) in ast::flatten_expression_list(parameters)
+            .into_iter()
+            .enumerate()
+        {
+            let (parameter, value) = match argument {
+                AstStatement::Assignment { left, right, .. }
+                | AstStatement::OutputAssignment { left, right, .. } => {
+                    if let AstStatement::Reference { name, .. } = left.as_ref() {
+                        (context.index.find_member(pou_name, name), right.as_ref())
+                    } else {
+                        continue;
+                    }
+                }
+                _ => (declared_parameters.get(i).map(|it| **it), argument),
+            };
+
+            if let Some(parameter) = parameter {
+                if !assigned_parameters.insert(parameter.get_qualified_name()) {
+                    self.diagnostics
+                        .push(Diagnostic::duplicate_parameter_assignment(
+                            parameter.get_name(),
+                            argument.get_location(),
+                        ));
+                }
+
+                if matches!(argument, AstStatement::OutputAssignment { .. })
+                    && parameter.get_variable_type() != VariableType::Output
+                {
+                    self.diagnostics.push(Diagnostic::invalid_output_assignment(
+                        parameter.get_name(),
+                        argument.get_location(),
+                    ));
+                }
+
+                let is_assignable = matches!(
+                    value,
+                    AstStatement::Reference { .. }
+                        | AstStatement::QualifiedReference { .. }
+                        | AstStatement::ArrayAccess { .. }
+                        | AstStatement::PointerAccess { .. }
+                );
+                if parameter.get_variable_type() == VariableType::InOut && !is_assignable {
+                    self.diagnostics.push(Diagnostic::invalid_in_out_argument(
+                        parameter.get_name(),
+                        value.get_location(),
+                    ));
+                }
+
+                let parameter_type = get_auto_deref_type(
+                    context
+                        .index
+                        .get_effective_type_by_name(parameter.get_type_name())
+                        .get_type_information(),
+                    context.index,
+                );
+                let value_type = get_auto_deref_type(
+                    context
+                        .ast_annotation
+                        .get_type_or_void(value, context.index)
+                        .get_type_information(),
+                    context.index,
+                );
+                // an output is assigned to the argument instead
+                let is_compatible = if matches!(argument, AstStatement::OutputAssignment { .. }) {
+                    is_assignment_compatible(value_type, parameter_type, value, context.index)
+                } else {
+                    is_assignment_compatible(parameter_type, value_type, value, context.index)
+                };
+                if !is_compatible {
+                    self.diagnostics.push(Diagnostic::invalid_argument_type(
+                        parameter.get_name(),
+                        value_type.get_name(),
+                        parameter_type.get_name(),
+                        value.get_location(),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// validates that the inputs of a generated selection function are numbers, that the
+    /// selector of `SEL` is a `BOOL` and that the selector of `MUX` is an integer
+    fn validate_selection(
+        &mut self,
+        operator: &AstStatement,
+        parameters: &Option<AstStatement>,
+        context: &ValidationContext,
+    ) {
+        let function_name = match operator {
+            AstStatement::Reference { name, .. }
+                if typesystem::is_selection_function(name)
+                    && context.index.find_implementation(name).is_none() =>
+            {
+                name
+            }
+            _ => return,
+        };
+        let arguments = ast::get_implicit_arguments(parameters).unwrap_or_default();
+        if let Some((selector, inputs)) =
+            typesystem::split_selection_arguments(function_name, &arguments)
+        {
+            let expected_selector = if function_name.eq_ignore_ascii_case("SEL") {
+                BOOL_TYPE
+            } else {
+                "integer"
+            };
+            let selector = selector.map(|it| (*it, expected_selector));
+            for (argument, expected) in selector
+                .into_iter()
+                .chain(inputs.iter().map(|it| (*it, "number")))
+            {
+                if let Some(data_type) = context.ast_annotation.get_type(argument, context.index) {
+                    let information = context
+                        .index
+                        .get_intrinsic_type_by_name(data_type.get_name())
+                        .get_type_information();
+                    let is_valid = match expected {
+                        BOOL_TYPE => information.is_bool(),
+                        "integer" => {
+                            information.is_int()
+                                && !information.is_bool()
+                                && !information.is_date_or_time()
+                        }
+                        _ => information.is_numerical(),
+                    };
+                    if !is_valid {
+                        self.diagnostics
+                            .push(Diagnostic::invalid_selection_argument(
+                                function_name,
+                                data_type.get_name(),
+                                expected,
+                                argument.get_location(),
+                            ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// validates that the value and the amount of a generated bit-shift function are integers
+    /// and that a constant amount does not exceed the bit-width of the value
+    fn validate_bit_shift(
+        &mut self,
+        operator: &AstStatement,
+        parameters: &Option<AstStatement>,
+        context: &ValidationContext,
+    ) {
+        let function_name = match operator {
+            AstStatement::Reference { name, .. }
+                if typesystem::is_bit_shift_function(name)
+                    && context.index.find_implementation(name).is_none() =>
+            {
+                name
+            }
+            _ => return,
+        };
+        if let Some((value, amount)) = ast::get_argument_pair(parameters) {
+            let mut integer_types = Vec::new();
+            for argument in [value, amount] {
+                if let Some(data_type) = context.ast_annotation.get_type(argument, context.index) {
+                    let information = context
+                        .index
+                        .get_intrinsic_type_by_name(data_type.get_name())
+                        .get_type_information();
+                    if information.is_int() && !information.is_date_or_time() {
+                        integer_types.push(information);
+                    } else {
+                        self.diagnostics
+                            .push(Diagnostic::invalid_bit_shift_argument(
+                                function_name,
+                                data_type.get_name(),
+                                argument.get_location(),
+                            ));
+                    }
+                }
+            }
+            if let [value_type, _] = integer_types.as_slice() {
+                if let Some(bits) = evaluate_to_integer(amount, context)
+                    .filter(|bits| *bits >= value_type.get_semantic_size() as i128)
+                {
+                    self.diagnostics.push(Diagnostic::bit_shift_exceeds_width(
+                        function_name,
+                        bits.to_string().as_str(),
+                        value_type.get_name(),
+                        amount.get_location(),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// validates that the arguments of a generated numeric function (e.g. `SQRT(x)`) are numbers
+    fn validate_numeric_function(
+        &mut self,
+        operator: &AstStatement,
+        parameters: &Option<AstStatement>,
+        context: &ValidationContext,
+    ) {
+        let function_name = match operator {
+            AstStatement::Reference { name, .. }
+                if typesystem::is_numeric_function(name)
+                    && context.index.find_implementation(name).is_none() =>
+            {
+                name
+            }
+            _ => return,
+        };
+        let arguments = typesystem::get_numeric_function_arguments(function_name, parameters)
+            .unwrap_or_default();
+        for argument in arguments {
+            if let Some(data_type) = context.ast_annotation.get_type(argument, context.index) {
+                let information = context
+                    .index
+                    .get_intrinsic_type_by_name(data_type.get_name())
+                    .get_type_information();
+                let is_number = information.is_float()
+                    || (information.is_int()
+                        && !information.is_bool()
+                        && !information.is_character()
+                        && !information.is_date_or_time());
+                if !is_number {
+                    self.diagnostics.push(Diagnostic::invalid_numeric_argument(
+                        function_name,
+                        data_type.get_name(),
+                        argument.get_location(),
+                    ));
+                }
+            }
+        }
+    }
+
+    /// validates that a generated conversion function does not change the value of a constant
+    /// argument (e.g. `INT_TO_SINT(300)`), rounding a real to an integer is not reported
+    fn validate_conversion(
+        &mut self,
+        operator: &AstStatement,
+        parameters: &Option<AstStatement>,
+        context: &ValidationContext,
+    ) {
+        let function_name = match operator {
+            AstStatement::Reference { name, .. }
+                if context.index.find_implementation(name).is_none() =>
+            {
+                name
+            }
+            _ => return,
+        };
+        if let (Some((source, target)), Some(argument)) = (
+            typesystem::find_conversion(function_name, context.index),
+            ast::get_single_argument(parameters),
+        ) {
+            if let Ok(Some(value)) =
+                const_evaluator::evaluate(argument, context.qualifier, context.index)
+            {
+                let truncate = typesystem::is_truncating_conversion(function_name);
+                //the argument is converted to the source-type first
+                let lossy_type = [source, target].into_iter().find(|it| {
+                    let data_type = context
+                        .index
+                        .get_intrinsic_type_by_name(it.get_name())
+                        .get_type_information();
+                    is_lossy_conversion(&value, data_type, truncate)
+                });
+                if let Some(lossy_type) = lossy_type {
+                    self.diagnostics.push(Diagnostic::lossy_conversion(
+                        function_name,
+                        StatementValidator::get_literal_value(&value).as_str(),
+                        lossy_type.get_name(),
+                        argument.get_location(),
+                    ));
+                }
+            }
+        }
+    }
+
     fn validate_access_index(
         &mut self,
         context: &ValidationContext,
@@ -271,18 +733,8 @@ impl StatementValidator {
         target_type: &DataTypeInformation,
         location: &SourceRange,
     ) {
-        match *access_index {
-            AstStatement::LiteralInteger { value, .. } => {
-                if !access_type.is_in_range(value.try_into().unwrap_or_default(), target_type) {
-                    self.diagnostics
-                        .push(Diagnostic::incompatible_directaccess_range(
-                            &format!("{:?}", access_type),
-                            target_type.get_name(),
-                            access_type.get_range(target_type),
-                            location.clone(),
-                        ))
-                }
-            }
+        let index_value = match *access_index {
+            AstStatement::LiteralInteger { value, .. } => Some(value),
             AstStatement::Reference { .. } => {
                 let ref_type = context
                     .ast_annotation
@@ -292,10 +744,30 @@ impl StatementValidator {
                         .push(Diagnostic::incompatible_directaccess_variable(
                             ref_type.get_name(),
                             location.clone(),
-                        ))
+                        ));
+                    None
+                } else {
+                    //the index of a constant is known at compile time
+                    find_constant_int_value(context, access_index)
                 }
             }
             _ => unreachable!(),
+        };
+
+        if let Some(value) = index_value {
+            let is_in_range = value
+                .try_into()
+                .map(|index| access_type.is_in_range(index, target_type))
+                .unwrap_or(false);
+            if !is_in_range {
+                self.diagnostics
+                    .push(Diagnostic::incompatible_directaccess_range(
+                        &format!("{:?}", access_type),
+                        target_type.get_name(),
+                        access_type.get_range(target_type),
+                        location.clone(),
+                    ))
+            }
         }
     }
 
@@ -306,12 +778,13 @@ impl StatementValidator {
         dimension_index: usize,
         context: &ValidationContext,
     ) {
-        if let AstStatement::LiteralInteger { value, .. } = access {
+        //the index of a literal or a constant expression is known at compile time
+        if let Some(value) = evaluate_to_integer(access, context) {
             let dimension = dimensions.get(dimension_index);
             if let Some(dimension) = dimension {
                 let range = dimension.get_range(context.index);
                 if let Ok(range) = range {
-                    if !(&range.start <= value && &range.end >= value) {
+                    if !(range.start <= value && range.end >= value) {
                         self.diagnostics
                             .push(Diagnostic::incompatible_array_access_range(
                                 range,
@@ -348,6 +821,41 @@ impl StatementValidator {
         }
     }
 
+    /// validates that a PRIVATE method is only referenced from inside its own class
+    fn validate_access(
+        &mut self,
+        statement: &AstStatement,
+        ref_name: &str,
+        location: &SourceRange,
+        context: &ValidationContext,
+    ) {
+        let private_owner = match context.ast_annotation.get(statement) {
+            Some(StatementAnnotation::Function { qualified_name, .. }) => context
+                .index
+                .find_implementation(qualified_name)
+                .filter(|it| it.get_access() == Some(ast::AccessModifier::Private))
+                .and_then(|it| it.get_associated_class_name()),
+            _ => None,
+        };
+
+        if let Some(owner) = private_owner {
+            let current_instance = context
+                .qualifier
+                .and_then(|it| context.index.find_implementation(it))
+                .and_then(|it| it.get_instance_type_name());
+            if !current_instance
+                .map(|it| it.eq_ignore_ascii_case(owner))
+                .unwrap_or(false)
+            {
+                self.diagnostics.push(Diagnostic::illegal_access(
+                    ref_name,
+                    owner,
+                    location.clone(),
+                ));
+            }
+        }
+    }
+
     /// validates a literal statement with a dedicated type-prefix (e.g. INT#3)
     ///
     /// checks whether ...
@@ -389,7 +897,7 @@ impl StatementValidator {
         if !is_typable_literal(literal) {
             self.diagnostics
                 .push(Diagnostic::literal_expected(location.clone()))
-        } else if is_date_or_time_type(cast_type) || is_date_or_time_type(literal_type) {
+        } else if cast_type.is_date_or_time() || literal_type.is_date_or_time() {
             self.diagnostics.push(Diagnostic::incompatible_literal_cast(
                 cast_type.get_name(),
                 literal_type.get_name(),
@@ -415,6 +923,12 @@ impl StatementValidator {
                     location.clone(),
                 ));
             }
+        } else if cast_type.is_float() && !is_in_float_range(literal, cast_type) {
+            self.diagnostics.push(Diagnostic::literal_out_of_range(
+                StatementValidator::get_literal_value(literal).as_str(),
+                cast_type.get_name(),
+                location.clone(),
+            ));
         } else if discriminant(cast_type) != discriminant(literal_type) {
             // different types
             // REAL#100 is fine, other differences are not
@@ -489,6 +1003,32 @@ impl StatementValidator {
         right: &AstStatement,
         binary_statement: &AstStatement,
     ) {
+        if matches!(operator, Operator::Division | Operator::Modulo)
+            && evaluate_to_integer(right, context) == Some(0)
+        {
+            self.diagnostics.push(Diagnostic::division_by_zero(
+                binary_statement.get_location(),
+            ));
+        }
+
+        //AND_THEN and OR_ELSE only accept BOOL operands
+        if operator.is_short_circuit_operator() {
+            for operand in [left, right] {
+                let operand_type = context
+                    .ast_annotation
+                    .get_type_or_void(operand, context.index)
+                    .get_type_information();
+                if !operand_type.is_bool() {
+                    self.diagnostics.push(Diagnostic::invalid_operand(
+                        &operator.to_string(),
+                        operand_type.get_name(),
+                        BOOL_TYPE,
+                        operand.get_location(),
+                    ));
+                }
+            }
+        }
+
         let left_type = context
             .ast_annotation
             .get_type_or_void(left, context.index)
@@ -518,6 +1058,14 @@ impl StatementValidator {
 }
 
 /// returns true if the index contains a compare function for the given operator and type
+/// returns the integer value of the given statement if it can be resolved to a constant
+fn evaluate_to_integer(statement: &AstStatement, context: &ValidationContext) -> Option<i128> {
+    match const_evaluator::evaluate(statement, context.qualifier, context.index) {
+        Ok(Some(AstStatement::LiteralInteger { value, .. })) => Some(value),
+        _ => None,
+    }
+}
+
 fn compare_function_exists(
     type_name: &str,
     operator: &Operator,
@@ -568,11 +1116,260 @@ fn compare_function_exists(
     false
 }
 
-fn is_date_or_time_type(cast_type: &crate::typesystem::DataTypeInformation) -> bool {
-    return cast_type.get_name() == DATE_TYPE
-        || cast_type.get_name() == DATE_AND_TIME_TYPE
-        || cast_type.get_name() == TIME_OF_DAY_TYPE
-        || cast_type.get_name() == TIME_TYPE;
+/// returns a diagnostic if the given numeric literal does not fit into the type it is
+/// assigned to or if a real literal loses precision when stored as a `REAL`
+pub(super) fn validate_assigned_literal(
+    literal: &AstStatement,
+    target_type: &DataTypeInformation,
+) -> Option<Diagnostic> {
+    match literal {
+        AstStatement::LiteralInteger {
+            value, location, ..
+        } if !target_type.is_bool() && !target_type.is_in_int_range(*value) => {
+            Some(Diagnostic::literal_out_of_range(
+                value.to_string().as_str(),
+                target_type.get_name(),
+                location.clone(),
+            ))
+        }
+        AstStatement::LiteralReal {
+            value, location, ..
+        } if target_type.is_float() && target_type.get_size() == REAL_SIZE => {
+            if !is_in_float_range(literal, target_type) {
+                Some(Diagnostic::literal_out_of_range(
+                    value,
+                    target_type.get_name(),
+                    location.clone(),
+                ))
+            } else if typesystem::get_real_type_name_for(value) != REAL_TYPE {
+                Some(Diagnostic::literal_precision_loss(
+                    value,
+                    target_type.get_name(),
+                    location.clone(),
+                ))
+            } else {
+                None
+            }
+        }
+        AstStatement::UnaryExpression {
+            operator: Operator::Minus,
+            value,
+            ..
+        } => validate_assigned_literal(value, target_type),
+        _ => None,
+    }
+}
+
+/// returns true if a value of the `right` type can be assigned to the `left` type
+/// numbers, dates, enums and pointers are converted into each other implicitly, strings, structs
+/// and arrays can only be assigned to the same kind of type, arrays need to have the same lengths
+/// unresolved (void) or generic types are not checked, they are reported elsewhere
+fn is_assignment_compatible(
+    left: &DataTypeInformation,
+    right: &DataTypeInformation,
+    right_statement: &AstStatement,
+    index: &Index,
+) -> bool {
+    let left = index.find_intrinsic_type(get_auto_deref_type(left, index));
+    let right = index.find_intrinsic_type(get_auto_deref_type(right, index));
+    match (left, right) {
+        (DataTypeInformation::Void, _)
+        | (_, DataTypeInformation::Void)
+        | (DataTypeInformation::Generic { .. }, _)
+        | (_, DataTypeInformation::Generic { .. }) => true,
+        // a string literal of the length 1 can be assigned to a character
+        (DataTypeInformation::Integer { .. }, DataTypeInformation::String { .. })
+            if left.is_character() =>
+        {
+            matches!(right_statement, AstStatement::LiteralString { .. })
+        }
+        (
+            DataTypeInformation::String { encoding, .. },
+            DataTypeInformation::String {
+                encoding: right_encoding,
+                ..
+            },
+        ) => {
+            // literals are generated with the encoding of the variable they are assigned to
+            encoding == right_encoding
+                || matches!(right_statement, AstStatement::LiteralString { .. })
+        }
+        (
+            DataTypeInformation::Struct { name, .. },
+            DataTypeInformation::Struct {
+                name: right_name, ..
+            },
+        ) => {
+            // instances can be assigned to a variable of their super class or interface
+            name.eq_ignore_ascii_case(right_name)
+                || index
+                    .get_super_classes(right_name)
+                    .iter()
+                    .any(|it| it.eq_ignore_ascii_case(name))
+                || (index.is_interface(name) && index.implements_interface(right_name, name))
+        }
+        (
+            DataTypeInformation::Array {
+                inner_type_name,
+                dimensions,
+                ..
+            },
+            DataTypeInformation::Array {
+                inner_type_name: right_inner_type_name,
+                dimensions: right_dimensions,
+                ..
+            },
+        ) => {
+            // the elements are copied as they are, so they need to be of the same type and the
+            // arrays need to have the same length in every dimension (the bounds may differ)
+            let has_same_lengths = dimensions.len() == right_dimensions.len()
+                && dimensions
+                    .iter()
+                    .zip(right_dimensions.iter())
+                    .all(|(l, r)| {
+                        match (l.get_length(index), r.get_length(index)) {
+                            (Ok(length), Ok(right_length)) => length == right_length,
+                            //unresolved lengths are reported elsewhere
+                            _ => true,
+                        }
+                    });
+            let inner_type = index.get_intrinsic_type_by_name(inner_type_name);
+            let right_inner_type = index.get_intrinsic_type_by_name(right_inner_type_name);
+            let is_unchecked = |it: &DataTypeInformation| {
+                matches!(
+                    it,
+                    DataTypeInformation::Void | DataTypeInformation::Generic { .. }
+                )
+            };
+            has_same_lengths
+                && (is_unchecked(inner_type.get_type_information())
+                    || is_unchecked(right_inner_type.get_type_information())
+                    || inner_type
+                        .get_name()
+                        .eq_ignore_ascii_case(right_inner_type.get_name()))
+        }
+        (DataTypeInformation::String { .. }, _)
+        | (_, DataTypeInformation::String { .. })
+        | (DataTypeInformation::Struct { .. }, _)
+        | (_, DataTypeInformation::Struct { .. })
+        | (DataTypeInformation::Array { .. }, _)
+        | (_, DataTypeInformation::Array { .. }) => false,
+        _ => true,
+    }
+}
+
+/// returns the type referenced by an auto-deref pointer (e.g. a VAR_IN_OUT), or the given type
+fn get_auto_deref_type<'i>(
+    data_type: &'i DataTypeInformation,
+    index: &'i Index,
+) -> &'i DataTypeInformation {
+    match data_type {
+        DataTypeInformation::Pointer {
+            inner_type_name,
+            auto_deref: true,
+            ..
+        } => index
+            .get_effective_type_by_name(inner_type_name)
+            .get_type_information(),
+        _ => data_type,
+    }
+}
+
+/// returns true if converting the given constant value to the given type changes the value,
+/// beyond rounding (or truncating) a real value to an integer
+fn is_lossy_conversion(
+    value: &AstStatement,
+    target_type: &DataTypeInformation,
+    truncate: bool,
+) -> bool {
+    if target_type.is_bool() {
+        //every value but zero converts to TRUE
+        return false;
+    }
+    match value {
+        AstStatement::LiteralInteger { value, .. } if target_type.is_float() => {
+            if target_type.get_size() == REAL_SIZE {
+                (*value as NativeRealType) as i128 != *value
+            } else {
+                (*value as f64) as i128 != *value
+            }
+        }
+        AstStatement::LiteralInteger { value, .. } => !target_type.is_in_int_range(*value),
+        AstStatement::LiteralReal { value, .. } if target_type.is_float() => {
+            target_type.get_size() == REAL_SIZE
+                && typesystem::get_real_type_name_for(value) != REAL_TYPE
+        }
+        AstStatement::LiteralReal { value, .. } => {
+            let value = value.parse::<f64>().unwrap_or_default();
+            let integer = if truncate {
+                value.trunc()
+            } else {
+                value.round()
+            };
+            !integer.is_finite() || !target_type.is_in_int_range(integer as i128)
+        }
+        _ => false,
+    }
+}
+
+/// returns false if the given numeric literal exceeds the range of the given float type
+fn is_in_float_range(literal: &AstStatement, float_type: &DataTypeInformation) -> bool {
+    let value = match literal {
+        AstStatement::LiteralInteger { value, .. } => *value as f64,
+        AstStatement::LiteralReal { value, .. } => value.parse::<f64>().unwrap_or_default(),
+        _ => return true,
+    };
+    float_type.get_size() != REAL_SIZE || value.abs() <= NativeRealType::MAX as f64
+}
+
+/// returns the qualified name of the constant array if the given statement accesses one of its
+/// elements (e.g. `c[1]`, `c[1][2]` or `s.c[1]`)
+fn find_constant_array_of_element<'s>(
+    statement: &AstStatement,
+    context: &ValidationContext<'s>,
+) -> Option<&'s str> {
+    match statement {
+        AstStatement::QualifiedReference { elements, .. } => elements
+            .last()
+            .and_then(|last| find_constant_array_of_element(last, context)),
+        AstStatement::ArrayAccess { reference, .. } => {
+            match context.ast_annotation.get(reference) {
+                Some(StatementAnnotation::Variable {
+                    qualified_name,
+                    constant: true,
+                    ..
+                }) => Some(qualified_name.as_str()),
+                Some(StatementAnnotation::Variable { .. }) => None,
+                _ => find_constant_array_of_element(reference, context),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// returns the value of the given reference if it points to a constant with a resolvable
+/// integer initial value
+fn find_constant_int_value(context: &ValidationContext, reference: &AstStatement) -> Option<i128> {
+    if let Some(StatementAnnotation::Variable {
+        qualified_name,
+        constant: true,
+        ..
+    }) = context.ast_annotation.get(reference)
+    {
+        context
+            .index
+            .find_fully_qualified_variable(qualified_name)
+            .and_then(|variable| variable.initial_value)
+            .and_then(|initial_value| {
+                context
+                    .index
+                    .get_const_expressions()
+                    .get_constant_int_statement_value(&initial_value)
+                    .ok()
+            })
+    } else {
+        None
+    }
 }
 
 /// returns true if this AST Statement is a literal that can be
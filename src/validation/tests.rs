@@ -1,10 +1,15 @@
 // Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
 mod array_access_validation_test;
 mod bitaccess_validation_test;
+mod flow_validation_tests;
 mod literals_validation_tests;
 mod reference_resolve_tests;
 mod variable_validation_tests;
 
 mod statement_validation_tests;
 
+mod usage_validation_tests;
+
+mod recursion_validation_tests;
+
 mod generic_validation_tests;
@@ -58,3 +58,32 @@ fn array_access_validation() {
         ]
     );
 }
+
+#[test]
+fn constant_expressions_are_validated_against_the_array_bounds() {
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL CONSTANT
+            last : INT := 3;
+        END_VAR
+
+        PROGRAM prg
+        VAR
+            arr : ARRAY[0..3] OF INT;
+        END_VAR
+        arr[last];
+        arr[last - 3];
+        arr[last + 1];
+        arr[2 * last];
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::incompatible_array_access_range(0..3, (215..223).into()),
+            Diagnostic::incompatible_array_access_range(0..3, (238..246).into()),
+        ]
+    );
+}
@@ -246,3 +246,31 @@ fn reference_direct_access_only_with_ints() {
         ]
     );
 }
+
+#[test]
+fn constant_direct_access_index_range_test() {
+    let diagnostics = parse_and_validate(
+        "
+            PROGRAM prg
+            VAR CONSTANT
+                seven : INT := 7; eight : INT := 8;
+            END_VAR
+            VAR 
+                a : BYTE; d : LWORD;
+            END_VAR
+                a.%Xseven;
+                a.%Xeight;
+                d.%Bseven;
+                d.%Beight;
+           END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::incompatible_directaccess_range("Bit", "BYTE", 0..7, (241..248).into()),
+            Diagnostic::incompatible_directaccess_range("Byte", "LWORD", 0..7, (295..302).into()),
+        ]
+    );
+}
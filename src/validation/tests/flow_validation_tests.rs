@@ -0,0 +1,101 @@
+use crate::test_utils::tests::parse_and_validate_flow;
+use crate::Diagnostic;
+
+#[test]
+fn variables_read_before_they_are_assigned_are_reported_with_the_path() {
+    let diagnostics = parse_and_validate_flow(
+        "
+        FUNCTION foo : INT
+            VAR_INPUT c : BOOL; END_VAR
+            VAR
+                a : INT;
+                b : INT;
+                initialized : INT := 1;
+            END_VAR
+            VAR_TEMP t : INT; END_VAR
+            foo := a;
+            IF c THEN
+                b := 1;
+                t := 2;
+            ELSE
+                t := 3;
+            END_IF
+            foo := b + t + initialized;
+        END_FUNCTION
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::read_before_assignment("a", &[], (251..252).into()),
+            Diagnostic::read_before_assignment(
+                "b",
+                &["the IF at line 11 takes the ELSE branch".to_string()],
+                (403..404).into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn loops_that_are_not_entered_do_not_assign_their_variables() {
+    let diagnostics = parse_and_validate_flow(
+        "
+        PROGRAM prg
+            VAR_TEMP i, x, y : INT; END_VAR
+            VAR n : INT; END_VAR
+            FOR i := 0 TO n DO
+                x := i;
+            END_FOR
+            n := x;
+            REPEAT
+                y := 1;
+            UNTIL y > 0 END_REPEAT
+            n := y;
+        END_PROGRAM
+       ",
+    );
+
+    //the counter is assigned before the loop is entered, a REPEAT loop is always entered
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::read_before_assignment(
+            "x",
+            &["the FOR loop at line 5 is not entered".to_string()],
+            (190..191).into()
+        )]
+    );
+}
+
+#[test]
+fn variables_passed_to_outputs_and_in_outs_are_assigned() {
+    let diagnostics = parse_and_validate_flow(
+        "
+        FUNCTION_BLOCK fb
+            VAR_OUTPUT q : INT; END_VAR
+        END_FUNCTION_BLOCK
+
+        FUNCTION foo : INT
+            VAR_IN_OUT io : INT; END_VAR
+        END_FUNCTION
+
+        FUNCTION bar : INT
+            VAR_TEMP a, b, c : INT; END_VAR
+            VAR inst : fb; END_VAR
+            inst(q => a);
+            foo(b);
+            bar := a + b + c;
+        END_FUNCTION
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::read_before_assignment(
+            "c",
+            &[],
+            (364..365).into()
+        )]
+    );
+}
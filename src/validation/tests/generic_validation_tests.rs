@@ -215,3 +215,22 @@ fn non_resolved_generics_reported() {
         ),]
     );
 }
+
+#[test]
+fn generic_classes_used_as_parameter_types_are_validated() {
+    let src = r"
+        FUNCTION test : INT VAR_INPUT x : ANY_INT; END_VAR END_FUNCTION
+        FUNCTION func  : INT VAR x : REAL; END_VAR test(x); END_FUNCTION
+        FUNCTION func1  : INT VAR x : UDINT; END_VAR test(x); END_FUNCTION
+    ";
+
+    let diagnostics = parse_and_validate(src);
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::invalid_type_nature(
+            "REAL",
+            "Int",
+            (129..130).into()
+        )]
+    );
+}
@@ -34,6 +34,35 @@ fn int_literal_casts_max_values_are_validated() {
     );
 }
 
+#[test]
+fn int_literals_exceeding_their_target_type_are_validated() {
+    let diagnostics = parse_and_validate(
+        "
+            PROGRAM prg
+            VAR
+                a : BYTE := 16#FF;
+                b : BYTE := 16#1_00;
+                c : SINT := -128;
+                d : WORD := 2#1_0000_0000_0000_0000;
+            END_VAR
+                a := 8#777;
+                c := -16#81;
+                d := 16#FFFF;
+           END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::literal_out_of_range("256", "BYTE", (104..111).into()),
+            Diagnostic::literal_out_of_range("65536", "WORD", (175..198).into()),
+            Diagnostic::literal_out_of_range("511", "BYTE", (241..246).into()),
+            Diagnostic::literal_out_of_range("-129", "SINT", (269..275).into()),
+        ]
+    );
+}
+
 #[test]
 fn bool_literal_casts_are_validated() {
     let diagnostics = parse_and_validate(
@@ -127,6 +156,51 @@ fn real_literal_casts_are_validated() {
     );
 }
 
+#[test]
+fn real_literal_casts_out_of_range_are_validated() {
+    let diagnostics = parse_and_validate(
+        "PROGRAM prg
+            REAL#3.4e38;
+            REAL#3.5e38;
+            LREAL#3.5e38;
+        END_PROGRAM",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::literal_out_of_range(
+            "3.5e38",
+            "REAL",
+            (49..60).into()
+        )]
+    );
+}
+
+#[test]
+fn real_literals_losing_precision_are_reported() {
+    let diagnostics = parse_and_validate(
+        "PROGRAM prg
+        VAR
+            a : REAL := 3.14159265359;
+            b : LREAL := 3.14159265359;
+            c : REAL := 1.5E-3;
+        END_VAR
+            a := -3.14159265359;
+            a := 1e39;
+            b := 1e39;
+        END_PROGRAM",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::literal_precision_loss("3.14159265359", "REAL", (48..61).into()),
+            Diagnostic::literal_precision_loss("3.14159265359", "REAL", (169..182).into()),
+            Diagnostic::literal_out_of_range("1e39", "REAL", (201..205).into()),
+        ]
+    );
+}
+
 #[test]
 fn literal_cast_with_non_literal() {
     let diagnostics = parse_and_validate(
@@ -12,3 +12,212 @@ fn function_no_return_unsupported() {
         vec![Diagnostic::function_return_missing((0..43).into())]
     );
 }
+
+#[test]
+fn actions_are_only_allowed_for_programs_and_function_blocks() {
+    // GIVEN actions declared for a PROGRAM, a FUNCTION_BLOCK, a FUNCTION and an unknown container
+    // WHEN parse_and_validate is done
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg END_PROGRAM
+        FUNCTION_BLOCK fb END_FUNCTION_BLOCK
+        FUNCTION foo : INT END_FUNCTION
+
+        ACTIONS prg ACTION a ; END_ACTION END_ACTIONS
+        ACTIONS fb ACTION a ; END_ACTION END_ACTIONS
+        ACTIONS foo ACTION a ; END_ACTION END_ACTIONS
+        ACTIONS bar ACTION a ; END_ACTION END_ACTIONS
+        ",
+    );
+    // THEN the actions of the FUNCTION and the unknown container are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_action_container("foo", (255..267).into()),
+            Diagnostic::invalid_action_container("bar", (309..321).into()),
+        ]
+    );
+}
+
+#[test]
+fn implemented_interfaces_must_be_implemented_completely_and_compatibly() {
+    // GIVEN function blocks implementing an interface completely, incompletely,
+    // with a different signature and implementing a function block
+    // WHEN parse_and_validate is done
+    let diagnostics = parse_and_validate(
+        "
+        INTERFACE ICounter
+            METHOD inc : INT VAR_INPUT step : INT; END_VAR END_METHOD
+            METHOD reset END_METHOD
+        END_INTERFACE
+
+        FUNCTION_BLOCK complete IMPLEMENTS ICounter
+            METHOD inc : INT VAR_INPUT step : INT; END_VAR END_METHOD
+            METHOD reset END_METHOD
+        END_FUNCTION_BLOCK
+
+        FUNCTION_BLOCK incomplete IMPLEMENTS ICounter
+            METHOD inc : INT VAR_INPUT step : INT; END_VAR END_METHOD
+        END_FUNCTION_BLOCK
+
+        FUNCTION_BLOCK mismatch IMPLEMENTS ICounter
+            METHOD inc : DINT VAR_INPUT step : INT; END_VAR END_METHOD
+            METHOD reset END_METHOD
+        END_FUNCTION_BLOCK
+
+        FUNCTION_BLOCK noInterface IMPLEMENTS complete
+        END_FUNCTION_BLOCK
+        ",
+    );
+    // THEN the missing method, the incompatible method and the invalid interface are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::missing_interface_method(
+                "incomplete",
+                "reset",
+                "ICounter",
+                (351..493).into()
+            ),
+            Diagnostic::incompatible_interface_method(
+                "mismatch.inc",
+                "ICounter",
+                (503..680).into()
+            ),
+            Diagnostic::invalid_interface("complete", (690..763).into()),
+        ]
+    );
+}
+
+#[test]
+fn super_classes_must_be_valid_and_overrides_compatible() {
+    // GIVEN a method overridden with a different signature, a function block extending
+    // a class and classes extending each other
+    // WHEN parse_and_validate is done
+    let diagnostics = parse_and_validate(
+        "
+        CLASS base
+            METHOD m : INT VAR_INPUT a : INT; END_VAR END_METHOD
+        END_CLASS
+
+        CLASS derived EXTENDS base
+            METHOD m : DINT VAR_INPUT a : INT; END_VAR END_METHOD
+        END_CLASS
+
+        FUNCTION_BLOCK fb EXTENDS base
+        END_FUNCTION_BLOCK
+
+        CLASS a EXTENDS b END_CLASS
+        CLASS b EXTENDS a END_CLASS
+        ",
+    );
+    // THEN the incompatible override, the invalid super class and the cycle are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::incompatible_override("derived.m", "base.m", (151..204).into()),
+            Diagnostic::invalid_super_class("fb", "base", (232..289).into()),
+            Diagnostic::cyclic_inheritance("a", (299..326).into()),
+            Diagnostic::cyclic_inheritance("b", (335..362).into()),
+        ]
+    );
+}
+
+#[test]
+fn program_instances_must_be_programs_scheduled_by_declared_tasks() {
+    // GIVEN program instances of a PROGRAM and a FUNCTION_BLOCK, scheduled by declared and
+    // unknown tasks, and event tasks triggered by declared and unknown variables
+    // WHEN parse_and_validate is done
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM main END_PROGRAM
+        FUNCTION_BLOCK fb END_FUNCTION_BLOCK
+        VAR_GLOBAL trigger : BOOL; END_VAR
+        CONFIGURATION plc
+            RESOURCE cpu ON PLC
+                TASK fast(INTERVAL := T#10ms, PRIORITY := 1);
+                TASK event(SINGLE := trigger, PRIORITY := 2);
+                TASK unknown(SINGLE := missing, PRIORITY := 3);
+                PROGRAM a WITH fast : main;
+                PROGRAM b WITH slow : main;
+                PROGRAM c : fb;
+            END_RESOURCE
+        END_CONFIGURATION
+        ",
+    );
+    // THEN the unknown task, the function block instance and the unknown trigger are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unresolved_task("slow", "cpu", (428..454).into()),
+            Diagnostic::invalid_program_instance("c", "fb", (472..486).into()),
+            Diagnostic::unresolved_reference("missing", (343..350).into()),
+        ]
+    );
+}
+
+#[test]
+fn var_config_entries_must_address_template_variables() {
+    // GIVEN VAR_CONFIG entries addressing a template variable, an unknown variable, variables
+    // without a template binding and entries with a mismatching direction, address or type
+    // WHEN parse_and_validate is done
+    let diagnostics = parse_and_validate(
+        "
+        FUNCTION_BLOCK fb
+        VAR_INPUT
+            input AT %I* : BOOL;
+            fixed AT %IX0.1 : BOOL;
+        END_VAR
+        END_FUNCTION_BLOCK
+        PROGRAM main
+        VAR
+            inst : fb;
+            plain : BOOL;
+        END_VAR
+        END_PROGRAM
+        CONFIGURATION plc
+            VAR_CONFIG
+                main.inst.input AT %IX1.2 : BOOL;
+                main.inst.missing AT %IX1.3 : BOOL;
+                main.plain AT %IX1.4 : BOOL;
+                main.inst.fixed AT %IX1.5 : BOOL;
+                main.inst.input AT %QX1.6 : BOOL;
+                main.inst.input AT %I* : BOOL;
+                main.inst.input AT %IX1.7 : INT;
+            END_VAR
+        END_CONFIGURATION
+        ",
+    );
+    // THEN every entry but the first one is reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unresolved_reference("main.inst.missing", (390..424).into()),
+            Diagnostic::invalid_config_variable(
+                "main.plain",
+                "the variable is not declared with an AT %I*, %Q* or %M* template",
+                (442..469).into()
+            ),
+            Diagnostic::invalid_config_variable(
+                "main.inst.fixed",
+                "the variable is already bound to an address",
+                (487..519).into()
+            ),
+            Diagnostic::invalid_config_variable(
+                "main.inst.input",
+                "the direction does not match the variable's declaration",
+                (537..569).into()
+            ),
+            Diagnostic::invalid_config_variable(
+                "main.inst.input",
+                "the address must not be a template",
+                (587..616).into()
+            ),
+            Diagnostic::invalid_config_variable(
+                "main.inst.input",
+                "the type does not match the variable's declaration",
+                (634..665).into()
+            ),
+        ]
+    );
+}
@@ -0,0 +1,77 @@
+use crate::test_utils::tests::parse_and_validate_recursions;
+use crate::Diagnostic;
+
+#[test]
+fn direct_recursions_are_reported() {
+    let diagnostics = parse_and_validate_recursions(
+        "
+        FUNCTION fact : DINT
+            VAR_INPUT n : DINT; END_VAR
+            IF n > 1 THEN
+                fact := n * fact(n - 1);
+            ELSE
+                fact := 1;
+            END_IF
+        END_FUNCTION
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::recursive_call(
+            &["fact", "fact"],
+            (124..135).into()
+        )]
+    );
+}
+
+#[test]
+fn indirect_recursions_are_reported() {
+    let diagnostics = parse_and_validate_recursions(
+        "
+        PROGRAM a
+            b();
+        END_PROGRAM
+
+        PROGRAM b
+            c();
+        END_PROGRAM
+
+        PROGRAM c
+            a();
+        END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::recursive_call(
+            &["a", "b", "c", "a"],
+            (143..146).into()
+        )]
+    );
+}
+
+#[test]
+fn calls_without_recursion_are_not_reported() {
+    let diagnostics = parse_and_validate_recursions(
+        "
+        FUNCTION_BLOCK fb
+            VAR inner : fb2; END_VAR
+            inner();
+            foo();
+        END_FUNCTION_BLOCK
+
+        FUNCTION_BLOCK fb2
+            foo();
+            foo();
+        END_FUNCTION_BLOCK
+
+        FUNCTION foo : INT
+            foo := 1;
+        END_FUNCTION
+       ",
+    );
+
+    assert_eq!(diagnostics, vec![]);
+}
@@ -236,3 +236,73 @@ fn resolve_function_members_via_qualifier() {
         ]
     );
 }
+
+/// tests whether PRIVATE methods can only be called from inside their class
+#[test]
+fn private_methods_cannot_be_accessed_outside_of_their_class() {
+    let diagnostics = parse_and_validate(
+        "
+            CLASS MyClass
+                METHOD PRIVATE secret : INT
+                END_METHOD
+
+                METHOD PUBLIC open : INT
+                    open := secret() + THIS.secret();
+                END_METHOD
+            END_CLASS
+
+            PROGRAM prg
+                VAR
+                    cl : MyClass;
+                END_VAR
+                cl.open();
+                cl.secret();
+            END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::illegal_access(
+            "secret",
+            "MyClass",
+            (392..398).into()
+        )]
+    );
+}
+
+/// tests whether properties are only read and written through existing accessors
+#[test]
+fn properties_cannot_be_accessed_without_accessor() {
+    let diagnostics = parse_and_validate(
+        "
+            FUNCTION_BLOCK fb
+                PROPERTY ReadOnly : INT
+                    GET END_GET
+                END_PROPERTY
+                PROPERTY WriteOnly : INT
+                    SET END_SET
+                END_PROPERTY
+            END_FUNCTION_BLOCK
+
+            PROGRAM prg
+                VAR
+                    inst : fb;
+                    x : INT;
+                END_VAR
+                inst.ReadOnly := 1;
+                x := inst.WriteOnly;
+                inst.WriteOnly := 2;
+                x := inst.ReadOnly;
+            END_PROGRAM
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_property_access("ReadOnly", "SET", (410..423).into()),
+            Diagnostic::invalid_property_access("WriteOnly", "GET", (456..465).into()),
+        ]
+    );
+}
@@ -42,6 +42,38 @@ fn assignment_to_constants_result_in_an_error() {
     );
 }
 
+#[test]
+fn assignment_to_elements_of_constant_arrays_result_in_an_error() {
+    // GIVEN assignments to the elements of constant and writable arrays
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR CONSTANT
+                carr : ARRAY[0..1] OF INT := [1, 2];
+                cgrid : ARRAY[0..1] OF ARRAY[0..1] OF INT;
+            END_VAR
+            VAR
+                arr : ARRAY[0..1] OF INT;
+            END_VAR
+
+            arr[0] := 1;
+            carr[0] := 1;
+            cgrid[1][0] := 1;
+        END_PROGRAM
+      ",
+    );
+
+    // THEN the elements of the constant arrays are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::cannot_assign_to_constant("prg.carr", (294..300).into()),
+            Diagnostic::cannot_assign_to_constant("prg.cgrid", (320..330).into()),
+        ]
+    );
+}
+
 #[test]
 fn assignment_to_enum_literals_results_in_error() {
     // GIVEN assignment statements to constants, some to writable variables
@@ -283,3 +315,522 @@ fn wstring_compare_function_cause_no_error_if_functions_exist() {
     // THEN everything but VAR and VAR_GLOBALS are reported
     assert_eq!(diagnostics, vec![]);
 }
+
+#[test]
+fn literals_passed_to_in_out_parameters_are_reported() {
+    // GIVEN calls passing variables and non-assignable expressions to VAR_IN_OUT parameters
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        FUNCTION foo : INT
+            VAR_INPUT a : INT; END_VAR
+            VAR_IN_OUT b : INT; END_VAR
+        END_FUNCTION
+
+        PROGRAM prg
+            VAR x : INT; arr : ARRAY[0..1] OF INT; END_VAR
+            foo(1, x);
+            foo(a := x, b := arr[1]);
+            foo(x, 2);
+            foo(b := x + 1, a := 1);
+        END_PROGRAM
+      ",
+    );
+
+    // THEN only the arguments that cannot be referenced are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_in_out_argument("b", (288..289).into()),
+            Diagnostic::invalid_in_out_argument("b", (313..318).into()),
+        ]
+    );
+}
+
+#[test]
+fn overlapping_case_conditions_are_reported() {
+    // GIVEN a case statement with overlapping literal, constant and range conditions
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL CONSTANT
+            FIVE : DINT := 5;
+        END_VAR
+
+        PROGRAM prg
+            VAR x, y : DINT; END_VAR
+            CASE x OF
+                1, 2:       y := 1;
+                3..FIVE:    y := 2;
+                4:          y := 3;
+                6, 1:       y := 4;
+                x:          y := 5;
+                7..9:       y := 6;
+                FIVE + 4:   y := 7;
+            END_CASE
+        END_PROGRAM
+      ",
+    );
+
+    // THEN every condition that matches an already covered value is reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::duplicate_case_condition("4", (243..244).into()),
+            Diagnostic::duplicate_case_condition("1", (282..283).into()),
+            Diagnostic::duplicate_case_condition("9", (387..395).into()),
+        ]
+    );
+}
+
+#[test]
+fn exit_and_continue_outside_of_loops_are_reported() {
+    // GIVEN EXIT and CONTINUE statements inside and outside of loops
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR x : DINT; END_VAR
+            WHILE x < 10 DO
+                IF x = 5 THEN EXIT; END_IF
+                CONTINUE;
+            END_WHILE
+            IF x = 5 THEN EXIT; END_IF
+            CONTINUE;
+        END_PROGRAM
+      ",
+    );
+
+    // THEN only the statements outside of the loop are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::loop_control_outside_loop("EXIT", (200..204).into()),
+            Diagnostic::loop_control_outside_loop("CONTINUE", (225..233).into()),
+        ]
+    );
+}
+
+#[test]
+fn statements_following_return_and_exit_are_reported() {
+    // GIVEN statements following an EXIT and a RETURN in their blocks
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR x : DINT; END_VAR
+            WHILE x < 10 DO
+                EXIT;
+                x := x + 1;
+            END_WHILE
+            IF x = 5 THEN
+                RETURN;
+                x := 1;
+                x := 2;
+            END_IF
+            x := 3;
+        END_PROGRAM
+      ",
+    );
+
+    // THEN the unreachable statements of each block are reported together
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unreachable_code("EXIT", (121..131).into()),
+            Diagnostic::unreachable_code("RETURN", (221..251).into()),
+        ]
+    );
+}
+
+#[test]
+fn parameters_assigned_more_than_once_are_reported() {
+    // GIVEN calls with named and positional arguments
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        FUNCTION_BLOCK fb
+            VAR_INPUT in1, in2 : INT; END_VAR
+            VAR_OUTPUT out : INT; END_VAR
+        END_FUNCTION_BLOCK
+
+        PROGRAM prg
+            VAR x, y : INT; inst : fb; END_VAR
+            inst(in1 := x, 5, out => y);
+            inst(in2 := x, in2 := 5);
+            inst(x, in1 := 5);
+        END_PROGRAM
+      ",
+    );
+
+    // THEN every repeated assignment of a parameter is reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::duplicate_parameter_assignment("in2", (278..286).into()),
+            Diagnostic::duplicate_parameter_assignment("in1", (309..317).into()),
+        ]
+    );
+}
+
+#[test]
+fn output_assignments_to_non_output_parameters_are_reported() {
+    // GIVEN output assignments to VAR_OUTPUT, VAR_INPUT and VAR_IN_OUT parameters
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        FUNCTION_BLOCK fb
+            VAR_INPUT in1 : INT; END_VAR
+            VAR_IN_OUT io : INT; END_VAR
+            VAR_OUTPUT out : INT; END_VAR
+        END_FUNCTION_BLOCK
+
+        PROGRAM prg
+            VAR x, y : INT; inst : fb; END_VAR
+            inst(io := x, out => y);
+            inst(in1 => x, io => y);
+        END_PROGRAM
+      ",
+    );
+
+    // THEN only the assignments to non-output parameters are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_output_assignment("in1", (300..308).into()),
+            Diagnostic::invalid_output_assignment("io", (310..317).into()),
+        ]
+    );
+}
+
+#[test]
+fn integer_divisions_by_constant_zero_are_reported() {
+    // GIVEN divisions by constant values
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL CONSTANT
+            ZERO : INT := 0;
+        END_VAR
+
+        PROGRAM prg
+            VAR x : INT; r : REAL; END_VAR
+            x := x / 0;
+            x := x MOD ZERO;
+            x := x / 2;
+            r := r / 0.0;
+        END_PROGRAM
+      ",
+    );
+
+    // THEN the integer divisions by zero are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::division_by_zero((155..160).into()),
+            Diagnostic::division_by_zero((179..189).into()),
+        ]
+    );
+}
+
+#[test]
+fn short_circuit_operators_with_non_bool_operands_are_reported() {
+    // GIVEN AND_THEN and OR_ELSE expressions with BOOL and INT operands
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR b : BOOL; i : INT; END_VAR
+            b := b AND_THEN i;
+            b := i OR_ELSE b;
+            b := b AND_THEN i > 0;
+        END_PROGRAM
+      ",
+    );
+
+    // THEN the operands that are no BOOL are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_operand("AND_THEN", "INT", "BOOL", (92..93).into()),
+            Diagnostic::invalid_operand("OR_ELSE", "INT", "BOOL", (112..113).into()),
+        ]
+    );
+}
+
+#[test]
+fn lossy_conversions_of_constant_values_are_reported() {
+    // GIVEN conversions of constant values that do (not) fit into the source- and target-type
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR x : SINT; i : INT; r : REAL; b : BOOL; END_VAR
+            x := INT_TO_SINT(300);
+            x := INT_TO_SINT(-100);
+            x := REAL_TO_SINT(127.6);
+            x := REAL_TRUNC_SINT(127.6);
+            r := LREAL_TO_REAL(3.14159265359);
+            r := DINT_TO_REAL(16777217);
+            b := INT_TO_BOOL(300);
+            i := SINT_TO_INT(300);
+        END_PROGRAM
+      ",
+    );
+
+    // THEN the conversions changing the value are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::lossy_conversion("INT_TO_SINT", "300", "SINT", (113..116).into()),
+            Diagnostic::lossy_conversion("REAL_TO_SINT", "127.6", "SINT", (185..190).into()),
+            Diagnostic::lossy_conversion(
+                "LREAL_TO_REAL",
+                "3.14159265359",
+                "REAL",
+                (265..278).into()
+            ),
+            Diagnostic::lossy_conversion("DINT_TO_REAL", "16777217", "REAL", (311..319).into()),
+            Diagnostic::lossy_conversion("SINT_TO_INT", "300", "SINT", (386..389).into()),
+        ]
+    );
+}
+
+#[test]
+fn bit_shifts_of_non_integers_and_beyond_the_bit_width_are_reported() {
+    // GIVEN bit-shifts with integer, real and constant arguments
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR b : BYTE; w : WORD; r : REAL; i : INT; END_VAR
+            w := SHL(w, 15);
+            w := SHL(w, 16);
+            b := ROR(b, 8 + 1);
+            w := SHR(w, i);
+            w := SHL(w, r);
+            r := ROL(r, 1);
+        END_PROGRAM
+      ",
+    );
+
+    // THEN real arguments and constant amounts exceeding the bit-width are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::bit_shift_exceeds_width("SHL", "16", "WORD", (137..139).into()),
+            Diagnostic::bit_shift_exceeds_width("ROR", "9", "BYTE", (166..171).into()),
+            Diagnostic::invalid_bit_shift_argument("SHL", "REAL", (226..227).into()),
+            Diagnostic::invalid_bit_shift_argument("ROL", "REAL", (251..252).into()),
+        ]
+    );
+}
+
+#[test]
+fn numeric_functions_with_non_numeric_arguments_are_reported() {
+    // GIVEN numeric functions with integer, real, bool and time arguments
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR b : BOOL; i : INT; r : REAL; t : TIME; END_VAR
+            r := SQRT(i);
+            r := ABS(r);
+            r := SIN(b);
+            r := ATAN2(r, t);
+        END_PROGRAM
+      ",
+    );
+
+    // THEN the bool and the time arguments are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_numeric_argument("SIN", "BOOL", (156..157).into()),
+            Diagnostic::invalid_numeric_argument("ATAN2", "TIME", (186..187).into()),
+        ]
+    );
+}
+
+#[test]
+fn selections_with_invalid_arguments_are_reported() {
+    // GIVEN selections with numeric, string and real arguments
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR b : BYTE; r : REAL; s : STRING; g : BOOL; END_VAR
+            r := SEL(g, b, r);
+            r := SEL(b, b, r);
+            r := MAX(b, s, r);
+            b := MUX(r, b, b);
+            b := LIMIT(0, b, 100);
+        END_PROGRAM
+      ",
+    );
+
+    // THEN selectors of the wrong type and inputs that are no numbers are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_selection_argument("SEL", "BYTE", "BOOL", (139..140).into()),
+            Diagnostic::invalid_selection_argument("MAX", "STRING", "number", (173..174).into()),
+            Diagnostic::invalid_selection_argument("MUX", "REAL", "integer", (201..202).into()),
+        ]
+    );
+}
+
+#[test]
+fn only_implementing_instances_can_be_assigned_to_interfaces() {
+    // GIVEN assignments of an implementing instance, an interface and
+    // a non-implementing instance to an interface
+    // WHEN parse_and_validate is done
+    let diagnostics = parse_and_validate(
+        "
+        INTERFACE ICounter
+            METHOD inc : INT END_METHOD
+        END_INTERFACE
+
+        FUNCTION_BLOCK counter IMPLEMENTS ICounter
+            METHOD inc : INT END_METHOD
+        END_FUNCTION_BLOCK
+
+        FUNCTION_BLOCK other
+        END_FUNCTION_BLOCK
+
+        PROGRAM prg
+            VAR
+                c : counter;
+                o : other;
+                i, j : ICounter;
+            END_VAR
+            i := c;
+            j := i;
+            i := o;
+        END_PROGRAM
+        ",
+    );
+    // THEN only the non-implementing instance is reported
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::invalid_assignment(
+            "other",
+            "ICounter",
+            (464..470).into()
+        )]
+    );
+}
+
+#[test]
+fn assignments_of_incompatible_types_are_reported() {
+    // GIVEN assignments of numbers, strings, structs and arrays to each other
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        TYPE Point : STRUCT x, y : INT; END_STRUCT END_TYPE
+        TYPE Color : STRUCT r, g, b : BYTE; END_STRUCT END_TYPE
+        TYPE IntArray : ARRAY[0..1] OF INT; END_TYPE
+        TYPE RealArray : ARRAY[0..1] OF REAL; END_TYPE
+
+        FUNCTION foo : INT
+            foo := 'five';
+        END_FUNCTION
+
+        PROGRAM prg
+            VAR
+                i : INT; r : REAL; s : STRING; ws : WSTRING;
+                p : Point; c : Color; a : IntArray; ar : RealArray;
+            END_VAR
+            i := r;
+            i := s;
+            s := i;
+            s := ws;
+            ws := 'abc';
+            p := c;
+            p := i;
+            a := ar;
+            a := a;
+        END_PROGRAM
+      ",
+    );
+
+    // THEN only numbers are converted implicitly, other types have to match
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_assignment("STRING", "INT", (273..286).into()),
+            Diagnostic::invalid_assignment("STRING", "INT", (527..533).into()),
+            Diagnostic::invalid_assignment("INT", "STRING", (547..553).into()),
+            Diagnostic::invalid_assignment("WSTRING", "STRING", (567..574).into()),
+            Diagnostic::invalid_assignment("Color", "Point", (613..619).into()),
+            Diagnostic::invalid_assignment("INT", "Point", (633..639).into()),
+            Diagnostic::invalid_assignment("RealArray", "IntArray", (653..660).into()),
+        ]
+    );
+}
+
+#[test]
+fn assignments_of_arrays_with_different_lengths_are_reported() {
+    // GIVEN assignments of arrays with the same and different lengths
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        PROGRAM prg
+            VAR
+                a : ARRAY[0..9] OF INT;
+                b : ARRAY[1..10] OF INT;
+                c : ARRAY[0..4] OF INT;
+                d : ARRAY[0..1, 0..4] OF INT;
+            END_VAR
+            a := b;
+            a := c;
+            a := d;
+        END_PROGRAM
+      ",
+    );
+
+    // THEN only arrays with the same length in every dimension can be assigned
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_assignment("__prg_c", "__prg_a", (256..262).into()),
+            Diagnostic::invalid_assignment("__prg_d", "__prg_a", (276..282).into()),
+        ]
+    );
+}
+
+#[test]
+fn arguments_of_incompatible_types_are_reported() {
+    // GIVEN calls passing arguments of matching and incompatible types
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        TYPE Point : STRUCT x, y : INT; END_STRUCT END_TYPE
+        TYPE Color : STRUCT r, g, b : BYTE; END_STRUCT END_TYPE
+
+        FUNCTION foo : INT
+            VAR_INPUT i : INT; s : STRING; END_VAR
+            VAR_IN_OUT p : Point; END_VAR
+            VAR_OUTPUT o : STRING; END_VAR
+        END_FUNCTION
+
+        PROGRAM prg
+            VAR i : INT; s : STRING; p : Point; c : Color; END_VAR
+            foo(i, s, p);
+            foo(s, i, c);
+            foo(i := i, s := 'abc', p := p, o => i);
+        END_PROGRAM
+      ",
+    );
+
+    // THEN the arguments that cannot be passed to their parameter are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_argument_type("i", "STRING", "INT", (440..441).into()),
+            Diagnostic::invalid_argument_type("s", "INT", "STRING", (443..444).into()),
+            Diagnostic::invalid_argument_type("p", "Color", "Point", (446..447).into()),
+            Diagnostic::invalid_argument_type("o", "INT", "STRING", (499..500).into()),
+        ]
+    );
+}
@@ -0,0 +1,88 @@
+use crate::test_utils::tests::parse_and_validate_usages;
+use crate::Diagnostic;
+
+#[test]
+fn local_variables_that_are_never_read_are_reported() {
+    let diagnostics = parse_and_validate_usages(
+        "
+        PROGRAM prg
+            VAR
+                read : INT;
+                written : INT;
+                unused : INT;
+                instance : FB;
+            END_VAR
+            VAR_TEMP
+                temp : INT;
+            END_VAR
+            VAR_INPUT
+                input : INT;
+            END_VAR
+            written := read + 1;
+            instance.x := 2;
+            instance(y => temp);
+        END_PROGRAM
+
+        FUNCTION_BLOCK FB
+            VAR_INPUT x : INT; END_VAR
+            VAR_OUTPUT y : INT; END_VAR
+        END_FUNCTION_BLOCK
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unused_variable("written", (81..88).into()),
+            Diagnostic::unused_variable("unused", (112..118).into()),
+            Diagnostic::unused_variable("temp", (214..218).into()),
+        ]
+    );
+}
+
+#[test]
+fn variables_read_by_other_pous_are_used() {
+    let diagnostics = parse_and_validate_usages(
+        "
+        PROGRAM prg
+            VAR x : INT; END_VAR
+        END_PROGRAM
+
+        ACTIONS prg
+        ACTION act
+            prg.x := x + 1;
+        END_ACTION
+        END_ACTIONS
+
+        FUNCTION foo : INT
+            foo := prg.x;
+        END_FUNCTION
+       ",
+    );
+
+    assert_eq!(diagnostics, vec![]);
+}
+
+#[test]
+fn private_methods_that_are_never_called_are_reported() {
+    let diagnostics = parse_and_validate_usages(
+        "
+        CLASS cls
+            METHOD PRIVATE called
+            END_METHOD
+
+            METHOD PRIVATE uncalled
+            END_METHOD
+
+            METHOD PUBLIC entry
+                called();
+            END_METHOD
+        END_CLASS
+       ",
+    );
+
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::unused_pou("cls.uncalled", (89..135).into())]
+    );
+}
@@ -2,7 +2,7 @@ use crate::test_utils::tests::parse_and_validate;
 use crate::Diagnostic;
 
 #[test]
-fn uninitialized_constants_are_reported() {
+fn uninitialized_constants_are_initialized_with_their_default_value() {
     let diagnostics = parse_and_validate(
         "
         VAR_GLOBAL 
@@ -29,13 +29,7 @@ fn uninitialized_constants_are_reported() {
        ",
     );
 
-    assert_eq!(
-        diagnostics,
-        vec![
-            Diagnostic::unresolved_constant("cx", None, (340..342).into()),
-            Diagnostic::unresolved_constant("cgX", None, (128..131).into()),
-        ]
-    );
+    assert_eq!(diagnostics, vec![]);
 }
 
 #[test]
@@ -48,7 +42,7 @@ fn unresolvable_variables_are_reported() {
         END_VAR
 
         VAR_GLOBAL CONSTANT
-            cgX : INT;  //unresolved
+            cgX : INT := cgY;  //unresolved
             cgXi : INT := 7;
         END_VAR
 
@@ -70,10 +64,26 @@ fn unresolvable_variables_are_reported() {
     assert_eq!(
         diagnostics,
         vec![
-            Diagnostic::unresolved_constant("cx", None, (392..394).into()),
-            Diagnostic::unresolved_constant("cai", None, (473..474).into()),
-            Diagnostic::unresolved_constant("gX", None, (45..52).into()),
-            Diagnostic::unresolved_constant("cgX", None, (154..157).into()),
+            Diagnostic::unresolved_constant(
+                "cx",
+                Some("Cyclic constant definition: prg.cx -> prg.cx"),
+                (399..401).into()
+            ),
+            Diagnostic::unresolved_constant(
+                "cai",
+                Some("Cannot resolve unknown reference 'a'"),
+                (480..481).into()
+            ),
+            Diagnostic::unresolved_constant(
+                "gX",
+                Some("Incomplete initialization - cannot evaluate const expressions"),
+                (45..52).into()
+            ),
+            Diagnostic::unresolved_constant(
+                "cgX",
+                Some("Cannot resolve unknown reference 'cgY'"),
+                (167..170).into()
+            ),
         ]
     );
 }
@@ -173,3 +183,404 @@ fn constant_fb_instances_are_illegal() {
         ]
     );
 }
+
+#[test]
+fn unknown_references_in_initializers_are_reported() {
+    // GIVEN initializers referencing an unknown constant
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        TYPE MyStruct : STRUCT
+            a : INT := unknown_const + 1;
+            b : INT := 2;
+        END_STRUCT END_TYPE
+
+        PROGRAM prg
+            VAR
+                x : INT := unknown_const + 1;
+            END_VAR
+        END_PROGRAM
+      ",
+    );
+
+    // THEN the initializers are reported with the unknown reference, struct members are
+    // resolved in the scope of their struct
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unresolved_constant(
+                "x",
+                Some("Cannot resolve unknown reference 'unknown_const'"),
+                (192..209).into()
+            ),
+            Diagnostic::unresolved_constant(
+                "a",
+                Some("Cannot resolve unknown reference 'unknown_const'"),
+                (55..72).into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn cyclic_constants_are_reported() {
+    // GIVEN constants that depend on each other and a constant that depends on them
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL CONSTANT
+            a : INT := b + 1;
+            b : INT := a + 1;
+            c : INT := a;
+        END_VAR
+      ",
+    );
+
+    // THEN every member of the cycle is reported with the cycle
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::unresolved_constant(
+                "a",
+                Some("Cyclic constant definition: a -> b -> a"),
+                (52..57).into()
+            ),
+            Diagnostic::unresolved_constant(
+                "b",
+                Some("Cyclic constant definition: b -> a -> b"),
+                (82..87).into()
+            ),
+            Diagnostic::unresolved_constant(
+                "c",
+                Some("Incomplete initialization - cannot evaluate const expressions"),
+                (112..113).into()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn unresolvable_enum_values_are_reported() {
+    // GIVEN an enum with an element referencing a non-constant variable
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL
+            x : INT;
+        END_VAR
+
+        TYPE MyEnum : (a := 1, b := x); END_TYPE
+      ",
+    );
+
+    // THEN the element's value is reported as unresolvable
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::unresolved_constant(
+            "b",
+            Some("'x' is no const reference"),
+            (94..95).into()
+        )]
+    );
+}
+
+#[test]
+fn array_ranges_with_a_lower_bound_greater_than_the_upper_bound_are_reported() {
+    // GIVEN some arrays with literal and constant bounds
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL CONSTANT
+            LEN : INT := 0;
+        END_VAR
+
+        TYPE arr : ARRAY[5..1] OF INT; END_TYPE
+        TYPE arr2 : ARRAY[1..LEN] OF INT; END_TYPE
+        TYPE arr3 : ARRAY[0..LEN, 1..2] OF INT; END_TYPE
+      ",
+    );
+
+    // THEN the descending ranges are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_array_range(5..1, (87..112).into()),
+            Diagnostic::invalid_array_range(1..0, (135..163).into()),
+        ]
+    );
+}
+
+#[test]
+fn global_variables_declared_more_than_once_are_reported() {
+    // GIVEN a global variable that is declared twice (names are case insensitive)
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL
+            x : INT;
+            y : INT;
+        END_VAR
+
+        VAR_GLOBAL
+            X : BOOL;
+        END_VAR
+      ",
+    );
+
+    // THEN the declaration that got replaced in the index is reported
+    assert_eq!(
+        diagnostics,
+        vec![Diagnostic::duplicate_global_variable("x", (32..33).into())]
+    );
+}
+
+#[test]
+fn cyclic_type_definitions_are_reported() {
+    // GIVEN aliases and sub-ranges that refer back to themselves
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        TYPE A : B; END_TYPE
+        TYPE B : C; END_TYPE
+        TYPE C : A; END_TYPE
+        TYPE D : INT(0..5); END_TYPE
+        TYPE E : D; END_TYPE
+        TYPE F : F(0..5); END_TYPE
+        TYPE G : A; END_TYPE
+      ",
+    );
+
+    // THEN every type that is part of a cycle is reported, but not the types referring into it
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::cyclic_type_definition(&["A", "B", "C", "A"], (14..20).into()),
+            Diagnostic::cyclic_type_definition(&["B", "C", "A", "B"], (43..49).into()),
+            Diagnostic::cyclic_type_definition(&["C", "A", "B", "C"], (72..78).into()),
+            Diagnostic::cyclic_type_definition(&["F", "F"], (167..179).into()),
+        ]
+    );
+}
+
+#[test]
+fn structs_containing_themselves_are_reported() {
+    // GIVEN structs that contain themselves directly or through an array, and a struct that
+    // only points to itself
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        TYPE A : STRUCT b : B; END_STRUCT END_TYPE
+        TYPE B : STRUCT a : ARRAY[0..1] OF A; END_STRUCT END_TYPE
+        TYPE C : STRUCT c : REF_TO C; a : A; END_STRUCT END_TYPE
+      ",
+    );
+
+    // THEN every struct that is part of the cycle is reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::cyclic_type_definition(&["A", "B", "A"], (14..42).into()),
+            Diagnostic::cyclic_type_definition(&["B", "A", "B"], (65..108).into()),
+        ]
+    );
+}
+
+#[test]
+fn function_blocks_containing_themselves_are_reported() {
+    // GIVEN function blocks that contain instances of themselves
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        FUNCTION_BLOCK fb VAR x : fb; END_VAR END_FUNCTION_BLOCK
+        FUNCTION_BLOCK fb2 VAR p : REF_TO fb2; END_VAR END_FUNCTION_BLOCK
+        FUNCTION_BLOCK outer VAR i : inner; END_VAR END_FUNCTION_BLOCK
+        FUNCTION_BLOCK inner VAR o : ARRAY[0..1] OF outer; END_VAR END_FUNCTION_BLOCK
+      ",
+    );
+
+    // THEN the function blocks are reported with the chain of instances
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::cyclic_type_definition(&["fb", "fb"], (9..65).into()),
+            Diagnostic::cyclic_type_definition(&["outer", "inner", "outer"], (148..210).into()),
+            Diagnostic::cyclic_type_definition(&["inner", "outer", "inner"], (219..296).into()),
+        ]
+    );
+}
+
+#[test]
+fn union_members_of_different_sizes_are_reported() {
+    // GIVEN a union with members smaller than the union
+    // WHEN it is validated
+    let diagnostics = parse_and_validate(
+        "
+        TYPE MyUnion : UNION
+            i : DINT;
+            s : INT;
+            b : ARRAY[0..3] OF BYTE;
+            r : REAL;
+            l : BOOL;
+        END_UNION END_TYPE
+      ",
+    );
+
+    // THEN the members that only partially overlap the others are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::union_member_size_mismatch("s", 16, 32, (64..65).into()),
+            Diagnostic::union_member_size_mismatch("l", 8, 32, (144..145).into()),
+        ]
+    );
+}
+
+#[test]
+fn external_variables_are_validated_against_their_global_variables() {
+    // GIVEN external variables referencing matching, mismatching and missing globals
+    // WHEN they are validated
+    let diagnostics = parse_and_validate(
+        "
+        VAR_GLOBAL
+            a : INT;
+            b : ARRAY[0..2] OF INT;
+            c : ARRAY[0..2] OF INT;
+        END_VAR
+
+        PROGRAM prg
+        VAR_EXTERNAL
+            a : INT;
+            b : ARRAY[0..2] OF INT;
+            c : ARRAY[1..3] OF INT;
+            d : INT;
+        END_VAR
+        END_PROGRAM
+
+        FUNCTION foo : INT
+        VAR_EXTERNAL
+            a : DINT;
+        END_VAR
+        END_FUNCTION
+      ",
+    );
+
+    // THEN the externals without a global of the same type are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::incompatible_external_variable(
+                "c",
+                "__prg_c",
+                "__global_c",
+                (240..241).into()
+            ),
+            Diagnostic::unresolved_external_variable("d", (276..277).into()),
+            Diagnostic::incompatible_external_variable("a", "DINT", "INT", (382..383).into()),
+        ]
+    );
+}
+
+#[test]
+fn array_initializers_with_missing_or_excess_elements_are_reported() {
+    // GIVEN array-initializers with too few or too many elements
+    // WHEN they are validated
+    let diagnostics = parse_and_validate(
+        "
+        TYPE MyArray : ARRAY[0..2] OF INT := [1, 2, 3, 4]; END_TYPE
+
+        VAR_GLOBAL
+            a : ARRAY[0..3] OF INT := [1, 2, 3, 4];
+            b : ARRAY[0..3] OF INT := [1, 2];
+            c : ARRAY[0..3] OF INT := [1, 2, 3, 4, 5, 6];
+            d : ARRAY[0..3] OF INT := [2(1), 3(7)];
+            e : ARRAY[0..1] OF ARRAY[0..1] OF INT := [[1, 2], [3]];
+        END_VAR
+      ",
+    );
+
+    // THEN missing elements are reported at the initializer and excess elements at the first excess element
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::too_many_initializers(3, (56..57).into()),
+            Diagnostic::partial_initializer(4, 2, (179..185).into()),
+            Diagnostic::too_many_initializers(4, (238..239).into()),
+            Diagnostic::too_many_initializers(4, (292..293).into()),
+            Diagnostic::partial_initializer(2, 1, (359..362).into()),
+        ]
+    );
+}
+
+#[test]
+fn declarations_that_differ_only_by_case_are_reported() {
+    // GIVEN types, POUs, struct members and POU members declared twice in the same or in a different case
+    // WHEN they are validated
+    let diagnostics = parse_and_validate(
+        "
+        TYPE Point : STRUCT x : INT; X : INT; END_STRUCT END_TYPE
+        TYPE POINT : STRUCT a : INT; END_STRUCT END_TYPE
+        FUNCTION_BLOCK fb
+        VAR_INPUT value : INT; END_VAR
+        VAR Value : INT; END_VAR
+        END_FUNCTION_BLOCK
+        FUNCTION_BLOCK FB END_FUNCTION_BLOCK
+        PROGRAM prg
+        VAR counter, counter : INT; END_VAR
+        END_PROGRAM
+      ",
+    );
+
+    // THEN every repeated declaration is reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::duplicate_declaration("POINT", "Point", (80..114).into()),
+            Diagnostic::duplicate_declaration("FB", "fb", (257..293).into()),
+            Diagnostic::duplicate_declaration("Value", "value", (201..206).into()),
+            Diagnostic::duplicate_declaration("counter", "counter", (335..342).into()),
+            Diagnostic::duplicate_declaration("X", "x", (38..39).into()),
+        ]
+    );
+}
+
+#[test]
+fn pack_modes_and_member_offsets_are_validated() {
+    // GIVEN structs with pack modes and members with expected byte offsets
+    // WHEN they are validated
+    let diagnostics = parse_and_validate(
+        "
+        {attribute 'pack_mode' := '3'}
+        TYPE A : STRUCT a : BYTE; END_STRUCT END_TYPE
+        {attribute 'pack_mode' := '2'}
+        TYPE B : STRUCT a : BYTE; b : DINT; END_STRUCT END_TYPE
+        {attribute 'pack_mode' := '1'}
+        TYPE C : STRUCT
+            a : BYTE;
+            {attribute 'offset' := '1'} b : DINT;
+            {attribute 'offset' := '4'} c : INT;
+        END_STRUCT END_TYPE
+        TYPE D : STRUCT a : BYTE; {attribute 'offset' := '4'} b : DINT; {offset x} c : INT; END_STRUCT END_TYPE
+      ",
+    );
+
+    // THEN invalid attribute values, pack modes that need padding and misplaced members are reported
+    assert_eq!(
+        diagnostics,
+        vec![
+            Diagnostic::invalid_attribute_value(
+                "pack_mode",
+                Some("3"),
+                "'0', '1', '2', '4' or '8'",
+                (9..39).into()
+            ),
+            Diagnostic::pack_mode_requires_padding("B", 2, (102..132).into()),
+            Diagnostic::member_offset_mismatch("c", 4, 5, (372..373).into()),
+            Diagnostic::invalid_attribute_value(
+                "offset",
+                Some("x"),
+                "a byte offset",
+                (481..491).into()
+            ),
+        ]
+    );
+}
@@ -0,0 +1,170 @@
+use std::collections::HashSet;
+
+use crate::{
+    ast::{
+        AccessModifier, AstStatement, CompilationUnit, Implementation, LinkageType, PouType,
+        VariableBlockType, PROPERTY_GETTER_PREFIX, PROPERTY_SETTER_PREFIX,
+    },
+    index::Index,
+    resolver::{AnnotationMap, AnnotationMapImpl, StatementAnnotation},
+    Diagnostic,
+};
+
+/// validates that the declared local variables are read and the private methods are called.
+/// A declaration may be used by any unit of the compilation, so the usages of all units are
+/// collected before the units are validated
+pub struct UsageValidator {
+    /// the qualified names of the variables that are read (lower case)
+    read_variables: HashSet<String>,
+    /// the qualified names of the called functions, methods and programs (lower case)
+    called_pous: HashSet<String>,
+}
+
+impl UsageValidator {
+    /// collects the variables read and the POUs called by the given units
+    pub fn new<'u>(
+        units: impl IntoIterator<Item = &'u CompilationUnit>,
+        annotations: &AnnotationMapImpl,
+    ) -> UsageValidator {
+        let mut validator = UsageValidator {
+            read_variables: HashSet::new(),
+            called_pous: HashSet::new(),
+        };
+        for unit in units {
+            let initializers = unit
+                .global_vars
+                .iter()
+                .chain(unit.units.iter().flat_map(|it| it.variable_blocks.iter()))
+                .flat_map(|it| it.variables.iter())
+                .filter_map(|it| it.initializer.as_ref())
+                .chain(unit.types.iter().filter_map(|it| it.initializer.as_ref()));
+            for initializer in initializers {
+                validator.visit(initializer, annotations);
+            }
+            for statement in unit.implementations.iter().flat_map(|it| &it.statements) {
+                validator.visit(statement, annotations);
+            }
+        }
+        validator
+    }
+
+    fn visit(&mut self, statement: &AstStatement, annotations: &AnnotationMapImpl) {
+        match statement {
+            AstStatement::Assignment {
+                left: target,
+                right: value,
+                ..
+            }
+            | AstStatement::OutputAssignment {
+                left: value,
+                right: target,
+                ..
+            } => {
+                self.visit_assignment_target(target, annotations);
+                self.visit(value, annotations);
+            }
+            _ => {
+                match annotations.get(statement) {
+                    Some(StatementAnnotation::Variable { qualified_name, .. }) => {
+                        self.read_variables.insert(qualified_name.to_lowercase());
+                    }
+                    Some(StatementAnnotation::Function { qualified_name, .. })
+                    | Some(StatementAnnotation::Program { qualified_name }) => {
+                        self.called_pous.insert(qualified_name.to_lowercase());
+                    }
+                    _ => {}
+                }
+                for child in statement.get_children() {
+                    self.visit(child, annotations);
+                }
+            }
+        }
+    }
+
+    /// visits the target of an assignment, the assigned variable is written, not read. The
+    /// variables accessed to get to the assigned element are read
+    fn visit_assignment_target(&mut self, target: &AstStatement, annotations: &AnnotationMapImpl) {
+        match target {
+            AstStatement::Reference { .. } => {}
+            AstStatement::QualifiedReference { elements, .. } => elements
+                .iter()
+                .take(elements.len().saturating_sub(1))
+                .for_each(|it| self.visit(it, annotations)),
+            _ => self.visit(target, annotations),
+        }
+    }
+
+    /// returns a warning for every local variable of the given unit that is never read and
+    /// every private method that is never called
+    pub fn validate_unit(&self, unit: &CompilationUnit, index: &Index) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        //the generic functions are used by their instances
+        let pous = unit
+            .units
+            .iter()
+            .filter(|it| it.linkage == LinkageType::Internal && it.generics.is_empty());
+        for pou in pous {
+            //constants may be used by the dimensions of declared types, which are not resolved
+            //to variables. Located variables are used by the hardware
+            let variables = pou
+                .variable_blocks
+                .iter()
+                .filter(|it| {
+                    matches!(
+                        it.variable_block_type,
+                        VariableBlockType::Local | VariableBlockType::Temp
+                    ) && !it.constant
+                })
+                .flat_map(|it| it.variables.iter())
+                .filter(|it| it.address.is_none());
+            for variable in variables {
+                let is_read = index
+                    .find_member(&pou.name, &variable.name)
+                    .map_or(true, |it| {
+                        self.read_variables
+                            .contains(&it.get_qualified_name().to_lowercase())
+                    });
+                if !is_read {
+                    diagnostics.push(Diagnostic::unused_variable(
+                        &variable.name,
+                        variable.location.clone(),
+                    ));
+                }
+            }
+        }
+
+        for implementation in unit
+            .implementations
+            .iter()
+            .filter(|it| is_private_method(it))
+        {
+            if !self
+                .called_pous
+                .contains(&implementation.name.to_lowercase())
+            {
+                let location = unit
+                    .units
+                    .iter()
+                    .find(|it| it.name == implementation.name)
+                    .map_or(&implementation.location, |it| &it.location);
+                diagnostics.push(Diagnostic::unused_pou(
+                    &implementation.name,
+                    location.clone(),
+                ));
+            }
+        }
+        diagnostics
+    }
+}
+
+/// returns true if the given implementation is a private method that can only be called by its
+/// own class. The accessors of properties are called by accessing their property
+fn is_private_method(implementation: &Implementation) -> bool {
+    let name = implementation.name.rsplit('.').next().unwrap_or_default();
+    matches!(implementation.pou_type, PouType::Method { .. })
+        && implementation.access == Some(AccessModifier::Private)
+        && implementation.linkage == LinkageType::Internal
+        && !implementation.overriding
+        && !name.starts_with(PROPERTY_GETTER_PREFIX)
+        && !name.starts_with(PROPERTY_SETTER_PREFIX)
+}
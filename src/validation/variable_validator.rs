@@ -1,14 +1,19 @@
 use crate::{
     ast::{
-        AstStatement, DataType, DataTypeDeclaration, PouType, SourceRange, Variable, VariableBlock,
-        VariableBlockType,
+        self, AstStatement, DataType, DataTypeDeclaration, LinkageType, PouType, SourceRange,
+        Variable, VariableBlock, VariableBlockType,
+    },
+    index::{const_expressions::ConstExpression, Index, VariableIndexEntry},
+    typesystem::{
+        self, DataTypeInformation, StringEncoding, StructSource, OFFSET_ATTRIBUTE,
+        PACK_MODE_ATTRIBUTE,
     },
-    index::{const_expressions::ConstExpression, Index},
-    typesystem::{DataTypeInformation, StructSource},
     Diagnostic,
 };
 
-use super::ValidationContext;
+use super::{
+    stmt_validator::validate_assigned_literal, validate_unique_declarations, ValidationContext,
+};
 
 /// validates variables & datatypes
 
@@ -27,16 +32,71 @@ impl VariableValidator {
         if block.constant
             && !matches!(
                 block.variable_block_type,
-                VariableBlockType::Global | VariableBlockType::Local
+                VariableBlockType::Global | VariableBlockType::Local | VariableBlockType::External
             )
         {
             self.diagnostics
                 .push(Diagnostic::invalid_constant_block(block.location.clone()))
         }
 
+        if block.variable_block_type == VariableBlockType::External {
+            for variable in &block.variables {
+                self.validate_external_variable(variable, context);
+            }
+            return;
+        }
+
         for variable in &block.variables {
             self.validate_variable(variable, context);
         }
+
+        if block.variable_block_type == VariableBlockType::Global
+            && block.linkage != LinkageType::External
+        {
+            for variable in &block.variables {
+                self.validate_unique_global_variable(variable, context);
+            }
+        }
+    }
+
+    /// validates that there is no other internal global variable with the same name
+    fn validate_unique_global_variable(
+        &mut self,
+        variable: &Variable,
+        context: &ValidationContext,
+    ) {
+        if let Some(v_entry) = context.index.find_global_variable(variable.name.as_str()) {
+            //the index only keeps one of the globals with the same name, all others are duplicates
+            if !v_entry.is_external() && !is_declared_by(v_entry, variable) {
+                self.diagnostics.push(Diagnostic::duplicate_global_variable(
+                    variable.name.as_str(),
+                    variable.location.clone(),
+                ));
+            }
+        }
+    }
+
+    /// validates that the given external variable references a global variable of the same type
+    fn validate_external_variable(&mut self, variable: &Variable, context: &ValidationContext) {
+        let external_type = variable.data_type.get_name().unwrap_or_default();
+        match context.index.find_global_variable(variable.name.as_str()) {
+            Some(global) if !is_same_type(external_type, global.get_type_name(), context.index) => {
+                self.diagnostics
+                    .push(Diagnostic::incompatible_external_variable(
+                        variable.name.as_str(),
+                        external_type,
+                        global.get_type_name(),
+                        variable.location.clone(),
+                    ))
+            }
+            Some(_) => {}
+            None => self
+                .diagnostics
+                .push(Diagnostic::unresolved_external_variable(
+                    variable.name.as_str(),
+                    variable.location.clone(),
+                )),
+        }
     }
 
     pub fn validate_variable(&mut self, variable: &Variable, context: &ValidationContext) {
@@ -45,34 +105,16 @@ impl VariableValidator {
             .and_then(|qualifier| context.index.find_member(qualifier, variable.name.as_str()))
             .or_else(|| context.index.find_global_variable(variable.name.as_str()))
         {
-            match v_entry.initial_value.and_then(|initial_id| {
-                context
+            self.validate_initial_value(v_entry, context);
+
+            if let Some(initializer) = &variable.initializer {
+                let target_type = context
                     .index
-                    .get_const_expressions()
-                    .find_const_expression(&initial_id)
-            }) {
-                Some(ConstExpression::Unresolvable { reason, statement }) => {
-                    self.diagnostics.push(Diagnostic::unresolved_constant(
-                        variable.name.as_str(),
-                        Some(reason),
-                        statement.get_location(),
-                    ));
-                }
-                Some(ConstExpression::Unresolved { statement, .. }) => {
-                    self.diagnostics.push(Diagnostic::unresolved_constant(
-                        variable.name.as_str(),
-                        None,
-                        statement.get_location(),
-                    ));
-                }
-                None if v_entry.is_constant() => {
-                    self.diagnostics.push(Diagnostic::unresolved_constant(
-                        variable.name.as_str(),
-                        None,
-                        variable.location.clone(),
-                    ));
-                }
-                _ => {}
+                    .get_effective_type_by_name(v_entry.get_type_name())
+                    .get_type_information();
+                self.diagnostics
+                    .extend(validate_assigned_literal(initializer, target_type));
+                self.validate_array_initializer(initializer, target_type, context.index);
             }
 
             //check if we declared a constant fb-instance or class-instance
@@ -87,15 +129,158 @@ impl VariableValidator {
         }
     }
 
+    /// validates that the given initializer provides a value for every element of an array,
+    /// missing elements are initialized with their default value, excess elements are not allowed
+    pub fn validate_array_initializer(
+        &mut self,
+        initializer: &AstStatement,
+        target_type: &DataTypeInformation,
+        index: &Index,
+    ) {
+        let (inner_type_name, dimensions) = if let DataTypeInformation::Array {
+            inner_type_name,
+            dimensions,
+            ..
+        } = target_type
+        {
+            (inner_type_name, dimensions)
+        } else {
+            return;
+        };
+        let elements = match initializer {
+            AstStatement::LiteralArray { elements, .. } => elements
+                .as_deref()
+                .map(ast::flatten_expression_list)
+                .unwrap_or_default(),
+            AstStatement::ExpressionList { .. } | AstStatement::MultipliedStatement { .. } => {
+                ast::flatten_expression_list(initializer)
+            }
+            _ => return,
+        };
+        //bounds that cannot be resolved to a literal are not checked here
+        let expected_len = match dimensions
+            .iter()
+            .map(|it| it.get_length(index))
+            .product::<Result<u32, _>>()
+        {
+            Ok(len) => len as usize,
+            Err(_) => return,
+        };
+
+        if let Some(first_excess) = elements.get(expected_len) {
+            self.diagnostics.push(Diagnostic::too_many_initializers(
+                expected_len,
+                first_excess.get_location(),
+            ));
+        } else if elements.len() < expected_len {
+            self.diagnostics.push(Diagnostic::partial_initializer(
+                expected_len,
+                elements.len(),
+                initializer.get_location(),
+            ));
+        }
+
+        //the elements of nested arrays are validated once, even if they are repeated (e.g. `[2([1, 2])]`)
+        let inner_type = index
+            .get_effective_type_by_name(inner_type_name)
+            .get_type_information();
+        let mut validated: Vec<&AstStatement> = Vec::new();
+        for element in elements.into_iter().take(expected_len) {
+            if !validated.iter().any(|it| std::ptr::eq(*it, element)) {
+                self.validate_array_initializer(element, inner_type, index);
+                validated.push(element);
+            }
+        }
+    }
+
+    /// reports the initial value of the given variable if it could not be resolved to a constant,
+    /// a constant without an initial value is initialized with its type's default value
+    fn validate_initial_value(
+        &mut self,
+        v_entry: &VariableIndexEntry,
+        context: &ValidationContext,
+    ) {
+        match v_entry.initial_value.and_then(|initial_id| {
+            context
+                .index
+                .get_const_expressions()
+                .find_const_expression(&initial_id)
+        }) {
+            Some(ConstExpression::Unresolvable { reason, statement }) => {
+                self.diagnostics.push(Diagnostic::unresolved_constant(
+                    v_entry.get_name(),
+                    Some(reason),
+                    statement.get_location(),
+                ));
+            }
+            Some(ConstExpression::Unresolved { statement, .. }) => {
+                self.diagnostics.push(Diagnostic::unresolved_constant(
+                    v_entry.get_name(),
+                    None,
+                    statement.get_location(),
+                ));
+            }
+            _ => {}
+        }
+    }
+
     pub fn validate_data_type_declaration(&self, _declaration: &DataTypeDeclaration) {}
 
-    pub fn validate_data_type(&mut self, declaration: &DataType, location: &SourceRange) {
+    fn validate_unique_members(&mut self, members: &[Variable]) {
+        self.diagnostics.extend(validate_unique_declarations(
+            members.iter().map(|it| (it.name.as_str(), &it.location)),
+        ));
+    }
+
+    pub fn validate_data_type(
+        &mut self,
+        declaration: &DataType,
+        location: &SourceRange,
+        context: &ValidationContext,
+    ) {
         match declaration {
-            DataType::StructType { variables, .. } => {
+            DataType::StructType { name, variables } => {
                 if variables.is_empty() {
                     self.diagnostics
                         .push(Diagnostic::empty_variable_block(location.clone()));
                 }
+                self.validate_unique_members(variables);
+                self.validate_struct_is_not_cyclic(name.as_deref(), location, context);
+                if let Some(name) = name {
+                    self.validate_struct_layout(name, variables, context);
+                }
+            }
+            DataType::UnionType { name, variables } => {
+                if variables.is_empty() {
+                    self.diagnostics
+                        .push(Diagnostic::empty_variable_block(location.clone()));
+                }
+                self.validate_unique_members(variables);
+                self.validate_struct_is_not_cyclic(name.as_deref(), location, context);
+                //members of different sizes only partially overlap each other
+                let member_sizes = variables
+                    .iter()
+                    .filter_map(|v| {
+                        v.data_type
+                            .get_name()
+                            .and_then(|type_name| get_size_in_bits(type_name, context.index))
+                            .map(|size| (v, size))
+                    })
+                    .collect::<Vec<_>>();
+                if let Some(union_size) = member_sizes.iter().map(|(_, size)| *size).max() {
+                    member_sizes
+                        .iter()
+                        .filter(|(_, size)| *size < union_size)
+                        .for_each(|(v, size)| {
+                            self.diagnostics
+                                .push(Diagnostic::union_member_size_mismatch(
+                                    v.name.as_str(),
+                                    *size,
+                                    union_size,
+                                    v.location.clone(),
+                                ))
+                        });
+                }
             }
             DataType::EnumType {
                 elements: AstStatement::ExpressionList { expressions, .. },
@@ -104,9 +289,243 @@ impl VariableValidator {
                 self.diagnostics
                     .push(Diagnostic::empty_variable_block(location.clone()));
             }
+            DataType::ArrayType {
+                name: Some(array_name),
+                ..
+            } => {
+                if let Some(DataTypeInformation::Array { dimensions, .. }) =
+                    context.index.find_effective_type_info(array_name)
+                {
+                    //bounds that cannot be resolved to a literal are not checked here
+                    dimensions
+                        .iter()
+                        .filter_map(|it| it.get_range(context.index).ok())
+                        .filter(|range| range.start > range.end)
+                        .for_each(|range| {
+                            self.diagnostics
+                                .push(Diagnostic::invalid_array_range(range, location.clone()))
+                        });
+                }
+            }
+            DataType::SubRangeType {
+                name: Some(type_name),
+                ..
+            } => {
+                if let Some(cycle) = find_cyclic_type_definition(type_name, context.index) {
+                    self.diagnostics
+                        .push(Diagnostic::cyclic_type_definition(&cycle, location.clone()));
+                }
+            }
+            DataType::EnumType {
+                name: Some(enum_name),
+                elements,
+                ..
+            } => {
+                //every element's value must be resolvable at compile time
+                for element_name in ast::get_enum_element_names(elements) {
+                    if let Some(v_entry) = context.index.find_enum_element(enum_name, &element_name)
+                    {
+                        self.validate_initial_value(v_entry, context);
+                    }
+                }
+            }
             _ => {}
         }
     }
+
+    /// validates the struct's pack mode and the byte offsets its members are expected at
+    fn validate_struct_layout(
+        &mut self,
+        type_name: &str,
+        members: &[Variable],
+        context: &ValidationContext,
+    ) {
+        let index = context.index;
+        let pack_mode = typesystem::get_pack_mode(type_name, index);
+        if let Some(attribute) = index.find_attribute(type_name, PACK_MODE_ATTRIBUTE) {
+            match pack_mode {
+                None => self.diagnostics.push(Diagnostic::invalid_attribute_value(
+                    PACK_MODE_ATTRIBUTE,
+                    attribute.value.as_deref(),
+                    "'0', '1', '2', '4' or '8'",
+                    attribute.location.clone(),
+                )),
+                //the generated struct is packed, its members cannot be aligned by padding
+                Some(pack_mode)
+                    if typesystem::is_packed_struct(type_name, index)
+                        && typesystem::get_struct_layout(type_name, Some(pack_mode), index)
+                            != typesystem::get_struct_layout(type_name, Some(1), index) =>
+                {
+                    self.diagnostics
+                        .push(Diagnostic::pack_mode_requires_padding(
+                            type_name,
+                            pack_mode,
+                            attribute.location.clone(),
+                        ))
+                }
+                _ => {}
+            }
+        }
+
+        let offsets = typesystem::get_struct_layout(type_name, pack_mode, index)
+            .map(|it| it.offsets)
+            .unwrap_or_default();
+        for (member, offset) in members.iter().zip(offsets) {
+            let member_name = format!("{}.{}", type_name, member.name);
+            if let Some(attribute) = index.find_attribute(&member_name, OFFSET_ATTRIBUTE) {
+                match attribute
+                    .value
+                    .as_deref()
+                    .and_then(|it| it.trim().parse::<u32>().ok())
+                {
+                    Some(expected) if expected != offset => {
+                        self.diagnostics.push(Diagnostic::member_offset_mismatch(
+                            member.name.as_str(),
+                            expected,
+                            offset,
+                            member.location.clone(),
+                        ))
+                    }
+                    Some(_) => {}
+                    None => self.diagnostics.push(Diagnostic::invalid_attribute_value(
+                        OFFSET_ATTRIBUTE,
+                        attribute.value.as_deref(),
+                        "a byte offset",
+                        attribute.location.clone(),
+                    )),
+                }
+            }
+        }
+    }
+
+    /// a struct that contains itself (without a pointer in between) would need infinite memory
+    pub fn validate_struct_is_not_cyclic(
+        &mut self,
+        type_name: Option<&str>,
+        location: &SourceRange,
+        context: &ValidationContext,
+    ) {
+        if let Some(cycle) =
+            type_name.and_then(|it| context.index.find_cyclic_struct_definition(it))
+        {
+            self.diagnostics
+                .push(Diagnostic::cyclic_type_definition(&cycle, location.clone()));
+        }
+    }
+}
+
+/// returns the chain of type-names if the given alias or sub-range type refers back to itself
+/// (e.g. `A -> B -> A` for `TYPE A : B; END_TYPE TYPE B : A; END_TYPE`)
+fn find_cyclic_type_definition<'idx>(
+    type_name: &'idx str,
+    index: &'idx Index,
+) -> Option<Vec<&'idx str>> {
+    let mut chain = vec![type_name];
+    let mut current_type = index.get_type(type_name).ok()?;
+    loop {
+        let referenced_type = match current_type.get_type_information() {
+            DataTypeInformation::Alias {
+                referenced_type, ..
+            }
+            | DataTypeInformation::SubRange {
+                referenced_type, ..
+            } => referenced_type.as_str(),
+            _ => return None,
+        };
+        let is_known = chain
+            .iter()
+            .any(|it| it.eq_ignore_ascii_case(referenced_type));
+        chain.push(referenced_type);
+        if referenced_type.eq_ignore_ascii_case(type_name) {
+            return Some(chain);
+        } else if is_known {
+            //a cycle that does not contain the given type, it is reported at its own members
+            return None;
+        }
+        current_type = index.get_type(referenced_type).ok()?;
+    }
+}
+
+/// returns whether both types resolve to the same type, arrays and strings declared inline
+/// are compared by their element type and their dimensions or length
+fn is_same_type(left: &str, right: &str, index: &Index) -> bool {
+    let left = index
+        .get_effective_type_by_name(left)
+        .get_type_information();
+    let right = index
+        .get_effective_type_by_name(right)
+        .get_type_information();
+    match (left, right) {
+        (
+            DataTypeInformation::Array {
+                inner_type_name: left_inner,
+                dimensions: left_dimensions,
+                ..
+            },
+            DataTypeInformation::Array {
+                inner_type_name: right_inner,
+                dimensions: right_dimensions,
+                ..
+            },
+        ) => {
+            left_dimensions.len() == right_dimensions.len()
+                && left_dimensions
+                    .iter()
+                    .zip(right_dimensions.iter())
+                    .all(|(l, r)| l.get_range(index).ok() == r.get_range(index).ok())
+                && is_same_type(left_inner, right_inner, index)
+        }
+        (
+            DataTypeInformation::String {
+                size: left_size,
+                encoding: left_encoding,
+            },
+            DataTypeInformation::String {
+                size: right_size,
+                encoding: right_encoding,
+            },
+        ) => {
+            left_encoding == right_encoding
+                && left_size.as_int_value(index).ok() == right_size.as_int_value(index).ok()
+        }
+        _ => left.get_name().eq_ignore_ascii_case(right.get_name()),
+    }
+}
+
+/// returns the number of bits used to store the given type or None if
+/// its size cannot be determined without generating it (e.g. structs)
+fn get_size_in_bits(type_name: &str, index: &Index) -> Option<u32> {
+    match index
+        .get_intrinsic_type_by_name(type_name)
+        .get_type_information()
+    {
+        info @ DataTypeInformation::Integer { .. }
+        | info @ DataTypeInformation::Float { .. }
+        | info @ DataTypeInformation::Enum { .. } => Some(info.get_size()),
+        DataTypeInformation::String { size, encoding } => {
+            let char_size = if *encoding == StringEncoding::Utf8 {
+                8
+            } else {
+                16
+            };
+            size.as_int_value(index)
+                .ok()
+                .map(|len| len as u32 * char_size)
+        }
+        DataTypeInformation::Array {
+            inner_type_name,
+            dimensions,
+            ..
+        } => {
+            let inner_size = get_size_in_bits(inner_type_name, index)?;
+            dimensions
+                .iter()
+                .map(|it| it.get_length(index).ok())
+                .product::<Option<u32>>()
+                .map(|len| len * inner_size)
+        }
+        _ => None,
+    }
 }
 
 /// returns whether this data_type is a function block, a class or an array/pointer of/to these
@@ -151,6 +570,17 @@ fn data_type_is_fb_or_class_instance(type_name: &str, index: &Index) -> bool {
     }
 }
 
+/// returns true if the given index entry was created from the given global variable. The entry is
+/// identified by its qualified name and the file and range it is declared at, the same range of
+/// another file belongs to another declaration
+fn is_declared_by(entry: &VariableIndexEntry, variable: &Variable) -> bool {
+    entry
+        .get_qualified_name()
+        .eq_ignore_ascii_case(variable.name.as_str())
+        && entry.source_location.get_file() == variable.location.get_file()
+        && entry.source_location.to_range() == variable.location.to_range()
+}
+
 #[cfg(test)]
 mod variable_validator_tests {
     use crate::test_utils::tests::parse_and_validate;
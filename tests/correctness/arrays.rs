@@ -431,3 +431,100 @@ fn bool_array_assignments() {
     assert_eq!(maintype.y, 222);
     assert_eq!(maintype.z, 333);
 }
+
+#[test]
+fn whole_arrays_and_structs_can_be_assigned() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    #[derive(Default)]
+    struct Point {
+        x: i32,
+        y: i32,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    #[derive(Default)]
+    struct MainType {
+        a: [i32; 4],
+        b: [i32; 4],
+        m: [[i32; 4]; 2],
+        p: Point,
+        q: Point,
+    }
+
+    // GIVEN arrays and structs assigned as a whole
+    let function = r"
+        TYPE Point : STRUCT x, y : DINT; END_STRUCT END_TYPE
+
+        PROGRAM main
+        VAR
+            a : ARRAY[0..3] OF DINT;
+            b : ARRAY[1..4] OF DINT := [1, 2, 3, 4];
+            m : ARRAY[0..1] OF ARRAY[0..3] OF DINT;
+            p : Point;
+            q : Point := (x := 5, y := 6);
+        END_VAR
+            a := b;
+            b[1] := 10;
+            m[1] := a;
+            m[0] := b;
+            p := q;
+            q.x := 7;
+        END_PROGRAM
+        ";
+
+    let mut maintype = MainType::default();
+    let _: i32 = compile_and_run(function.to_string(), &mut maintype);
+
+    // THEN the values are copied, changing the source afterwards does not change the copy
+    assert_eq!(maintype.a, [1, 2, 3, 4]);
+    assert_eq!(maintype.m, [[10, 2, 3, 4], [1, 2, 3, 4]]);
+    assert_eq!((maintype.p.x, maintype.p.y), (5, 6));
+    assert_eq!((maintype.q.x, maintype.q.y), (7, 6));
+}
+
+#[test]
+fn nested_member_and_array_accesses_can_be_read_and_written() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    #[derive(Default)]
+    struct MainType {
+        bit: bool,
+        bits: u16,
+        speed: i16,
+    }
+
+    // GIVEN structs with arrays of structs accessed through several levels
+    let function = r"
+        TYPE Status : STRUCT bits : WORD; speed : INT; END_STRUCT END_TYPE
+        TYPE Motor : STRUCT status : Status; END_STRUCT END_TYPE
+        TYPE Conveyor : STRUCT motors : ARRAY[0..3] OF Motor; END_STRUCT END_TYPE
+
+        PROGRAM main
+        VAR
+            bit : BOOL;
+            bits : WORD;
+            speed : INT;
+        END_VAR
+        VAR_TEMP
+            conveyors : ARRAY[0..1] OF Conveyor;
+            i : INT := 2;
+        END_VAR
+            conveyors[1].motors[i].status.bits := 16#0008;
+            conveyors[1].motors[i].status.bits.0 := TRUE;
+            conveyors[1].motors[conveyors[0].motors[i].status.speed + 1].status.speed := 42;
+            bit := conveyors[1].motors[i].status.bits.3;
+            bits := conveyors[1].motors[i].status.bits;
+            speed := conveyors[1].motors[1].status.speed;
+        END_PROGRAM
+        ";
+
+    let mut maintype = MainType::default();
+    let _: i32 = compile_and_run(function.to_string(), &mut maintype);
+
+    // THEN every level is accessed at the right address
+    assert!(maintype.bit);
+    assert_eq!(maintype.bits, 0x0009);
+    assert_eq!(maintype.speed, 42);
+}
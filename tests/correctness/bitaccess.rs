@@ -227,3 +227,60 @@ fn byteaccess_assignment_should_not_override_current_values() {
     let res: i32 = compile_and_run(prog, &mut MainType {});
     assert_eq!(res, 0b0000_0000_1100_0011_1010_1010_0101_0101);
 }
+
+#[test]
+fn bit_shifts_and_rotations() {
+    let prog = "
+    PROGRAM main
+    VAR
+        b : BYTE;
+        w : WORD;
+        d : DWORD;
+        amount : INT;
+        shifted_left : BYTE;
+        shifted_right : WORD;
+        shifted_out : DWORD;
+        rotated_left : BYTE;
+        rotated_right : WORD;
+        rotated_modulo : DWORD;
+    END_VAR
+    shifted_left    := SHL(b, 1);       //2#0000_0010
+    shifted_right   := SHR(w, 4);       //16#0800
+    shifted_out     := SHL(d, amount);  //0
+    rotated_left    := ROL(b, 1);       //2#0000_0011
+    rotated_right   := ROR(w, 4);       //16#1800
+    rotated_modulo  := ROL(d, amount);  //16#3456_7812
+    END_PROGRAM";
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    #[derive(Default, Debug)]
+    struct Type {
+        b: u8,
+        w: u16,
+        d: u32,
+        amount: i16,
+        shifted_left: u8,
+        shifted_right: u16,
+        shifted_out: u32,
+        rotated_left: u8,
+        rotated_right: u16,
+        rotated_modulo: u32,
+    }
+    let mut param = Type {
+        b: 0b1000_0001,
+        w: 0x8001,
+        d: 0x1234_5678,
+        amount: 40,
+        ..Type::default()
+    };
+
+    let _: i32 = compile_and_run(prog, &mut param);
+
+    assert_eq!(0b0000_0010, param.shifted_left);
+    assert_eq!(0x0800, param.shifted_right);
+    assert_eq!(0, param.shifted_out);
+    assert_eq!(0b0000_0011, param.rotated_left);
+    assert_eq!(0x1800, param.rotated_right);
+    assert_eq!(0x3456_7812, param.rotated_modulo);
+}
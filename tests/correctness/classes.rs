@@ -57,3 +57,390 @@ fn class_reference_in_pou() {
     );
     assert_eq!(res, 10);
 }
+
+#[test]
+fn this_references_the_current_instance() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MyClass {
+        x: i16,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MyFb {
+        y: i16,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        cl: MyClass,
+        fb: MyFb,
+        x: i16,
+    }
+
+    let source = "
+        CLASS MyClass
+            VAR
+                x : INT;
+            END_VAR
+
+            METHOD PRIVATE double : INT
+                double := THIS.x * 2;
+            END_METHOD
+
+            METHOD PUBLIC set : INT
+                VAR_INPUT x : INT; END_VAR
+                THIS.x := x;
+                set := THIS.double();
+            END_METHOD
+        END_CLASS
+
+        FUNCTION_BLOCK MyFb
+            VAR
+                y : INT;
+            END_VAR
+            THIS.y := THIS.y + 1;
+        END_FUNCTION_BLOCK
+
+        FUNCTION main : DINT
+        VAR
+            cl : MyClass;
+            fb : MyFb;
+            x : INT;
+        END_VAR
+        x := cl.set(20);
+        fb();
+        fb();
+        main := x + cl.x + fb.y;
+        END_FUNCTION
+        ";
+
+    let res: i32 = compile_and_run(
+        source,
+        &mut MainType {
+            cl: MyClass { x: 0 },
+            fb: MyFb { y: 0 },
+            x: 0,
+        },
+    );
+    assert_eq!(res, 62);
+}
+
+#[test]
+fn interface_methods_are_dispatched_to_the_assigned_instance() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct Square {
+        side: i32,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct Rectangle {
+        width: i32,
+        height: i32,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct Shape {
+        instance: *mut u8,
+        vtable: *mut u8,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        sq: Square,
+        re: Rectangle,
+        shape: Shape,
+        square_area: i32,
+        square_old: i32,
+        rectangle_area: i32,
+        rectangle_old: i32,
+    }
+
+    let source = "
+        INTERFACE IShape
+            METHOD area : DINT END_METHOD
+            METHOD scale
+                VAR_INPUT factor : DINT; END_VAR
+                VAR_OUTPUT old : DINT; END_VAR
+            END_METHOD
+        END_INTERFACE
+
+        FUNCTION_BLOCK square IMPLEMENTS IShape
+            VAR side : DINT; END_VAR
+
+            METHOD area : DINT
+                area := side * side;
+            END_METHOD
+
+            METHOD scale
+                VAR_INPUT factor : DINT; END_VAR
+                VAR_OUTPUT old : DINT; END_VAR
+                VAR previous : DINT; END_VAR
+                previous := side;
+                side := side * factor;
+                old := previous;
+            END_METHOD
+        END_FUNCTION_BLOCK
+
+        FUNCTION_BLOCK rectangle IMPLEMENTS IShape
+            VAR width, height : DINT; END_VAR
+
+            METHOD scale
+                VAR_INPUT factor : DINT; END_VAR
+                VAR_OUTPUT old : DINT; END_VAR
+                old := width * height;
+                width := width * factor;
+                height := height * factor;
+            END_METHOD
+
+            METHOD area : DINT
+                area := width * height;
+            END_METHOD
+        END_FUNCTION_BLOCK
+
+        PROGRAM main
+            VAR
+                sq : square;
+                re : rectangle;
+                shape : IShape;
+                square_area, square_old : DINT;
+                rectangle_area, rectangle_old : DINT;
+            END_VAR
+            sq.side := 3;
+            re.width := 2;
+            re.height := 5;
+
+            shape := sq;
+            shape.scale(factor := 2, old => square_old);
+            square_area := shape.area();
+
+            shape := re;
+            shape.scale(factor := 3, old => rectangle_old);
+            rectangle_area := shape.area();
+        END_PROGRAM
+        ";
+
+    let mut main = MainType {
+        sq: Square { side: 0 },
+        re: Rectangle {
+            width: 0,
+            height: 0,
+        },
+        shape: Shape {
+            instance: std::ptr::null_mut(),
+            vtable: std::ptr::null_mut(),
+        },
+        square_area: 0,
+        square_old: 0,
+        rectangle_area: 0,
+        rectangle_old: 0,
+    };
+    let _: i32 = compile_and_run(source, &mut main);
+    assert_eq!(main.square_old, 3);
+    assert_eq!(main.square_area, 36);
+    assert_eq!(main.rectangle_old, 10);
+    assert_eq!(main.rectangle_area, 90);
+}
+
+#[test]
+fn derived_function_blocks_inherit_and_override_methods() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct Shape {
+        id: i32,
+        vtable: *mut u8,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct Square {
+        base: Shape,
+        side: i32,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct Cube {
+        base: Square,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        sh: Shape,
+        sq: Square,
+        cu: Cube,
+        r: *mut u8,
+        shape_area: i32,
+        square_area: i32,
+        cube_area: i32,
+        square_description: i32,
+        cube_description: i32,
+        side_of_cube: i32,
+    }
+
+    let source = "
+        FUNCTION_BLOCK shape
+            VAR id : DINT := 1; END_VAR
+
+            METHOD area : DINT
+                area := 0;
+            END_METHOD
+
+            METHOD describe : DINT
+                describe := id * 1000 + THIS.area();
+            END_METHOD
+        END_FUNCTION_BLOCK
+
+        FUNCTION_BLOCK square EXTENDS shape
+            VAR side : DINT; END_VAR
+
+            METHOD area : DINT
+                area := side * side;
+            END_METHOD
+        END_FUNCTION_BLOCK
+
+        FUNCTION_BLOCK cube EXTENDS square
+            METHOD area : DINT
+                VAR face : DINT; END_VAR
+                face := SUPER^.area();
+                area := 6 * face;
+            END_METHOD
+
+            id := 3;
+        END_FUNCTION_BLOCK
+
+        FUNCTION area_of : DINT
+            VAR_IN_OUT s : shape; END_VAR
+            area_of := s.area();
+        END_FUNCTION
+
+        FUNCTION main : DINT
+            VAR
+                sh : shape;
+                sq : square;
+                cu : cube;
+                r : REF_TO shape;
+                shape_area, square_area, cube_area : DINT;
+                square_description, cube_description : DINT;
+                side_of_cube : DINT;
+            END_VAR
+            sq.side := 3;
+            cu.side := 2;
+            cu();
+
+            shape_area := area_of(sh);
+            square_area := area_of(sq);
+            r := REF(cu);
+            cube_area := r^.area();
+
+            square_description := sq.describe();
+            cube_description := cu.describe();
+            side_of_cube := cu.side;
+        END_FUNCTION
+        ";
+
+    let mut main = MainType {
+        sh: Shape {
+            id: 0,
+            vtable: std::ptr::null_mut(),
+        },
+        sq: Square {
+            base: Shape {
+                id: 0,
+                vtable: std::ptr::null_mut(),
+            },
+            side: 0,
+        },
+        cu: Cube {
+            base: Square {
+                base: Shape {
+                    id: 0,
+                    vtable: std::ptr::null_mut(),
+                },
+                side: 0,
+            },
+        },
+        r: std::ptr::null_mut(),
+        shape_area: 0,
+        square_area: 0,
+        cube_area: 0,
+        square_description: 0,
+        cube_description: 0,
+        side_of_cube: 0,
+    };
+    let _: i32 = compile_and_run(source, &mut main);
+    assert_eq!(main.shape_area, 0);
+    assert_eq!(main.square_area, 9);
+    assert_eq!(main.cube_area, 24);
+    assert_eq!(main.square_description, 1009);
+    assert_eq!(main.cube_description, 3024);
+    assert_eq!(main.side_of_cube, 2);
+}
+
+#[test]
+fn properties_are_accessed_through_their_accessors() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MyFb {
+        internal: i16,
+        writes: i16,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        fb: MyFb,
+        x: i16,
+        y: i16,
+    }
+
+    let source = "
+        FUNCTION_BLOCK MyFb
+        VAR
+            internal : INT;
+            writes : INT;
+        END_VAR
+            PROPERTY Level : INT
+                GET
+                    Level := internal;
+                END_GET
+                SET
+                    internal := Level * 10;
+                    writes := writes + 1;
+                END_SET
+            END_PROPERTY
+        END_FUNCTION_BLOCK
+
+        PROGRAM main
+        VAR
+            fb : MyFb;
+            x : INT;
+            y : INT;
+        END_VAR
+            fb.Level := 5;
+            x := fb.Level;
+            fb.Level := fb.Level + 1;
+            y := fb.Level + fb.writes;
+        END_PROGRAM
+        ";
+
+    let mut main = MainType {
+        fb: MyFb {
+            internal: 0,
+            writes: 0,
+        },
+        x: 0,
+        y: 0,
+    };
+    let _: i32 = compile_and_run(source, &mut main);
+    assert_eq!(main.fb.internal, 510);
+    assert_eq!(main.x, 50);
+    assert_eq!(main.y, 512);
+}
@@ -341,6 +341,45 @@ fn for_loop_overflow() {
     assert_eq!(-1, main.i);
 }
 
+#[test]
+fn for_loop_with_constant_and_runtime_negative_steps() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        i: i16,
+        ret: i32,
+    }
+
+    let function = r#"
+    VAR_GLOBAL CONSTANT
+        DOWN : INT := -3;
+    END_VAR
+
+    FUNCTION main : DINT
+    VAR
+        i : INT;
+    END_VAR
+    VAR_TEMP
+        step : INT;
+    END_VAR
+    main := 100;
+    FOR i:= 10 TO 1 BY DOWN DO
+        main := main + 1;
+    END_FOR
+    step := -5;
+    FOR i:= 10 TO 1 BY step DO
+        main := main + 10;
+    END_FOR
+    FOR i:= 1 TO 10 BY 2 - step DO
+        main := main + 100;
+    END_FOR
+    END_FUNCTION
+    "#;
+
+    let res: i32 = compile_and_run(function.to_string(), &mut MainType { i: 0, ret: 0 });
+    assert_eq!(res, 324);
+}
+
 #[test]
 fn for_loop_variable_does_not_affect_other_variables_dint() {
     #[allow(dead_code)]
@@ -628,7 +667,7 @@ fn repeat_loop_no_entry() {
     REPEAT
         i := i+1;
         main := main + 10;
-    UNTIL i < 0 
+    UNTIL i > 0
     END_REPEAT
     main := main + (i * 1000);
     END_FUNCTION
@@ -682,7 +721,7 @@ fn repeat_loop_10_times() {
     REPEAT
         i := i+1;
         main := main + 10;
-    UNTIL i < 10
+    UNTIL i >= 10
     END_REPEAT
     main := main + (i * 1000);
     END_FUNCTION
@@ -692,6 +731,40 @@ fn repeat_loop_10_times() {
     assert_eq!(res, 10101);
 }
 
+#[test]
+fn repeat_loop_with_exit_and_continue() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        i: i32,
+        ret: i32,
+    }
+    let function = r#"
+    FUNCTION main : DINT
+    VAR
+        i : DINT;
+    END_VAR
+    main := 0;
+    i := 0;
+    REPEAT
+        i := i+1;
+        IF i = 2 THEN
+            CONTINUE;
+        END_IF
+        IF i = 5 THEN
+            EXIT;
+        END_IF
+        main := main + 10;
+    UNTIL i >= 10
+    END_REPEAT
+    main := main + (i * 1000);
+    END_FUNCTION
+    "#;
+
+    let res: i32 = compile_and_run(function.to_string(), &mut MainType { i: 0, ret: 0 });
+    assert_eq!(res, 5030);
+}
+
 #[test]
 fn case_statement() {
     #[allow(dead_code)]
@@ -733,3 +806,43 @@ fn case_statement() {
     let res: i32 = compile_and_run(function.to_string(), &mut MainType { i: 999 });
     assert_eq!(res, 7);
 }
+
+#[test]
+fn return_from_loops_and_conditions_keeps_the_return_value() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        i: i32,
+        ret: i32,
+    }
+
+    let function = r#"
+    FUNCTION find_first_above : DINT
+    VAR_INPUT
+        limit : DINT;
+    END_VAR
+    VAR
+        i : DINT;
+    END_VAR
+    find_first_above := -1;
+    FOR i := 1 TO 100 DO
+        IF i * i > limit THEN
+            find_first_above := i;
+            RETURN;
+        END_IF
+    END_FOR
+    find_first_above := 0;
+    END_FUNCTION
+
+    FUNCTION main : DINT
+    main := find_first_above(50) * 1000 + find_first_above(20000);
+    IF main > 0 THEN
+        RETURN;
+    END_IF
+    main := 1;
+    END_FUNCTION
+    "#;
+
+    let res: i32 = compile_and_run(function.to_string(), &mut MainType { i: 0, ret: 0 });
+    assert_eq!(res, 8000);
+}
@@ -0,0 +1,66 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+use super::super::*;
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct MainType {
+    rounded_up: i16,
+    rounded_down: i16,
+    rounded_negative: i32,
+    truncated: i32,
+    truncated_negative: i8,
+    real_value: f32,
+    lreal_value: f64,
+    bool_from_int: u8,
+    bool_from_real: u8,
+    int_from_bool: i16,
+    unsigned: u32,
+}
+
+#[test]
+fn elementary_conversions_round_and_truncate_reals() {
+    let function = r"
+        PROGRAM main
+        VAR
+            rounded_up : INT;
+            rounded_down : INT;
+            rounded_negative : DINT;
+            truncated : DINT;
+            truncated_negative : SINT;
+            real_value : REAL;
+            lreal_value : LREAL;
+            bool_from_int : BOOL;
+            bool_from_real : BOOL;
+            int_from_bool : INT;
+            unsigned : UDINT;
+        END_VAR
+            rounded_up := REAL_TO_INT(2.5);
+            rounded_down := LREAL_TO_INT(2.49);
+            rounded_negative := REAL_TO_DINT(-2.5);
+            truncated := TRUNC(2.9);
+            truncated_negative := REAL_TRUNC_SINT(-2.9);
+            real_value := DINT_TO_REAL(-7);
+            lreal_value := REAL_TO_LREAL(0.5);
+            bool_from_int := INT_TO_BOOL(256);
+            bool_from_real := REAL_TO_BOOL(0.1);
+            int_from_bool := BOOL_TO_INT(TRUE);
+            unsigned := LREAL_TO_UDINT(3000000000.4);
+        END_PROGRAM
+        ";
+
+    let mut maintype = MainType::default();
+    let _: i32 = compile_and_run(function, &mut maintype);
+
+    assert_eq!(3, maintype.rounded_up);
+    assert_eq!(2, maintype.rounded_down);
+    assert_eq!(-3, maintype.rounded_negative);
+    assert_eq!(2, maintype.truncated);
+    assert_eq!(-2, maintype.truncated_negative);
+    assert_eq!(-7.0, maintype.real_value);
+    assert_eq!(0.5, maintype.lreal_value);
+    assert_eq!(1, maintype.bool_from_int);
+    assert_eq!(1, maintype.bool_from_real);
+    assert_eq!(1, maintype.int_from_bool);
+    assert_eq!(3_000_000_000, maintype.unsigned);
+}
@@ -49,6 +49,60 @@ fn using_structs() {
     assert_eq!(10, main_data.my_s.field3);
 }
 
+#[test]
+fn union_members_share_their_memory() {
+    #[repr(C)]
+    #[derive(Debug, Default)]
+    struct MainType {
+        my_u: u32,
+        low_byte: u8,
+        high_byte: u8,
+        word: u16,
+    }
+
+    let mut main_data = MainType::default();
+
+    let testcode = r#"
+    TYPE MyUnion:
+        UNION
+            Whole : DWORD;
+            Bytes : ARRAY[0..3] OF BYTE;
+        END_UNION
+    END_TYPE
+
+    TYPE Nested:
+        UNION
+            Inner : MyUnion;
+            Word : WORD;
+        END_UNION
+    END_TYPE
+
+    PROGRAM main
+    VAR
+        myU: MyUnion;
+        lowByte : BYTE;
+        highByte : BYTE;
+        word : WORD;
+    END_VAR
+    VAR_TEMP
+        n : Nested;
+    END_VAR
+        myU.Whole := 16#11223344;
+        lowByte := myU.Bytes[0];
+        highByte := myU.Bytes[3];
+        n.Inner.Whole := 16#AABBCCDD;
+        word := n.Word;
+    END_PROGRAM
+    "#;
+
+    let _: i32 = compile_and_run(testcode, &mut main_data);
+    assert_eq!(0x11223344, main_data.my_u);
+    //the tests run on little endian targets
+    assert_eq!(0x44, main_data.low_byte);
+    assert_eq!(0x11, main_data.high_byte);
+    assert_eq!(0xCCDD, main_data.word);
+}
+
 #[test]
 fn using_nested_structs() {
     #[repr(C)]
@@ -334,3 +388,103 @@ fn using_arrays() {
         assert_eq!(i as i32, *j);
     }
 }
+
+#[test]
+fn assigning_whole_structs() {
+    #[repr(C)]
+    #[derive(Debug, Default)]
+    struct MyStruct {
+        field1: i16,
+        field2: i32,
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Default)]
+    struct MainType {
+        s1: MyStruct,
+        s2: MyStruct,
+        s3: MyStruct,
+        x: i16,
+    }
+
+    let mut main_data = MainType::default();
+
+    let testcode = r#"
+    TYPE MyStruct:
+        STRUCT
+            Field1 : INT;
+            Field2 : DINT;
+        END_STRUCT
+    END_TYPE
+
+    PROGRAM main
+    VAR
+        s1 : MyStruct;
+        s2 : MyStruct;
+        s3 : MyStruct;
+        x : INT;
+    END_VAR
+        x := 7;
+        s1 := (Field1 := 1, Field2 := 2);
+        s2 := s1;
+        s3 := (Field2 := x * 2, Field1 := x);
+    END_PROGRAM
+    "#;
+
+    let _: i32 = compile_and_run(testcode, &mut main_data);
+    assert_eq!(1, main_data.s1.field1);
+    assert_eq!(2, main_data.s1.field2);
+    assert_eq!(1, main_data.s2.field1);
+    assert_eq!(2, main_data.s2.field2);
+    assert_eq!(7, main_data.s3.field1);
+    assert_eq!(14, main_data.s3.field2);
+}
+
+#[test]
+fn packed_structs_have_no_padding() {
+    #[repr(C, packed)]
+    #[derive(Debug, Default, Clone, Copy)]
+    struct Frame {
+        id: u8,
+        value: i32,
+        flags: u16,
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Default)]
+    struct MainType {
+        frame: Frame,
+        size: u64,
+    }
+
+    let mut main_data = MainType::default();
+
+    let testcode = r#"
+    {attribute 'pack_mode' := '1'}
+    TYPE Frame :
+        STRUCT
+            id : BYTE;
+            value : DINT;
+            flags : WORD;
+        END_STRUCT
+    END_TYPE
+
+    PROGRAM main
+    VAR
+        frame : Frame;
+        size : ULINT;
+    END_VAR
+        frame.id := 1;
+        frame.value := -2;
+        frame.flags := 16#8001;
+        size := SIZEOF(frame);
+    END_PROGRAM
+    "#;
+
+    let _: i32 = compile_and_run(testcode, &mut main_data);
+    let frame = main_data.frame;
+    assert_eq!(1, { frame.id });
+    assert_eq!(-2, { frame.value });
+    assert_eq!(0x8001, { frame.flags });
+    assert_eq!(7, main_data.size);
+}
@@ -1013,3 +1013,68 @@ fn lreal_to_int_assignment() {
     let res: i32 = compile_and_run(function.to_string(), &mut Type {});
     assert_eq!(1, res);
 }
+
+#[test]
+fn sizeof_results_in_the_size_of_types_and_variables() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    #[derive(Default)]
+    struct MyStruct {
+        a: u8,
+        b: i32,
+        c: bool,
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    #[derive(Default)]
+    struct Type {
+        s: MyStruct,
+        arr: [f64; 10],
+        str: [u8; 11],
+        bounded: [u8; 2],
+        size_of_int: u64,
+        size_of_struct: u64,
+        size_of_array: u64,
+        size_of_string: u64,
+        size_of_bounded: u64,
+        const_size: u64,
+    }
+
+    let program = r#"
+        TYPE MyStruct : STRUCT
+            a : BYTE;
+            b : DINT;
+            c : BOOL;
+        END_STRUCT END_TYPE
+
+        PROGRAM main
+        VAR
+            s : MyStruct;
+            arr : ARRAY[0..9] OF LREAL;
+            str : STRING[10];
+            bounded : ARRAY[1..SIZEOF(INT)] OF BYTE;
+            size_of_int : ULINT;
+            size_of_struct : ULINT;
+            size_of_array : ULINT;
+            size_of_string : ULINT;
+            size_of_bounded : ULINT;
+            const_size : ULINT := SIZEOF(MyStruct) * 2;
+        END_VAR
+        size_of_int := SIZEOF(INT);
+        size_of_struct := SIZEOF(s);
+        size_of_array := SIZEOF(arr);
+        size_of_string := SIZEOF(str);
+        size_of_bounded := SIZEOF(bounded);
+        END_PROGRAM
+        "#;
+
+    let mut maintype = Type::default();
+    let _: i32 = compile_and_run(program, &mut maintype);
+    assert_eq!(2, maintype.size_of_int);
+    assert_eq!(12, maintype.size_of_struct);
+    assert_eq!(80, maintype.size_of_array);
+    assert_eq!(11, maintype.size_of_string);
+    assert_eq!(2, maintype.size_of_bounded);
+    assert_eq!(24, maintype.const_size);
+}
@@ -0,0 +1,102 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+use super::super::*;
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct MainType {
+    elapsed: i64,
+    later: i64,
+    date: i64,
+    time_of_day: i64,
+    old_date: i64,
+    old_time_of_day: i64,
+    nanos: i32,
+}
+
+#[test]
+fn date_and_time_values_share_the_resolution_of_time() {
+    let function = r"
+        PROGRAM main
+        VAR
+            elapsed : TIME;
+            later : DT;
+            date : DATE;
+            time_of_day : TOD;
+            old_date : DATE;
+            old_time_of_day : TOD;
+            nanos : DINT;
+        END_VAR
+            elapsed := DT#2021-05-01-19:29:17 - DT#2021-04-30-07:00:00;
+            later := DT#2021-04-30-07:00:00 + elapsed;
+            date := DT_TO_DATE(later);
+            time_of_day := DT_TO_TOD(later);
+            old_date := DT_TO_DATE(DT#1969-12-31-23:00:00);
+            old_time_of_day := DT_TO_TOD(DT#1969-12-31-23:00:00);
+            nanos := TIME_TO_DINT(T#3us / 2);
+        END_PROGRAM
+        ";
+
+    let mut maintype = MainType::default();
+    let _: i32 = compile_and_run(function, &mut maintype);
+
+    assert_eq!(131_357_000_000_000, maintype.elapsed);
+    assert_eq!(
+        1_619_897_357_000_000_000, /*DT#2021-05-01-19:29:17*/
+        maintype.later
+    );
+    assert_eq!(
+        1_619_827_200_000_000_000, /*D#2021-05-01*/
+        maintype.date
+    );
+    assert_eq!(
+        70_157_000_000_000, /*TOD#19:29:17*/
+        maintype.time_of_day
+    );
+    //dates before 1970 are truncated to the start of their day as well
+    assert_eq!(-86_400_000_000_000 /*D#1969-12-31*/, maintype.old_date);
+    assert_eq!(
+        82_800_000_000_000, /*TOD#23:00:00*/
+        maintype.old_time_of_day
+    );
+    assert_eq!(1500, maintype.nanos);
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct LongType {
+    duration: i64,
+    time_of_day: i64,
+    date: i64,
+    elapsed: i64,
+}
+
+#[test]
+fn long_date_and_time_values_keep_nanoseconds() {
+    let function = r"
+        PROGRAM main
+        VAR
+            duration : LTIME;
+            time_of_day : LTOD;
+            date : LDATE;
+            elapsed : LTIME;
+        END_VAR
+            duration := LTIME#1d2h3.5s;
+            time_of_day := LTOD#12:00:00.123456789;
+            date := LDT_TO_LDATE(LDT#2021-04-20-22:33:14.5);
+            elapsed := LDT#2021-04-20-22:33:14.000000001 - LDT#2021-04-20-22:33:14;
+        END_PROGRAM
+        ";
+
+    let mut maintype = LongType::default();
+    let _: i32 = compile_and_run(function, &mut maintype);
+
+    assert_eq!(93_603_500_000_000, maintype.duration);
+    assert_eq!(43_200_123_456_789, maintype.time_of_day);
+    assert_eq!(
+        1_618_876_800_000_000_000, /*LD#2021-04-20*/
+        maintype.date
+    );
+    assert_eq!(1, maintype.elapsed);
+}
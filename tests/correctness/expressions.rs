@@ -223,3 +223,109 @@ fn enums_can_be_compared() {
     let _: i32 = compile_and_run(function, &mut main);
     assert_eq!([true, true, true], [main.a, main.b, main.c]);
 }
+
+#[test]
+fn selection_functions_select_without_branching() {
+    let prog = "
+    PROGRAM main
+    VAR
+        g : BOOL;
+        k : INT;
+        i : INT;
+        r : REAL;
+        u : UDINT;
+        selected : INT;
+        multiplexed : INT;
+        biggest : REAL;
+        smallest : INT;
+        limited : INT;
+        biggest_unsigned : UDINT;
+    END_VAR
+        selected := SEL(g, 1, i);
+        multiplexed := MUX(k, 10, 20, 30);
+        biggest := MAX(i, r, 2);
+        smallest := MIN(i, 5, -3);
+        limited := LIMIT(-10, i, 10);
+        biggest_unsigned := MAX(u, UDINT#7);
+    END_PROGRAM";
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    #[derive(Default, Debug)]
+    struct Type {
+        g: bool,
+        k: i16,
+        i: i16,
+        r: f32,
+        u: u32,
+        selected: i16,
+        multiplexed: i16,
+        biggest: f32,
+        smallest: i16,
+        limited: i16,
+        biggest_unsigned: u32,
+    }
+    let mut param = Type {
+        g: true,
+        k: 2,
+        i: 42,
+        r: 42.5,
+        u: 3_000_000_000,
+        ..Type::default()
+    };
+
+    let _: i32 = compile_and_run(prog, &mut param);
+
+    assert_eq!(42, param.selected);
+    assert_eq!(30, param.multiplexed);
+    assert_eq!(42.5, param.biggest);
+    assert_eq!(-3, param.smallest);
+    assert_eq!(10, param.limited);
+    assert_eq!(3_000_000_000, param.biggest_unsigned);
+}
+
+#[test]
+fn short_circuit_operators_only_evaluate_the_right_operand_if_needed() {
+    let prog = "
+    FUNCTION check : BOOL
+    VAR_INPUT
+        result : BOOL;
+    END_VAR
+    VAR_IN_OUT
+        calls : DINT;
+    END_VAR
+        calls := calls + 1;
+        check := result;
+    END_FUNCTION
+
+    PROGRAM main
+    VAR
+        and_then_calls : DINT;
+        or_else_calls : DINT;
+        a : BOOL;
+        b : BOOL;
+    END_VAR
+        a := FALSE AND_THEN check(TRUE, and_then_calls);
+        a := TRUE AND_THEN check(TRUE, and_then_calls);
+        b := TRUE OR_ELSE check(FALSE, or_else_calls);
+        b := FALSE OR_ELSE check(FALSE, or_else_calls);
+    END_PROGRAM";
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    #[derive(Default, Debug)]
+    struct Type {
+        and_then_calls: i32,
+        or_else_calls: i32,
+        a: bool,
+        b: bool,
+    }
+    let mut param = Type::default();
+
+    let _: i32 = compile_and_run(prog, &mut param);
+
+    assert_eq!(1, param.and_then_calls);
+    assert_eq!(1, param.or_else_calls);
+    assert!(param.a);
+    assert!(!param.b);
+}
@@ -40,6 +40,7 @@ fn test_external_function_called() {
         vec![],
         None,
         Diagnostician::default(),
+        CodegenOptions::default(),
     )
     .unwrap();
     let exec_engine = code_gen
@@ -56,3 +57,62 @@ fn test_external_function_called() {
     //Call that function
     //Test the function's result is executed
 }
+
+extern "C" fn add_and_compare(a: i32, b: i16, smaller: &mut bool) -> i32 {
+    *smaller = a < b as i32;
+    a + b as i32
+}
+
+#[test]
+fn foreign_function_called_with_its_parameters() {
+    //Given a C function declared with the {external} pragma
+    let prog = "
+    {external} FUNCTION add_and_compare : DINT
+    VAR_INPUT
+        a : DINT;
+        b : INT;
+    END_VAR
+    VAR_OUTPUT
+        smaller : BOOL;
+    END_VAR
+    END_FUNCTION
+
+    FUNCTION main : DINT
+    VAR
+        smaller : BOOL;
+    END_VAR
+        main := add_and_compare(100, 20, smaller => smaller);
+        IF smaller THEN
+            main := -1;
+        END_IF
+    END_FUNCTION
+    ";
+
+    Target::initialize_native(&InitializationConfig::default()).unwrap();
+    let context: Context = Context::create();
+    let source = SourceCode {
+        path: "external_test.st".to_string(),
+        source: prog.to_string(),
+    };
+    let (_, code_gen) = compile_module(
+        &context,
+        vec![source],
+        vec![],
+        None,
+        Diagnostician::default(),
+        CodegenOptions::default(),
+    )
+    .unwrap();
+    let exec_engine = code_gen
+        .module
+        .create_jit_execution_engine(inkwell::OptimizationLevel::None)
+        .unwrap();
+
+    let fn_value = code_gen.module.get_function("add_and_compare").unwrap();
+
+    exec_engine.add_global_mapping(&fn_value, add_and_compare as usize);
+    let res: i32 = run(&exec_engine, "main", &mut MainType { val: 0 });
+
+    //Then the function is called with its parameters
+    assert_eq!(res, 120)
+}
@@ -601,3 +601,92 @@ fn nested_calls_in_call_statement() {
         (interface.var1, interface.var2)
     );
 }
+
+#[test]
+fn mixed_named_positional_and_output_arguments() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        var1: i32,
+        var2: i32,
+        inst: [i32; 3],
+    }
+
+    let function = r#"
+        FUNCTION_BLOCK multiply
+            VAR_INPUT a, b : DINT; END_VAR
+            VAR_OUTPUT product : DINT; END_VAR
+            product := a * b;
+        END_FUNCTION_BLOCK
+
+        PROGRAM main
+            VAR
+                var1 : DINT;
+                var2 : DINT;
+                inst : multiply;
+            END_VAR
+            inst(a := 3, 4, product => var1);
+            inst(b := 5, a := 6, product => var2);
+        END_PROGRAM
+    "#;
+
+    let mut interface = MainType {
+        var1: 0,
+        var2: 0,
+        inst: [0; 3],
+    };
+    let _: i32 = compile_and_run(function.to_string(), &mut interface);
+
+    assert_eq!((12, 30), (interface.var1, interface.var2));
+}
+
+#[test]
+fn temp_variables_are_reinitialized_on_every_call() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        var1: i32,
+        var2: i32,
+        inst: [i32; 3],
+        calls: i32,
+    }
+
+    let function = r#"
+        FUNCTION_BLOCK counter
+            VAR_OUTPUT kept, temp : DINT; END_VAR
+            VAR count : DINT := 10; END_VAR
+            VAR_TEMP tmp : DINT := 10; END_VAR
+            count := count + 1;
+            tmp := tmp + 1;
+            kept := count;
+            temp := tmp;
+        END_FUNCTION_BLOCK
+
+        PROGRAM main
+            VAR
+                var1 : DINT;
+                var2 : DINT;
+                inst : counter;
+            END_VAR
+            VAR CONSTANT
+                calls : DINT := 3;
+            END_VAR
+            VAR_TEMP
+                i : DINT;
+            END_VAR
+            FOR i := 1 TO calls DO
+                inst(kept => var1, temp => var2);
+            END_FOR
+        END_PROGRAM
+    "#;
+
+    let mut interface = MainType {
+        var1: 0,
+        var2: 0,
+        inst: [0, 0, 10],
+        calls: 3,
+    };
+    let _: i32 = compile_and_run(function.to_string(), &mut interface);
+
+    assert_eq!((13, 11), (interface.var1, interface.var2));
+}
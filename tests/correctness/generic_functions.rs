@@ -60,6 +60,7 @@ fn test_external_function_called() {
         vec![],
         None,
         Diagnostician::default(),
+        CodegenOptions::default(),
     )
     .unwrap();
     let exec_engine = code_gen
@@ -117,3 +118,99 @@ fn test_generic_function_implemented_in_st_called() {
     assert_eq!(main_type.a, 200);
     assert_eq!(main_type.b, 5.0f32);
 }
+
+#[test]
+fn generic_symbols_and_named_parameters_are_case_insensitive() {
+    let prog = "
+    FUNCTION times_two<t: ANY_NUM> : T
+    VAR_INPUT
+        val : t;
+    END_VAR
+    END_FUNCTION
+
+    FUNCTION times_two__INT : INT
+    VAR_INPUT
+        val : INT;
+    END_VAR
+        times_two__INT := val * 2;
+    END_FUNCTION
+
+    FUNCTION times_two__REAL : REAL
+    VAR_INPUT
+        val : REAL;
+    END_VAR
+        times_two__REAL := val * 2.0;
+    END_FUNCTION
+
+    FUNCTION main : DINT
+    VAR
+        a : INT;
+        b : REAL;
+    END_VAR
+        A := times_two(VAL := INT#100);
+        B := times_two(Val := 2.5);
+    END_FUNCTION
+    ";
+
+    let mut main_type = MainType { a: 0, b: 0.0f32 };
+    let _: i32 = compile_and_run(prog.to_string(), &mut main_type);
+    assert_eq!(main_type.a, 200);
+    assert_eq!(main_type.b, 5.0f32);
+}
+
+#[test]
+fn generic_class_parameters_are_instantiated_per_call_site_type() {
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct MainType {
+        a: i16,
+        b: f32,
+        c: i32,
+        d: u8,
+    }
+
+    let prog = "
+    FUNCTION times_two : ANY_NUM
+    VAR_INPUT
+        val : ANY_NUM;
+    END_VAR
+        times_two := val + val;
+    END_FUNCTION
+
+    FUNCTION times_four : ANY_INT
+    VAR_INPUT
+        val : ANY_INT;
+    END_VAR
+    VAR
+        tmp : ANY_INT;
+    END_VAR
+        tmp := times_two(val);
+        times_four := times_two(tmp);
+    END_FUNCTION
+
+    FUNCTION main : DINT
+    VAR
+        a : INT;
+        b : REAL;
+        c : DINT;
+        d : USINT;
+    END_VAR
+        a := times_two(INT#100);
+        b := times_two(REAL#2.5);
+        c := times_four(DINT#100000);
+        d := times_four(USINT#3);
+    END_FUNCTION
+    ";
+
+    let mut main_type = MainType {
+        a: 0,
+        b: 0.0f32,
+        c: 0,
+        d: 0,
+    };
+    let _: i32 = compile_and_run(prog.to_string(), &mut main_type);
+    assert_eq!(main_type.a, 200);
+    assert_eq!(main_type.b, 5.0f32);
+    assert_eq!(main_type.c, 400000);
+    assert_eq!(main_type.d, 12);
+}
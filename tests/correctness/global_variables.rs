@@ -175,3 +175,21 @@ fn uninitialized_global_struct() {
     let res: f32 = compile_and_run(function.to_string(), &mut maintype);
     assert!((res - 31f32 / 5f32) <= f32::EPSILON);
 }
+
+#[test]
+fn variables_located_at_the_same_address_share_their_value() {
+    let function = r"
+    VAR_GLOBAL
+        a AT %IW1 : INT;
+        b AT %IW1 : INT;
+        c AT %IW2 : INT;
+    END_VAR
+    FUNCTION main : DINT
+    a := 7;
+    c := 3;
+    main := b * 10 + c;
+    END_FUNCTION
+    ";
+    let res: i32 = compile_and_run(function.to_string(), &mut MainType { x: 0 });
+    assert_eq!(res, 73);
+}
@@ -231,7 +231,7 @@ fn add_date_basic() {
     FUNCTION main : DATE
     VAR
         date_var : DATE := D#2021-01-01;
-        date_10_days : DATE := 777600000;
+        date_10_days : DATE := 777600000000000;
         result : DATE;
     END_VAR
         result := date_10_days + date_var;
@@ -242,7 +242,7 @@ fn add_date_basic() {
     let mut main = MainType::default();
 
     let res: u64 = compile_and_run(prog.to_string(), &mut main);
-    assert_eq!(res, 1610236800000);
+    assert_eq!(res, 1610236800000000000);
 }
 
 #[test]
@@ -196,7 +196,7 @@ fn division_date_basic() {
     FUNCTION main : DATE
     VAR
         date_var : DATE := D#2021-01-01;
-        date_10_days : DATE := 777600000;
+        date_10_days : DATE := 777600000000000;
         result,div_result : DATE;
     END_VAR
         div_result := date_10_days / 2;
@@ -208,7 +208,7 @@ fn division_date_basic() {
     let mut main = MainType::default();
 
     let res: u64 = compile_and_run(prog.to_string(), &mut main);
-    assert_eq!(res, 1609848000000);
+    assert_eq!(res, 1609848000000000000);
 }
 
 #[test]
@@ -253,6 +253,66 @@ fn real_division_by_zero() {
     assert!(main.r.is_infinite());
 }
 
+#[test]
+fn integer_division_and_modulo_truncate_towards_zero() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        quotient: i32,
+        remainder: i32,
+        negative_divisor: i32,
+        unsigned_quotient: u64,
+        unsigned_remainder: u64,
+        constant_quotient: i32,
+        constant_remainder: i32,
+    }
+
+    let prog = "
+    VAR_GLOBAL CONSTANT
+        MINUS_SEVEN : DINT := -7;
+        CONSTANT_QUOTIENT : DINT := MINUS_SEVEN / 2;
+        CONSTANT_REMAINDER : DINT := MINUS_SEVEN MOD 2;
+    END_VAR
+
+    PROGRAM main
+        VAR
+            quotient : DINT;
+            remainder : DINT;
+            negative_divisor : DINT;
+            unsigned_quotient : ULINT;
+            unsigned_remainder : ULINT;
+            constant_quotient : DINT;
+            constant_remainder : DINT;
+        END_VAR
+        VAR_TEMP
+            a : DINT := -7;
+            b : DINT := 2;
+            big : ULINT := 16#FFFF_FFFF_FFFF_FFFF;
+        END_VAR
+        quotient := a / b;
+        remainder := a MOD b;
+        negative_divisor := 7 MOD -b;
+        unsigned_quotient := big / 16;
+        unsigned_remainder := big MOD 16;
+        constant_quotient := CONSTANT_QUOTIENT;
+        constant_remainder := CONSTANT_REMAINDER;
+    END_PROGRAM
+    ";
+
+    let mut main = MainType::default();
+    let _: i32 = compile_and_run(prog.to_string(), &mut main);
+
+    // the quotient is truncated, so the remainder has the sign of the dividend
+    assert_eq!(-3, main.quotient);
+    assert_eq!(-1, main.remainder);
+    assert_eq!(1, main.negative_divisor);
+    assert_eq!(0x0FFF_FFFF_FFFF_FFFF, main.unsigned_quotient);
+    assert_eq!(15, main.unsigned_remainder);
+    // constants are evaluated like at runtime
+    assert_eq!(main.quotient, main.constant_quotient);
+    assert_eq!(main.remainder, main.constant_remainder);
+}
+
 //--------------------------
 
 fn approx_equal<T: Float>(a: T, b: T, decimal_places: u16) -> bool {
@@ -0,0 +1,89 @@
+use crate::{compile_and_run, MainType};
+
+//--------------------------------------------------------------
+//exponentiation tests
+
+#[test]
+fn exponentiation_basic() {
+    let prog = "
+    FUNCTION main : DINT
+        main := 3 ** 4;
+    END_FUNCTION
+    ";
+
+    let res: i32 = compile_and_run(prog.to_string(), &mut MainType::default());
+    assert_eq!(res, 81)
+}
+
+#[test]
+fn order_of_operations_exponentiation() {
+    let prog = "
+    FUNCTION main : DINT
+        main := 2 * 3 ** 2 ** 2 - -2 ** 2;
+    END_FUNCTION
+    ";
+
+    // (3 ** 2) ** 2 is evaluated first, the negation binds stronger than the exponentiation
+    let res: i32 = compile_and_run(prog.to_string(), &mut MainType::default());
+    assert_eq!(res, 158)
+}
+
+#[test]
+fn exponentiation_of_integer_and_real_variables() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        power: i32,
+        zero_exponent: i32,
+        negative_exponent: i32,
+        minus_one: i32,
+        wrapped: i16,
+        real_power: f32,
+        root: f64,
+        expt: f64,
+    }
+
+    let prog = "
+    PROGRAM main
+        VAR
+            power : DINT;
+            zero_exponent : DINT;
+            negative_exponent : DINT;
+            minus_one : DINT;
+            wrapped : INT;
+            real_power : REAL;
+            root : LREAL;
+            expt : LREAL;
+        END_VAR
+        VAR_TEMP
+            base : DINT := 2;
+            exponent : DINT := 10;
+            negative_base : DINT := -1;
+            r : REAL := 1.5;
+            l : LREAL := 16.0;
+        END_VAR
+        power := base ** exponent;
+        zero_exponent := base ** 0;
+        negative_exponent := base ** -exponent;
+        minus_one := negative_base ** -3;
+        wrapped := base ** 16 + 1;
+        real_power := r ** base;
+        root := l ** 0.5;
+        expt := EXPT(l, -1);
+    END_PROGRAM
+    ";
+
+    let mut main = MainType::default();
+    let _: i32 = compile_and_run(prog.to_string(), &mut main);
+
+    assert_eq!(1024, main.power);
+    assert_eq!(1, main.zero_exponent);
+    // the truncated reciprocal of 2 ** 10
+    assert_eq!(0, main.negative_exponent);
+    assert_eq!(-1, main.minus_one);
+    // 2 ** 16 does not fit into an INT
+    assert_eq!(1, main.wrapped);
+    assert_eq!(2.25, main.real_power);
+    assert_eq!(4.0, main.root);
+    assert_eq!(0.0625, main.expt);
+}
@@ -166,8 +166,8 @@ fn mixed_math_date_basic() {
     FUNCTION main : DATE
     VAR
         date_var : DATE := D#2021-01-01;
-        date_10_days : DATE := 777600000;
-        date_1_day : DATE := 86400;
+        date_10_days : DATE := 777600000000000;
+        date_1_day : DATE := 86400000000;
         result : DATE;
     END_VAR
         result := date_var + date_10_days * 2 - date_1_day / 2;
@@ -178,7 +178,7 @@ fn mixed_math_date_basic() {
     let mut main = MainType::default();
 
     let res: u64 = compile_and_run(prog.to_string(), &mut main);
-    assert_eq!(res, 1611014356800);
+    assert_eq!(res, 1611014356800000000);
 }
 
 #[test]
@@ -187,8 +187,8 @@ fn mixed_math_dt_basic() {
     FUNCTION main : DT
     VAR
         date_var : DT := D#2021-01-01;
-        date_10_days : DT := 777600000;
-        date_1_day : DT := 86400;
+        date_10_days : DT := 777600000000000;
+        date_1_day : DT := 86400000000;
         result : DT;
     END_VAR
         result := date_var + date_10_days * 2 - date_1_day / 2;
@@ -199,7 +199,7 @@ fn mixed_math_dt_basic() {
     let mut main = MainType::default();
 
     let res: u64 = compile_and_run(prog.to_string(), &mut main);
-    assert_eq!(res, 1611014356800);
+    assert_eq!(res, 1611014356800000000);
 }
 
 #[test]
@@ -254,6 +254,24 @@ fn mixed_array_math_basic() {
     assert_eq!(res, 165);
 }
 
+#[test]
+fn mixed_signed_and_unsigned_variables_are_promoted_to_a_bigger_signed_type() {
+    let prog = "
+    FUNCTION main : LINT
+    VAR
+        d : DINT := -1;
+        u : UDINT := 4000000000;
+    END_VAR
+        IF d < u THEN
+            main := u - d;
+        END_IF
+    END_FUNCTION
+    ";
+
+    let res: i64 = compile_and_run(prog.to_string(), &mut MainType::default());
+    assert_eq!(res, 4000000001);
+}
+
 //-----------------------------
 
 fn approx_equal<T: Float>(a: T, b: T, decimal_places: u16) -> bool {
@@ -159,7 +159,7 @@ fn multiplication_date_basic() {
     FUNCTION main : DATE
     VAR
         date_var : DATE := D#2021-01-01;
-        date_10_days : DATE := 777600000;
+        date_10_days : DATE := 777600000000000;
         result,div_result : DATE;
     END_VAR
         div_result := date_10_days * 2;
@@ -171,7 +171,7 @@ fn multiplication_date_basic() {
     let mut main = MainType::default();
 
     let res: u64 = compile_and_run(prog.to_string(), &mut main);
-    assert_eq!(res, 1611014400000);
+    assert_eq!(res, 1611014400000000000);
 }
 
 #[test]
@@ -0,0 +1,125 @@
+use crate::compile_and_run;
+
+//--------------------------------------------------------------
+//numeric function tests
+
+#[test]
+fn numeric_functions_of_reals() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        abs_value: f32,
+        sqrt_value: f32,
+        ln_value: f64,
+        log_value: f64,
+        exp_value: f64,
+        sin_value: f64,
+        cos_value: f64,
+        tan_value: f32,
+        asin_value: f64,
+        acos_value: f64,
+        atan_value: f32,
+        atan2_value: f64,
+    }
+
+    let prog = "
+    PROGRAM main
+        VAR
+            abs_value : REAL;
+            sqrt_value : REAL;
+            ln_value : LREAL;
+            log_value : LREAL;
+            exp_value : LREAL;
+            sin_value : LREAL;
+            cos_value : LREAL;
+            tan_value : REAL;
+            asin_value : LREAL;
+            acos_value : LREAL;
+            atan_value : REAL;
+            atan2_value : LREAL;
+        END_VAR
+        VAR_TEMP
+            r : REAL := -2.25;
+            l : LREAL := 1.0;
+            zero : LREAL := 0.0;
+        END_VAR
+        abs_value := ABS(r);
+        sqrt_value := SQRT(ABS(r));
+        ln_value := LN(EXP(l));
+        log_value := LOG(1000.0);
+        exp_value := EXP(zero);
+        sin_value := SIN(zero);
+        cos_value := COS(zero);
+        tan_value := TAN(REAL#0.0);
+        asin_value := ASIN(l);
+        acos_value := ACOS(l);
+        atan_value := ATAN(REAL#1.0);
+        atan2_value := ATAN2(-l, -l);
+    END_PROGRAM
+    ";
+
+    let mut main = MainType::default();
+    let _: i32 = compile_and_run(prog.to_string(), &mut main);
+
+    assert_eq!(2.25, main.abs_value);
+    assert_eq!(1.5, main.sqrt_value);
+    assert!((main.ln_value - 1.0).abs() < 1e-12);
+    assert!((main.log_value - 3.0).abs() < 1e-12);
+    assert_eq!(1.0, main.exp_value);
+    assert_eq!(0.0, main.sin_value);
+    assert_eq!(1.0, main.cos_value);
+    assert_eq!(0.0, main.tan_value);
+    assert!((main.asin_value - std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+    assert_eq!(0.0, main.acos_value);
+    assert!((main.atan_value - std::f32::consts::FRAC_PI_4).abs() < f32::EPSILON);
+    assert!((main.atan2_value + 3.0 * std::f64::consts::FRAC_PI_4).abs() < 1e-12);
+}
+
+#[test]
+fn numeric_functions_of_integers() {
+    #[derive(Default)]
+    #[repr(C)]
+    struct MainType {
+        abs_int: i16,
+        abs_dint: i32,
+        abs_udint: u32,
+        sqrt_value: f32,
+        constant: f64,
+    }
+
+    let prog = "
+    VAR_GLOBAL CONSTANT
+        ROOT : LREAL := SQRT(2) * SQRT(2);
+    END_VAR
+
+    PROGRAM main
+        VAR
+            abs_int : INT;
+            abs_dint : DINT;
+            abs_udint : UDINT;
+            sqrt_value : REAL;
+            constant : LREAL;
+        END_VAR
+        VAR_TEMP
+            i : INT := -7;
+            d : DINT := 7;
+            u : UDINT := 4000000000;
+        END_VAR
+        abs_int := ABS(i);
+        abs_dint := ABS(d);
+        abs_udint := ABS(u);
+        sqrt_value := SQRT(d + 2);
+        constant := ROOT;
+    END_PROGRAM
+    ";
+
+    let mut main = MainType::default();
+    let _: i32 = compile_and_run(prog.to_string(), &mut main);
+
+    assert_eq!(7, main.abs_int);
+    assert_eq!(7, main.abs_dint);
+    // unsigned values are never negative
+    assert_eq!(4_000_000_000, main.abs_udint);
+    assert_eq!(3.0, main.sqrt_value);
+    assert!((main.constant - 2.0).abs() < 1e-15);
+}
@@ -207,7 +207,7 @@ fn substract_date_basic() {
     let mut main = MainType::default();
 
     let res: u64 = compile_and_run(prog.to_string(), &mut main);
-    assert_eq!(res, 777600000);
+    assert_eq!(res, 777600000000000);
 }
 
 #[test]
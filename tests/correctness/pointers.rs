@@ -41,6 +41,30 @@ END_FUNCTION
     assert_eq!(36, res);
 }
 
+#[test]
+fn adr_and_ref_builtins_return_the_address() {
+    let function = r"
+FUNCTION main : DINT
+VAR
+    x : DINT;
+    a, r : REF_TO DINT;
+END_VAR
+    a := ADR(x);
+    r := REF(x);
+    a^ := 3;
+    IF a = r AND r <> NULL THEN
+        main := r^ * 2;
+    END_IF
+END_FUNCTION
+ ";
+
+    let mut maintype = MainType::default();
+
+    let res: i32 = compile_and_run(function.to_string(), &mut maintype);
+
+    assert_eq!(6, res);
+}
+
 #[test]
 fn binary_expressions_for_pointers() {
     #[derive(Default)]
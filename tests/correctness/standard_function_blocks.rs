@@ -0,0 +1,393 @@
+// Copyright (c) 2020 Ghaith Hachem and Mathias Rieder
+use super::super::*;
+use inkwell::execution_engine::ExecutionEngine;
+use inkwell::targets::{InitializationConfig, Target};
+use std::cell::Cell;
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct TriggerType {
+    clk: bool,
+    q: bool,
+    m: bool,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct MainType {
+    input: bool,
+    rising: bool,
+    falling: bool,
+    r_trig: TriggerType,
+    f_trig: TriggerType,
+}
+
+#[test]
+fn r_trig_and_f_trig_detect_edges_without_declaration() {
+    let src = "
+    PROGRAM main
+    VAR
+        input : BOOL;
+        rising : BOOL;
+        falling : BOOL;
+        r_trig_inst : R_TRIG;
+        f_trig_inst : F_TRIG;
+    END_VAR
+        r_trig_inst(CLK := input, Q => rising);
+        f_trig_inst(CLK := input, Q => falling);
+    END_PROGRAM
+    ";
+
+    let context = Context::create();
+    let engine = compile(&context, src);
+    let mut main = MainType::default();
+
+    let mut edges = vec![];
+    for input in [false, true, true, false, false, true, false] {
+        main.input = input;
+        let _: i32 = run(&engine, "main", &mut main);
+        edges.push((main.rising, main.falling));
+    }
+
+    assert_eq!(
+        edges,
+        vec![
+            (false, false),
+            (true, false),
+            (false, false),
+            (false, true),
+            (false, false),
+            (true, false),
+            (false, true),
+        ]
+    );
+}
+
+#[test]
+fn user_declared_r_trig_takes_precedence_over_the_builtin() {
+    let src = "
+    FUNCTION_BLOCK R_TRIG
+    VAR_INPUT
+        CLK : BOOL;
+    END_VAR
+    VAR_OUTPUT
+        Q : BOOL;
+    END_VAR
+    VAR
+        M : BOOL;
+    END_VAR
+        Q := CLK;
+    END_FUNCTION_BLOCK
+
+    PROGRAM main
+    VAR
+        input : BOOL;
+        rising : BOOL;
+        falling : BOOL;
+        r_trig_inst : R_TRIG;
+    END_VAR
+        r_trig_inst(CLK := input, Q => rising);
+    END_PROGRAM
+    ";
+
+    let context = Context::create();
+    let engine = compile(&context, src);
+    let mut main = MainType::default();
+
+    for _ in 0..2 {
+        main.input = true;
+        let _: i32 = run(&engine, "main", &mut main);
+        assert!(main.rising);
+    }
+}
+
+thread_local! {
+    static CURRENT_TIME: Cell<i64> = Cell::new(0);
+}
+
+extern "C" fn rusty_get_time_ns() -> i64 {
+    CURRENT_TIME.with(|it| it.get())
+}
+
+/// compiles the given source and maps the time source of the timers to `CURRENT_TIME`
+fn compile_with_time_source<'c>(context: &'c Context, src: &str) -> ExecutionEngine<'c> {
+    Target::initialize_native(&InitializationConfig::default()).unwrap();
+    let (_, code_gen) = compile_module(
+        context,
+        vec![SourceCode::from(src)],
+        vec![],
+        None,
+        Diagnostician::default(),
+        CodegenOptions::default(),
+    )
+    .unwrap();
+    let exec_engine = code_gen
+        .module
+        .create_jit_execution_engine(inkwell::OptimizationLevel::None)
+        .unwrap();
+    let time_source = code_gen.module.get_function("rusty_get_time_ns").unwrap();
+    exec_engine.add_global_mapping(&time_source, rusty_get_time_ns as usize);
+    exec_engine
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct TimerType {
+    input: bool,
+    pt: i64,
+    q: bool,
+    et: i64,
+    running: bool,
+    start: i64,
+    current_time: i64,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct PulseType {
+    timer: TimerType,
+    m: bool,
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct TimerMainType {
+    input: bool,
+    ton_q: bool,
+    ton_et: i64,
+    tof_q: bool,
+    tof_et: i64,
+    tp_q: bool,
+    tp_et: i64,
+    ton: TimerType,
+    tof: TimerType,
+    tp: PulseType,
+}
+
+#[test]
+fn timers_follow_their_input_over_time() {
+    let src = "
+    PROGRAM main
+    VAR
+        input : BOOL;
+        ton_q : BOOL;
+        ton_et : TIME;
+        tof_q : BOOL;
+        tof_et : TIME;
+        tp_q : BOOL;
+        tp_et : TIME;
+        ton_inst : TON;
+        tof_inst : TOF;
+        tp_inst : TP;
+    END_VAR
+        ton_inst(IN := input, PT := T#10ns, Q => ton_q, ET => ton_et);
+        tof_inst(IN := input, PT := T#10ns, Q => tof_q, ET => tof_et);
+        tp_inst(IN := input, PT := T#10ns, Q => tp_q, ET => tp_et);
+    END_PROGRAM
+    ";
+
+    let context = Context::create();
+    let engine = compile_with_time_source(&context, src);
+    let mut main = TimerMainType::default();
+
+    // (time, input) => ((ton.Q, ton.ET), (tof.Q, tof.ET), (tp.Q, tp.ET))
+    let steps = [
+        ((0, false), ((false, 0), (false, 0), (false, 0))),
+        ((100, true), ((false, 0), (true, 0), (true, 0))),
+        ((105, true), ((false, 5), (true, 0), (true, 5))),
+        ((110, true), ((true, 10), (true, 0), (false, 10))),
+        ((120, false), ((false, 0), (true, 0), (false, 0))),
+        ((125, true), ((false, 0), (true, 0), (true, 0))),
+        ((128, false), ((false, 0), (true, 0), (true, 3))),
+        ((130, false), ((false, 0), (true, 2), (true, 5))),
+        ((133, true), ((false, 0), (true, 0), (true, 8))),
+        ((150, false), ((false, 0), (true, 0), (false, 10))),
+        ((165, false), ((false, 0), (false, 10), (false, 0))),
+    ];
+
+    for ((time, input), expected) in steps {
+        CURRENT_TIME.with(|it| it.set(time));
+        main.input = input;
+        let _: i32 = run(&engine, "main", &mut main);
+        assert_eq!(
+            (
+                (main.ton_q, main.ton_et),
+                (main.tof_q, main.tof_et),
+                (main.tp_q, main.tp_et)
+            ),
+            expected,
+            "at time {}",
+            time
+        );
+    }
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct CounterMainType {
+    up: bool,
+    down: bool,
+    reset: bool,
+    load: bool,
+    ctu_q: bool,
+    ctu_cv: i16,
+    ctd_q: bool,
+    ctd_cv: i16,
+    ctud_qu: bool,
+    ctud_qd: bool,
+    ctud_cv: i16,
+    instances: [i16; 32],
+}
+
+#[test]
+fn counters_count_rising_edges() {
+    let src = "
+    PROGRAM main
+    VAR
+        up : BOOL;
+        down : BOOL;
+        reset : BOOL;
+        load : BOOL;
+        ctu_q : BOOL;
+        ctu_cv : INT;
+        ctd_q : BOOL;
+        ctd_cv : INT;
+        ctud_qu : BOOL;
+        ctud_qd : BOOL;
+        ctud_cv : INT;
+        ctu_inst : CTU;
+        ctd_inst : CTD;
+        ctud_inst : CTUD;
+    END_VAR
+        ctu_inst(CU := up, R := reset, PV := 2, Q => ctu_q, CV => ctu_cv);
+        ctd_inst(CD := down, LD := load, PV := 2, Q => ctd_q, CV => ctd_cv);
+        ctud_inst(CU := up, CD := down, R := reset, LD := load, PV := 2,
+            QU => ctud_qu, QD => ctud_qd, CV => ctud_cv);
+    END_PROGRAM
+    ";
+
+    let context = Context::create();
+    let engine = compile(&context, src);
+    let mut main = CounterMainType::default();
+
+    // (up, down, reset, load) => ((ctu.Q, ctu.CV), (ctd.Q, ctd.CV), (ctud.QU, ctud.QD, ctud.CV))
+    let steps = [
+        (
+            (false, false, false, true),
+            ((false, 0), (false, 2), (true, false, 2)),
+        ),
+        (
+            (false, false, false, false),
+            ((false, 0), (false, 2), (true, false, 2)),
+        ),
+        (
+            (true, false, false, false),
+            ((false, 1), (false, 2), (true, false, 3)),
+        ),
+        (
+            (true, false, false, false),
+            ((false, 1), (false, 2), (true, false, 3)),
+        ),
+        (
+            (false, true, false, false),
+            ((false, 1), (false, 1), (true, false, 2)),
+        ),
+        (
+            (true, false, false, false),
+            ((true, 2), (false, 1), (true, false, 3)),
+        ),
+        (
+            (false, true, false, false),
+            ((true, 2), (true, 0), (true, false, 2)),
+        ),
+        (
+            (false, false, false, false),
+            ((true, 2), (true, 0), (true, false, 2)),
+        ),
+        (
+            (true, true, false, false),
+            ((true, 3), (true, -1), (true, false, 2)),
+        ),
+        (
+            (false, false, true, true),
+            ((false, 0), (false, 2), (false, true, 0)),
+        ),
+        (
+            (false, true, false, false),
+            ((false, 0), (false, 1), (false, true, -1)),
+        ),
+    ];
+
+    for ((up, down, reset, load), expected) in steps {
+        main.up = up;
+        main.down = down;
+        main.reset = reset;
+        main.load = load;
+        let _: i32 = run(&engine, "main", &mut main);
+        assert_eq!(
+            (
+                (main.ctu_q, main.ctu_cv),
+                (main.ctd_q, main.ctd_cv),
+                (main.ctud_qu, main.ctud_qd, main.ctud_cv)
+            ),
+            expected
+        );
+    }
+}
+
+#[allow(dead_code)]
+#[repr(C)]
+#[derive(Default)]
+struct BistableMainType {
+    set: bool,
+    reset: bool,
+    sr_q: bool,
+    rs_q: bool,
+    instances: [bool; 8],
+}
+
+#[test]
+fn bistables_latch_with_set_or_reset_dominance() {
+    let src = "
+    PROGRAM main
+    VAR
+        set : BOOL;
+        reset : BOOL;
+        sr_q : BOOL;
+        rs_q : BOOL;
+        sr_inst : SR;
+        rs_inst : RS;
+    END_VAR
+        sr_inst(S1 := set, R := reset, Q1 => sr_q);
+        rs_inst(S := set, R1 := reset, Q1 => rs_q);
+    END_PROGRAM
+    ";
+
+    let context = Context::create();
+    let engine = compile(&context, src);
+    let mut main = BistableMainType::default();
+
+    // (set, reset) => (SR.Q1, RS.Q1)
+    let steps = [
+        ((false, false), (false, false)),
+        ((true, false), (true, true)),
+        ((false, false), (true, true)),
+        ((true, true), (true, false)),
+        ((false, false), (true, false)),
+        ((false, true), (false, false)),
+        ((false, false), (false, false)),
+    ];
+
+    for ((set, reset), expected) in steps {
+        main.set = set;
+        main.reset = reset;
+        let _: i32 = run(&engine, "main", &mut main);
+        assert_eq!((main.sr_q, main.rs_q), expected);
+    }
+}
@@ -246,3 +246,88 @@ fn initialization_of_string_arrays() {
     assert_eq!(main_type.y, "world\0\0\0\0\0\0".as_bytes());
     assert_eq!(main_type.z, "ten chars!\0".as_bytes());
 }
+
+#[test]
+fn wstring_assignment_from_bigger_wstring() {
+    let src = r#"
+        FUNCTION main : DINT
+            VAR x : WSTRING[4]; y : WSTRING[5]; z : WSTRING[6]; END_VAR
+            y := "hello";
+            x := y;
+            z := y;
+        END_FUNCTION
+    "#;
+
+    #[allow(dead_code)]
+    struct MainType {
+        x: [u16; 5],
+        y: [u16; 6],
+        z: [u16; 7],
+    }
+    let mut main_type = MainType {
+        x: [0; 5],
+        y: [0; 6],
+        z: [0; 7],
+    };
+
+    let _: i32 = compile_and_run(src, &mut main_type);
+    let hell: Vec<u16> = "hell\0".encode_utf16().collect();
+    let hello: Vec<u16> = "hello\0\0".encode_utf16().collect();
+    assert_eq!(hell.as_slice(), &main_type.x);
+    assert_eq!(hello.as_slice(), &main_type.z);
+}
+
+#[test]
+fn characters_convert_to_strings_and_back() {
+    let src = r#"
+        PROGRAM main
+            VAR
+                s : STRING[3];
+                c : CHAR;
+                first : CHAR;
+                literal_first : CHAR;
+                is_a : BOOL;
+                is_before_b : BOOL;
+                code : INT;
+                ws : WSTRING[3];
+                wc : WCHAR;
+            END_VAR
+            c := 'A';
+            s := CHAR_TO_STRING(c);
+            first := STRING_TO_CHAR(s);
+            literal_first := STRING_TO_CHAR('xyz');
+            is_a := first = 'A';
+            is_before_b := c < 'B';
+            code := CHAR_TO_INT(c);
+            ws := WCHAR_TO_WSTRING("Ä");
+            wc := WSTRING_TO_WCHAR(ws);
+        END_PROGRAM
+    "#;
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    #[derive(Default)]
+    struct MainType {
+        s: [u8; 4],
+        c: u8,
+        first: u8,
+        literal_first: u8,
+        is_a: bool,
+        is_before_b: bool,
+        code: i16,
+        ws: [u16; 4],
+        wc: u16,
+    }
+    let mut main_type = MainType::default();
+
+    let _: i32 = compile_and_run(src, &mut main_type);
+    assert_eq!(main_type.s, "A\0\0\0".as_bytes());
+    assert_eq!(b'A', main_type.first);
+    assert_eq!(b'x', main_type.literal_first);
+    assert!(main_type.is_a);
+    assert!(main_type.is_before_b);
+    assert_eq!(65, main_type.code);
+    let umlaut: Vec<u16> = "Ä\0\0\0".encode_utf16().collect();
+    assert_eq!(umlaut.as_slice(), &main_type.ws);
+    assert_eq!(0xC4, main_type.wc);
+}
@@ -3,7 +3,7 @@
 use std::{env, fs};
 
 use encoding_rs::Encoding;
-use rusty::{build, get_target_triple, CompileOptions, FilePath};
+use rusty::{build, get_target_triple, CodegenOptions, CompileOptions, ErrorFormat, FilePath};
 
 use crate::get_test_file;
 
@@ -21,6 +21,13 @@ fn compile_all(name: &str, encoding: Option<&'static Encoding>) {
             format: rusty::FormatOption::IR,
             output: out.clone(),
             target: None,
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         encoding,
         &target,
@@ -34,6 +41,13 @@ fn compile_all(name: &str, encoding: Option<&'static Encoding>) {
             format: rusty::FormatOption::Bitcode,
             output: out.clone(),
             target: None,
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         encoding,
         &target,
@@ -47,6 +61,13 @@ fn compile_all(name: &str, encoding: Option<&'static Encoding>) {
             format: rusty::FormatOption::Shared,
             output: out.clone(),
             target: None,
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         encoding,
         &target,
@@ -60,6 +81,13 @@ fn compile_all(name: &str, encoding: Option<&'static Encoding>) {
             format: rusty::FormatOption::PIC,
             output: out.clone(),
             target: None,
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         encoding,
         &target,
@@ -73,6 +101,13 @@ fn compile_all(name: &str, encoding: Option<&'static Encoding>) {
             format: rusty::FormatOption::Static,
             output: out.clone(),
             target: None,
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         encoding,
         &target,
@@ -2,7 +2,8 @@ use std::{env, fs};
 
 use crate::get_test_file;
 use rusty::{
-    build, diagnostics::Diagnostic, get_target_triple, link, CompileOptions, FilePath, FormatOption,
+    build, diagnostics::Diagnostic, get_target_triple, link, CodegenOptions, CompileOptions,
+    ErrorFormat, FilePath, FormatOption, LtoMode, OptimizationLevel,
 };
 
 static TARGET: Option<&str> = Some("x86_64-unkown-linux-gnu");
@@ -32,6 +33,13 @@ fn link_as_shared_object() {
             output: out2.clone(),
             format: FormatOption::Shared,
             target: TARGET.map(String::from),
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         None,
         &triple,
@@ -46,6 +54,13 @@ fn link_as_shared_object() {
             output: out1.clone(),
             format: FormatOption::Shared,
             target: TARGET.map(String::from),
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         None,
         &triple,
@@ -94,6 +109,13 @@ fn link_as_pic_object() {
             output: out2.clone(),
             format: FormatOption::PIC,
             target: TARGET.map(String::from),
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         None,
         &triple,
@@ -108,6 +130,13 @@ fn link_as_pic_object() {
             output: out1.clone(),
             format: FormatOption::PIC,
             target: TARGET.map(String::from),
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         None,
         &triple,
@@ -156,6 +185,13 @@ fn link_as_static_object() {
             output: out2.clone(),
             format: FormatOption::Static,
             target: TARGET.map(String::from),
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         None,
         &triple,
@@ -170,6 +206,13 @@ fn link_as_static_object() {
             output: out1.clone(),
             format: FormatOption::Static,
             target: TARGET.map(String::from),
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         None,
         &triple,
@@ -218,6 +261,13 @@ fn link_as_relocatable_object() {
             output: out2.clone(),
             format: FormatOption::Static,
             target: TARGET.map(String::from),
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         None,
         &triple,
@@ -232,6 +282,13 @@ fn link_as_relocatable_object() {
             output: out1.clone(),
             format: FormatOption::Relocatable,
             target: TARGET.map(String::from),
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         None,
         &triple,
@@ -271,6 +328,13 @@ fn link_missing_file() {
             output: out.clone(),
             format: FormatOption::Static,
             target: TARGET.map(String::from),
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions::default(),
         },
         None,
         &triple,
@@ -297,3 +361,162 @@ fn link_missing_file() {
     //Delete it
     fs::remove_file(&out).unwrap();
 }
+
+#[test]
+fn units_are_linked_with_link_time_optimization() {
+    let file1 = FilePath {
+        path: get_test_file("multi/func.st"),
+    };
+    let file2 = FilePath {
+        path: get_test_file("multi/prog.st"),
+    };
+
+    let mut out = env::temp_dir();
+    out.push("lto.ll");
+    let out = out.into_os_string().into_string().unwrap();
+    let triple = get_target_triple(TARGET);
+
+    build(
+        vec![file1, file2],
+        vec![],
+        &CompileOptions {
+            output: out.clone(),
+            format: FormatOption::IR,
+            target: TARGET.map(String::from),
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions {
+                optimization: OptimizationLevel::Default,
+                lto: Some(LtoMode::Full),
+                ..CodegenOptions::default()
+            },
+        },
+        None,
+        &triple,
+    )
+    .unwrap();
+
+    // both units are defined in the linked module and the program is inlined into main
+    let ir = fs::read_to_string(&out).unwrap();
+    assert_eq!(ir.matches("define void @mainProg(").count(), 1);
+    assert_eq!(ir.matches("define i16 @main(").count(), 1);
+    assert!(!ir.contains("call void @mainProg("));
+
+    //Delete it
+    fs::remove_file(&out).unwrap();
+}
+
+#[test]
+fn units_generated_in_parallel_define_the_same_symbols_as_a_serial_build() {
+    let files = vec![
+        FilePath {
+            path: get_test_file("multi/func.st"),
+        },
+        FilePath {
+            path: get_test_file("multi/prog.st"),
+        },
+    ];
+    let triple = get_target_triple(TARGET);
+
+    //without optimizations, the linked modules keep every symbol of the units
+    let build_ir = |name: &str, lto: Option<LtoMode>| {
+        let mut out = env::temp_dir();
+        out.push(name);
+        let out = out.into_os_string().into_string().unwrap();
+        build(
+            files.clone(),
+            vec![],
+            &CompileOptions {
+                output: out.clone(),
+                format: FormatOption::IR,
+                target: TARGET.map(String::from),
+                error_format: ErrorFormat::Rich,
+                deny_partial_initializers: false,
+                warn_recursion: false,
+                warning_levels: vec![],
+                warnings_as_errors: false,
+                stack_report: None,
+                codegen: CodegenOptions {
+                    optimization: OptimizationLevel::None,
+                    lto,
+                    ..CodegenOptions::default()
+                },
+            },
+            None,
+            &triple,
+        )
+        .unwrap();
+        let ir = fs::read_to_string(&out).unwrap();
+        fs::remove_file(&out).unwrap();
+        ir
+    };
+    let defined_symbols = |ir: &str| {
+        let mut symbols = ir
+            .lines()
+            .filter(|it| {
+                it.starts_with("define ")
+                    || (it.starts_with('@')
+                        && !it.contains("= private ")
+                        && !it.contains("= external "))
+            })
+            .filter_map(|it| it.split('@').nth(1))
+            .filter_map(|it| it.split(|c| c == '(' || c == ' ').next())
+            .map(String::from)
+            .collect::<Vec<_>>();
+        symbols.sort();
+        symbols
+    };
+
+    let serial = build_ir("serial.ll", None);
+    let parallel = build_ir("parallel.ll", Some(LtoMode::Full));
+
+    let symbols = defined_symbols(&serial);
+    assert!(symbols.contains(&"main".to_string()));
+    assert!(symbols.contains(&"mainProg".to_string()));
+    assert_eq!(symbols, defined_symbols(&parallel));
+}
+
+#[test]
+fn thin_lto_is_reported_as_unsupported() {
+    let file = FilePath {
+        path: get_test_file("multi/prog.st"),
+    };
+
+    let mut out = env::temp_dir();
+    out.push("thin_lto.o");
+    let out = out.into_os_string().into_string().unwrap();
+    let triple = get_target_triple(TARGET);
+
+    let res = build(
+        vec![file],
+        vec![],
+        &CompileOptions {
+            output: out,
+            format: FormatOption::Static,
+            target: TARGET.map(String::from),
+            error_format: ErrorFormat::Rich,
+            deny_partial_initializers: false,
+            warn_recursion: false,
+            warning_levels: vec![],
+            warnings_as_errors: false,
+            stack_report: None,
+            codegen: CodegenOptions {
+                lto: Some(LtoMode::Thin),
+                ..CodegenOptions::default()
+            },
+        },
+        None,
+        &triple,
+    );
+
+    assert_eq!(
+        res.err(),
+        Some(Diagnostic::param_error(
+            "ThinLTO requires module summaries, which cannot be generated yet. Use --lto=full instead"
+        ))
+    );
+}
@@ -14,3 +14,30 @@ fn sources_accross_multiple_files_compiled() {
     let res: i32 = compile_and_run(vec![file1, file2], &mut ());
     assert_eq!(42, res);
 }
+
+#[test]
+fn namespaced_declarations_are_referenced_across_files() {
+    let lib = FilePath {
+        path: get_test_file("namespaces/lib.st"),
+    };
+    let main = FilePath {
+        path: get_test_file("namespaces/main.st"),
+    };
+
+    let res: i32 = compile_and_run(vec![lib, main], &mut ());
+    // a.count = 4, b.count = 2
+    assert_eq!(10, res);
+}
+
+#[test]
+fn external_variables_reference_globals_of_other_files() {
+    let globals = FilePath {
+        path: get_test_file("external/globals.st"),
+    };
+    let main = FilePath {
+        path: get_test_file("external/main.st"),
+    };
+
+    let res: i32 = compile_and_run(vec![globals, main], &mut ());
+    assert_eq!(42, res);
+}
@@ -13,8 +13,10 @@ mod correctness {
     mod bitaccess;
     mod classes;
     mod control_flow;
+    mod conversions;
     mod custom_datatypes;
     mod datatypes;
+    mod date_time;
     mod expressions;
     mod external_functions;
     mod functions;
@@ -23,13 +25,16 @@ mod correctness {
     mod initial_values;
     mod methods;
     mod pointers;
+    mod standard_function_blocks;
     mod strings;
     mod sub_range_types;
     mod math_operators {
         mod addition;
         mod division;
+        mod exponentiation;
         mod mixed;
         mod multiplication;
+        mod numeric_functions;
         mod substraction;
     }
 }